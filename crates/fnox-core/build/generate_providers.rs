@@ -31,6 +31,10 @@ struct ProviderTomlRaw {
     /// Used to skip the provider in non-interactive contexts like the TUI.
     #[serde(default)]
     requires_interactive_auth: bool,
+    /// When true, a reference ending in `/*` loads every secret under a path,
+    /// resolving to a JSON object of env var name to value.
+    #[serde(default)]
+    path_references: bool,
     #[serde(default)]
     fields: IndexMap<String, FieldDef>,
     #[serde(default)]
@@ -52,6 +56,7 @@ struct ProviderToml {
     auth_command: Option<String>,
     pass_provider_name: bool,
     requires_interactive_auth: bool,
+    path_references: bool,
     fields: IndexMap<String, FieldDef>,
     wizard_fields: IndexMap<String, WizardFieldDef>,
 }
@@ -88,6 +93,7 @@ impl ProviderTomlRaw {
             auth_command: self.auth_command,
             pass_provider_name: self.pass_provider_name,
             requires_interactive_auth: self.requires_interactive_auth,
+            path_references: self.path_references,
             fields: self.fields,
             wizard_fields: self.wizard_fields,
         }
@@ -104,6 +110,19 @@ struct FieldDef {
     label: String,
     #[serde(default)]
     wizard: bool,
+    /// Enum type of a `backend_enum` field
+    #[serde(default)]
+    enum_type: Option<String>,
+}
+
+impl FieldDef {
+    /// The enum a `backend_enum` field holds, `BitwardenBackend` unless set
+    fn enum_ident(&self) -> Ident {
+        Ident::new(
+            self.enum_type.as_deref().unwrap_or("BitwardenBackend"),
+            Span::call_site(),
+        )
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -242,9 +261,10 @@ fn generate_provider_config(
     let output = quote! {
         use schemars::JsonSchema;
         use serde::{Deserialize, Serialize};
+        use indexmap::IndexMap;
         use strum::AsRefStr;
         use super::super::secret_ref::{OptionProviderSecretRef, OptionStringOrSecretRef, StringOrSecretRef};
-        use super::super::BitwardenBackend;
+        use super::super::{BitwardenBackend, OnePasswordBackend};
 
         fn default_bitwarden_backend() -> Option<BitwardenBackend> {
             Some(BitwardenBackend::Bw)
@@ -294,7 +314,31 @@ fn generate_config_variant_fields(provider: &ProviderToml) -> Vec<TokenStream> {
                     #field_name: Vec<String>
                 });
             }
-            "backend_enum" => {
+            "optional_vec_string" => {
+                fields.push(quote! {
+                    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+                    #field_name: Vec<String>
+                });
+            }
+            "map_string" => {
+                fields.push(quote! {
+                    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+                    #field_name: IndexMap<String, StringOrSecretRef>
+                });
+            }
+            "optional_u32" => {
+                fields.push(quote! {
+                    #[serde(default, skip_serializing_if = "Option::is_none")]
+                    #field_name: Option<u32>
+                });
+            }
+            "optional_bool" => {
+                fields.push(quote! {
+                    #[serde(default, skip_serializing_if = "Option::is_none")]
+                    #field_name: Option<bool>
+                });
+            }
+            "backend_enum" if field.enum_type.is_none() => {
                 fields.push(quote! {
                     #[serde(
                         default = "default_bitwarden_backend",
@@ -303,6 +347,13 @@ fn generate_config_variant_fields(provider: &ProviderToml) -> Vec<TokenStream> {
                     backend: Option<BitwardenBackend>
                 });
             }
+            "backend_enum" => {
+                let enum_ident = field.enum_ident();
+                fields.push(quote! {
+                    #[serde(default, skip_serializing_if = "Option::is_none")]
+                    backend: Option<#enum_ident>
+                });
+            }
             "provider_ref" => {
                 fields.push(quote! {
                     #[serde(default, skip_serializing_if = "OptionProviderSecretRef::is_none")]
@@ -339,11 +390,21 @@ fn generate_resolved_variant_fields(provider: &ProviderToml) -> Vec<TokenStream>
             "optional" => {
                 fields.push(quote! { #field_name: Option<String> });
             }
-            "vec_string" => {
+            "vec_string" | "optional_vec_string" => {
                 fields.push(quote! { #field_name: Vec<String> });
             }
+            "map_string" => {
+                fields.push(quote! { #field_name: IndexMap<String, String> });
+            }
+            "optional_u32" => {
+                fields.push(quote! { #field_name: Option<u32> });
+            }
+            "optional_bool" => {
+                fields.push(quote! { #field_name: Option<bool> });
+            }
             "backend_enum" => {
-                fields.push(quote! { backend: Option<BitwardenBackend> });
+                let enum_ident = field.enum_ident();
+                fields.push(quote! { backend: Option<#enum_ident> });
             }
             "provider_ref" => {
                 fields.push(quote! { #field_name: OptionProviderSecretRef });
@@ -364,6 +425,7 @@ fn generate_provider_methods(
     let mut daemon_cache_arms = Vec::new();
     let mut env_deps_arms = Vec::new();
     let mut interactive_auth_arms = Vec::new();
+    let mut path_references_arms = Vec::new();
 
     for (_name, provider) in providers {
         let variant = Ident::new(&provider.rust_variant, Span::call_site());
@@ -414,6 +476,10 @@ fn generate_provider_methods(
         interactive_auth_arms.push(quote! {
             Self::#variant { .. } => #interactive
         });
+        let path_references = provider.path_references;
+        path_references_arms.push(quote! {
+            Self::#variant { .. } => #path_references
+        });
     }
 
     // Note: Use super::super:: because this is included inside mod generated { mod providers_methods { ... } }
@@ -528,6 +594,14 @@ fn generate_provider_methods(
                 }
             }
 
+            /// Whether a reference ending in `/*` loads every secret under a
+            /// path, resolving to a JSON object of env var name to value.
+            pub fn supports_path_references(&self) -> bool {
+                match self {
+                    #(#path_references_arms),*
+                }
+            }
+
             /// Get the auth command for this provider.
             /// Returns the instance-level override if set, otherwise the static default.
             pub fn default_auth_command(&self) -> Option<&str> {
@@ -591,9 +665,20 @@ fn generate_try_to_resolved_body(provider: &ProviderToml) -> TokenStream {
             "optional" => {
                 field_conversions.push(quote! { #field_name: opt(#local_ident)? });
             }
-            "vec_string" => {
+            "vec_string" | "optional_vec_string" => {
                 field_conversions.push(quote! { #field_name: #local_ident.clone() });
             }
+            "map_string" => {
+                field_conversions.push(quote! {
+                    #field_name: #local_ident
+                        .iter()
+                        .map(|(k, v)| Ok((k.clone(), req(v)?)))
+                        .collect::<Result<_>>()?
+                });
+            }
+            "optional_u32" | "optional_bool" => {
+                field_conversions.push(quote! { #field_name: *#local_ident });
+            }
             "backend_enum" => {
                 field_conversions.push(quote! { backend: *backend });
             }
@@ -618,9 +703,21 @@ fn generate_from_wizard_fields_body(provider: &ProviderToml) -> TokenStream {
         return quote! { Ok(ProviderConfig::#variant { auth_command: None, daemon_cache: None }) };
     }
 
-    // Special handling for age provider
+    // Special handling for age provider: a keypair's recipient, or a
+    // passphrase when the wizard's "mode" is "passphrase"
     if provider.serde_rename == "age" {
         return quote! {
+            if fields.get("mode").map(String::as_str) == Some("passphrase") {
+                return Ok(ProviderConfig::AgeEncryption {
+                    recipients: Vec::new(),
+                    key_file: OptionStringOrSecretRef::none(),
+                    identity: OptionProviderSecretRef::none(),
+                    key_files: Vec::new(),
+                    passphrase: Some(true),
+                    auth_command: None,
+                    daemon_cache: None,
+                });
+            }
             Ok(ProviderConfig::AgeEncryption {
                 recipients: vec![
                     fields
@@ -631,6 +728,33 @@ fn generate_from_wizard_fields_body(provider: &ProviderToml) -> TokenStream {
                 ],
                 key_file: OptionStringOrSecretRef::none(),
                 identity: OptionProviderSecretRef::none(),
+                key_files: Vec::new(),
+                passphrase: None,
+                auth_command: None,
+                daemon_cache: None,
+            })
+        };
+    }
+
+    // Special handling for etcd provider: 'endpoint' is a comma-separated list
+    if provider.serde_rename == "etcd" {
+        return quote! {
+            Ok(ProviderConfig::Etcd {
+                endpoints: fields
+                    .get("endpoint")
+                    .map(|s| {
+                        s.split(',')
+                            .map(str::trim)
+                            .filter(|e| !e.is_empty())
+                            .map(String::from)
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|endpoints| !endpoints.is_empty())
+                    .ok_or_else(|| FnoxError::Config("endpoint is required".to_string()))?,
+                prefix: get_optional("prefix"),
+                ca_cert: OptionStringOrSecretRef::none(),
+                client_cert: OptionStringOrSecretRef::none(),
+                client_key: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             })
@@ -657,6 +781,7 @@ fn generate_from_wizard_fields_body(provider: &ProviderToml) -> TokenStream {
                 prefix: get_optional("prefix"),
                 store_dir: get_optional("store_dir"),
                 gpg_opts: OptionStringOrSecretRef::none(),
+                whole_entry: None,
                 auth_command: None,
                 daemon_cache: None,
             })
@@ -689,9 +814,21 @@ fn generate_from_wizard_fields_body(provider: &ProviderToml) -> TokenStream {
             "optional" => {
                 field_inits.push(quote! { #field_name: get_optional(#name_str) });
             }
-            "vec_string" | "backend_enum" => {
+            "vec_string" => {
                 // Skip - handled specially
             }
+            "optional_vec_string" => {
+                field_inits.push(quote! { #field_name: Vec::new() });
+            }
+            "backend_enum" => {
+                field_inits.push(quote! { backend: None });
+            }
+            "map_string" => {
+                field_inits.push(quote! { #field_name: Default::default() });
+            }
+            "optional_u32" | "optional_bool" => {
+                field_inits.push(quote! { #field_name: None });
+            }
             _ => {}
         }
     }
@@ -765,6 +902,8 @@ fn generate_new_args(provider: &ProviderToml) -> Vec<TokenStream> {
             let local_ident = Ident::new(&local_name, Span::call_site());
             match field.typ.as_str() {
                 "backend_enum" => quote! { *backend },
+                // Copy types are passed by value
+                "optional_u32" | "optional_bool" => quote! { *#local_ident },
                 _ => quote! { #local_ident.clone() },
             }
         })
@@ -838,12 +977,20 @@ fn generate_resolver_fields(provider: &ProviderToml) -> Vec<TokenStream> {
                 }
                 "optional" => {
                     quote! {
-                        #field_name: super::super::resolver::resolve_option(config, profile, provider_name, #local_ident, ctx).await?
+                        #field_name: super::super::resolver::resolve_option(config, profile, provider_name, #name_str, #local_ident, ctx).await?
                     }
                 }
-                "vec_string" => {
+                "vec_string" | "optional_vec_string" => {
                     quote! { #field_name: #local_ident.clone() }
                 }
+                "map_string" => {
+                    quote! {
+                        #field_name: super::super::resolver::resolve_map(config, profile, provider_name, #local_ident, ctx).await?
+                    }
+                }
+                "optional_u32" | "optional_bool" => {
+                    quote! { #field_name: *#local_ident }
+                }
                 "backend_enum" => {
                     quote! { backend: *backend }
                 }
@@ -83,6 +83,9 @@ mod tests {
             vault: OptionStringOrSecretRef::literal("default"),
             account: OptionStringOrSecretRef::none(),
             token: OptionStringOrSecretRef::none(),
+            backend: None,
+            connect_host: OptionStringOrSecretRef::none(),
+            connect_token: OptionStringOrSecretRef::none(),
             auth_command: None,
             daemon_cache: None,
         }
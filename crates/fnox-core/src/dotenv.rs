@@ -0,0 +1,394 @@
+//! Reading and writing `.env` files.
+//!
+//! Shared by the `envfile` provider, `fnox import` and `fnox export`, so a
+//! value written by one reads back the same through the others. Values are
+//! taken literally: `$` is never expanded or escaped, and backslashes only
+//! escape `\n`, `\r`, `\t`, `"` and `\` inside double quotes.
+
+use std::ops::Range;
+
+/// A `KEY=value` assignment and the lines it spans in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    pub lines: Range<usize>,
+    pub export: bool,
+    /// Comment after the value on its last line, with the space before it
+    pub comment: Option<String>,
+    /// The comment lines directly above the key, joined with spaces
+    pub description: Option<String>,
+}
+
+/// A quoted value that runs to the end of the file
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("'{key}' has no closing {quote} quote")]
+pub struct UnterminatedQuote {
+    pub key: String,
+    pub quote: char,
+    /// Byte offset of the opening quote
+    pub offset: usize,
+}
+
+/// Parse a `.env` file: `#` comments, optional `export ` prefixes, and single-
+/// or double-quoted values that may span several lines. Lines that aren't
+/// assignments are skipped. Comment lines directly above a key become its
+/// description, and a blank line drops them.
+pub fn parse(content: &str) -> Result<Vec<Entry>, UnterminatedQuote> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut entries = Vec::new();
+    let mut comments: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        let line = lines[i].trim();
+        i += 1;
+        if let Some(comment) = line.strip_prefix('#') {
+            comments.push(comment.trim());
+            continue;
+        }
+        if line.is_empty() {
+            comments.clear();
+            continue;
+        }
+        let (export, line) = match line.strip_prefix("export ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        let Some((key, after)) = line.split_once('=') else {
+            comments.clear();
+            continue;
+        };
+        let key = key.trim().to_string();
+        let rest = after.trim_start();
+
+        let (value, comment) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = rest[1..].to_string();
+                let comment = loop {
+                    if let Some(end) = closing_quote(&raw, quote) {
+                        let tail = raw[end + 1..].trim_end();
+                        let comment = tail.trim_start().starts_with('#').then(|| tail.to_string());
+                        raw.truncate(end);
+                        break comment;
+                    }
+                    let Some(next) = lines.get(i) else {
+                        return Err(UnterminatedQuote {
+                            key,
+                            quote,
+                            offset: rest.as_ptr() as usize - content.as_ptr() as usize,
+                        });
+                    };
+                    raw.push('\n');
+                    raw.push_str(next);
+                    i += 1;
+                };
+                let value = if quote == '"' { unescape(&raw) } else { raw };
+                (value, comment)
+            }
+            _ => {
+                let (value, comment) = split_inline_comment(after);
+                (
+                    value.trim().to_string(),
+                    comment.map(|c| c.trim_end().to_string()),
+                )
+            }
+        };
+
+        let description = comments
+            .iter()
+            .filter(|comment| !comment.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        comments.clear();
+        if !key.is_empty() {
+            entries.push(Entry {
+                key,
+                value,
+                lines: start..i,
+                export,
+                comment,
+                description: (!description.is_empty()).then_some(description),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Byte offset of the closing quote, skipping backslash escapes in double quotes
+fn closing_quote(raw: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (offset, c) in raw.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(offset);
+        }
+    }
+    None
+}
+
+/// Split an unquoted value from its comment, which starts at a `#` after
+/// whitespace, so `pa#ss` keeps its `#`. The comment keeps the space before it.
+fn split_inline_comment(value: &str) -> (&str, Option<&str>) {
+    let mut previous = None;
+    for (i, c) in value.char_indices() {
+        if c == '#'
+            && let Some((space, p)) = previous
+            && char::is_whitespace(p)
+        {
+            return (&value[..space], Some(&value[space..]));
+        }
+        previous = Some((i, c));
+    }
+    (value, None)
+}
+
+/// Undo the escapes of a double-quoted value. Unknown escapes such as `\$`
+/// are kept as written.
+fn unescape(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(other @ ('"' | '\\')) => value.push(other),
+            Some(other) => {
+                value.push('\\');
+                value.push(other);
+            }
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// Quote `value` for the right-hand side of `KEY=value`, leaving simple
+/// values bare.
+pub fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':'))
+    {
+        return value.to_string();
+    }
+
+    // Dotenv parsers treat `$` and backticks literally; use `--format shell`
+    // for sourceable shell output.
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENV: &str = r#"# Database
+export DATABASE_URL="postgres://localhost/db" # primary
+API_KEY=abc123 # inline comment
+SINGLE='no $expansion here'
+CERT="-----BEGIN CERT-----
+line \"two\"
+-----END CERT-----"
+ESCAPED="a\nb"
+EMPTY=
+"#;
+
+    fn value(key: &str) -> String {
+        parse(ENV)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.key == key)
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn parses_quotes_exports_and_comments() {
+        assert_eq!(value("DATABASE_URL"), "postgres://localhost/db");
+        assert_eq!(value("API_KEY"), "abc123");
+        assert_eq!(value("SINGLE"), "no $expansion here");
+        assert_eq!(value("ESCAPED"), "a\nb");
+        assert_eq!(value("EMPTY"), "");
+    }
+
+    #[test]
+    fn parses_multiline_values() {
+        assert_eq!(
+            value("CERT"),
+            "-----BEGIN CERT-----\nline \"two\"\n-----END CERT-----"
+        );
+        let entries = parse(ENV).unwrap();
+        let cert = entries.iter().find(|e| e.key == "CERT").unwrap();
+        assert_eq!(cert.lines, 4..7);
+    }
+
+    fn pairs(input: &str) -> Vec<(String, String)> {
+        parse(input)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect()
+    }
+
+    fn expected(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_quoted_values_in_file_order() {
+        let input = r#"
+PLAIN=value
+export EXPORTED=exported
+SPACED = spaced out
+DOUBLE="with \"escapes\"\nand a newline"
+SINGLE='literal \n $HOME'
+EMPTY=
+EMPTY_QUOTED=""
+MULTILINE="-----BEGIN KEY-----
+abc
+-----END KEY-----"
+"#;
+        assert_eq!(
+            pairs(input),
+            expected(&[
+                ("PLAIN", "value"),
+                ("EXPORTED", "exported"),
+                ("SPACED", "spaced out"),
+                ("DOUBLE", "with \"escapes\"\nand a newline"),
+                ("SINGLE", r"literal \n $HOME"),
+                ("EMPTY", ""),
+                ("EMPTY_QUOTED", ""),
+                ("MULTILINE", "-----BEGIN KEY-----\nabc\n-----END KEY-----"),
+            ])
+        );
+    }
+
+    #[test]
+    fn strips_inline_comments() {
+        let input = "URL=https://example.com/#anchor # the homepage\n\
+                     PASSWORD=pa#ss\n\
+                     TABBED=value\t# after a tab\n\
+                     QUOTED=\"a # b\" # not part of it\n\
+                     SINGLE='c # d'#\n\
+                     NOTHING= # just a comment\r\n";
+        assert_eq!(
+            pairs(input),
+            expected(&[
+                ("URL", "https://example.com/#anchor"),
+                ("PASSWORD", "pa#ss"),
+                ("TABBED", "value"),
+                ("QUOTED", "a # b"),
+                ("SINGLE", "c # d"),
+                ("NOTHING", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn skips_lines_that_are_not_assignments() {
+        let input = "just some words\nKEY=1\n=no key\nexport=2\n";
+        assert_eq!(pairs(input), expected(&[("KEY", "1"), ("export", "2")]));
+    }
+
+    #[test]
+    fn carries_preceding_comments_as_descriptions() {
+        let input = "# Database connection\n\
+                     # for the app\n\
+                     DATABASE_URL=postgres://localhost/app\n\
+                     API_KEY=key # inline comments aren't descriptions\n\
+                     # Separated by a blank line\n\
+                     \n\
+                     LOG_LEVEL=info\n\
+                     #\n\
+                     # Signing key\n\
+                     SIGNING_KEY=abc\n";
+        let descriptions: Vec<(String, Option<String>)> = parse(input)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.key, entry.description))
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                (
+                    "DATABASE_URL".to_string(),
+                    Some("Database connection for the app".to_string())
+                ),
+                ("API_KEY".to_string(), None),
+                ("LOG_LEVEL".to_string(), None),
+                ("SIGNING_KEY".to_string(), Some("Signing key".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        let input = "KEY=1\nBAD=\"never closed\nOTHER=1\n";
+        let err = parse(input).unwrap_err();
+        assert_eq!(err.to_string(), "'BAD' has no closing \" quote");
+        assert_eq!(&input[err.offset..err.offset + 1], "\"");
+    }
+
+    #[test]
+    fn unescape_handles_quote_escapes() {
+        assert_eq!(
+            unescape(r#"line1\nline2\t\"quoted\"\\path"#),
+            "line1\nline2\t\"quoted\"\\path"
+        );
+    }
+
+    #[test]
+    fn unescape_preserves_unknown_escapes() {
+        assert_eq!(unescape(r#"secret\$value\`tick"#), r#"secret\$value\`tick"#);
+    }
+
+    #[test]
+    fn quote_leaves_simple_values_unquoted() {
+        assert_eq!(quote("kek"), "kek");
+        assert_eq!(
+            quote("/tmp/fnox-export-FILE_SECRET-abc"),
+            "/tmp/fnox-export-FILE_SECRET-abc"
+        );
+    }
+
+    #[test]
+    fn quote_escapes_special_values() {
+        assert_eq!(quote("value with spaces"), "\"value with spaces\"");
+        assert_eq!(quote("it's \"fine\""), "\"it's \\\"fine\\\"\"");
+        assert_eq!(quote("a\nb\t$c`d"), "\"a\\nb\\t$c`d\"");
+    }
+
+    #[test]
+    fn quoted_values_read_back_unchanged() {
+        for value in ["pa$$word $HOME", "line1\nline2", "back\\slash \"q\"", ""] {
+            let content = format!("KEY={}\n", quote(value));
+            assert_eq!(parse(&content).unwrap()[0].value, value, "{content}");
+        }
+    }
+}
@@ -16,6 +16,23 @@ pub fn is_non_interactive() -> bool {
     NON_INTERACTIVE.load(Ordering::Acquire)
 }
 
+/// Whether prompting for input is ruled out even with a terminal attached,
+/// e.g. in `hook-env`, which runs from the shell prompt hook.
+static PROMPTS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn disable_prompts() {
+    PROMPTS_DISABLED.store(true, Ordering::Release);
+}
+
+/// Whether a provider may prompt for input: prompts aren't disabled, we're
+/// not non-interactive, and stdin and stderr are terminals.
+pub fn can_prompt() -> bool {
+    !is_non_interactive()
+        && !PROMPTS_DISABLED.load(Ordering::Acquire)
+        && atty::is(atty::Stream::Stdin)
+        && atty::is(atty::Stream::Stderr)
+}
+
 /// Mutex to serialize access to std::env::set_var, which is unsafe in Rust 2024 edition.
 static ENV_MUTEX: Mutex<()> = Mutex::new(());
 
@@ -81,6 +98,8 @@ pub static FNOX_PROFILE: LazyLock<Option<String>> = LazyLock::new(|| {
 
 // Age encryption key configuration
 pub static FNOX_AGE_KEY: LazyLock<Option<String>> = LazyLock::new(|| var("FNOX_AGE_KEY").ok());
+pub static FNOX_AGE_PASSPHRASE: LazyLock<Option<String>> =
+    LazyLock::new(|| var("FNOX_AGE_PASSPHRASE").ok().filter(|p| !p.is_empty()));
 
 // Auth prompt configuration (defaults to true if not set)
 pub static FNOX_PROMPT_AUTH: LazyLock<Option<bool>> = LazyLock::new(|| {
@@ -89,6 +108,13 @@ pub static FNOX_PROMPT_AUTH: LazyLock<Option<bool>> = LazyLock::new(|| {
         .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
 });
 
+// Record provider interactions to test cassettes instead of replaying them
+pub static FNOX_RECORD: LazyLock<bool> = LazyLock::new(|| {
+    var("FNOX_RECORD")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+});
+
 // Helper functions for parsing environment variables
 fn var_path(name: &str) -> Option<PathBuf> {
     var(name)
@@ -109,6 +109,20 @@ pub enum FnoxError {
         issues: Vec<ValidationIssue>,
     },
 
+    /// Secret keys that break the `[settings.naming]` policy
+    #[error("Secret keys break the naming policy: {keys}")]
+    #[diagnostic(
+        code(fnox::config::naming_policy),
+        help("Secret keys must {expected}{fix}"),
+        url("https://fnox.jdx.dev/reference/configuration#settings-naming")
+    )]
+    NamingPolicyViolation {
+        keys: String,
+        expected: String,
+        /// Extra hint appended to the help, such as a flag that renames keys
+        fix: String,
+    },
+
     /// Backward compatibility for ConfigNotFound with custom message/help
     #[error("{message}")]
     #[diagnostic(help("{help}"))]
@@ -122,6 +136,22 @@ pub enum FnoxError {
     // ========================================================================
     // Profile Errors
     // ========================================================================
+    #[error("Profile '{profile}' not found")]
+    #[diagnostic(
+        code(fnox::profile::not_found),
+        help(
+            "{suggestion}Available profiles: {available}\n  • List profiles: fnox profiles",
+            suggestion = suggestion.as_ref()
+                .map(|s| format!("{}\n\n", s))
+                .unwrap_or_default()
+        ),
+        url("https://fnox.jdx.dev/guide/profiles")
+    )]
+    ProfileNotFound {
+        profile: String,
+        available: String,
+        suggestion: Option<String>,
+    },
 
     // ========================================================================
     // Secret Errors
@@ -156,6 +186,23 @@ pub enum FnoxError {
         suggestion: Option<String>,
     },
 
+    #[error("Secret '{key}' already exists in profile '{profile}'{}",
+        config_path.as_ref()
+            .map(|p| format!("\n  Config file: {}", p.display()))
+            .unwrap_or_default()
+    )]
+    #[diagnostic(
+        code(fnox::secret::already_exists),
+        help(
+            "Choose another name, or remove the existing secret first: fnox remove {key} -P {profile}"
+        )
+    )]
+    SecretAlreadyExists {
+        key: String,
+        profile: String,
+        config_path: Option<std::path::PathBuf>,
+    },
+
     #[error("Failed to decode secret: {details}")]
     #[diagnostic(code(fnox::secret::decode_failed))]
     SecretDecodeFailed { details: String },
@@ -303,6 +350,20 @@ pub enum FnoxError {
         url: String,
     },
 
+    #[error("{provider}: invalid reference '{reference}': {details}")]
+    #[diagnostic(
+        code(fnox::provider::invalid_reference),
+        help("Expected {syntax}"),
+        url("{url}")
+    )]
+    ProviderInvalidReference {
+        provider: String,
+        reference: String,
+        details: String,
+        syntax: String,
+        url: String,
+    },
+
     #[error("{provider}: API error: {details}")]
     #[diagnostic(code(fnox::provider::api_error), help("{hint}"), url("{url}"))]
     ProviderApiError {
@@ -312,6 +373,17 @@ pub enum FnoxError {
         url: String,
     },
 
+    #[error("{provider}: '{key}' is {size} bytes, over the {limit}-byte limit for a value")]
+    #[diagnostic(code(fnox::provider::value_too_large), help("{hint}"), url("{url}"))]
+    ProviderValueTooLarge {
+        provider: String,
+        key: String,
+        size: usize,
+        limit: usize,
+        hint: String,
+        url: String,
+    },
+
     #[error("Circular dependency detected in provider configuration for '{provider}'")]
     #[diagnostic(
         code(fnox::provider::config_cycle),
@@ -398,6 +470,18 @@ pub enum FnoxError {
     )]
     AgeDecryptionFailed { details: String },
 
+    #[error(
+        "The local provider's key is missing from the OS keychain (service '{service}'), so values encrypted with it can't be decrypted"
+    )]
+    #[diagnostic(
+        code(fnox::encryption::local::key_missing),
+        help(
+            "Without the key these values are unrecoverable. Restore the keychain entry from a backup, or set the secrets again with `fnox set` to encrypt them with a new key"
+        ),
+        url("https://fnox.jdx.dev/providers/local")
+    )]
+    LocalKeyMissing { service: String },
+
     // ========================================================================
     // Editor Errors
     // ========================================================================
@@ -413,6 +497,16 @@ pub enum FnoxError {
     #[diagnostic(code(fnox::editor::exit_failed))]
     EditorExitFailed { editor: String, status: i32 },
 
+    #[error("Couldn't save edited secrets: {keys}")]
+    #[diagnostic(
+        code(fnox::editor::secrets_not_saved),
+        help(
+            "The other changes were saved. The values you entered for these secrets are in {recovery}; save them again with 'fnox set' or 'fnox edit', then delete that file"
+        ),
+        url("https://fnox.jdx.dev/cli/edit")
+    )]
+    EditSecretsNotSaved { keys: String, recovery: String },
+
     // ========================================================================
     // Lease Errors
     // ========================================================================
@@ -519,6 +613,17 @@ pub enum FnoxError {
     )]
     ImportProviderUnsupported { provider: String, help: String },
 
+    // ========================================================================
+    // Format Errors
+    // ========================================================================
+    #[error("Formatting would change {}", pluralizer::pluralize("config file", std::cmp::min(*files, isize::MAX as usize) as isize, true))]
+    #[diagnostic(
+        code(fnox::fmt::check_failed),
+        help("Run 'fnox fmt' to format them"),
+        url("https://fnox.jdx.dev/cli/fmt")
+    )]
+    FormatCheckFailed { files: usize },
+
     // ========================================================================
     // Scan Errors
     // ========================================================================
@@ -530,6 +635,14 @@ pub enum FnoxError {
     )]
     ScanSecretsFound,
 
+    #[error("Failed to read staged files: {details}")]
+    #[diagnostic(
+        code(fnox::scan::git_failed),
+        help("`fnox scan --staged` must run inside a git repository, with git on PATH"),
+        url("https://fnox.jdx.dev/cli/scan")
+    )]
+    ScanGitFailed { details: String },
+
     // ========================================================================
     // Sync Errors
     // ========================================================================
@@ -606,6 +719,38 @@ pub enum FnoxError {
         source: std::io::Error,
     },
 
+    #[error("Failed to write output to file: {}", path.display())]
+    #[diagnostic(
+        code(fnox::io::output_write_failed),
+        help("Ensure you have write permissions for the output path")
+    )]
+    OutputWriteFailed {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Refusing to export secrets to {}, which git would commit", path.display())]
+    #[diagnostic(
+        code(fnox::export::would_be_committed),
+        help(
+            "Add the file to .gitignore, export somewhere outside the repository, or pass --force (FNOX_EXPORT_GIT_GUARD=false turns the check off)"
+        ),
+        url("https://fnox.jdx.dev/cli/export")
+    )]
+    ExportWouldBeCommitted { path: std::path::PathBuf },
+
+    #[error("Secrets '{first}' and '{second}' would both be exported as '{name}'")]
+    #[diagnostic(
+        code(fnox::export::env_name_collision),
+        help("Use --map to give one of them a different name")
+    )]
+    EnvNameCollision {
+        name: String,
+        first: String,
+        second: String,
+    },
+
     #[error("Failed to read from stdin")]
     #[diagnostic(code(fnox::io::stdin_read_failed))]
     StdinReadFailed {
@@ -613,6 +758,18 @@ pub enum FnoxError {
         source: std::io::Error,
     },
 
+    #[error("Failed to read required keys from {}", path.display())]
+    #[diagnostic(
+        code(fnox::check::require_file_read_failed),
+        help("Ensure the file exists and lists one secret key per line"),
+        url("https://fnox.jdx.dev/cli/check")
+    )]
+    RequireFileReadFailed {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     // ========================================================================
     // Generic I/O Errors (fallback)
     // ========================================================================
@@ -722,6 +879,19 @@ impl FnoxError {
                 hint: hint.clone(),
                 url: url.clone(),
             },
+            FnoxError::ProviderInvalidReference {
+                provider,
+                reference,
+                details,
+                syntax,
+                url,
+            } => FnoxError::ProviderInvalidReference {
+                provider: provider.clone(),
+                reference: reference.clone(),
+                details: details.clone(),
+                syntax: syntax.clone(),
+                url: url.clone(),
+            },
             FnoxError::ProviderApiError {
                 provider,
                 details,
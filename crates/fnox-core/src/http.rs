@@ -1,14 +1,86 @@
+use std::sync::OnceLock;
 use std::time::Duration;
 
-/// Build an HTTP client with the configured timeout from settings.
+/// How long an idle pooled connection is kept for reuse.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// TCP keep-alive interval for pooled connections.
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+/// Upper bound on a warm-up request; it only needs to get through DNS/TLS.
+const WARM_UP_TIMEOUT: Duration = Duration::from_secs(5);
+
+static SHARED_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static INSECURE_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Get the process-wide HTTP client, configured with the timeouts from settings.
+///
+/// The client is built once and shared: `reqwest::Client` clones share one
+/// connection pool, so repeated calls (e.g. every provider in a batch, or every
+/// lease backend in `fnox exec`) reuse keep-alive connections instead of
+/// re-negotiating DNS and TLS each time.
 pub fn http_client() -> reqwest::Client {
+    SHARED_CLIENT
+        .get_or_init(|| build_from_settings(false))
+        .clone()
+}
+
+/// Like [`http_client`], but accepts invalid TLS certificates.
+/// Only for providers that explicitly opt out of verification (e.g. self-signed servers).
+pub fn insecure_http_client() -> reqwest::Client {
+    INSECURE_CLIENT
+        .get_or_init(|| build_from_settings(true))
+        .clone()
+}
+
+/// A client builder with the same settings as [`http_client`], for providers
+/// that need their own TLS setup (a private CA or a client certificate) and so
+/// can't share the process-wide client.
+pub fn http_client_builder() -> reqwest::ClientBuilder {
+    let (timeout, connect_timeout) = timeouts_from_settings();
+    client_builder(timeout, connect_timeout, false)
+}
+
+fn timeouts_from_settings() -> (Duration, Duration) {
     let settings = crate::settings::Settings::get();
     let timeout =
         crate::lease::parse_duration(&settings.http_timeout).unwrap_or(Duration::from_secs(30));
+    let connect_timeout = crate::lease::parse_duration(&settings.http_connect_timeout)
+        .unwrap_or(Duration::from_secs(10));
+    (timeout, connect_timeout)
+}
+
+fn build_from_settings(accept_invalid_certs: bool) -> reqwest::Client {
+    let (timeout, connect_timeout) = timeouts_from_settings();
+    build_client(timeout, connect_timeout, accept_invalid_certs)
+}
+
+/// Configure a pooled client. A zero duration disables the corresponding timeout.
+fn client_builder(
+    timeout: Duration,
+    connect_timeout: Duration,
+    accept_invalid_certs: bool,
+) -> reqwest::ClientBuilder {
     let user_agent = format!("fnox/{}", env!("CARGO_PKG_VERSION"));
-    reqwest::Client::builder()
-        .timeout(timeout)
+    let mut builder = reqwest::Client::builder()
         .user_agent(user_agent)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .danger_accept_invalid_certs(accept_invalid_certs);
+    if !timeout.is_zero() {
+        builder = builder.timeout(timeout);
+    }
+    if !connect_timeout.is_zero() {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    builder
+}
+
+/// Build a pooled client. A zero duration disables the corresponding timeout.
+fn build_client(
+    timeout: Duration,
+    connect_timeout: Duration,
+    accept_invalid_certs: bool,
+) -> reqwest::Client {
+    client_builder(timeout, connect_timeout, accept_invalid_certs)
         .build()
         .unwrap_or_else(|e| {
             tracing::warn!(
@@ -17,3 +89,58 @@ pub fn http_client() -> reqwest::Client {
             reqwest::Client::new()
         })
 }
+
+/// Prime DNS, TCP and TLS for `url` so the connection is pooled before real requests.
+///
+/// Any HTTP response counts as success, since only reachability matters here;
+/// the error is returned when the server can't be reached at all.
+pub async fn warm_up(
+    client: &reqwest::Client,
+    url: &str,
+) -> std::result::Result<(), reqwest::Error> {
+    let started = std::time::Instant::now();
+    client.head(url).timeout(WARM_UP_TIMEOUT).send().await?;
+    tracing::debug!(
+        "Warmed up HTTP connection to {} in {:?}",
+        url,
+        started.elapsed()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn shared_client_reuses_pooled_connection() {
+        let server = test_server::serve(|_, _| (200, "ok".to_string())).await;
+        let (url, connections) = (&server.url, &server.connections);
+
+        // Before: a fresh client per request opens a connection each time
+        for _ in 0..5 {
+            let client = build_client(Duration::from_secs(5), Duration::from_secs(5), false);
+            client.get(url).send().await.unwrap();
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 5);
+
+        // After: one warmed-up client serves every request over one connection
+        connections.store(0, Ordering::SeqCst);
+        let client = build_client(Duration::from_secs(5), Duration::from_secs(5), false);
+        warm_up(&client, url).await.unwrap();
+        for _ in 0..5 {
+            let response = client.get(url).send().await.unwrap();
+            assert_eq!(response.text().await.unwrap(), "ok");
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn warm_up_reports_unreachable_servers() {
+        let url = test_server::unreachable_url().await;
+        let client = build_client(Duration::from_secs(5), Duration::from_secs(5), false);
+        assert!(warm_up(&client, &url).await.is_err());
+    }
+}
@@ -11,6 +11,7 @@
 pub mod auth_prompt;
 pub mod config;
 pub(crate) mod credential_command;
+pub mod dotenv;
 pub mod env;
 pub mod error;
 pub mod http;
@@ -18,13 +19,19 @@ pub(crate) mod keyring_store;
 pub mod lease;
 pub mod lease_backends;
 pub mod library;
+pub mod naming;
 pub mod providers;
+pub mod recording;
+pub mod secret_groups;
 pub mod secret_resolver;
 pub mod settings;
 pub mod source_registry;
 pub mod spanned;
 pub mod suggest;
 pub mod temp_file_secrets;
+#[cfg(test)]
+pub(crate) mod test_server;
+pub mod worktree;
 
 // Re-export commonly used items
 pub use error::{FnoxError, Result};
@@ -0,0 +1,211 @@
+//! Secret key naming policy, configured under `[settings.naming]`.
+//!
+//! The policy is opt-in: without a `[settings.naming]` table any key is
+//! accepted. With one, keys must match `pattern` (upper snake case unless set)
+//! and start with the profile's required prefix, if any.
+
+use crate::config::Config;
+use crate::error::{FnoxError, Result};
+use regex::Regex;
+
+/// Pattern keys must match when `[settings.naming]` doesn't set one
+pub const DEFAULT_PATTERN: &str = "^[A-Z][A-Z0-9_]*$";
+
+#[derive(Debug, Clone)]
+pub struct NamingPolicy {
+    pattern: Regex,
+    prefix: Option<String>,
+}
+
+impl NamingPolicy {
+    /// The policy for `profile`, or `None` when the config has no
+    /// `[settings.naming]` table
+    pub fn for_profile(config: &Config, profile: &str) -> Result<Option<Self>> {
+        let Some(naming) = config.settings.as_ref().and_then(|s| s.naming.as_ref()) else {
+            return Ok(None);
+        };
+        let pattern = naming.pattern.as_deref().unwrap_or(DEFAULT_PATTERN);
+        let pattern = Regex::new(pattern).map_err(|e| {
+            FnoxError::Config(format!("Invalid settings.naming.pattern '{pattern}': {e}"))
+        })?;
+        let prefix = naming
+            .profile_prefixes
+            .get(profile)
+            .or(naming.require_prefix.as_ref())
+            .filter(|prefix| !prefix.is_empty())
+            .cloned();
+        Ok(Some(Self { pattern, prefix }))
+    }
+
+    /// Why `key` breaks the policy, or `None` if it doesn't
+    pub fn violation(&self, key: &str) -> Option<String> {
+        if let Some(prefix) = &self.prefix
+            && !key.starts_with(prefix.as_str())
+        {
+            return Some(format!("must start with '{prefix}'"));
+        }
+        if !self.pattern.is_match(key) {
+            return Some(format!("must match {}", self.pattern.as_str()));
+        }
+        None
+    }
+
+    /// Keys that break the policy, in the order given
+    pub fn violations<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        keys.into_iter()
+            .filter(|key| self.violation(key).is_some())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// What keys must look like, for messages
+    pub fn expected(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("start with '{prefix}' and match {}", self.pattern.as_str()),
+            None => format!("match {}", self.pattern.as_str()),
+        }
+    }
+
+    /// Rename `key` towards the policy: upper snake case with the required
+    /// prefix. The result can still break a custom pattern.
+    pub fn fix(&self, key: &str) -> String {
+        let fixed = upper_snake_case(key);
+        match &self.prefix {
+            Some(prefix) if !fixed.starts_with(prefix.as_str()) => format!("{prefix}{fixed}"),
+            _ => fixed,
+        }
+    }
+
+    /// Error listing `keys` as breaking the policy, with `fix` appended to the
+    /// help (e.g. a flag that renames them)
+    pub fn error(&self, keys: &[String], fix: &str) -> FnoxError {
+        FnoxError::NamingPolicyViolation {
+            keys: keys.join(", "),
+            expected: self.expected(),
+            fix: fix.to_string(),
+        }
+    }
+}
+
+/// `databaseUrl`, `database-url`, and `database.url` all become `DATABASE_URL`
+fn upper_snake_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut prev: Option<char> = None;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            let word_start = c.is_ascii_uppercase()
+                && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit());
+            if word_start && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+        prev = Some(c);
+    }
+    out.trim_end_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NamingConfig, SettingsConfig};
+
+    fn config(naming: NamingConfig) -> Config {
+        let mut config = Config::new();
+        config.settings = Some(SettingsConfig {
+            naming: Some(naming),
+            ..Default::default()
+        });
+        config
+    }
+
+    fn policy(naming: NamingConfig, profile: &str) -> NamingPolicy {
+        NamingPolicy::for_profile(&config(naming), profile)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn no_policy_without_settings() {
+        assert!(
+            NamingPolicy::for_profile(&Config::new(), "default")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn default_pattern_is_upper_snake_case() {
+        let policy = policy(NamingConfig::default(), "default");
+        assert!(policy.violation("DATABASE_URL").is_none());
+        assert!(policy.violation("API_KEY_2").is_none());
+        for key in ["database_url", "DatabaseUrl", "_KEY", "2FA_CODE", "API-KEY"] {
+            assert!(policy.violation(key).is_some(), "{key}");
+        }
+    }
+
+    #[test]
+    fn profile_prefix_overrides_require_prefix() {
+        let naming = NamingConfig {
+            require_prefix: Some("APP_".into()),
+            profile_prefixes: [("prod".to_string(), "PROD_".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let default = policy(naming.clone(), "default");
+        assert!(default.violation("APP_TOKEN").is_none());
+        assert_eq!(
+            default.violation("TOKEN").as_deref(),
+            Some("must start with 'APP_'")
+        );
+
+        let prod = policy(naming, "prod");
+        assert!(prod.violation("PROD_TOKEN").is_none());
+        assert!(prod.violation("APP_TOKEN").is_some());
+    }
+
+    #[test]
+    fn custom_pattern() {
+        let policy = policy(
+            NamingConfig {
+                pattern: Some("^[a-z][a-z0-9-]*$".into()),
+                ..Default::default()
+            },
+            "default",
+        );
+        assert!(policy.violation("api-key").is_none());
+        assert_eq!(
+            policy.violations(["api-key", "API_KEY", "db-url"]),
+            vec!["API_KEY"]
+        );
+    }
+
+    #[test]
+    fn invalid_pattern_is_a_config_error() {
+        let config = config(NamingConfig {
+            pattern: Some("[".into()),
+            ..Default::default()
+        });
+        let err = NamingPolicy::for_profile(&config, "default").unwrap_err();
+        assert!(err.to_string().contains("settings.naming.pattern"));
+    }
+
+    #[test]
+    fn fixes_keys_with_case_transform_and_prefix() {
+        let policy = policy(
+            NamingConfig {
+                require_prefix: Some("APP_".into()),
+                ..Default::default()
+            },
+            "default",
+        );
+        assert_eq!(policy.fix("databaseUrl"), "APP_DATABASE_URL");
+        assert_eq!(policy.fix("database-url"), "APP_DATABASE_URL");
+        assert_eq!(policy.fix("api.key.v2"), "APP_API_KEY_V2");
+        assert_eq!(policy.fix("APP_TOKEN"), "APP_TOKEN");
+        assert_eq!(policy.fix("HTTPProxy"), "APP_HTTPPROXY");
+    }
+}
@@ -1,10 +1,16 @@
 use crate::env;
 use crate::error::{FnoxError, Result};
 use crate::providers::OptionProviderSecretRef;
+use age::secrecy::{ExposeSecret, SecretString};
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::io::Read;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Passphrases prompted for this process, by provider name, so each is
+/// asked for once
+static PROMPTED_PASSPHRASES: OnceLock<Mutex<HashMap<String, SecretString>>> = OnceLock::new();
 
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
@@ -14,22 +20,44 @@ pub struct AgeEncryptionProvider {
     recipients: Vec<String>,
     key_file: Option<PathBuf>,
     identity: OptionProviderSecretRef,
+    /// Further identity files, tried along with `key_file`
+    key_files: Vec<PathBuf>,
+    /// Encrypt to a passphrase (scrypt) instead of recipients
+    passphrase: bool,
+    /// Directory of the config file defining the provider, which relative
+    /// recipients files are read from
+    config_dir: Option<PathBuf>,
     config: Option<Arc<crate::config::Config>>,
     profile: String,
     provider_name: String,
     identity_cycle_guard: Option<AgeIdentityCycleGuard>,
 }
 
+/// The config an age provider is defined in, which its `identity` reference
+/// and relative recipients files are resolved against
+pub struct AgeConfigContext {
+    pub config: Arc<crate::config::Config>,
+    pub profile: String,
+    pub provider_name: String,
+    pub identity_cycle_guard: Option<AgeIdentityCycleGuard>,
+}
+
 impl AgeEncryptionProvider {
     pub fn new(
         recipients: Vec<String>,
         key_file: Option<String>,
         identity: OptionProviderSecretRef,
+        key_files: Vec<String>,
+        passphrase: Option<bool>,
     ) -> Result<Self> {
+        let passphrase = passphrase_mode(passphrase, &recipients)?;
         Ok(Self {
             recipients,
-            key_file: key_file.map(|k| PathBuf::from(shellexpand::tilde(&k).to_string())),
+            key_file: key_file.as_deref().map(expand_path),
             identity,
+            key_files: key_files.iter().map(|k| expand_path(k)).collect(),
+            passphrase,
+            config_dir: None,
             config: None,
             profile: "default".to_string(),
             provider_name: "age".to_string(),
@@ -41,15 +69,28 @@ impl AgeEncryptionProvider {
         recipients: Vec<String>,
         key_file: Option<String>,
         identity: OptionProviderSecretRef,
-        config: Arc<crate::config::Config>,
-        profile: String,
-        provider_name: String,
-        identity_cycle_guard: Option<AgeIdentityCycleGuard>,
+        key_files: Vec<String>,
+        passphrase: Option<bool>,
+        context: AgeConfigContext,
     ) -> Result<Self> {
+        let AgeConfigContext {
+            config,
+            profile,
+            provider_name,
+            identity_cycle_guard,
+        } = context;
+        let passphrase = passphrase_mode(passphrase, &recipients)?;
+        let config_dir = config
+            .provider_source(&profile, &provider_name)
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
         Ok(Self {
             recipients,
-            key_file: key_file.map(|k| PathBuf::from(shellexpand::tilde(&k).to_string())),
+            key_file: key_file.as_deref().map(expand_path),
             identity,
+            key_files: key_files.iter().map(|k| expand_path(k)).collect(),
+            passphrase,
+            config_dir,
             config: Some(config),
             profile,
             provider_name,
@@ -87,6 +128,331 @@ impl AgeEncryptionProvider {
         )
         .await
     }
+
+    /// The passphrase from FNOX_AGE_PASSPHRASE, or prompted for once per
+    /// process when prompting is possible. Prompting to encrypt asks twice,
+    /// since a typo would make the secret unreadable.
+    fn resolve_passphrase(&self, confirm: bool) -> Result<Option<SecretString>> {
+        if let Some(passphrase) = env::FNOX_AGE_PASSPHRASE.as_deref() {
+            return Ok(Some(SecretString::from(passphrase.to_string())));
+        }
+
+        let cache = PROMPTED_PASSPHRASES.get_or_init(|| Mutex::new(HashMap::new()));
+        // Hold the lock while prompting so concurrent resolutions ask once
+        let mut guard = cache
+            .lock()
+            .map_err(|_| FnoxError::Config("Age passphrase cache lock poisoned".to_string()))?;
+        if let Some(cached) = guard.get(&self.provider_name) {
+            return Ok(Some(SecretString::from(cached.expose_secret().to_string())));
+        }
+        if !env::can_prompt() {
+            return Ok(None);
+        }
+
+        let prompt = |title: String| {
+            demand::Input::new(title)
+                .password(true)
+                .run()
+                .map_err(|e| FnoxError::Config(format!("Prompt cancelled: {}", e)))
+        };
+        let passphrase = prompt(format!(
+            "Passphrase for age provider '{}'",
+            self.provider_name
+        ))?;
+        if passphrase.is_empty() {
+            return Ok(None);
+        }
+        if confirm && prompt("Confirm passphrase".to_string())? != passphrase {
+            return Err(FnoxError::AgeEncryptionFailed {
+                details: "Passphrases don't match".to_string(),
+            });
+        }
+        guard.insert(
+            self.provider_name.clone(),
+            SecretString::from(passphrase.clone()),
+        );
+        Ok(Some(SecretString::from(passphrase)))
+    }
+
+    /// Identities from the configured key files, or from the settings or
+    /// default key file when none are configured. With several key files,
+    /// missing ones are skipped as long as one of them exists.
+    fn load_key_files(&self) -> Result<Vec<Box<dyn age::Identity>>> {
+        let mut paths: Vec<PathBuf> = self
+            .key_file
+            .iter()
+            .chain(&self.key_files)
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            // Get settings which merges CLI flags, env vars, and defaults
+            let settings = crate::settings::Settings::get();
+
+            if let Some(ref age_key_file) = settings.age_key_file {
+                // Use age key file from settings (CLI flag or env var - deprecated)
+                paths.push(age_key_file.clone());
+            } else {
+                // Try default path
+                let default_key_path = env::FNOX_CONFIG_DIR.join("age.txt");
+                if !default_key_path.exists() {
+                    return Err(FnoxError::AgeIdentityNotFound {
+                        path: default_key_path,
+                    });
+                }
+                paths.push(default_key_path);
+            }
+        }
+
+        let mut identities = Vec::new();
+        for path in &paths {
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) if paths.len() > 1 && e.kind() == std::io::ErrorKind::NotFound => {
+                    tracing::debug!("Skipping missing age key file {}", path.display());
+                    continue;
+                }
+                Err(e) => {
+                    return Err(FnoxError::AgeIdentityReadFailed {
+                        path: path.clone(),
+                        source: e,
+                    });
+                }
+            };
+            identities.extend(parse_identities(&content, Some(path))?);
+        }
+
+        if identities.is_empty() {
+            return Err(FnoxError::AgeIdentityNotFound {
+                path: paths.swap_remove(0),
+            });
+        }
+        Ok(identities)
+    }
+}
+
+fn expand_path(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).to_string())
+}
+
+/// Whether `passphrase = true`; age can't mix a passphrase with recipients
+fn passphrase_mode(passphrase: Option<bool>, recipients: &[String]) -> Result<bool> {
+    let passphrase = passphrase.unwrap_or(false);
+    if passphrase && !recipients.is_empty() {
+        return Err(FnoxError::Config(
+            "age provider can't use both `passphrase = true` and `recipients`".to_string(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+const NO_PASSPHRASE: &str = "No passphrase: set FNOX_AGE_PASSPHRASE \
+     (fnox can't prompt for one here)";
+
+/// Encrypt `plaintext` to a passphrase with age's scrypt recipient
+fn encrypt_with_passphrase(plaintext: &str, passphrase: SecretString) -> Result<String> {
+    seal(age::Encryptor::with_user_passphrase(passphrase), plaintext)
+}
+
+/// Decrypt a ciphertext that was encrypted to a passphrase
+fn decrypt_with_passphrase(encrypted: &[u8], passphrase: SecretString) -> Result<String> {
+    let identity = age::scrypt::Identity::new(passphrase);
+    open(encrypted, std::iter::once(&identity as &dyn age::Identity)).map_err(|e| match e {
+        FnoxError::AgeDecryptionFailed { details } => FnoxError::AgeDecryptionFailed {
+            details: format!(
+                "{} (wrong passphrase, or not encrypted to a passphrase?)",
+                details
+            ),
+        },
+        e => e,
+    })
+}
+
+/// Encrypt `plaintext` with `encryptor`, base64-encoded
+fn seal(encryptor: age::Encryptor, plaintext: &str) -> Result<String> {
+    use std::io::Write;
+
+    let mut encrypted = vec![];
+    let mut writer =
+        encryptor
+            .wrap_output(&mut encrypted)
+            .map_err(|e| FnoxError::AgeEncryptionFailed {
+                details: format!("Failed to create encrypted writer: {}", e),
+            })?;
+
+    writer
+        .write_all(plaintext.as_bytes())
+        .map_err(|e| FnoxError::AgeEncryptionFailed {
+            details: format!("Failed to write plaintext: {}", e),
+        })?;
+
+    writer
+        .finish()
+        .map_err(|e| FnoxError::AgeEncryptionFailed {
+            details: format!("Failed to finalize encryption: {}", e),
+        })?;
+
+    // Base64 encode the encrypted output
+    use base64::Engine;
+    Ok(base64::engine::general_purpose::STANDARD.encode(&encrypted))
+}
+
+/// Decrypt `encrypted` with the first of `identities` that matches its header
+fn open<'a>(
+    encrypted: &[u8],
+    identities: impl Iterator<Item = &'a dyn age::Identity>,
+) -> Result<String> {
+    let decryptor = age::Decryptor::new(encrypted).map_err(|e| FnoxError::AgeDecryptionFailed {
+        details: format!("Failed to create decryptor: {}", e),
+    })?;
+
+    let mut reader = decryptor
+        .decrypt(identities)
+        .map_err(|e| FnoxError::AgeDecryptionFailed {
+            details: e.to_string(),
+        })?;
+
+    let mut decrypted = vec![];
+    reader
+        .read_to_end(&mut decrypted)
+        .map_err(|e| FnoxError::AgeDecryptionFailed {
+            details: format!("Failed to read decrypted data: {}", e),
+        })?;
+
+    String::from_utf8(decrypted).map_err(|e| FnoxError::AgeDecryptionFailed {
+        details: format!("Failed to decode UTF-8: {}", e),
+    })
+}
+
+/// Parse an SSH private key or an age identity file
+fn parse_identities(content: &str, path: Option<&PathBuf>) -> Result<Vec<Box<dyn age::Identity>>> {
+    let mut cursor = std::io::Cursor::new(content.as_bytes());
+
+    // First try to parse as SSH identity
+    match age::ssh::Identity::from_buffer(
+        &mut cursor,
+        path.map(|p| p.to_string_lossy().to_string()),
+    ) {
+        Ok(ssh_identity) => {
+            // SSH identity parsed successfully
+            Ok(vec![Box::new(ssh_identity) as Box<dyn age::Identity>])
+        }
+        Err(_) => {
+            // Not an SSH identity, try age identity file. Setting
+            // callbacks lets `into_identities` construct plugin
+            // identities (e.g. AGE-PLUGIN-YUBIKEY-1...) by driving the
+            // matching `age-plugin-*` binary, which may ask for a PIN or touch.
+            cursor.set_position(0);
+            age::IdentityFile::from_buffer(cursor)
+                .map_err(|e| FnoxError::AgeIdentityParseFailed {
+                    details: e.to_string(),
+                })?
+                .with_callbacks(PluginPrompts::new())
+                .into_identities()
+                .map_err(|e| FnoxError::AgeIdentityParseFailed {
+                    details: e.to_string(),
+                })
+        }
+    }
+}
+
+/// Callbacks for age plugins such as age-plugin-yubikey.
+///
+/// Messages like "touch your YubiKey" are printed to stderr. Requests for a
+/// PIN or a confirmation are prompted for on the terminal when fnox can
+/// prompt; otherwise they are declined with a note on stderr, so the plugin
+/// fails instead of waiting for input that never comes.
+#[derive(Clone, Copy)]
+struct PluginPrompts {
+    interactive: bool,
+}
+
+impl PluginPrompts {
+    fn new() -> Self {
+        Self {
+            interactive: env::can_prompt(),
+        }
+    }
+
+    fn decline(&self, request: &str) {
+        eprintln!("age plugin: {}", request);
+        eprintln!("age plugin: fnox can't prompt for input here; run the command in a terminal");
+    }
+}
+
+impl age::Callbacks for PluginPrompts {
+    fn display_message(&self, message: &str) {
+        eprintln!("age plugin: {}", message);
+    }
+
+    fn confirm(&self, message: &str, yes_string: &str, no_string: Option<&str>) -> Option<bool> {
+        if !self.interactive {
+            self.decline(message);
+            return None;
+        }
+        age::Callbacks::confirm(
+            &age::cli_common::UiCallbacks,
+            message,
+            yes_string,
+            no_string,
+        )
+    }
+
+    fn request_public_string(&self, description: &str) -> Option<String> {
+        if !self.interactive {
+            self.decline(description);
+            return None;
+        }
+        age::Callbacks::request_public_string(&age::cli_common::UiCallbacks, description)
+    }
+
+    fn request_passphrase(&self, description: &str) -> Option<SecretString> {
+        if !self.interactive {
+            self.decline(description);
+            return None;
+        }
+        age::Callbacks::request_passphrase(&age::cli_common::UiCallbacks, description)
+    }
+}
+
+/// Expand `recipients` entries that name a recipients file into the
+/// recipients listed in it. Entries starting with `age1` or `ssh-` are
+/// recipients themselves; anything else is read as a file with one
+/// recipient per line, where blank lines and `#` comments are ignored.
+/// Relative file paths are read from `config_dir`, the directory of the
+/// config file that lists them, when given.
+pub fn expand_recipients(recipients: &[String], config_dir: Option<&Path>) -> Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for entry in recipients {
+        if entry.starts_with("age1") || entry.starts_with("ssh-") {
+            expanded.push(entry.clone());
+            continue;
+        }
+
+        let mut path = expand_path(entry);
+        if let Some(dir) = config_dir
+            && path.is_relative()
+        {
+            path = dir.join(path);
+        }
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| FnoxError::AgeEncryptionFailed {
+                details: format!("Failed to read recipients file '{}': {}", path.display(), e),
+            })?;
+        let before = expanded.len();
+        expanded.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+        if expanded.len() == before {
+            return Err(FnoxError::AgeEncryptionFailed {
+                details: format!("Recipients file '{}' has no recipients", path.display()),
+            });
+        }
+    }
+    Ok(expanded)
 }
 
 #[derive(Clone, Default)]
@@ -143,17 +509,25 @@ impl crate::providers::Provider for AgeEncryptionProvider {
     }
 
     async fn encrypt(&self, plaintext: &str) -> Result<String> {
-        use std::io::Write;
+        if self.passphrase {
+            let passphrase =
+                self.resolve_passphrase(true)?
+                    .ok_or_else(|| FnoxError::AgeEncryptionFailed {
+                        details: NO_PASSPHRASE.to_string(),
+                    })?;
+            return encrypt_with_passphrase(plaintext, passphrase);
+        }
 
         if self.recipients.is_empty() {
             return Err(FnoxError::AgeNotConfigured);
         }
+        let recipients = expand_recipients(&self.recipients, self.config_dir.as_deref())?;
 
         // Parse recipients - try SSH, native age, then plugin formats
         let mut parsed_recipients: Vec<Box<dyn age::Recipient + Send + Sync>> = Vec::new();
         let mut plugin_recipients: Vec<age::plugin::Recipient> = Vec::new();
 
-        for recipient in &self.recipients {
+        for recipient in &recipients {
             // Try parsing as SSH recipient first
             if let Ok(ssh_recipient) = recipient.parse::<age::ssh::Recipient>() {
                 parsed_recipients.push(Box::new(ssh_recipient));
@@ -189,7 +563,7 @@ impl crate::providers::Provider for AgeEncryptionProvider {
                 &plugin_name,
                 &plugin_recipients,
                 &[],
-                age::cli_common::UiCallbacks,
+                PluginPrompts::new(),
             )
             .map_err(|e| FnoxError::AgeEncryptionFailed {
                 details: format!(
@@ -221,32 +595,7 @@ impl crate::providers::Provider for AgeEncryptionProvider {
             details: format!("Failed to initialize age encryptor: {}", e),
         })?;
 
-        // Encrypt the plaintext
-        let mut encrypted = vec![];
-        let mut writer =
-            encryptor
-                .wrap_output(&mut encrypted)
-                .map_err(|e| FnoxError::AgeEncryptionFailed {
-                    details: format!("Failed to create encrypted writer: {}", e),
-                })?;
-
-        writer
-            .write_all(plaintext.as_bytes())
-            .map_err(|e| FnoxError::AgeEncryptionFailed {
-                details: format!("Failed to write plaintext: {}", e),
-            })?;
-
-        writer
-            .finish()
-            .map_err(|e| FnoxError::AgeEncryptionFailed {
-                details: format!("Failed to finalize encryption: {}", e),
-            })?;
-
-        // Base64 encode the encrypted output
-        use base64::Engine;
-        let encrypted_base64 = base64::engine::general_purpose::STANDARD.encode(&encrypted);
-
-        Ok(encrypted_base64)
+        seal(encryptor, plaintext)
     }
 
     async fn get_secret(&self, value: &str) -> Result<String> {
@@ -262,109 +611,142 @@ impl crate::providers::Provider for AgeEncryptionProvider {
                 }
             };
 
+        if self.passphrase {
+            let passphrase =
+                self.resolve_passphrase(false)?
+                    .ok_or_else(|| FnoxError::AgeDecryptionFailed {
+                        details: NO_PASSPHRASE.to_string(),
+                    })?;
+            return decrypt_with_passphrase(&encrypted_bytes, passphrase);
+        }
+
         // Priority for identity:
         // 1. FNOX_AGE_KEY env var (inline key content)
         // 2. self.identity (from provider config, resolved from another provider)
-        // 3. self.key_file (from provider config)
+        // 3. self.key_file and self.key_files (from provider config)
         // 4. Settings age_key_file (from CLI flag - deprecated)
         // 5. Default path (~/.config/fnox/age.txt)
-        let (identity_content, key_file_path_opt) = if let Some(ref age_key) = *env::FNOX_AGE_KEY {
+        let identities = if let Some(ref age_key) = *env::FNOX_AGE_KEY {
             // Use the key directly from the environment variable
-            (age_key.clone(), None)
+            parse_identities(age_key, None)?
         } else if let Some(identity) = self.resolve_provider_identity().await? {
-            (identity, None)
+            parse_identities(&identity, None)?
         } else {
-            // Determine which key file to use
-            let key_file_path = if let Some(ref config_key_file) = self.key_file {
-                // Use key file from provider config
-                config_key_file.clone()
-            } else {
-                // Get settings which merges CLI flags, env vars, and defaults
-                let settings = crate::settings::Settings::get();
-
-                if let Some(ref age_key_file) = settings.age_key_file {
-                    // Use age key file from settings (CLI flag or env var - deprecated)
-                    age_key_file.clone()
-                } else {
-                    // Try default path
-                    let default_key_path = env::FNOX_CONFIG_DIR.join("age.txt");
-                    if !default_key_path.exists() {
-                        return Err(FnoxError::AgeIdentityNotFound {
-                            path: default_key_path,
-                        });
-                    }
-                    default_key_path
-                }
-            };
+            self.load_key_files()?
+        };
 
-            // Load identity file content
-            let content = std::fs::read_to_string(&key_file_path).map_err(|e| {
-                FnoxError::AgeIdentityReadFailed {
-                    path: key_file_path.clone(),
-                    source: e,
-                }
-            })?;
+        // age tries each identity against the header until one matches
+        open(
+            &encrypted_bytes,
+            identities.iter().map(|i| i.as_ref() as &dyn age::Identity),
+        )
+    }
+}
 
-            (content, Some(key_file_path))
-        };
+/// A recipient stanza from an age header, e.g. `-> X25519 <share>` or
+/// `-> ssh-ed25519 <tag> <share>`. The body is not kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stanza {
+    pub tag: String,
+    pub args: Vec<String>,
+}
 
-        // Try parsing as SSH identity first, then fall back to age identity file
-        let identities = {
-            let mut cursor = std::io::Cursor::new(identity_content.as_bytes());
-
-            // First try to parse as SSH identity
-            match age::ssh::Identity::from_buffer(
-                &mut cursor,
-                key_file_path_opt
-                    .as_ref()
-                    .map(|p| p.to_string_lossy().to_string()),
-            ) {
-                Ok(ssh_identity) => {
-                    // SSH identity parsed successfully
-                    vec![Box::new(ssh_identity) as Box<dyn age::Identity>]
-                }
-                Err(_) => {
-                    // Not an SSH identity, try age identity file. Setting
-                    // callbacks lets `into_identities` construct plugin
-                    // identities (e.g. AGE-PLUGIN-YUBIKEY-1...) by driving the
-                    // matching `age-plugin-*` binary for PIN/touch prompts.
-                    cursor.set_position(0);
-                    age::IdentityFile::from_buffer(cursor)
-                        .map_err(|e| FnoxError::AgeIdentityParseFailed {
-                            details: e.to_string(),
-                        })?
-                        .with_callbacks(age::cli_common::UiCallbacks)
-                        .into_identities()
-                        .map_err(|e| FnoxError::AgeIdentityParseFailed {
-                            details: e.to_string(),
-                        })?
-                }
-            }
+/// Whether a ciphertext is addressed to any of the configured recipients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientCoverage {
+    /// At least one stanza is for a configured recipient.
+    Current,
+    /// Every stanza names its recipient and none of them is configured.
+    Stale,
+    /// Some stanzas don't say who they're for, so it can't be decided
+    /// without the matching identity.
+    Unknown,
+}
+
+/// Parse the recipient stanzas from an age ciphertext as stored in config
+/// (base64-encoded or raw binary format). Grease stanzas are skipped.
+pub fn ciphertext_stanzas(value: &str) -> Result<Vec<Stanza>> {
+    let bytes = match base64::Engine::decode(&base64::engine::general_purpose::STANDARD, value) {
+        Ok(bytes) => bytes,
+        Err(_) => value.as_bytes().to_vec(),
+    };
+    let invalid = |details: &str| FnoxError::AgeDecryptionFailed {
+        details: format!("Invalid age header: {}", details),
+    };
+
+    let mut lines = bytes.split(|&b| b == b'\n');
+    if lines.next() != Some(b"age-encryption.org/v1".as_slice()) {
+        return Err(invalid("not an age v1 ciphertext"));
+    }
+
+    let mut stanzas = Vec::new();
+    let mut in_body = false;
+    for line in lines {
+        let line = std::str::from_utf8(line).map_err(|_| invalid("non-UTF-8 header line"))?;
+        if in_body {
+            // Bodies are wrapped at 64 columns; a shorter line ends the stanza
+            in_body = line.len() == 64;
+            continue;
+        }
+        if line.starts_with("---") {
+            return Ok(stanzas);
+        }
+        let Some(rest) = line.strip_prefix("-> ") else {
+            return Err(invalid("expected a recipient stanza"));
         };
+        let mut parts = rest.split(' ').map(str::to_string);
+        let tag = parts.next().unwrap_or_default();
+        if !tag.ends_with("-grease") {
+            stanzas.push(Stanza {
+                tag,
+                args: parts.collect(),
+            });
+        }
+        in_body = true;
+    }
+    Err(invalid("header is truncated"))
+}
 
-        let decryptor = age::Decryptor::new(encrypted_bytes.as_slice()).map_err(|e| {
-            FnoxError::AgeDecryptionFailed {
-                details: format!("Failed to create decryptor: {}", e),
+/// Check a ciphertext's stanzas against the configured recipients.
+///
+/// SSH stanzas carry a tag derived from the recipient's public key, so they
+/// can be matched exactly. X25519 and plugin stanzas are anonymous by design;
+/// a ciphertext made of those is [`RecipientCoverage::Unknown`].
+pub fn recipient_coverage(stanzas: &[Stanza], recipients: &[String]) -> RecipientCoverage {
+    let tags: Vec<String> = recipients.iter().filter_map(|r| ssh_tag(r)).collect();
+    let mut anonymous = false;
+    for stanza in stanzas {
+        match stanza.tag.as_str() {
+            "ssh-ed25519" | "ssh-rsa" => {
+                if stanza.args.first().is_some_and(|tag| tags.contains(tag)) {
+                    return RecipientCoverage::Current;
+                }
             }
-        })?;
-
-        let mut reader = decryptor
-            .decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))
-            .map_err(|e| FnoxError::AgeDecryptionFailed {
-                details: e.to_string(),
-            })?;
+            _ => anonymous = true,
+        }
+    }
+    if anonymous {
+        RecipientCoverage::Unknown
+    } else {
+        RecipientCoverage::Stale
+    }
+}
 
-        let mut decrypted = vec![];
-        reader
-            .read_to_end(&mut decrypted)
-            .map_err(|e| FnoxError::AgeDecryptionFailed {
-                details: format!("Failed to read decrypted data: {}", e),
-            })?;
+/// The stanza tag age uses for an SSH recipient: the first 4 bytes of the
+/// SHA-256 of the public key blob, base64-encoded without padding.
+fn ssh_tag(recipient: &str) -> Option<String> {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
 
-        String::from_utf8(decrypted).map_err(|e| FnoxError::AgeDecryptionFailed {
-            details: format!("Failed to decode UTF-8: {}", e),
-        })
+    let mut parts = recipient.split_whitespace();
+    if !matches!(parts.next(), Some("ssh-ed25519" | "ssh-rsa")) {
+        return None;
     }
+    let blob = base64::engine::general_purpose::STANDARD
+        .decode(parts.next()?)
+        .ok()?;
+    let digest = Sha256::digest(&blob);
+    Some(base64::engine::general_purpose::STANDARD_NO_PAD.encode(&digest[..4]))
 }
 
 #[cfg(test)]
@@ -382,9 +764,14 @@ mod tests {
     async fn plugin_recipient_is_not_rejected_as_invalid_hrp() {
         let recipient =
             "age1yubikey1qwla8v7cu3mx6mp79asgrh5ad2h52flwln7c66ydcyy50lg5uh0gxh4kmaz".to_string();
-        let provider =
-            AgeEncryptionProvider::new(vec![recipient], None, OptionProviderSecretRef::none())
-                .expect("provider construction should succeed");
+        let provider = AgeEncryptionProvider::new(
+            vec![recipient],
+            None,
+            OptionProviderSecretRef::none(),
+            Vec::new(),
+            None,
+        )
+        .expect("provider construction should succeed");
 
         if let Err(err) = provider.encrypt("plaintext").await {
             let message = err.to_string();
@@ -398,4 +785,269 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn plugin_prompts_decline_input_when_fnox_cannot_prompt() {
+        use age::Callbacks;
+
+        let prompts = PluginPrompts { interactive: false };
+        assert!(
+            prompts
+                .request_passphrase("Enter PIN for YubiKey")
+                .is_none()
+        );
+        assert!(prompts.request_public_string("Serial number").is_none());
+        assert_eq!(
+            prompts.confirm("Use this YubiKey?", "Yes", Some("No")),
+            None
+        );
+    }
+
+    const SSH_KEY_1: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAICZbez9nwoZOyECA+bL3hl63hY/GeAQ4rQAs3m10czBU test1";
+    const SSH_KEY_2: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIFNadJa0QY4FW+Ir4247SMphg1UbJcazESttTg0GYMY6 test2";
+
+    fn x25519_recipient() -> String {
+        age::x25519::Identity::generate().to_public().to_string()
+    }
+
+    async fn encrypt_to(recipients: &[String]) -> String {
+        AgeEncryptionProvider::new(
+            recipients.to_vec(),
+            None,
+            OptionProviderSecretRef::none(),
+            Vec::new(),
+            None,
+        )
+        .unwrap()
+        .encrypt("plaintext")
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ciphertext_stanzas_lists_every_recipient() {
+        let ciphertext = encrypt_to(&[
+            x25519_recipient(),
+            x25519_recipient(),
+            SSH_KEY_1.to_string(),
+        ])
+        .await;
+        let stanzas = ciphertext_stanzas(&ciphertext).unwrap();
+        let tags: Vec<&str> = stanzas.iter().map(|s| s.tag.as_str()).collect();
+        assert_eq!(tags, ["X25519", "X25519", "ssh-ed25519"]);
+    }
+
+    #[tokio::test]
+    async fn ssh_stanzas_match_configured_recipients() {
+        let ciphertext = encrypt_to(&[SSH_KEY_1.to_string()]).await;
+        let stanzas = ciphertext_stanzas(&ciphertext).unwrap();
+
+        assert_eq!(
+            recipient_coverage(&stanzas, &[SSH_KEY_2.to_string(), SSH_KEY_1.to_string()]),
+            RecipientCoverage::Current
+        );
+        // The key it was encrypted to has been removed from the config
+        assert_eq!(
+            recipient_coverage(&stanzas, &[SSH_KEY_2.to_string()]),
+            RecipientCoverage::Stale
+        );
+    }
+
+    #[tokio::test]
+    async fn x25519_stanzas_are_unknown() {
+        let old = x25519_recipient();
+        let ciphertext = encrypt_to(std::slice::from_ref(&old)).await;
+        let stanzas = ciphertext_stanzas(&ciphertext).unwrap();
+        assert_eq!(
+            recipient_coverage(&stanzas, &[old]),
+            RecipientCoverage::Unknown
+        );
+
+        // A matching SSH stanza still proves the ciphertext is current
+        let ciphertext = encrypt_to(&[x25519_recipient(), SSH_KEY_1.to_string()]).await;
+        let stanzas = ciphertext_stanzas(&ciphertext).unwrap();
+        assert_eq!(
+            recipient_coverage(&stanzas, &[SSH_KEY_1.to_string()]),
+            RecipientCoverage::Current
+        );
+    }
+
+    /// A provider decrypting with `key_file` and `key_files`
+    fn decrypting_with(key_file: Option<&Path>, key_files: &[&Path]) -> AgeEncryptionProvider {
+        AgeEncryptionProvider::new(
+            Vec::new(),
+            key_file.map(|p| p.to_string_lossy().to_string()),
+            OptionProviderSecretRef::none(),
+            key_files
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn encrypts_to_recipients_files_and_decrypts_with_each_identity() {
+        use age::secrecy::ExposeSecret;
+
+        let dir = tempfile::tempdir().unwrap();
+        let identities: Vec<_> = (0..3).map(|_| age::x25519::Identity::generate()).collect();
+        let key_files: Vec<PathBuf> = identities
+            .iter()
+            .enumerate()
+            .map(|(i, identity)| {
+                let path = dir.path().join(format!("key{i}.txt"));
+                std::fs::write(&path, identity.to_string().expose_secret()).unwrap();
+                path
+            })
+            .collect();
+        let recipients_file = dir.path().join("recipients.txt");
+        std::fs::write(
+            &recipients_file,
+            format!(
+                "# the team\n{}\n\n{}\n",
+                identities[0].to_public(),
+                identities[1].to_public()
+            ),
+        )
+        .unwrap();
+
+        let ciphertext = encrypt_to(&[
+            recipients_file.to_string_lossy().to_string(),
+            identities[2].to_public().to_string(),
+        ])
+        .await;
+        assert_eq!(ciphertext_stanzas(&ciphertext).unwrap().len(), 3);
+
+        for key_file in &key_files {
+            let provider = decrypting_with(Some(key_file), &[]);
+            assert_eq!(provider.get_secret(&ciphertext).await.unwrap(), "plaintext");
+        }
+
+        // Only the last of several key files can decrypt it
+        let other = dir.path().join("other.txt");
+        std::fs::write(
+            &other,
+            age::x25519::Identity::generate()
+                .to_string()
+                .expose_secret(),
+        )
+        .unwrap();
+        let provider =
+            decrypting_with(Some(&other), &[&dir.path().join("gone.txt"), &key_files[2]]);
+        assert_eq!(provider.get_secret(&ciphertext).await.unwrap(), "plaintext");
+
+        let err = decrypting_with(Some(&other), &[])
+            .get_secret(&ciphertext)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, FnoxError::AgeDecryptionFailed { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn expand_recipients_reads_recipients_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let recipient = x25519_recipient();
+        let file = dir.path().join("recipients.txt");
+        std::fs::write(&file, format!("{recipient}\n{SSH_KEY_1}\n")).unwrap();
+
+        let expanded = expand_recipients(
+            &[SSH_KEY_2.to_string(), file.to_string_lossy().to_string()],
+            None,
+        )
+        .unwrap();
+        assert_eq!(expanded, [SSH_KEY_2, &recipient, SSH_KEY_1]);
+
+        // Relative paths are read from the config file's directory
+        let expanded =
+            expand_recipients(&["recipients.txt".to_string()], Some(dir.path())).unwrap();
+        assert_eq!(expanded, [&recipient, SSH_KEY_1]);
+
+        std::fs::write(&file, "# nobody yet\n").unwrap();
+        let err = expand_recipients(&[file.to_string_lossy().to_string()], None).unwrap_err();
+        assert!(err.to_string().contains("has no recipients"), "{err}");
+        assert!(expand_recipients(&["missing.txt".to_string()], None).is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_key_files_fail_when_none_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        let ciphertext = encrypt_to(&[x25519_recipient()]).await;
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+
+        let err = decrypting_with(Some(&a), &[&b])
+            .get_secret(&ciphertext)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, FnoxError::AgeIdentityNotFound { .. }),
+            "{err}"
+        );
+        // A single configured key file must exist
+        let err = decrypting_with(Some(&a), &[])
+            .get_secret(&ciphertext)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, FnoxError::AgeIdentityReadFailed { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn passphrase_round_trips_and_rejects_a_wrong_one() {
+        let passphrase = || SecretString::from("correct horse".to_string());
+        let ciphertext = encrypt_with_passphrase("plaintext", passphrase()).unwrap();
+        let stanzas = ciphertext_stanzas(&ciphertext).unwrap();
+        assert_eq!(stanzas.len(), 1);
+        assert_eq!(stanzas[0].tag, "scrypt");
+
+        let encrypted =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &ciphertext)
+                .unwrap();
+        assert_eq!(
+            decrypt_with_passphrase(&encrypted, passphrase()).unwrap(),
+            "plaintext"
+        );
+
+        let err =
+            decrypt_with_passphrase(&encrypted, SecretString::from("battery staple".to_string()))
+                .unwrap_err();
+        assert!(
+            matches!(err, FnoxError::AgeDecryptionFailed { .. }),
+            "{err}"
+        );
+        assert!(err.to_string().contains("wrong passphrase"), "{err}");
+    }
+
+    #[test]
+    fn passphrase_mode_excludes_recipients() {
+        let provider = |passphrase, recipients: Vec<String>| {
+            AgeEncryptionProvider::new(
+                recipients,
+                None,
+                OptionProviderSecretRef::none(),
+                Vec::new(),
+                passphrase,
+            )
+        };
+        assert!(provider(Some(true), Vec::new()).is_ok());
+        assert!(provider(Some(false), vec![x25519_recipient()]).is_ok());
+        let err = provider(Some(true), vec![x25519_recipient()])
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("both"), "{err}");
+    }
+
+    #[test]
+    fn ciphertext_stanzas_rejects_other_data() {
+        assert!(ciphertext_stanzas("not a ciphertext").is_err());
+        assert!(ciphertext_stanzas("age-encryption.org/v1\n-> X25519 abc\n").is_err());
+    }
 }
@@ -0,0 +1,356 @@
+//! Credentials shared by the AWS providers (`aws-sm`, `aws-ps`, `aws-kms`):
+//! an optional named profile, and an optional role to assume with it, with
+//! the external ID the role's trust policy may require.
+
+use crate::error::{FnoxError, Result};
+use aws_config::{BehaviorVersion, ConfigLoader, SdkConfig};
+use aws_sdk_sts::config::Credentials;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Name of the STS session fnox opens when assuming a role
+const SESSION_NAME: &str = "fnox";
+
+/// Assumed credentials are reused until this close to their expiry
+const EXPIRY_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Assumed-role credentials, kept for the process lifetime so that every
+/// provider instance and every secret in a batch shares one `AssumeRole` call
+static ASSUMED_CREDENTIALS: LazyLock<Mutex<HashMap<AssumeKey, Credentials>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// (region, profile, role ARN, external ID)
+type AssumeKey = (String, Option<String>, String, Option<String>);
+
+#[derive(Debug, Clone)]
+pub struct AwsAuth {
+    provider: &'static str,
+    url: &'static str,
+    region: String,
+    profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+}
+
+impl AwsAuth {
+    /// Validate the auth settings of an AWS provider. `provider` and `url`
+    /// are used in error messages.
+    pub fn new(
+        provider: &'static str,
+        url: &'static str,
+        region: &str,
+        profile: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
+    ) -> Result<Self> {
+        if external_id.is_some() && role_arn.is_none() {
+            return Err(FnoxError::ProviderAuthFailed {
+                provider: provider.to_string(),
+                details: "external_id is set without role_arn".to_string(),
+                hint: "external_id is passed when assuming role_arn; set role_arn too or remove external_id".to_string(),
+                url: url.to_string(),
+            });
+        }
+        if let Some(role_arn) = &role_arn {
+            check_role_arn(role_arn, region).map_err(|details| FnoxError::ProviderAuthFailed {
+                provider: provider.to_string(),
+                details,
+                hint: "Set role_arn to an IAM role ARN like 'arn:aws:iam::123456789012:role/name' in the region's partition".to_string(),
+                url: url.to_string(),
+            })?;
+        }
+        Ok(Self {
+            provider,
+            url,
+            region: region.to_string(),
+            profile,
+            role_arn,
+            external_id,
+        })
+    }
+
+    /// Config loader for the region and the named profile, if any
+    pub fn loader(&self) -> ConfigLoader {
+        let mut loader = aws_config::defaults(BehaviorVersion::latest())
+            .region(aws_config::Region::new(self.region.clone()));
+        if let Some(profile) = &self.profile {
+            loader = loader.profile_name(profile);
+        }
+        loader
+    }
+
+    /// Load the SDK config, plus the assumed role's credentials when
+    /// `role_arn` is set. Services should use those credentials instead of
+    /// the config's own. A provider's custom `endpoint` is for its service
+    /// only; the role is always assumed through the region's STS endpoint.
+    pub async fn load(&self) -> Result<(SdkConfig, Option<Credentials>)> {
+        let config = self.loader().load().await;
+        let Some(role_arn) = &self.role_arn else {
+            return Ok((config, None));
+        };
+
+        let key = (
+            self.region.clone(),
+            self.profile.clone(),
+            role_arn.clone(),
+            self.external_id.clone(),
+        );
+        let cached = ASSUMED_CREDENTIALS
+            .lock()
+            .expect("assumed credentials lock poisoned")
+            .get(&key)
+            .filter(|credentials| fresh(credentials))
+            .cloned();
+        if let Some(credentials) = cached {
+            tracing::debug!("Reusing assumed credentials for {}", role_arn);
+            return Ok((config, Some(credentials)));
+        }
+
+        let credentials = self.assume_role(&config, role_arn).await?;
+        ASSUMED_CREDENTIALS
+            .lock()
+            .expect("assumed credentials lock poisoned")
+            .insert(key, credentials.clone());
+        Ok((config, Some(credentials)))
+    }
+
+    async fn assume_role(&self, config: &SdkConfig, role_arn: &str) -> Result<Credentials> {
+        tracing::debug!("Assuming role {} for {}", role_arn, self.provider);
+
+        let client = aws_sdk_sts::Client::new(config);
+
+        let result = client
+            .assume_role()
+            .role_arn(role_arn)
+            .role_session_name(SESSION_NAME)
+            .set_external_id(self.external_id.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                let details = aws_sdk_sts::error::DisplayErrorContext(&e).to_string();
+                if details.contains("AccessDenied") || details.contains("not authorized") {
+                    FnoxError::ProviderAuthFailed {
+                        provider: self.provider.to_string(),
+                        details,
+                        hint: format!("Check IAM permissions for sts:AssumeRole on '{}'", role_arn),
+                        url: self.url.to_string(),
+                    }
+                } else {
+                    FnoxError::ProviderAuthFailed {
+                        provider: self.provider.to_string(),
+                        details,
+                        hint:
+                            "Run 'aws sso login' or check the credentials used to assume the role"
+                                .to_string(),
+                        url: self.url.to_string(),
+                    }
+                }
+            })?;
+
+        let credentials =
+            result
+                .credentials()
+                .ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                    provider: self.provider.to_string(),
+                    details: "AssumeRole response missing credentials".to_string(),
+                    hint: "Unexpected AWS STS response".to_string(),
+                    url: self.url.to_string(),
+                })?;
+        Ok(Credentials::new(
+            credentials.access_key_id(),
+            credentials.secret_access_key(),
+            Some(credentials.session_token().to_string()),
+            SystemTime::try_from(*credentials.expiration()).ok(),
+            "fnox-assume-role",
+        ))
+    }
+}
+
+/// Whether cached credentials are far enough from expiry to reuse
+fn fresh(credentials: &Credentials) -> bool {
+    credentials
+        .expiry()
+        .is_none_or(|expiry| expiry > SystemTime::now() + EXPIRY_MARGIN)
+}
+
+/// Check that `role_arn` is an IAM role ARN in the partition `region` is in
+fn check_role_arn(role_arn: &str, region: &str) -> std::result::Result<(), String> {
+    let parts: Vec<&str> = role_arn.splitn(6, ':').collect();
+    let ["arn", partition, "iam", "", account, resource] = parts.as_slice() else {
+        return Err(format!("'{}' is not an IAM ARN", role_arn));
+    };
+    if account.len() != 12 || !account.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!(
+            "'{}' does not have a 12-digit account ID",
+            role_arn
+        ));
+    }
+    if !resource.starts_with("role/") {
+        return Err(format!("'{}' is not a role ARN", role_arn));
+    }
+    let expected = region_partition(region);
+    if *partition != expected {
+        return Err(format!(
+            "role_arn is in the '{}' partition but region '{}' is in '{}'",
+            partition, region, expected
+        ));
+    }
+    Ok(())
+}
+
+/// AWS partition a region belongs to
+fn region_partition(region: &str) -> &'static str {
+    if region.starts_with("cn-") {
+        "aws-cn"
+    } else if region.starts_with("us-gov-") {
+        "aws-us-gov"
+    } else {
+        "aws"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_runtime::env_config::file::{EnvConfigFileKind, EnvConfigFiles};
+    use aws_sdk_sts::config::ProvideCredentials;
+
+    fn auth(region: &str, profile: Option<&str>, role_arn: Option<&str>) -> Result<AwsAuth> {
+        AwsAuth::new(
+            "AWS Test",
+            "https://fnox.jdx.dev",
+            region,
+            profile.map(String::from),
+            role_arn.map(String::from),
+            None,
+        )
+    }
+
+    #[test]
+    fn accepts_role_arns_in_the_regions_partition() {
+        assert!(
+            auth(
+                "us-east-1",
+                None,
+                Some("arn:aws:iam::123456789012:role/deploy")
+            )
+            .is_ok()
+        );
+        assert!(
+            auth(
+                "cn-north-1",
+                None,
+                Some("arn:aws-cn:iam::123456789012:role/a/b")
+            )
+            .is_ok()
+        );
+        assert!(
+            auth(
+                "us-gov-west-1",
+                Some("gov"),
+                Some("arn:aws-us-gov:iam::123456789012:role/deploy")
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_role_arns() {
+        for role_arn in [
+            "deploy",
+            "arn:aws:iam::123456789012:user/alice",
+            "arn:aws:iam::1234:role/deploy",
+            "arn:aws:sts::123456789012:assumed-role/deploy/fnox",
+        ] {
+            let err = auth("us-east-1", None, Some(role_arn)).unwrap_err();
+            assert!(
+                matches!(err, FnoxError::ProviderAuthFailed { .. }),
+                "{role_arn}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_role_arns_outside_the_regions_partition() {
+        let err = auth(
+            "cn-north-1",
+            Some("china"),
+            Some("arn:aws:iam::123456789012:role/deploy"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("partition"), "{err}");
+    }
+
+    #[test]
+    fn external_id_needs_a_role() {
+        let with_role = AwsAuth::new(
+            "AWS Test",
+            "https://fnox.jdx.dev",
+            "us-east-1",
+            None,
+            Some("arn:aws:iam::123456789012:role/vendor".to_string()),
+            Some("tenant-42".to_string()),
+        )
+        .unwrap();
+        assert_eq!(with_role.external_id.as_deref(), Some("tenant-42"));
+
+        let err = AwsAuth::new(
+            "AWS Test",
+            "https://fnox.jdx.dev",
+            "us-east-1",
+            None,
+            None,
+            Some("tenant-42".to_string()),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("without role_arn"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn loads_credentials_from_the_named_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("config");
+        std::fs::write(
+            &config_file,
+            "[default]\n\
+             aws_access_key_id = AKIDDEFAULT\n\
+             aws_secret_access_key = default-secret\n\
+             [profile staging]\n\
+             aws_access_key_id = AKIDSTAGING\n\
+             aws_secret_access_key = staging-secret\n",
+        )
+        .unwrap();
+        let files = EnvConfigFiles::builder()
+            .with_file(EnvConfigFileKind::Config, &config_file)
+            .build();
+
+        let config = auth("eu-west-1", Some("staging"), None)
+            .unwrap()
+            .loader()
+            .profile_files(files)
+            .load()
+            .await;
+
+        assert_eq!(config.region().unwrap().as_ref(), "eu-west-1");
+        let credentials = config
+            .credentials_provider()
+            .unwrap()
+            .provide_credentials()
+            .await
+            .unwrap();
+        assert_eq!(credentials.access_key_id(), "AKIDSTAGING");
+    }
+
+    #[test]
+    fn reuses_credentials_until_close_to_expiry() {
+        let credentials = |expiry| Credentials::new("AKID", "secret", None, expiry, "test");
+        assert!(fresh(&credentials(None)));
+        assert!(fresh(&credentials(Some(
+            SystemTime::now() + Duration::from_secs(3600)
+        ))));
+        assert!(!fresh(&credentials(Some(
+            SystemTime::now() + Duration::from_secs(60)
+        ))));
+    }
+}
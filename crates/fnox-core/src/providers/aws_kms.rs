@@ -1,8 +1,9 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::aws_auth::AwsAuth;
 use async_trait::async_trait;
-use aws_config::BehaviorVersion;
 use aws_sdk_kms::Client;
 use aws_sdk_kms::primitives::Blob;
+use indexmap::IndexMap;
 
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
@@ -118,30 +119,73 @@ where
     }
 }
 
+/// The error for a ciphertext KMS can't decrypt with the configured
+/// encryption context
+fn context_mismatch_error(encryption_context: &IndexMap<String, String>) -> FnoxError {
+    let context = encryption_context
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join(", ");
+    FnoxError::ProviderInvalidResponse {
+        provider: "AWS KMS".to_string(),
+        details: format!(
+            "The encryption context in config ({}) doesn't match the one the value \
+             was encrypted with",
+            context
+        ),
+        hint: "Check encryption_context in the provider config against the context used \
+               to encrypt, or re-encrypt the value"
+            .to_string(),
+        url: URL.to_string(),
+    }
+}
+
 pub struct AwsKmsProvider {
     key_id: String,
-    region: String,
+    auth: AwsAuth,
     endpoint: Option<String>,
+    /// Only sent when configured, so ciphertexts without one keep decrypting
+    encryption_context: IndexMap<String, String>,
 }
 
 impl AwsKmsProvider {
-    pub fn new(key_id: String, region: String, endpoint: Option<String>) -> Result<Self> {
+    pub fn new(
+        key_id: String,
+        region: String,
+        endpoint: Option<String>,
+        profile: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
+        encryption_context: IndexMap<String, String>,
+    ) -> Result<Self> {
+        let auth = AwsAuth::new("AWS KMS", URL, &region, profile, role_arn, external_id)?;
         Ok(Self {
             key_id,
-            region,
+            auth,
             endpoint,
+            encryption_context,
+        })
+    }
+
+    /// The encryption context to send, if one is configured
+    fn context(&self) -> Option<std::collections::HashMap<String, String>> {
+        (!self.encryption_context.is_empty()).then(|| {
+            self.encryption_context
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
         })
     }
 
     /// Create an AWS KMS client
     async fn create_client(&self) -> Result<Client> {
-        // Load AWS config with the specified region
-        let config = aws_config::defaults(BehaviorVersion::latest())
-            .region(aws_sdk_kms::config::Region::new(self.region.clone()))
-            .load()
-            .await;
+        let (config, credentials) = self.auth.load().await?;
 
         let mut kms_config_builder = aws_sdk_kms::config::Builder::from(&config);
+        if let Some(credentials) = credentials {
+            kms_config_builder = kms_config_builder.credentials_provider(credentials);
+        }
         if let Some(endpoint) = &self.endpoint {
             kms_config_builder = kms_config_builder.endpoint_url(endpoint);
         }
@@ -169,9 +213,20 @@ impl AwsKmsProvider {
             .decrypt()
             .key_id(&self.key_id)
             .ciphertext_blob(Blob::new(ciphertext_bytes))
+            .set_encryption_context(self.context())
             .send()
             .await
-            .map_err(|e| aws_kms_error_to_fnox(&e, "Decrypt", &self.key_id))?;
+            .map_err(|e| {
+                // KMS rejects a mismatched context as an invalid ciphertext
+                if !self.encryption_context.is_empty()
+                    && e.as_service_error()
+                        .is_some_and(|e| e.is_invalid_ciphertext_exception())
+                {
+                    context_mismatch_error(&self.encryption_context)
+                } else {
+                    aws_kms_error_to_fnox(&e, "Decrypt", &self.key_id)
+                }
+            })?;
 
         let plaintext_blob =
             result
@@ -213,6 +268,7 @@ impl crate::providers::Provider for AwsKmsProvider {
             .encrypt()
             .key_id(&self.key_id)
             .plaintext(Blob::new(plaintext.as_bytes()))
+            .set_encryption_context(self.context())
             .send()
             .await
             .map_err(|e| aws_kms_error_to_fnox(&e, "Encrypt", &self.key_id))?;
@@ -248,12 +304,9 @@ impl crate::providers::Provider for AwsKmsProvider {
         Ok(())
     }
 
-    async fn get_secrets_batch(
-        &self,
-        secrets: &[(String, String)],
-    ) -> std::collections::HashMap<String, Result<String>> {
+    fn recommended_concurrency(&self) -> usize {
         // AWS KMS has a rate limit allowance of 10000+ TPS by default.
         // 10 -> 100 should generally not cause issues.
-        crate::providers::get_secrets_concurrent(self, secrets, 100).await
+        100
     }
 }
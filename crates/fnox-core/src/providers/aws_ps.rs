@@ -1,7 +1,11 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::aws_auth::AwsAuth;
+use crate::providers::path_reference;
+use crate::providers::prefix::KeyPrefix;
 use async_trait::async_trait;
-use aws_config::BehaviorVersion;
 use aws_sdk_ssm::Client;
+use aws_sdk_ssm::types::{ParameterStringFilter, ParameterTier};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
 pub fn env_dependencies() -> &'static [&'static str] {
@@ -117,11 +121,127 @@ where
     }
 }
 
+/// A parameter reference, optionally selecting a version or label
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParameterRef {
+    /// Parameter name or ARN, without the provider's prefix
+    name: String,
+    /// Version number or label, as in `my-param:3` or `my-param:stable`
+    selector: Option<String>,
+}
+
+/// Parse a parameter reference. Parameter names can't contain `:`, so one
+/// after the name (after the `parameter/...` part of an ARN) starts a
+/// selector, which GetParameter and GetParameters accept as `name:selector`.
+fn parse_parameter_ref(value: &str) -> Result<ParameterRef> {
+    let invalid = |details: &str| FnoxError::ProviderInvalidReference {
+        provider: "AWS Parameter Store".to_string(),
+        reference: value.to_string(),
+        details: details.to_string(),
+        syntax: "a parameter name or ARN, optionally followed by ':<version>' or ':<label>'"
+            .to_string(),
+        url: URL.to_string(),
+    };
+    if value.trim().is_empty() {
+        return Err(invalid("reference is empty"));
+    }
+
+    // arn:partition:ssm:region:account:parameter/name[:selector]
+    let name_start = if value.starts_with("arn:") {
+        value
+            .match_indices(':')
+            .nth(4)
+            .map(|(i, _)| i + 1)
+            .ok_or_else(|| invalid("incomplete ARN"))?
+    } else {
+        0
+    };
+    match value[name_start..].split_once(':') {
+        Some((_, "")) => Err(invalid("empty version")),
+        Some((name, selector)) => Ok(ParameterRef {
+            name: value[..name_start + name.len()].to_string(),
+            selector: Some(selector.to_string()),
+        }),
+        None => Ok(ParameterRef {
+            name: value.to_string(),
+            selector: None,
+        }),
+    }
+}
+
+/// The env var name a parameter loaded by path gets: its last path segment
+/// upper-cased, with `-` and `.` replaced by `_`
+fn env_name_for_parameter(name: &str) -> String {
+    name.rsplit('/')
+        .next()
+        .unwrap_or(name)
+        .to_uppercase()
+        .replace(['-', '.'], "_")
+}
+
+/// Name each parameter loaded by path after its last segment, failing when
+/// two parameters would get the same name
+fn parameters_by_env_name(
+    path: &str,
+    parameters: impl IntoIterator<Item = (String, String)>,
+) -> Result<IndexMap<String, String>> {
+    let mut by_env_name: IndexMap<String, (String, String)> = IndexMap::new();
+    for (name, value) in parameters {
+        let env_name = env_name_for_parameter(&name);
+        if let Some((other, _)) = by_env_name.get(&env_name) {
+            return Err(FnoxError::ProviderInvalidResponse {
+                provider: "AWS Parameter Store".to_string(),
+                details: format!(
+                    "Parameters '{}' and '{}' under '{}' both map to {}",
+                    other, name, path, env_name
+                ),
+                hint: "Rename one of the parameters or reference a narrower path".to_string(),
+                url: URL.to_string(),
+            });
+        }
+        by_env_name.insert(env_name, (name, value));
+    }
+    by_env_name.sort_keys();
+    Ok(by_env_name
+        .into_iter()
+        .map(|(env_name, (_, value))| (env_name, value))
+        .collect())
+}
+
+/// The name a parameter in a GetParameters response was requested by. The
+/// response names it without its selector, which comes back separately as
+/// `:<version>` or `:<label>`.
+fn requested_name(name: &str, selector: Option<&str>) -> String {
+    match selector {
+        Some(selector) => format!("{}:{}", name, selector.trim_start_matches(':')),
+        None => name.to_string(),
+    }
+}
+
 pub struct AwsParameterStoreProvider {
     region: String,
-    profile: Option<String>,
-    prefix: Option<String>,
+    auth: AwsAuth,
+    prefix: KeyPrefix,
     endpoint: Option<String>,
+    tier: Option<ParameterTier>,
+}
+
+/// Largest standard parameter value
+const STANDARD_VALUE_SIZE: usize = 4 * 1024;
+/// Largest advanced parameter value
+const ADVANCED_VALUE_SIZE: usize = 8 * 1024;
+
+/// Parse the `tier` setting, spelled as in the AWS console
+fn parse_tier(tier: &str) -> Result<ParameterTier> {
+    match tier.to_ascii_lowercase().as_str() {
+        "standard" => Ok(ParameterTier::Standard),
+        "advanced" => Ok(ParameterTier::Advanced),
+        "intelligent-tiering" => Ok(ParameterTier::IntelligentTiering),
+        _ => Err(FnoxError::Config(format!(
+            "Unknown AWS Parameter Store tier '{}'; expected Standard, Advanced or Intelligent-Tiering",
+            tier
+        ))),
+    }
 }
 
 impl AwsParameterStoreProvider {
@@ -130,34 +250,50 @@ impl AwsParameterStoreProvider {
         profile: Option<String>,
         prefix: Option<String>,
         endpoint: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
+        tier: Option<String>,
     ) -> Result<Self> {
+        let tier = tier.as_deref().map(parse_tier).transpose()?;
+        let auth = AwsAuth::new(
+            "AWS Parameter Store",
+            URL,
+            &region,
+            profile,
+            role_arn,
+            external_id,
+        )?;
         Ok(Self {
             region,
-            profile,
-            prefix,
+            auth,
+            prefix: KeyPrefix::new(prefix),
             endpoint,
+            tier,
         })
     }
 
     pub fn get_parameter_name(&self, key: &str) -> String {
-        match &self.prefix {
-            Some(prefix) => format!("{}{}", prefix, key),
-            None => key.to_string(),
+        self.prefix.apply(key)
+    }
+
+    /// The name to request `parameter_ref` by: the prefixed name, with its
+    /// selector if any
+    fn request_name(&self, parameter_ref: &ParameterRef) -> String {
+        let name = self.get_parameter_name(&parameter_ref.name);
+        match &parameter_ref.selector {
+            Some(selector) => format!("{}:{}", name, selector),
+            None => name,
         }
     }
 
     /// Create an AWS SSM client
     async fn create_client(&self) -> Result<Client> {
-        let mut builder = aws_config::defaults(BehaviorVersion::latest())
-            .region(aws_sdk_ssm::config::Region::new(self.region.clone()));
-
-        if let Some(profile) = &self.profile {
-            builder = builder.profile_name(profile);
-        }
-
-        let config = builder.load().await;
+        let (config, credentials) = self.auth.load().await?;
 
         let mut ssm_config_builder = aws_sdk_ssm::config::Builder::from(&config);
+        if let Some(credentials) = credentials {
+            ssm_config_builder = ssm_config_builder.credentials_provider(credentials);
+        }
         if let Some(endpoint) = &self.endpoint {
             ssm_config_builder = ssm_config_builder.endpoint_url(endpoint);
         }
@@ -166,16 +302,38 @@ impl AwsParameterStoreProvider {
     }
 
     /// Get a parameter value from AWS Systems Manager Parameter Store
-    async fn get_parameter_value(&self, parameter_name: &str) -> Result<String> {
+    async fn get_parameter_value(&self, parameter_ref: &ParameterRef) -> Result<String> {
         let client = self.create_client().await?;
+        let parameter_name = self.request_name(parameter_ref);
 
         let result = client
             .get_parameter()
-            .name(parameter_name)
+            .name(&parameter_name)
             .with_decryption(true) // Automatically decrypt SecureString parameters
             .send()
             .await
-            .map_err(|e| aws_ps_error_to_fnox(&e, parameter_name))?;
+            .map_err(|e| {
+                let err = aws_ps_error_to_fnox(&e, &parameter_name);
+                // A missing version or label is reported as a missing
+                // parameter; keep AWS's message, which says which
+                match (&parameter_ref.selector, e.as_service_error()) {
+                    (Some(selector), Some(service_err))
+                        if matches!(err, FnoxError::ProviderSecretNotFound { .. }) =>
+                    {
+                        FnoxError::ProviderApiError {
+                            provider: "AWS Parameter Store".to_string(),
+                            details: service_err.to_string(),
+                            hint: format!(
+                                "Check that parameter '{}' has version or label '{}'",
+                                self.get_parameter_name(&parameter_ref.name),
+                                selector
+                            ),
+                            url: URL.to_string(),
+                        }
+                    }
+                    _ => err,
+                }
+            })?;
 
         // Get the parameter value
         result
@@ -190,6 +348,39 @@ impl AwsParameterStoreProvider {
             .map(|s| s.to_string())
     }
 
+    /// Every parameter under the path of a `path/*` reference, recursively
+    /// and decrypted, as a JSON object of env var name to value
+    async fn get_parameters_by_path(&self, client: &Client, path: &str) -> Result<String> {
+        let path = match self.get_parameter_name(path) {
+            path if path.is_empty() => "/".to_string(),
+            path => path,
+        };
+        tracing::debug!(
+            "Getting parameters under '{}' from AWS Parameter Store",
+            path
+        );
+
+        let mut parameters = Vec::new();
+        let mut pages = client
+            .get_parameters_by_path()
+            .path(&path)
+            .recursive(true)
+            .with_decryption(true)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| aws_ps_error_to_fnox(&e, &path))?;
+            for parameter in page.parameters() {
+                if let (Some(name), Some(value)) = (parameter.name(), parameter.value()) {
+                    parameters.push((name.to_string(), value.to_string()));
+                }
+            }
+        }
+
+        let by_env_name = parameters_by_env_name(&path, parameters)?;
+        Ok(serde_json::to_string(&by_env_name)?)
+    }
+
     /// Fetch a batch of parameters (up to 10) from AWS Parameter Store
     async fn fetch_batch(
         &self,
@@ -197,10 +388,17 @@ impl AwsParameterStoreProvider {
         chunk: &[(String, String)],
     ) -> HashMap<String, Result<String>> {
         // Build mapping from parameter name to original keys (multiple keys can share same param)
+        let mut results = HashMap::new();
         let mut param_name_to_keys: HashMap<String, Vec<String>> = HashMap::new();
         let mut param_names: Vec<String> = Vec::new();
         for (key, value) in chunk {
-            let param_name = self.get_parameter_name(value);
+            let param_name = match parse_parameter_ref(value) {
+                Ok(parameter_ref) => self.request_name(&parameter_ref),
+                Err(e) => {
+                    results.insert(key.clone(), Err(e));
+                    continue;
+                }
+            };
             param_name_to_keys
                 .entry(param_name.clone())
                 .or_default()
@@ -211,7 +409,9 @@ impl AwsParameterStoreProvider {
             }
         }
 
-        let mut results = HashMap::new();
+        if param_names.is_empty() {
+            return results;
+        }
         tracing::debug!(
             "Fetching batch of {} parameters from AWS Parameter Store",
             param_names.len()
@@ -229,7 +429,8 @@ impl AwsParameterStoreProvider {
                 // Process successfully retrieved parameters
                 for parameter in response.parameters() {
                     if let Some(name) = parameter.name()
-                        && let Some(keys) = param_name_to_keys.get(name)
+                        && let Some(keys) =
+                            param_name_to_keys.get(&requested_name(name, parameter.selector()))
                     {
                         // Insert result for all keys that reference this parameter
                         for key in keys {
@@ -307,6 +508,7 @@ impl AwsParameterStoreProvider {
             .name(parameter_name)
             .value(parameter_value)
             .r#type(aws_sdk_ssm::types::ParameterType::SecureString)
+            .set_tier(self.tier.clone())
             .overwrite(true) // Overwrite if exists
             .send()
             .await
@@ -327,14 +529,19 @@ impl crate::providers::Provider for AwsParameterStoreProvider {
     }
 
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let parameter_name = self.get_parameter_name(value);
+        if let Some(path) = path_reference(value) {
+            let client = self.create_client().await?;
+            return self.get_parameters_by_path(&client, path).await;
+        }
+
+        let parameter_ref = parse_parameter_ref(value)?;
         tracing::debug!(
             "Getting parameter '{}' from AWS Parameter Store in region '{}'",
-            parameter_name,
+            self.request_name(&parameter_ref),
             self.region
         );
 
-        self.get_parameter_value(&parameter_name).await
+        self.get_parameter_value(&parameter_ref).await
     }
 
     async fn get_secrets_batch(
@@ -372,19 +579,41 @@ impl crate::providers::Provider for AwsParameterStoreProvider {
             }
         };
 
-        // Process chunks concurrently (up to 10 concurrent batches)
-        let chunks: Vec<_> = secrets.chunks(BATCH_SIZE).map(|c| c.to_vec()).collect();
+        // Path references each take their own GetParametersByPath calls
+        let (by_path, by_name): (Vec<_>, Vec<_>) = secrets
+            .iter()
+            .cloned()
+            .partition(|(_, value)| path_reference(value).is_some());
+
+        // Process chunks concurrently, one batch_concurrency's worth at a time
+        let chunks: Vec<_> = by_name.chunks(BATCH_SIZE).map(|c| c.to_vec()).collect();
         let chunk_results: Vec<_> = stream::iter(chunks)
             .map(|chunk| {
                 let client = &client;
                 async move { self.fetch_batch(client, &chunk).await }
             })
-            .buffer_unordered(10)
+            .buffer_unordered(crate::providers::batch_concurrency(self))
+            .collect()
+            .await;
+
+        let path_results: Vec<_> = stream::iter(by_path)
+            .map(|(key, value)| {
+                let client = &client;
+                async move {
+                    let path = path_reference(&value).unwrap_or_default();
+                    (key, self.get_parameters_by_path(client, path).await)
+                }
+            })
+            .buffer_unordered(crate::providers::batch_concurrency(self))
             .collect()
             .await;
 
         // Merge all chunk results into a single HashMap
-        chunk_results.into_iter().flatten().collect()
+        chunk_results
+            .into_iter()
+            .flatten()
+            .chain(path_results)
+            .collect()
     }
 
     async fn test_connection(&self) -> Result<()> {
@@ -401,10 +630,227 @@ impl crate::providers::Provider for AwsParameterStoreProvider {
         Ok(())
     }
 
+    fn max_value_size(&self) -> Option<crate::providers::ValueSizeLimit> {
+        // Intelligent-Tiering makes a parameter advanced when its value needs it
+        let (bytes, hint) = match self.tier {
+            Some(ParameterTier::Advanced | ParameterTier::IntelligentTiering) => (
+                ADVANCED_VALUE_SIZE,
+                crate::providers::LARGE_VALUE_HINT.to_string(),
+            ),
+            _ => (
+                STANDARD_VALUE_SIZE,
+                "Set tier = \"Advanced\" on the provider to store values up to 8 KB (advanced parameters are billed per parameter)".to_string(),
+            ),
+        };
+        Some(crate::providers::ValueSizeLimit {
+            provider: "AWS Parameter Store",
+            bytes,
+            hint,
+            url: URL,
+        })
+    }
+
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        let name_prefix = self.get_parameter_name(prefix.unwrap_or_default());
+        tracing::debug!(
+            "Listing parameters in AWS Parameter Store starting with '{}'",
+            name_prefix
+        );
+
+        let client = self.create_client().await?;
+        let mut request = client.describe_parameters();
+        if !name_prefix.is_empty() {
+            let filter = ParameterStringFilter::builder()
+                .key("Name")
+                .option("BeginsWith")
+                .values(&name_prefix)
+                .build()
+                .map_err(|e| FnoxError::Provider(format!("Invalid parameter filter: {}", e)))?;
+            request = request.parameter_filters(filter);
+        }
+
+        let mut references = Vec::new();
+        let mut pages = request.into_paginator().send();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| aws_ps_error_to_fnox(&e, &name_prefix))?;
+            references.extend(
+                page.parameters()
+                    .iter()
+                    .filter_map(|parameter| parameter.name())
+                    .map(|name| self.prefix.strip(name).to_string()),
+            );
+        }
+        references.sort();
+        Ok(references)
+    }
+
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
         let parameter_name = self.get_parameter_name(key);
         self.put_parameter(&parameter_name, value).await?;
-        // Return the key name (without prefix) to store in config
+        // Return the key name to store in config, relative to the prefix
         Ok(key.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::check_value_size;
+
+    fn parse(value: &str) -> ParameterRef {
+        parse_parameter_ref(value).unwrap()
+    }
+
+    #[test]
+    fn references_select_versions_and_labels() {
+        assert_eq!(
+            parse("/myapp/db-url"),
+            ParameterRef {
+                name: "/myapp/db-url".to_string(),
+                selector: None,
+            }
+        );
+        assert_eq!(
+            parse("my-param:3"),
+            ParameterRef {
+                name: "my-param".to_string(),
+                selector: Some("3".to_string()),
+            }
+        );
+        assert_eq!(
+            parse("/myapp/db-url:stable").selector.as_deref(),
+            Some("stable")
+        );
+
+        let arn = "arn:aws:ssm:us-east-1:123456789012:parameter/myapp/db-url";
+        assert_eq!(parse(arn).name, arn);
+        assert_eq!(parse(arn).selector, None);
+        let pinned = parse(&format!("{arn}:2"));
+        assert_eq!(pinned.name, arn);
+        assert_eq!(pinned.selector.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn malformed_references_are_rejected() {
+        for (value, details) in [
+            ("", "reference is empty"),
+            ("my-param:", "empty version"),
+            ("arn:aws:ssm", "incomplete ARN"),
+        ] {
+            let err = parse_parameter_ref(value).unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    FnoxError::ProviderInvalidReference { details: d, .. } if d == details
+                ),
+                "{value}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn path_references_name_parameters_after_their_last_segment() {
+        assert_eq!(path_reference("/myapp/prod/*"), Some("/myapp/prod"));
+        assert_eq!(path_reference("/myapp/prod/db-url"), None);
+
+        let by_env_name = parameters_by_env_name(
+            "/myapp",
+            [
+                ("/myapp/db/url".to_string(), "postgres://".to_string()),
+                ("/myapp/api-key".to_string(), "k3y".to_string()),
+                ("/myapp/log.level".to_string(), "debug".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            by_env_name,
+            IndexMap::from([
+                ("API_KEY".to_string(), "k3y".to_string()),
+                ("LOG_LEVEL".to_string(), "debug".to_string()),
+                ("URL".to_string(), "postgres://".to_string()),
+            ])
+        );
+
+        let err = parameters_by_env_name(
+            "/myapp",
+            [
+                ("/myapp/a/token".to_string(), "1".to_string()),
+                ("/myapp/b/TOKEN".to_string(), "2".to_string()),
+            ],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("both map to TOKEN"), "{err}");
+    }
+
+    #[test]
+    fn requests_keep_the_selector_after_the_prefix() {
+        let provider = AwsParameterStoreProvider::new(
+            "us-east-1".to_string(),
+            None,
+            Some("/myapp/".to_string()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(provider.request_name(&parse("db-url:3")), "/myapp/db-url:3");
+        assert_eq!(provider.request_name(&parse("db-url")), "/myapp/db-url");
+
+        // GetParameters reports the selector apart from the name
+        assert_eq!(
+            requested_name("/myapp/db-url", Some(":3")),
+            "/myapp/db-url:3"
+        );
+        assert_eq!(requested_name("/myapp/db-url", None), "/myapp/db-url");
+    }
+
+    fn with_tier(tier: Option<&str>) -> AwsParameterStoreProvider {
+        AwsParameterStoreProvider::new(
+            "us-east-1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            tier.map(str::to_string),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn standard_parameters_hold_4_kib() {
+        let provider = with_tier(None);
+        assert!(check_value_size(&provider, "KEY", &"x".repeat(4096)).is_ok());
+        match check_value_size(&provider, "KEY", &"x".repeat(4097)).unwrap_err() {
+            FnoxError::ProviderValueTooLarge {
+                size, limit, hint, ..
+            } => {
+                assert_eq!((size, limit), (4097, 4096));
+                assert!(hint.contains("tier = \"Advanced\""), "{hint}");
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn advanced_parameters_hold_8_kib() {
+        for tier in ["Advanced", "intelligent-tiering"] {
+            let provider = with_tier(Some(tier));
+            assert!(check_value_size(&provider, "KEY", &"x".repeat(8192)).is_ok());
+            assert!(check_value_size(&provider, "KEY", &"x".repeat(8193)).is_err());
+        }
+        assert!(
+            AwsParameterStoreProvider::new(
+                "us-east-1".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("premium".to_string()),
+            )
+            .is_err()
+        );
+    }
+}
@@ -1,9 +1,19 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::aws_auth::AwsAuth;
+use crate::providers::prefix::KeyPrefix;
+use crate::providers::reference;
 use async_trait::async_trait;
-use aws_config::BehaviorVersion;
 use aws_sdk_secretsmanager::Client;
+use aws_sdk_secretsmanager::operation::batch_get_secret_value::BatchGetSecretValueOutput;
+use aws_sdk_secretsmanager::types::{Filter, FilterNameStringType};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
+const URL: &str = "https://fnox.jdx.dev/providers/aws-sm";
+
+/// Largest secret value Secrets Manager stores
+const MAX_VALUE_SIZE: usize = 64 * 1024;
+
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
 }
@@ -19,8 +29,6 @@ where
 {
     use aws_sdk_secretsmanager::error::SdkError;
 
-    const URL: &str = "https://fnox.jdx.dev/providers/aws-sm";
-
     match err {
         SdkError::ServiceError(service_err) => {
             let err_str = service_err.err().to_string();
@@ -145,10 +153,172 @@ fn extract_name_from_arn(arn_or_name: &str) -> String {
     arn_or_name.to_string()
 }
 
+/// A secret reference, optionally pinned to one version of the secret
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SecretRef {
+    /// Secret name or ARN, without the provider's prefix
+    name: String,
+    version: Option<SecretVersion>,
+}
+
+/// Version selected with `name#<version>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SecretVersion {
+    /// A version ID, as in `my-secret#EXAMPLE1-90ab-cdef-fedc-ba987EXAMPLE`
+    Id(String),
+    /// A staging label, as in `my-secret#AWSPREVIOUS`
+    Stage(String),
+}
+
+impl std::fmt::Display for SecretVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretVersion::Id(id) => write!(f, "version ID '{}'", id),
+            SecretVersion::Stage(stage) => write!(f, "staging label '{}'", stage),
+        }
+    }
+}
+
+/// Parse a secret reference. A version follows `#`, which secret names and
+/// ARNs can't contain: a version ID (a UUID) or any other staging label.
+fn parse_secret_ref(value: &str) -> Result<SecretRef> {
+    let reference = reference::AWS_SM.parse(value)?;
+    let name = reference.path_str();
+    let version = reference.version.map(|version| {
+        if is_version_id(&version) {
+            SecretVersion::Id(version)
+        } else {
+            SecretVersion::Stage(version)
+        }
+    });
+    Ok(SecretRef { name, version })
+}
+
+/// Whether `s` is shaped like a UUID, the form of version IDs AWS generates
+fn is_version_id(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len && group.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// (key, value) pairs of the secrets to read
+type SecretPairs = Vec<(String, String)>;
+
+/// Split `secrets` into those pinned to a version and the rest.
+/// BatchGetSecretValue always returns the current version, so pinned
+/// versions are fetched one at a time.
+fn partition_pinned(secrets: &[(String, String)]) -> (SecretPairs, SecretPairs) {
+    secrets.iter().cloned().partition(|(_, value)| {
+        parse_secret_ref(value).is_ok_and(|secret_ref| secret_ref.version.is_some())
+    })
+}
+
+/// BatchGetSecretValue takes at most 20 secret IDs per call
+const BATCH_SIZE: usize = 20;
+
+/// Record the outcome of one BatchGetSecretValue call for every key in
+/// `keys_by_id`: the secret's value, its entry in the response's `Errors`, or
+/// not found when the response doesn't mention it at all.
+fn apply_batch_response(
+    response: &BatchGetSecretValueOutput,
+    keys_by_id: &IndexMap<String, Vec<String>>,
+    results: &mut HashMap<String, Result<String>>,
+) {
+    // Responses may name a secret by its ARN, whose name has a random suffix
+    let requested = |id: &str| {
+        keys_by_id
+            .get_key_value(id)
+            .or_else(|| keys_by_id.get_key_value(&extract_name_from_arn(id)))
+    };
+
+    for secret in response.secret_values() {
+        let Some((secret_id, keys)) = secret.name().or(secret.arn()).and_then(requested) else {
+            tracing::warn!("Received a secret that was not requested in batch");
+            continue;
+        };
+        for key in keys {
+            let value = secret.secret_string().map(str::to_string).ok_or_else(|| {
+                FnoxError::ProviderInvalidResponse {
+                    provider: "AWS Secrets Manager".to_string(),
+                    details: format!("Secret '{}' has no string value", secret_id),
+                    hint: "Binary secrets are not supported".to_string(),
+                    url: URL.to_string(),
+                }
+            });
+            results.insert(key.clone(), value);
+        }
+    }
+
+    for error in response.errors() {
+        let Some((secret_id, keys)) = error.secret_id().and_then(requested) else {
+            continue;
+        };
+        for key in keys {
+            results.insert(
+                key.clone(),
+                Err(batch_error_to_fnox(
+                    secret_id,
+                    error.error_code(),
+                    error.message(),
+                )),
+            );
+        }
+    }
+
+    for (secret_id, keys) in keys_by_id {
+        for key in keys {
+            results.entry(key.clone()).or_insert_with(|| {
+                Err(FnoxError::ProviderSecretNotFound {
+                    provider: "AWS Secrets Manager".to_string(),
+                    secret: secret_id.clone(),
+                    hint: "Check that the secret exists".to_string(),
+                    url: URL.to_string(),
+                })
+            });
+        }
+    }
+}
+
+/// Convert a per-secret entry of BatchGetSecretValue's `Errors` the way
+/// [`aws_error_to_fnox`] converts the same failure of a single get
+fn batch_error_to_fnox(secret_id: &str, code: Option<&str>, message: Option<&str>) -> FnoxError {
+    let code = code.unwrap_or("UnknownError");
+    let details = format!(
+        "Failed to get '{}': {}: {}",
+        secret_id,
+        code,
+        message.unwrap_or("Unknown error")
+    );
+    match code {
+        "ResourceNotFoundException" => FnoxError::ProviderSecretNotFound {
+            provider: "AWS Secrets Manager".to_string(),
+            secret: secret_id.to_string(),
+            hint: "Check that the secret exists in AWS Secrets Manager".to_string(),
+            url: URL.to_string(),
+        },
+        code if code.contains("AccessDenied") || code.contains("UnauthorizedAccess") => {
+            FnoxError::ProviderAuthFailed {
+                provider: "AWS Secrets Manager".to_string(),
+                details,
+                hint: "Check IAM permissions for secretsmanager:GetSecretValue".to_string(),
+                url: URL.to_string(),
+            }
+        }
+        _ => FnoxError::ProviderApiError {
+            provider: "AWS Secrets Manager".to_string(),
+            details,
+            hint: "Check that the secret exists and you have access".to_string(),
+            url: URL.to_string(),
+        },
+    }
+}
+
 pub struct AwsSecretsManagerProvider {
     region: String,
-    profile: Option<String>,
-    prefix: Option<String>,
+    auth: AwsAuth,
+    prefix: KeyPrefix,
     endpoint: Option<String>,
 }
 
@@ -158,35 +328,73 @@ impl AwsSecretsManagerProvider {
         profile: Option<String>,
         prefix: Option<String>,
         endpoint: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
     ) -> Result<Self> {
+        let auth = AwsAuth::new(
+            "AWS Secrets Manager",
+            "https://fnox.jdx.dev/providers/aws-sm",
+            &region,
+            profile,
+            role_arn,
+            external_id,
+        )?;
         Ok(Self {
             region,
-            profile,
-            prefix,
+            auth,
+            prefix: KeyPrefix::new(prefix),
             endpoint,
         })
     }
 
     pub fn get_secret_name(&self, key: &str) -> String {
-        match &self.prefix {
-            Some(prefix) => format!("{}{}", prefix, key),
-            None => key.to_string(),
+        self.prefix.apply(key)
+    }
+
+    /// Group `secrets` into BatchGetSecretValue calls of at most
+    /// [`BATCH_SIZE`] distinct secret IDs, each mapped to the keys that want
+    /// it. Secret IDs get the prefix like single gets do. Keys whose
+    /// reference doesn't parse get their error in `results` instead.
+    fn plan_batches(
+        &self,
+        secrets: &[(String, String)],
+        results: &mut HashMap<String, Result<String>>,
+    ) -> Vec<IndexMap<String, Vec<String>>> {
+        let mut batches: Vec<IndexMap<String, Vec<String>>> = Vec::new();
+        // Batch index of every secret ID, so a secret shared by several keys
+        // is fetched once
+        let mut batch_of: HashMap<String, usize> = HashMap::new();
+        for (key, value) in secrets {
+            let secret_ref = match parse_secret_ref(value) {
+                Ok(secret_ref) => secret_ref,
+                Err(e) => {
+                    results.insert(key.clone(), Err(e));
+                    continue;
+                }
+            };
+            let secret_id = self.get_secret_name(&secret_ref.name);
+            let index = *batch_of.entry(secret_id.clone()).or_insert_with(|| {
+                if batches.last().is_none_or(|batch| batch.len() == BATCH_SIZE) {
+                    batches.push(IndexMap::new());
+                }
+                batches.len() - 1
+            });
+            batches[index]
+                .entry(secret_id)
+                .or_default()
+                .push(key.clone());
         }
+        batches
     }
 
     /// Create an AWS Secrets Manager client
     async fn create_client(&self) -> Result<Client> {
-        let mut builder = aws_config::defaults(BehaviorVersion::latest()).region(
-            aws_sdk_secretsmanager::config::Region::new(self.region.clone()),
-        );
-
-        if let Some(profile) = &self.profile {
-            builder = builder.profile_name(profile);
-        }
-
-        let config = builder.load().await;
+        let (config, credentials) = self.auth.load().await?;
 
         let mut sm_config_builder = aws_sdk_secretsmanager::config::Builder::from(&config);
+        if let Some(credentials) = credentials {
+            sm_config_builder = sm_config_builder.credentials_provider(credentials);
+        }
         if let Some(endpoint) = &self.endpoint {
             sm_config_builder = sm_config_builder.endpoint_url(endpoint);
         }
@@ -194,16 +402,46 @@ impl AwsSecretsManagerProvider {
         Ok(Client::from_conf(sm_config_builder.build()))
     }
 
-    /// Get a secret value from AWS Secrets Manager
-    async fn get_secret_value(&self, secret_name: &str) -> Result<String> {
-        let client = self.create_client().await?;
-
+    /// Get a secret value from AWS Secrets Manager, at `version` if given
+    async fn get_secret_value(
+        &self,
+        client: &Client,
+        secret_name: &str,
+        version: Option<&SecretVersion>,
+    ) -> Result<String> {
+        let (version_id, version_stage) = match version {
+            Some(SecretVersion::Id(id)) => (Some(id.clone()), None),
+            Some(SecretVersion::Stage(stage)) => (None, Some(stage.clone())),
+            None => (None, None),
+        };
         let result = client
             .get_secret_value()
             .secret_id(secret_name)
+            .set_version_id(version_id)
+            .set_version_stage(version_stage)
             .send()
             .await
-            .map_err(|e| aws_error_to_fnox(&e, secret_name))?;
+            .map_err(|e| {
+                let err = aws_error_to_fnox(&e, secret_name);
+                // A missing version is reported as a missing secret; keep
+                // AWS's message, which says which version it couldn't find
+                match (version, e.as_service_error()) {
+                    (Some(version), Some(service_err))
+                        if matches!(err, FnoxError::ProviderSecretNotFound { .. }) =>
+                    {
+                        FnoxError::ProviderApiError {
+                            provider: "AWS Secrets Manager".to_string(),
+                            details: service_err.to_string(),
+                            hint: format!(
+                                "Check that secret '{}' has a version with {}",
+                                secret_name, version
+                            ),
+                            url: URL.to_string(),
+                        }
+                    }
+                    _ => err,
+                }
+            })?;
 
         // Get the secret string (not binary)
         result
@@ -259,44 +497,63 @@ impl crate::providers::Provider for AwsSecretsManagerProvider {
         vec![crate::providers::ProviderCapability::RemoteStorage]
     }
 
+    fn max_value_size(&self) -> Option<crate::providers::ValueSizeLimit> {
+        Some(crate::providers::ValueSizeLimit {
+            provider: "AWS Secrets Manager",
+            bytes: MAX_VALUE_SIZE,
+            hint: crate::providers::LARGE_VALUE_HINT.to_string(),
+            url: URL,
+        })
+    }
+
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let secret_name = self.get_secret_name(value);
+        let secret_ref = parse_secret_ref(value)?;
+        let secret_name = self.get_secret_name(&secret_ref.name);
         tracing::debug!(
             "Getting secret '{}' from AWS Secrets Manager in region '{}'",
             secret_name,
             self.region
         );
 
-        self.get_secret_value(&secret_name).await
+        let client = self.create_client().await?;
+        self.get_secret_value(&client, &secret_name, secret_ref.version.as_ref())
+            .await
     }
 
     async fn get_secrets_batch(
         &self,
         secrets: &[(String, String)],
     ) -> HashMap<String, Result<String>> {
+        use futures::stream::{self, StreamExt};
+
         tracing::debug!(
             "Getting {} secrets from AWS Secrets Manager using batch API",
             secrets.len()
         );
 
+        let (pinned, current) = partition_pinned(secrets);
         let mut results = HashMap::new();
-
-        // AWS Secrets Manager BatchGetSecretValue supports up to 20 secrets per call
-        // So we need to chunk the requests
-        const BATCH_SIZE: usize = 20;
+        let batches = self.plan_batches(&current, &mut results);
+        if batches.is_empty() && pinned.is_empty() {
+            return results;
+        }
 
         let client = match self.create_client().await {
             Ok(c) => c,
             Err(e) => {
                 // If we can't create client, return errors for all secrets
-                for (key, _) in secrets {
+                let keys = batches
+                    .iter()
+                    .flat_map(|batch| batch.values().flatten())
+                    .chain(pinned.iter().map(|(key, _)| key));
+                for key in keys {
                     results.insert(
                         key.clone(),
                         Err(FnoxError::ProviderAuthFailed {
                             provider: "AWS Secrets Manager".to_string(),
                             details: e.to_string(),
                             hint: "Run 'aws sso login' or check AWS credentials".to_string(),
-                            url: "https://fnox.jdx.dev/providers/aws-sm".to_string(),
+                            url: URL.to_string(),
                         }),
                     );
                 }
@@ -304,134 +561,22 @@ impl crate::providers::Provider for AwsSecretsManagerProvider {
             }
         };
 
-        for chunk in secrets.chunks(BATCH_SIZE) {
-            // Build mapping from secret ID to original keys (multiple keys can share same secret)
-            let mut secret_id_to_keys: HashMap<String, Vec<String>> = HashMap::new();
-            let mut secret_ids: Vec<String> = Vec::new();
-            for (key, value) in chunk {
-                let secret_id = self.get_secret_name(value);
-                secret_id_to_keys
-                    .entry(secret_id.clone())
-                    .or_default()
-                    .push(key.clone());
-                // Only add unique secret IDs to the request
-                if !secret_ids.contains(&secret_id) {
-                    secret_ids.push(secret_id);
-                }
-            }
-
+        for keys_by_id in &batches {
             tracing::debug!(
                 "Fetching batch of {} secrets from AWS Secrets Manager",
-                secret_ids.len()
+                keys_by_id.len()
             );
 
-            // Call BatchGetSecretValue
             match client
                 .batch_get_secret_value()
-                .set_secret_id_list(Some(secret_ids.clone()))
+                .set_secret_id_list(Some(keys_by_id.keys().cloned().collect()))
                 .send()
                 .await
             {
-                Ok(response) => {
-                    // Process successfully retrieved secrets
-                    for secret in response.secret_values() {
-                        // Use name field for matching (not ARN, which has random suffix)
-                        let secret_name = if let Some(name) = secret.name() {
-                            name.to_string()
-                        } else if let Some(arn) = secret.arn() {
-                            // Fallback: extract name from ARN if name field is missing
-                            // ARN format: arn:aws:secretsmanager:region:account:secret:name-SUFFIX
-                            // We need to match against the name we requested (without suffix)
-                            extract_name_from_arn(arn)
-                        } else {
-                            tracing::warn!("Secret in batch response has no name or ARN");
-                            continue;
-                        };
-
-                        // Find all matching keys using exact name match
-                        if let Some(keys) = secret_id_to_keys.get(&secret_name) {
-                            for key in keys {
-                                if let Some(secret_string) = secret.secret_string() {
-                                    results.insert(key.clone(), Ok(secret_string.to_string()));
-                                } else {
-                                    results.insert(
-                                        key.clone(),
-                                        Err(FnoxError::ProviderInvalidResponse {
-                                            provider: "AWS Secrets Manager".to_string(),
-                                            details: format!(
-                                                "Secret '{}' has no string value",
-                                                secret_name
-                                            ),
-                                            hint: "Binary secrets are not supported".to_string(),
-                                            url: "https://fnox.jdx.dev/providers/aws-sm"
-                                                .to_string(),
-                                        }),
-                                    );
-                                }
-                            }
-                        } else {
-                            tracing::warn!(
-                                "Received secret '{}' that was not requested in batch",
-                                secret_name
-                            );
-                        }
-                    }
-
-                    // Handle errors for secrets that weren't retrieved
-                    for error in response.errors() {
-                        if let Some(error_secret_id) = error.secret_id() {
-                            // Try exact match first, then check if it's an ARN
-                            let lookup_name = if secret_id_to_keys.contains_key(error_secret_id) {
-                                error_secret_id.to_string()
-                            } else {
-                                // Might be an ARN in the error response
-                                extract_name_from_arn(error_secret_id)
-                            };
-
-                            if let Some(keys) = secret_id_to_keys.get(&lookup_name) {
-                                let error_msg =
-                                    error.message().unwrap_or("Unknown error").to_string();
-                                for key in keys {
-                                    results.insert(
-                                        key.clone(),
-                                        Err(FnoxError::ProviderApiError {
-                                            provider: "AWS Secrets Manager".to_string(),
-                                            details: format!(
-                                                "Failed to get '{}': {}",
-                                                lookup_name, error_msg
-                                            ),
-                                            hint:
-                                                "Check that the secret exists and you have access"
-                                                    .to_string(),
-                                            url: "https://fnox.jdx.dev/providers/aws-sm"
-                                                .to_string(),
-                                        }),
-                                    );
-                                }
-                            }
-                        }
-                    }
-
-                    // Check for any secrets that weren't in response (neither success nor error)
-                    for (secret_id, keys) in &secret_id_to_keys {
-                        for key in keys {
-                            if !results.contains_key(key) {
-                                results.insert(
-                                    key.clone(),
-                                    Err(FnoxError::ProviderSecretNotFound {
-                                        provider: "AWS Secrets Manager".to_string(),
-                                        secret: secret_id.clone(),
-                                        hint: "Check that the secret exists".to_string(),
-                                        url: "https://fnox.jdx.dev/providers/aws-sm".to_string(),
-                                    }),
-                                );
-                            }
-                        }
-                    }
-                }
+                Ok(response) => apply_batch_response(&response, keys_by_id, &mut results),
                 Err(e) => {
-                    // Batch call failed entirely, return errors for all secrets in this chunk
-                    for (secret_id, keys) in &secret_id_to_keys {
+                    // Batch call failed entirely, return errors for all secrets in this batch
+                    for (secret_id, keys) in keys_by_id {
                         for key in keys {
                             results.insert(key.clone(), Err(aws_error_to_fnox(&e, secret_id)));
                         }
@@ -440,6 +585,26 @@ impl crate::providers::Provider for AwsSecretsManagerProvider {
             }
         }
 
+        let pinned_results: Vec<_> = stream::iter(pinned)
+            .map(|(key, value)| {
+                let client = &client;
+                async move {
+                    let result = match parse_secret_ref(&value) {
+                        Ok(secret_ref) => {
+                            let secret_name = self.get_secret_name(&secret_ref.name);
+                            self.get_secret_value(client, &secret_name, secret_ref.version.as_ref())
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    (key, result)
+                }
+            })
+            .buffer_unordered(crate::providers::batch_concurrency(self))
+            .collect()
+            .await;
+        results.extend(pinned_results);
+
         results
     }
 
@@ -457,10 +622,331 @@ impl crate::providers::Provider for AwsSecretsManagerProvider {
         Ok(())
     }
 
+    /// List the secret's versions, and with `with_values` read each one.
+    /// Versions AWS has deprecated (those with no staging label) aren't
+    /// included.
+    async fn get_history(
+        &self,
+        value: &str,
+        with_values: bool,
+    ) -> Result<Vec<crate::providers::SecretVersion>> {
+        let secret_ref = parse_secret_ref(value)?;
+        let secret_name = self.get_secret_name(&secret_ref.name);
+        tracing::debug!(
+            "Listing versions of secret '{}' in AWS Secrets Manager",
+            secret_name
+        );
+
+        let client = self.create_client().await?;
+        let mut entries = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = client
+                .list_secret_version_ids()
+                .secret_id(&secret_name)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| aws_error_to_fnox(&e, &secret_name))?;
+            entries.extend(response.versions().iter().cloned());
+            next_token = response.next_token().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.created_date().map(|d| d.secs())));
+
+        let mut versions = Vec::new();
+        for entry in entries {
+            let Some(version_id) = entry.version_id() else {
+                continue;
+            };
+            let value = if with_values {
+                let version = SecretVersion::Id(version_id.to_string());
+                Some(
+                    self.get_secret_value(&client, &secret_name, Some(&version))
+                        .await?,
+                )
+            } else {
+                None
+            };
+            versions.push(crate::providers::SecretVersion {
+                id: version_id.to_string(),
+                created: entry.created_date().and_then(|date| {
+                    chrono::DateTime::from_timestamp(date.secs(), date.subsec_nanos())
+                }),
+                labels: entry.version_stages().to_vec(),
+                value,
+            });
+        }
+        Ok(versions)
+    }
+
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        let name_prefix = self.get_secret_name(prefix.unwrap_or_default());
+        tracing::debug!(
+            "Listing secrets in AWS Secrets Manager starting with '{}'",
+            name_prefix
+        );
+
+        let client = self.create_client().await?;
+        let mut references = Vec::new();
+        let mut next_token = None;
+        loop {
+            let mut request = client.list_secrets().set_next_token(next_token);
+            if !name_prefix.is_empty() {
+                request = request.filters(
+                    Filter::builder()
+                        .key(FilterNameStringType::Name)
+                        .values(&name_prefix)
+                        .build(),
+                );
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| aws_error_to_fnox(&e, &name_prefix))?;
+            // The name filter matches words anywhere in the name, so check
+            // the prefix again
+            references.extend(
+                response
+                    .secret_list()
+                    .iter()
+                    .filter_map(|entry| entry.name())
+                    .filter(|name| name.starts_with(&name_prefix))
+                    .map(|name| self.prefix.strip(name).to_string()),
+            );
+            next_token = response.next_token().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+        references.sort();
+        Ok(references)
+    }
+
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
         let secret_name = self.get_secret_name(key);
         self.put_secret(&secret_name, value).await?;
-        // Return the key name (without prefix) to store in config
+        // Return the key name to store in config, relative to the prefix
         Ok(key.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::check_value_size;
+    use aws_sdk_secretsmanager::types::{ApiErrorType, SecretValueEntry};
+
+    fn provider(prefix: Option<&str>) -> AwsSecretsManagerProvider {
+        AwsSecretsManagerProvider::new(
+            "us-east-1".to_string(),
+            None,
+            prefix.map(String::from),
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn secrets(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn batches_hold_at_most_20_distinct_prefixed_ids() {
+        let mut pairs: Vec<(String, String)> = (0..42)
+            .map(|i| (format!("KEY_{i}"), format!("secret-{i}")))
+            .collect();
+        // Keys sharing a secret don't take up another slot
+        pairs.push(("SHARED".to_string(), "secret-3".to_string()));
+        pairs.push(("LATE_SHARED".to_string(), "secret-41".to_string()));
+        pairs.push(("BROKEN".to_string(), " ".to_string()));
+
+        let mut results = HashMap::new();
+        let batches = provider(Some("myapp/")).plan_batches(&pairs, &mut results);
+
+        let sizes: Vec<usize> = batches.iter().map(IndexMap::len).collect();
+        assert_eq!(sizes, [20, 20, 2]);
+        assert_eq!(batches[0]["myapp/secret-3"], ["KEY_3", "SHARED"]);
+        assert_eq!(batches[2]["myapp/secret-41"], ["KEY_41", "LATE_SHARED"]);
+        // The same name a single get would use
+        assert_eq!(
+            provider(Some("myapp/")).get_secret_name("secret-0"),
+            "myapp/secret-0"
+        );
+        assert!(batches[0].contains_key("myapp/secret-0"));
+
+        assert_eq!(results.len(), 1);
+        assert!(results["BROKEN"].is_err());
+    }
+
+    #[test]
+    fn batch_responses_map_values_and_errors_back_to_keys() {
+        let mut results = HashMap::new();
+        let batches = provider(Some("app/")).plan_batches(
+            &secrets(&[
+                ("DB_URL", "db"),
+                ("DB_URL_COPY", "db"),
+                ("API_KEY", "api"),
+                ("GONE", "gone"),
+                ("LOCKED", "locked"),
+                ("BINARY", "binary"),
+                ("MISSING", "missing"),
+            ]),
+            &mut results,
+        );
+        let response = BatchGetSecretValueOutput::builder()
+            .secret_values(
+                SecretValueEntry::builder()
+                    .name("app/db")
+                    .secret_string("postgres://db")
+                    .build(),
+            )
+            // Matched by ARN when the name is missing
+            .secret_values(
+                SecretValueEntry::builder()
+                    .arn("arn:aws:secretsmanager:us-east-1:123456789012:secret:app/api-AbCdEf")
+                    .secret_string("k3y")
+                    .build(),
+            )
+            .secret_values(SecretValueEntry::builder().name("app/binary").build())
+            .errors(
+                ApiErrorType::builder()
+                    .secret_id("app/gone")
+                    .error_code("ResourceNotFoundException")
+                    .message("Secrets Manager can't find the specified secret.")
+                    .build(),
+            )
+            .errors(
+                ApiErrorType::builder()
+                    .secret_id("app/locked")
+                    .error_code("AccessDeniedException")
+                    .message("not authorized")
+                    .build(),
+            )
+            .build();
+
+        apply_batch_response(&response, &batches[0], &mut results);
+
+        assert_eq!(results["DB_URL"].as_ref().unwrap(), "postgres://db");
+        assert_eq!(results["DB_URL_COPY"].as_ref().unwrap(), "postgres://db");
+        assert_eq!(results["API_KEY"].as_ref().unwrap(), "k3y");
+        assert!(matches!(
+            results["GONE"],
+            Err(FnoxError::ProviderSecretNotFound { ref secret, .. }) if secret == "app/gone"
+        ));
+        assert!(matches!(
+            results["LOCKED"],
+            Err(FnoxError::ProviderAuthFailed { ref details, .. })
+                if details.contains("AccessDeniedException: not authorized")
+        ));
+        assert!(matches!(
+            results["BINARY"],
+            Err(FnoxError::ProviderInvalidResponse { .. })
+        ));
+        assert!(matches!(
+            results["MISSING"],
+            Err(FnoxError::ProviderSecretNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn references_pin_versions_by_id_or_staging_label() {
+        let parse = |value: &str| parse_secret_ref(value).unwrap();
+
+        assert_eq!(
+            parse("db#AWSPREVIOUS"),
+            SecretRef {
+                name: "db".to_string(),
+                version: Some(SecretVersion::Stage("AWSPREVIOUS".to_string())),
+            }
+        );
+        assert_eq!(
+            parse("myapp/db#a1b2c3d4-5678-90ab-cdef-EXAMPLE11111").version,
+            Some(SecretVersion::Id(
+                "a1b2c3d4-5678-90ab-cdef-EXAMPLE11111".to_string()
+            ))
+        );
+        // Custom labels needn't be upper case, and `@` stays in the name
+        assert_eq!(
+            parse("ops@team#blue"),
+            SecretRef {
+                name: "ops@team".to_string(),
+                version: Some(SecretVersion::Stage("blue".to_string())),
+            }
+        );
+        let arn = "arn:aws:secretsmanager:us-east-1:123456789012:secret:db-AbCdEf";
+        assert_eq!(parse(&format!("{arn}#AWSPENDING")).name, arn);
+
+        for name in ["user@example.com", "db@AWSPREVIOUS"] {
+            assert_eq!(
+                parse(name),
+                SecretRef {
+                    name: name.to_string(),
+                    version: None,
+                }
+            );
+        }
+        assert!(parse_secret_ref(" ").is_err());
+        assert!(parse_secret_ref("db#").is_err());
+    }
+
+    #[test]
+    fn pinned_versions_are_fetched_outside_batches() {
+        let (pinned, current) = partition_pinned(&secrets(&[
+            ("CURRENT", "db"),
+            ("PINNED", "db#AWSPREVIOUS"),
+            ("EMAIL", "user@example.com"),
+            ("BROKEN", " "),
+        ]));
+        assert_eq!(pinned, secrets(&[("PINNED", "db#AWSPREVIOUS")]));
+        // Broken references are left to plan_batches to report
+        assert_eq!(
+            current,
+            secrets(&[
+                ("CURRENT", "db"),
+                ("EMAIL", "user@example.com"),
+                ("BROKEN", " ")
+            ])
+        );
+    }
+
+    #[test]
+    fn other_batch_errors_are_api_errors() {
+        let err = batch_error_to_fnox(
+            "app/kms",
+            Some("DecryptionFailure"),
+            Some("KMS key disabled"),
+        );
+        assert!(matches!(err, FnoxError::ProviderApiError { .. }), "{err}");
+        assert!(
+            err.to_string()
+                .contains("DecryptionFailure: KMS key disabled"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn values_up_to_64_kib_fit() {
+        let provider = provider(None);
+        assert!(check_value_size(&provider, "KEY", &"x".repeat(65536)).is_ok());
+        let err = check_value_size(&provider, "KEY", &"x".repeat(65537)).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FnoxError::ProviderValueTooLarge {
+                    size: 65537,
+                    limit: 65536,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
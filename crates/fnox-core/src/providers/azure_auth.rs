@@ -0,0 +1,186 @@
+//! Credentials shared by the Azure providers (`azure-sm`, `azure-kms`), from
+//! their optional `auth` table: the developer tools chain (Azure CLI, Azure
+//! Developer CLI) by default, a managed identity, or a service principal
+//! with a client secret.
+
+use crate::error::{FnoxError, Result};
+use azure_core::credentials::{Secret, TokenCredential};
+use azure_identity::{
+    ClientSecretCredential, DeveloperToolsCredential, ManagedIdentityCredential,
+    ManagedIdentityCredentialOptions, UserAssignedId,
+};
+use indexmap::IndexMap;
+use std::sync::Arc;
+
+const METHODS: &str = "\"default\", \"managed_identity\" or \"client_secret\"";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AzureAuth {
+    /// Azure CLI, then Azure Developer CLI
+    Default,
+    /// The system-assigned managed identity, or the user-assigned one with
+    /// this client ID
+    ManagedIdentity { client_id: Option<String> },
+    /// A service principal's client secret
+    ClientSecret {
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+    },
+}
+
+impl AzureAuth {
+    /// Parse a provider's `auth` table. An empty table is the default chain.
+    pub fn from_config(auth: &IndexMap<String, String>) -> Result<Self> {
+        if auth.is_empty() {
+            return Ok(Self::Default);
+        }
+        let field = |name: &str| auth.get(name).filter(|v| !v.is_empty()).cloned();
+        let required = |method: &str, name: &str| {
+            field(name).ok_or_else(|| {
+                FnoxError::Config(format!(
+                    "Azure auth method '{}' requires '{}'",
+                    method, name
+                ))
+            })
+        };
+
+        let method = field("method").ok_or_else(|| {
+            FnoxError::Config(format!("Azure auth requires 'method' ({})", METHODS))
+        })?;
+        let (parsed, known): (Self, &[&str]) = match method.as_str() {
+            "default" => (Self::Default, &["method"]),
+            "managed_identity" => (
+                Self::ManagedIdentity {
+                    client_id: field("client_id"),
+                },
+                &["method", "client_id"],
+            ),
+            "client_secret" => (
+                Self::ClientSecret {
+                    tenant_id: required("client_secret", "tenant_id")?,
+                    client_id: required("client_secret", "client_id")?,
+                    client_secret: required("client_secret", "client_secret")?,
+                },
+                &["method", "tenant_id", "client_id", "client_secret"],
+            ),
+            other => {
+                return Err(FnoxError::Config(format!(
+                    "Unknown Azure auth method '{}', expected {}",
+                    other, METHODS
+                )));
+            }
+        };
+        if let Some(key) = auth.keys().find(|key| !known.contains(&key.as_str())) {
+            return Err(FnoxError::Config(format!(
+                "Unknown key '{}' for Azure auth method '{}'",
+                key, method
+            )));
+        }
+        Ok(parsed)
+    }
+
+    /// The credential to authenticate requests with. `url` is the provider's
+    /// documentation page, for errors.
+    pub fn credential(&self, url: &str) -> Result<Arc<dyn TokenCredential>> {
+        let failed = |hint: &str| {
+            let hint = hint.to_string();
+            move |e: azure_core::Error| FnoxError::ProviderAuthFailed {
+                provider: "Azure Key Vault".to_string(),
+                details: e.to_string(),
+                hint,
+                url: url.to_string(),
+            }
+        };
+
+        let credential: Arc<dyn TokenCredential> = match self {
+            Self::Default => DeveloperToolsCredential::new(None)
+                .map_err(failed("Run 'az login' to authenticate with Azure"))?,
+            Self::ManagedIdentity { client_id } => {
+                let options = ManagedIdentityCredentialOptions {
+                    user_assigned_id: client_id.clone().map(UserAssignedId::ClientId),
+                    ..Default::default()
+                };
+                ManagedIdentityCredential::new(Some(options)).map_err(failed(
+                    "Managed identities only work on Azure hosts the identity is assigned to",
+                ))?
+            }
+            Self::ClientSecret {
+                tenant_id,
+                client_id,
+                client_secret,
+            } => ClientSecretCredential::new(
+                tenant_id,
+                client_id.clone(),
+                Secret::new(client_secret.clone()),
+                None,
+            )
+            .map_err(failed(
+                "Check the service principal's tenant_id, client_id and client_secret",
+            ))?,
+        };
+        Ok(credential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(pairs: &[(&str, &str)]) -> Result<AzureAuth> {
+        AzureAuth::from_config(
+            &pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parses_each_method() {
+        assert_eq!(parse(&[]).unwrap(), AzureAuth::Default);
+        assert_eq!(
+            parse(&[("method", "managed_identity")]).unwrap(),
+            AzureAuth::ManagedIdentity { client_id: None }
+        );
+        assert_eq!(
+            parse(&[("method", "managed_identity"), ("client_id", "abc")]).unwrap(),
+            AzureAuth::ManagedIdentity {
+                client_id: Some("abc".to_string())
+            }
+        );
+        assert_eq!(
+            parse(&[
+                ("method", "client_secret"),
+                ("tenant_id", "t"),
+                ("client_id", "c"),
+                ("client_secret", "s"),
+            ])
+            .unwrap(),
+            AzureAuth::ClientSecret {
+                tenant_id: "t".to_string(),
+                client_id: "c".to_string(),
+                client_secret: "s".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_or_unknown_settings() {
+        let err = parse(&[("client_id", "abc")]).unwrap_err();
+        assert!(err.to_string().contains("requires 'method'"), "{err}");
+
+        let err = parse(&[("method", "client_secret"), ("client_id", "c")]).unwrap_err();
+        assert!(err.to_string().contains("requires 'tenant_id'"), "{err}");
+
+        let err = parse(&[("method", "default"), ("client_id", "c")]).unwrap_err();
+        assert!(err.to_string().contains("Unknown key 'client_id'"), "{err}");
+
+        let err = parse(&[("method", "certificate")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unknown Azure auth method 'certificate'"),
+            "{err}"
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::azure_auth::AzureAuth;
 use async_trait::async_trait;
-use azure_identity::DeveloperToolsCredential;
 use azure_security_keyvault_keys::{
     KeyClient,
     models::{EncryptionAlgorithm, KeyOperationParameters},
 };
+use indexmap::IndexMap;
 
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
@@ -15,28 +16,25 @@ const URL: &str = "https://fnox.jdx.dev/providers/azure-kms";
 pub struct AzureKeyVaultProvider {
     vault_url: String,
     key_name: String,
+    auth: AzureAuth,
 }
 
 impl AzureKeyVaultProvider {
-    pub fn new(vault_url: String, key_name: String) -> Result<Self> {
+    pub fn new(
+        vault_url: String,
+        key_name: String,
+        auth: IndexMap<String, String>,
+    ) -> Result<Self> {
         Ok(Self {
             vault_url,
             key_name,
+            auth: AzureAuth::from_config(&auth)?,
         })
     }
 
     /// Create an Azure Key Vault key client
     fn create_client(&self) -> Result<KeyClient> {
-        // Use DeveloperToolsCredential which supports multiple auth methods:
-        // - Azure CLI
-        // - Azure Developer CLI
-        let credential =
-            DeveloperToolsCredential::new(None).map_err(|e| FnoxError::ProviderAuthFailed {
-                provider: "Azure Key Vault".to_string(),
-                details: e.to_string(),
-                hint: "Run 'az login' to authenticate with Azure".to_string(),
-                url: URL.to_string(),
-            })?;
+        let credential = self.auth.credential(URL)?;
 
         KeyClient::new(&self.vault_url, credential, None).map_err(|e| FnoxError::ProviderApiError {
             provider: "Azure Key Vault".to_string(),
@@ -1,7 +1,13 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::azure_auth::AzureAuth;
+use crate::providers::prefix::KeyPrefix;
+use crate::providers::reference;
 use async_trait::async_trait;
-use azure_identity::DeveloperToolsCredential;
-use azure_security_keyvault_secrets::{SecretClient, models::SetSecretParameters};
+use azure_security_keyvault_secrets::{
+    SecretClient,
+    models::{SecretClientGetSecretOptions, SetSecretParameters},
+};
+use indexmap::IndexMap;
 
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
@@ -9,35 +15,35 @@ pub fn env_dependencies() -> &'static [&'static str] {
 
 const URL: &str = "https://fnox.jdx.dev/providers/azure-sm";
 
+/// Largest secret value Key Vault stores
+const MAX_VALUE_SIZE: usize = 25 * 1024;
+
 pub struct AzureSecretsManagerProvider {
     vault_url: String,
-    prefix: Option<String>,
+    prefix: KeyPrefix,
+    auth: AzureAuth,
 }
 
 impl AzureSecretsManagerProvider {
-    pub fn new(vault_url: String, prefix: Option<String>) -> Result<Self> {
-        Ok(Self { vault_url, prefix })
+    pub fn new(
+        vault_url: String,
+        prefix: Option<String>,
+        auth: IndexMap<String, String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            vault_url,
+            prefix: KeyPrefix::new(prefix),
+            auth: AzureAuth::from_config(&auth)?,
+        })
     }
 
     pub fn get_secret_name(&self, key: &str) -> String {
-        match &self.prefix {
-            Some(prefix) => format!("{}{}", prefix, key),
-            None => key.to_string(),
-        }
+        self.prefix.apply(key)
     }
 
     /// Create an Azure Key Vault secret client
     fn create_client(&self) -> Result<SecretClient> {
-        // Use DeveloperToolsCredential which supports multiple auth methods:
-        // - Azure CLI
-        // - Azure Developer CLI
-        let credential =
-            DeveloperToolsCredential::new(None).map_err(|e| FnoxError::ProviderAuthFailed {
-                provider: "Azure Key Vault".to_string(),
-                details: e.to_string(),
-                hint: "Run 'az login' to authenticate with Azure".to_string(),
-                url: URL.to_string(),
-            })?;
+        let credential = self.auth.credential(URL)?;
 
         SecretClient::new(&self.vault_url, credential, None).map_err(|e| {
             FnoxError::ProviderApiError {
@@ -49,11 +55,16 @@ impl AzureSecretsManagerProvider {
         })
     }
 
-    /// Get a secret value from Azure Key Vault
-    async fn get_secret_value(&self, secret_name: &str) -> Result<String> {
+    /// Get a secret value from Azure Key Vault, the latest version unless
+    /// `version` is given
+    async fn get_secret_value(&self, secret_name: &str, version: Option<&str>) -> Result<String> {
         let client = self.create_client()?;
 
-        let response = client.get_secret(secret_name, None).await.map_err(|e| {
+        let options = version.map(|version| SecretClientGetSecretOptions {
+            secret_version: Some(version.to_string()),
+            ..Default::default()
+        });
+        let response = client.get_secret(secret_name, options).await.map_err(|e| {
             let err_str = e.to_string();
             // Check for Azure-specific "not found" error patterns
             if err_str.contains("SecretNotFound")
@@ -63,8 +74,11 @@ impl AzureSecretsManagerProvider {
             {
                 FnoxError::ProviderSecretNotFound {
                     provider: "Azure Key Vault".to_string(),
-                    secret: secret_name.to_string(),
-                    hint: "Check that the secret exists in the vault".to_string(),
+                    secret: match version {
+                        Some(version) => format!("{}/{}", secret_name, version),
+                        None => secret_name.to_string(),
+                    },
+                    hint: "Check that the secret and version exist in the vault".to_string(),
                     url: URL.to_string(),
                 }
             } else if err_str.contains("Forbidden") || err_str.contains("Unauthorized") {
@@ -151,21 +165,45 @@ impl AzureSecretsManagerProvider {
     }
 }
 
+/// The name in a secret id such as
+/// `https://my-vault.vault.azure.net/secrets/db-password/<version>`
+fn secret_name_from_id(id: &str) -> Option<&str> {
+    let (_, path) = id.split_once("/secrets/")?;
+    path.split('/').next().filter(|name| !name.is_empty())
+}
+
 #[async_trait]
 impl crate::providers::Provider for AzureSecretsManagerProvider {
     fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
         vec![crate::providers::ProviderCapability::RemoteStorage]
     }
 
+    fn max_value_size(&self) -> Option<crate::providers::ValueSizeLimit> {
+        Some(crate::providers::ValueSizeLimit {
+            provider: "Azure Key Vault",
+            bytes: MAX_VALUE_SIZE,
+            hint: crate::providers::LARGE_VALUE_HINT.to_string(),
+            url: URL,
+        })
+    }
+
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let secret_name = self.get_secret_name(value);
+        // `name` or `name/version` to pin a version
+        let reference = reference::AZURE_SM.parse(value)?;
+        let (name, version) = match reference.segments().as_slice() {
+            [name] => (*name, None),
+            [name, version] => (*name, Some(*version)),
+            _ => unreachable!("the grammar allows 1 or 2 segments"),
+        };
+        let secret_name = self.get_secret_name(name);
         tracing::debug!(
-            "Getting secret '{}' from Azure Key Vault '{}'",
+            "Getting secret '{}' (version {}) from Azure Key Vault '{}'",
             secret_name,
+            version.unwrap_or("latest"),
             self.vault_url
         );
 
-        self.get_secret_value(&secret_name).await
+        self.get_secret_value(&secret_name, version).await
     }
 
     async fn test_connection(&self) -> Result<()> {
@@ -201,10 +239,97 @@ impl crate::providers::Provider for AzureSecretsManagerProvider {
         Ok(())
     }
 
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        use futures::TryStreamExt;
+
+        let name_prefix = self.get_secret_name(prefix.unwrap_or_default());
+        tracing::debug!(
+            "Listing secrets in Azure Key Vault starting with '{}'",
+            name_prefix
+        );
+
+        let client = self.create_client()?;
+        let list_error = |e: azure_core::Error| {
+            let err_str = e.to_string();
+            if err_str.contains("Forbidden") || err_str.contains("Unauthorized") {
+                FnoxError::ProviderAuthFailed {
+                    provider: "Azure Key Vault".to_string(),
+                    details: err_str,
+                    hint: "Listing secrets needs the 'list' secret permission (or the Key Vault Secrets User role)".to_string(),
+                    url: URL.to_string(),
+                }
+            } else {
+                FnoxError::ProviderApiError {
+                    provider: "Azure Key Vault".to_string(),
+                    details: err_str,
+                    hint: "Check your Azure Key Vault configuration".to_string(),
+                    url: URL.to_string(),
+                }
+            }
+        };
+
+        let mut pager = client.list_secret_properties(None).map_err(list_error)?;
+        let mut references = Vec::new();
+        while let Some(properties) = pager.try_next().await.map_err(list_error)? {
+            if let Some(name) = properties.id.as_deref().and_then(secret_name_from_id)
+                && name.starts_with(&name_prefix)
+            {
+                references.push(self.prefix.strip(name).to_string());
+            }
+        }
+        references.sort();
+        Ok(references)
+    }
+
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
         let secret_name = self.get_secret_name(key);
         self.put_secret(&secret_name, value).await?;
-        // Return the key name (without prefix) to store in config
+        // Return the key name to store in config, relative to the prefix
         Ok(key.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::check_value_size;
+
+    #[test]
+    fn values_up_to_25_kib_fit() {
+        let provider = AzureSecretsManagerProvider::new(
+            "https://my-vault.vault.azure.net/".to_string(),
+            None,
+            IndexMap::new(),
+        )
+        .unwrap();
+        assert!(check_value_size(&provider, "KEY", &"x".repeat(25600)).is_ok());
+        let err = check_value_size(&provider, "KEY", &"x".repeat(25601)).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FnoxError::ProviderValueTooLarge {
+                    size: 25601,
+                    limit: 25600,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn names_come_from_secret_ids() {
+        assert_eq!(
+            secret_name_from_id("https://my-vault.vault.azure.net/secrets/db-password"),
+            Some("db-password")
+        );
+        assert_eq!(
+            secret_name_from_id("https://my-vault.vault.azure.net/secrets/db-password/0123abcd"),
+            Some("db-password")
+        );
+        assert_eq!(
+            secret_name_from_id("https://my-vault.vault.azure.net/keys/k"),
+            None
+        );
+    }
+}
@@ -296,6 +296,12 @@ pub fn env_dependencies() -> &'static [&'static str] {
     &["BW_SESSION", "FNOX_BW_SESSION"]
 }
 
+/// Whether `value` reads an item's TOTP code, which changes every period and
+/// so mustn't be cached
+pub fn is_totp_reference(value: &str) -> bool {
+    matches!(value.split_once('/'), Some((_, "totp")))
+}
+
 fn bw_session_token() -> Option<String> {
     env::var("FNOX_BW_SESSION")
         .or_else(|_| env::var("BW_SESSION"))
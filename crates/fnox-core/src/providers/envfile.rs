@@ -0,0 +1,211 @@
+use crate::dotenv::{self, Entry, quote};
+use crate::error::{FnoxError, Result};
+use crate::providers::ProviderCapability;
+use async_trait::async_trait;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+const URL: &str = "https://fnox.jdx.dev/providers/envfile";
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &[]
+}
+
+/// Provider that reads and writes variables in a `.env`-style file
+pub struct EnvfileProvider {
+    path: PathBuf,
+}
+
+impl EnvfileProvider {
+    pub fn new(path: String) -> Result<Self> {
+        Ok(Self {
+            path: PathBuf::from(shellexpand::tilde(&path).to_string()),
+        })
+    }
+
+    /// Resolve a relative path against `config_dir`, the directory of the
+    /// config file that defines the provider, instead of the current directory
+    pub fn with_config_dir(mut self, config_dir: Option<&Path>) -> Self {
+        if let Some(dir) = config_dir
+            && self.path.is_relative()
+        {
+            self.path = dir.join(&self.path);
+        }
+        self
+    }
+
+    fn read(&self) -> Result<String> {
+        std::fs::read_to_string(&self.path).map_err(|e| FnoxError::ProviderApiError {
+            provider: "envfile".to_string(),
+            details: format!("Failed to read '{}': {}", self.path.display(), e),
+            hint: "Check that the file exists and is readable".to_string(),
+            url: URL.to_string(),
+        })
+    }
+
+    /// Write the file through a temp file so a failed write never truncates it
+    fn write(&self, content: &str) -> Result<()> {
+        let write_error = |e: std::io::Error| FnoxError::ProviderApiError {
+            provider: "envfile".to_string(),
+            details: format!("Failed to write '{}': {}", self.path.display(), e),
+            hint: "Check that you have write permissions".to_string(),
+            url: URL.to_string(),
+        };
+
+        let parent_dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut temp_file = NamedTempFile::new_in(parent_dir).map_err(write_error)?;
+        temp_file
+            .write_all(content.as_bytes())
+            .map_err(write_error)?;
+        if let Ok(metadata) = std::fs::metadata(&self.path) {
+            std::fs::set_permissions(temp_file.path(), metadata.permissions())
+                .map_err(write_error)?;
+        }
+        temp_file
+            .persist(&self.path)
+            .map_err(|e| write_error(e.error))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for EnvfileProvider {
+    fn capabilities(&self) -> Vec<ProviderCapability> {
+        vec![ProviderCapability::RemoteStorage]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        tracing::debug!(
+            "Getting '{}' from env file '{}'",
+            value,
+            self.path.display()
+        );
+
+        let content = self.read()?;
+        // Like a shell sourcing the file, the last assignment wins
+        parse(&content)?
+            .into_iter()
+            .rev()
+            .find(|entry| entry.key == value)
+            .map(|entry| entry.value)
+            .ok_or_else(|| FnoxError::ProviderSecretNotFound {
+                provider: "envfile".to_string(),
+                secret: value.to_string(),
+                hint: format!("Check that '{}' defines {}", self.path.display(), value),
+                url: URL.to_string(),
+            })
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        tracing::debug!("Storing '{}' in env file '{}'", key, self.path.display());
+
+        let content = if self.path.exists() {
+            self.read()?
+        } else {
+            String::new()
+        };
+        self.write(&upsert(&content, key, value)?)?;
+        Ok(key.to_string())
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        parse(&self.read()?)?;
+        Ok(())
+    }
+}
+
+/// Parse the file, reporting an unterminated quote as an invalid response
+fn parse(content: &str) -> Result<Vec<Entry>> {
+    dotenv::parse(content).map_err(|e| FnoxError::ProviderInvalidResponse {
+        provider: "envfile".to_string(),
+        details: e.to_string(),
+        hint: format!("Add the closing {} to the value", e.quote),
+        url: URL.to_string(),
+    })
+}
+
+/// Set `key` in the file content, replacing the last existing assignment in
+/// place or appending a new line. Comments, including the replaced line's own
+/// inline comment, and other lines are left untouched.
+fn upsert(content: &str, key: &str, value: &str) -> Result<String> {
+    let existing = parse(content)?.into_iter().rev().find(|e| e.key == key);
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    match existing {
+        Some(entry) => {
+            let export = if entry.export { "export " } else { "" };
+            let comment = entry.comment.as_deref().unwrap_or("");
+            let line = format!("{}{}={}{}", export, key, quote(value), comment);
+            lines.splice(entry.lines, [line]);
+        }
+        None => lines.push(format!("{}={}", key, quote(value))),
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENV: &str = r#"# Database
+export DATABASE_URL="postgres://localhost/db" # primary
+API_KEY=abc123 # inline comment
+SINGLE='no $expansion here'
+CERT="-----BEGIN CERT-----
+line \"two\"
+-----END CERT-----"
+ESCAPED="a\nb"
+EMPTY=
+"#;
+
+    #[test]
+    fn relative_paths_resolve_against_the_config_dir() {
+        let provider = |path: &str| {
+            EnvfileProvider::new(path.to_string())
+                .unwrap()
+                .with_config_dir(Some(Path::new("/project")))
+                .path
+        };
+        assert_eq!(provider(".env"), Path::new("/project/.env"));
+        assert_eq!(provider("/etc/app.env"), Path::new("/etc/app.env"));
+    }
+
+    #[test]
+    fn upsert_updates_in_place_and_keeps_comments() {
+        let updated = upsert(ENV, "DATABASE_URL", "postgres://prod/db").unwrap();
+        assert!(
+            updated.starts_with("# Database\nexport DATABASE_URL=postgres://prod/db # primary\n")
+        );
+        assert!(updated.contains("API_KEY=abc123 # inline comment\n"));
+
+        let updated = upsert(ENV, "API_KEY", "xyz789").unwrap();
+        assert!(updated.contains("API_KEY=xyz789 # inline comment\n"));
+
+        let updated = upsert(ENV, "CERT", "short").unwrap();
+        assert!(updated.contains("SINGLE='no $expansion here'\nCERT=short\nESCAPED="));
+    }
+
+    #[test]
+    fn upsert_appends_new_keys() {
+        let updated = upsert("# only a comment", "NEW_KEY", "two words").unwrap();
+        assert_eq!(updated, "# only a comment\nNEW_KEY=\"two words\"\n");
+
+        let updated = upsert("", "NEW_KEY", "line1\nline2").unwrap();
+        assert_eq!(updated, "NEW_KEY=\"line1\\nline2\"\n");
+        assert_eq!(parse(&updated).unwrap()[0].value, "line1\nline2");
+    }
+
+    #[test]
+    fn upsert_leaves_dollar_signs_literal() {
+        let updated = upsert("", "NEW_KEY", "pa$$word $HOME").unwrap();
+        assert_eq!(updated, "NEW_KEY=\"pa$$word $HOME\"\n");
+        assert_eq!(parse(&updated).unwrap()[0].value, "pa$$word $HOME");
+    }
+}
@@ -0,0 +1,670 @@
+use crate::error::{FnoxError, Result};
+use crate::providers::prefix::KeyPrefix;
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const PROVIDER_NAME: &str = "etcd";
+const PROVIDER_URL: &str = "https://fnox.jdx.dev/providers/etcd";
+
+/// etcd's default `--max-txn-ops`; larger batches are split into several
+/// transactions
+const MAX_TXN_OPS: usize = 128;
+
+/// etcd v3 provider.
+///
+/// Uses the JSON gateway that etcd serves on its client port (`/v3/kv/*`), so
+/// every call maps to a single gRPC request on the server without needing a
+/// gRPC client here.
+pub struct EtcdProvider {
+    endpoints: Vec<String>,
+    prefix: KeyPrefix,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RangeResponse {
+    #[serde(default)]
+    kvs: Vec<KeyValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TxnResponse {
+    #[serde(default)]
+    responses: Vec<ResponseOp>,
+}
+
+/// One operation's result in a transaction response
+#[derive(Debug, Deserialize)]
+struct ResponseOp {
+    #[serde(default)]
+    response_range: Option<RangeResponse>,
+}
+
+/// A key-value pair from a range response; both fields are base64-encoded
+#[derive(Debug, Deserialize)]
+struct KeyValue {
+    key: String,
+    /// Omitted by the gateway when the value is empty
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct HealthResponse {
+    health: String,
+    #[serde(default)]
+    reason: String,
+}
+
+impl EtcdProvider {
+    pub fn new(
+        endpoints: Vec<String>,
+        prefix: Option<String>,
+        ca_cert: Option<String>,
+        client_cert: Option<String>,
+        client_key: Option<String>,
+    ) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(FnoxError::Config(
+                "etcd provider requires at least one endpoint".to_string(),
+            ));
+        }
+        let endpoints = endpoints
+            .into_iter()
+            .map(|endpoint| {
+                if endpoint.starts_with("https://") || endpoint.starts_with("http://") {
+                    Ok(endpoint.trim_end_matches('/').to_string())
+                } else {
+                    Err(FnoxError::Config(format!(
+                        "Invalid etcd endpoint '{}': must start with https:// or http://",
+                        endpoint
+                    )))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            endpoints,
+            prefix: KeyPrefix::new(prefix),
+            client: build_client(ca_cert, client_cert, client_key)?,
+        })
+    }
+
+    /// Send a request to the first endpoint that accepts a connection.
+    ///
+    /// Only connection failures move on to the next endpoint; any HTTP response,
+    /// including an error status, comes from the cluster and is returned as-is.
+    async fn send(
+        &self,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<reqwest::Response> {
+        let mut failures = Vec::new();
+
+        for endpoint in &self.endpoints {
+            let url = format!("{}{}", endpoint, path);
+            let request = match body {
+                Some(body) => self.client.post(&url).json(body),
+                None => self.client.get(&url),
+            };
+            match request.send().await {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    tracing::debug!("etcd endpoint '{}' failed: {}", endpoint, e);
+                    failures.push(format!("{}: {}", endpoint, e));
+                }
+            }
+        }
+
+        Err(FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("No etcd endpoint reachable ({})", failures.join("; ")),
+            hint: "Check the provider's endpoints and network connectivity to the cluster"
+                .to_string(),
+            url: PROVIDER_URL.to_string(),
+        })
+    }
+
+    /// POST a JSON body to a `/v3/kv/*` method and parse the response.
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<T> {
+        let response = self.send(path, Some(&body)).await?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| FnoxError::ProviderInvalidResponse {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!("Failed to read response body: {}", e),
+                hint: "The etcd gateway returned an unreadable response".to_string(),
+                url: PROVIDER_URL.to_string(),
+            })?;
+
+        if !status.is_success() {
+            return Err(status_error(status, &text));
+        }
+
+        serde_json::from_str(&text).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to parse etcd response: {}", e),
+            hint: "Check that the endpoint is an etcd v3 client URL".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })
+    }
+
+    /// Read `key`, or `None` when it doesn't exist.
+    async fn range(&self, key: &str) -> Result<Option<String>> {
+        let response: RangeResponse = self
+            .call(
+                "/v3/kv/range",
+                serde_json::json!({ "key": BASE64.encode(key) }),
+            )
+            .await?;
+        first_value(response)
+    }
+
+    /// Read each of `keys` in one transaction, in order.
+    async fn range_txn(&self, keys: &[String]) -> Result<Vec<Option<String>>> {
+        let ops: Vec<_> = keys
+            .iter()
+            .map(|key| serde_json::json!({ "request_range": { "key": BASE64.encode(key) } }))
+            .collect();
+        // Without compares, the success branch always runs
+        let response: TxnResponse = self
+            .call("/v3/kv/txn", serde_json::json!({ "success": ops }))
+            .await?;
+        if response.responses.len() != keys.len() {
+            return Err(FnoxError::ProviderInvalidResponse {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!(
+                    "Expected {} results from the transaction, got {}",
+                    keys.len(),
+                    response.responses.len()
+                ),
+                hint: "Check that the endpoint is an etcd v3 client URL".to_string(),
+                url: PROVIDER_URL.to_string(),
+            });
+        }
+        response
+            .responses
+            .into_iter()
+            .map(|op| first_value(op.response_range.unwrap_or_default()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for EtcdProvider {
+    fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
+        vec![crate::providers::ProviderCapability::RemoteStorage]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        let key = self.prefix.apply(value);
+        tracing::debug!("Getting key '{}' from etcd", key);
+
+        self.range(&key).await?.ok_or_else(|| not_found(value))
+    }
+
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        if secrets.len() < 2 {
+            return crate::providers::get_secrets_concurrent(
+                self,
+                secrets,
+                crate::providers::batch_concurrency(self),
+            )
+            .await;
+        }
+
+        tracing::debug!(
+            "Batch fetching {} secrets from etcd in one transaction",
+            secrets.len()
+        );
+
+        let mut results = HashMap::new();
+        for chunk in secrets.chunks(MAX_TXN_OPS) {
+            let keys: Vec<String> = chunk.iter().map(|(_, v)| self.prefix.apply(v)).collect();
+            match self.range_txn(&keys).await {
+                Ok(values) => {
+                    for ((key, value), found) in chunk.iter().zip(values) {
+                        results.insert(key.clone(), found.ok_or_else(|| not_found(value)));
+                    }
+                }
+                Err(e) => {
+                    for (key, _) in chunk {
+                        results.insert(key.clone(), Err(batch_error(&e)));
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        let remote = self.prefix.apply(key);
+        tracing::debug!("Storing key '{}' in etcd", remote);
+
+        let _: serde_json::Value = self
+            .call(
+                "/v3/kv/put",
+                serde_json::json!({
+                    "key": BASE64.encode(&remote),
+                    "value": BASE64.encode(value),
+                }),
+            )
+            .await?;
+
+        // Return the key name to store in config, relative to the prefix
+        Ok(key.to_string())
+    }
+
+    async fn delete_secret(&self, value: &str) -> Result<()> {
+        let remote = self.prefix.apply(value);
+        tracing::debug!("Deleting key '{}' from etcd", remote);
+
+        let _: serde_json::Value = self
+            .call(
+                "/v3/kv/deleterange",
+                serde_json::json!({ "key": BASE64.encode(&remote) }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        // /health does a quorum read, so it fails when the cluster has lost quorum
+        let response = self.send("/health", None).await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+
+        match serde_json::from_str::<HealthResponse>(&text) {
+            Ok(health) if health.health == "true" => Ok(()),
+            Ok(health) => Err(FnoxError::ProviderApiError {
+                provider: PROVIDER_NAME.to_string(),
+                details: if health.reason.is_empty() {
+                    "etcd cluster is unhealthy".to_string()
+                } else {
+                    format!("etcd cluster is unhealthy: {}", health.reason)
+                },
+                hint: "Check the cluster with 'etcdctl endpoint health --cluster'".to_string(),
+                url: PROVIDER_URL.to_string(),
+            }),
+            Err(_) => Err(status_error(status, &text)),
+        }
+    }
+}
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &[]
+}
+
+/// Use the shared client unless the config needs its own TLS setup.
+fn build_client(
+    ca_cert: Option<String>,
+    client_cert: Option<String>,
+    client_key: Option<String>,
+) -> Result<reqwest::Client> {
+    let identity = match (client_cert, client_key) {
+        (None, None) => None,
+        (Some(cert), Some(key)) => {
+            let mut pem = read_pem(&cert, "client_cert")?;
+            pem.push(b'\n');
+            pem.extend(read_pem(&key, "client_key")?);
+            Some(reqwest::Identity::from_pem(&pem).map_err(|e| {
+                FnoxError::Config(format!("Invalid etcd client certificate or key: {}", e))
+            })?)
+        }
+        _ => {
+            return Err(FnoxError::Config(
+                "etcd provider needs both client_cert and client_key for client certificate authentication"
+                    .to_string(),
+            ));
+        }
+    };
+
+    if ca_cert.is_none() && identity.is_none() {
+        return Ok(crate::http::http_client());
+    }
+
+    let mut builder = crate::http::http_client_builder();
+    if let Some(ca_cert) = ca_cert {
+        let pem = read_pem(&ca_cert, "ca_cert")?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            FnoxError::Config(format!("Invalid etcd CA certificate '{}': {}", ca_cert, e))
+        })?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if let Some(identity) = identity {
+        builder = builder.identity(identity);
+    }
+    builder
+        .build()
+        .map_err(|e| FnoxError::Config(format!("Failed to build etcd HTTP client: {}", e)))
+}
+
+fn read_pem(path: &str, field: &str) -> Result<Vec<u8>> {
+    let path = PathBuf::from(shellexpand::tilde(path).to_string());
+    std::fs::read(&path).map_err(|e| {
+        FnoxError::Config(format!(
+            "Failed to read etcd {} '{}': {}",
+            field,
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Invalid base64 in etcd response: {}", e),
+            hint: "Check that the endpoint is an etcd v3 client URL".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })
+}
+
+/// The value of the only key in a point read, decoded
+fn first_value(response: RangeResponse) -> Result<Option<String>> {
+    response
+        .kvs
+        .into_iter()
+        .next()
+        .map(|kv| {
+            let key = decode(&kv.key)?;
+            let value = String::from_utf8(decode(&kv.value)?).map_err(|_| {
+                FnoxError::ProviderInvalidResponse {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!(
+                        "Value of '{}' is not valid UTF-8",
+                        String::from_utf8_lossy(&key)
+                    ),
+                    hint: "fnox can only read text values from etcd".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            })?;
+            Ok(value)
+        })
+        .transpose()
+}
+
+fn not_found(secret: &str) -> FnoxError {
+    FnoxError::ProviderSecretNotFound {
+        provider: PROVIDER_NAME.to_string(),
+        secret: secret.to_string(),
+        hint: "Check that the key exists with 'etcdctl get <key>'".to_string(),
+        url: PROVIDER_URL.to_string(),
+    }
+}
+
+/// Copy a failed batch read into a per-secret error.
+fn batch_error(e: &FnoxError) -> FnoxError {
+    match e {
+        FnoxError::ProviderAuthFailed {
+            provider,
+            details,
+            hint,
+            url,
+        } => FnoxError::ProviderAuthFailed {
+            provider: provider.clone(),
+            details: details.clone(),
+            hint: hint.clone(),
+            url: url.clone(),
+        },
+        other => FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details: other.to_string(),
+            hint: "Check the provider's endpoints and network connectivity to the cluster"
+                .to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+    }
+}
+
+/// Map a non-success gateway response to the matching provider error.
+fn status_error(status: reqwest::StatusCode, body: &str) -> FnoxError {
+    // The gateway reports gRPC errors as {"error": "...", "code": N, "message": "..."}
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| {
+            v.get("message")
+                .or_else(|| v.get("error"))
+                .and_then(|m| m.as_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| body.trim().to_string());
+    let details = format!("HTTP {}: {}", status, message);
+
+    match status.as_u16() {
+        401 | 403 => FnoxError::ProviderAuthFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details,
+            hint: "Check the client certificate, or the user's etcd role permissions".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+        _ => FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details,
+            hint: "Check that the endpoint is an etcd v3 client URL".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Provider;
+    use crate::test_server::{self, Requests};
+
+    type Handler = fn(&str, &serde_json::Value) -> (u16, String);
+
+    /// Minimal etcd gateway stand-in: answers each request with
+    /// `handler(path, json_body)`.
+    async fn mock_etcd(handler: Handler) -> (String, Requests) {
+        let server =
+            test_server::serve(move |request, _| handler(&request.path, &request.json())).await;
+        (server.url, server.requests)
+    }
+
+    fn b64(s: &str) -> String {
+        BASE64.encode(s)
+    }
+
+    fn decoded(body: &serde_json::Value, field: &str) -> String {
+        String::from_utf8(BASE64.decode(body[field].as_str().unwrap()).unwrap()).unwrap()
+    }
+
+    /// Serves `/fnox/DB_URL` and `/fnox/API_KEY`, for point reads and
+    /// transactions of them
+    fn fnox_keys(path: &str, body: &serde_json::Value) -> (u16, String) {
+        let range = |request: &serde_json::Value| {
+            let all = [("/fnox/API_KEY", "k3y"), ("/fnox/DB_URL", "postgres://db")];
+            let key = decoded(request, "key");
+            let kvs: Vec<_> = all
+                .iter()
+                .filter(|(k, _)| *k == key)
+                .map(|(k, v)| serde_json::json!({ "key": b64(k), "value": b64(v) }))
+                .collect();
+            serde_json::json!({ "header": {}, "kvs": kvs })
+        };
+        let response = match path {
+            "/v3/kv/range" => range(body),
+            "/v3/kv/txn" => {
+                let responses: Vec<_> = body["success"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|op| serde_json::json!({ "response_range": range(&op["request_range"]) }))
+                    .collect();
+                serde_json::json!({ "header": {}, "succeeded": true, "responses": responses })
+            }
+            _ => return (404, r#"{"error":"Not Found","code":5}"#.to_string()),
+        };
+        (200, response.to_string())
+    }
+
+    fn provider(endpoints: Vec<String>, prefix: Option<&str>) -> EtcdProvider {
+        EtcdProvider::new(endpoints, prefix.map(String::from), None, None, None).unwrap()
+    }
+
+    #[test]
+    fn new_validates_endpoints_and_tls_options() {
+        assert!(EtcdProvider::new(vec![], None, None, None, None).is_err());
+        assert!(
+            EtcdProvider::new(vec!["localhost:2379".to_string()], None, None, None, None).is_err()
+        );
+        assert!(
+            EtcdProvider::new(
+                vec!["https://localhost:2379".to_string()],
+                None,
+                None,
+                Some("client.pem".to_string()),
+                None,
+            )
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_secret_reads_the_prefixed_key() {
+        let (url, requests) = mock_etcd(fnox_keys).await;
+        let provider = provider(vec![url], Some("/fnox/"));
+
+        assert_eq!(
+            provider.get_secret("DB_URL").await.unwrap(),
+            "postgres://db"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].path, "/v3/kv/range");
+        assert_eq!(decoded(&requests[0].json(), "key"), "/fnox/DB_URL");
+        assert!(requests[0].json().get("range_end").is_none());
+    }
+
+    #[tokio::test]
+    async fn get_secret_reports_missing_keys() {
+        let (url, _) = mock_etcd(fnox_keys).await;
+        let provider = provider(vec![url], Some("/fnox/"));
+
+        match provider.get_secret("MISSING").await {
+            Err(FnoxError::ProviderSecretNotFound { secret, .. }) => assert_eq!(secret, "MISSING"),
+            other => panic!("Expected ProviderSecretNotFound, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_reads_every_key_in_one_transaction() {
+        let (url, requests) = mock_etcd(fnox_keys).await;
+        let provider = provider(vec![url], Some("/fnox/"));
+        let secrets = [
+            ("DATABASE_URL".to_string(), "DB_URL".to_string()),
+            ("API_KEY".to_string(), "API_KEY".to_string()),
+            ("OTHER".to_string(), "MISSING".to_string()),
+        ];
+
+        let results = provider.get_secrets_batch(&secrets).await;
+
+        assert_eq!(results["DATABASE_URL"].as_ref().unwrap(), "postgres://db");
+        assert_eq!(results["API_KEY"].as_ref().unwrap(), "k3y");
+        assert!(matches!(
+            results["OTHER"],
+            Err(FnoxError::ProviderSecretNotFound { .. })
+        ));
+
+        // Only the requested keys are read, not everything under the prefix
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/v3/kv/txn");
+        let keys: Vec<_> = requests[0].json()["success"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| decoded(&op["request_range"], "key"))
+            .collect();
+        assert_eq!(keys, ["/fnox/DB_URL", "/fnox/API_KEY", "/fnox/MISSING"]);
+    }
+
+    #[tokio::test]
+    async fn put_secret_writes_the_prefixed_key() {
+        let (url, requests) =
+            mock_etcd(|_, _| (200, r#"{"header":{"revision":"7"}}"#.to_string())).await;
+        let provider = provider(vec![url], Some("/fnox/"));
+
+        assert_eq!(
+            provider.put_secret("TOKEN", "s3cret").await.unwrap(),
+            "TOKEN"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].path, "/v3/kv/put");
+        assert_eq!(decoded(&requests[0].json(), "key"), "/fnox/TOKEN");
+        assert_eq!(decoded(&requests[0].json(), "value"), "s3cret");
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_the_next_endpoint() {
+        let down = test_server::unreachable_url().await;
+        let (url, _) = mock_etcd(fnox_keys).await;
+        let provider = provider(vec![down, url], Some("/fnox/"));
+
+        assert_eq!(provider.get_secret("API_KEY").await.unwrap(), "k3y");
+    }
+
+    #[tokio::test]
+    async fn test_connection_checks_cluster_health() {
+        let (url, _) = mock_etcd(|path, _| match path {
+            "/health" => (200, r#"{"health":"true","reason":""}"#.to_string()),
+            _ => (404, String::new()),
+        })
+        .await;
+        provider(vec![url], None).test_connection().await.unwrap();
+
+        let (url, _) = mock_etcd(|_, _| {
+            (
+                503,
+                r#"{"health":"false","reason":"RAFT NO LEADER"}"#.to_string(),
+            )
+        })
+        .await;
+        match provider(vec![url], None).test_connection().await {
+            Err(FnoxError::ProviderApiError { details, .. }) => {
+                assert_eq!(details, "etcd cluster is unhealthy: RAFT NO LEADER")
+            }
+            other => panic!("Expected ProviderApiError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn maps_permission_errors_to_auth_failures() {
+        let (url, _) = mock_etcd(|_, _| {
+            (
+                403,
+                r#"{"error":"etcdserver: permission denied","code":7,"message":"etcdserver: permission denied"}"#
+                    .to_string(),
+            )
+        })
+        .await;
+        let provider = provider(vec![url], None);
+
+        match provider.get_secret("KEY").await {
+            Err(FnoxError::ProviderAuthFailed { details, .. }) => {
+                assert!(details.contains("permission denied"))
+            }
+            other => panic!("Expected ProviderAuthFailed, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::error::{FnoxError, Result};
+use async_trait::async_trait;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const PROVIDER_NAME: &str = "Exec";
+const PROVIDER_URL: &str = "https://fnox.jdx.dev/providers/exec";
+
+/// Placeholder replaced with the secret reference in command templates
+const KEY_PLACEHOLDER: &str = "{key}";
+
+pub struct ExecProvider {
+    command: String,
+    put_command: Option<String>,
+}
+
+impl ExecProvider {
+    pub fn new(command: String, put_command: Option<String>) -> Result<Self> {
+        Ok(Self {
+            command,
+            put_command,
+        })
+    }
+
+    /// Run a command template for `key`, optionally feeding `stdin`, and return trimmed stdout.
+    async fn run_command(&self, template: &str, key: &str, stdin: Option<&str>) -> Result<String> {
+        let argv = build_argv(template, key)?;
+        tracing::debug!("Executing exec provider command: {:?}", argv);
+
+        let mut cmd = Command::new(&argv[0]);
+        cmd.args(&argv[1..]);
+        cmd.stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        });
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let mut child = cmd.spawn().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                FnoxError::ProviderCliNotFound {
+                    provider: PROVIDER_NAME.to_string(),
+                    cli: argv[0].clone(),
+                    install_hint: format!("Make sure '{}' is installed and on PATH", argv[0]),
+                    url: PROVIDER_URL.to_string(),
+                }
+            } else {
+                FnoxError::ProviderCliFailed {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!("Failed to run '{}': {}", argv[0], e),
+                    hint: "Check the command in your exec provider config".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            }
+        })?;
+
+        if let Some(input) = stdin
+            && let Some(mut child_stdin) = child.stdin.take()
+        {
+            child_stdin.write_all(input.as_bytes()).await.map_err(|e| {
+                FnoxError::ProviderCliFailed {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!("Failed to write to '{}' stdin: {}", argv[0], e),
+                    hint: "Make sure the put_command reads the value from stdin".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            })?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| FnoxError::ProviderCliFailed {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!("Failed to run '{}': {}", argv[0], e),
+                hint: "Check the command in your exec provider config".to_string(),
+                url: PROVIDER_URL.to_string(),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(FnoxError::ProviderCliFailed {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!(
+                    "'{}' failed ({}): {}",
+                    argv[0],
+                    output.status,
+                    stderr.trim()
+                ),
+                hint: "Run the command manually to see what went wrong".to_string(),
+                url: PROVIDER_URL.to_string(),
+            });
+        }
+
+        let stdout =
+            String::from_utf8(output.stdout).map_err(|e| FnoxError::ProviderInvalidResponse {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!("Invalid UTF-8 in command output: {}", e),
+                hint: "The secret value contains invalid UTF-8 characters".to_string(),
+                url: PROVIDER_URL.to_string(),
+            })?;
+
+        Ok(stdout.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for ExecProvider {
+    fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
+        if self.put_command.is_some() {
+            vec![crate::providers::ProviderCapability::RemoteStorage]
+        } else {
+            vec![crate::providers::ProviderCapability::RemoteRead]
+        }
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        tracing::debug!("Getting secret '{}' via exec provider", value);
+        self.run_command(&self.command, value, None).await
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        let Some(ref put_command) = self.put_command else {
+            return Err(FnoxError::Provider(
+                "Exec provider has no put_command configured".to_string(),
+            ));
+        };
+
+        tracing::debug!("Storing secret '{}' via exec provider", key);
+        // The value goes over stdin so it never appears in argv or `ps` output
+        self.run_command(put_command, key, Some(value)).await?;
+        Ok(key.to_string())
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        // Running the command would fetch a real secret, so only check that
+        // the template parses and its program can be found.
+        let argv = build_argv(&self.command, "")?;
+        which::which(&argv[0]).map_err(|_| FnoxError::ProviderCliNotFound {
+            provider: PROVIDER_NAME.to_string(),
+            cli: argv[0].clone(),
+            install_hint: format!("Make sure '{}' is installed and on PATH", argv[0]),
+            url: PROVIDER_URL.to_string(),
+        })?;
+        Ok(())
+    }
+}
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &[]
+}
+
+/// Split a command template into argv and substitute `{key}` in each argument.
+///
+/// Splitting happens before substitution, so a reference containing spaces or
+/// shell metacharacters always stays a single argument and is never interpreted
+/// by a shell.
+fn build_argv(template: &str, key: &str) -> Result<Vec<String>> {
+    let words = shlex::split(template).ok_or_else(|| {
+        FnoxError::Config(format!(
+            "Invalid exec provider command '{}': unbalanced quotes",
+            template
+        ))
+    })?;
+
+    if words.is_empty() {
+        return Err(FnoxError::Config(
+            "Exec provider command must not be empty".to_string(),
+        ));
+    }
+
+    Ok(words
+        .into_iter()
+        .map(|word| word.replace(KEY_PLACEHOLDER, key))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Provider;
+
+    #[test]
+    fn build_argv_substitutes_key_per_argument() {
+        let argv = build_argv("my-tool get --name={key} {key}", "db/password").unwrap();
+        assert_eq!(
+            argv,
+            vec!["my-tool", "get", "--name=db/password", "db/password"]
+        );
+    }
+
+    #[test]
+    fn build_argv_keeps_hostile_keys_in_one_argument() {
+        let argv = build_argv("my-tool get {key}", "x; rm -rf / $(whoami)").unwrap();
+        assert_eq!(argv, vec!["my-tool", "get", "x; rm -rf / $(whoami)"]);
+    }
+
+    #[test]
+    fn build_argv_honors_quotes_in_template() {
+        let argv = build_argv(r#"my-tool --vault "Team Vault" get {key}"#, "k").unwrap();
+        assert_eq!(argv, vec!["my-tool", "--vault", "Team Vault", "get", "k"]);
+    }
+
+    #[test]
+    fn build_argv_rejects_invalid_templates() {
+        assert!(build_argv("", "k").is_err());
+        assert!(build_argv("my-tool \"unterminated", "k").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn get_secret_returns_trimmed_stdout() {
+        let provider = ExecProvider::new("echo value-for-{key}".to_string(), None).unwrap();
+        assert_eq!(
+            provider.get_secret("MY_KEY").await.unwrap(),
+            "value-for-MY_KEY"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn get_secret_reports_stderr_on_failure() {
+        let provider =
+            ExecProvider::new("sh -c 'echo boom >&2; exit 3'".to_string(), None).unwrap();
+        match provider.get_secret("MY_KEY").await {
+            Err(FnoxError::ProviderCliFailed { details, .. }) => {
+                assert!(details.contains("boom"), "details: {}", details);
+            }
+            other => panic!("Expected ProviderCliFailed, got {:?}", other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn put_secret_passes_value_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("{key}.txt");
+        let put_command = format!("tee {}", shlex::try_quote(&out.to_string_lossy()).unwrap());
+        let provider = ExecProvider::new("true".to_string(), Some(put_command)).unwrap();
+
+        let stored = provider.put_secret("MY_KEY", "s3cret").await.unwrap();
+        assert_eq!(stored, "MY_KEY");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("MY_KEY.txt")).unwrap(),
+            "s3cret"
+        );
+    }
+}
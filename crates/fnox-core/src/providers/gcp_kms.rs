@@ -9,23 +9,57 @@ pub fn env_dependencies() -> &'static [&'static str] {
 
 const URL: &str = "https://fnox.jdx.dev/providers/gcp-kms";
 
+/// The error for a ciphertext Cloud KMS can't decrypt with the configured
+/// AAD, which usually means it was encrypted with different AAD
+fn aad_mismatch_error(aad: &str, details: String) -> FnoxError {
+    FnoxError::ProviderInvalidResponse {
+        provider: "GCP KMS".to_string(),
+        details: format!(
+            "The value couldn't be decrypted with the configured aad ('{}'); it was \
+             probably encrypted with different aad: {}",
+            aad, details
+        ),
+        hint: "The ciphertext may have been copied from another profile or project; check aad \
+               in the provider config, or re-encrypt the value with 'fnox set'"
+            .to_string(),
+        url: URL.to_string(),
+    }
+}
+
 pub struct GcpKmsProvider {
     project: String,
     location: String,
     keyring: String,
     key: String,
+    /// Only sent when configured, so ciphertexts without AAD keep decrypting
+    aad: Option<String>,
 }
 
 impl GcpKmsProvider {
-    pub fn new(project: String, location: String, keyring: String, key: String) -> Result<Self> {
+    pub fn new(
+        project: String,
+        location: String,
+        keyring: String,
+        key: String,
+        aad: Option<String>,
+    ) -> Result<Self> {
         Ok(Self {
             project,
             location,
             keyring,
             key,
+            aad,
         })
     }
 
+    /// The additional authenticated data to send with Encrypt and Decrypt
+    fn aad_bytes(&self) -> Vec<u8> {
+        self.aad
+            .as_deref()
+            .map(|aad| aad.as_bytes().to_vec())
+            .unwrap_or_default()
+    }
+
     /// Get the full resource name for the crypto key
     fn key_name(&self) -> String {
         format!(
@@ -75,7 +109,7 @@ impl GcpKmsProvider {
         let request = DecryptRequest {
             name: self.key_name(),
             ciphertext: ciphertext_bytes,
-            additional_authenticated_data: vec![],
+            additional_authenticated_data: self.aad_bytes(),
             ..Default::default()
         };
 
@@ -96,6 +130,13 @@ impl GcpKmsProvider {
                     hint: "Check that the KMS key exists and is accessible".to_string(),
                     url: URL.to_string(),
                 }
+            } else if let Some(aad) = self.aad.as_deref()
+                && (err_str.contains("INVALID_ARGUMENT")
+                    || err_str.contains("InvalidArgument")
+                    || err_str.contains("Decryption failed"))
+            {
+                // KMS rejects a ciphertext/AAD mismatch as an invalid argument
+                aad_mismatch_error(aad, err_str)
             } else {
                 FnoxError::ProviderApiError {
                     provider: "GCP KMS".to_string(),
@@ -133,7 +174,7 @@ impl crate::providers::Provider for GcpKmsProvider {
         let request = EncryptRequest {
             name: self.key_name(),
             plaintext: plaintext.as_bytes().to_vec(),
-            additional_authenticated_data: vec![],
+            additional_authenticated_data: self.aad_bytes(),
             ..Default::default()
         };
 
@@ -1,10 +1,14 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::prefix::KeyPrefix;
+use crate::providers::reference;
 use async_trait::async_trait;
 use bytes::Bytes;
 use google_cloud_secretmanager_v1::{
     client::SecretManagerService,
     model::{Replication, Secret, SecretPayload, replication::Automatic},
 };
+use std::collections::HashMap;
+use tokio::sync::OnceCell;
 
 pub fn env_dependencies() -> &'static [&'static str] {
     &[]
@@ -14,25 +18,42 @@ const URL: &str = "https://fnox.jdx.dev/providers/gcp-sm";
 
 const PROVIDER_NAME: &str = "GCP Secret Manager";
 
+/// Largest secret version payload Secret Manager stores
+const MAX_VALUE_SIZE: usize = 64 * 1024;
+
 pub struct GoogleSecretManagerProvider {
     project: String,
-    prefix: Option<String>,
+    prefix: KeyPrefix,
+    /// Built on first use, so its credentials and token are shared by every
+    /// request this provider makes
+    client: OnceCell<SecretManagerService>,
 }
 
 impl GoogleSecretManagerProvider {
     pub fn new(project: String, prefix: Option<String>) -> Result<Self> {
-        Ok(Self { project, prefix })
+        Ok(Self {
+            project,
+            prefix: KeyPrefix::new(prefix),
+            client: OnceCell::new(),
+        })
     }
 
-    /// Build the full secret name with optional prefix
-    fn build_secret_name(&self, value: &str) -> String {
+    /// Build the full secret version name with optional prefix. Without a
+    /// version, the latest one is used.
+    fn build_secret_name(&self, value: &str, version: Option<&str>) -> String {
         format!(
-            "projects/{}/secrets/{}/versions/latest",
+            "projects/{}/secrets/{}/versions/{}",
             self.project,
-            self.get_secret_id(value)
+            self.get_secret_id(value),
+            version.unwrap_or("latest")
         )
     }
 
+    /// The Secret Manager client, created on first use
+    async fn client(&self) -> Result<&SecretManagerService> {
+        self.client.get_or_try_init(|| self.create_client()).await
+    }
+
     /// Create a Secret Manager client
     async fn create_client(&self) -> Result<SecretManagerService> {
         SecretManagerService::builder().build().await.map_err(|e| {
@@ -60,31 +81,59 @@ impl GoogleSecretManagerProvider {
 
     /// Get the secret ID (without version path)
     fn get_secret_id(&self, key: &str) -> String {
-        if let Some(prefix) = &self.prefix {
-            format!("{}{}", prefix, key)
-        } else {
-            key.to_string()
-        }
+        self.prefix.apply(key)
+    }
+
+    /// Access the secret version `value` refers to
+    async fn access_secret(&self, client: &SecretManagerService, value: &str) -> Result<String> {
+        let reference = reference::GCP_SM.parse(value)?;
+        let secret_name =
+            self.build_secret_name(&reference.path_str(), reference.version.as_deref());
+
+        let response = client
+            .access_secret_version()
+            .set_name(secret_name)
+            .send()
+            .await
+            .map_err(|e| convert_secret_error(e, value, "secretmanager.versions.access"))?;
+
+        // Extract the payload data
+        let payload = response
+            .payload
+            .ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                provider: PROVIDER_NAME.to_string(),
+                details: "Secret has no payload".to_string(),
+                hint: "The secret exists but has no value".to_string(),
+                url: URL.to_string(),
+            })?;
+
+        // Convert bytes to string
+        String::from_utf8(payload.data.to_vec()).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Secret value is not valid UTF-8: {}", e),
+            hint: "The secret contains binary data that cannot be decoded as UTF-8".to_string(),
+            url: URL.to_string(),
+        })
     }
 
     /// Create or update a secret in GCP Secret Manager
     async fn put_secret_value(&self, secret_id: &str, secret_value: &str) -> Result<()> {
-        let client = self.create_client().await?;
+        let client = self.client().await?;
 
         let project = self.project.as_str();
         let parent = format!("projects/{project}");
         let name = format!("{parent}/secrets/{secret_id}");
 
-        match add_secret_version(&client, &name, secret_value).await {
+        match add_secret_version(client, &name, secret_value).await {
             Ok(_) => Ok(()),
             Err(e) if e.http_status_code() == Some(404) => {
-                if let Err(e) = create_secret(&client, &parent, secret_id, &name).await
+                if let Err(e) = create_secret(client, &parent, secret_id, &name).await
                     && e.http_status_code() != Some(409)
                 {
                     return Err(convert_provider_error(e, "secretmanager.secrets.create"));
                 }
 
-                add_secret_version(&client, &name, secret_value)
+                add_secret_version(client, &name, secret_value)
                     .await
                     .map_err(|e| convert_provider_error(e, "secretmanager.versions.add"))
             }
@@ -174,38 +223,69 @@ impl crate::providers::Provider for GoogleSecretManagerProvider {
         vec![crate::providers::ProviderCapability::RemoteStorage]
     }
 
+    fn max_value_size(&self) -> Option<crate::providers::ValueSizeLimit> {
+        Some(crate::providers::ValueSizeLimit {
+            provider: PROVIDER_NAME,
+            bytes: MAX_VALUE_SIZE,
+            hint: crate::providers::LARGE_VALUE_HINT.to_string(),
+            url: URL,
+        })
+    }
+
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let client = self.create_client().await?;
-        let secret_name = self.build_secret_name(value);
+        let client = self.client().await?;
+        self.access_secret(client, value).await
+    }
 
-        let response = client
-            .access_secret_version()
-            .set_name(secret_name)
-            .send()
-            .await
-            .map_err(|e| convert_secret_error(e, value, "secretmanager.versions.access"))?;
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        use futures::stream::{self, StreamExt};
 
-        // Extract the payload data
-        let payload = response
-            .payload
-            .ok_or_else(|| FnoxError::ProviderInvalidResponse {
-                provider: PROVIDER_NAME.to_string(),
-                details: "Secret has no payload".to_string(),
-                hint: "The secret exists but has no value".to_string(),
-                url: URL.to_string(),
-            })?;
+        tracing::debug!(
+            "Getting {} secrets from GCP Secret Manager concurrently",
+            secrets.len()
+        );
 
-        // Convert bytes to string
-        String::from_utf8(payload.data.to_vec()).map_err(|e| FnoxError::ProviderInvalidResponse {
-            provider: PROVIDER_NAME.to_string(),
-            details: format!("Secret value is not valid UTF-8: {}", e),
-            hint: "The secret contains binary data that cannot be decoded as UTF-8".to_string(),
-            url: URL.to_string(),
-        })
+        let client = match self.client().await {
+            Ok(client) => client,
+            Err(e) => {
+                // Without a client every secret fails the same way
+                let details = e.to_string();
+                return secrets
+                    .iter()
+                    .map(|(key, _)| {
+                        (
+                            key.clone(),
+                            Err(FnoxError::ProviderAuthFailed {
+                                provider: PROVIDER_NAME.to_string(),
+                                details: details.clone(),
+                                hint: "Run 'gcloud auth application-default login' or set GOOGLE_APPLICATION_CREDENTIALS".to_string(),
+                                url: URL.to_string(),
+                            }),
+                        )
+                    })
+                    .collect();
+            }
+        };
+
+        // Own the keys and values so the futures don't borrow from the
+        // stream's items
+        let secrets_vec: Vec<_> = secrets.to_vec();
+
+        stream::iter(secrets_vec)
+            .map(|(key, value)| async move {
+                let result = self.access_secret(client, &value).await;
+                (key, result)
+            })
+            .buffer_unordered(crate::providers::batch_concurrency(self))
+            .collect()
+            .await
     }
 
     async fn test_connection(&self) -> Result<()> {
-        let client = self.create_client().await?;
+        let client = self.client().await?;
 
         // Try to list secrets to verify access
         client
@@ -238,10 +318,135 @@ impl crate::providers::Provider for GoogleSecretManagerProvider {
         Ok(())
     }
 
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        let id_prefix = self.get_secret_id(prefix.unwrap_or_default());
+        tracing::debug!(
+            "Listing secrets in GCP Secret Manager starting with '{}'",
+            id_prefix
+        );
+
+        let client = self.client().await?;
+        let mut references = Vec::new();
+        let mut page_token = String::new();
+        loop {
+            let mut request = client
+                .list_secrets()
+                .set_parent(format!("projects/{}", self.project))
+                .set_page_token(page_token);
+            if !id_prefix.is_empty() {
+                request = request.set_filter(format!("name:{}", id_prefix));
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| convert_provider_error(e, "secretmanager.secrets.list"))?;
+            // Names are projects/<project>/secrets/<id>, and the name filter
+            // matches anywhere in them, so check the prefix again
+            references.extend(
+                response
+                    .secrets
+                    .iter()
+                    .filter_map(|secret| secret.name.rsplit('/').next())
+                    .filter(|id| id.starts_with(&id_prefix))
+                    .map(|id| self.prefix.strip(id).to_string()),
+            );
+            page_token = response.next_page_token;
+            if page_token.is_empty() {
+                break;
+            }
+        }
+        references.sort();
+        Ok(references)
+    }
+
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
         let secret_id = self.get_secret_id(key);
         self.put_secret_value(&secret_id, value).await?;
-        // Return the key name (without prefix) to store in config
+        // Return the key name to store in config, relative to the prefix
         Ok(key.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{Provider, check_value_size};
+    use google_cloud_gax::options::RequestOptions;
+    use google_cloud_gax::response::Response;
+    use google_cloud_secretmanager_v1::model::{
+        AccessSecretVersionRequest, AccessSecretVersionResponse,
+    };
+
+    /// Serves `projects/test/secrets/<id>/versions/<version>` with the value
+    /// `<id>@<version>`, and 404 for secrets whose ids end in `missing`
+    #[derive(Debug)]
+    struct FakeSecretManager;
+
+    impl google_cloud_secretmanager_v1::stub::SecretManagerService for FakeSecretManager {
+        async fn access_secret_version(
+            &self,
+            req: AccessSecretVersionRequest,
+            _options: RequestOptions,
+        ) -> google_cloud_secretmanager_v1::Result<Response<AccessSecretVersionResponse>> {
+            let parts: Vec<&str> = req.name.split('/').collect();
+            let (id, version) = (parts[3], parts[5]);
+            if id.ends_with("missing") {
+                return Err(google_cloud_secretmanager_v1::Error::http(
+                    404,
+                    Default::default(),
+                    Default::default(),
+                ));
+            }
+            let payload = SecretPayload::new().set_data(Bytes::from(format!("{id}@{version}")));
+            Ok(Response::from(
+                AccessSecretVersionResponse::new().set_payload(payload),
+            ))
+        }
+    }
+
+    fn fake_provider(prefix: Option<&str>) -> GoogleSecretManagerProvider {
+        let provider =
+            GoogleSecretManagerProvider::new("test".to_string(), prefix.map(str::to_string))
+                .unwrap();
+        provider
+            .client
+            .set(SecretManagerService::from_stub(FakeSecretManager))
+            .unwrap();
+        provider
+    }
+
+    #[tokio::test]
+    async fn batch_reads_each_secret_and_version() {
+        let provider = fake_provider(Some("app-"));
+        let secrets = [
+            ("DB_URL".to_string(), "db-url".to_string()),
+            ("OLD_TOKEN".to_string(), "token#3".to_string()),
+            ("MISSING".to_string(), "missing".to_string()),
+        ];
+
+        let results = provider.get_secrets_batch(&secrets).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["DB_URL"].as_ref().unwrap(), "app-db-url@latest");
+        assert_eq!(results["OLD_TOKEN"].as_ref().unwrap(), "app-token@3");
+        assert!(results["MISSING"].is_err());
+    }
+
+    #[test]
+    fn values_up_to_64_kib_fit() {
+        let provider = GoogleSecretManagerProvider::new("my-project".to_string(), None).unwrap();
+        assert!(check_value_size(&provider, "KEY", &"x".repeat(65536)).is_ok());
+        let err = check_value_size(&provider, "KEY", &"x".repeat(65537)).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FnoxError::ProviderValueTooLarge {
+                    size: 65537,
+                    limit: 65536,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use crate::error::{FnoxError, Result};
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use std::time::Duration;
+
+const PROVIDER_NAME: &str = "HTTP";
+const PROVIDER_URL: &str = "https://fnox.jdx.dev/providers/http";
+
+/// Placeholder replaced with the secret reference in the URL template
+const KEY_PLACEHOLDER: &str = "{key}";
+
+/// Total attempts for a request that keeps failing with a 5xx response
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+pub struct HttpProvider {
+    url_template: String,
+    headers: IndexMap<String, String>,
+    json_path: Option<String>,
+    health_path: Option<String>,
+}
+
+impl HttpProvider {
+    pub fn new(
+        url_template: String,
+        headers: IndexMap<String, String>,
+        json_path: Option<String>,
+        health_path: Option<String>,
+    ) -> Result<Self> {
+        if !url_template.starts_with("https://") && !url_template.starts_with("http://") {
+            return Err(FnoxError::Config(format!(
+                "Invalid HTTP provider url_template '{}': must start with https:// or http://",
+                url_template
+            )));
+        }
+
+        Ok(Self {
+            url_template,
+            headers,
+            json_path: json_path.filter(|p| !p.is_empty()),
+            health_path: health_path.filter(|p| !p.is_empty()),
+        })
+    }
+
+    /// Send a GET request with the configured headers, retrying on 5xx responses.
+    async fn get_with_retry(&self, url: &str) -> Result<reqwest::Response> {
+        let client = crate::http::http_client();
+        let mut attempt = 1;
+
+        loop {
+            let mut request = client.get(url);
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| FnoxError::ProviderApiError {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!("HTTP request to '{}' failed: {}", redact_query(url), e),
+                    hint: "Check network connectivity to the server".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                })?;
+
+            if !response.status().is_server_error() || attempt >= MAX_ATTEMPTS {
+                return Ok(response);
+            }
+
+            let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+            tracing::debug!(
+                "HTTP provider got {} from '{}', retrying in {:?} (attempt {}/{})",
+                response.status(),
+                redact_query(url),
+                delay,
+                attempt + 1,
+                MAX_ATTEMPTS
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// URL for `test_connection`: the health path on the template's origin, if configured.
+    fn health_url(&self) -> Result<String> {
+        let origin = origin_of(&self.url_template)?;
+        Ok(match self.health_path {
+            Some(ref path) if path.starts_with("https://") || path.starts_with("http://") => {
+                path.clone()
+            }
+            Some(ref path) => format!("{}/{}", origin, path.trim_start_matches('/')),
+            None => origin,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for HttpProvider {
+    fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
+        vec![crate::providers::ProviderCapability::RemoteRead]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        tracing::debug!("Getting secret '{}' via HTTP provider", value);
+
+        let url = substitute_key(&self.url_template, value);
+        let response = self.get_with_retry(&url).await?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| FnoxError::ProviderInvalidResponse {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!("Failed to read response body: {}", e),
+                hint: "The server returned an unreadable response".to_string(),
+                url: PROVIDER_URL.to_string(),
+            })?;
+
+        if !status.is_success() {
+            return Err(status_error(status, &body, value));
+        }
+
+        match self.json_path {
+            Some(ref path) => extract_json_path(&body, path),
+            None => Ok(body),
+        }
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        let url = self.health_url()?;
+
+        if self.health_path.is_none() {
+            // Without a health endpoint, only check that the server is reachable
+            let client = crate::http::http_client();
+            return crate::http::warm_up(&client, &url).await.map_err(|e| {
+                FnoxError::ProviderApiError {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!("Cannot reach '{}': {}", url, e),
+                    hint: "Check network connectivity to the server, or set health_path"
+                        .to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            });
+        }
+
+        tracing::debug!("Testing HTTP provider health endpoint: {}", url);
+        let response = self.get_with_retry(&url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(status, &body, &url));
+        }
+        Ok(())
+    }
+}
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &[]
+}
+
+/// Replace `{key}` in the URL template with the reference, URL-encoding each
+/// `/`-separated segment so that path-like references keep their slashes.
+fn substitute_key(template: &str, key: &str) -> String {
+    let encoded = key
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    template.replace(KEY_PLACEHOLDER, &encoded)
+}
+
+/// Scheme, host and port of a URL template, without any path.
+fn origin_of(url_template: &str) -> Result<String> {
+    let url = reqwest::Url::parse(url_template).map_err(|e| {
+        FnoxError::Config(format!(
+            "Invalid HTTP provider url_template '{}': {}",
+            url_template, e
+        ))
+    })?;
+    Ok(url.origin().ascii_serialization())
+}
+
+/// Drop the query string from a URL before it is logged or shown in an error,
+/// since APIs sometimes take tokens as query parameters.
+fn redact_query(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+/// Map a non-success HTTP status to the matching provider error.
+fn status_error(status: reqwest::StatusCode, body: &str, secret: &str) -> FnoxError {
+    let details = format!("HTTP {}: {}", status, body.trim());
+    match status.as_u16() {
+        401 | 403 => FnoxError::ProviderAuthFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details,
+            hint: "Check the credentials in the provider's headers".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+        404 => FnoxError::ProviderSecretNotFound {
+            provider: PROVIDER_NAME.to_string(),
+            secret: secret.to_string(),
+            hint: "Check that the secret exists at the configured URL".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+        _ => FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details,
+            hint: "Check the server logs or the provider's url_template".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+    }
+}
+
+/// Extract a value from a JSON response body using dot notation (e.g. `data.value`).
+///
+/// Numeric path segments index into arrays, and `\.` escapes a literal dot.
+fn extract_json_path(body: &str, path: &str) -> Result<String> {
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to parse response as JSON: {}", e),
+            hint: "Remove json_path to use the raw response body".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })?;
+
+    let mut current = &value;
+    for part in crate::secret_resolver::split_key_path(path) {
+        let next = match current {
+            serde_json::Value::Array(items) => {
+                part.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => current.get(&part),
+        };
+        current = next.ok_or_else(|| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("JSON path '{}' not found in response", path),
+            hint: "Check the json_path in your HTTP provider config".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })?;
+    }
+
+    match current {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        other => Ok(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Provider;
+    use crate::test_server::{self, Request, Requests};
+
+    type Handler = fn(&Request, usize) -> (u16, &'static str);
+
+    /// Answers each request with `handler(request, n)`, where `n` counts
+    /// requests so far. Returns the base URL and the requests.
+    async fn mock_server(handler: Handler) -> (String, Requests) {
+        let server = test_server::serve(move |request, n| {
+            let (status, body) = handler(request, n);
+            (status, body.to_string())
+        })
+        .await;
+        (server.url, server.requests)
+    }
+
+    fn provider(url_template: String, json_path: Option<&str>) -> HttpProvider {
+        let headers = IndexMap::from([("Authorization".to_string(), "Bearer t0ken".to_string())]);
+        HttpProvider::new(url_template, headers, json_path.map(String::from), None).unwrap()
+    }
+
+    #[test]
+    fn substitute_key_encodes_each_segment() {
+        assert_eq!(
+            substitute_key("https://x/v1/{key}", "prod/db password"),
+            "https://x/v1/prod/db%20password"
+        );
+        assert_eq!(
+            substitute_key("https://x/v1?name={key}", "a&b"),
+            "https://x/v1?name=a%26b"
+        );
+    }
+
+    #[test]
+    fn extract_json_path_handles_nested_objects_and_arrays() {
+        let body = r#"{"data": {"items": [{"value": "first"}], "count": 2}}"#;
+        assert_eq!(
+            extract_json_path(body, "data.items.0.value").unwrap(),
+            "first"
+        );
+        assert_eq!(extract_json_path(body, "data.count").unwrap(), "2");
+        assert!(extract_json_path(body, "data.missing").is_err());
+        assert!(extract_json_path("not json", "data").is_err());
+    }
+
+    #[test]
+    fn new_rejects_non_http_urls() {
+        assert!(
+            HttpProvider::new("ftp://x/{key}".to_string(), IndexMap::new(), None, None).is_err()
+        );
+    }
+
+    #[test]
+    fn health_url_joins_path_to_origin() {
+        let provider = HttpProvider::new(
+            "https://secrets.example.com:8443/v1/{key}".to_string(),
+            IndexMap::new(),
+            None,
+            Some("/healthz".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            provider.health_url().unwrap(),
+            "https://secrets.example.com:8443/healthz"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_secret_sends_headers_and_extracts_json_path() {
+        let (url, requests) = mock_server(|_, _| (200, r#"{"data": {"value": "s3cret"}}"#)).await;
+        let provider = provider(format!("{url}/v1/secrets/{{key}}"), Some("data.value"));
+
+        assert_eq!(provider.get_secret("prod/db").await.unwrap(), "s3cret");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/v1/secrets/prod/db");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer t0ken"));
+    }
+
+    #[tokio::test]
+    async fn get_secret_returns_raw_body_without_json_path() {
+        let (url, _) = mock_server(|_, _| (200, "plain-value")).await;
+        let provider = provider(format!("{url}/{{key}}"), None);
+        assert_eq!(provider.get_secret("KEY").await.unwrap(), "plain-value");
+    }
+
+    #[tokio::test]
+    async fn get_secret_retries_server_errors() {
+        let (url, requests) =
+            mock_server(|_, n| if n < 2 { (503, "busy") } else { (200, "ok") }).await;
+        let provider = provider(format!("{url}/{{key}}"), None);
+
+        assert_eq!(provider.get_secret("KEY").await.unwrap(), "ok");
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_secret_does_not_retry_client_errors() {
+        let (url, requests) = mock_server(|_, _| (404, "nope")).await;
+        let provider = provider(format!("{url}/{{key}}"), None);
+
+        match provider.get_secret("KEY").await {
+            Err(FnoxError::ProviderSecretNotFound { secret, .. }) => assert_eq!(secret, "KEY"),
+            other => panic!("Expected ProviderSecretNotFound, got {:?}", other),
+        }
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connection_hits_health_path() {
+        let (url, requests) = mock_server(|request, _| {
+            if request.method == "GET" && request.path == "/healthz" {
+                (200, "ok")
+            } else {
+                (500, "wrong path")
+            }
+        })
+        .await;
+        let provider = HttpProvider::new(
+            format!("{url}/v1/{{key}}"),
+            IndexMap::new(),
+            None,
+            Some("healthz".to_string()),
+        )
+        .unwrap();
+
+        provider.test_connection().await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::env;
 use crate::error::{FnoxError, Result};
+use crate::recording::CommandRunner;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::{LazyLock, Mutex};
@@ -170,7 +171,7 @@ impl InfisicalProvider {
 
         cmd.stdin(std::process::Stdio::null());
 
-        let output = cmd.output().await.map_err(|e| {
+        let output = CommandRunner::new(cmd).output().await.map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 FnoxError::ProviderCliNotFound {
                     provider: PROVIDER_NAME.to_string(),
@@ -414,6 +415,15 @@ impl crate::providers::Provider for InfisicalProvider {
         }
     }
 
+    /// The token from the environment or a client credentials login
+    async fn session_env(&self) -> Result<Vec<(String, String)>> {
+        let mut vars = vec![("INFISICAL_TOKEN".to_string(), self.get_auth_token()?)];
+        if let Some(api_url) = infisical_api_url() {
+            vars.push(("INFISICAL_API_URL".to_string(), api_url));
+        }
+        Ok(vars)
+    }
+
     async fn test_connection(&self) -> Result<()> {
         tracing::debug!("Testing connection to Infisical");
 
@@ -541,6 +551,9 @@ fn classify_cli_error(stderr: &str, secret_ref: Option<&str>) -> FnoxError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::Provider;
+    use crate::recording::Cassette;
+    use std::sync::Arc;
 
     #[test]
     fn classify_cli_error_unauthorized() {
@@ -690,4 +703,77 @@ mod tests {
             other => panic!("Expected ProviderCliFailed, got {:?}", other),
         }
     }
+
+    /// A cassette with the values that differ between environments filtered,
+    /// and a provider for it. Recording needs the server from
+    /// test/setup-infisical-ci.sh.
+    fn cassette(name: &str, environment: &str) -> (Arc<Cassette>, InfisicalProvider) {
+        // Replays pass a token and domain like the recordings did
+        if infisical_token().is_none() {
+            env::set_var("INFISICAL_TOKEN", "test-token");
+        }
+        if infisical_api_url().is_none() {
+            env::set_var("INFISICAL_API_URL", "http://localhost:8081/api");
+        }
+        let project_id =
+            env::var("INFISICAL_PROJECT_ID").unwrap_or_else(|_| "test-project".to_string());
+        let api_url = infisical_api_url().unwrap();
+
+        let cassette = Cassette::open(name).unwrap();
+        cassette.filter(&project_id, "<PROJECT_ID>");
+        cassette.filter(
+            api_url.trim_end_matches("/api").trim_end_matches('/'),
+            "<INFISICAL_URL>",
+        );
+        let provider =
+            InfisicalProvider::new(Some(project_id), Some(environment.to_string()), None).unwrap();
+        (cassette, provider)
+    }
+
+    #[tokio::test]
+    async fn replays_reads() {
+        let (cassette, provider) = cassette("infisical_get", "dev");
+
+        let value = cassette.run(provider.get_secret("API_KEY")).await;
+
+        assert_eq!(value.unwrap(), "[VALUE-1]");
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_batch_reads() {
+        let (cassette, provider) = cassette("infisical_batch", "dev");
+        let secrets = [
+            ("DB_USER".to_string(), "DB_USER".to_string()),
+            ("DB_PASSWORD".to_string(), "DB_PASSWORD".to_string()),
+            ("MISSING".to_string(), "MISSING".to_string()),
+        ];
+
+        let results = cassette.run(provider.get_secrets_batch(&secrets)).await;
+
+        assert_eq!(results["DB_USER"].as_ref().unwrap(), "[VALUE-1]");
+        assert_eq!(results["DB_PASSWORD"].as_ref().unwrap(), "[VALUE-2]");
+        assert!(
+            matches!(
+                results["MISSING"],
+                Err(FnoxError::ProviderSecretNotFound { .. })
+            ),
+            "{:?}",
+            results["MISSING"]
+        );
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_auth_errors() {
+        let (cassette, provider) = cassette("infisical_errors", "dev");
+
+        let result = cassette.run(provider.get_secret("API_KEY")).await;
+
+        assert!(
+            matches!(result, Err(FnoxError::ProviderAuthFailed { .. })),
+            "{result:?}"
+        );
+        cassette.finish().unwrap();
+    }
 }
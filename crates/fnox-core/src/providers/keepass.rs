@@ -1,13 +1,70 @@
 use crate::error::{FnoxError, Result};
-use crate::providers::ProviderCapability;
+use crate::providers::reference;
+use crate::providers::totp::{self, Totp};
+use crate::providers::{ProviderCapability, SecretVersion};
 use async_trait::async_trait;
+use base64::Engine;
 use keepass::DatabaseKey;
-use keepass::db::{Database, EntryId, GroupId, GroupRef};
+use keepass::db::{Database, Entry, EntryId, GroupId, GroupRef};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
 use tempfile::NamedTempFile;
 
+/// Decrypted databases, kept for the process lifetime so that a batch, or a
+/// TUI session that lists and then runs, derives the key once per file. An
+/// entry is reused only while the file's modification time and size are the
+/// ones it was decrypted at; fnox's own writes drop it.
+///
+/// Each slot has its own lock, held while decrypting, so concurrent lookups of
+/// one database wait for a single key derivation without blocking lookups of
+/// other databases.
+static DATABASES: LazyLock<Mutex<HashMap<DatabaseCacheKey, DatabaseSlot>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// (database path, keyfile path, hash of the password). The password itself
+/// isn't kept in the cache.
+type DatabaseCacheKey = (PathBuf, Option<PathBuf>, blake3::Hash);
+
+type DatabaseSlot = Arc<Mutex<Option<CachedDatabase>>>;
+
+struct CachedDatabase {
+    modified: Option<SystemTime>,
+    len: u64,
+    db: Arc<Database>,
+}
+
+/// What a reference reads from or writes to in its entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// A standard or custom string field, by its KeePass key
+    Field(String),
+    /// A binary attachment, by name
+    Attachment(String),
+    /// The current code of the entry's TOTP configuration
+    Totp,
+}
+
+impl Target {
+    fn password() -> Self {
+        Target::Field("Password".to_string())
+    }
+}
+
+/// A parsed KeePass reference
+#[derive(Debug, PartialEq, Eq)]
+struct EntryRef {
+    /// Group names followed by the entry title
+    path: Vec<String>,
+    target: Target,
+    /// The path as written, when its last segment had a `:` or `@`, for
+    /// entries whose title contains one (e.g. `admin@example.com`)
+    literal: Option<Vec<String>>,
+}
+
 /// Provider that reads and writes secrets from KeePass database files (.kdbx)
 pub struct KeePassProvider {
     database_path: PathBuf,
@@ -100,6 +157,58 @@ impl KeePassProvider {
         })
     }
 
+    /// The decrypted database, from the process cache when the file hasn't
+    /// changed since it was last opened with the same credentials
+    fn cached_database(&self) -> Result<Arc<Database>> {
+        let cache_key = self.cache_key()?;
+        let metadata =
+            std::fs::metadata(&self.database_path).map_err(|e| FnoxError::ProviderApiError {
+                provider: "KeePass".to_string(),
+                details: format!(
+                    "Failed to open database '{}': {}",
+                    self.database_path.display(),
+                    e
+                ),
+                hint: "Check that the database file exists and is readable".to_string(),
+                url: "https://fnox.jdx.dev/providers/keepass".to_string(),
+            })?;
+        let modified = metadata.modified().ok();
+
+        let slot = DATABASES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(cache_key)
+            .or_default()
+            .clone();
+        let mut cached_db = slot.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cached_db.as_ref()
+            && cached.modified == modified
+            && cached.len == metadata.len()
+        {
+            tracing::debug!(
+                "Using cached KeePass database '{}'",
+                self.database_path.display()
+            );
+            return Ok(cached.db.clone());
+        }
+
+        let db = Arc::new(self.open_database()?);
+        *cached_db = Some(CachedDatabase {
+            modified,
+            len: metadata.len(),
+            db: db.clone(),
+        });
+        Ok(db)
+    }
+
+    fn cache_key(&self) -> Result<DatabaseCacheKey> {
+        Ok((
+            self.database_path.clone(),
+            self.keyfile_path.clone(),
+            blake3::hash(self.get_password()?.as_bytes()),
+        ))
+    }
+
     /// Save the database back to disk
     fn save_database(&self, db: &Database) -> Result<()> {
         // Write to a temporary file first, then atomically rename to avoid data loss
@@ -190,41 +299,205 @@ impl KeePassProvider {
                 url: "https://fnox.jdx.dev/providers/keepass".to_string(),
             })?;
 
+        // The file may change within the mtime's resolution, so don't rely on it
+        DATABASES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(path, _, _), _| *path != self.database_path);
+
         Ok(())
     }
 
-    /// Parse a reference value into (entry_path, field)
+    /// Parse a reference value into the entry path and what to read from it.
     /// Formats:
-    /// - "entry-name" -> (["entry-name"], "Password")
-    /// - "entry-name/username" -> (["entry-name"], "UserName")
-    /// - "group/subgroup/entry-name" -> (["group", "subgroup", "entry-name"], "Password")
-    /// - "group/subgroup/entry-name/password" -> (["group", "subgroup", "entry-name"], "Password")
-    fn parse_reference(value: &str) -> (Vec<&str>, &str) {
-        let parts: Vec<&str> = value.split('/').collect();
-
-        // Known field names (case-insensitive check, but return proper casing)
-        let known_fields = ["password", "username", "url", "notes", "title"];
-
-        if parts.len() == 1 {
-            // Just entry name, default to password
-            (parts, "Password")
-        } else {
-            // Check if the last part is a known field
-            let last = parts.last().unwrap().to_lowercase();
-            if known_fields.contains(&last.as_str()) {
-                let field = match last.as_str() {
-                    "password" => "Password",
-                    "username" => "UserName",
-                    "url" => "URL",
-                    "notes" => "Notes",
-                    "title" => "Title",
-                    _ => "Password",
-                };
-                (parts[..parts.len() - 1].to_vec(), field)
-            } else {
-                // Last part is not a known field, treat entire path as entry path
-                (parts, "Password")
+    /// - "entry-name" -> (["entry-name"], Password)
+    /// - "entry-name/username" -> (["entry-name"], UserName)
+    /// - "group/subgroup/entry-name" -> (["group", "subgroup", "entry-name"], Password)
+    /// - "group/subgroup/entry-name/password" -> (["group", "subgroup", "entry-name"], Password)
+    /// - "group/entry-name:API_TOKEN" -> (["group", "entry-name"], custom field API_TOKEN)
+    /// - "entry-name@cert.pem" -> (["entry-name"], attachment cert.pem)
+    /// - "entry-name/totp" -> (["entry-name"], current TOTP code)
+    ///
+    /// Only the standard field names are recognized after a `/`, so
+    /// "group/API_TOKEN" is the entry API_TOKEN in the group, not a field.
+    fn parse_reference(value: &str) -> Result<EntryRef> {
+        let reference = reference::KEEPASS.parse(value)?;
+        let mut path = reference.path;
+        // The grammar matches field names case-insensitively; map them to
+        // KeePass's own casing
+        if let Some(field) = reference.field.as_deref() {
+            if field == "totp" {
+                return Ok(EntryRef {
+                    path,
+                    target: Target::Totp,
+                    literal: None,
+                });
             }
+            let field = match field {
+                "username" => "UserName",
+                "url" => "URL",
+                "notes" => "Notes",
+                "title" => "Title",
+                _ => "Password",
+            };
+            return Ok(EntryRef {
+                path,
+                target: Target::Field(field.to_string()),
+                literal: None,
+            });
+        }
+
+        let last = path.last().expect("references have at least one segment");
+        if let Some(i) = last.rfind([':', '@'])
+            && i > 0
+            && i + 1 < last.len()
+        {
+            let name = last[i + 1..].to_string();
+            let target = if last[i..].starts_with(':') {
+                Target::Field(name)
+            } else {
+                Target::Attachment(name)
+            };
+            let literal = path.clone();
+            let title = last[..i].to_string();
+            *path.last_mut().expect("checked above") = title;
+            return Ok(EntryRef {
+                path,
+                target,
+                literal: Some(literal),
+            });
+        }
+
+        Ok(EntryRef {
+            path,
+            target: Target::password(),
+            literal: None,
+        })
+    }
+
+    /// Find the entry a reference names, and what to read from it. When no
+    /// entry has the title before a `:` or `@`, an entry titled with the whole
+    /// segment is used instead, with its password.
+    fn resolve(db: &Database, reference: &EntryRef) -> Option<(EntryId, Target)> {
+        if let Some(id) = Self::find_entry_id(db, &reference.path) {
+            return Some((id, reference.target.clone()));
+        }
+        let id = Self::find_entry_id(db, reference.literal.as_ref()?)?;
+        Some((id, Target::password()))
+    }
+
+    /// Read what `reference` points at from an opened database
+    fn read(db: &Database, reference: &EntryRef) -> Result<String> {
+        let (entry_id, target) =
+            Self::resolve(db, reference).ok_or_else(|| Self::entry_not_found(&reference.path))?;
+        let entry = db.entry(entry_id).expect("entry exists");
+
+        match target {
+            Target::Field(field) => entry.get(&field).map(|s| s.to_string()).ok_or_else(|| {
+                FnoxError::ProviderInvalidResponse {
+                    provider: "KeePass".to_string(),
+                    details: format!(
+                        "Field '{}' not found in entry '{}'",
+                        field,
+                        reference.path.join("/")
+                    ),
+                    hint: "Standard fields are password, username, url, notes and title; \
+                           read custom fields with 'entry:FIELD'"
+                        .to_string(),
+                    url: "https://fnox.jdx.dev/providers/keepass".to_string(),
+                }
+            }),
+            // Attachments can hold any bytes, so they're returned base64-encoded
+            Target::Attachment(name) => entry
+                .attachments_named()
+                .find(|(attachment_name, _)| *attachment_name == name)
+                .map(|(_, attachment)| {
+                    base64::engine::general_purpose::STANDARD.encode(attachment.data.get())
+                })
+                .ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                    provider: "KeePass".to_string(),
+                    details: format!(
+                        "Attachment '{}' not found in entry '{}'",
+                        name,
+                        reference.path.join("/")
+                    ),
+                    hint: "Check the attachment's name in your KeePass client".to_string(),
+                    url: "https://fnox.jdx.dev/providers/keepass".to_string(),
+                }),
+            Target::Totp => Ok(Self::entry_totp(&entry, &reference.path)?.now()),
+        }
+    }
+
+    /// The TOTP configuration of an entry: an `otpauth://` URI in the `otp`
+    /// field (KeePassXC), or the `TimeOtp-*` fields of KeePass 2 and KeeOtp2
+    fn entry_totp(entry: &Entry, path: &[String]) -> Result<Totp> {
+        let invalid = |details: String| FnoxError::ProviderInvalidResponse {
+            provider: "KeePass".to_string(),
+            details: format!(
+                "Invalid TOTP configuration in entry '{}': {}",
+                path.join("/"),
+                details
+            ),
+            hint: "Set up TOTP for the entry again in your KeePass client".to_string(),
+            url: "https://fnox.jdx.dev/providers/keepass".to_string(),
+        };
+
+        if let Some(uri) = entry.get("otp") {
+            return Totp::from_uri(uri).map_err(invalid);
+        }
+
+        let secret = if let Some(secret) = entry.get("TimeOtp-Secret-Base32") {
+            totp::decode_base32(secret)
+        } else if let Some(secret) = entry.get("TimeOtp-Secret-Hex") {
+            hex::decode(secret.replace(' ', ""))
+                .map_err(|e| format!("TimeOtp-Secret-Hex isn't valid hex: {}", e))
+        } else if let Some(secret) = entry.get("TimeOtp-Secret-Base64") {
+            base64::engine::general_purpose::STANDARD
+                .decode(secret.trim())
+                .map_err(|e| format!("TimeOtp-Secret-Base64 isn't valid base64: {}", e))
+        } else if let Some(secret) = entry.get("TimeOtp-Secret") {
+            Ok(secret.as_bytes().to_vec())
+        } else {
+            return Err(FnoxError::ProviderInvalidResponse {
+                provider: "KeePass".to_string(),
+                details: format!("Entry '{}' has no TOTP configuration", path.join("/")),
+                hint: "Set up TOTP for the entry in your KeePass client; fnox reads the 'otp' \
+                       field (KeePassXC) or the TimeOtp-Secret fields (KeePass 2, KeeOtp2)"
+                    .to_string(),
+                url: "https://fnox.jdx.dev/providers/keepass".to_string(),
+            });
+        };
+
+        let number = |field: &str| {
+            entry
+                .get(field)
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse::<u64>()
+                        .map_err(|_| format!("{} must be a number, not '{}'", field, value))
+                })
+                .transpose()
+        };
+        let mut otp = Totp::new(secret.map_err(invalid)?);
+        if let Some(digits) = number("TimeOtp-Length").map_err(invalid)? {
+            otp = otp.with_digits(digits as u32).map_err(invalid)?;
+        }
+        if let Some(period) = number("TimeOtp-Period").map_err(invalid)? {
+            otp = otp.with_period(period).map_err(invalid)?;
+        }
+        if let Some(algorithm) = entry.get("TimeOtp-Algorithm") {
+            otp = otp.with_algorithm(totp::Algorithm::parse(algorithm).map_err(invalid)?);
+        }
+        Ok(otp)
+    }
+
+    fn entry_not_found(path: &[String]) -> FnoxError {
+        FnoxError::ProviderSecretNotFound {
+            provider: "KeePass".to_string(),
+            secret: path.join("/"),
+            hint: "Check that the entry exists in the database".to_string(),
+            url: "https://fnox.jdx.dev/providers/keepass".to_string(),
         }
     }
 
@@ -233,16 +506,16 @@ impl KeePassProvider {
     /// Path segments before the last name exact-named subgroups to navigate into;
     /// the final segment is searched recursively by entry title inside the
     /// navigated group (matching the pre-0.12 lookup behavior).
-    fn find_entry_id(db: &Database, path: &[&str]) -> Option<EntryId> {
+    fn find_entry_id(db: &Database, path: &[String]) -> Option<EntryId> {
         Self::walk_group(db.root(), path)
     }
 
-    fn walk_group(group: GroupRef<'_>, path: &[&str]) -> Option<EntryId> {
+    fn walk_group(group: GroupRef<'_>, path: &[String]) -> Option<EntryId> {
         if path.is_empty() {
             return None;
         }
         if path.len() == 1 {
-            Self::find_entry_id_by_title(group, path[0])
+            Self::find_entry_id_by_title(group, &path[0])
         } else {
             let subgroup = group.groups().find(|g| g.name == path[0])?;
             Self::walk_group(subgroup, &path[1..])
@@ -264,9 +537,30 @@ impl KeePassProvider {
         None
     }
 
+    /// Collect the reference of every titled entry under `group`, as its group
+    /// path followed by its title.
+    fn collect_references(
+        group: GroupRef<'_>,
+        path: &mut Vec<String>,
+        references: &mut Vec<String>,
+    ) {
+        for entry in group.entries() {
+            if let Some(title) = entry.get_title().filter(|title| !title.is_empty()) {
+                path.push(title.to_string());
+                references.push(path.join("/"));
+                path.pop();
+            }
+        }
+        for subgroup in group.groups() {
+            path.push(subgroup.name.clone());
+            Self::collect_references(subgroup, path, references);
+            path.pop();
+        }
+    }
+
     /// Walk the group path from the root, creating any missing groups along the
     /// way, and return the id of the deepest group.
-    fn navigate_or_create_group_path(db: &mut Database, group_path: &[&str]) -> GroupId {
+    fn navigate_or_create_group_path(db: &mut Database, group_path: &[String]) -> GroupId {
         let mut current_id = db.root().id();
         for name in group_path {
             let next_id = db
@@ -280,7 +574,7 @@ impl KeePassProvider {
                 None => {
                     let mut group_mut = db.group_mut(current_id).expect("current group exists");
                     let mut new_group = group_mut.add_group();
-                    new_group.name = (*name).to_string();
+                    new_group.name = name.clone();
                     new_group.as_ref().id()
                 }
             };
@@ -292,7 +586,7 @@ impl KeePassProvider {
     /// Returns the entry name (title) that was used.
     fn find_or_create_entry(
         db: &mut Database,
-        path: &[&str],
+        path: &[String],
         value: &str,
         field: &str,
     ) -> Result<String> {
@@ -315,7 +609,12 @@ impl KeePassProvider {
             });
         }
 
-        let entry_name = path[path.len() - 1];
+        // Use protected storage (in-memory encryption and proper KDBX marking)
+        // for the password and custom fields, which usually hold secrets too;
+        // the other standard fields are unprotected.
+        let protect = !matches!(field, "UserName" | "URL" | "Notes");
+
+        let entry_name = path[path.len() - 1].as_str();
         let group_path = &path[..path.len() - 1];
         let target_group_id = Self::navigate_or_create_group_path(db, group_path);
 
@@ -327,18 +626,24 @@ impl KeePassProvider {
 
         if let Some(eid) = existing_id {
             let mut entry_mut = db.entry_mut(eid).expect("entry exists");
-            // Use protected storage for the Password field (in-memory encryption
-            // and proper KDBX marking); other fields are unprotected.
-            if field == "Password" {
+            // Keep the previous version in the entry's history, as KeePass
+            // clients do, so `fnox history` can list it
+            let previous = (*entry_mut).clone();
+            entry_mut
+                .history
+                .get_or_insert_with(Default::default)
+                .add_entry(previous);
+            if protect {
                 entry_mut.set_protected(field, value);
             } else {
                 entry_mut.set_unprotected(field, value);
             }
+            entry_mut.times.last_modification = Some(keepass::db::Times::now());
         } else {
             let mut group_mut = db.group_mut(target_group_id).expect("target group exists");
             let mut entry_mut = group_mut.add_entry();
             entry_mut.set_unprotected("Title", entry_name);
-            if field == "Password" {
+            if protect {
                 entry_mut.set_protected(field, value);
             } else {
                 entry_mut.set_unprotected(field, value);
@@ -347,6 +652,41 @@ impl KeePassProvider {
 
         Ok(entry_name.to_string())
     }
+
+    /// Versions of `field` in an entry and its history, newest first. Versions
+    /// are numbered from the oldest, so the current one has the highest id.
+    fn entry_versions(entry: &Entry, field: &str, with_values: bool) -> Vec<SecretVersion> {
+        // History is kept newest first; reverse it so versions saved within the
+        // same second (timestamps have no sub-second part) stay in order
+        let mut versions: Vec<&Entry> = entry
+            .history
+            .as_ref()
+            .map(|history| history.get_entries().iter().rev().collect())
+            .unwrap_or_default();
+        versions.sort_by_key(|version| version.times.last_modification);
+
+        let current_id = versions.len() + 1;
+        let mut result: Vec<SecretVersion> = versions
+            .into_iter()
+            .chain(std::iter::once(entry))
+            .enumerate()
+            .filter_map(|(i, version)| {
+                let value = version.get(field)?;
+                Some(SecretVersion {
+                    id: (i + 1).to_string(),
+                    created: version.times.last_modification.map(|time| time.and_utc()),
+                    labels: if i + 1 == current_id {
+                        vec!["current".to_string()]
+                    } else {
+                        Vec::new()
+                    },
+                    value: with_values.then(|| value.to_string()),
+                })
+            })
+            .collect();
+        result.reverse();
+        result
+    }
 }
 
 #[async_trait]
@@ -356,52 +696,52 @@ impl crate::providers::Provider for KeePassProvider {
     }
 
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let (entry_path, field) = Self::parse_reference(value);
+        let reference = Self::parse_reference(value)?;
 
         tracing::debug!(
-            "Getting KeePass secret '{}' field '{}' from '{}'",
-            entry_path.join("/"),
-            field,
+            "Getting KeePass secret '{}' {:?} from '{}'",
+            reference.path.join("/"),
+            reference.target,
             self.database_path.display()
         );
 
-        let db = self.open_database()?;
+        let db = self.cached_database()?;
+        Self::read(&db, &reference)
+    }
 
-        let entry_id = Self::find_entry_id(&db, &entry_path).ok_or_else(|| {
-            FnoxError::ProviderSecretNotFound {
-                provider: "KeePass".to_string(),
-                secret: entry_path.join("/"),
-                hint: "Check that the entry exists in the database".to_string(),
-                url: "https://fnox.jdx.dev/providers/keepass".to_string(),
-            }
-        })?;
-        let entry = db.entry(entry_id).expect("entry exists");
+    /// Decrypt the database once and read every reference from it
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        tracing::debug!(
+            "Getting {} KeePass secrets from '{}'",
+            secrets.len(),
+            self.database_path.display()
+        );
 
-        entry
-            .get(field)
-            .map(|s| s.to_string())
-            .ok_or_else(|| FnoxError::ProviderInvalidResponse {
-                provider: "KeePass".to_string(),
-                details: format!(
-                    "Field '{}' not found in entry '{}'",
-                    field,
-                    entry_path.join("/")
-                ),
-                hint: "Available fields: password, username, url, notes, title".to_string(),
-                url: "https://fnox.jdx.dev/providers/keepass".to_string(),
-            })
+        let db = self.cached_database();
+        let mut results = HashMap::with_capacity(secrets.len());
+        for (name, value) in secrets {
+            let result = match &db {
+                Ok(db) => {
+                    Self::parse_reference(value).and_then(|reference| Self::read(db, &reference))
+                }
+                Err(e) => Err(e.map_batch_error(
+                    value,
+                    "KeePass",
+                    "Check that the database file exists and is readable",
+                    "https://fnox.jdx.dev/providers/keepass",
+                )),
+            };
+            results.insert(name.clone(), result);
+        }
+        results
     }
 
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
         // Parse the key to determine entry path and field
-        let (entry_path, field) = Self::parse_reference(key);
-
-        tracing::debug!(
-            "Storing KeePass secret '{}' field '{}' in '{}'",
-            entry_path.join("/"),
-            field,
-            self.database_path.display()
-        );
+        let reference = Self::parse_reference(key)?;
 
         // Check if database exists; if not, create a new one
         let mut db = if self.database_path.exists() {
@@ -415,8 +755,43 @@ impl crate::providers::Provider for KeePassProvider {
             Database::new()
         };
 
+        // Fall back to an entry titled with the whole last segment as reads
+        // do. fnox can't write attachments, so a new key with `@` names such
+        // an entry too.
+        let (entry_path, field) = if Self::find_entry_id(&db, &reference.path).is_none()
+            && let Some(literal) = reference.literal.filter(|literal| {
+                matches!(reference.target, Target::Attachment(_))
+                    || Self::find_entry_id(&db, literal).is_some()
+            }) {
+            (literal, "Password".to_string())
+        } else {
+            match reference.target {
+                Target::Field(field) => (reference.path, field),
+                Target::Attachment(name) => {
+                    return Err(FnoxError::Provider(format!(
+                        "Can't write attachment '{}' of KeePass entry '{}'; add attachments in a KeePass client",
+                        name,
+                        reference.path.join("/")
+                    )));
+                }
+                Target::Totp => {
+                    return Err(FnoxError::Provider(format!(
+                        "Can't write the TOTP code of KeePass entry '{}'; set up TOTP in a KeePass client, or write its 'otp' field with 'entry:otp'",
+                        reference.path.join("/")
+                    )));
+                }
+            }
+        };
+
+        tracing::debug!(
+            "Storing KeePass secret '{}' field '{}' in '{}'",
+            entry_path.join("/"),
+            field,
+            self.database_path.display()
+        );
+
         // Find or create the entry
-        let entry_name = Self::find_or_create_entry(&mut db, &entry_path, value, field)?;
+        let entry_name = Self::find_or_create_entry(&mut db, &entry_path, value, &field)?;
 
         // Save the database
         self.save_database(&db)?;
@@ -430,6 +805,114 @@ impl crate::providers::Provider for KeePassProvider {
         Ok(key.to_string())
     }
 
+    async fn get_history(&self, value: &str, with_values: bool) -> Result<Vec<SecretVersion>> {
+        let reference = Self::parse_reference(value)?;
+
+        tracing::debug!(
+            "Reading history of KeePass secret '{}' {:?} from '{}'",
+            reference.path.join("/"),
+            reference.target,
+            self.database_path.display()
+        );
+
+        let db = self.cached_database()?;
+        let (entry_id, target) =
+            Self::resolve(&db, &reference).ok_or_else(|| Self::entry_not_found(&reference.path))?;
+        let field = match target {
+            Target::Field(field) => field,
+            Target::Attachment(_) => {
+                return Err(FnoxError::Provider(format!(
+                    "KeePass keeps no history of attachments such as '{}'",
+                    value
+                )));
+            }
+            Target::Totp => {
+                return Err(FnoxError::Provider(format!(
+                    "TOTP codes such as '{}' are generated when read, so they have no history",
+                    value
+                )));
+            }
+        };
+        let entry = db.entry(entry_id).expect("entry exists");
+
+        Ok(Self::entry_versions(&entry, &field, with_values))
+    }
+
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        // A database that doesn't exist yet has no entries
+        if !self.database_path.exists() {
+            return Ok(Vec::new());
+        }
+        let db = self.cached_database()?;
+
+        let mut references = Vec::new();
+        Self::collect_references(db.root(), &mut Vec::new(), &mut references);
+        references.retain(|reference| prefix.is_none_or(|prefix| reference.starts_with(prefix)));
+        references.sort();
+        Ok(references)
+    }
+
+    /// Rename the entry instead of copying it, so its other fields and
+    /// history are kept. A bare title keeps the entry in its groups; a path
+    /// moves it under that path's groups, creating any that are missing.
+    async fn move_secret(&self, value: &str, new_key: &str) -> Result<String> {
+        let reference = Self::parse_reference(value)?;
+        let new_reference = reference::KEEPASS.parse(new_key)?;
+        let mut db = self.open_database()?;
+        let resolved = Self::resolve(&db, &reference);
+        if resolved
+            .as_ref()
+            .is_some_and(|(_, target)| *target != Target::password())
+            || new_reference.field.is_some()
+        {
+            return Err(FnoxError::Provider(format!(
+                "Can only move whole KeePass entries, not '{}' to '{}'",
+                value, new_key
+            )));
+        }
+
+        let source_groups = &reference.path[..reference.path.len() - 1];
+        let new_path = if new_reference.path.len() == 1 {
+            let mut path = source_groups.to_vec();
+            path.push(new_reference.path[0].clone());
+            path
+        } else {
+            new_reference.path.clone()
+        };
+        if Self::find_entry_id(&db, &new_path).is_some() {
+            return Err(FnoxError::Provider(format!(
+                "KeePass entry '{}' already exists",
+                new_path.join("/")
+            )));
+        }
+        let (entry_id, _) = resolved.ok_or_else(|| Self::entry_not_found(&reference.path))?;
+
+        tracing::debug!(
+            "Moving KeePass entry '{}' to '{}' in '{}'",
+            reference.path.join("/"),
+            new_path.join("/"),
+            self.database_path.display()
+        );
+        let (title, target_groups) = new_path.split_last().expect("path is not empty");
+        let target_group_id = (target_groups != source_groups)
+            .then(|| Self::navigate_or_create_group_path(&mut db, target_groups));
+        let mut entry_mut = db.entry_mut(entry_id).expect("entry exists");
+        entry_mut.set_unprotected("Title", title);
+        if let Some(group_id) = target_group_id {
+            entry_mut.move_to(group_id).map_err(|e| {
+                FnoxError::Provider(format!(
+                    "Failed to move KeePass entry '{}' to '{}': {}",
+                    value,
+                    new_path.join("/"),
+                    e
+                ))
+            })?;
+        }
+        self.save_database(&db)?;
+
+        Ok(new_path.join("/"))
+    }
+
     async fn test_connection(&self) -> Result<()> {
         tracing::debug!(
             "Testing connection to KeePass database '{}'",
@@ -471,6 +954,12 @@ pub fn env_dependencies() -> &'static [&'static str] {
     &["KEEPASS_PASSWORD", "FNOX_KEEPASS_PASSWORD"]
 }
 
+/// Whether `value` reads an entry's TOTP code, which changes every period
+/// and so mustn't be cached
+pub fn is_totp_reference(value: &str) -> bool {
+    KeePassProvider::parse_reference(value).is_ok_and(|reference| reference.target == Target::Totp)
+}
+
 /// Read KeePass password from environment
 fn keepass_password() -> Option<String> {
     std::env::var("FNOX_KEEPASS_PASSWORD")
@@ -482,51 +971,423 @@ fn keepass_password() -> Option<String> {
 mod tests {
     use super::*;
 
+    fn parse(value: &str) -> (Vec<String>, Target) {
+        let reference = KeePassProvider::parse_reference(value).unwrap();
+        (reference.path, reference.target)
+    }
+
+    fn field(name: &str) -> Target {
+        Target::Field(name.to_string())
+    }
+
     #[test]
     fn test_parse_reference_simple() {
-        let (path, field) = KeePassProvider::parse_reference("my-entry");
+        let (path, target) = parse("my-entry");
         assert_eq!(path, vec!["my-entry"]);
-        assert_eq!(field, "Password");
+        assert_eq!(target, field("Password"));
     }
 
     #[test]
     fn test_parse_reference_with_field() {
-        let (path, field) = KeePassProvider::parse_reference("my-entry/username");
+        let (path, target) = parse("my-entry/username");
         assert_eq!(path, vec!["my-entry"]);
-        assert_eq!(field, "UserName");
+        assert_eq!(target, field("UserName"));
 
-        let (path, field) = KeePassProvider::parse_reference("my-entry/password");
+        let (path, target) = parse("my-entry/password");
         assert_eq!(path, vec!["my-entry"]);
-        assert_eq!(field, "Password");
+        assert_eq!(target, field("Password"));
 
-        let (path, field) = KeePassProvider::parse_reference("my-entry/url");
+        let (path, target) = parse("my-entry/url");
         assert_eq!(path, vec!["my-entry"]);
-        assert_eq!(field, "URL");
+        assert_eq!(target, field("URL"));
 
-        let (path, field) = KeePassProvider::parse_reference("my-entry/notes");
+        let (path, target) = parse("my-entry/notes");
         assert_eq!(path, vec!["my-entry"]);
-        assert_eq!(field, "Notes");
+        assert_eq!(target, field("Notes"));
     }
 
     #[test]
     fn test_parse_reference_with_group() {
-        let (path, field) = KeePassProvider::parse_reference("group/my-entry");
+        let (path, target) = parse("group/my-entry");
         assert_eq!(path, vec!["group", "my-entry"]);
-        assert_eq!(field, "Password");
+        assert_eq!(target, field("Password"));
 
-        let (path, field) = KeePassProvider::parse_reference("group/subgroup/my-entry");
+        let (path, target) = parse("group/subgroup/my-entry");
         assert_eq!(path, vec!["group", "subgroup", "my-entry"]);
-        assert_eq!(field, "Password");
+        assert_eq!(target, field("Password"));
+    }
+
+    #[tokio::test]
+    async fn test_history_lists_previous_values() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("history.kdbx").display().to_string(),
+            None,
+            Some("history-test-password".to_string()),
+        )
+        .unwrap();
+        for value in ["first", "second", "third"] {
+            provider.put_secret("group/api-key", value).await.unwrap();
+        }
+        provider.put_secret("other", "unrelated").await.unwrap();
+
+        let history = provider.get_history("group/api-key", true).await.unwrap();
+        assert_eq!(history.len(), 3, "{history:?}");
+        assert_eq!(history[0].id, "3");
+        assert_eq!(history[0].labels, vec!["current"]);
+        assert_eq!(history[0].value.as_deref(), Some("third"));
+        assert!(history[0].created.is_some());
+        assert!(history[1..].iter().all(|v| v.labels.is_empty()));
+
+        assert_eq!(history[1].value.as_deref(), Some("second"));
+        assert_eq!(history[2].value.as_deref(), Some("first"));
+
+        let history = provider.get_history("other", true).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].value.as_deref(), Some("unrelated"));
+
+        let history = provider.get_history("other", false).await.unwrap();
+        assert_eq!(history[0].value, None);
+    }
+
+    #[tokio::test]
+    async fn test_older_versions_survive_reopening_the_database() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reopen.kdbx").display().to_string();
+        let password = Some("reopen-test-password".to_string());
+        let provider = KeePassProvider::new(path.clone(), None, password.clone()).unwrap();
+        provider.put_secret("db-password", "old").await.unwrap();
+        provider.put_secret("db-password", "new").await.unwrap();
+
+        let reopened = KeePassProvider::new(path, None, password).unwrap();
+        assert_eq!(reopened.get_secret("db-password").await.unwrap(), "new");
+        let history = reopened.get_history("db-password", true).await.unwrap();
+        assert_eq!(history.len(), 2, "{history:?}");
+        assert_eq!(history[1].id, "1");
+        assert_eq!(history[1].value.as_deref(), Some("old"));
+    }
+
+    #[tokio::test]
+    async fn test_list_secrets_returns_entry_references() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("list.kdbx").display().to_string(),
+            None,
+            Some("list-test-password".to_string()),
+        )
+        .unwrap();
+        assert!(provider.list_secrets(None).await.unwrap().is_empty());
+
+        provider.put_secret("prod/db/password", "a").await.unwrap();
+        provider.put_secret("prod/api-key", "b").await.unwrap();
+        provider.put_secret("standalone", "c").await.unwrap();
+
+        assert_eq!(
+            provider.list_secrets(None).await.unwrap(),
+            vec!["prod/api-key", "prod/db", "standalone"]
+        );
+        assert_eq!(
+            provider.list_secrets(Some("prod/")).await.unwrap(),
+            vec!["prod/api-key", "prod/db"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_keeps_or_changes_the_entry_groups() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("move.kdbx").display().to_string(),
+            None,
+            Some("move-test-password".to_string()),
+        )
+        .unwrap();
+        provider.put_secret("prod/db/old", "pw").await.unwrap();
+
+        // A bare title renames the entry where it is
+        let moved = provider.move_secret("prod/db/old", "new").await.unwrap();
+        assert_eq!(moved, "prod/db/new");
+        assert_eq!(
+            provider.list_secrets(None).await.unwrap(),
+            vec!["prod/db/new"]
+        );
+
+        // A path moves it under those groups
+        let moved = provider
+            .move_secret("prod/db/new", "staging/db/new")
+            .await
+            .unwrap();
+        assert_eq!(moved, "staging/db/new");
+        assert_eq!(
+            provider.list_secrets(None).await.unwrap(),
+            vec!["staging/db/new"]
+        );
+        assert_eq!(provider.get_secret("staging/db/new").await.unwrap(), "pw");
     }
 
     #[test]
     fn test_parse_reference_with_group_and_field() {
-        let (path, field) = KeePassProvider::parse_reference("group/my-entry/username");
+        let (path, target) = parse("group/my-entry/username");
         assert_eq!(path, vec!["group", "my-entry"]);
-        assert_eq!(field, "UserName");
+        assert_eq!(target, field("UserName"));
 
-        let (path, field) = KeePassProvider::parse_reference("group/subgroup/my-entry/password");
+        let (path, target) = parse("group/subgroup/my-entry/password");
         assert_eq!(path, vec!["group", "subgroup", "my-entry"]);
-        assert_eq!(field, "Password");
+        assert_eq!(target, field("Password"));
+    }
+
+    #[test]
+    fn test_parse_reference_with_custom_field_and_attachment() {
+        let (path, target) = parse("my-entry:API_TOKEN");
+        assert_eq!(path, vec!["my-entry"]);
+        assert_eq!(target, field("API_TOKEN"));
+
+        let (path, target) = parse("group/my-entry:API_TOKEN");
+        assert_eq!(path, vec!["group", "my-entry"]);
+        assert_eq!(target, field("API_TOKEN"));
+
+        let (path, target) = parse("group/my-entry@cert.pem");
+        assert_eq!(path, vec!["group", "my-entry"]);
+        assert_eq!(target, Target::Attachment("cert.pem".to_string()));
+
+        // The last marker wins, so titles can contain the other one
+        let (path, target) = parse("deploy@host:TOKEN");
+        assert_eq!(path, vec!["deploy@host"]);
+        assert_eq!(target, field("TOKEN"));
+    }
+
+    #[test]
+    fn test_totp_references_are_recognized() {
+        assert!(is_totp_reference("github/totp"));
+        assert!(is_totp_reference("work/github/TOTP"));
+        assert!(!is_totp_reference("github"));
+        assert!(!is_totp_reference("github:totp"));
+    }
+
+    #[test]
+    fn test_parse_reference_group_path_is_not_a_custom_field() {
+        // Only standard field names are fields after a `/`
+        let (path, target) = parse("my-entry/API_TOKEN");
+        assert_eq!(path, vec!["my-entry", "API_TOKEN"]);
+        assert_eq!(target, field("Password"));
+
+        // A marker at either end is part of the title
+        let (path, target) = parse(":entry");
+        assert_eq!(path, vec![":entry"]);
+        assert_eq!(target, field("Password"));
+        let (path, target) = parse("entry@");
+        assert_eq!(path, vec!["entry@"]);
+        assert_eq!(target, field("Password"));
+
+        // A `:` in a group name isn't a field marker
+        let (path, target) = parse("team:ops/my-entry");
+        assert_eq!(path, vec!["team:ops", "my-entry"]);
+        assert_eq!(target, field("Password"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_fields_round_trip() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("fields.kdbx").display().to_string(),
+            None,
+            Some("fields-test-password".to_string()),
+        )
+        .unwrap();
+        provider.put_secret("group/svc", "hunter2").await.unwrap();
+        provider
+            .put_secret("group/svc:API_TOKEN", "tok-123")
+            .await
+            .unwrap();
+
+        assert_eq!(provider.get_secret("group/svc").await.unwrap(), "hunter2");
+        assert_eq!(
+            provider.get_secret("group/svc:API_TOKEN").await.unwrap(),
+            "tok-123"
+        );
+        assert!(provider.get_secret("group/svc:MISSING").await.is_err());
+        assert!(provider.get_secret("group/svc@cert.pem").await.is_err());
+        // Attachments can't be written
+        assert!(
+            provider
+                .put_secret("group/svc@cert.pem", "x")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attachments_are_read_base64_encoded() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("attachments.kdbx").display().to_string(),
+            None,
+            Some("attachments-test-password".to_string()),
+        )
+        .unwrap();
+        provider.put_secret("group/svc", "hunter2").await.unwrap();
+
+        // fnox can't write attachments, so add one the way a KeePass client would
+        let mut db = provider.open_database().unwrap();
+        let entry_id =
+            KeePassProvider::find_entry_id(&db, &["group".to_string(), "svc".to_string()]).unwrap();
+        db.entry_mut(entry_id).unwrap().add_attachment(
+            "cert.pem",
+            keepass::db::Value::unprotected(b"\x00cert".to_vec()),
+        );
+        provider.save_database(&db).unwrap();
+
+        assert_eq!(
+            provider.get_secret("group/svc@cert.pem").await.unwrap(),
+            base64::engine::general_purpose::STANDARD.encode(b"\x00cert")
+        );
+        assert!(provider.get_secret("group/svc@other.pem").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_reads_every_reference_and_sees_writes() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch.kdbx");
+        let provider = KeePassProvider::new(
+            path.display().to_string(),
+            None,
+            Some("batch-test-password".to_string()),
+        )
+        .unwrap();
+        provider.put_secret("db", "pg-pass").await.unwrap();
+        provider.put_secret("group/api", "api-pass").await.unwrap();
+
+        let secrets = [
+            ("DB".to_string(), "db".to_string()),
+            ("API".to_string(), "group/api".to_string()),
+            ("MISSING".to_string(), "nope".to_string()),
+        ];
+        let results = provider.get_secrets_batch(&secrets).await;
+        assert_eq!(results["DB"].as_ref().unwrap(), "pg-pass");
+        assert_eq!(results["API"].as_ref().unwrap(), "api-pass");
+        assert!(results["MISSING"].is_err());
+
+        // A write replaces the cached database instead of being hidden by it
+        provider.put_secret("db", "rotated").await.unwrap();
+        assert_eq!(provider.get_secret("db").await.unwrap(), "rotated");
+
+        // Other credentials don't get the cached copy
+        let wrong = KeePassProvider::new(
+            path.display().to_string(),
+            None,
+            Some("not-the-password".to_string()),
+        )
+        .unwrap();
+        let results = wrong.get_secrets_batch(&secrets).await;
+        assert!(results.values().all(|result| matches!(
+            result,
+            Err(FnoxError::ProviderAuthFailed { hint, .. }) if hint.contains("password")
+        )));
+
+        // The cache keeps a hash of the password, not the password
+        let cached = format!("{:?}", DATABASES.lock().unwrap().keys().collect::<Vec<_>>());
+        assert!(!cached.contains("batch-test-password"));
+    }
+
+    #[tokio::test]
+    async fn test_totp_codes_from_otp_fields() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("totp.kdbx").display().to_string(),
+            None,
+            Some("totp-test-password".to_string()),
+        )
+        .unwrap();
+        // base32 of "12345678901234567890"
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        provider.put_secret("github", "pass").await.unwrap();
+        provider
+            .put_secret(
+                "github:otp",
+                &format!(
+                    "otpauth://totp/GitHub:alice?secret={}&issuer=GitHub",
+                    secret
+                ),
+            )
+            .await
+            .unwrap();
+        provider.put_secret("keeotp", "pass").await.unwrap();
+        provider
+            .put_secret("keeotp:TimeOtp-Secret-Base32", secret)
+            .await
+            .unwrap();
+        provider
+            .put_secret("keeotp:TimeOtp-Length", "8")
+            .await
+            .unwrap();
+        provider.put_secret("plain", "pass").await.unwrap();
+
+        // A code may roll over between reading it and computing the expected one
+        let expected = |digits| {
+            let totp = Totp::new(b"12345678901234567890".to_vec())
+                .with_digits(digits)
+                .unwrap();
+            move |code: &str| {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                code == totp.code_at(now) || code == totp.code_at(now - 30)
+            }
+        };
+        let code = provider.get_secret("github/totp").await.unwrap();
+        assert_eq!(code.len(), 6);
+        assert!(expected(6)(&code), "unexpected code {code}");
+        let code = provider.get_secret("keeotp/TOTP").await.unwrap();
+        assert_eq!(code.len(), 8);
+        assert!(expected(8)(&code), "unexpected code {code}");
+
+        let err = provider.get_secret("plain/totp").await.unwrap_err();
+        assert!(
+            err.to_string().contains("has no TOTP configuration"),
+            "{err}"
+        );
+        assert!(provider.put_secret("github/totp", "123456").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_titles_with_markers_still_resolve() {
+        use crate::providers::Provider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KeePassProvider::new(
+            dir.path().join("titles.kdbx").display().to_string(),
+            None,
+            Some("titles-test-password".to_string()),
+        )
+        .unwrap();
+        provider
+            .put_secret("admin@example.com", "s3cret")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            provider.list_secrets(None).await.unwrap(),
+            vec!["admin@example.com"]
+        );
+        assert_eq!(
+            provider.get_secret("admin@example.com").await.unwrap(),
+            "s3cret"
+        );
     }
 }
@@ -1,4 +1,5 @@
 use crate::error::{FnoxError, Result};
+use crate::providers::prefix::KeyPrefix;
 use async_trait::async_trait;
 use keyring_core::Entry;
 use std::collections::HashMap;
@@ -9,47 +10,31 @@ pub fn env_dependencies() -> &'static [&'static str] {
 
 pub struct KeychainProvider {
     service: String,
-    prefix: Option<String>,
+    prefix: KeyPrefix,
+    require_biometrics: bool,
 }
 
 impl KeychainProvider {
-    pub fn new(service: String, prefix: Option<String>) -> Result<Self> {
-        Ok(Self { service, prefix })
+    pub fn new(
+        service: String,
+        prefix: Option<String>,
+        require_biometrics: Option<bool>,
+    ) -> Result<Self> {
+        Ok(Self {
+            service,
+            prefix: KeyPrefix::new(prefix),
+            require_biometrics: require_biometrics.unwrap_or(false),
+        })
     }
 
     /// Build the full key name with optional prefix
     fn build_key_name(&self, key: &str) -> String {
-        match &self.prefix {
-            Some(prefix) => format!("{}{}", prefix, key),
-            None => key.to_string(),
-        }
+        self.prefix.apply(key)
     }
 
     /// Create a keyring entry
     fn create_entry(&self, key: &str) -> Result<Entry> {
-        crate::keyring_store::init();
-        let full_key = self.build_key_name(key);
-        Entry::new(&self.service, &full_key).map_err(|e| match e {
-            // `Entry::new` itself fails with `NoDefaultStore` when the platform
-            // backend couldn't be registered (e.g. headless Linux without
-            // Secret Service). Surface a backend-specific hint here, since the
-            // call never reaches `get_password`/`set_password`.
-            keyring_core::Error::NoDefaultStore => FnoxError::ProviderAuthFailed {
-                provider: "Keychain".to_string(),
-                details: e.to_string(),
-                hint: platform_backend_hint().to_string(),
-                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
-            },
-            _ => FnoxError::ProviderApiError {
-                provider: "Keychain".to_string(),
-                details: format!(
-                    "Failed to create entry for service '{}', key '{}': {}",
-                    self.service, full_key, e
-                ),
-                hint: "Check that the keychain is accessible".to_string(),
-                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
-            },
-        })
+        open_entry(&self.service, &self.build_key_name(key))
     }
 
     /// Store a secret in the OS keychain.
@@ -60,7 +45,6 @@ impl KeychainProvider {
     /// keeps making progress (and so concurrent calls don't pin every worker
     /// thread).
     pub async fn put_secret(&self, key: &str, value: &str) -> Result<()> {
-        let entry = self.create_entry(key)?;
         let full_key = self.build_key_name(key);
         let service = self.service.clone();
 
@@ -71,6 +55,20 @@ impl KeychainProvider {
             full_key
         );
 
+        if self.require_biometrics {
+            let value = value.to_string();
+            let set_full_key = full_key.clone();
+            spawn_keychain_blocking(move || biometric::set(&service, &set_full_key, &value))
+                .await??;
+            tracing::debug!(
+                "Stored secret '{}' behind biometric access control (service: '{}')",
+                full_key,
+                self.service
+            );
+            return Ok(());
+        }
+
+        let entry = self.create_entry(key)?;
         let value = value.to_string();
         let set_full_key = full_key.clone();
         let set_service = service.clone();
@@ -101,10 +99,23 @@ impl crate::providers::Provider for KeychainProvider {
         vec![crate::providers::ProviderCapability::RemoteStorage]
     }
 
+    /// Windows Credential Manager caps a credential at 2560 bytes, and
+    /// passwords are stored as UTF-16. The macOS Keychain and Secret Service
+    /// have no practical limit.
+    #[cfg(target_os = "windows")]
+    fn max_value_size(&self) -> Option<crate::providers::ValueSizeLimit> {
+        Some(crate::providers::ValueSizeLimit {
+            provider: "Keychain",
+            bytes: 1280,
+            hint: crate::providers::LARGE_VALUE_HINT.to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain",
+        })
+    }
+
     async fn get_secret(&self, value: &str) -> Result<String> {
-        let entry = self.create_entry(value)?;
         let full_key = self.build_key_name(value);
         let service = self.service.clone();
+        let require_biometrics = self.require_biometrics;
 
         tracing::debug!(
             "Getting secret '{}' from OS keychain (service: '{}')",
@@ -112,7 +123,118 @@ impl crate::providers::Provider for KeychainProvider {
             service
         );
 
-        spawn_keychain_blocking(move || entry.get_password())
+        spawn_keychain_blocking(move || read_secret(&service, &full_key, require_biometrics))
+            .await?
+    }
+
+    /// Override the default parallel `get_secrets_batch` to fetch every
+    /// entry in one keychain session.
+    ///
+    /// The OS keychain API is synchronous and can pop a confirmation dialog
+    /// per access. Running them concurrently would surface several
+    /// overlapping dialogs and — once the number of secrets reaches the
+    /// tokio worker-thread count — would also deadlock the runtime even with
+    /// `spawn_blocking`, since the runtime still needs at least one free
+    /// thread to drive completions. So the whole batch runs in a single
+    /// blocking task: on Linux it shares one Secret Service connection and
+    /// unlocks all locked items with one prompt; elsewhere the reads run one
+    /// after another.
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        let lookups: Vec<(String, String)> = secrets
+            .iter()
+            .map(|(key, value)| (key.clone(), self.build_key_name(value)))
+            .collect();
+        let service = self.service.clone();
+        let require_biometrics = self.require_biometrics;
+
+        tracing::debug!(
+            "Getting {} secrets from OS keychain in one session (service: '{}')",
+            lookups.len(),
+            service
+        );
+
+        match spawn_keychain_blocking(move || read_secrets(&service, &lookups, require_biometrics))
+            .await
+        {
+            Ok(results) => results,
+            Err(e) => secrets
+                .iter()
+                .map(|(key, _)| {
+                    let err = FnoxError::ProviderApiError {
+                        provider: "Keychain".to_string(),
+                        details: e.to_string(),
+                        hint: "This is a bug; please report it".to_string(),
+                        url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+                    };
+                    (key.clone(), Err(err))
+                })
+                .collect(),
+        }
+    }
+
+    /// Probe the keychain with a read of an entry that shouldn't exist.
+    ///
+    /// This writes nothing, but still goes all the way to the OS backend, so
+    /// a locked keychain or a missing Secret Service daemon is reported here
+    /// rather than on the first real lookup. A missing entry means the
+    /// keychain answered, which is all we're checking.
+    async fn test_connection(&self) -> Result<()> {
+        let entry = self.create_entry(PROBE_KEY)?;
+        let service = self.service.clone();
+
+        let result = spawn_keychain_blocking(move || entry.get_password()).await?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => probe_result(&service, e),
+        }
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        self.put_secret(key, value).await?;
+        // Return the key name to store in config, relative to the prefix
+        Ok(key.to_string())
+    }
+
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        let key_prefix = self.build_key_name(prefix.unwrap_or_default());
+        let service = self.service.clone();
+
+        tracing::debug!(
+            "Listing secrets in OS keychain (service: '{}') starting with '{}'",
+            service,
+            key_prefix
+        );
+
+        let accounts = spawn_keychain_blocking(move || list_accounts(&service)).await??;
+        let mut references: Vec<String> = accounts
+            .iter()
+            .filter(|account| account.starts_with(&key_prefix))
+            .map(|account| self.prefix.strip(account).to_string())
+            .collect();
+        references.sort();
+        references.dedup();
+        Ok(references)
+    }
+
+    async fn delete_secret(&self, value: &str) -> Result<()> {
+        let full_key = self.build_key_name(value);
+        let service = self.service.clone();
+
+        tracing::debug!(
+            "Deleting secret '{}' from OS keychain (service: '{}')",
+            full_key,
+            service
+        );
+
+        if self.require_biometrics {
+            return spawn_keychain_blocking(move || biometric::delete(&service, &full_key)).await?;
+        }
+        let entry = self.create_entry(value)?;
+
+        spawn_keychain_blocking(move || entry.delete_credential())
             .await?
             .map_err(|e| match e {
                 keyring_core::Error::NoEntry => FnoxError::ProviderSecretNotFound {
@@ -124,71 +246,442 @@ impl crate::providers::Provider for KeychainProvider {
                     ),
                     url: "https://fnox.jdx.dev/providers/keychain".to_string(),
                 },
-                keyring_core::Error::NoStorageAccess(_) => FnoxError::ProviderAuthFailed {
-                    provider: "Keychain".to_string(),
-                    details: e.to_string(),
-                    hint: "Check that the keychain is unlocked and accessible".to_string(),
-                    url: "https://fnox.jdx.dev/providers/keychain".to_string(),
-                },
                 _ => FnoxError::ProviderApiError {
                     provider: "Keychain".to_string(),
-                    details: e.to_string(),
-                    hint: format!(
-                        "Failed to get secret from keychain (service: '{}')",
-                        service
+                    details: format!(
+                        "Failed to delete secret '{}' (service: '{}'): {}",
+                        full_key, service, e
                     ),
+                    hint: "Check that the keychain is accessible and writable".to_string(),
                     url: "https://fnox.jdx.dev/providers/keychain".to_string(),
                 },
             })
     }
+}
 
-    /// Override the default parallel `get_secrets_batch` to fetch keychain
-    /// entries sequentially.
-    ///
-    /// The OS keychain API is synchronous and can pop a confirmation dialog
-    /// per access. Running them concurrently would surface several
-    /// overlapping dialogs and — once the number of secrets reaches the
-    /// tokio worker-thread count — would also deadlock the runtime even with
-    /// `spawn_blocking`, since the runtime still needs at least one free
-    /// thread to drive completions.
-    async fn get_secrets_batch(
-        &self,
-        secrets: &[(String, String)],
-    ) -> HashMap<String, Result<String>> {
-        let mut results = HashMap::with_capacity(secrets.len());
-        for (key, value) in secrets {
-            results.insert(key.clone(), self.get_secret(value).await);
+/// Open the keyring entry for `full_key` in the default store
+fn open_entry(service: &str, full_key: &str) -> Result<Entry> {
+    crate::keyring_store::init();
+    Entry::new(service, full_key).map_err(|e| match e {
+        // `Entry::new` itself fails with `NoDefaultStore` when the platform
+        // backend couldn't be registered (e.g. headless Linux without
+        // Secret Service). Surface a backend-specific hint here, since the
+        // call never reaches `get_password`/`set_password`.
+        keyring_core::Error::NoDefaultStore => FnoxError::ProviderAuthFailed {
+            provider: "Keychain".to_string(),
+            details: e.to_string(),
+            hint: platform_backend_hint().to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        },
+        _ => FnoxError::ProviderApiError {
+            provider: "Keychain".to_string(),
+            details: format!(
+                "Failed to create entry for service '{}', key '{}': {}",
+                service, full_key, e
+            ),
+            hint: "Check that the keychain is accessible".to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        },
+    })
+}
+
+/// Read one secret. Blocks, and may wait on a system dialog.
+fn read_secret(service: &str, full_key: &str, require_biometrics: bool) -> Result<String> {
+    if require_biometrics {
+        return biometric::get(service, full_key);
+    }
+    open_entry(service, full_key)?
+        .get_password()
+        .map_err(|e| read_error(service, full_key, e))
+}
+
+/// Read `(key, full_key)` pairs in one session, keyed by `key`. Blocks.
+fn read_secrets(
+    service: &str,
+    lookups: &[(String, String)],
+    require_biometrics: bool,
+) -> HashMap<String, Result<String>> {
+    #[cfg(target_os = "macos")]
+    if require_biometrics {
+        return biometric::get_all(service, lookups);
+    }
+    #[cfg(target_os = "linux")]
+    if !require_biometrics {
+        match secret_service::read_all(service, lookups) {
+            Ok(results) => return results,
+            Err(e) => tracing::debug!(
+                "Couldn't share a Secret Service session, reading items one at a time: {e}"
+            ),
         }
-        results
     }
+    lookups
+        .iter()
+        .map(|(key, full_key)| {
+            (
+                key.clone(),
+                read_secret(service, full_key, require_biometrics),
+            )
+        })
+        .collect()
+}
 
-    async fn test_connection(&self) -> Result<()> {
-        // Try to create an entry with a test key to verify keychain access
-        let test_key = "__fnox_test__";
-        let entry = self.create_entry(test_key)?;
-        let service = self.service.clone();
+/// The account (full key) of every item stored for `service`. Blocks.
+#[cfg(target_os = "linux")]
+fn list_accounts(service: &str) -> Result<Vec<String>> {
+    secret_service::accounts(service).map_err(|e| list_error(service, e))
+}
+
+/// The account (full key) of every item stored for `service`, apart from
+/// `require_biometrics` items, which are in the data protection keychain.
+/// Blocks.
+#[cfg(target_os = "macos")]
+fn list_accounts(service: &str) -> Result<Vec<String>> {
+    use security_framework::item::{ItemClass, ItemSearchOptions, Limit};
+
+    /// `errSecItemNotFound`
+    const ITEM_NOT_FOUND: i32 = -25300;
+
+    let results = match ItemSearchOptions::new()
+        .class(ItemClass::generic_password())
+        .service(service)
+        .load_attributes(true)
+        .limit(Limit::All)
+        .search()
+    {
+        Ok(results) => results,
+        Err(e) if e.code() == ITEM_NOT_FOUND => return Ok(Vec::new()),
+        Err(e) => return Err(list_error(service, e)),
+    };
+    Ok(results
+        .iter()
+        .filter_map(|result| result.simplify_dict()?.remove("acct"))
+        .collect())
+}
+
+/// Listing is only implemented for the Secret Service and the macOS Keychain
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn list_accounts(_service: &str) -> Result<Vec<String>> {
+    Err(FnoxError::Provider(
+        "Listing keychain secrets is only supported on Linux and macOS".to_string(),
+    ))
+}
 
-        // Run all three blocking operations on a single background thread to
-        // avoid hopping through the runtime three times.
-        spawn_keychain_blocking(move || {
-            entry.set_password("test")?;
-            entry.get_password()?;
-            let _ = entry.delete_credential();
-            Ok(())
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn list_error(service: &str, e: impl std::fmt::Display) -> FnoxError {
+    FnoxError::ProviderApiError {
+        provider: "Keychain".to_string(),
+        details: format!("Failed to list secrets (service: '{}'): {}", service, e),
+        hint: "Check that the keychain is accessible".to_string(),
+        url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+    }
+}
+
+fn read_error(service: &str, full_key: &str, e: keyring_core::Error) -> FnoxError {
+    match e {
+        keyring_core::Error::NoEntry => not_found(service, full_key),
+        keyring_core::Error::NoStorageAccess(_) => FnoxError::ProviderAuthFailed {
+            provider: "Keychain".to_string(),
+            details: e.to_string(),
+            hint: "Check that the keychain is unlocked and accessible".to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        },
+        _ => FnoxError::ProviderApiError {
+            provider: "Keychain".to_string(),
+            details: e.to_string(),
+            hint: format!(
+                "Failed to get secret from keychain (service: '{}')",
+                service
+            ),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        },
+    }
+}
+
+fn not_found(service: &str, full_key: &str) -> FnoxError {
+    FnoxError::ProviderSecretNotFound {
+        provider: "Keychain".to_string(),
+        secret: full_key.to_string(),
+        hint: format!(
+            "Check that the secret exists in the keychain (service: '{}')",
+            service
+        ),
+        url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+    }
+}
+
+/// Batch reads over a single Secret Service connection. keyring-core opens a
+/// connection per entry, which for a large batch means a D-Bus handshake and
+/// collection lookup per secret. Secrets are transferred over a
+/// Diffie-Hellman session, never in plain text on the bus.
+#[cfg(target_os = "linux")]
+mod secret_service {
+    use super::not_found;
+    use crate::error::{FnoxError, Result};
+    use dbus_secret_service::{EncryptionType, Item, SecretService};
+    use std::collections::HashMap;
+
+    /// Read every lookup, unlocking the locked ones together so the user is
+    /// asked once. Fails only when the connection itself can't be made, so
+    /// the caller can fall back to keyring-core.
+    pub(super) fn read_all(
+        service: &str,
+        lookups: &[(String, String)],
+    ) -> std::result::Result<HashMap<String, Result<String>>, dbus_secret_service::Error> {
+        let ss = SecretService::connect(EncryptionType::Dh)?;
+
+        let mut found = Vec::with_capacity(lookups.len());
+        let mut locked = Vec::new();
+        for (_, full_key) in lookups {
+            // The attributes keyring-core's Secret Service store writes
+            let attributes = HashMap::from([("service", service), ("username", full_key.as_str())]);
+            let item = ss.search_items(attributes).map(|mut search| {
+                search.unlocked.pop().or_else(|| {
+                    let item = search.locked.pop();
+                    if item.is_some() {
+                        locked.push(found.len());
+                    }
+                    item
+                })
+            });
+            found.push(item);
+        }
+
+        if !locked.is_empty() {
+            let items: Vec<&Item<'_>> = locked
+                .iter()
+                .filter_map(|&i| found[i].as_ref().ok().and_then(Option::as_ref))
+                .collect();
+            if let Err(e) = ss.unlock_all(&items) {
+                tracing::debug!("Unlocking {} keychain items failed: {e}", items.len());
+            }
+        }
+
+        Ok(lookups
+            .iter()
+            .zip(found)
+            .map(|((key, full_key), item)| {
+                let secret = match item {
+                    Ok(Some(item)) => item.get_secret(),
+                    Ok(None) => return (key.clone(), Err(not_found(service, full_key))),
+                    Err(e) => Err(e),
+                };
+                let result = secret
+                    .map_err(|e| FnoxError::ProviderAuthFailed {
+                        provider: "Keychain".to_string(),
+                        details: format!("Failed to read '{full_key}': {e}"),
+                        hint: "Check that the keychain is unlocked and accessible".to_string(),
+                        url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+                    })
+                    .and_then(|secret| {
+                        String::from_utf8(secret).map_err(|e| FnoxError::ProviderInvalidResponse {
+                            provider: "Keychain".to_string(),
+                            details: format!("'{full_key}' isn't valid UTF-8: {e}"),
+                            hint: "Store the secret as text".to_string(),
+                            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+                        })
+                    });
+                (key.clone(), result)
+            })
+            .collect())
+    }
+
+    /// The `username` (full key) of every item keyring-core stored for
+    /// `service`, locked or not. Attributes can be read without unlocking.
+    pub(super) fn accounts(
+        service: &str,
+    ) -> std::result::Result<Vec<String>, dbus_secret_service::Error> {
+        let ss = SecretService::connect(EncryptionType::Dh)?;
+        let search = ss.search_items(HashMap::from([("service", service)]))?;
+        let mut accounts = Vec::new();
+        for item in search.unlocked.iter().chain(&search.locked) {
+            if let Some(account) = item.get_attributes()?.remove("username") {
+                accounts.push(account);
+            }
+        }
+        Ok(accounts)
+    }
+}
+
+/// Items stored with `require_biometrics`, which carry an access control
+/// that asks for Touch ID (or the login password) before they're read. Access
+/// controls only exist in the data protection keychain, which keyring-core's
+/// store doesn't use, so these go through the Security framework directly.
+#[cfg(target_os = "macos")]
+mod biometric {
+    use super::not_found;
+    use crate::error::{FnoxError, Result};
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::data::CFData;
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::string::{CFString, CFStringRef};
+    use objc2::rc::Retained;
+    use objc2_local_authentication::LAContext;
+    use security_framework::passwords::{
+        delete_generic_password_options, generic_password, set_generic_password_options,
+    };
+    use security_framework::passwords_options::{AccessControlOptions, PasswordOptions};
+    use security_framework_sys::base::errSecSuccess;
+    use security_framework_sys::item::{
+        kSecAttrAccount, kSecAttrService, kSecClass, kSecClassGenericPassword, kSecReturnData,
+    };
+    use security_framework_sys::keychain_item::SecItemCopyMatching;
+    use std::collections::HashMap;
+
+    #[link(name = "Security", kind = "framework")]
+    unsafe extern "C" {
+        static kSecUseAuthenticationContext: CFStringRef;
+        static kSecUseDataProtectionKeychain: CFStringRef;
+    }
+
+    /// How long, in seconds, a Touch ID match authorizes further reads through
+    /// the same context
+    const REUSE_SECONDS: f64 = 60.0;
+
+    /// `errSecItemNotFound`
+    const ITEM_NOT_FOUND: i32 = -25300;
+    /// `errSecUserCanceled`
+    const USER_CANCELED: i32 = -128;
+    /// `errSecMissingEntitlement`
+    const MISSING_ENTITLEMENT: i32 = -34018;
+
+    fn options(service: &str, full_key: &str) -> PasswordOptions {
+        let mut options = PasswordOptions::new_generic_password(service, full_key);
+        options.use_protected_keychain();
+        options
+    }
+
+    pub(super) fn get(service: &str, full_key: &str) -> Result<String> {
+        let secret = generic_password(options(service, full_key))
+            .map_err(|e| error(service, full_key, e))?;
+        String::from_utf8(secret).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: "Keychain".to_string(),
+            details: format!("'{full_key}' isn't valid UTF-8: {e}"),
+            hint: "Store the secret as text".to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
         })
-        .await?
-        .map_err(|e: keyring_core::Error| FnoxError::ProviderAuthFailed {
+    }
+
+    /// Read every lookup through one authentication context, so a batch asks
+    /// for Touch ID once instead of once per item
+    pub(super) fn get_all(
+        service: &str,
+        lookups: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        let context = unsafe { LAContext::new() };
+        unsafe { context.setTouchIDAuthenticationAllowableReuseDuration(REUSE_SECONDS) };
+        lookups
+            .iter()
+            .map(|(key, full_key)| (key.clone(), get_in(service, full_key, &context)))
+            .collect()
+    }
+
+    /// `SecItemCopyMatching` with `kSecUseAuthenticationContext`, which
+    /// `PasswordOptions` has no setter for
+    fn get_in(service: &str, full_key: &str, context: &Retained<LAContext>) -> Result<String> {
+        let key = |name: CFStringRef| unsafe { CFString::wrap_under_get_rule(name) };
+        let query = unsafe {
+            CFDictionary::from_CFType_pairs(&[
+                (key(kSecClass), key(kSecClassGenericPassword).as_CFType()),
+                (key(kSecAttrService), CFString::new(service).as_CFType()),
+                (key(kSecAttrAccount), CFString::new(full_key).as_CFType()),
+                (key(kSecReturnData), CFBoolean::true_value().as_CFType()),
+                (
+                    key(kSecUseDataProtectionKeychain),
+                    CFBoolean::true_value().as_CFType(),
+                ),
+                (
+                    key(kSecUseAuthenticationContext),
+                    CFType::wrap_under_get_rule(Retained::as_ptr(context).cast()),
+                ),
+            ])
+        };
+        let mut result = std::ptr::null();
+        let status = unsafe { SecItemCopyMatching(query.as_concrete_TypeRef(), &mut result) };
+        if status != errSecSuccess {
+            return Err(error(
+                service,
+                full_key,
+                security_framework::base::Error::from_code(status),
+            ));
+        }
+        let secret = unsafe { CFData::wrap_under_create_rule(result.cast()) };
+        String::from_utf8(secret.bytes().to_vec()).map_err(|e| FnoxError::ProviderInvalidResponse {
             provider: "Keychain".to_string(),
-            details: format!("Failed to access keychain (service: '{service}'): {e}"),
-            hint: "Check that you have permission to access the OS keychain".to_string(),
+            details: format!("'{full_key}' isn't valid UTF-8: {e}"),
+            hint: "Store the secret as text".to_string(),
             url: "https://fnox.jdx.dev/providers/keychain".to_string(),
         })
     }
 
-    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
-        self.put_secret(key, value).await?;
-        // Return the key name to store in config
-        Ok(key.to_string())
+    pub(super) fn set(service: &str, full_key: &str, value: &str) -> Result<()> {
+        // An item's access control can't be updated, so replace the item
+        delete(service, full_key).or_else(|e| match e {
+            FnoxError::ProviderSecretNotFound { .. } => Ok(()),
+            e => Err(e),
+        })?;
+        let mut options = options(service, full_key);
+        options.set_access_control_options(
+            AccessControlOptions::BIOMETRY_CURRENT_SET
+                | AccessControlOptions::OR
+                | AccessControlOptions::DEVICE_PASSCODE,
+        );
+        set_generic_password_options(value.as_bytes(), options)
+            .map_err(|e| error(service, full_key, e))
+    }
+
+    pub(super) fn delete(service: &str, full_key: &str) -> Result<()> {
+        delete_generic_password_options(options(service, full_key))
+            .map_err(|e| error(service, full_key, e))
+    }
+
+    fn error(service: &str, full_key: &str, e: security_framework::base::Error) -> FnoxError {
+        match e.code() {
+            ITEM_NOT_FOUND => not_found(service, full_key),
+            USER_CANCELED => FnoxError::ProviderAuthFailed {
+                provider: "Keychain".to_string(),
+                details: format!("Access to '{full_key}' was canceled"),
+                hint: "Approve the Touch ID prompt to read this secret".to_string(),
+                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+            },
+            MISSING_ENTITLEMENT => FnoxError::ProviderAuthFailed {
+                provider: "Keychain".to_string(),
+                details: format!("This fnox binary can't use the data protection keychain: {e}"),
+                hint: "require_biometrics needs a signed fnox build with the keychain-access-groups entitlement".to_string(),
+                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+            },
+            _ => FnoxError::ProviderApiError {
+                provider: "Keychain".to_string(),
+                details: format!("Keychain access to '{full_key}' failed: {e}"),
+                hint: "Check that the keychain is accessible".to_string(),
+                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+            },
+        }
+    }
+}
+
+/// Biometric access control is a macOS Keychain feature
+#[cfg(not(target_os = "macos"))]
+mod biometric {
+    use crate::error::{FnoxError, Result};
+
+    fn unsupported() -> FnoxError {
+        FnoxError::Config(
+            "The keychain provider's require_biometrics setting is only supported by the macOS Keychain"
+                .to_string(),
+        )
+    }
+
+    pub(super) fn get(_service: &str, _full_key: &str) -> Result<String> {
+        Err(unsupported())
+    }
+
+    pub(super) fn set(_service: &str, _full_key: &str, _value: &str) -> Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn delete(_service: &str, _full_key: &str) -> Result<()> {
+        Err(unsupported())
     }
 }
 
@@ -197,7 +690,7 @@ impl crate::providers::Provider for KeychainProvider {
 /// The OS keychain APIs are synchronous and may present a system dialog that
 /// blocks until the user responds. Calling them directly on a tokio worker
 /// thread pins that thread — concurrent calls can deadlock the runtime.
-async fn spawn_keychain_blocking<F, T>(f: F) -> Result<T>
+pub(crate) async fn spawn_keychain_blocking<F, T>(f: F) -> Result<T>
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
@@ -212,7 +705,38 @@ where
         })
 }
 
-fn platform_backend_hint() -> &'static str {
+/// Key read by `test_connection`. Nothing is ever stored under it.
+const PROBE_KEY: &str = "__fnox_probe__";
+
+/// Interpret the error from the `test_connection` probe read. Errors that
+/// still show the keychain answering count as reachable.
+fn probe_result(service: &str, e: keyring_core::Error) -> Result<()> {
+    match e {
+        keyring_core::Error::NoEntry | keyring_core::Error::Ambiguous(_) => Ok(()),
+        keyring_core::Error::NoStorageAccess(_) => Err(FnoxError::ProviderAuthFailed {
+            provider: "Keychain".to_string(),
+            details: format!("Keychain is locked or access was denied (service: '{service}'): {e}"),
+            hint: "Unlock the keychain and grant fnox access, then try again".to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        }),
+        keyring_core::Error::PlatformFailure(_) | keyring_core::Error::NoDefaultStore => {
+            Err(FnoxError::ProviderAuthFailed {
+                provider: "Keychain".to_string(),
+                details: format!("Keychain is unavailable (service: '{service}'): {e}"),
+                hint: platform_backend_hint().to_string(),
+                url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+            })
+        }
+        _ => Err(FnoxError::ProviderApiError {
+            provider: "Keychain".to_string(),
+            details: format!("Failed to access keychain (service: '{service}'): {e}"),
+            hint: "Check that the keychain is accessible".to_string(),
+            url: "https://fnox.jdx.dev/providers/keychain".to_string(),
+        }),
+    }
+}
+
+pub(crate) fn platform_backend_hint() -> &'static str {
     #[cfg(target_os = "linux")]
     {
         "No OS keyring backend is available. Start a Secret Service provider \
@@ -240,7 +764,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_keychain_set_and_get() {
-        let provider = KeychainProvider::new("fnox-unit-test".to_string(), None).unwrap();
+        let provider = KeychainProvider::new("fnox-unit-test".to_string(), None, None).unwrap();
 
         if let Err(err) = provider.test_connection().await {
             if is_keychain_unavailable(&err) {
@@ -264,6 +788,80 @@ mod tests {
         let _ = entry.delete_credential();
     }
 
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    #[tokio::test]
+    async fn test_connection_does_not_need_the_service_to_exist() {
+        let provider =
+            KeychainProvider::new("fnox-unit-test-no-such-service".to_string(), None, None)
+                .unwrap();
+
+        match provider.test_connection().await {
+            Ok(()) => {}
+            Err(err) if is_keychain_unavailable(&err) => {
+                eprintln!("skipping keychain test: {err}");
+                return;
+            }
+            Err(err) => panic!("bogus service should still be reachable: {err:?}"),
+        }
+        // The probe never writes anything
+        let entry = provider.create_entry(PROBE_KEY).unwrap();
+        assert!(entry.get_password().is_err());
+    }
+
+    #[test]
+    fn probe_treats_a_missing_entry_as_reachable() {
+        assert!(probe_result("fnox", keyring_core::Error::NoEntry).is_ok());
+    }
+
+    #[test]
+    fn probe_reports_a_locked_keychain() {
+        let locked = keyring_core::Error::NoStorageAccess("keychain is locked".into());
+        let err = probe_result("fnox", locked).unwrap_err();
+        assert!(
+            matches!(err, FnoxError::ProviderAuthFailed { .. }),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("locked"), "{err}");
+    }
+
+    #[test]
+    fn probe_reports_a_missing_backend_with_a_platform_hint() {
+        let missing = keyring_core::Error::PlatformFailure(
+            "org.freedesktop.DBus.Error.ServiceUnknown".into(),
+        );
+        match probe_result("fnox", missing).unwrap_err() {
+            FnoxError::ProviderAuthFailed { hint, .. } => {
+                assert_eq!(hint, platform_backend_hint())
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn credential_manager_values_are_capped() {
+        let provider = KeychainProvider::new("fnox-unit-test".to_string(), None, None).unwrap();
+        let fits = crate::providers::check_value_size(&provider, "KEY", &"x".repeat(1280));
+        assert!(fits.is_ok());
+        let err =
+            crate::providers::check_value_size(&provider, "KEY", &"x".repeat(1281)).unwrap_err();
+        assert!(
+            matches!(err, FnoxError::ProviderValueTooLarge { limit: 1280, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[tokio::test]
+    async fn biometrics_are_rejected_off_macos() {
+        let provider =
+            KeychainProvider::new("fnox-unit-test".to_string(), None, Some(true)).unwrap();
+        let err = provider.get_secret("test_key").await.unwrap_err();
+        assert!(err.to_string().contains("require_biometrics"), "{err}");
+        let err = provider.put_secret("test_key", "value").await.unwrap_err();
+        assert!(err.to_string().contains("require_biometrics"), "{err}");
+    }
+
     fn is_keychain_unavailable(err: &FnoxError) -> bool {
         matches!(err, FnoxError::ProviderAuthFailed { .. })
     }
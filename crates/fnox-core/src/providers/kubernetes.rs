@@ -0,0 +1,584 @@
+use crate::error::{FnoxError, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use std::collections::HashMap;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const PROVIDER_NAME: &str = "Kubernetes";
+const PROVIDER_URL: &str = "https://fnox.jdx.dev/providers/kubernetes";
+
+pub struct KubernetesProvider {
+    namespace: String,
+    context: Option<String>,
+    kubeconfig: Option<String>,
+}
+
+impl KubernetesProvider {
+    pub fn new(
+        namespace: String,
+        context: Option<String>,
+        kubeconfig: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            namespace,
+            context,
+            kubeconfig,
+        })
+    }
+
+    /// Build the args that scope every kubectl call to the configured cluster/namespace
+    fn build_common_args(&self) -> Vec<String> {
+        let mut args = vec![format!("--namespace={}", self.namespace)];
+
+        if let Some(ref context) = self.context {
+            args.push(format!("--context={}", context));
+        }
+        if let Some(ref kubeconfig) = self.kubeconfig {
+            args.push(format!("--kubeconfig={}", kubeconfig));
+        }
+
+        args
+    }
+
+    /// Execute a kubectl command and return stdout.
+    ///
+    /// Manifests are passed on stdin so secret values never show up in argv.
+    async fn execute_kubectl_command(
+        &self,
+        args: &[&str],
+        stdin: Option<&str>,
+        secret_ref: Option<&str>,
+    ) -> Result<String> {
+        tracing::debug!("Executing kubectl command with args: {:?}", args);
+
+        let mut cmd = Command::new("kubectl");
+        cmd.args(args);
+        cmd.args(self.build_common_args());
+
+        cmd.stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        });
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let mut child = cmd.spawn().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                FnoxError::ProviderCliNotFound {
+                    provider: PROVIDER_NAME.to_string(),
+                    cli: "kubectl".to_string(),
+                    install_hint: "brew install kubectl".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            } else {
+                FnoxError::ProviderCliFailed {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: e.to_string(),
+                    hint: "Check that kubectl is installed and accessible".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            }
+        })?;
+
+        if let Some(input) = stdin
+            && let Some(mut child_stdin) = child.stdin.take()
+        {
+            child_stdin.write_all(input.as_bytes()).await.map_err(|e| {
+                FnoxError::ProviderCliFailed {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: format!("Failed to write to kubectl stdin: {}", e),
+                    hint: "Check that kubectl is installed and accessible".to_string(),
+                    url: PROVIDER_URL.to_string(),
+                }
+            })?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| FnoxError::ProviderCliFailed {
+                provider: PROVIDER_NAME.to_string(),
+                details: e.to_string(),
+                hint: "Check that kubectl is installed and accessible".to_string(),
+                url: PROVIDER_URL.to_string(),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(classify_cli_error(stderr.trim(), secret_ref));
+        }
+
+        String::from_utf8(output.stdout).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Invalid UTF-8 in command output: {}", e),
+            hint: "kubectl returned unexpected output".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })
+    }
+
+    /// Fetch a Secret object as JSON
+    async fn fetch_secret(&self, name: &str, secret_ref: &str) -> Result<serde_json::Value> {
+        let output = self
+            .execute_kubectl_command(
+                &["get", "secret", name, "--output=json"],
+                None,
+                Some(secret_ref),
+            )
+            .await?;
+
+        serde_json::from_str(&output).map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to parse Secret '{}': {}", name, e),
+            hint: "kubectl returned an unexpected response format".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for KubernetesProvider {
+    fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
+        vec![crate::providers::ProviderCapability::RemoteStorage]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        tracing::debug!("Getting secret '{}' from Kubernetes", value);
+
+        let (name, key) = parse_reference(value)?;
+        let secret = self.fetch_secret(name, value).await?;
+        decode_data_field(&secret, key, value)
+    }
+
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        // Group references by Secret object so each object is fetched once
+        let mut by_name: HashMap<&str, Vec<(&String, &str, &String)>> = HashMap::new();
+        let mut results = HashMap::new();
+
+        for (key, value) in secrets {
+            match parse_reference(value) {
+                Ok((name, field)) => by_name.entry(name).or_default().push((key, field, value)),
+                Err(e) => {
+                    results.insert(key.clone(), Err(e));
+                }
+            }
+        }
+
+        tracing::debug!(
+            "Batch fetching {} secrets from {} Kubernetes Secret(s)",
+            secrets.len(),
+            by_name.len()
+        );
+
+        let fetches = by_name.into_iter().map(|(name, entries)| async move {
+            let secret = self.fetch_secret(name, entries[0].2).await;
+            entries
+                .into_iter()
+                .map(|(key, field, value)| {
+                    let result = match &secret {
+                        Ok(secret) => decode_data_field(secret, field, value),
+                        Err(e) => Err(map_batch_error(e, value)),
+                    };
+                    (key.clone(), result)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for entries in futures::future::join_all(fetches).await {
+            results.extend(entries);
+        }
+
+        results
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        let (name, field) = match parse_reference(key) {
+            Ok((name, field)) => (name.to_string(), field.to_string()),
+            Err(_) => (secret_name_for_key(key), key.to_string()),
+        };
+        let reference = format!("{}/{}", name, field);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(value);
+
+        tracing::debug!("Writing secret '{}' to Kubernetes", reference);
+
+        match self.fetch_secret(&name, &reference).await {
+            Ok(mut secret) => {
+                // Drop server-managed fields so the replace doesn't conflict
+                if let Some(metadata) = secret.get_mut("metadata").and_then(|m| m.as_object_mut()) {
+                    metadata.remove("resourceVersion");
+                    metadata.remove("managedFields");
+                }
+                let data = secret
+                    .as_object_mut()
+                    .map(|obj| obj.entry("data").or_insert_with(|| serde_json::json!({})))
+                    .and_then(|d| d.as_object_mut())
+                    .ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                        provider: PROVIDER_NAME.to_string(),
+                        details: format!("Secret '{}' has an unexpected structure", name),
+                        hint: "kubectl returned an unexpected response format".to_string(),
+                        url: PROVIDER_URL.to_string(),
+                    })?;
+                data.insert(field.clone(), serde_json::Value::String(encoded));
+
+                self.execute_kubectl_command(
+                    &["replace", "--filename=-"],
+                    Some(&secret.to_string()),
+                    None,
+                )
+                .await?;
+            }
+            Err(FnoxError::ProviderSecretNotFound { .. }) => {
+                let manifest = serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "Secret",
+                    "type": "Opaque",
+                    "metadata": {
+                        "name": name,
+                        "namespace": self.namespace,
+                    },
+                    "data": { (field.as_str()): encoded },
+                });
+
+                self.execute_kubectl_command(
+                    &["create", "--filename=-"],
+                    Some(&manifest.to_string()),
+                    None,
+                )
+                .await?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        tracing::debug!("Successfully wrote secret '{}' to Kubernetes", reference);
+        Ok(reference)
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        tracing::debug!(
+            "Testing connection to Kubernetes namespace '{}'",
+            self.namespace
+        );
+
+        // `auth can-i` prints "no" and exits non-zero when access is denied, and
+        // fails with a connection error when the API server is unreachable
+        let output = self
+            .execute_kubectl_command(&["auth", "can-i", "get", "secrets"], None, None)
+            .await
+            .map_err(|e| match e {
+                FnoxError::ProviderCliFailed { details, .. }
+                    if details.is_empty() || details == "no" =>
+                {
+                    namespace_access_denied(&self.namespace)
+                }
+                other => other,
+            })?;
+
+        if output.trim() != "yes" {
+            return Err(namespace_access_denied(&self.namespace));
+        }
+
+        tracing::debug!("Kubernetes connection test successful");
+        Ok(())
+    }
+}
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &["KUBECONFIG"]
+}
+
+fn namespace_access_denied(namespace: &str) -> FnoxError {
+    FnoxError::ProviderAuthFailed {
+        provider: PROVIDER_NAME.to_string(),
+        details: format!("Not allowed to read secrets in namespace '{}'", namespace),
+        hint: "Check the RBAC permissions for your kube context".to_string(),
+        url: PROVIDER_URL.to_string(),
+    }
+}
+
+/// Split a reference of the form `secret-name/key`
+fn parse_reference(value: &str) -> Result<(&str, &str)> {
+    match value.split_once('/') {
+        Some((name, key)) if !name.is_empty() && !key.is_empty() && !key.contains('/') => {
+            Ok((name, key))
+        }
+        _ => Err(FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Invalid secret reference format: '{}'", value),
+            hint: "Expected 'secret-name/key'".to_string(),
+            url: PROVIDER_URL.to_string(),
+        }),
+    }
+}
+
+/// Derive a valid Secret object name (RFC 1123 subdomain) from an env var key
+fn secret_name_for_key(key: &str) -> String {
+    key.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| c == '-' || c == '.')
+        .to_string()
+}
+
+/// Read and base64-decode a key from a Secret's `data` map
+fn decode_data_field(secret: &serde_json::Value, key: &str, secret_ref: &str) -> Result<String> {
+    let encoded = secret
+        .get("data")
+        .and_then(|data| data.get(key))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| FnoxError::ProviderSecretNotFound {
+            provider: PROVIDER_NAME.to_string(),
+            secret: secret_ref.to_string(),
+            hint: format!("Check that the Secret has a '{}' key", key),
+            url: PROVIDER_URL.to_string(),
+        })?;
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| FnoxError::ProviderInvalidResponse {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to decode '{}': {}", secret_ref, e),
+            hint: "Secret data values must be base64-encoded".to_string(),
+            url: PROVIDER_URL.to_string(),
+        })?;
+
+    String::from_utf8(bytes).map_err(|e| FnoxError::ProviderInvalidResponse {
+        provider: PROVIDER_NAME.to_string(),
+        details: format!("Invalid UTF-8 in '{}': {}", secret_ref, e),
+        hint: "The secret value contains invalid UTF-8 characters".to_string(),
+        url: PROVIDER_URL.to_string(),
+    })
+}
+
+/// Map a batch-level error to a per-secret error, preserving structured variants.
+fn map_batch_error(e: &FnoxError, secret_ref: &str) -> FnoxError {
+    match e {
+        FnoxError::ProviderSecretNotFound {
+            provider,
+            hint,
+            url,
+            ..
+        } => FnoxError::ProviderSecretNotFound {
+            provider: provider.clone(),
+            secret: secret_ref.to_string(),
+            hint: hint.clone(),
+            url: url.clone(),
+        },
+        FnoxError::ProviderAuthFailed {
+            provider,
+            details,
+            hint,
+            url,
+        } => FnoxError::ProviderAuthFailed {
+            provider: provider.clone(),
+            details: details.clone(),
+            hint: hint.clone(),
+            url: url.clone(),
+        },
+        FnoxError::ProviderCliNotFound {
+            provider,
+            cli,
+            install_hint,
+            url,
+        } => FnoxError::ProviderCliNotFound {
+            provider: provider.clone(),
+            cli: cli.clone(),
+            install_hint: install_hint.clone(),
+            url: url.clone(),
+        },
+        FnoxError::ProviderInvalidResponse {
+            provider,
+            details,
+            hint,
+            url,
+        } => FnoxError::ProviderInvalidResponse {
+            provider: provider.clone(),
+            details: details.clone(),
+            hint: hint.clone(),
+            url: url.clone(),
+        },
+        _ => FnoxError::ProviderCliFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details: e.to_string(),
+            hint: "Check your Kubernetes configuration".to_string(),
+            url: PROVIDER_URL.to_string(),
+        },
+    }
+}
+
+const AUTH_ERROR_PATTERNS: &[&str] = &[
+    "forbidden",
+    "unauthorized",
+    "must be logged in",
+    "provide credentials",
+    "token has expired",
+];
+
+const SECRET_NOT_FOUND_PATTERNS: &[&str] = &["notfound", "not found"];
+
+const CONNECTION_ERROR_PATTERNS: &[&str] = &[
+    "unable to connect to the server",
+    "connection refused",
+    "no such host",
+    "context was not found",
+    "no configuration has been provided",
+];
+
+fn contains_any(haystack: &str, patterns: &[&str]) -> bool {
+    patterns.iter().any(|pattern| haystack.contains(pattern))
+}
+
+/// Classify CLI stderr output into the appropriate FnoxError variant.
+fn classify_cli_error(stderr: &str, secret_ref: Option<&str>) -> FnoxError {
+    let stderr_lower = stderr.to_lowercase();
+
+    if contains_any(&stderr_lower, AUTH_ERROR_PATTERNS) {
+        return FnoxError::ProviderAuthFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details: stderr.to_string(),
+            hint: "Check your kubeconfig credentials and RBAC permissions".to_string(),
+            url: PROVIDER_URL.to_string(),
+        };
+    }
+
+    if contains_any(&stderr_lower, CONNECTION_ERROR_PATTERNS) {
+        return FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details: stderr.to_string(),
+            hint: "Check the context/kubeconfig settings in your Kubernetes provider config"
+                .to_string(),
+            url: PROVIDER_URL.to_string(),
+        };
+    }
+
+    if let Some(secret_ref) = secret_ref
+        && contains_any(&stderr_lower, SECRET_NOT_FOUND_PATTERNS)
+    {
+        return FnoxError::ProviderSecretNotFound {
+            provider: PROVIDER_NAME.to_string(),
+            secret: secret_ref.to_string(),
+            hint: "Check that the Secret exists in the configured namespace".to_string(),
+            url: PROVIDER_URL.to_string(),
+        };
+    }
+
+    FnoxError::ProviderCliFailed {
+        provider: PROVIDER_NAME.to_string(),
+        details: stderr.to_string(),
+        hint: "Check your Kubernetes configuration and authentication".to_string(),
+        url: PROVIDER_URL.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reference_splits_name_and_key() {
+        let (name, key) = parse_reference("db-credentials/password").unwrap();
+        assert_eq!(name, "db-credentials");
+        assert_eq!(key, "password");
+    }
+
+    #[test]
+    fn parse_reference_rejects_invalid_formats() {
+        for value in ["db-credentials", "/password", "db-credentials/", "a/b/c"] {
+            assert!(
+                matches!(
+                    parse_reference(value),
+                    Err(FnoxError::ProviderInvalidResponse { .. })
+                ),
+                "Expected '{}' to be rejected",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn secret_name_for_key_is_dns_safe() {
+        assert_eq!(secret_name_for_key("DATABASE_URL"), "database-url");
+        assert_eq!(secret_name_for_key("_API_KEY_"), "api-key");
+    }
+
+    #[test]
+    fn decode_data_field_decodes_base64() {
+        let secret = serde_json::json!({ "data": { "password": "aHVudGVyMg==" } });
+        assert_eq!(
+            decode_data_field(&secret, "password", "db/password").unwrap(),
+            "hunter2"
+        );
+    }
+
+    #[test]
+    fn decode_data_field_missing_key() {
+        let secret = serde_json::json!({ "data": { "username": "YWRtaW4=" } });
+        match decode_data_field(&secret, "password", "db/password") {
+            Err(FnoxError::ProviderSecretNotFound { secret, .. }) => {
+                assert_eq!(secret, "db/password");
+            }
+            other => panic!("Expected ProviderSecretNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn classify_cli_error_forbidden() {
+        let err = classify_cli_error(
+            r#"Error from server (Forbidden): secrets "db" is forbidden"#,
+            Some("db/password"),
+        );
+        assert!(
+            matches!(err, FnoxError::ProviderAuthFailed { .. }),
+            "Expected ProviderAuthFailed, got {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn classify_cli_error_secret_not_found() {
+        let err = classify_cli_error(
+            r#"Error from server (NotFound): secrets "db" not found"#,
+            Some("db/password"),
+        );
+        match err {
+            FnoxError::ProviderSecretNotFound { secret, .. } => {
+                assert_eq!(secret, "db/password");
+            }
+            other => panic!("Expected ProviderSecretNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn classify_cli_error_unreachable() {
+        let err = classify_cli_error(
+            "Unable to connect to the server: dial tcp 127.0.0.1:6443: connect: connection refused",
+            None,
+        );
+        assert!(
+            matches!(err, FnoxError::ProviderApiError { .. }),
+            "Expected ProviderApiError, got {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn classify_cli_error_generic() {
+        let err = classify_cli_error("some unexpected error", Some("db/password"));
+        assert!(
+            matches!(err, FnoxError::ProviderCliFailed { .. }),
+            "Expected ProviderCliFailed, got {:?}",
+            err
+        );
+    }
+}
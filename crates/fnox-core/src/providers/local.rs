@@ -0,0 +1,440 @@
+//! Symmetric encryption with a key held in the OS keychain.
+//!
+//! The first encryption generates a random 256-bit key and stores it with
+//! `keyring-core` under the configured service, so nothing key-like is
+//! written to disk. Values are encrypted with XChaCha20-Poly1305 and stored
+//! as `local:v1:base64(nonce || ciphertext || tag)`.
+//!
+//! Every project using the service shares its key, and a rekey only
+//! re-encrypts the values of one config, so the keys a rekey replaces are all
+//! kept: values elsewhere that still use one of them can always be read.
+
+use crate::error::{FnoxError, Result};
+use crate::providers::ProviderCapability;
+use crate::providers::keychain::{platform_backend_hint, spawn_keychain_blocking};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use keyring_core::Entry;
+use std::collections::HashMap;
+
+const URL: &str = "https://fnox.jdx.dev/providers/local";
+
+/// Keychain service the key is stored under when none is configured
+pub const DEFAULT_SERVICE: &str = "fnox-local";
+/// Keychain account holding the current key
+const KEY_ACCOUNT: &str = "data-key";
+/// Keychain account holding every key a rekey replaced, newest first and one
+/// per line, so values that weren't re-encrypted can still be read
+const PREVIOUS_KEY_ACCOUNT: &str = "data-key.previous";
+/// Prefix of values in the current format. The version lets the format
+/// change later without misreading older values.
+const CIPHERTEXT_PREFIX: &str = "local:v1:";
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+/// A 256-bit XChaCha20-Poly1305 key
+pub type DataKey = [u8; 32];
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &[]
+}
+
+pub struct LocalProvider {
+    service: String,
+}
+
+impl LocalProvider {
+    pub fn new(service: Option<String>) -> Result<Self> {
+        Ok(Self {
+            service: service
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| DEFAULT_SERVICE.to_string()),
+        })
+    }
+
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    fn entry(&self, account: &str) -> Result<Entry> {
+        crate::keyring_store::init();
+        Entry::new(&self.service, account).map_err(|e| self.keychain_error(e))
+    }
+
+    fn keychain_error(&self, e: keyring_core::Error) -> FnoxError {
+        match e {
+            keyring_core::Error::NoStorageAccess(_) => FnoxError::ProviderAuthFailed {
+                provider: "Local".to_string(),
+                details: format!(
+                    "Keychain is locked or access was denied (service: '{}'): {}",
+                    self.service, e
+                ),
+                hint: "Unlock the keychain and grant fnox access, then try again".to_string(),
+                url: URL.to_string(),
+            },
+            keyring_core::Error::PlatformFailure(_) | keyring_core::Error::NoDefaultStore => {
+                FnoxError::ProviderAuthFailed {
+                    provider: "Local".to_string(),
+                    details: format!(
+                        "Keychain is unavailable (service: '{}'): {}",
+                        self.service, e
+                    ),
+                    hint: platform_backend_hint().to_string(),
+                    url: URL.to_string(),
+                }
+            }
+            _ => FnoxError::ProviderApiError {
+                provider: "Local".to_string(),
+                details: format!(
+                    "Failed to access the key in the keychain (service: '{}'): {}",
+                    self.service, e
+                ),
+                hint: "Check that the keychain is accessible".to_string(),
+                url: URL.to_string(),
+            },
+        }
+    }
+
+    /// Read the keys stored under `account`, one per line, or an empty list
+    /// if there aren't any
+    async fn load_keys(&self, account: &str) -> Result<Vec<DataKey>> {
+        let entry = self.entry(account)?;
+        match spawn_keychain_blocking(move || entry.get_password()).await? {
+            Ok(encoded) => {
+                decode_keys(&encoded).ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                    provider: "Local".to_string(),
+                    details: format!(
+                        "The keychain entry (service: '{}', account: '{}') is not a 256-bit key",
+                        self.service, account
+                    ),
+                    hint: "The entry was changed outside fnox; restore it from a backup"
+                        .to_string(),
+                    url: URL.to_string(),
+                })
+            }
+            Err(keyring_core::Error::NoEntry) => Ok(Vec::new()),
+            Err(e) => Err(self.keychain_error(e)),
+        }
+    }
+
+    /// Read the key stored under `account`, or `None` if there isn't one
+    async fn load_key(&self, account: &str) -> Result<Option<DataKey>> {
+        Ok(self.load_keys(account).await?.into_iter().next())
+    }
+
+    async fn store_keys(&self, account: &str, keys: &[DataKey]) -> Result<()> {
+        let entry = self.entry(account)?;
+        let encoded = encode_keys(keys);
+        spawn_keychain_blocking(move || entry.set_password(&encoded))
+            .await?
+            .map_err(|e| self.keychain_error(e))
+    }
+
+    /// Hold the lock that stops two fnox processes from generating or
+    /// rotating the service's key at the same time, which would leave values
+    /// encrypted with a key that was overwritten
+    async fn lock_key(&self) -> Result<xx::fslock::LockFile> {
+        let hash = blake3::hash(self.service.as_bytes());
+        let path = crate::env::FNOX_STATE_DIR
+            .join("local")
+            .join(format!("{}.lock", &hash.to_hex()[..16]));
+        spawn_keychain_blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            xx::fslock::FSLock::new(&path)
+                .lock()
+                .map_err(|e| e.to_string())
+        })
+        .await?
+        .map_err(|e| {
+            FnoxError::Provider(format!(
+                "Failed to lock the local provider's key (service: '{}'): {}",
+                self.service, e
+            ))
+        })
+    }
+
+    /// The key to encrypt with, generated and stored on first use
+    async fn encryption_key(&self) -> Result<DataKey> {
+        if let Some(key) = self.load_key(KEY_ACCOUNT).await? {
+            return Ok(key);
+        }
+        // Another fnox may have stored a key while this one waited for the lock
+        let _lock = self.lock_key().await?;
+        if let Some(key) = self.load_key(KEY_ACCOUNT).await? {
+            return Ok(key);
+        }
+        tracing::info!(
+            "Generating a new key in the OS keychain (service: '{}')",
+            self.service
+        );
+        let key = generate_key();
+        self.store_keys(KEY_ACCOUNT, &[key]).await?;
+        Ok(key)
+    }
+
+    /// Replace the key with a newly generated one and return it. The old key
+    /// is added to the previous keys, which decryption falls back to, so
+    /// values that weren't re-encrypted (in this config or any other using the
+    /// service) can still be read.
+    pub async fn rotate_key(&self) -> Result<DataKey> {
+        let _lock = self.lock_key().await?;
+        let current = self.decryption_key().await?;
+        let mut previous = self.load_keys(PREVIOUS_KEY_ACCOUNT).await?;
+        previous.retain(|key| *key != current);
+        previous.insert(0, current);
+        // Saved before the key changes, so a failure in between loses nothing
+        self.store_keys(PREVIOUS_KEY_ACCOUNT, &previous).await?;
+        let key = generate_key();
+        self.store_keys(KEY_ACCOUNT, &[key]).await?;
+        tracing::debug!(
+            "Rotated the key in the OS keychain (service: '{}'), keeping {} previous keys",
+            self.service,
+            previous.len()
+        );
+        Ok(key)
+    }
+
+    /// The current key, which values can't be decrypted without
+    async fn decryption_key(&self) -> Result<DataKey> {
+        self.load_key(KEY_ACCOUNT)
+            .await?
+            .ok_or_else(|| FnoxError::LocalKeyMissing {
+                service: self.service.clone(),
+            })
+    }
+
+    /// Decrypt with `key`, falling back to the previous keys for values a
+    /// rekey didn't get to
+    async fn decrypt(&self, key: &DataKey, value: &str) -> Result<String> {
+        match decrypt_with(key, value) {
+            Err(e) => self
+                .load_keys(PREVIOUS_KEY_ACCOUNT)
+                .await?
+                .iter()
+                .find_map(|previous| decrypt_with(previous, value).ok())
+                .ok_or(e),
+            result => result,
+        }
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for LocalProvider {
+    fn capabilities(&self) -> Vec<ProviderCapability> {
+        vec![ProviderCapability::Encryption]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        let key = self.decryption_key().await?;
+        self.decrypt(&key, value).await
+    }
+
+    /// Read the key from the keychain once for the whole batch, since each
+    /// read can prompt for access
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        let key = self.decryption_key().await;
+        let mut results = HashMap::with_capacity(secrets.len());
+        for (name, value) in secrets {
+            let result = match &key {
+                Ok(key) => self.decrypt(key, value).await,
+                Err(FnoxError::LocalKeyMissing { service }) => Err(FnoxError::LocalKeyMissing {
+                    service: service.clone(),
+                }),
+                Err(e) => Err(FnoxError::Provider(e.to_string())),
+            };
+            results.insert(name.clone(), result);
+        }
+        results
+    }
+
+    async fn encrypt(&self, value: &str) -> Result<String> {
+        let key = self.encryption_key().await?;
+        encrypt_with(&key, value)
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        self.load_key(KEY_ACCOUNT).await?;
+        Ok(())
+    }
+}
+
+fn generate_key() -> DataKey {
+    let mut key = [0u8; 32];
+    rand::fill(&mut key);
+    key
+}
+
+fn decode_key(encoded: &str) -> Option<DataKey> {
+    STANDARD.decode(encoded.trim()).ok()?.try_into().ok()
+}
+
+/// Keys stored one per line, as base64
+fn decode_keys(encoded: &str) -> Option<Vec<DataKey>> {
+    encoded
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(decode_key)
+        .collect()
+}
+
+fn encode_keys(keys: &[DataKey]) -> String {
+    keys.iter()
+        .map(|key| STANDARD.encode(key))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn cipher(key: &DataKey) -> Result<XChaCha20Poly1305> {
+    XChaCha20Poly1305::new_from_slice(key).map_err(|e| {
+        FnoxError::Provider(format!("Failed to create XChaCha20-Poly1305 cipher: {}", e))
+    })
+}
+
+/// Encrypt `plaintext` with `key` into a `local:v1:` value
+pub fn encrypt_with(key: &DataKey, plaintext: &str) -> Result<String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::fill(&mut nonce);
+    let ciphertext = cipher(key)?
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|e| FnoxError::Provider(format!("XChaCha20-Poly1305 encryption failed: {}", e)))?;
+
+    let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", CIPHERTEXT_PREFIX, STANDARD.encode(output)))
+}
+
+/// Decrypt a value made by [`encrypt_with`]. Fails if the value was changed
+/// or was encrypted with a different key.
+pub fn decrypt_with(key: &DataKey, value: &str) -> Result<String> {
+    let data = parse_ciphertext(value)?;
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plaintext = cipher(key)?
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| FnoxError::ProviderInvalidResponse {
+            provider: "Local".to_string(),
+            details: "Decryption failed: the value was modified or was encrypted with a different key".to_string(),
+            hint: "Check that the value in the config wasn't edited, and that it was encrypted on this machine's keychain".to_string(),
+            url: URL.to_string(),
+        })?;
+    String::from_utf8(plaintext)
+        .map_err(|e| FnoxError::Provider(format!("Decrypted data is not valid UTF-8: {}", e)))
+}
+
+/// Check the version prefix and decode `nonce || ciphertext || tag`
+fn parse_ciphertext(value: &str) -> Result<Vec<u8>> {
+    let invalid = |details: String, hint: &str| FnoxError::ProviderInvalidResponse {
+        provider: "Local".to_string(),
+        details,
+        hint: hint.to_string(),
+        url: URL.to_string(),
+    };
+    let Some(encoded) = value.strip_prefix(CIPHERTEXT_PREFIX) else {
+        let hint = if value.starts_with("local:") {
+            "The value was written by a newer fnox; upgrade fnox to read it"
+        } else {
+            "Set the secret again with `fnox set` to encrypt it with this provider"
+        };
+        return Err(invalid(
+            format!("Value doesn't start with '{}'", CIPHERTEXT_PREFIX),
+            hint,
+        ));
+    };
+    let data = STANDARD.decode(encoded).map_err(|e| {
+        invalid(
+            format!("Invalid base64 in encrypted value: {}", e),
+            "The value in the config was edited or truncated",
+        )
+    })?;
+    if data.len() < NONCE_LEN + TAG_LEN {
+        return Err(invalid(
+            "Encrypted value is too short".to_string(),
+            "The value in the config was edited or truncated",
+        ));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_values() {
+        let key = generate_key();
+        for plaintext in ["secret", "", "multi\nline ✓ value"] {
+            let encrypted = encrypt_with(&key, plaintext).unwrap();
+            assert!(encrypted.starts_with("local:v1:"), "{encrypted}");
+            assert!(!encrypted.contains(plaintext) || plaintext.is_empty());
+            assert_eq!(decrypt_with(&key, &encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn uses_a_fresh_nonce_each_time() {
+        let key = generate_key();
+        assert_ne!(
+            encrypt_with(&key, "same").unwrap(),
+            encrypt_with(&key, "same").unwrap()
+        );
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let key = generate_key();
+        let encrypted = encrypt_with(&key, "secret").unwrap();
+        let mut data = STANDARD
+            .decode(encrypted.strip_prefix(CIPHERTEXT_PREFIX).unwrap())
+            .unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let tampered = format!("{}{}", CIPHERTEXT_PREFIX, STANDARD.encode(&data));
+
+        let err = decrypt_with(&key, &tampered).unwrap_err();
+        assert!(err.to_string().contains("modified"), "{err}");
+    }
+
+    #[test]
+    fn rejects_another_key() {
+        let encrypted = encrypt_with(&generate_key(), "secret").unwrap();
+        assert!(decrypt_with(&generate_key(), &encrypted).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let key = generate_key();
+        let err = decrypt_with(&key, "local:v2:AAAA").unwrap_err();
+        assert!(err.to_string().contains("local:v1:"), "{err}");
+        assert!(decrypt_with(&key, "plaintext").is_err());
+        assert!(decrypt_with(&key, "local:v1:AAAA").is_err());
+        assert!(decrypt_with(&key, "local:v1:not base64!").is_err());
+    }
+
+    #[test]
+    fn decodes_only_256_bit_keys() {
+        let key = generate_key();
+        assert_eq!(decode_key(&STANDARD.encode(key)), Some(key));
+        assert_eq!(decode_key(&STANDARD.encode([0u8; 16])), None);
+        assert_eq!(decode_key("not base64!"), None);
+    }
+
+    #[test]
+    fn previous_keys_are_kept_one_per_line() {
+        let keys = [generate_key(), generate_key(), generate_key()];
+        assert_eq!(decode_keys(&encode_keys(&keys)), Some(keys.to_vec()));
+        // A single key, as older versions stored it
+        assert_eq!(decode_keys(&STANDARD.encode(keys[0])), Some(vec![keys[0]]));
+        assert_eq!(
+            decode_keys(&format!("{}\nnot a key", STANDARD.encode(keys[0]))),
+            None
+        );
+    }
+}
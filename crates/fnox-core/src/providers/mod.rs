@@ -4,39 +4,69 @@ use std::collections::HashMap;
 
 // Provider implementation modules
 pub mod age;
+pub mod aws_auth;
 pub mod aws_kms;
 pub mod aws_ps;
 pub mod aws_sm;
+pub mod azure_auth;
 pub mod azure_kms;
 pub mod azure_sm;
 pub mod bitwarden;
 pub mod bitwarden_sm;
 pub mod doppler;
+pub mod envfile;
+pub mod etcd;
+pub mod exec;
 #[cfg(not(target_env = "musl"))]
 pub mod fido2;
 pub mod foks;
 pub mod gcp_kms;
 pub mod gcp_sm;
+pub mod http;
 pub mod hw_encrypt;
 pub mod infisical;
 pub mod keepass;
 pub mod keychain;
+pub mod kubernetes;
+pub mod local;
 pub mod onepassword;
 pub mod password_store;
 pub mod passwordstate;
 pub mod plain;
+pub mod prefix;
 pub mod proton_pass;
+pub mod reference;
 pub mod resolved;
 pub mod resolver;
 pub mod secret_ref;
+pub mod systemd_creds;
+pub mod totp;
 pub mod vault;
 pub mod yubikey;
 pub mod yubikey_usb;
 
 pub use bitwarden::BitwardenBackend;
+pub use onepassword::OnePasswordBackend;
 pub use resolver::resolve_provider_config;
 pub use secret_ref::{OptionProviderSecretRef, OptionStringOrSecretRef, StringOrSecretRef};
 
+/// Secrets fetched at once by the default `get_secrets_batch`, unless the provider
+/// recommends otherwise or the `batch_concurrency` setting overrides it
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 10;
+
+/// A stored version of a secret, as listed by `fnox history`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretVersion {
+    /// Provider-specific version id
+    pub id: String,
+    /// When this version was written, if the provider records it
+    pub created: Option<chrono::DateTime<chrono::Utc>>,
+    /// Labels the provider attaches to the version, such as `current` or `AWSPREVIOUS`
+    pub labels: Vec<String>,
+    /// The value stored in this version, when it was asked for
+    pub value: Option<String>,
+}
+
 /// Provider capabilities - what a provider can do
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderCapability {
@@ -143,8 +173,9 @@ mod generated {
         use super::super::fido2;
         use super::super::{
             age, aws_kms, aws_ps, aws_sm, azure_kms, azure_sm, bitwarden, bitwarden_sm, doppler,
-            foks, gcp_kms, gcp_sm, infisical, keepass, keychain, onepassword, password_store,
-            passwordstate, plain, proton_pass, vault, yubikey,
+            envfile, etcd, exec, foks, gcp_kms, gcp_sm, http, infisical, keepass, keychain,
+            kubernetes, local, onepassword, password_store, passwordstate, plain, proton_pass,
+            systemd_creds, vault, yubikey,
         };
         include!(concat!(
             env!("OUT_DIR"),
@@ -169,6 +200,12 @@ pub trait Provider: Send + Sync {
     /// Get a secret value from the provider (decrypt if needed)
     async fn get_secret(&self, value: &str) -> Result<String>;
 
+    /// Get the secret stored as `key` in fnox's config, for providers that can
+    /// look a secret up by that name. Defaults to [`Provider::get_secret`].
+    async fn get_named_secret(&self, _key: &str, value: &str) -> Result<String> {
+        self.get_secret(value).await
+    }
+
     /// Get multiple secrets in a batch (more efficient for some providers)
     ///
     /// Takes a slice of (key, value) tuples where:
@@ -178,14 +215,23 @@ pub trait Provider: Send + Sync {
     /// Returns a HashMap of successfully resolved secrets. Failures are logged but don't
     /// stop other secrets from being resolved.
     ///
-    /// Default implementation fetches secrets in parallel using tokio tasks.
-    /// Providers can override this for true batch operations (e.g., single API call)
-    /// or different concurrency using `get_secrets_concurrent`.
+    /// Default implementation fetches secrets in parallel using tokio tasks, up to
+    /// [`batch_concurrency`] at a time. Providers can override this for true batch
+    /// operations (e.g., single API call), or tune the concurrency with
+    /// `recommended_concurrency`.
     async fn get_secrets_batch(
         &self,
         secrets: &[(String, String)],
     ) -> HashMap<String, Result<String>> {
-        get_secrets_concurrent(self, secrets, 10).await
+        get_secrets_concurrent(self, secrets, batch_concurrency(self)).await
+    }
+
+    /// How many secrets the default `get_secrets_batch` fetches at once.
+    ///
+    /// Lower it for APIs with strict rate limits, raise it for cheap lookups.
+    /// The `batch_concurrency` setting takes precedence when set.
+    fn recommended_concurrency(&self) -> usize {
+        DEFAULT_BATCH_CONCURRENCY
     }
 
     /// Encrypt a value with this provider (for encryption providers)
@@ -223,6 +269,72 @@ pub trait Provider: Send + Sync {
         }
     }
 
+    /// The largest value the provider can store, when it has a limit.
+    /// Checked by [`check_value_size`] before a write is attempted.
+    fn max_value_size(&self) -> Option<ValueSizeLimit> {
+        None
+    }
+
+    /// Delete a secret from remote storage by its provider reference
+    async fn delete_secret(&self, _value: &str) -> Result<()> {
+        Err(crate::error::FnoxError::Provider(
+            "This provider does not support deleting secrets".to_string(),
+        ))
+    }
+
+    /// Move a remotely stored secret so it is stored under `new_key`.
+    ///
+    /// Returns the new value to save in config. The default implementation
+    /// reads the old value, writes it under the new key, then deletes the old
+    /// one; providers with a native rename can override it.
+    async fn move_secret(&self, value: &str, new_key: &str) -> Result<String> {
+        if !self
+            .capabilities()
+            .contains(&ProviderCapability::RemoteStorage)
+        {
+            return Err(crate::error::FnoxError::Provider(
+                "Only remote storage providers can move secrets".to_string(),
+            ));
+        }
+
+        let secret = self.get_secret(value).await?;
+        let new_value = self.put_secret(new_key, &secret).await?;
+        if new_value != value {
+            self.delete_secret(value).await.map_err(|e| {
+                crate::error::FnoxError::Provider(format!(
+                    "Copied secret to '{}' but failed to delete '{}': {}",
+                    new_value, value, e
+                ))
+            })?;
+        }
+        Ok(new_value)
+    }
+
+    /// Stored versions of a secret, newest first and including the current
+    /// one, for providers that keep a history. Each version's value is only
+    /// read with `with_values`.
+    async fn get_history(&self, _value: &str, _with_values: bool) -> Result<Vec<SecretVersion>> {
+        Err(crate::error::FnoxError::Provider(
+            "This provider does not keep a history of secret values".to_string(),
+        ))
+    }
+
+    /// References to the secrets in the provider's store, in the form a
+    /// secret's `value` takes, for providers that can enumerate it. With
+    /// `prefix`, only references starting with it are listed.
+    async fn list_secrets(&self, _prefix: Option<&str>) -> Result<Vec<String>> {
+        Err(crate::error::FnoxError::Provider(
+            "This provider can't list the secrets it stores".to_string(),
+        ))
+    }
+
+    /// Credentials for the provider's own CLI, as environment variables, so a
+    /// child process can reuse the session fnox authenticated with instead of
+    /// logging in again. Only exported with `fnox exec --share-session`.
+    async fn session_env(&self) -> Result<Vec<(String, String)>> {
+        Ok(Vec::new())
+    }
+
     /// Get the capabilities of this provider
     fn capabilities(&self) -> Vec<ProviderCapability> {
         // Default: read-only remote provider (like 1Password, Bitwarden)
@@ -236,6 +348,92 @@ pub trait Provider: Send + Sync {
     }
 }
 
+/// The largest value a provider can store, from [`Provider::max_value_size`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueSizeLimit {
+    /// Provider name shown in errors
+    pub provider: &'static str,
+    /// Largest value, in bytes
+    pub bytes: usize,
+    /// What to do with larger values
+    pub hint: String,
+    /// Provider documentation page
+    pub url: &'static str,
+}
+
+/// Suggestion for values too big for a remote store
+pub const LARGE_VALUE_HINT: &str = "Store large blobs encrypted in the config instead, e.g. with an age provider, and set as_file = true to pass them to commands as a file";
+
+/// Size past which ciphertext kept in the config makes it hard to read and diff
+const LARGE_INLINE_VALUE: usize = 64 * 1024;
+
+/// Fail before writing `value` under `key` when it's over the provider's
+/// size limit, naming the limit and the actual size. Encryption providers
+/// have no limit, but a large value is warned about since its ciphertext
+/// lands in the config file.
+pub fn check_value_size(provider: &(impl Provider + ?Sized), key: &str, value: &str) -> Result<()> {
+    let size = value.len();
+    if let Some(limit) = provider.max_value_size()
+        && size > limit.bytes
+    {
+        return Err(crate::error::FnoxError::ProviderValueTooLarge {
+            provider: limit.provider.to_string(),
+            key: key.to_string(),
+            size,
+            limit: limit.bytes,
+            hint: limit.hint,
+            url: limit.url.to_string(),
+        });
+    }
+    if size > LARGE_INLINE_VALUE
+        && provider
+            .capabilities()
+            .contains(&ProviderCapability::Encryption)
+    {
+        tracing::warn!(
+            "'{}' is {} KiB; its ciphertext will be stored in the config file",
+            key,
+            size / 1024
+        );
+    }
+    Ok(())
+}
+
+/// The path of a reference like `/myapp/prod/*` that loads every secret
+/// under a path, or `None` for a reference to a single secret. Only
+/// providers whose config [supports path references] read it this way.
+///
+/// [supports path references]: ProviderConfig::supports_path_references
+pub fn path_reference(value: &str) -> Option<&str> {
+    value.strip_suffix("/*")
+}
+
+/// Concurrency for a provider's batch fetches: the `batch_concurrency` setting
+/// if set, otherwise the provider's recommendation.
+pub fn batch_concurrency(provider: &(impl Provider + ?Sized)) -> usize {
+    let settings = crate::settings::Settings::get();
+    resolve_batch_concurrency(
+        settings.batch_concurrency.as_deref(),
+        provider.recommended_concurrency(),
+    )
+}
+
+fn resolve_batch_concurrency(configured: Option<&str>, recommended: usize) -> usize {
+    let Some(value) = configured.map(str::trim).filter(|v| !v.is_empty()) else {
+        return recommended.max(1);
+    };
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            tracing::warn!(
+                "Ignoring invalid FNOX_BATCH_CONCURRENCY '{}': expected a positive integer",
+                value
+            );
+            recommended.max(1)
+        }
+    }
+}
+
 /// Fetch secrets concurrently with configurable concurrency limit.
 ///
 /// Helper for providers that want to use the default parallel fetch behavior
@@ -256,7 +454,7 @@ pub async fn get_secrets_concurrent(
             let result = provider.get_secret(&value).await;
             (key, result)
         })
-        .buffer_unordered(concurrency)
+        .buffer_unordered(concurrency.max(1))
         .collect()
         .await;
 
@@ -271,6 +469,16 @@ impl ProviderConfig {
             .filter(|info| info.category == category)
             .collect()
     }
+
+    /// Whether the value `reference` reads changes over time, like a TOTP
+    /// code, so a cached copy would soon be wrong
+    pub fn is_time_based_reference(&self, reference: &str) -> bool {
+        match self {
+            Self::KeePass { .. } => keepass::is_totp_reference(reference),
+            Self::Bitwarden { .. } => bitwarden::is_totp_reference(reference),
+            _ => false,
+        }
+    }
 }
 
 /// Create a provider from an unresolved provider configuration.
@@ -316,17 +524,148 @@ pub(crate) fn get_provider_from_resolved_with_context_and_identity_cycle_guard(
         recipients,
         key_file,
         identity,
+        key_files,
+        passphrase,
     } = resolved
     {
         return Ok(Box::new(age::AgeEncryptionProvider::new_with_config(
             recipients.clone(),
             key_file.clone(),
             identity.clone(),
-            std::sync::Arc::new(config.clone()),
-            profile.to_string(),
-            provider_name.to_string(),
-            identity_cycle_guard,
+            key_files.clone(),
+            *passphrase,
+            age::AgeConfigContext {
+                config: std::sync::Arc::new(config.clone()),
+                profile: profile.to_string(),
+                provider_name: provider_name.to_string(),
+                identity_cycle_guard,
+            },
         )?));
     }
+    if let ResolvedProviderConfig::Envfile { path } = resolved {
+        let config_dir = config
+            .provider_source(profile, provider_name)
+            .and_then(std::path::Path::parent);
+        return Ok(Box::new(
+            envfile::EnvfileProvider::new(path.clone())?.with_config_dir(config_dir),
+        ));
+    }
     get_provider_from_resolved(provider_name, resolved)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Provider that records how many `get_secret` calls run at the same time
+    struct CountingProvider {
+        recommended: usize,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl CountingProvider {
+        fn new(recommended: usize) -> Self {
+            Self {
+                recommended,
+                in_flight: AtomicUsize::new(0),
+                max_in_flight: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        async fn get_secret(&self, value: &str) -> Result<String> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(value.to_string())
+        }
+
+        fn recommended_concurrency(&self) -> usize {
+            self.recommended
+        }
+    }
+
+    /// Provider with a 10-byte limit
+    struct TinyProvider;
+
+    #[async_trait]
+    impl Provider for TinyProvider {
+        async fn get_secret(&self, value: &str) -> Result<String> {
+            Ok(value.to_string())
+        }
+
+        fn max_value_size(&self) -> Option<ValueSizeLimit> {
+            Some(ValueSizeLimit {
+                provider: "Tiny",
+                bytes: 10,
+                hint: "Use a bigger provider".to_string(),
+                url: "https://fnox.jdx.dev",
+            })
+        }
+    }
+
+    #[test]
+    fn value_size_is_checked_in_bytes() {
+        assert!(check_value_size(&TinyProvider, "KEY", "0123456789").is_ok());
+        // 10 characters, 11 bytes
+        let err = check_value_size(&TinyProvider, "KEY", "012345678é").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tiny: 'KEY' is 11 bytes, over the 10-byte limit for a value"
+        );
+        // Providers without a limit take anything
+        let unlimited = CountingProvider::new(1);
+        assert!(check_value_size(&unlimited, "KEY", &"x".repeat(1 << 20)).is_ok());
+    }
+
+    fn secrets(n: usize) -> Vec<(String, String)> {
+        (0..n)
+            .map(|i| (format!("KEY_{i}"), format!("value-{i}")))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn get_secrets_concurrent_respects_the_limit() {
+        let provider = CountingProvider::new(DEFAULT_BATCH_CONCURRENCY);
+        let results = get_secrets_concurrent(&provider, &secrets(12), 3).await;
+
+        assert_eq!(results.len(), 12);
+        assert_eq!(results["KEY_7"].as_ref().unwrap(), "value-7");
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn default_batch_uses_the_recommended_concurrency() {
+        let provider = CountingProvider::new(4);
+        let results = provider.get_secrets_batch(&secrets(12)).await;
+
+        assert_eq!(results.len(), 12);
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn zero_concurrency_still_makes_progress() {
+        let provider = CountingProvider::new(DEFAULT_BATCH_CONCURRENCY);
+        let results = get_secrets_concurrent(&provider, &secrets(3), 0).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn configured_concurrency_overrides_the_recommendation() {
+        assert_eq!(resolve_batch_concurrency(None, 10), 10);
+        assert_eq!(resolve_batch_concurrency(Some("2"), 10), 2);
+        assert_eq!(resolve_batch_concurrency(Some(" 50 "), 100), 50);
+        // Invalid or empty values fall back to the provider's recommendation
+        assert_eq!(resolve_batch_concurrency(Some(""), 100), 100);
+        assert_eq!(resolve_batch_concurrency(Some("0"), 10), 10);
+        assert_eq!(resolve_batch_concurrency(Some("many"), 10), 10);
+    }
+}
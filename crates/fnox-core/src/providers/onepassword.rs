@@ -1,11 +1,17 @@
 use crate::env;
 use crate::error::{FnoxError, Result};
+use crate::providers::reference;
+use crate::recording::{CommandRunner, Stdout};
 use async_trait::async_trait;
+use indexmap::IndexMap;
 use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use std::collections::HashMap;
-use std::process::Stdio;
+use std::fmt;
+use std::io::Write;
 use std::sync::LazyLock;
-use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
 /// Precompiled regex to remove leading error prefixes from stderr output of `op`.
@@ -13,10 +19,31 @@ use tokio::process::Command;
 static ERROR_PREFIX_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)^\[ERROR\] \d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2} ").unwrap());
 
+const URL: &str = "https://fnox.jdx.dev/providers/1password";
+
 pub struct OnePasswordProvider {
     vault: Option<String>,
     account: Option<String>,
     token: Option<String>,
+    backend: OnePasswordBackend,
+    connect_host: Option<String>,
+    connect_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OnePasswordBackend {
+    Op,
+    Connect,
+}
+
+impl fmt::Display for OnePasswordBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnePasswordBackend::Op => write!(f, "op"),
+            OnePasswordBackend::Connect => write!(f, "connect"),
+        }
+    }
 }
 
 impl OnePasswordProvider {
@@ -24,14 +51,130 @@ impl OnePasswordProvider {
         vault: Option<String>,
         account: Option<String>,
         token: Option<String>,
+        backend: Option<OnePasswordBackend>,
+        connect_host: Option<String>,
+        connect_token: Option<String>,
     ) -> Result<Self> {
         Ok(Self {
             vault,
             account,
             token,
+            backend: backend.unwrap_or(OnePasswordBackend::Op),
+            connect_host,
+            connect_token,
+        })
+    }
+
+    /// Client for the Connect server, with host and token from the config or
+    /// `OP_CONNECT_HOST` / `OP_CONNECT_TOKEN`
+    fn connect_client(&self) -> Result<ConnectClient> {
+        let host = self
+            .connect_host
+            .clone()
+            .or_else(|| env_var("OP_CONNECT_HOST"))
+            .ok_or_else(|| FnoxError::ProviderAuthFailed {
+                provider: "1Password".to_string(),
+                details: "No Connect server configured".to_string(),
+                hint: "Set OP_CONNECT_HOST or connect_host in the provider config".to_string(),
+                url: URL.to_string(),
+            })?;
+        let token = self
+            .connect_token
+            .clone()
+            .or_else(|| env_var("OP_CONNECT_TOKEN"))
+            .ok_or_else(|| FnoxError::ProviderAuthFailed {
+                provider: "1Password".to_string(),
+                details: "No Connect token configured".to_string(),
+                hint: "Set OP_CONNECT_TOKEN or connect_token in the provider config".to_string(),
+                url: URL.to_string(),
+            })?;
+        Ok(ConnectClient {
+            host: host.trim_end_matches('/').to_string(),
+            token,
+            client: crate::http::http_client(),
         })
     }
 
+    /// Read one secret through the Connect server
+    async fn get_secret_connect(&self, value: &str) -> Result<String> {
+        let reference = self.value_to_reference(value)?;
+        let location = ItemLocation::parse(&reference)?;
+        let client = self.connect_client()?;
+        let vault_id = client.vault_id(&location.vault).await?;
+        let item = client.item(&vault_id, &location.item).await?;
+        field_value(&item, &location, &reference)
+    }
+
+    /// Read secrets through the Connect server, looking up each vault once
+    /// and fetching each item once however many of its fields are referenced
+    async fn get_secrets_batch_connect(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        let mut results = HashMap::new();
+        let client = match self.connect_client() {
+            Ok(client) => client,
+            Err(e) => {
+                for (key, _) in secrets {
+                    results.insert(key.clone(), Err(self.batch_error(&e, key)));
+                }
+                return results;
+            }
+        };
+
+        // vault -> item -> (key, reference, location)
+        let mut groups: IndexMap<String, VaultItems> = IndexMap::new();
+        for (key, value) in secrets {
+            let located = self
+                .value_to_reference(value)
+                .and_then(|reference| Ok((ItemLocation::parse(&reference)?, reference)));
+            match located {
+                Ok((location, reference)) => groups
+                    .entry(location.vault.clone())
+                    .or_default()
+                    .entry(location.item.clone())
+                    .or_default()
+                    .push((key.clone(), reference, location)),
+                Err(e) => {
+                    results.insert(key.clone(), Err(e));
+                }
+            }
+        }
+
+        for (vault, items) in groups {
+            let vault_id = match client.vault_id(&vault).await {
+                Ok(id) => id,
+                Err(e) => {
+                    for (key, _, _) in items.values().flatten() {
+                        results.insert(key.clone(), Err(self.batch_error(&e, key)));
+                    }
+                    continue;
+                }
+            };
+            for (item_name, fields) in &items {
+                let item = client.item(&vault_id, item_name).await;
+                for (key, reference, location) in fields {
+                    let result = match &item {
+                        Ok(item) => field_value(item, location, reference),
+                        Err(e) => Err(self.batch_error(e, key)),
+                    };
+                    results.insert(key.clone(), result);
+                }
+            }
+        }
+
+        results
+    }
+
+    fn batch_error(&self, e: &FnoxError, key: &str) -> FnoxError {
+        e.map_batch_error(
+            key,
+            "1Password",
+            "Check your 1Password Connect configuration",
+            URL,
+        )
+    }
+
     /// Get the service account token, preferring the configured token over environment variable.
     fn get_token(&self) -> Option<String> {
         self.token
@@ -42,12 +185,14 @@ impl OnePasswordProvider {
 
     /// Convert a value to an op:// reference
     fn value_to_reference(&self, value: &str) -> Result<String> {
-        // Check if value is already a full op:// reference
-        if value.starts_with("op://") {
+        let parsed = reference::ONEPASSWORD.parse(value)?;
+
+        // Full op:// references are passed to op as written
+        if parsed.scheme.is_some() {
             return Ok(value.to_string());
         }
 
-        if self.vault.is_none() {
+        let Some(vault) = &self.vault else {
             return Err(FnoxError::ProviderInvalidResponse {
                 provider: "1Password".to_string(),
                 details: format!("Unknown secret vault for: '{}'", value),
@@ -55,32 +200,66 @@ impl OnePasswordProvider {
                     .to_string(),
                 url: "https://fnox.jdx.dev/providers/1password".to_string(),
             });
-        }
+        };
 
-        // Parse value as "item/field" or just "item"
         // Default field is "password" if not specified
-        let parts: Vec<&str> = value.split('/').collect();
-        match parts.len() {
-            1 => Ok(format!(
-                "op://{}/{}/password",
-                self.vault.as_ref().unwrap(),
-                parts[0]
-            )),
-            2 => Ok(format!(
-                "op://{}/{}/{}",
-                self.vault.as_ref().unwrap(),
-                parts[0],
-                parts[1]
-            )),
-            _ => Err(FnoxError::ProviderInvalidResponse {
-                provider: "1Password".to_string(),
-                details: format!("Invalid secret reference format: '{}'", value),
-                hint: "Expected 'item', 'item/field', or 'op://vault/item/field'".to_string(),
-                url: "https://fnox.jdx.dev/providers/1password".to_string(),
+        let field = parsed.field.as_deref().unwrap_or("password");
+        Ok(format!("op://{}/{}/{}", vault, parsed.path_str(), field))
+    }
+
+    /// Fetch an item as JSON, or `None` if the vault has no such item
+    async fn get_item(&self, location: &ItemLocation) -> Result<Option<Value>> {
+        let output = self
+            .execute_op_command(&[
+                "item",
+                "get",
+                &location.item,
+                "--vault",
+                &location.vault,
+                "--format",
+                "json",
+            ])
+            .await;
+        match output {
+            Ok(output) => serde_json::from_str(&output).map(Some).map_err(|e| {
+                FnoxError::ProviderInvalidResponse {
+                    provider: "1Password".to_string(),
+                    details: format!("Failed to parse item '{}': {}", location.item, e),
+                    hint: "Check that your 1Password CLI is up to date".to_string(),
+                    url: URL.to_string(),
+                }
             }),
+            Err(FnoxError::ProviderCliFailed { details, .. })
+                if details.contains("isn't an item") =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
         }
     }
 
+    /// Run an `op item` command that reads the item from a template file.
+    /// Templates keep secret values out of the process list, where
+    /// `field=value` arguments would be visible to other users.
+    async fn execute_op_with_template(&self, args: &[&str], template: &Value) -> Result<String> {
+        let write_error = |e: std::io::Error| FnoxError::ProviderCliFailed {
+            provider: "1Password".to_string(),
+            details: format!("Failed to write item template: {}", e),
+            hint: "Check that the temporary directory is writable".to_string(),
+            url: URL.to_string(),
+        };
+        // Created readable by the current user only
+        let mut file = tempfile::NamedTempFile::new().map_err(write_error)?;
+        file.write_all(template.to_string().as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(write_error)?;
+        let path = file.path().to_string_lossy().to_string();
+
+        let mut args = args.to_vec();
+        args.extend(["--template", &path]);
+        self.execute_op_command(&args).await
+    }
+
     /// Execute op CLI command with proper authentication
     async fn execute_op_command(&self, args: &[&str]) -> Result<String> {
         tracing::debug!("Executing op command with args: {:?}", args);
@@ -100,23 +279,34 @@ impl OnePasswordProvider {
             cmd.arg("--account").arg(account);
         }
 
-        let output = cmd.output().await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                FnoxError::ProviderCliNotFound {
-                    provider: "1Password".to_string(),
-                    cli: "op".to_string(),
-                    install_hint: "brew install 1password-cli".to_string(),
-                    url: "https://fnox.jdx.dev/providers/1password".to_string(),
-                }
-            } else {
-                FnoxError::ProviderCliFailed {
-                    provider: "1Password".to_string(),
-                    details: e.to_string(),
-                    hint: "Check that the 1Password CLI is installed and accessible".to_string(),
-                    url: "https://fnox.jdx.dev/providers/1password".to_string(),
+        // `op read` prints the bare secret value
+        let stdout = if args.first() == Some(&"read") {
+            Stdout::Value
+        } else {
+            Stdout::Plain
+        };
+        let output = CommandRunner::new(cmd)
+            .stdout(stdout)
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    FnoxError::ProviderCliNotFound {
+                        provider: "1Password".to_string(),
+                        cli: "op".to_string(),
+                        install_hint: "brew install 1password-cli".to_string(),
+                        url: "https://fnox.jdx.dev/providers/1password".to_string(),
+                    }
+                } else {
+                    FnoxError::ProviderCliFailed {
+                        provider: "1Password".to_string(),
+                        details: e.to_string(),
+                        hint: "Check that the 1Password CLI is installed and accessible"
+                            .to_string(),
+                        url: "https://fnox.jdx.dev/providers/1password".to_string(),
+                    }
                 }
-            }
-        })?;
+            })?;
 
         if !output.status.success() {
             let cow = String::from_utf8_lossy(&output.stderr);
@@ -177,50 +367,30 @@ impl OnePasswordProvider {
             cmd.arg("--account").arg(account);
         }
 
-        cmd.arg("inject")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        cmd.arg("inject");
 
-        let mut child = cmd.spawn().map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                FnoxError::ProviderCliNotFound {
-                    provider: "1Password".to_string(),
-                    cli: "op".to_string(),
-                    install_hint: "brew install 1password-cli".to_string(),
-                    url: "https://fnox.jdx.dev/providers/1password".to_string(),
-                }
-            } else {
-                FnoxError::ProviderCliFailed {
-                    provider: "1Password".to_string(),
-                    details: e.to_string(),
-                    hint: "Check that the 1Password CLI is installed and accessible".to_string(),
-                    url: "https://fnox.jdx.dev/providers/1password".to_string(),
-                }
-            }
-        })?;
-
-        // Write input to stdin
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(input.as_bytes())
-                .await
-                .map_err(|e| FnoxError::ProviderCliFailed {
-                    provider: "1Password".to_string(),
-                    details: format!("Failed to write to stdin: {}", e),
-                    hint: "This is an internal error".to_string(),
-                    url: "https://fnox.jdx.dev/providers/1password".to_string(),
-                })?;
-        }
-
-        let output = child
-            .wait_with_output()
+        let output = CommandRunner::new(cmd)
+            .stdin(input)
+            .stdout(Stdout::Env)
+            .output()
             .await
-            .map_err(|e| FnoxError::ProviderCliFailed {
-                provider: "1Password".to_string(),
-                details: format!("Failed to wait for command: {}", e),
-                hint: "This is an internal error".to_string(),
-                url: "https://fnox.jdx.dev/providers/1password".to_string(),
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    FnoxError::ProviderCliNotFound {
+                        provider: "1Password".to_string(),
+                        cli: "op".to_string(),
+                        install_hint: "brew install 1password-cli".to_string(),
+                        url: "https://fnox.jdx.dev/providers/1password".to_string(),
+                    }
+                } else {
+                    FnoxError::ProviderCliFailed {
+                        provider: "1Password".to_string(),
+                        details: e.to_string(),
+                        hint: "Check that the 1Password CLI is installed and accessible"
+                            .to_string(),
+                        url: "https://fnox.jdx.dev/providers/1password".to_string(),
+                    }
+                }
             })?;
 
         if !output.status.success() {
@@ -270,6 +440,10 @@ impl crate::providers::Provider for OnePasswordProvider {
     async fn get_secret(&self, value: &str) -> Result<String> {
         tracing::debug!("Getting secret '{}' from 1Password", value);
 
+        if self.backend == OnePasswordBackend::Connect {
+            return self.get_secret_connect(value).await;
+        }
+
         let reference = self.value_to_reference(value)?;
         tracing::debug!("Reading 1Password secret: {}", reference);
 
@@ -286,6 +460,10 @@ impl crate::providers::Provider for OnePasswordProvider {
             secrets.len()
         );
 
+        if self.backend == OnePasswordBackend::Connect {
+            return self.get_secrets_batch_connect(secrets).await;
+        }
+
         // If only one secret, fall back to single get_secret
         if secrets.len() == 1 {
             let (key, value) = &secrets[0];
@@ -380,24 +558,118 @@ impl crate::providers::Provider for OnePasswordProvider {
                     }
                 }
             }
+            // Reading each reference on its own would fail the same way
+            Err(
+                e @ (FnoxError::ProviderAuthFailed { .. } | FnoxError::ProviderCliNotFound { .. }),
+            ) => {
+                for key in key_order {
+                    let error = e.map_batch_error(
+                        &key,
+                        "1Password",
+                        "Check your 1Password configuration and authentication",
+                        URL,
+                    );
+                    results.insert(key, Err(error));
+                }
+            }
             Err(e) => {
-                // If op inject failed, fall back to individual get_secret calls
+                // op inject fails as a whole when any reference can't be
+                // resolved, so read them individually to report per key
                 tracing::warn!("op inject failed, falling back to individual calls: {}", e);
-                for (key, value) in secrets {
-                    if !results.contains_key(key) {
-                        let result = self.get_secret(value).await;
-                        results.insert(key.clone(), result);
-                    }
-                }
+                let remaining: Vec<_> = secrets
+                    .iter()
+                    .filter(|(key, _)| !results.contains_key(key))
+                    .cloned()
+                    .collect();
+                let fallback = crate::providers::get_secrets_concurrent(
+                    self,
+                    &remaining,
+                    crate::providers::batch_concurrency(self),
+                )
+                .await;
+                results.extend(fallback);
             }
         }
 
         results
     }
 
+    fn capabilities(&self) -> Vec<crate::providers::ProviderCapability> {
+        // Storing goes through `op item create/edit`
+        if self.vault.is_some() && self.backend == OnePasswordBackend::Op {
+            vec![crate::providers::ProviderCapability::RemoteStorage]
+        } else {
+            vec![crate::providers::ProviderCapability::RemoteRead]
+        }
+    }
+
+    /// Store `value` in the field `key` refers to, creating the item if the
+    /// vault doesn't have it yet. Returns the full `op://` reference.
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        let reference = self.value_to_reference(key)?;
+        let location = ItemLocation::parse(&reference)?;
+
+        match self.get_item(&location).await? {
+            Some(mut item) => {
+                tracing::debug!("Updating 1Password item '{}'", location.item);
+                set_item_field(&mut item, &location, value);
+                let id = item
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or(&location.item)
+                    .to_string();
+                self.execute_op_with_template(
+                    &["item", "edit", &id, "--vault", &location.vault],
+                    &item,
+                )
+                .await?;
+            }
+            None => {
+                tracing::debug!("Creating 1Password item '{}'", location.item);
+                self.execute_op_with_template(
+                    &["item", "create", "--vault", &location.vault],
+                    &new_item_template(&location, value),
+                )
+                .await?;
+            }
+        }
+
+        Ok(reference)
+    }
+
+    /// The Connect host and token, or the service account token and any
+    /// `op signin` account sessions
+    async fn session_env(&self) -> Result<Vec<(String, String)>> {
+        if self.backend == OnePasswordBackend::Connect {
+            let client = self.connect_client()?;
+            return Ok(vec![
+                ("OP_CONNECT_HOST".to_string(), client.host),
+                ("OP_CONNECT_TOKEN".to_string(), client.token),
+            ]);
+        }
+
+        let mut vars: Vec<(String, String)> = env::vars()
+            .filter(|(name, _)| name.starts_with("OP_SESSION_"))
+            .collect();
+        vars.sort();
+        if let Some(token) = self.get_token() {
+            vars.push(("OP_SERVICE_ACCOUNT_TOKEN".to_string(), token));
+        }
+        if !vars.is_empty()
+            && let Some(account) = &self.account
+        {
+            vars.push(("OP_ACCOUNT".to_string(), account.clone()));
+        }
+        Ok(vars)
+    }
+
     async fn test_connection(&self) -> Result<()> {
         tracing::debug!("Testing connection to 1Password");
 
+        if self.backend == OnePasswordBackend::Connect {
+            return self.connect_client()?.test().await;
+        }
+
         // Try to get the current user as a basic connectivity test
         let output = self.execute_op_command(&["whoami"]).await?;
 
@@ -407,8 +679,290 @@ impl crate::providers::Provider for OnePasswordProvider {
     }
 }
 
+/// The secrets read from one vault: item -> (key, reference, location)
+type VaultItems = IndexMap<String, Vec<(String, String, ItemLocation)>>;
+
+/// Where an `op://vault/item[/section]/field` reference points
+#[derive(Debug, PartialEq, Eq)]
+struct ItemLocation {
+    vault: String,
+    item: String,
+    section: Option<String>,
+    field: String,
+}
+
+impl ItemLocation {
+    fn parse(reference: &str) -> Result<Self> {
+        let parsed = reference::ONEPASSWORD.parse(reference)?;
+        let mut path = parsed.path.into_iter();
+        let (Some(vault), Some(item), Some(field)) = (path.next(), path.next(), parsed.field)
+        else {
+            return Err(FnoxError::ProviderInvalidResponse {
+                provider: "1Password".to_string(),
+                details: format!("Cannot store a secret at '{}'", reference),
+                hint: "Use a reference of the form 'op://vault/item[/section]/field'".to_string(),
+                url: URL.to_string(),
+            });
+        };
+        Ok(Self {
+            vault,
+            item,
+            section: path.next(),
+            field,
+        })
+    }
+}
+
+/// Client for the REST API of a 1Password Connect server
+struct ConnectClient {
+    host: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl ConnectClient {
+    /// GET a path of the API, or `None` on 404
+    async fn get(&self, path: &str, filter: Option<String>) -> Result<Option<Value>> {
+        let url = format!("{}{}", self.host, path);
+        tracing::debug!("Requesting 1Password Connect: {}", url);
+
+        let mut request = self.client.get(&url).bearer_auth(&self.token);
+        if let Some(filter) = filter {
+            request = request.query(&[("filter", filter)]);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| FnoxError::ProviderApiError {
+                provider: "1Password".to_string(),
+                details: format!("HTTP request failed: {}", e),
+                hint: "Check network connectivity to the Connect server".to_string(),
+                url: URL.to_string(),
+            })?;
+
+        let status = response.status();
+        if status.as_u16() == 404 {
+            return Ok(None);
+        }
+        if status.as_u16() == 401 || status.as_u16() == 403 {
+            let body = response.text().await.unwrap_or_default();
+            return Err(FnoxError::ProviderAuthFailed {
+                provider: "1Password".to_string(),
+                details: format!("HTTP {}: {}", status, body),
+                hint: "Check that OP_CONNECT_TOKEN is valid and can access the vault".to_string(),
+                url: URL.to_string(),
+            });
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(FnoxError::ProviderApiError {
+                provider: "1Password".to_string(),
+                details: format!("HTTP {}: {}", status, body),
+                hint: "Check the Connect server's logs".to_string(),
+                url: URL.to_string(),
+            });
+        }
+
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| FnoxError::ProviderInvalidResponse {
+                provider: "1Password".to_string(),
+                details: format!("Failed to parse Connect response: {}", e),
+                hint: "Check that OP_CONNECT_HOST points at a 1Password Connect server".to_string(),
+                url: URL.to_string(),
+            })
+    }
+
+    /// ID of the vault named `vault`, which may also be given by ID
+    async fn vault_id(&self, vault: &str) -> Result<String> {
+        let by_name = self
+            .get("/v1/vaults", Some(format!("name eq \"{}\"", vault)))
+            .await?;
+        if let Some(id) = first_id(by_name.as_ref()) {
+            return Ok(id);
+        }
+        if let Some(id) = self
+            .get(&format!("/v1/vaults/{}", vault), None)
+            .await?
+            .as_ref()
+            .and_then(|v| v.get("id"))
+            .and_then(Value::as_str)
+        {
+            return Ok(id.to_string());
+        }
+        Err(FnoxError::ProviderApiError {
+            provider: "1Password".to_string(),
+            details: format!("Vault '{}' not found", vault),
+            hint: "Check the vault name and that the Connect token can access it".to_string(),
+            url: URL.to_string(),
+        })
+    }
+
+    /// The full item titled `item` in a vault, which may also be given by ID
+    async fn item(&self, vault_id: &str, item: &str) -> Result<Value> {
+        let items = format!("/v1/vaults/{}/items", vault_id);
+        let by_title = self
+            .get(&items, Some(format!("title eq \"{}\"", item)))
+            .await?;
+        let id = first_id(by_title.as_ref()).unwrap_or_else(|| item.to_string());
+        self.get(&format!("{}/{}", items, id), None)
+            .await?
+            .ok_or_else(|| FnoxError::ProviderSecretNotFound {
+                provider: "1Password".to_string(),
+                secret: item.to_string(),
+                hint: "Check that the item exists in your 1Password vault".to_string(),
+                url: URL.to_string(),
+            })
+    }
+
+    async fn test(&self) -> Result<()> {
+        self.get("/v1/vaults", None).await?;
+        Ok(())
+    }
+}
+
+/// `id` of the first entry of a list response
+fn first_id(list: Option<&Value>) -> Option<String> {
+    list?
+        .as_array()?
+        .first()?
+        .get("id")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Value of the field `location` names in an item's JSON
+fn field_value(item: &Value, location: &ItemLocation, reference: &str) -> Result<String> {
+    let section_id = match &location.section {
+        Some(section) => item
+            .get("sections")
+            .and_then(Value::as_array)
+            .and_then(|sections| sections.iter().find(|s| is_named(s, section)))
+            .and_then(|s| s.get("id"))
+            .and_then(Value::as_str),
+        None => None,
+    };
+    let field = item
+        .get("fields")
+        .and_then(Value::as_array)
+        .and_then(|fields| {
+            fields.iter().find(|field| {
+                is_named(field, &location.field)
+                    && (location.section.is_none()
+                        || section_id.is_some_and(|id| {
+                            field
+                                .get("section")
+                                .is_some_and(|section| is_named(section, id))
+                        }))
+            })
+        });
+    field
+        .and_then(|field| field.get("value"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| FnoxError::ProviderSecretNotFound {
+            provider: "1Password".to_string(),
+            secret: reference.to_string(),
+            hint: "Check that the item has this field".to_string(),
+            url: URL.to_string(),
+        })
+}
+
+/// Whether an item, section, or field JSON object has `name` as its ID or
+/// label
+fn is_named(candidate: &Value, name: &str) -> bool {
+    ["id", "label"].iter().any(|k| {
+        candidate
+            .get(*k)
+            .and_then(Value::as_str)
+            .is_some_and(|v| v.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Template for a new Password item holding `value` at `location`
+fn new_item_template(location: &ItemLocation, value: &str) -> Value {
+    let mut item = json!({
+        "title": location.item,
+        "category": "PASSWORD",
+        "fields": [],
+    });
+    set_item_field(&mut item, location, value);
+    item
+}
+
+/// Set the field `location` names in an item's JSON, adding the field (and
+/// its section) if the item doesn't have it
+fn set_item_field(item: &mut Value, location: &ItemLocation, value: &str) {
+    let section_id = location.section.as_ref().map(|section| {
+        let sections = array_entry(item, "sections");
+        match sections.iter().find(|s| is_named(s, section)) {
+            Some(existing) => existing
+                .get("id")
+                .and_then(Value::as_str)
+                .unwrap_or(section)
+                .to_string(),
+            None => {
+                sections.push(json!({ "id": section, "label": section }));
+                section.clone()
+            }
+        }
+    });
+
+    let fields = array_entry(item, "fields");
+    let existing = fields.iter_mut().find(|field| {
+        is_named(field, &location.field)
+            && section_id.as_ref().is_none_or(|id| {
+                field
+                    .get("section")
+                    .is_some_and(|section| is_named(section, id))
+            })
+    });
+    if let Some(field) = existing {
+        field["value"] = json!(value);
+        return;
+    }
+
+    let mut field = json!({
+        "label": location.field,
+        "type": "CONCEALED",
+        "value": value,
+    });
+    if let Some(id) = section_id {
+        field["section"] = json!({ "id": id });
+    } else if location.field == "password" {
+        // Fill in the item's built-in password rather than a custom field
+        field["id"] = json!("password");
+        field["purpose"] = json!("PASSWORD");
+    }
+    fields.push(field);
+}
+
+/// The array at `key` in an item's JSON, created if missing
+fn array_entry<'a>(item: &'a mut Value, key: &str) -> &'a mut Vec<Value> {
+    if !item.get(key).is_some_and(Value::is_array) {
+        item[key] = json!([]);
+    }
+    item[key].as_array_mut().expect("just set to an array")
+}
+
 pub fn env_dependencies() -> &'static [&'static str] {
-    &["OP_SERVICE_ACCOUNT_TOKEN", "FNOX_OP_SERVICE_ACCOUNT_TOKEN"]
+    &[
+        "OP_SERVICE_ACCOUNT_TOKEN",
+        "FNOX_OP_SERVICE_ACCOUNT_TOKEN",
+        "OP_CONNECT_HOST",
+        "FNOX_OP_CONNECT_HOST",
+        "OP_CONNECT_TOKEN",
+        "FNOX_OP_CONNECT_TOKEN",
+    ]
+}
+
+/// `name` from the environment, preferring its `FNOX_` prefixed form
+fn env_var(name: &str) -> Option<String> {
+    env::var(format!("FNOX_{name}"))
+        .or_else(|_| env::var(name))
+        .ok()
 }
 
 fn op_service_account_token() -> Option<String> {
@@ -416,3 +970,214 @@ fn op_service_account_token() -> Option<String> {
         .or_else(|_| env::var("OP_SERVICE_ACCOUNT_TOKEN"))
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{Provider, ProviderCapability};
+    use crate::recording::Cassette;
+
+    fn location(reference: &str) -> ItemLocation {
+        ItemLocation::parse(reference).unwrap()
+    }
+
+    #[test]
+    fn parses_item_locations() {
+        assert_eq!(
+            location("op://Private/GitHub/token"),
+            ItemLocation {
+                vault: "Private".to_string(),
+                item: "GitHub".to_string(),
+                section: None,
+                field: "token".to_string(),
+            }
+        );
+        assert_eq!(
+            location("op://Private/GitHub/API/token").section.as_deref(),
+            Some("API")
+        );
+    }
+
+    #[test]
+    fn new_items_store_passwords_in_the_builtin_field() {
+        let item = new_item_template(&location("op://Private/db/password"), "s3cret");
+        assert_eq!(item["title"], "db");
+        assert_eq!(item["category"], "PASSWORD");
+        assert_eq!(
+            item["fields"],
+            json!([{
+                "id": "password",
+                "purpose": "PASSWORD",
+                "label": "password",
+                "type": "CONCEALED",
+                "value": "s3cret",
+            }])
+        );
+    }
+
+    #[test]
+    fn new_items_create_missing_sections() {
+        let item = new_item_template(&location("op://Private/db/Prod/url"), "postgres://");
+        assert_eq!(item["sections"], json!([{ "id": "Prod", "label": "Prod" }]));
+        assert_eq!(item["fields"][0]["section"], json!({ "id": "Prod" }));
+        assert_eq!(item["fields"][0]["label"], "url");
+    }
+
+    #[test]
+    fn updates_existing_fields_in_place() {
+        let mut item = json!({
+            "id": "abc123",
+            "sections": [{ "id": "s1", "label": "Prod" }],
+            "fields": [
+                { "id": "password", "label": "password", "value": "old" },
+                { "id": "f1", "label": "url", "value": "dev", "section": { "id": "s1", "label": "Prod" } },
+            ],
+        });
+
+        set_item_field(&mut item, &location("op://Private/db/password"), "new");
+        set_item_field(&mut item, &location("op://Private/db/prod/URL"), "prod");
+
+        assert_eq!(item["fields"][0]["value"], "new");
+        assert_eq!(item["fields"][1]["value"], "prod");
+        assert_eq!(item["fields"].as_array().unwrap().len(), 2);
+        assert_eq!(item["sections"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn adds_fields_missing_from_existing_items() {
+        let mut item = json!({ "id": "abc123", "fields": [] });
+        set_item_field(&mut item, &location("op://Private/db/api_key"), "k");
+        assert_eq!(
+            item["fields"],
+            json!([{ "label": "api_key", "type": "CONCEALED", "value": "k" }])
+        );
+    }
+
+    fn provider(vault: Option<&str>, backend: OnePasswordBackend) -> OnePasswordProvider {
+        OnePasswordProvider::new(
+            vault.map(String::from),
+            None,
+            None,
+            Some(backend),
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn storage_requires_a_vault() {
+        let with_vault = provider(Some("Private"), OnePasswordBackend::Op);
+        assert_eq!(
+            with_vault.capabilities(),
+            vec![ProviderCapability::RemoteStorage]
+        );
+        let without_vault = provider(None, OnePasswordBackend::Op);
+        assert_eq!(
+            without_vault.capabilities(),
+            vec![ProviderCapability::RemoteRead]
+        );
+    }
+
+    #[test]
+    fn connect_backend_is_read_only() {
+        let connect = provider(Some("Private"), OnePasswordBackend::Connect);
+        assert_eq!(connect.capabilities(), vec![ProviderCapability::RemoteRead]);
+    }
+
+    #[test]
+    fn reads_fields_from_connect_items() {
+        let item = json!({
+            "id": "abc123",
+            "sections": [{ "id": "s1", "label": "Prod" }],
+            "fields": [
+                { "id": "password", "label": "password", "value": "hunter2" },
+                { "id": "f1", "label": "url", "value": "dev" },
+                { "id": "f2", "label": "url", "value": "prod", "section": { "id": "s1" } },
+            ],
+        });
+        let read = |reference: &str| field_value(&item, &location(reference), reference);
+
+        assert_eq!(read("op://Private/db/password").unwrap(), "hunter2");
+        assert_eq!(read("op://Private/db/url").unwrap(), "dev");
+        assert_eq!(read("op://Private/db/prod/url").unwrap(), "prod");
+        assert!(matches!(
+            read("op://Private/db/username"),
+            Err(FnoxError::ProviderSecretNotFound { .. })
+        ));
+        assert!(read("op://Private/db/Staging/url").is_err());
+    }
+
+    #[test]
+    fn takes_ids_from_connect_lists() {
+        assert_eq!(
+            first_id(Some(&json!([{ "id": "v1", "name": "Private" }]))),
+            Some("v1".to_string())
+        );
+        assert_eq!(first_id(Some(&json!([]))), None);
+        assert_eq!(first_id(None), None);
+    }
+
+    #[tokio::test]
+    async fn replays_reads() {
+        let cassette = Cassette::open("onepassword_get").unwrap();
+        let provider = provider(Some("fnox"), OnePasswordBackend::Op);
+
+        let (password, field, missing) = cassette
+            .run(async {
+                (
+                    provider.get_secret("db").await,
+                    provider.get_secret("api/credential").await,
+                    provider.get_secret("missing").await,
+                )
+            })
+            .await;
+
+        assert_eq!(password.unwrap(), "[VALUE-1]");
+        assert_eq!(field.unwrap(), "[VALUE-2]");
+        // The "[ERROR] <timestamp>" prefix is stripped
+        assert!(
+            matches!(&missing, Err(FnoxError::ProviderCliFailed { details, .. })
+                if details.starts_with("could not read secret 'op://fnox/missing/password'")),
+            "{missing:?}"
+        );
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_batch_reads() {
+        let cassette = Cassette::open("onepassword_batch").unwrap();
+        let provider = provider(Some("fnox"), OnePasswordBackend::Op);
+        let secrets = [
+            ("DB_PASSWORD".to_string(), "db".to_string()),
+            ("API_KEY".to_string(), "api/credential".to_string()),
+        ];
+
+        let results = cassette.run(provider.get_secrets_batch(&secrets)).await;
+
+        assert_eq!(results["DB_PASSWORD"].as_ref().unwrap(), "[VALUE-1]");
+        assert_eq!(results["API_KEY"].as_ref().unwrap(), "[VALUE-2]");
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_batches_with_missing_items_one_by_one() {
+        let cassette = Cassette::open("onepassword_batch_missing").unwrap();
+        let provider = provider(Some("fnox"), OnePasswordBackend::Op);
+        let secrets = [
+            ("DB_PASSWORD".to_string(), "db".to_string()),
+            ("MISSING".to_string(), "missing".to_string()),
+        ];
+
+        // `op inject` fails as a whole, so each reference is read on its own
+        let results = cassette.run(provider.get_secrets_batch(&secrets)).await;
+
+        assert_eq!(results["DB_PASSWORD"].as_ref().unwrap(), "[VALUE-1]");
+        assert!(
+            matches!(results["MISSING"], Err(FnoxError::ProviderCliFailed { .. })),
+            "{:?}",
+            results["MISSING"]
+        );
+        cassette.finish().unwrap();
+    }
+}
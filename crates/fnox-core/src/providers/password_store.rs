@@ -1,5 +1,6 @@
 use crate::error::{FnoxError, Result};
 use crate::providers::ProviderCapability;
+use crate::providers::reference;
 use async_trait::async_trait;
 use tokio::process::Command;
 
@@ -8,6 +9,145 @@ pub struct PasswordStoreProvider {
     prefix: Option<String>,
     store_dir: Option<String>,
     gpg_opts: Option<String>,
+    whole_entry: bool,
+}
+
+/// Part of an entry a reference selects with `#`. pass entries conventionally
+/// hold the password on the first line and `key: value` lines below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    /// The first line, or the whole entry with `whole_entry = true`
+    Password,
+    /// `#line:N`, 1-indexed
+    Line(usize),
+    /// `#name`, the value of the first `name: value` line below the password
+    Field(String),
+}
+
+impl Selector {
+    fn parse(value: &str) -> Result<(String, Self)> {
+        let reference = reference::PASSWORD_STORE.parse(value)?;
+        let path = reference.path_str();
+        let Some(fragment) = reference.fragment else {
+            return Ok((path, Selector::Password));
+        };
+        let selector = match fragment.strip_prefix("line:") {
+            Some(n) => match n.trim().parse::<usize>() {
+                Ok(line) if line > 0 => Selector::Line(line),
+                _ => {
+                    return Err(FnoxError::ProviderInvalidReference {
+                        provider: reference::PASSWORD_STORE.provider.to_string(),
+                        reference: value.to_string(),
+                        details: format!("'{n}' isn't a line number; lines start at 1"),
+                        syntax: reference::PASSWORD_STORE.syntax.to_string(),
+                        url: reference::PASSWORD_STORE.url.to_string(),
+                    });
+                }
+            },
+            None => Selector::Field(fragment),
+        };
+        Ok((path, selector))
+    }
+}
+
+/// The `name: value` line below the password whose name matches `name`,
+/// ignoring case, as its index and trimmed value
+fn find_field<'a>(lines: &[&'a str], name: &str) -> Option<(usize, &'a str)> {
+    lines.iter().enumerate().skip(1).find_map(|(i, line)| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| (i, value.trim()))
+    })
+}
+
+/// The part of `content` that `selector` picks, or a message saying what's
+/// missing
+fn select(
+    content: &str,
+    selector: &Selector,
+    whole_entry: bool,
+) -> std::result::Result<String, String> {
+    // `lines` leaves the `\r` of a last line written with CRLF but no final
+    // newline
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .collect();
+    match selector {
+        Selector::Password if whole_entry => Ok(content.trim().to_string()),
+        Selector::Password => Ok(lines.first().copied().unwrap_or_default().to_string()),
+        Selector::Line(n) => lines
+            .get(n - 1)
+            .map(|line| line.to_string())
+            .ok_or_else(|| {
+                format!(
+                    "line {n} is out of range; the entry has {} line(s)",
+                    lines.len()
+                )
+            }),
+        Selector::Field(name) => find_field(&lines, name)
+            .map(|(_, value)| value.to_string())
+            .ok_or_else(|| format!("the entry has no '{name}: ...' line")),
+    }
+}
+
+/// Without `whole_entry`, reads only return one line, so a multi-line value
+/// would come back truncated or overwrite the lines below it
+fn check_single_line(value: &str) -> std::result::Result<(), String> {
+    if value.contains('\n') {
+        return Err(
+            "a multi-line value would only be read back up to its first line; set whole_entry = true on the provider to store whole entries"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// `content` with the part `selector` picks replaced by `value`, keeping every
+/// other line. A field that isn't there yet is added at the end.
+fn replace(content: &str, selector: &Selector, value: &str) -> std::result::Result<String, String> {
+    check_single_line(value)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    if let Selector::Password = selector
+        && lines.len() <= 1
+    {
+        // Nothing below the password to keep
+        return Ok(value.to_string());
+    }
+    match selector {
+        Selector::Password => lines[0] = value.to_string(),
+        Selector::Line(n) => {
+            let Some(line) = lines.get_mut(n - 1) else {
+                return Err(format!(
+                    "line {n} is out of range; the entry has {} line(s)",
+                    lines.len()
+                ));
+            };
+            *line = value.to_string();
+        }
+        Selector::Field(name) => {
+            let existing = find_field(&content.lines().collect::<Vec<_>>(), name).map(|(i, _)| i);
+            match existing {
+                Some(i) => {
+                    // Keep the name as written, e.g. `Username:`
+                    let key = lines[i]
+                        .split_once(':')
+                        .map(|(key, _)| key.to_string())
+                        .unwrap_or_default();
+                    lines[i] = format!("{key}: {value}");
+                }
+                None => {
+                    if lines.is_empty() {
+                        // Fields go below the password, even an empty one
+                        lines.push(String::new());
+                    }
+                    lines.push(format!("{name}: {value}"));
+                }
+            }
+        }
+    }
+    Ok(lines.join("\n") + "\n")
 }
 
 impl PasswordStoreProvider {
@@ -15,11 +155,13 @@ impl PasswordStoreProvider {
         prefix: Option<String>,
         store_dir: Option<String>,
         gpg_opts: Option<String>,
+        whole_entry: Option<bool>,
     ) -> Result<Self> {
         Ok(Self {
             prefix,
             store_dir,
             gpg_opts,
+            whole_entry: whole_entry.unwrap_or(false),
         })
     }
 
@@ -113,30 +255,20 @@ impl PasswordStoreProvider {
                 url: "https://fnox.jdx.dev/providers/password-store".to_string(),
             })?;
 
-        Ok(stdout.trim().to_string())
+        Ok(stdout)
     }
-}
 
-#[async_trait]
-impl crate::providers::Provider for PasswordStoreProvider {
-    fn capabilities(&self) -> Vec<ProviderCapability> {
-        vec![ProviderCapability::RemoteStorage]
-    }
-
-    async fn get_secret(&self, value: &str) -> Result<String> {
-        let secret_path = self.build_secret_path(value);
-
-        tracing::debug!("Getting secret '{secret_path}' from password-store");
-
-        // Use `pass show` to retrieve the secret
-        self.execute_pass_command(&["show", &secret_path]).await
+    /// Decrypt the entry at `secret_path`, or None when there isn't one
+    async fn show_entry(&self, secret_path: &str) -> Result<Option<String>> {
+        match self.execute_pass_command(&["show", secret_path]).await {
+            Ok(content) => Ok(Some(content)),
+            Err(FnoxError::ProviderSecretNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
-    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
-        let secret_path = self.build_secret_path(key);
-
-        tracing::debug!("Storing secret '{secret_path}' in password-store");
-
+    /// Write `content` as the whole entry at `secret_path`
+    async fn insert_entry(&self, secret_path: &str, content: &str) -> Result<()> {
         // Use `pass insert` with multiline support
         // pass insert -m will read from stdin until EOF
         let mut cmd = Command::new("pass");
@@ -145,7 +277,7 @@ impl crate::providers::Provider for PasswordStoreProvider {
         cmd.arg("insert")
             .arg("-m") // Multiline
             .arg("-f") // Force (overwrite if exists)
-            .arg(&secret_path)
+            .arg(secret_path)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
@@ -172,15 +304,14 @@ impl crate::providers::Provider for PasswordStoreProvider {
         if let Some(mut stdin) = child.stdin.take() {
             use tokio::io::AsyncWriteExt;
 
-            stdin
-                .write_all(value.as_bytes())
-                .await
-                .map_err(|e| FnoxError::ProviderCliFailed {
+            stdin.write_all(content.as_bytes()).await.map_err(|e| {
+                FnoxError::ProviderCliFailed {
                     provider: "password-store".to_string(),
                     details: format!("Failed to write to stdin: {}", e),
                     hint: "This is an internal error".to_string(),
                     url: "https://fnox.jdx.dev/providers/password-store".to_string(),
-                })?;
+                }
+            })?;
             drop(stdin); // Explicitly close stdin to signal EOF
         }
 
@@ -213,6 +344,82 @@ impl crate::providers::Provider for PasswordStoreProvider {
             });
         }
 
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for PasswordStoreProvider {
+    fn capabilities(&self) -> Vec<ProviderCapability> {
+        vec![ProviderCapability::RemoteStorage]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        let (path, selector) = Selector::parse(value)?;
+        let secret_path = self.build_secret_path(&path);
+
+        tracing::debug!("Getting secret '{secret_path}' from password-store");
+
+        // Use `pass show` to retrieve the secret
+        let content = self.execute_pass_command(&["show", &secret_path]).await?;
+        select(&content, &selector, self.whole_entry).map_err(|details| {
+            FnoxError::ProviderSecretNotFound {
+                provider: "password-store".to_string(),
+                secret: format!("{value} ({details})"),
+                hint: "Check the entry with `pass show`; fields are 'name: value' lines below the password"
+                    .to_string(),
+                url: "https://fnox.jdx.dev/providers/password-store".to_string(),
+            }
+        })
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        let (path, selector) = Selector::parse(key)?;
+        let secret_path = self.build_secret_path(&path);
+
+        tracing::debug!("Storing secret '{secret_path}' in password-store");
+
+        // Rewrite only the selected line of an existing entry, so the
+        // metadata lines stored with the password survive
+        let existing = if selector == Selector::Password && self.whole_entry {
+            None
+        } else {
+            self.show_entry(&secret_path).await?
+        };
+        let content = match (&selector, existing) {
+            (Selector::Password, None) if self.whole_entry => value.to_string(),
+            (Selector::Password, None) => {
+                check_single_line(value).map_err(|details| FnoxError::ProviderCliFailed {
+                    provider: "password-store".to_string(),
+                    details: format!("Can't store '{key}': {details}"),
+                    hint: "Set whole_entry = true on the provider for multi-line secrets"
+                        .to_string(),
+                    url: "https://fnox.jdx.dev/providers/password-store".to_string(),
+                })?;
+                value.to_string()
+            }
+            (_, None) => {
+                return Err(FnoxError::ProviderSecretNotFound {
+                    provider: "password-store".to_string(),
+                    secret: secret_path,
+                    hint: "Store the password first; fields and lines are set in an existing entry"
+                        .to_string(),
+                    url: "https://fnox.jdx.dev/providers/password-store".to_string(),
+                });
+            }
+            (selector, Some(existing)) => {
+                replace(&existing, selector, value).map_err(|details| {
+                    FnoxError::ProviderCliFailed {
+                        provider: "password-store".to_string(),
+                        details: format!("Can't update '{key}': {details}"),
+                        hint: "Edit the entry with `pass edit` instead".to_string(),
+                        url: "https://fnox.jdx.dev/providers/password-store".to_string(),
+                    }
+                })?
+            }
+        };
+        self.insert_entry(&secret_path, &content).await?;
+
         tracing::debug!("Successfully stored secret '{secret_path}' in password-store");
         Ok(key.to_string())
     }
@@ -248,3 +455,109 @@ fn password_store_gpg_opts() -> Option<String> {
         .or_else(|_| std::env::var("PASSWORD_STORE_GPG_OPTS"))
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "hunter2\nUsername: alice\nurl: https://example.com\n";
+
+    fn parse(value: &str) -> (String, Selector) {
+        Selector::parse(value).unwrap()
+    }
+
+    fn field(name: &str) -> Selector {
+        Selector::Field(name.to_string())
+    }
+
+    #[test]
+    fn parses_selectors() {
+        let path = || "web/example".to_string();
+        assert_eq!(parse("web/example"), (path(), Selector::Password));
+        assert_eq!(parse("web/example#username"), (path(), field("username")));
+        assert_eq!(parse("web/example#line:3"), (path(), Selector::Line(3)));
+        assert!(Selector::parse("web/example#line:0").is_err());
+        assert!(Selector::parse("web/example#line:two").is_err());
+        assert!(Selector::parse("web/example#").is_err());
+    }
+
+    #[test]
+    fn selects_the_password_fields_and_lines() {
+        assert_eq!(
+            select(ENTRY, &Selector::Password, false).unwrap(),
+            "hunter2"
+        );
+        assert_eq!(
+            select(ENTRY, &Selector::Password, true).unwrap(),
+            ENTRY.trim()
+        );
+        assert_eq!(
+            select(ENTRY, &Selector::Line(3), false).unwrap(),
+            "url: https://example.com"
+        );
+        assert_eq!(select(ENTRY, &field("username"), false).unwrap(), "alice");
+        assert_eq!(
+            select(ENTRY, &field("URL"), true).unwrap(),
+            "https://example.com"
+        );
+
+        assert_eq!(
+            select(ENTRY, &Selector::Line(4), false).unwrap_err(),
+            "line 4 is out of range; the entry has 3 line(s)"
+        );
+        assert_eq!(
+            select(ENTRY, &field("email"), false).unwrap_err(),
+            "the entry has no 'email: ...' line"
+        );
+        // The password line is never a field, even when it has a colon
+        assert!(select("user: pw\n", &field("user"), false).is_err());
+    }
+
+    #[test]
+    fn selects_lines_of_crlf_entries_without_their_line_endings() {
+        let crlf = "hunter2\r\nUsername: alice\r\nurl: https://example.com\r";
+        assert_eq!(select(crlf, &Selector::Password, false).unwrap(), "hunter2");
+        assert_eq!(
+            select("hunter2\r", &Selector::Password, false).unwrap(),
+            "hunter2"
+        );
+        assert_eq!(
+            select(crlf, &Selector::Line(3), false).unwrap(),
+            "url: https://example.com"
+        );
+        assert_eq!(select(crlf, &field("username"), false).unwrap(), "alice");
+    }
+
+    #[test]
+    fn replacing_the_password_keeps_the_lines_below() {
+        assert_eq!(
+            replace(ENTRY, &Selector::Password, "correct-horse").unwrap(),
+            "correct-horse\nUsername: alice\nurl: https://example.com\n"
+        );
+        // Without whole_entry only the first line would be read back
+        assert!(replace("old\n", &Selector::Password, "a\nb").is_err());
+        assert!(replace(ENTRY, &field("username"), "a\nb").is_err());
+        assert!(
+            replace(ENTRY, &Selector::Password, "a\nb")
+                .unwrap_err()
+                .contains("whole_entry = true")
+        );
+    }
+
+    #[test]
+    fn replaces_fields_and_lines() {
+        assert_eq!(
+            replace(ENTRY, &field("username"), "bob").unwrap(),
+            "hunter2\nUsername: bob\nurl: https://example.com\n"
+        );
+        assert_eq!(
+            replace(ENTRY, &field("email"), "alice@example.com").unwrap(),
+            "hunter2\nUsername: alice\nurl: https://example.com\nemail: alice@example.com\n"
+        );
+        assert_eq!(
+            replace(ENTRY, &Selector::Line(3), "https://example.org").unwrap(),
+            "hunter2\nUsername: alice\nhttps://example.org\n"
+        );
+        assert!(replace(ENTRY, &Selector::Line(5), "x").is_err());
+    }
+}
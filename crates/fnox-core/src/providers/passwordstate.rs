@@ -2,7 +2,6 @@ use crate::env;
 use crate::error::{FnoxError, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
-use std::collections::HashMap;
 pub fn env_dependencies() -> &'static [&'static str] {
     &["PASSWORDSTATE_API_KEY", "FNOX_PASSWORDSTATE_API_KEY"]
 }
@@ -64,17 +63,22 @@ impl PasswordstateProvider {
         })
     }
 
-    /// Create an HTTP client with appropriate SSL settings
+    /// Get the shared HTTP client with appropriate SSL settings
     fn create_client(&self) -> Result<reqwest::Client> {
-        reqwest::Client::builder()
-            .danger_accept_invalid_certs(!self.verify_ssl)
-            .build()
-            .map_err(|e| FnoxError::ProviderApiError {
+        // Without a key the server answers 401, which reads like a bad key
+        if self.api_key.is_empty() {
+            return Err(FnoxError::ProviderAuthFailed {
                 provider: "Passwordstate".to_string(),
-                details: format!("Failed to create HTTP client: {}", e),
-                hint: "Check your network configuration".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
-            })
+                details: "No API key configured".to_string(),
+                hint: "Set PASSWORDSTATE_API_KEY or api_key in the provider config".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
+            });
+        }
+        Ok(if self.verify_ssl {
+            crate::http::http_client()
+        } else {
+            crate::http::insecure_http_client()
+        })
     }
 
     /// Parse value reference into (identifier, field, is_id)
@@ -100,7 +104,7 @@ impl PasswordstateProvider {
                 provider: "Passwordstate".to_string(),
                 details: format!("Invalid reference format: '{}'", value),
                 hint: "Expected 'id', 'id/field', 'title', or 'title/field'".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             }),
         }
     }
@@ -121,7 +125,7 @@ impl PasswordstateProvider {
                 provider: "Passwordstate".to_string(),
                 details: format!("HTTP request failed: {}", e),
                 hint: "Check network connectivity to the Passwordstate server".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             })?;
 
         if !response.status().is_success() {
@@ -133,14 +137,14 @@ impl PasswordstateProvider {
                     details: format!("HTTP {}: {}", status, body),
                     hint: "Check your API key is valid and has access to this password list"
                         .to_string(),
-                    url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                    url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
                 });
             }
             return Err(FnoxError::ProviderApiError {
                 provider: "Passwordstate".to_string(),
                 details: format!("HTTP {}: {}", status, body),
                 hint: "Check your Passwordstate server configuration".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             });
         }
 
@@ -154,7 +158,7 @@ impl PasswordstateProvider {
                     details: format!("Failed to parse response: {}", e),
                     hint: "The Passwordstate API returned an unexpected response format"
                         .to_string(),
-                    url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                    url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
                 })?;
 
         entries
@@ -164,7 +168,7 @@ impl PasswordstateProvider {
                 provider: "Passwordstate".to_string(),
                 secret: password_id.to_string(),
                 hint: "Check that the password ID exists in Passwordstate".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             })
     }
 
@@ -185,7 +189,7 @@ impl PasswordstateProvider {
             details: format!("Field '{}' not found or empty in password entry", field),
             hint: "Available fields: password, username, title, url, description, notes"
                 .to_string(),
-            url: "https://fnox.jdx.dev/providers/overview".to_string(),
+            url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
         })
     }
 
@@ -211,7 +215,7 @@ impl PasswordstateProvider {
                 provider: "Passwordstate".to_string(),
                 details: format!("HTTP request failed: {}", e),
                 hint: "Check network connectivity to the Passwordstate server".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             })?;
 
         if !response.status().is_success() {
@@ -223,14 +227,14 @@ impl PasswordstateProvider {
                     details: format!("HTTP {}: {}", status, body),
                     hint: "Check your API key is valid and has access to this password list"
                         .to_string(),
-                    url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                    url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
                 });
             }
             return Err(FnoxError::ProviderApiError {
                 provider: "Passwordstate".to_string(),
                 details: format!("HTTP {}: {}", status, body),
                 hint: "Check your Passwordstate server configuration".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             });
         }
 
@@ -243,7 +247,7 @@ impl PasswordstateProvider {
                     details: format!("Failed to parse response: {}", e),
                     hint: "The Passwordstate API returned an unexpected response format"
                         .to_string(),
-                    url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                    url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
                 })?;
 
         // Find exact title match (case-insensitive)
@@ -260,7 +264,7 @@ impl PasswordstateProvider {
                 secret: format!("{} (in list {})", title, self.password_list_id),
                 hint: "Check that the password title exists in the specified password list"
                     .to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             })
     }
 }
@@ -285,30 +289,20 @@ impl crate::providers::Provider for PasswordstateProvider {
         Self::extract_field(&entry, &field)
     }
 
-    async fn get_secrets_batch(
-        &self,
-        secrets: &[(String, String)],
-    ) -> HashMap<String, Result<String>> {
-        // Passwordstate doesn't have a batch API, so we fetch in parallel
-        use futures::stream::{self, StreamExt};
-
-        let secrets_vec: Vec<_> = secrets.to_vec();
-
-        let results: Vec<_> = stream::iter(secrets_vec)
-            .map(|(key, value)| async move {
-                let result = self.get_secret(&value).await;
-                (key, result)
-            })
-            .buffer_unordered(10)
-            .collect()
-            .await;
-
-        results.into_iter().collect()
-    }
-
     async fn test_connection(&self) -> Result<()> {
         let client = self.create_client()?;
 
+        // Prime DNS/TLS first so an unreachable server is reported as such, and so
+        // later batch requests in this process reuse the pooled connection
+        crate::http::warm_up(&client, &self.base_url)
+            .await
+            .map_err(|e| FnoxError::ProviderApiError {
+                provider: "Passwordstate".to_string(),
+                details: format!("Cannot reach '{}': {}", self.base_url, e),
+                hint: "Check network connectivity to the Passwordstate server".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
+            })?;
+
         // Try to access the password list to verify connection and authentication
         let url = format!("{}/api/passwords/{}", self.base_url, self.password_list_id);
 
@@ -323,7 +317,7 @@ impl crate::providers::Provider for PasswordstateProvider {
                 provider: "Passwordstate".to_string(),
                 details: format!("Failed to connect to '{}': {}", self.base_url, e),
                 hint: "Check network connectivity to the Passwordstate server".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             })?;
 
         if !response.status().is_success() {
@@ -334,14 +328,14 @@ impl crate::providers::Provider for PasswordstateProvider {
                     details: format!("Connection test failed: HTTP {}", status),
                     hint: "Check your API key is valid and has access to this password list"
                         .to_string(),
-                    url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                    url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
                 });
             }
             return Err(FnoxError::ProviderApiError {
                 provider: "Passwordstate".to_string(),
                 details: format!("Connection test failed: HTTP {}", status),
                 hint: "Check your Passwordstate server configuration".to_string(),
-                url: "https://fnox.jdx.dev/providers/overview".to_string(),
+                url: "https://fnox.jdx.dev/providers/passwordstate".to_string(),
             });
         }
 
@@ -0,0 +1,75 @@
+//! Key prefixes for remote storage providers.
+//!
+//! Providers with a `prefix` option (aws-sm, aws-ps, azure-sm, gcp-sm, keychain, etcd)
+//! store the un-prefixed key in the secret's `value` and apply the prefix only when
+//! talking to the remote API. Every command (`get`, `set`, `list --values`, `exec`,
+//! `edit`, `rename --move-remote`) goes through the provider, so routing all prefix
+//! handling through [`KeyPrefix`] keeps them in agreement.
+
+/// An optional prefix applied to secret references at the provider's API boundary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyPrefix(Option<String>);
+
+impl KeyPrefix {
+    /// Create a prefix; an empty string means no prefix.
+    pub fn new(prefix: Option<String>) -> Self {
+        Self(prefix.filter(|p| !p.is_empty()))
+    }
+
+    /// The remote name for a reference from config. The prefix is always
+    /// added, even to a reference that already starts with it.
+    pub fn apply(&self, key: &str) -> String {
+        match &self.0 {
+            Some(prefix) => format!("{}{}", prefix, key),
+            None => key.to_string(),
+        }
+    }
+
+    /// The reference for a remote name listed by the provider.
+    ///
+    /// This is the inverse of [`KeyPrefix::apply`]: `strip(&apply(k)) == k`.
+    /// Listings only return names under the prefix, so a name without it is
+    /// returned unchanged.
+    pub fn strip<'a>(&self, name: &'a str) -> &'a str {
+        match &self.0 {
+            Some(prefix) => name.strip_prefix(prefix.as_str()).unwrap_or(name),
+            None => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_always_adds_prefix() {
+        let prefix = KeyPrefix::new(Some("myapp/".to_string()));
+        assert_eq!(prefix.apply("db-password"), "myapp/db-password");
+        assert_eq!(prefix.apply("myapp/db-password"), "myapp/myapp/db-password");
+    }
+
+    #[test]
+    fn strip_returns_unprefixed_key() {
+        let prefix = KeyPrefix::new(Some("myapp/".to_string()));
+        assert_eq!(prefix.strip("myapp/db-password"), "db-password");
+        assert_eq!(prefix.strip("myapp/myapp/db-password"), "myapp/db-password");
+        assert_eq!(prefix.strip("db-password"), "db-password");
+    }
+
+    #[test]
+    fn empty_prefix_is_no_prefix() {
+        let prefix = KeyPrefix::new(Some(String::new()));
+        assert_eq!(prefix, KeyPrefix::default());
+        assert_eq!(prefix.apply("key"), "key");
+        assert_eq!(prefix.strip("key"), "key");
+    }
+
+    #[test]
+    fn listed_name_round_trips_to_the_same_remote_name() {
+        let prefix = KeyPrefix::new(Some("fnox-".to_string()));
+        for key in ["API_KEY", "fnox-API_KEY"] {
+            assert_eq!(prefix.strip(&prefix.apply(key)), key);
+        }
+    }
+}
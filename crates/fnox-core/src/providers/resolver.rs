@@ -10,6 +10,7 @@ use crate::config::Config;
 use crate::env;
 use crate::error::{FnoxError, Result};
 use crate::suggest::{find_similar, format_suggestions};
+use indexmap::IndexMap;
 use std::collections::HashSet;
 
 use super::secret_ref::{OptionProviderSecretRef, OptionStringOrSecretRef, StringOrSecretRef};
@@ -22,6 +23,9 @@ pub struct ResolutionContext {
     provider_stack: HashSet<String>,
     /// Stack path for error messages
     resolution_path: Vec<String>,
+    /// For each provider on the path, the field and secret it's resolving,
+    /// which lead to the next provider
+    resolving_secret: Vec<Option<(String, String)>>,
 }
 
 impl ResolutionContext {
@@ -30,6 +34,7 @@ impl ResolutionContext {
         Self {
             provider_stack: HashSet::new(),
             resolution_path: Vec::new(),
+            resolving_secret: Vec::new(),
         }
     }
 
@@ -42,18 +47,43 @@ impl ResolutionContext {
     pub fn push(&mut self, provider_name: &str) {
         self.provider_stack.insert(provider_name.to_string());
         self.resolution_path.push(provider_name.to_string());
+        self.resolving_secret.push(None);
     }
 
     /// Pop a provider from the resolution stack
     pub fn pop(&mut self) {
         if let Some(provider_name) = self.resolution_path.pop() {
             self.provider_stack.remove(&provider_name);
+            self.resolving_secret.pop();
         }
     }
 
-    /// Get the current resolution path as a string for error messages
+    /// Record that the provider being resolved is reading `field` from
+    /// `secret`, so a cycle through it names both
+    fn enter_secret(&mut self, field: &str, secret: &str) {
+        if let Some(current) = self.resolving_secret.last_mut() {
+            *current = Some((field.to_string(), secret.to_string()));
+        }
+    }
+
+    fn leave_secret(&mut self) {
+        if let Some(current) = self.resolving_secret.last_mut() {
+            *current = None;
+        }
+    }
+
+    /// Get the current resolution path as a string for error messages, e.g.
+    /// `vault.token -> secret 'VAULT_TOKEN' -> keychain`
     fn path_string(&self) -> String {
-        self.resolution_path.join(" -> ")
+        self.resolution_path
+            .iter()
+            .zip(&self.resolving_secret)
+            .map(|(provider, secret)| match secret {
+                Some((field, secret)) => format!("{provider}.{field} -> secret '{secret}'"),
+                None => provider.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ")
     }
 }
 
@@ -130,7 +160,7 @@ pub fn resolve_required<'a>(
     config: &'a Config,
     profile: &'a str,
     provider_name: &'a str,
-    _field_name: &'a str,
+    field_name: &'a str,
     value: &'a StringOrSecretRef,
     ctx: &'a mut ResolutionContext,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
@@ -138,7 +168,7 @@ pub fn resolve_required<'a>(
         match value {
             StringOrSecretRef::Literal(s) => Ok(s.clone()),
             StringOrSecretRef::SecretRef { secret } => {
-                resolve_secret_ref(config, profile, provider_name, secret, ctx).await
+                resolve_secret_ref(config, profile, provider_name, field_name, secret, ctx).await
             }
         }
     })
@@ -149,6 +179,7 @@ pub fn resolve_option<'a>(
     config: &'a Config,
     profile: &'a str,
     provider_name: &'a str,
+    field_name: &'a str,
     value: &'a OptionStringOrSecretRef,
     ctx: &'a mut ResolutionContext,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<String>>> + Send + 'a>> {
@@ -158,13 +189,31 @@ pub fn resolve_option<'a>(
             Some(StringOrSecretRef::Literal(s)) => Ok(Some(s.clone())),
             Some(StringOrSecretRef::SecretRef { secret }) => {
                 let resolved =
-                    resolve_secret_ref(config, profile, provider_name, secret, ctx).await?;
+                    resolve_secret_ref(config, profile, provider_name, field_name, secret, ctx)
+                        .await?;
                 Ok(Some(resolved))
             }
         }
     })
 }
 
+/// Resolve each value of a map field (e.g. HTTP headers) to its actual string
+/// value. Recursion is boxed by [`resolve_required`], so this needn't be.
+pub async fn resolve_map(
+    config: &Config,
+    profile: &str,
+    provider_name: &str,
+    values: &IndexMap<String, StringOrSecretRef>,
+    ctx: &mut ResolutionContext,
+) -> Result<IndexMap<String, String>> {
+    let mut resolved = IndexMap::with_capacity(values.len());
+    for (key, value) in values {
+        let value = resolve_required(config, profile, provider_name, key, value, ctx).await?;
+        resolved.insert(key.clone(), value);
+    }
+    Ok(resolved)
+}
+
 /// Resolve an optional provider-backed secret reference to its actual value.
 pub fn resolve_provider_ref<'a>(
     config: &'a Config,
@@ -237,22 +286,32 @@ pub fn resolve_provider_ref_with_identity_cycle_guard<'a>(
 /// Resolve a secret reference by name.
 ///
 /// This looks up the secret in config first, then falls back to environment variable.
-/// If the secret is defined in config and uses another provider, that provider's
-/// config will also be resolved recursively.
+/// If the secret is defined in config and uses another provider (its own or the
+/// profile's default provider), that provider's config will also be resolved
+/// recursively.
 fn resolve_secret_ref<'a>(
     config: &'a Config,
     profile: &'a str,
     provider_name: &'a str,
+    field_name: &'a str,
     secret_name: &'a str,
     ctx: &'a mut ResolutionContext,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
     Box::pin(async move {
+        if let Some(value) = crate::secret_resolver::override_value(secret_name) {
+            return Ok(value);
+        }
+
         // First, try to find the secret in config
         let secrets = config.get_secrets(profile).unwrap_or_default();
 
         if let Some(secret_config) = secrets.get(secret_name) {
+            let secret_provider_name = match secret_config.provider() {
+                Some(name) => Some(name.to_string()),
+                None => config.get_default_provider(profile)?,
+            };
             // Secret found in config - resolve it
-            if let Some(secret_provider_name) = secret_config.provider()
+            if let Some(secret_provider_name) = secret_provider_name.as_deref()
                 && let Some(provider_value) = secret_config.value()
             {
                 // This secret uses a provider - need to resolve that provider first
@@ -268,6 +327,7 @@ fn resolve_secret_ref<'a>(
                     }
 
                     // Recursively resolve the provider's config
+                    ctx.enter_secret(field_name, secret_name);
                     let resolved_provider = resolve_provider_config_with_context(
                         config,
                         profile,
@@ -275,7 +335,9 @@ fn resolve_secret_ref<'a>(
                         secret_provider_config,
                         ctx,
                     )
-                    .await?;
+                    .await;
+                    ctx.leave_secret();
+                    let resolved_provider = resolved_provider?;
 
                     // Create the provider and get the secret
                     let provider = super::get_provider_from_resolved_with_context(
@@ -355,4 +417,135 @@ mod tests {
 
         assert_eq!(ctx.path_string(), "a -> b -> c");
     }
+
+    #[test]
+    fn test_resolution_path_names_secrets() {
+        let mut ctx = ResolutionContext::new();
+
+        ctx.push("vault");
+        ctx.enter_secret("token", "VAULT_TOKEN");
+        ctx.push("keychain");
+        assert_eq!(
+            ctx.path_string(),
+            "vault.token -> secret 'VAULT_TOKEN' -> keychain"
+        );
+
+        ctx.pop();
+        ctx.leave_secret();
+        assert_eq!(ctx.path_string(), "vault");
+    }
+
+    fn parse_config(toml: &str) -> Config {
+        toml_edit::de::from_str(toml).unwrap()
+    }
+
+    async fn resolve(config: &Config, provider_name: &str) -> Result<ResolvedProviderConfig> {
+        let providers = config.get_providers("default");
+        resolve_provider_config(config, "default", provider_name, &providers[provider_name]).await
+    }
+
+    fn vault_token(resolved: ResolvedProviderConfig) -> Option<String> {
+        match resolved {
+            ResolvedProviderConfig::HashiCorpVault { token, .. } => token,
+            other => panic!("expected a Vault config, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_credential_from_another_provider() {
+        // The token is stored with another provider; `plain` stands in for
+        // the OS keychain, which isn't available in tests
+        let config = parse_config(
+            r#"
+            [providers.vault]
+            type = "vault"
+            address = "http://127.0.0.1:8200"
+            token = { secret = "VAULT_TOKEN" }
+
+            [providers.store]
+            type = "plain"
+
+            [secrets.VAULT_TOKEN]
+            provider = "store"
+            value = "hvs.from-store"
+            "#,
+        );
+        let resolved = resolve(&config, "vault").await.unwrap();
+        assert_eq!(vault_token(resolved).as_deref(), Some("hvs.from-store"));
+
+        // A secret without a provider of its own uses the default provider
+        let config = config_with_default_provider();
+        let resolved = resolve(&config, "vault").await.unwrap();
+        assert_eq!(vault_token(resolved).as_deref(), Some("hvs.default"));
+    }
+
+    fn config_with_default_provider() -> Config {
+        parse_config(
+            r#"
+            default_provider = "store"
+
+            [providers.vault]
+            type = "vault"
+            token = { secret = "VAULT_TOKEN" }
+
+            [providers.store]
+            type = "plain"
+
+            [secrets.VAULT_TOKEN]
+            value = "hvs.default"
+            "#,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_provider_credential_cycle() {
+        // Each Vault's token is stored in the other Vault
+        let config = parse_config(
+            r#"
+            [providers.vault_a]
+            type = "vault"
+            token = { secret = "A_TOKEN" }
+
+            [providers.vault_b]
+            type = "vault"
+            token = { secret = "B_TOKEN" }
+
+            [secrets.A_TOKEN]
+            provider = "vault_b"
+            value = "tokens/a"
+
+            [secrets.B_TOKEN]
+            provider = "vault_a"
+            value = "tokens/b"
+            "#,
+        );
+        let err = resolve(&config, "vault_a").await.unwrap_err();
+        let FnoxError::ProviderConfigCycle { provider, cycle } = err else {
+            panic!("expected a cycle error, got {err:?}");
+        };
+        assert_eq!(provider, "vault_a");
+        assert_eq!(
+            cycle,
+            "vault_a.token -> secret 'A_TOKEN' -> vault_b.token -> secret 'B_TOKEN' -> vault_a"
+        );
+
+        // A provider whose token is stored in itself
+        let config = parse_config(
+            r#"
+            [providers.vault]
+            type = "vault"
+            token = { secret = "VAULT_TOKEN" }
+
+            [secrets.VAULT_TOKEN]
+            provider = "vault"
+            value = "vault-token"
+            "#,
+        );
+        let err = resolve(&config, "vault").await.unwrap_err();
+        assert!(
+            matches!(&err, FnoxError::ProviderConfigCycle { cycle, .. }
+                if cycle == "vault.token -> secret 'VAULT_TOKEN' -> vault"),
+            "{err:?}"
+        );
+    }
 }
@@ -0,0 +1,280 @@
+use crate::env;
+use crate::error::{FnoxError, Result};
+use crate::providers::{ProviderCapability, batch_concurrency, get_secrets_concurrent};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const PROVIDER_NAME: &str = "systemd-creds";
+const URL: &str = "https://fnox.jdx.dev/providers/systemd-creds";
+
+pub fn env_dependencies() -> &'static [&'static str] {
+    &["CREDENTIALS_DIRECTORY"]
+}
+
+/// Provider that encrypts secrets with `systemd-creds`, using the host key
+/// and/or the TPM2 chip.
+///
+/// Inside a systemd service that loads the credentials itself
+/// (`LoadCredentialEncrypted=`/`SetCredentialEncrypted=`), the decrypted values
+/// are read from `$CREDENTIALS_DIRECTORY` instead.
+pub struct SystemdCredsProvider {
+    with_key: Option<String>,
+}
+
+impl SystemdCredsProvider {
+    pub fn new(with_key: Option<String>) -> Result<Self> {
+        Ok(Self { with_key })
+    }
+
+    /// Encrypt `value`, embedding `name` as the credential name so systemd
+    /// can check it when the unit loads the credential under that name
+    async fn encrypt_named(&self, name: &str, value: &str) -> Result<String> {
+        ensure_supported()?;
+        let mut args = vec![
+            "encrypt".to_string(),
+            format!("--name={name}"),
+            "-".to_string(),
+            "-".to_string(),
+        ];
+        if let Some(with_key) = self.with_key.as_deref().filter(|k| !k.is_empty()) {
+            args.insert(1, format!("--with-key={with_key}"));
+        }
+        let output = run_systemd_creds(&args, value.as_bytes()).await?;
+        let encrypted = String::from_utf8(output).map_err(|e| invalid_output(e.to_string()))?;
+        // The credential is base64; drop the line breaks so it fits on one line
+        // in fnox.toml
+        Ok(encrypted.split_whitespace().collect())
+    }
+}
+
+#[async_trait]
+impl crate::providers::Provider for SystemdCredsProvider {
+    fn capabilities(&self) -> Vec<ProviderCapability> {
+        vec![ProviderCapability::Encryption]
+    }
+
+    async fn get_secret(&self, value: &str) -> Result<String> {
+        ensure_supported()?;
+        // An empty --name skips the check of the embedded credential name, which
+        // is the secret's name when it was set and may have changed since
+        let output = run_systemd_creds(&["decrypt", "--name=", "-", "-"], value.as_bytes()).await?;
+        String::from_utf8(output).map_err(|e| invalid_output(e.to_string()))
+    }
+
+    /// Read the credential systemd already decrypted for the service under
+    /// the secret's name, if there is one
+    async fn get_named_secret(&self, key: &str, value: &str) -> Result<String> {
+        if let Some(result) = env::var_os("CREDENTIALS_DIRECTORY")
+            .and_then(|dir| read_loaded_credential(Path::new(&dir), key))
+        {
+            tracing::debug!("Read '{}' from $CREDENTIALS_DIRECTORY", key);
+            return result;
+        }
+        self.get_secret(value).await
+    }
+
+    /// Read credentials systemd already decrypted for the service by secret
+    /// name, and decrypt the rest with `systemd-creds`
+    async fn get_secrets_batch(
+        &self,
+        secrets: &[(String, String)],
+    ) -> HashMap<String, Result<String>> {
+        let mut results = HashMap::new();
+        let mut remaining = Vec::new();
+        let credentials_dir = env::var_os("CREDENTIALS_DIRECTORY");
+        for (key, value) in secrets {
+            match credentials_dir
+                .as_deref()
+                .and_then(|dir| read_loaded_credential(Path::new(dir), key))
+            {
+                Some(result) => {
+                    tracing::debug!("Read '{}' from $CREDENTIALS_DIRECTORY", key);
+                    results.insert(key.clone(), result);
+                }
+                None => remaining.push((key.clone(), value.clone())),
+            }
+        }
+        if !remaining.is_empty() {
+            results.extend(get_secrets_concurrent(self, &remaining, batch_concurrency(self)).await);
+        }
+        results
+    }
+
+    async fn encrypt(&self, value: &str) -> Result<String> {
+        self.encrypt_named("", value).await
+    }
+
+    async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
+        self.encrypt_named(key, value).await
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        let probe = "fnox-test";
+        let encrypted = self.encrypt(probe).await?;
+        if self.get_secret(&encrypted).await? != probe {
+            return Err(invalid_output(
+                "decrypting a test value returned something else".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn ensure_supported() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ensure_supported() -> Result<()> {
+    Err(FnoxError::Provider(
+        "The systemd-creds provider is only available on Linux; use the age provider on other platforms"
+            .to_string(),
+    ))
+}
+
+/// Read a credential systemd placed in the service's credentials directory.
+/// Returns `None` when there's no credential by that name.
+fn read_loaded_credential(dir: &Path, name: &str) -> Option<Result<String>> {
+    // Credential names are file names; anything else can't be in the directory
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return None;
+    }
+    let path = dir.join(name);
+    if !path.is_file() {
+        return None;
+    }
+    Some(
+        std::fs::read_to_string(&path).map_err(|e| FnoxError::ProviderApiError {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to read credential '{}': {}", path.display(), e),
+            hint: "Check that the service can read $CREDENTIALS_DIRECTORY".to_string(),
+            url: URL.to_string(),
+        }),
+    )
+}
+
+/// Run `systemd-creds` with `input` on stdin and return its stdout
+async fn run_systemd_creds(args: &[impl AsRef<str>], input: &[u8]) -> Result<Vec<u8>> {
+    let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+    tracing::debug!("Executing systemd-creds {}", args[0]);
+
+    let mut child = Command::new("systemd-creds")
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                FnoxError::ProviderCliNotFound {
+                    provider: PROVIDER_NAME.to_string(),
+                    cli: "systemd-creds".to_string(),
+                    install_hint: "Install systemd 250 or later".to_string(),
+                    url: URL.to_string(),
+                }
+            } else {
+                FnoxError::ProviderCliFailed {
+                    provider: PROVIDER_NAME.to_string(),
+                    details: e.to_string(),
+                    hint: "Check that systemd-creds is installed and accessible".to_string(),
+                    url: URL.to_string(),
+                }
+            }
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .await
+            .map_err(|e| FnoxError::ProviderCliFailed {
+                provider: PROVIDER_NAME.to_string(),
+                details: format!("Failed to write to stdin: {}", e),
+                hint: "This is an internal error".to_string(),
+                url: URL.to_string(),
+            })?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| FnoxError::ProviderCliFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details: format!("Failed to wait for command: {}", e),
+            hint: "This is an internal error".to_string(),
+            url: URL.to_string(),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        if stderr.contains("Permission denied")
+            || stderr.contains("credential.secret")
+            || stderr.contains("TPM")
+        {
+            return Err(FnoxError::ProviderAuthFailed {
+                provider: PROVIDER_NAME.to_string(),
+                details: stderr.to_string(),
+                hint: "The host key is only readable by root and the TPM2 device may need extra permissions; try with_key = \"tpm2\" or run as root".to_string(),
+                url: URL.to_string(),
+            });
+        }
+        return Err(FnoxError::ProviderCliFailed {
+            provider: PROVIDER_NAME.to_string(),
+            details: stderr.to_string(),
+            hint: "Check that the value was encrypted on this machine".to_string(),
+            url: URL.to_string(),
+        });
+    }
+
+    Ok(output.stdout)
+}
+
+fn invalid_output(details: String) -> FnoxError {
+    FnoxError::ProviderInvalidResponse {
+        provider: PROVIDER_NAME.to_string(),
+        details,
+        hint: "fnox only stores UTF-8 text with systemd-creds".to_string(),
+        url: URL.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_loaded_credentials_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("DATABASE_URL"), "postgres://db").unwrap();
+
+        let value = read_loaded_credential(dir.path(), "DATABASE_URL");
+        assert_eq!(value.unwrap().unwrap(), "postgres://db");
+        assert!(read_loaded_credential(dir.path(), "MISSING").is_none());
+    }
+
+    #[test]
+    fn ignores_names_that_are_not_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub").join("KEY"), "x").unwrap();
+
+        for name in ["", ".", "..", "sub", "sub/KEY", "../KEY"] {
+            assert!(read_loaded_credential(dir.path(), name).is_none(), "{name}");
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[tokio::test]
+    async fn errors_outside_linux() {
+        use crate::providers::Provider;
+
+        let provider = SystemdCredsProvider::new(None).unwrap();
+        let err = provider.get_secret("abc").await.unwrap_err();
+        assert!(err.to_string().contains("only available on Linux"));
+    }
+}
@@ -1,17 +1,32 @@
 use crate::env;
 use crate::error::{FnoxError, Result};
+use crate::providers::reference;
+use crate::recording::{CommandRunner, Stdout};
 use async_trait::async_trait;
-use serde_json::json;
+use indexmap::IndexMap;
+use reqwest::{Method, StatusCode};
+use serde_json::{Map, Value, json};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tokio::process::Command;
+use tokio::sync::Mutex;
 
 const URL: &str = "https://fnox.jdx.dev/providers/vault";
 
+/// Where Kubernetes mounts the pod's service account token
+const KUBERNETES_JWT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
 pub struct HashiCorpVaultProvider {
     address: Option<String>,
     path: Option<String>,
     token: Option<String>,
     namespace: Option<String>,
     credential_command: Option<String>,
+    auth: Option<VaultAuth>,
+    /// KV engine version of the mount; detected when unset
+    kv_version: Option<u32>,
+    /// Token from the last `auth` login, shared by every request
+    session: Mutex<Option<Session>>,
 }
 
 impl HashiCorpVaultProvider {
@@ -21,13 +36,26 @@ impl HashiCorpVaultProvider {
         token: Option<String>,
         namespace: Option<String>,
         credential_command: Option<String>,
+        auth: IndexMap<String, String>,
+        kv_version: Option<u32>,
     ) -> Result<Self> {
+        if let Some(version) = kv_version
+            && !matches!(version, 1 | 2)
+        {
+            return Err(FnoxError::Config(format!(
+                "HashiCorp Vault kv_version must be 1 or 2, got {}",
+                version
+            )));
+        }
         Ok(Self {
             address,
             path,
             token,
             namespace,
             credential_command,
+            auth: VaultAuth::from_config(&auth)?,
+            kv_version,
+            session: Mutex::new(None),
         })
     }
 
@@ -43,7 +71,13 @@ impl HashiCorpVaultProvider {
     }
 
     async fn get_token(&self, address: &str) -> Result<Option<String>> {
-        if let Some(token) = self.token.clone().or_else(vault_token) {
+        if let Some(token) = self.token.clone() {
+            return Ok(Some(token));
+        }
+        if let Some(auth) = &self.auth {
+            return self.auth_token(auth, address).await.map(Some);
+        }
+        if let Some(token) = vault_token() {
             return Ok(Some(token));
         }
 
@@ -73,6 +107,103 @@ impl HashiCorpVaultProvider {
         .map(Some)
     }
 
+    /// A token from the `auth` method: logs in on first use, and renews the
+    /// token once half its TTL has passed, logging in again if that fails
+    async fn auth_token(&self, auth: &VaultAuth, address: &str) -> Result<String> {
+        let mut session = self.session.lock().await;
+        if let Some(current) = session.as_ref() {
+            if current.renew_at.is_none_or(|at| Instant::now() < at) {
+                return Ok(current.token.clone());
+            }
+            if current.renewable {
+                match self.renew(address, &current.token).await {
+                    Ok(renewed) => {
+                        let token = renewed.token.clone();
+                        *session = Some(renewed);
+                        return Ok(token);
+                    }
+                    Err(e) => {
+                        tracing::debug!("Renewing Vault token failed, logging in again: {}", e)
+                    }
+                }
+            }
+        }
+
+        let login = self.login(auth, address).await?;
+        let token = login.token.clone();
+        *session = Some(login);
+        Ok(token)
+    }
+
+    async fn login(&self, auth: &VaultAuth, address: &str) -> Result<Session> {
+        tracing::debug!("Logging in to Vault with the {} auth method", auth.method());
+        let (path, body) = auth.login_request()?;
+        let (status, response) = self
+            .client(address, None)
+            .send(Method::POST, &path, Some(&body))
+            .await?;
+        if !status.is_success() {
+            let mut details = errors(&response);
+            if details.is_empty() {
+                details = format!("HTTP {}", status);
+            }
+            return Err(FnoxError::ProviderAuthFailed {
+                provider: "HashiCorp Vault".to_string(),
+                details: format!("{} login failed: {}", auth.method(), details),
+                hint: "Check the role and credentials in the provider's auth config".to_string(),
+                url: URL.to_string(),
+            });
+        }
+        Session::from_response(&response)
+    }
+
+    async fn renew(&self, address: &str, token: &str) -> Result<Session> {
+        tracing::debug!("Renewing Vault token");
+        let (status, response) = self
+            .client(address, Some(token.to_string()))
+            .send(Method::POST, "auth/token/renew-self", Some(&json!({})))
+            .await?;
+        if !status.is_success() {
+            return Err(api_error(status, &response, "auth/token/renew-self"));
+        }
+        Session::from_response(&response)
+    }
+
+    fn require_address(&self) -> Result<String> {
+        self.get_address().ok_or_else(|| {
+            FnoxError::Config(
+                "HashiCorp Vault provider address is not configured. Please set it in your provider configuration or via the VAULT_ADDR environment variable.".to_string(),
+            )
+        })
+    }
+
+    async fn require_token(&self, address: &str) -> Result<String> {
+        self.get_token(address)
+            .await?
+            .ok_or_else(|| FnoxError::ProviderAuthFailed {
+                provider: "HashiCorp Vault".to_string(),
+                details: "VAULT_TOKEN not set".to_string(),
+                hint: "Set VAULT_TOKEN in provider config or environment, or configure credential_command".to_string(),
+                url: URL.to_string(),
+            })
+    }
+
+    /// Client for Vault's HTTP API, authenticated like the CLI
+    async fn api(&self) -> Result<VaultApi> {
+        let address = self.require_address()?;
+        let token = self.require_token(&address).await?;
+        Ok(self.client(&address, Some(token)))
+    }
+
+    fn client(&self, address: &str, token: Option<String>) -> VaultApi {
+        VaultApi {
+            address: address.trim_end_matches('/').to_string(),
+            token,
+            namespace: self.namespace.clone().or_else(vault_namespace),
+            client: crate::http::http_client(),
+        }
+    }
+
     /// Execute vault CLI command with proper authentication
     async fn execute_vault_command(&self, args: &[&str]) -> Result<String> {
         tracing::debug!("Executing vault command with args: {:?}", args);
@@ -80,11 +211,7 @@ impl HashiCorpVaultProvider {
         let mut cmd = Command::new("vault");
 
         // Set VAULT_ADDR from provider config or environment
-        let address = self.get_address().ok_or_else(|| {
-            FnoxError::Config(
-                "HashiCorp Vault provider address is not configured. Please set it in your provider configuration or via the VAULT_ADDR environment variable.".to_string(),
-            )
-        })?;
+        let address = self.require_address()?;
 
         tracing::debug!("Setting VAULT_ADDR to '{}'", address);
         cmd.env("VAULT_ADDR", &address);
@@ -96,15 +223,7 @@ impl HashiCorpVaultProvider {
         }
 
         // Set VAULT_TOKEN from provider config or environment
-        let token = self
-            .get_token(&address)
-            .await?
-            .ok_or_else(|| FnoxError::ProviderAuthFailed {
-                provider: "HashiCorp Vault".to_string(),
-                details: "VAULT_TOKEN not set".to_string(),
-                hint: "Set VAULT_TOKEN in provider config or environment, or configure credential_command".to_string(),
-                url: URL.to_string(),
-            })?;
+        let token = self.require_token(&address).await?;
 
         tracing::debug!(
             "Setting VAULT_TOKEN environment variable (token length: {})",
@@ -114,23 +233,33 @@ impl HashiCorpVaultProvider {
 
         cmd.args(args);
 
-        let output = cmd.output().await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                FnoxError::ProviderCliNotFound {
-                    provider: "HashiCorp Vault".to_string(),
-                    cli: "vault".to_string(),
-                    install_hint: "brew install vault".to_string(),
-                    url: URL.to_string(),
-                }
-            } else {
-                FnoxError::ProviderCliFailed {
-                    provider: "HashiCorp Vault".to_string(),
-                    details: e.to_string(),
-                    hint: "Check that the Vault CLI is installed and accessible".to_string(),
-                    url: URL.to_string(),
+        // `kv get -field=...` prints the bare secret value
+        let stdout = if args.starts_with(&["kv", "get"]) {
+            Stdout::Value
+        } else {
+            Stdout::Plain
+        };
+        let output = CommandRunner::new(cmd)
+            .stdout(stdout)
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    FnoxError::ProviderCliNotFound {
+                        provider: "HashiCorp Vault".to_string(),
+                        cli: "vault".to_string(),
+                        install_hint: "brew install vault".to_string(),
+                        url: URL.to_string(),
+                    }
+                } else {
+                    FnoxError::ProviderCliFailed {
+                        provider: "HashiCorp Vault".to_string(),
+                        details: e.to_string(),
+                        hint: "Check that the Vault CLI is installed and accessible".to_string(),
+                        url: URL.to_string(),
+                    }
                 }
-            }
-        })?;
+            })?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -175,27 +304,30 @@ impl crate::providers::Provider for HashiCorpVaultProvider {
         vec![crate::providers::ProviderCapability::RemoteStorage]
     }
 
+    /// The address and token, including one from an `auth` login
+    async fn session_env(&self) -> Result<Vec<(String, String)>> {
+        let address = self.require_address()?;
+        let token = self.require_token(&address).await?;
+        let mut vars = vec![
+            ("VAULT_ADDR".to_string(), address),
+            ("VAULT_TOKEN".to_string(), token),
+        ];
+        if let Some(namespace) = self.namespace.clone().or_else(vault_namespace) {
+            vars.push(("VAULT_NAMESPACE".to_string(), namespace));
+        }
+        Ok(vars)
+    }
+
     async fn get_secret(&self, value: &str) -> Result<String> {
         tracing::debug!("Getting secret '{}' from HashiCorp Vault", value);
 
         // Parse value as "secret-name/field" or just "secret-name"
         // Default field is "value" if not specified (Vault KV v2 convention)
-        let parts: Vec<&str> = value.split('/').collect();
+        let reference = reference::VAULT.parse(value)?;
+        let secret_name = reference.path_str();
+        let field_name = reference.field.as_deref().unwrap_or("value");
 
-        let (secret_name, field_name) = match parts.len() {
-            1 => (parts[0], "value"),
-            2 => (parts[0], parts[1]),
-            _ => {
-                return Err(FnoxError::ProviderInvalidResponse {
-                    provider: "HashiCorp Vault".to_string(),
-                    details: format!("Invalid secret reference format: '{}'", value),
-                    hint: "Expected 'secret' or 'secret/field'".to_string(),
-                    url: URL.to_string(),
-                });
-            }
-        };
-
-        let secret_path = self.get_secret_path(secret_name);
+        let secret_path = self.get_secret_path(&secret_name);
 
         tracing::debug!(
             "Reading Vault secret '{}' field '{}'",
@@ -213,7 +345,7 @@ impl crate::providers::Provider for HashiCorpVaultProvider {
 
     async fn test_connection(&self) -> Result<()> {
         let address = self.get_address();
-        if let Some(addr) = address {
+        if let Some(addr) = &address {
             tracing::debug!("Testing connection to Vault at {}", addr);
         } else {
             tracing::debug!(
@@ -221,6 +353,12 @@ impl crate::providers::Provider for HashiCorpVaultProvider {
             );
         }
 
+        // Log in first, so a misconfigured auth method is reported as such
+        if self.auth.is_some() {
+            let address = self.require_address()?;
+            self.require_token(&address).await?;
+        }
+
         // Try to get Vault status
         let args = vec!["status"];
         self.execute_vault_command(&args).await?;
@@ -228,22 +366,475 @@ impl crate::providers::Provider for HashiCorpVaultProvider {
         Ok(())
     }
 
+    /// Write `value` to the field `key` names ("secret" or "secret/field",
+    /// default field "value"), keeping the secret's other fields. On KV v2
+    /// the write is check-and-set against the version that was read.
     async fn put_secret(&self, key: &str, value: &str) -> Result<String> {
-        let secret_path = self.get_secret_path(key);
-
-        tracing::debug!("Writing secret '{}' to HashiCorp Vault", secret_path);
+        let reference = reference::VAULT.parse(key)?;
+        let field = reference.field.as_deref().unwrap_or("value");
+        let secret_path = self.get_secret_path(&reference.path_str());
 
-        // Use vault kv put command: vault kv put <path> value=<value>
-        let value_arg = format!("value={}", value);
-        let args = vec!["kv", "put", &secret_path, &value_arg];
+        tracing::debug!(
+            "Writing field '{}' of secret '{}' to HashiCorp Vault",
+            field,
+            secret_path
+        );
 
-        self.execute_vault_command(&args).await?;
+        let api = self.api().await?;
+        let mount = api.kv_mount(&secret_path, self.kv_version).await?;
+        let mut secret = api.read_kv(&mount, &secret_path).await?;
+        secret
+            .data
+            .insert(field.to_string(), Value::String(value.to_string()));
+        api.write_kv(&mount, &secret_path, &secret).await?;
 
         tracing::debug!("Successfully wrote secret '{}' to Vault", secret_path);
 
-        // Return the key name to store in config
+        // Return the key name to store in config, relative to `path`
         Ok(key.to_string())
     }
+
+    /// One reference per field of each secret directly under `path`: the
+    /// secret's name for its "value" field and `name/field` for the others.
+    /// Subdirectories aren't listed. KV can't list a secret's fields without
+    /// reading it, so this reads every listed secret, values included, and
+    /// the reads show in Vault's audit log.
+    async fn list_secrets(&self, prefix: Option<&str>) -> Result<Vec<String>> {
+        let prefix = prefix.unwrap_or_default();
+        let dir = self
+            .path
+            .as_deref()
+            .unwrap_or("secret")
+            .trim_end_matches('/');
+        tracing::debug!("Listing secrets in HashiCorp Vault under '{}'", dir);
+
+        let api = self.api().await?;
+        let mount = api.kv_mount(dir, self.kv_version).await?;
+        let mut references = Vec::new();
+        for name in api.list_kv(&mount, dir).await? {
+            // Only secrets whose references can start with the prefix
+            if !name.starts_with(prefix) && !prefix.starts_with(name.as_str()) {
+                continue;
+            }
+            let secret = api.read_kv(&mount, &format!("{}/{}", dir, name)).await?;
+            references.extend(
+                secret
+                    .data
+                    .keys()
+                    .map(|field| match field.as_str() {
+                        "value" => name.clone(),
+                        field => format!("{}/{}", name, field),
+                    })
+                    .filter(|reference| reference.starts_with(prefix)),
+            );
+        }
+        references.sort();
+        Ok(references)
+    }
+
+    async fn delete_secret(&self, value: &str) -> Result<()> {
+        // "secret/field" names one field of a secret that may hold others, and
+        // deleting would remove them all
+        let reference = reference::VAULT.parse(value)?;
+        if reference.field.is_some() {
+            return Err(FnoxError::Provider(format!(
+                "Refusing to delete Vault secret '{}': it refers to a single field",
+                value
+            )));
+        }
+        let secret_path = self.get_secret_path(&reference.path_str());
+
+        tracing::debug!("Deleting secret '{}' from HashiCorp Vault", secret_path);
+
+        // Soft delete: the latest version can still be restored with `vault kv undelete`
+        let args = vec!["kv", "delete", &secret_path];
+        self.execute_vault_command(&args).await?;
+        Ok(())
+    }
+}
+
+/// How the provider logs in when no token is configured, from its `auth`
+/// table
+enum VaultAuth {
+    AppRole {
+        mount: String,
+        role_id: String,
+        secret_id: Option<String>,
+    },
+    Kubernetes {
+        mount: String,
+        role: String,
+        jwt_path: PathBuf,
+    },
+}
+
+impl VaultAuth {
+    fn from_config(auth: &IndexMap<String, String>) -> Result<Option<Self>> {
+        if auth.is_empty() {
+            return Ok(None);
+        }
+        let field = |name: &str| auth.get(name).filter(|v| !v.is_empty()).cloned();
+        let required = |method: &str, name: &str| {
+            field(name).ok_or_else(|| {
+                FnoxError::Config(format!(
+                    "HashiCorp Vault auth method '{}' requires '{}'",
+                    method, name
+                ))
+            })
+        };
+
+        let method = field("method").ok_or_else(|| {
+            FnoxError::Config(
+                "HashiCorp Vault auth requires 'method' (\"approle\" or \"kubernetes\")"
+                    .to_string(),
+            )
+        })?;
+        let (parsed, known) = match method.as_str() {
+            "approle" => (
+                Self::AppRole {
+                    mount: field("mount").unwrap_or_else(|| "approle".to_string()),
+                    role_id: required("approle", "role_id")?,
+                    secret_id: field("secret_id"),
+                },
+                ["method", "mount", "role_id", "secret_id"],
+            ),
+            "kubernetes" => (
+                Self::Kubernetes {
+                    mount: field("mount").unwrap_or_else(|| "kubernetes".to_string()),
+                    role: required("kubernetes", "role")?,
+                    jwt_path: field("jwt_path")
+                        .unwrap_or_else(|| KUBERNETES_JWT_PATH.to_string())
+                        .into(),
+                },
+                ["method", "mount", "role", "jwt_path"],
+            ),
+            other => {
+                return Err(FnoxError::Config(format!(
+                    "Unknown HashiCorp Vault auth method '{}', expected \"approle\" or \"kubernetes\"",
+                    other
+                )));
+            }
+        };
+        if let Some(key) = auth.keys().find(|key| !known.contains(&key.as_str())) {
+            return Err(FnoxError::Config(format!(
+                "Unknown key '{}' for HashiCorp Vault auth method '{}'",
+                key, method
+            )));
+        }
+        Ok(Some(parsed))
+    }
+
+    fn method(&self) -> &'static str {
+        match self {
+            Self::AppRole { .. } => "approle",
+            Self::Kubernetes { .. } => "kubernetes",
+        }
+    }
+
+    /// API path and body of the login request
+    fn login_request(&self) -> Result<(String, Value)> {
+        match self {
+            Self::AppRole {
+                mount,
+                role_id,
+                secret_id,
+            } => {
+                let mut body = json!({ "role_id": role_id });
+                if let Some(secret_id) = secret_id {
+                    body["secret_id"] = json!(secret_id);
+                }
+                Ok((format!("auth/{}/login", mount.trim_matches('/')), body))
+            }
+            Self::Kubernetes {
+                mount,
+                role,
+                jwt_path,
+            } => {
+                let jwt = std::fs::read_to_string(jwt_path).map_err(|e| {
+                    FnoxError::Config(format!(
+                        "Failed to read Kubernetes service account token from {}: {}",
+                        jwt_path.display(),
+                        e
+                    ))
+                })?;
+                Ok((
+                    format!("auth/{}/login", mount.trim_matches('/')),
+                    json!({ "role": role, "jwt": jwt.trim() }),
+                ))
+            }
+        }
+    }
+}
+
+/// A client token from an auth method login
+struct Session {
+    token: String,
+    /// When half the token's TTL has passed; `None` if it doesn't expire
+    renew_at: Option<Instant>,
+    renewable: bool,
+}
+
+impl Session {
+    /// The token of a login or renewal response
+    fn from_response(body: &Value) -> Result<Self> {
+        let auth = &body["auth"];
+        let token =
+            auth["client_token"]
+                .as_str()
+                .ok_or_else(|| FnoxError::ProviderInvalidResponse {
+                    provider: "HashiCorp Vault".to_string(),
+                    details: "Login response has no client token".to_string(),
+                    hint: "Check that the auth method's mount is correct".to_string(),
+                    url: URL.to_string(),
+                })?;
+        let ttl = auth["lease_duration"].as_u64().unwrap_or(0);
+        Ok(Self {
+            token: token.to_string(),
+            renew_at: (ttl > 0).then(|| Instant::now() + Duration::from_secs(ttl) / 2),
+            renewable: auth["renewable"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// A KV secrets engine mount
+#[derive(Debug, PartialEq, Eq)]
+struct KvMount {
+    /// Mount path with a trailing slash, e.g. "secret/"
+    path: String,
+    /// KV v2 (versioned) rather than v1
+    versioned: bool,
+}
+
+impl KvMount {
+    /// API path of a secret's data
+    fn data_path(&self, secret_path: &str) -> String {
+        if self.versioned {
+            let relative = secret_path.strip_prefix(&self.path).unwrap_or(secret_path);
+            format!("{}data/{}", self.path, relative)
+        } else {
+            secret_path.to_string()
+        }
+    }
+
+    /// API path listing the secrets in the directory `dir`
+    fn list_path(&self, dir: &str) -> String {
+        let dir = format!("{}/", dir.trim_end_matches('/'));
+        if self.versioned {
+            let relative = dir.strip_prefix(&self.path).unwrap_or(&dir);
+            format!("{}metadata/{}", self.path, relative)
+        } else {
+            dir
+        }
+    }
+}
+
+/// A KV secret's fields, and the version to check-and-set against (0 when
+/// it doesn't exist yet)
+struct KvSecret {
+    data: Map<String, Value>,
+    version: u64,
+}
+
+/// Vault's HTTP API
+struct VaultApi {
+    address: String,
+    /// Client token; `None` for logins
+    token: Option<String>,
+    namespace: Option<String>,
+    client: reqwest::Client,
+}
+
+impl VaultApi {
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<(StatusCode, Value)> {
+        let url = format!("{}/v1/{}", self.address, path);
+        tracing::debug!("Requesting Vault API: {} {}", method, url);
+
+        let mut request = self.client.request(method, &url);
+        if let Some(token) = &self.token {
+            request = request.header("X-Vault-Token", token);
+        }
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| FnoxError::ProviderApiError {
+                provider: "HashiCorp Vault".to_string(),
+                details: format!("HTTP request failed: {}", e),
+                hint: "Check the Vault address and that the server is reachable".to_string(),
+                url: URL.to_string(),
+            })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        Ok((status, serde_json::from_str(&text).unwrap_or(Value::Null)))
+    }
+
+    /// The KV mount `secret_path` is in, checked against the configured
+    /// `kv_version`. Tokens that can't read mount info fall back to the first
+    /// path segment, and to `kv_version` or else KV v2, the server default.
+    async fn kv_mount(&self, secret_path: &str, kv_version: Option<u32>) -> Result<KvMount> {
+        let (status, body) = self
+            .send(
+                Method::GET,
+                &format!("sys/internal/ui/mounts/{}", secret_path),
+                None,
+            )
+            .await?;
+        if status.is_success()
+            && let Some(path) = body["data"]["path"].as_str()
+        {
+            let mount_type = body["data"]["type"].as_str().unwrap_or("unknown");
+            if !matches!(mount_type, "kv" | "generic") {
+                return Err(FnoxError::ProviderApiError {
+                    provider: "HashiCorp Vault".to_string(),
+                    details: format!(
+                        "Vault reports '{}' as a {} mount, not a KV secrets engine",
+                        path, mount_type
+                    ),
+                    hint: "Check the provider's path, which must be in a KV mount".to_string(),
+                    url: URL.to_string(),
+                });
+            }
+            // Vault omits the version for KV v1
+            let reported = match body["data"]["options"]["version"].as_str() {
+                Some("2") => 2,
+                _ => 1,
+            };
+            if let Some(configured) = kv_version
+                && configured != reported
+            {
+                return Err(FnoxError::Config(format!(
+                    "HashiCorp Vault provider has kv_version = {}, but Vault reports '{}' as a KV v{} mount. Set kv_version = {}, or remove it to detect the version",
+                    configured, path, reported, reported
+                )));
+            }
+            return Ok(KvMount {
+                path: path.to_string(),
+                versioned: reported == 2,
+            });
+        }
+
+        let first = secret_path.split('/').next().unwrap_or(secret_path);
+        Ok(KvMount {
+            path: format!("{}/", first),
+            versioned: kv_version != Some(1),
+        })
+    }
+
+    async fn read_kv(&self, mount: &KvMount, secret_path: &str) -> Result<KvSecret> {
+        let path = mount.data_path(secret_path);
+        let (status, body) = self.send(Method::GET, &path, None).await?;
+
+        // KV v2 answers 404 for a deleted latest version too, with its metadata
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(api_error(status, &body, secret_path));
+        }
+        let (data, version) = if mount.versioned {
+            (
+                &body["data"]["data"],
+                body["data"]["metadata"]["version"].as_u64().unwrap_or(0),
+            )
+        } else {
+            (&body["data"], 0)
+        };
+        Ok(KvSecret {
+            data: data.as_object().cloned().unwrap_or_default(),
+            version,
+        })
+    }
+
+    /// Names of the secrets in `dir`, without its subdirectories
+    async fn list_kv(&self, mount: &KvMount, dir: &str) -> Result<Vec<String>> {
+        let path = format!("{}?list=true", mount.list_path(dir));
+        let (status, body) = self.send(Method::GET, &path, None).await?;
+
+        // Vault answers 404 for a directory with no secrets in it
+        if status == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !status.is_success() {
+            return Err(api_error(status, &body, dir));
+        }
+        Ok(body["data"]["keys"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|name| !name.ends_with('/'))
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn write_kv(&self, mount: &KvMount, secret_path: &str, secret: &KvSecret) -> Result<()> {
+        let path = mount.data_path(secret_path);
+        let body = if mount.versioned {
+            json!({ "options": { "cas": secret.version }, "data": secret.data })
+        } else {
+            Value::Object(secret.data.clone())
+        };
+        let (status, response) = self.send(Method::POST, &path, Some(&body)).await?;
+        if status.is_success() {
+            return Ok(());
+        }
+
+        if status == StatusCode::BAD_REQUEST && errors(&response).contains("check-and-set") {
+            return Err(FnoxError::ProviderApiError {
+                provider: "HashiCorp Vault".to_string(),
+                details: format!(
+                    "Secret '{}' changed while it was being written (expected version {})",
+                    secret_path, secret.version
+                ),
+                hint: "Run the command again to write over the latest version".to_string(),
+                url: URL.to_string(),
+            });
+        }
+        Err(api_error(status, &response, secret_path))
+    }
+}
+
+/// The `errors` of a Vault API response, joined
+fn errors(body: &Value) -> String {
+    body["errors"]
+        .as_array()
+        .map(|errors| {
+            errors
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+        .unwrap_or_default()
+}
+
+fn api_error(status: StatusCode, body: &Value, secret_path: &str) -> FnoxError {
+    let mut details = errors(body);
+    if details.is_empty() {
+        details = format!("HTTP {}", status);
+    }
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        FnoxError::ProviderAuthFailed {
+            provider: "HashiCorp Vault".to_string(),
+            details,
+            hint: "Check your Vault token has the required permissions".to_string(),
+            url: URL.to_string(),
+        }
+    } else {
+        FnoxError::ProviderApiError {
+            provider: "HashiCorp Vault".to_string(),
+            details: format!("{} ({})", details, secret_path),
+            hint: "Check your Vault configuration".to_string(),
+            url: URL.to_string(),
+        }
+    }
 }
 
 pub fn env_dependencies() -> &'static [&'static str] {
@@ -274,3 +865,606 @@ fn vault_namespace() -> Option<String> {
         .or_else(|_| env::var("VAULT_NAMESPACE"))
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Provider;
+    use crate::recording::Cassette;
+    use crate::test_server::{self, Requests};
+
+    type Handler = fn(&str, &Value) -> (u16, String);
+
+    /// Minimal Vault API stand-in: answers each request with
+    /// `handler(path, json_body)`. GETs have a null body.
+    async fn mock_vault(handler: Handler) -> (String, Requests) {
+        let server =
+            test_server::serve(move |request, _| handler(&request.path, &request.json())).await;
+        (server.url, server.requests)
+    }
+
+    fn provider(address: String) -> HashiCorpVaultProvider {
+        HashiCorpVaultProvider::new(
+            Some(address),
+            Some("secret/fnox".to_string()),
+            Some("test-token".to_string()),
+            None,
+            None,
+            IndexMap::new(),
+            None,
+        )
+        .unwrap()
+    }
+
+    const KV2_MOUNT: &str = r#"{"data":{"path":"secret/","type":"kv","options":{"version":"2"}}}"#;
+
+    /// The writes the provider sent, as (path, body)
+    fn writes(requests: &Requests) -> Vec<(String, Value)> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| (request.path.clone(), request.json()))
+            .filter(|(_, body)| !body.is_null())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn put_secret_creates_a_new_secret() {
+        let (url, requests) = mock_vault(|path, body| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/myapp" => (200, KV2_MOUNT.to_string()),
+            "/v1/secret/data/fnox/myapp" if body.is_null() => (404, r#"{"errors":[]}"#.to_string()),
+            "/v1/secret/data/fnox/myapp" => (200, r#"{"data":{"version":1}}"#.to_string()),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        let stored = provider(url).put_secret("myapp", "s3cret").await.unwrap();
+
+        assert_eq!(stored, "myapp");
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/secret/data/fnox/myapp".to_string(),
+                json!({ "options": { "cas": 0 }, "data": { "value": "s3cret" } })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn put_secret_keeps_the_other_fields_of_an_existing_secret() {
+        let (url, requests) = mock_vault(|path, body| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (200, KV2_MOUNT.to_string()),
+            "/v1/secret/data/fnox/db" if body.is_null() => (
+                200,
+                json!({
+                    "data": {
+                        "data": { "username": "admin", "password": "old" },
+                        "metadata": { "version": 3 }
+                    }
+                })
+                .to_string(),
+            ),
+            "/v1/secret/data/fnox/db" => (200, r#"{"data":{"version":4}}"#.to_string()),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        let stored = provider(url)
+            .put_secret("db/password", "new")
+            .await
+            .unwrap();
+
+        assert_eq!(stored, "db/password");
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/secret/data/fnox/db".to_string(),
+                json!({
+                    "options": { "cas": 3 },
+                    "data": { "username": "admin", "password": "new" }
+                })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn put_secret_reports_check_and_set_conflicts() {
+        let (url, _) = mock_vault(|path, body| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (200, KV2_MOUNT.to_string()),
+            "/v1/secret/data/fnox/db" if body.is_null() => (
+                200,
+                r#"{"data":{"data":{"password":"old"},"metadata":{"version":3}}}"#.to_string(),
+            ),
+            _ => (
+                400,
+                r#"{"errors":["check-and-set parameter did not match the current version"]}"#
+                    .to_string(),
+            ),
+        })
+        .await;
+
+        match provider(url).put_secret("db/password", "new").await {
+            Err(FnoxError::ProviderApiError { details, .. }) => {
+                assert!(
+                    details.contains("changed while it was being written"),
+                    "{details}"
+                )
+            }
+            other => panic!("Expected ProviderApiError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn put_secret_writes_kv_v1_without_versions() {
+        let (url, requests) = mock_vault(|path, body| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (
+                200,
+                r#"{"data":{"path":"secret/","type":"kv","options":{"version":"1"}}}"#.to_string(),
+            ),
+            "/v1/secret/fnox/db" if body.is_null() => {
+                (200, r#"{"data":{"username":"admin"}}"#.to_string())
+            }
+            "/v1/secret/fnox/db" => (204, String::new()),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        provider(url)
+            .put_secret("db/password", "new")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/secret/fnox/db".to_string(),
+                json!({ "username": "admin", "password": "new" })
+            )]
+        );
+    }
+
+    fn kv_provider(address: String, kv_version: u32) -> HashiCorpVaultProvider {
+        HashiCorpVaultProvider::new(
+            Some(address),
+            Some("secret/fnox".to_string()),
+            Some("test-token".to_string()),
+            None,
+            None,
+            IndexMap::new(),
+            Some(kv_version),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn put_secret_uses_the_configured_kv_version_without_mount_info() {
+        let (url, requests) = mock_vault(|path, body| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (403, r#"{"errors":[]}"#.to_string()),
+            "/v1/secret/fnox/db" if body.is_null() => (404, r#"{"errors":[]}"#.to_string()),
+            "/v1/secret/fnox/db" => (204, String::new()),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        kv_provider(url, 1)
+            .put_secret("db/password", "new")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/secret/fnox/db".to_string(),
+                json!({ "password": "new" })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn put_secret_reports_kv_version_mismatches() {
+        let (url, requests) = mock_vault(|path, _| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (
+                200,
+                r#"{"data":{"path":"secret/","type":"kv","options":null}}"#.to_string(),
+            ),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        let err = kv_provider(url, 2)
+            .put_secret("db/password", "new")
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "kv_version = 2, but Vault reports 'secret/' as a KV v1 mount. Set kv_version = 1"
+            ),
+            "{err}"
+        );
+        assert!(writes(&requests).is_empty());
+    }
+
+    #[tokio::test]
+    async fn put_secret_rejects_mounts_that_are_not_kv() {
+        let (url, _) = mock_vault(|path, _| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox/db" => (
+                200,
+                r#"{"data":{"path":"secret/","type":"pki","options":null}}"#.to_string(),
+            ),
+            _ => (500, String::new()),
+        })
+        .await;
+
+        match provider(url).put_secret("db/password", "new").await {
+            Err(FnoxError::ProviderApiError { details, .. }) => assert_eq!(
+                details,
+                "Vault reports 'secret/' as a pki mount, not a KV secrets engine"
+            ),
+            other => panic!("Expected ProviderApiError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn list_secrets_names_each_field() {
+        let (url, _) = mock_vault(|path, _| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox" => (200, KV2_MOUNT.to_string()),
+            "/v1/secret/metadata/fnox/?list=true" => (
+                200,
+                r#"{"data":{"keys":["api-key","db","nested/"]}}"#.to_string(),
+            ),
+            "/v1/secret/data/fnox/api-key" => (
+                200,
+                r#"{"data":{"data":{"value":"k"},"metadata":{"version":1}}}"#.to_string(),
+            ),
+            "/v1/secret/data/fnox/db" => (
+                200,
+                r#"{"data":{"data":{"username":"admin","password":"p"},"metadata":{"version":2}}}"#
+                    .to_string(),
+            ),
+            _ => (500, String::new()),
+        })
+        .await;
+        let provider = provider(url);
+
+        assert_eq!(
+            provider.list_secrets(None).await.unwrap(),
+            ["api-key", "db/password", "db/username"]
+        );
+        assert_eq!(
+            provider.list_secrets(Some("db/p")).await.unwrap(),
+            ["db/password"]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_secrets_of_an_empty_directory() {
+        let (url, _) = mock_vault(|path, _| match path {
+            "/v1/sys/internal/ui/mounts/secret/fnox" => (200, KV2_MOUNT.to_string()),
+            _ => (404, r#"{"errors":[]}"#.to_string()),
+        })
+        .await;
+
+        assert!(provider(url).list_secrets(None).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_paths_follow_the_kv_version() {
+        let v2 = KvMount {
+            path: "secret/".to_string(),
+            versioned: true,
+        };
+        assert_eq!(v2.list_path("secret/fnox"), "secret/metadata/fnox/");
+        assert_eq!(v2.list_path("secret"), "secret/metadata/");
+        let v1 = KvMount {
+            path: "kv/".to_string(),
+            versioned: false,
+        };
+        assert_eq!(v1.list_path("kv/fnox/"), "kv/fnox/");
+    }
+
+    #[test]
+    fn kv_version_must_be_1_or_2() {
+        let result =
+            HashiCorpVaultProvider::new(None, None, None, None, None, IndexMap::new(), Some(3));
+        assert!(
+            matches!(result, Err(FnoxError::Config(message)) if message.contains("kv_version must be 1 or 2")),
+        );
+    }
+
+    /// A provider that logs in with the `auth` table `auth`
+    fn auth_provider(address: String, auth: &[(&str, &str)]) -> HashiCorpVaultProvider {
+        HashiCorpVaultProvider::new(
+            Some(address),
+            Some("secret/fnox".to_string()),
+            None,
+            None,
+            None,
+            auth.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            None,
+        )
+        .unwrap()
+    }
+
+    const LOGIN: &str =
+        r#"{"auth":{"client_token":"s.login","lease_duration":3600,"renewable":true}}"#;
+
+    #[test]
+    fn auth_config_is_validated() {
+        let error = |auth: &[(&str, &str)]| match HashiCorpVaultProvider::new(
+            None,
+            None,
+            None,
+            None,
+            None,
+            auth.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            None,
+        ) {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("{auth:?} was accepted"),
+        };
+
+        assert!(error(&[("role_id", "r")]).contains("requires 'method'"));
+        assert!(
+            error(&[("method", "ldap")]).contains("Unknown HashiCorp Vault auth method 'ldap'")
+        );
+        assert!(error(&[("method", "approle")]).contains("'approle' requires 'role_id'"));
+        assert!(error(&[("method", "kubernetes")]).contains("'kubernetes' requires 'role'"));
+        assert!(
+            error(&[("method", "kubernetes"), ("role", "r"), ("role_id", "x")])
+                .contains("Unknown key 'role_id'")
+        );
+    }
+
+    #[tokio::test]
+    async fn approle_login_is_cached() {
+        let (url, requests) = mock_vault(|path, _| match path {
+            "/v1/auth/approle/login" => (200, LOGIN.to_string()),
+            _ => (500, String::new()),
+        })
+        .await;
+        let provider = auth_provider(
+            url.clone(),
+            &[
+                ("method", "approle"),
+                ("role_id", "role"),
+                ("secret_id", "s3cret"),
+            ],
+        );
+
+        assert_eq!(
+            provider.get_token(&url).await.unwrap().as_deref(),
+            Some("s.login")
+        );
+        assert_eq!(
+            provider.get_token(&url).await.unwrap().as_deref(),
+            Some("s.login")
+        );
+
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/auth/approle/login".to_string(),
+                json!({ "role_id": "role", "secret_id": "s3cret" })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn tokens_are_renewed_after_half_their_ttl() {
+        let (url, requests) = mock_vault(|path, _| match path {
+            "/v1/auth/approle/login" => (200, LOGIN.to_string()),
+            "/v1/auth/token/renew-self" => (
+                200,
+                r#"{"auth":{"client_token":"s.login","lease_duration":3600,"renewable":true}}"#
+                    .to_string(),
+            ),
+            _ => (500, String::new()),
+        })
+        .await;
+        let provider = auth_provider(url.clone(), &[("method", "approle"), ("role_id", "role")]);
+        provider.get_token(&url).await.unwrap();
+
+        let half_ttl_ago = Instant::now() - Duration::from_secs(1);
+        provider.session.lock().await.as_mut().unwrap().renew_at = Some(half_ttl_ago);
+        assert_eq!(
+            provider.get_token(&url).await.unwrap().as_deref(),
+            Some("s.login")
+        );
+
+        let paths: Vec<String> = writes(&requests)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            ["/v1/auth/approle/login", "/v1/auth/token/renew-self"]
+        );
+        let renew_at = provider.session.lock().await.as_ref().unwrap().renew_at;
+        assert!(renew_at.is_some_and(|at| at > Instant::now()));
+    }
+
+    #[tokio::test]
+    async fn failed_renewals_log_in_again() {
+        let (url, requests) = mock_vault(|path, _| match path {
+            "/v1/auth/approle/login" => (200, LOGIN.to_string()),
+            "/v1/auth/token/renew-self" => (403, r#"{"errors":["permission denied"]}"#.to_string()),
+            _ => (500, String::new()),
+        })
+        .await;
+        let provider = auth_provider(url.clone(), &[("method", "approle"), ("role_id", "role")]);
+        provider.get_token(&url).await.unwrap();
+
+        provider.session.lock().await.as_mut().unwrap().renew_at = Some(Instant::now());
+        assert_eq!(
+            provider.get_token(&url).await.unwrap().as_deref(),
+            Some("s.login")
+        );
+
+        let paths: Vec<String> = writes(&requests)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/v1/auth/approle/login",
+                "/v1/auth/token/renew-self",
+                "/v1/auth/approle/login"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn kubernetes_login_sends_the_service_account_token() {
+        let (url, requests) = mock_vault(|path, _| match path {
+            "/v1/auth/k8s-prod/login" => (200, LOGIN.to_string()),
+            _ => (500, String::new()),
+        })
+        .await;
+        let jwt = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(jwt.path(), "eyJhbGciOi.jwt\n").unwrap();
+        let jwt_path = jwt.path().to_str().unwrap();
+        let provider = auth_provider(
+            url.clone(),
+            &[
+                ("method", "kubernetes"),
+                ("mount", "k8s-prod"),
+                ("role", "fnox"),
+                ("jwt_path", jwt_path),
+            ],
+        );
+
+        assert_eq!(
+            provider.get_token(&url).await.unwrap().as_deref(),
+            Some("s.login")
+        );
+        assert_eq!(
+            writes(&requests),
+            [(
+                "/v1/auth/k8s-prod/login".to_string(),
+                json!({ "role": "fnox", "jwt": "eyJhbGciOi.jwt" })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_logins_are_auth_errors() {
+        let (url, _) =
+            mock_vault(|_, _| (400, r#"{"errors":["invalid role ID"]}"#.to_string())).await;
+        let provider = auth_provider(url.clone(), &[("method", "approle"), ("role_id", "role")]);
+
+        match provider.test_connection().await {
+            Err(FnoxError::ProviderAuthFailed { details, .. }) => {
+                assert_eq!(details, "approle login failed: invalid role ID")
+            }
+            other => panic!("Expected ProviderAuthFailed, got {:?}", other),
+        }
+    }
+
+    /// A provider for cassette tests. Recording needs the dev server from
+    /// test/VAULT_TESTING.md; when replaying, the address and token are
+    /// never used.
+    fn cassette_provider(address: Option<String>) -> HashiCorpVaultProvider {
+        let address = address
+            .or_else(vault_address)
+            .unwrap_or_else(|| "http://127.0.0.1:8200".to_string());
+        let token = vault_token().unwrap_or_else(|| "test-token".to_string());
+        HashiCorpVaultProvider::new(
+            Some(address),
+            Some("secret/fnox".to_string()),
+            Some(token),
+            None,
+            None,
+            IndexMap::new(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn replays_reads() {
+        let cassette = Cassette::open("vault_get").unwrap();
+        let provider = cassette_provider(None);
+
+        let (value, field) = cassette
+            .run(async {
+                (
+                    provider.get_secret("api-key").await,
+                    provider.get_secret("db/password").await,
+                )
+            })
+            .await;
+
+        assert_eq!(value.unwrap(), "[VALUE-1]");
+        assert_eq!(field.unwrap(), "[VALUE-2]");
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_batch_reads() {
+        let cassette = Cassette::open("vault_batch").unwrap();
+        let provider = cassette_provider(None);
+        let secrets = [
+            ("DB_USER".to_string(), "db/username".to_string()),
+            ("DB_PASSWORD".to_string(), "db/password".to_string()),
+        ];
+
+        let results = cassette.run(provider.get_secrets_batch(&secrets)).await;
+
+        let user = results["DB_USER"].as_ref().unwrap();
+        let password = results["DB_PASSWORD"].as_ref().unwrap();
+        assert!(user.starts_with("[VALUE-"), "{user}");
+        assert!(password.starts_with("[VALUE-"), "{password}");
+        assert_ne!(user, password);
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_read_errors() {
+        let cassette = Cassette::open("vault_errors").unwrap();
+        let provider = cassette_provider(None);
+
+        let (missing, denied) = cassette
+            .run(async {
+                (
+                    provider.get_secret("missing").await,
+                    provider.get_secret("restricted").await,
+                )
+            })
+            .await;
+
+        assert!(
+            matches!(&missing, Err(FnoxError::ProviderCliFailed { details, .. })
+                if details.contains("No value found at secret/data/fnox/missing")),
+            "{missing:?}"
+        );
+        assert!(
+            matches!(denied, Err(FnoxError::ProviderAuthFailed { .. })),
+            "{denied:?}"
+        );
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_writes_through_the_api() {
+        let cassette = Cassette::open("vault_write").unwrap();
+        let endpoint = cassette
+            .http_endpoint(vault_address().as_deref())
+            .await
+            .unwrap();
+        let provider = cassette_provider(Some(endpoint));
+
+        let stored = cassette
+            .run(provider.put_secret("db/password", "new-password"))
+            .await
+            .unwrap();
+
+        assert_eq!(stored, "db/password");
+        cassette.finish().unwrap();
+    }
+}
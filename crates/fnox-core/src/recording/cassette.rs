@@ -0,0 +1,800 @@
+//! Record and replay of provider interactions, so provider tests can run
+//! without credentials. Only built for this crate's tests.
+//!
+//! A [`Cassette`] holds the CLI invocations and HTTP requests a test made,
+//! stored as JSON under `tests/fixtures/cassettes`. Tests replay cassettes by
+//! default; with `FNOX_RECORD=1` they talk to the real service instead and
+//! the cassette is rewritten. Credentials and secret values are scrubbed
+//! before anything is written to disk.
+//!
+//! CLI providers run their commands through [`CommandRunner`], which answers
+//! from the current task's cassette, if any. HTTP providers are pointed at
+//! [`Cassette::http_endpoint`], a local server that replays responses, or
+//! forwards requests to the real service and records them.
+
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+
+use super::Stdout;
+use crate::env;
+use crate::error::{FnoxError, Result};
+use crate::test_server::{Request, read_request, write_response};
+
+/// Placeholder for scrubbed credentials
+const REDACTED: &str = "[REDACTED]";
+/// Placeholder for temporary file arguments, whose names differ between runs
+const TEMP_FILE: &str = "[TEMP_FILE]";
+/// Known secrets shorter than this are only scrubbed where they make up a
+/// whole string, since they'd match unrelated text
+const MIN_SCRUB_LEN: usize = 4;
+
+/// Flags, environment variables and headers that carry credentials
+static CREDENTIAL_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)token|secret|password|passphrase|credential|api[-_]?key|authorization")
+        .unwrap()
+});
+
+/// JSON keys holding secret values. The fields of a KV v2 secret
+/// (`data.data`) count too.
+static SECRET_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)token|password|passphrase|value").unwrap());
+
+/// Markers providers print in place of a value, which replays need as is
+const NOT_SECRETS: &[&str] = &["*not found*"];
+
+/// Lines of `op inject` style output that start a new `KEY=value` pair
+static ENV_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)=(.*)$").unwrap());
+
+/// The temp directory as given and as resolved (e.g. /private/var on macOS)
+static TEMP_DIRS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let temp = std::env::temp_dir();
+    let mut dirs = vec![temp.to_string_lossy().to_string()];
+    if let Ok(resolved) = std::fs::canonicalize(&temp) {
+        dirs.push(resolved.to_string_lossy().to_string());
+    }
+    dirs
+});
+
+tokio::task_local! {
+    static CASSETTE: Arc<Cassette>;
+}
+
+/// A recorded CLI invocation or HTTP exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Interaction {
+    Command {
+        program: String,
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stdin: Option<String>,
+        status: i32,
+        /// JSON output is stored as JSON, anything else as a string
+        stdout: Value,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        stderr: String,
+    },
+    Http {
+        method: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Value::is_null")]
+        body: Value,
+        status: u16,
+        response: Value,
+    },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Recorded interactions for one test
+pub struct Cassette {
+    path: PathBuf,
+    record: bool,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    /// When replaying, which interactions have been played back
+    used: Vec<bool>,
+    scrubber: Scrubber,
+}
+
+impl Cassette {
+    /// The cassette `name` under `tests/fixtures/cassettes`, loaded for
+    /// replay, or empty when recording (`FNOX_RECORD=1`)
+    pub fn open(name: &str) -> Result<Arc<Self>> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/cassettes")
+            .join(format!("{}.json", name));
+        Self::at(path, *env::FNOX_RECORD)
+    }
+
+    fn at(path: PathBuf, record: bool) -> Result<Arc<Self>> {
+        let mut state = State::default();
+        if record {
+            // Credentials in the environment can show up in any output
+            for (name, value) in std::env::vars() {
+                if CREDENTIAL_NAME.is_match(&name) {
+                    state.scrubber.credential(&value);
+                }
+            }
+        } else {
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                FnoxError::Config(format!(
+                    "Failed to read cassette '{}': {}. Record it with FNOX_RECORD=1",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let file: CassetteFile = serde_json::from_str(&content).map_err(|e| {
+                FnoxError::Config(format!(
+                    "Failed to parse cassette '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            state.used = vec![false; file.interactions.len()];
+            state.interactions = file.interactions;
+        }
+        Ok(Arc::new(Self {
+            path,
+            record,
+            state: Mutex::new(state),
+        }))
+    }
+
+    /// Record `value` as `placeholder`, for values that differ between
+    /// environments, like a project ID. When replaying, pass the value the
+    /// test uses so its requests still match.
+    pub fn filter(&self, value: &str, placeholder: &str) {
+        self.lock().scrubber.add(value, placeholder.to_string());
+    }
+
+    /// The cassette of the current task, if it runs under one
+    pub(super) fn current() -> Option<Arc<Self>> {
+        CASSETTE.try_with(Arc::clone).ok()
+    }
+
+    /// Whether the cassette records rather than replays
+    pub(super) fn is_recording(&self) -> bool {
+        self.record
+    }
+
+    /// Run `future` with this cassette answering the commands it runs
+    pub async fn run<F: Future>(self: &Arc<Self>, future: F) -> F::Output {
+        CASSETTE.scope(Arc::clone(self), future).await
+    }
+
+    /// Write the recording, or when replaying, check that every recorded
+    /// interaction was played back
+    pub fn finish(&self) -> Result<()> {
+        let state = self.lock();
+        if !self.record {
+            let unused = state.used.iter().filter(|used| !**used).count();
+            if unused > 0 {
+                return Err(FnoxError::Config(format!(
+                    "{} interaction(s) in cassette '{}' were not replayed",
+                    unused,
+                    self.path.display()
+                )));
+            }
+            return Ok(());
+        }
+
+        let write_error = |e: io::Error| {
+            FnoxError::Config(format!(
+                "Failed to write cassette '{}': {}",
+                self.path.display(),
+                e
+            ))
+        };
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| FnoxError::Config(format!("Failed to serialize cassette: {}", e)))?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(write_error)?;
+        }
+        std::fs::write(&self.path, content + "\n").map_err(write_error)
+    }
+
+    /// Base URL of a local server standing in for an HTTP service. Point the
+    /// provider's address at it. `upstream` is the real service's base URL,
+    /// which is only needed when recording.
+    pub async fn http_endpoint(self: &Arc<Self>, upstream: Option<&str>) -> Result<String> {
+        let upstream = upstream.map(|url| url.trim_end_matches('/').to_string());
+        if self.record && upstream.is_none() {
+            return Err(FnoxError::Config(
+                "Recording HTTP interactions needs the real service's address".to_string(),
+            ));
+        }
+
+        let bind_error =
+            |e: io::Error| FnoxError::Config(format!("Failed to start cassette server: {}", e));
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(bind_error)?;
+        let address = listener.local_addr().map_err(bind_error)?;
+
+        let cassette = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let cassette = Arc::clone(&cassette);
+                let upstream = upstream.clone();
+                tokio::spawn(async move { cassette.serve(socket, upstream).await });
+            }
+        });
+
+        Ok(format!("http://{}", address))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Play back the first unused recording of this command
+    pub(super) fn replay_command(
+        &self,
+        command: &Command,
+        stdin: Option<&str>,
+    ) -> io::Result<Output> {
+        let mut state = self.lock();
+        state.scrubber.command_env(command);
+        let (program, args) = command_line(command);
+        let args = state.scrubber.args(&args);
+        let stdin = stdin.map(|input| state.scrubber.text(input));
+
+        let recorded = state.interactions.iter().enumerate().position(|(i, interaction)| {
+            !state.used[i]
+                && matches!(interaction, Interaction::Command { program: p, args: a, stdin: s, .. }
+                    if *p == program && *a == args && *s == stdin)
+        });
+        let Some(index) = recorded else {
+            return Err(io::Error::other(format!(
+                "no recorded interaction for `{} {}` in cassette '{}'",
+                program,
+                args.join(" "),
+                self.path.display()
+            )));
+        };
+        state.used[index] = true;
+
+        let Interaction::Command {
+            status,
+            stdout,
+            stderr,
+            ..
+        } = &state.interactions[index]
+        else {
+            unreachable!("matched a command interaction");
+        };
+        Ok(Output {
+            status: exit_status(*status),
+            stdout: raw(stdout).into_bytes(),
+            stderr: stderr.clone().into_bytes(),
+        })
+    }
+
+    pub(super) fn record_command(
+        &self,
+        command: &Command,
+        stdin: Option<&str>,
+        kind: Stdout,
+        output: &Output,
+    ) {
+        let mut state = self.lock();
+        let State {
+            interactions,
+            scrubber,
+            ..
+        } = &mut *state;
+
+        scrubber.command_env(command);
+        let (program, args) = command_line(command);
+        let args = scrubber.args(&args);
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stdout = match kind {
+            // Failures print errors, not values
+            _ if !output.status.success() => scrubber.document(&stdout),
+            Stdout::Plain => scrubber.document(&stdout),
+            Stdout::Value => Value::String(scrubber.value(stdout.trim())),
+            Stdout::Env => Value::String(scrubber.env_lines(&stdout)),
+        };
+
+        interactions.push(Interaction::Command {
+            program,
+            args,
+            stdin: stdin.map(|input| scrubber.text(input)),
+            status: output.status.code().unwrap_or(-1),
+            stdout,
+            stderr: scrubber.text(&String::from_utf8_lossy(&output.stderr)),
+        });
+    }
+
+    /// Answer the requests on one connection
+    async fn serve(&self, mut socket: TcpStream, upstream: Option<String>) {
+        let mut buf = Vec::new();
+        while let Some(request) = read_request(&mut socket, &mut buf).await {
+            let (status, body) = match &upstream {
+                Some(upstream) if self.record => self.forward(upstream, &request).await,
+                _ => self.replay_http(&request),
+            };
+            if write_response(&mut socket, status, &body).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Play back the first unused recording of this request
+    fn replay_http(&self, request: &Request) -> (u16, String) {
+        let mut state = self.lock();
+        let path = state.scrubber.text(&request.path);
+
+        let recorded = state
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| {
+                !state.used[i]
+                    && matches!(interaction, Interaction::Http { method, path: p, .. }
+                    if *method == request.method && *p == path)
+            });
+        let Some(index) = recorded else {
+            let message = format!(
+                "no recorded interaction for {} {} in cassette '{}'",
+                request.method,
+                path,
+                self.path.display()
+            );
+            return (500, json!({ "errors": [message] }).to_string());
+        };
+        state.used[index] = true;
+
+        match &state.interactions[index] {
+            Interaction::Http {
+                status, response, ..
+            } => (*status, raw(response)),
+            Interaction::Command { .. } => unreachable!("matched an HTTP interaction"),
+        }
+    }
+
+    /// Send a request to the real service and record the exchange
+    async fn forward(&self, upstream: &str, request: &Request) -> (u16, String) {
+        let method =
+            reqwest::Method::from_bytes(request.method.as_bytes()).unwrap_or(reqwest::Method::GET);
+        let mut builder = crate::http::http_client()
+            .request(method, format!("{}{}", upstream, request.path))
+            .body(request.body.clone());
+        for (name, value) in &request.headers {
+            // Set by the client for the upstream connection
+            if !["host", "content-length", "connection"]
+                .contains(&name.to_ascii_lowercase().as_str())
+            {
+                builder = builder.header(name, value);
+            }
+        }
+        let (status, body) = match builder.send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                (status, response.text().await.unwrap_or_default())
+            }
+            Err(e) => (502, json!({ "errors": [e.to_string()] }).to_string()),
+        };
+
+        let mut state = self.lock();
+        let State {
+            interactions,
+            scrubber,
+            ..
+        } = &mut *state;
+        for (name, value) in &request.headers {
+            if CREDENTIAL_NAME.is_match(name) {
+                scrubber.credential(value);
+                // "Bearer <token>"
+                if let Some((_, token)) = value.split_once(' ') {
+                    scrubber.credential(token);
+                }
+            }
+        }
+        let request_body = String::from_utf8_lossy(&request.body);
+        interactions.push(Interaction::Http {
+            method: request.method.clone(),
+            path: scrubber.text(&request.path),
+            body: if request_body.is_empty() {
+                Value::Null
+            } else {
+                scrubber.document(&request_body)
+            },
+            status,
+            response: scrubber.document(&body),
+        });
+
+        (status, body)
+    }
+}
+
+/// Replaces credentials and secret values in what gets recorded
+#[derive(Default)]
+struct Scrubber {
+    /// Known secrets and their placeholders, longest first
+    known: Vec<(String, String)>,
+    /// Secret values seen so far
+    values: usize,
+}
+
+impl Scrubber {
+    fn credential(&mut self, credential: &str) {
+        self.add(credential, REDACTED.to_string());
+    }
+
+    /// The placeholder for a secret value. Each distinct value gets its own
+    /// numbered placeholder, so replayed batches still tell values apart.
+    fn value(&mut self, value: &str) -> String {
+        if value.is_empty() || NOT_SECRETS.contains(&value) {
+            return value.to_string();
+        }
+        if let Some((_, placeholder)) = self.known.iter().find(|(known, _)| known == value) {
+            return placeholder.clone();
+        }
+        self.values += 1;
+        let placeholder = format!("[VALUE-{}]", self.values);
+        self.add(value, placeholder.clone());
+        placeholder
+    }
+
+    fn add(&mut self, secret: &str, placeholder: String) {
+        if secret.is_empty() || self.known.iter().any(|(known, _)| known == secret) {
+            return;
+        }
+        self.known.push((secret.to_string(), placeholder));
+        // Replace secrets that contain others first
+        self.known
+            .sort_by_key(|(known, _)| std::cmp::Reverse(known.len()));
+    }
+
+    /// Replace the known secrets in `text`
+    fn text(&self, text: &str) -> String {
+        if let Some((_, placeholder)) = self.known.iter().find(|(known, _)| known == text) {
+            return placeholder.clone();
+        }
+        self.known
+            .iter()
+            .filter(|(known, _)| known.len() >= MIN_SCRUB_LEN)
+            .fold(text.to_string(), |text, (known, placeholder)| {
+                text.replace(known, placeholder)
+            })
+    }
+
+    /// Treat credentials passed to `command` in its environment as such
+    fn command_env(&mut self, command: &Command) {
+        for (name, value) in command.as_std().get_envs() {
+            if let Some(value) = value
+                && CREDENTIAL_NAME.is_match(&name.to_string_lossy())
+            {
+                self.credential(&value.to_string_lossy());
+            }
+        }
+    }
+
+    /// Scrub command arguments: the values of credential flags (`--token X`
+    /// or `--token=X`), and temporary files
+    fn args(&mut self, args: &[String]) -> Vec<String> {
+        let mut credential_follows = false;
+        for arg in args {
+            if std::mem::take(&mut credential_follows) {
+                self.credential(arg);
+            } else if let Some((flag, value)) = arg.split_once('=') {
+                // `-field=password` names a field, it isn't a credential flag
+                if is_credential_flag(flag) {
+                    self.credential(value);
+                }
+            } else {
+                credential_follows = is_credential_flag(arg);
+            }
+        }
+
+        args.iter()
+            .map(|arg| {
+                if TEMP_DIRS.iter().any(|dir| arg.starts_with(dir.as_str())) {
+                    TEMP_FILE.to_string()
+                } else {
+                    self.text(arg)
+                }
+            })
+            .collect()
+    }
+
+    /// Scrub a response body or command output: JSON by key, anything else
+    /// as text
+    fn document(&mut self, text: &str) -> Value {
+        match serde_json::from_str::<Value>(text) {
+            Ok(mut json) if json.is_object() || json.is_array() => {
+                self.json(&mut json, "", false);
+                json
+            }
+            _ => Value::String(self.text(text)),
+        }
+    }
+
+    /// Scrub `json`, found under `key`. Strings under keys that hold secrets
+    /// (`secret`) become value placeholders.
+    fn json(&mut self, json: &mut Value, key: &str, secret: bool) {
+        match json {
+            Value::String(s) if secret => *s = self.value(s),
+            Value::String(s) => *s = self.text(s),
+            Value::Array(items) => {
+                for item in items {
+                    self.json(item, key, secret);
+                }
+            }
+            Value::Object(map) => {
+                for (child, item) in map.iter_mut() {
+                    let secret = child != "metadata"
+                        && (secret
+                            || SECRET_KEY.is_match(child)
+                            || (key == "data" && child == "data"));
+                    self.json(item, child, secret);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Scrub `KEY=value` lines; lines that don't start a pair continue the
+    /// previous (multi-line) value
+    fn env_lines(&mut self, text: &str) -> String {
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            match (ENV_LINE.captures(line), pairs.last_mut()) {
+                (None, Some((_, value))) => {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+                (Some(pair), _) => pairs.push((pair[1].to_string(), pair[2].to_string())),
+                (None, None) => {}
+            }
+        }
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}\n", key, self.value(value)))
+            .collect()
+    }
+}
+
+fn is_credential_flag(arg: &str) -> bool {
+    arg.starts_with('-') && CREDENTIAL_NAME.is_match(arg)
+}
+
+fn command_line(command: &Command) -> (String, Vec<String>) {
+    let command = command.as_std();
+    let program = command.get_program().to_string_lossy().to_string();
+    let args = command
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    (program, args)
+}
+
+/// Recorded output as the service or command produced it
+fn raw(recorded: &Value) -> String {
+    match recorded {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        json => json.to_string(),
+    }
+}
+
+fn exit_status(code: i32) -> ExitStatus {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(code << 8)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(code as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::CommandRunner;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn scrubs_credential_flags() {
+        let mut scrubber = Scrubber::default();
+        let scrubbed = scrubber.args(&args(&[
+            "secrets",
+            "get",
+            "--token",
+            "st.abc123",
+            "--client-secret=shh-secret",
+            "--env=dev",
+            "-field=password",
+            "secret/fnox/db",
+        ]));
+        assert_eq!(
+            scrubbed,
+            args(&[
+                "secrets",
+                "get",
+                "--token",
+                REDACTED,
+                &format!("--client-secret={}", REDACTED),
+                "--env=dev",
+                "-field=password",
+                "secret/fnox/db",
+            ])
+        );
+        // And wherever they show up later
+        assert_eq!(
+            scrubber.text("invalid token st.abc123"),
+            "invalid token [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn scrubs_temporary_files() {
+        let template = std::env::temp_dir().join(".tmpAbC123");
+        let mut scrubber = Scrubber::default();
+        assert_eq!(
+            scrubber.args(&args(&["--template", &template.to_string_lossy()])),
+            args(&["--template", TEMP_FILE])
+        );
+    }
+
+    #[test]
+    fn numbers_secret_values_consistently() {
+        let mut scrubber = Scrubber::default();
+        assert_eq!(scrubber.value("hunter22"), "[VALUE-1]");
+        assert_eq!(scrubber.value("correct horse"), "[VALUE-2]");
+        assert_eq!(scrubber.value("hunter22"), "[VALUE-1]");
+        assert_eq!(scrubber.text("was hunter22"), "was [VALUE-1]");
+    }
+
+    #[test]
+    fn scrubs_json_by_key() {
+        let mut scrubber = Scrubber::default();
+        let scrubbed = scrubber.document(
+            r#"{"auth": {"client_token": "hvs.abcd"},
+                "data": {"data": {"password": "p4ss", "user": "admin"},
+                         "metadata": {"created_time": "2024-01-01T00:00:00Z"}},
+                "items": [{"secretKey": "API_KEY", "secretValue": "k3y!"}]}"#,
+        );
+        assert_eq!(
+            scrubbed,
+            json!({
+                "auth": { "client_token": "[VALUE-1]" },
+                "data": {
+                    "data": { "password": "[VALUE-2]", "user": "[VALUE-3]" },
+                    "metadata": { "created_time": "2024-01-01T00:00:00Z" }
+                },
+                "items": [{ "secretKey": "API_KEY", "secretValue": "[VALUE-4]" }]
+            })
+        );
+
+        // Other `data` isn't secret, e.g. Vault's mount info
+        let mount = r#"{"data":{"options":{"version":"2"},"path":"secret/"}}"#;
+        assert_eq!(
+            scrubber.document(mount),
+            serde_json::from_str::<Value>(mount).unwrap()
+        );
+    }
+
+    #[test]
+    fn scrubs_env_lines_including_multi_line_values() {
+        let mut scrubber = Scrubber::default();
+        assert_eq!(
+            scrubber.env_lines("A=one\nB=two\nlines\nC=\n"),
+            "A=[VALUE-1]\nB=[VALUE-2]\nC=\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn commands_run_without_a_cassette() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo hi"]);
+        let output = CommandRunner::new(command).output().await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\n");
+    }
+
+    /// `sh -c` printing a secret value, with a token in its environment
+    /// that it also echoes as an argument
+    #[cfg(unix)]
+    fn print_secret() -> Command {
+        let mut command = Command::new("sh");
+        command
+            .env("FAKE_TOKEN", "tok-12345")
+            .env("FAKE_VALUE", "s3cret-value")
+            .args([
+                "-c",
+                "cat >/dev/null; echo \"$FAKE_VALUE\"",
+                "--",
+                "tok-12345",
+            ]);
+        command
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn records_scrubbed_commands_and_replays_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let cassette = Cassette::at(path.clone(), true).unwrap();
+        let output = cassette
+            .run(
+                CommandRunner::new(print_secret())
+                    .stdin("input")
+                    .stdout(Stdout::Value)
+                    .output(),
+            )
+            .await
+            .unwrap();
+        // The caller gets the real output
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "s3cret-value\n");
+        cassette.finish().unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("s3cret-value"), "{written}");
+        assert!(!written.contains("tok-12345"), "{written}");
+
+        let cassette = Cassette::at(path, false).unwrap();
+        let output = cassette
+            .run(CommandRunner::new(print_secret()).stdin("input").output())
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "[VALUE-1]");
+        cassette.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replay_fails_for_unrecorded_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+        std::fs::write(
+            &path,
+            r#"{"interactions": [{"type": "command", "program": "op", "args": ["whoami"], "status": 0, "stdout": "me"}]}"#,
+        )
+        .unwrap();
+
+        let cassette = Cassette::at(path, false).unwrap();
+        let err = cassette
+            .run(async {
+                let mut command = Command::new("op");
+                command.arg("read");
+                CommandRunner::new(command).output().await.unwrap_err()
+            })
+            .await;
+        assert!(
+            err.to_string()
+                .contains("no recorded interaction for `op read`"),
+            "{err}"
+        );
+        assert!(cassette.finish().is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Running provider CLI commands through [`CommandRunner`], which this
+//! crate's tests record and replay with cassettes (see `cassette`), so
+//! provider tests can run without credentials.
+
+use std::io;
+use std::process::{Output, Stdio};
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[cfg(test)]
+mod cassette;
+
+#[cfg(test)]
+pub(crate) use cassette::Cassette;
+
+/// What a command prints on stdout, so recordings can scrub secret values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stdout {
+    /// No secret values, apart from JSON fields named like one
+    Plain,
+    /// A single secret value
+    Value,
+    /// `KEY=value` pairs, one per line, as printed by `op inject`
+    Env,
+}
+
+/// Runs a provider's CLI command. In this crate's tests, it plays the command
+/// back from the current task's cassette instead, or records it.
+pub struct CommandRunner {
+    command: Command,
+    stdin: Option<String>,
+    #[cfg_attr(not(test), allow(dead_code))]
+    stdout: Stdout,
+}
+
+impl CommandRunner {
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            stdin: None,
+            stdout: Stdout::Plain,
+        }
+    }
+
+    /// Write `input` to the command's stdin
+    pub fn stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    /// What the command prints, so recordings scrub secret values
+    pub fn stdout(mut self, stdout: Stdout) -> Self {
+        self.stdout = stdout;
+        self
+    }
+
+    /// Run the command to completion and collect its output
+    pub async fn output(mut self) -> io::Result<Output> {
+        #[cfg(test)]
+        let cassette = Cassette::current();
+        #[cfg(test)]
+        if let Some(cassette) = &cassette
+            && !cassette.is_recording()
+        {
+            return cassette.replay_command(&self.command, self.stdin.as_deref());
+        }
+
+        let output = self.run().await?;
+        #[cfg(test)]
+        if let Some(cassette) = cassette {
+            cassette.record_command(&self.command, self.stdin.as_deref(), self.stdout, &output);
+        }
+        Ok(output)
+    }
+
+    async fn run(&mut self) -> io::Result<Output> {
+        let Some(input) = &self.stdin else {
+            return self.command.output().await;
+        };
+        self.command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = self.command.spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes()).await?;
+        }
+        child.wait_with_output().await
+    }
+}
@@ -0,0 +1,972 @@
+//! Nested secret groups.
+//!
+//! A table under `[secrets]` (or `[profiles.<name>.secrets]`) whose entries are
+//! themselves tables is a group rather than a secret:
+//!
+//! ```toml
+//! [secrets.stripe]
+//! provider = "age"
+//! api_key = { value = "..." }
+//! webhook_secret = { value = "..." }
+//! ```
+//!
+//! Groups are flattened when a config file is loaded, so the rest of fnox only
+//! ever sees `STRIPE_API_KEY` and `STRIPE_WEBHOOK_SECRET`. Settings on the group
+//! table itself (see [`INHERITED_FIELDS`]) are defaults for every secret in it.
+//! Spans of `provider` and `value` are mapped back to the original file, and the
+//! write helpers here let `set`, `import`, `remove` and `rename` edit a secret
+//! where it lives instead of adding a flat duplicate.
+
+use crate::error::{FnoxError, Result};
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::ops::Range;
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// Fields of a secret, used to tell a secret's settings apart from group members.
+const SECRET_FIELDS: &[&str] = &[
+    "description",
+    "if_missing",
+    "default",
+    "provider",
+    "providers",
+    "value",
+    "template",
+    "env",
+    "as_file",
+    "json_path",
+    "line",
+    "sync",
+    "daemon_cache",
+];
+
+/// Secret fields that may be set on a group table and are inherited by its secrets.
+pub const INHERITED_FIELDS: &[&str] = &[
+    "provider",
+    "providers",
+    "if_missing",
+    "env",
+    "as_file",
+    "daemon_cache",
+];
+
+/// How group paths become secret names (`[secret_groups]` in config).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SecretGroupsConfig {
+    /// Joiner between group and key names (default: "_")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+
+    /// Case of the resulting secret name (default: upper)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<GroupKeyCase>,
+}
+
+/// Case applied to each segment of a grouped secret name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupKeyCase {
+    // `stripe.api_key` -> `STRIPE_API_KEY`
+    #[default]
+    Upper,
+    // `Stripe.API_KEY` -> `stripe_api_key`
+    Lower,
+    // Keep segments as written
+    Preserve,
+}
+
+impl SecretGroupsConfig {
+    /// Read the options from a parsed config document, ignoring malformed values
+    /// (config deserialization reports those with a proper span).
+    pub fn from_document(doc: &DocumentMut) -> Self {
+        let Some(table) = doc.get("secret_groups").and_then(Item::as_table_like) else {
+            return Self::default();
+        };
+        Self {
+            separator: table
+                .get("separator")
+                .and_then(Item::as_str)
+                .map(str::to_string),
+            case: table
+                .get("case")
+                .and_then(Item::as_str)
+                .and_then(|case| match case {
+                    "upper" => Some(GroupKeyCase::Upper),
+                    "lower" => Some(GroupKeyCase::Lower),
+                    "preserve" => Some(GroupKeyCase::Preserve),
+                    _ => None,
+                }),
+        }
+    }
+
+    pub fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or("_")
+    }
+
+    pub fn case(&self) -> GroupKeyCase {
+        self.case.unwrap_or_default()
+    }
+
+    /// The secret name for a path of group names followed by the key.
+    pub fn key_name<S: AsRef<str>>(&self, path: &[S]) -> String {
+        path.iter()
+            .map(|segment| self.apply_case(segment.as_ref()))
+            .collect::<Vec<_>>()
+            .join(self.separator())
+    }
+
+    fn apply_case(&self, segment: &str) -> String {
+        match self.case() {
+            GroupKeyCase::Upper => segment.to_uppercase(),
+            GroupKeyCase::Lower => segment.to_lowercase(),
+            GroupKeyCase::Preserve => segment.to_string(),
+        }
+    }
+
+    /// The key to write inside a group for the rest of a secret name, if a key
+    /// written that way maps back to exactly `rest`.
+    fn member_key(&self, rest: &str) -> Option<String> {
+        let key = match self.case() {
+            GroupKeyCase::Upper => rest.to_lowercase(),
+            GroupKeyCase::Lower | GroupKeyCase::Preserve => rest.to_string(),
+        };
+        (!key.is_empty() && self.apply_case(&key) == rest).then_some(key)
+    }
+}
+
+/// JSON schema for a `secrets` table, whose entries are secrets or groups.
+pub fn secrets_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": "object",
+        "additionalProperties": generator.subschema_for::<SecretEntry>()
+    })
+}
+
+/// A `[secrets]` entry: a secret, or a group of secrets (schema only; groups are
+/// flattened when the config is loaded)
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum SecretEntry {
+    Secret(Box<crate::config::SecretConfig>),
+    Group(SecretGroup),
+}
+
+/// A group of secrets named `<group><separator><key>`. Its settings are defaults
+/// for every secret in it.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct SecretGroup {
+    /// Provider for secrets that don't set `provider`, `providers` or `template`
+    provider: Option<String>,
+    /// Fallback chain for secrets that don't set `provider`, `providers` or `template`
+    providers: Option<Vec<String>>,
+    /// Default if_missing behavior for secrets in this group
+    if_missing: Option<crate::config::IfMissing>,
+    /// Default for `env` of secrets in this group
+    env: Option<bool>,
+    /// Default for `as_file` of secrets in this group
+    as_file: Option<bool>,
+    /// Default for `daemon_cache` of secrets in this group
+    daemon_cache: Option<bool>,
+    /// Secrets and nested groups
+    #[serde(flatten)]
+    members: IndexMap<String, SecretEntry>,
+}
+
+/// A secret that was defined inside a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupedSecret {
+    /// Profile whose `secrets` table holds the group (`None` for top-level `[secrets]`)
+    pub profile: Option<String>,
+    /// Flattened secret name
+    pub name: String,
+    /// Group names followed by the secret's key inside the innermost group
+    pub path: Vec<String>,
+}
+
+/// Result of flattening a config file that uses groups
+#[derive(Debug)]
+pub struct Flattened {
+    /// The config with every group replaced by flat secrets
+    pub content: String,
+    pub secrets: Vec<GroupedSecret>,
+}
+
+/// Whether a `[secrets]` entry is a group rather than a secret.
+pub fn is_group(table: &dyn TableLike) -> bool {
+    table
+        .iter()
+        .any(|(key, item)| !SECRET_FIELDS.contains(&key) && item.is_table_like())
+}
+
+/// Flatten all groups in a config file.
+///
+/// Returns `Ok(None)` if the file has no groups (or doesn't parse, in which case
+/// regular deserialization reports the error), so ungrouped configs are loaded
+/// from their original text.
+pub fn flatten(content: &str) -> Result<Option<Flattened>> {
+    let Ok(mut doc) = content.parse::<DocumentMut>() else {
+        return Ok(None);
+    };
+    let options = SecretGroupsConfig::from_document(&doc);
+    let mut secrets = Vec::new();
+
+    if let Some(table) = doc.get_mut("secrets").and_then(Item::as_table_mut) {
+        flatten_table(table, None, &options, &mut secrets)?;
+    }
+    if let Some(profiles) = doc.get_mut("profiles").and_then(Item::as_table_mut) {
+        for (profile, item) in profiles.iter_mut() {
+            let profile = profile.get().to_string();
+            if let Some(table) = item
+                .as_table_mut()
+                .and_then(|p| p.get_mut("secrets"))
+                .and_then(Item::as_table_mut)
+            {
+                flatten_table(table, Some(&profile), &options, &mut secrets)?;
+            }
+        }
+    }
+
+    if secrets.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Flattened {
+        content: doc.to_string(),
+        secrets,
+    }))
+}
+
+/// Replace the groups in one `secrets` table with their flattened secrets, in place.
+fn flatten_table(
+    table: &mut Table,
+    profile: Option<&str>,
+    options: &SecretGroupsConfig,
+    out: &mut Vec<GroupedSecret>,
+) -> Result<()> {
+    if !table
+        .iter()
+        .any(|(_, item)| item.as_table_like().is_some_and(is_group))
+    {
+        return Ok(());
+    }
+
+    let entries: Vec<(toml_edit::Key, Item)> = table
+        .iter()
+        .map(|(name, item)| (table.key(name).expect("key exists").clone(), item.clone()))
+        .collect();
+    table.clear();
+
+    for (key, item) in entries {
+        let group = item.as_table_like().filter(|t| is_group(*t));
+        let Some(group) = group else {
+            insert_unique(table, key, item, None)?;
+            continue;
+        };
+
+        let mut members = Vec::new();
+        collect_members(
+            group,
+            &mut vec![key.get().to_string()],
+            &InlineTable::new(),
+            &mut members,
+        )?;
+        let mut first = true;
+        for (path, member) in members {
+            let name = options.key_name(&path);
+            let mut flat_key = toml_edit::Key::new(name.as_str());
+            // Keep comments above the group with its first secret
+            if first {
+                *flat_key.leaf_decor_mut() = key.leaf_decor().clone();
+                first = false;
+            }
+            let group_name = path[..path.len() - 1].join(".");
+            insert_unique(
+                table,
+                flat_key,
+                Item::Value(Value::InlineTable(member)),
+                Some(&group_name),
+            )?;
+            out.push(GroupedSecret {
+                profile: profile.map(str::to_string),
+                name,
+                path,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn insert_unique(
+    table: &mut Table,
+    key: toml_edit::Key,
+    item: Item,
+    group: Option<&str>,
+) -> Result<()> {
+    if table.contains_key(key.get()) {
+        let origin = match group {
+            Some(group) => format!(" (from group '{}')", group),
+            None => String::new(),
+        };
+        return Err(FnoxError::Config(format!(
+            "Secret '{}'{} is defined more than once; check your secret groups",
+            key.get(),
+            origin
+        )));
+    }
+    table.insert_formatted(&key, item);
+    Ok(())
+}
+
+/// Walk a group, collecting `(path, secret)` for every secret with the group's
+/// defaults applied.
+fn collect_members(
+    group: &dyn TableLike,
+    path: &mut Vec<String>,
+    inherited: &InlineTable,
+    out: &mut Vec<(Vec<String>, InlineTable)>,
+) -> Result<()> {
+    let group_name = path.join(".");
+    let mut defaults = inherited.clone();
+    for (key, item) in group.iter() {
+        if INHERITED_FIELDS.contains(&key) {
+            let Some(value) = item.as_value() else {
+                return Err(FnoxError::Config(format!(
+                    "Secret group '{}' has an invalid '{}' setting",
+                    group_name, key
+                )));
+            };
+            // A group's provider choice replaces the inherited one entirely
+            if key == "provider" {
+                defaults.remove("providers");
+            } else if key == "providers" {
+                defaults.remove("provider");
+            }
+            defaults.insert(key, value.clone().decorated("", ""));
+        } else if SECRET_FIELDS.contains(&key) {
+            return Err(FnoxError::Config(format!(
+                "Secret group '{}' can't set '{}'; only {} are inherited by its secrets",
+                group_name,
+                key,
+                INHERITED_FIELDS.join(", ")
+            )));
+        } else if !item.is_table_like() {
+            return Err(FnoxError::Config(format!(
+                "'{}' in secret group '{}' must be a secret or a group table",
+                key, group_name
+            )));
+        }
+    }
+
+    for (key, item) in group.iter() {
+        let Some(member) = item
+            .as_table_like()
+            .filter(|_| !SECRET_FIELDS.contains(&key))
+        else {
+            continue;
+        };
+        path.push(key.to_string());
+        if is_group(member) {
+            collect_members(member, path, &defaults, out)?;
+        } else {
+            out.push((path.clone(), with_defaults(member, &defaults)));
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+/// A secret's settings as an inline table, with group defaults filled in.
+fn with_defaults(secret: &dyn TableLike, defaults: &InlineTable) -> InlineTable {
+    let mut inline = InlineTable::new();
+    for (key, item) in secret.iter() {
+        let value = match item {
+            Item::Value(value) => value.clone(),
+            Item::Table(table) => Value::InlineTable(table.clone().into_inline_table()),
+            _ => continue,
+        };
+        inline.insert(key, value.decorated("", ""));
+    }
+    // A secret that picks its own source doesn't inherit the group's provider
+    let has_source = ["provider", "providers", "template"]
+        .iter()
+        .any(|key| inline.contains_key(key));
+    for (key, value) in defaults.iter() {
+        if has_source && (key == "provider" || key == "providers") {
+            continue;
+        }
+        if !inline.contains_key(key) {
+            inline.insert(key, value.clone());
+        }
+    }
+    inline.fmt();
+    inline
+}
+
+/// Byte spans of the original file, used to point `provider`/`value` of grouped
+/// secrets back at where they were written.
+#[derive(Debug, Default)]
+pub struct SourceSpans {
+    secrets: IndexMap<String, SpanNode>,
+    profiles: IndexMap<String, IndexMap<String, SpanNode>>,
+}
+
+impl SourceSpans {
+    /// Collect spans from the original file. Best-effort: returns empty spans if
+    /// the file can't be read that way.
+    pub fn parse(content: &str) -> Self {
+        #[derive(Deserialize)]
+        struct Probe {
+            #[serde(default)]
+            secrets: IndexMap<String, SpanNode>,
+            #[serde(default)]
+            profiles: IndexMap<String, ProfileProbe>,
+        }
+        #[derive(Deserialize)]
+        struct ProfileProbe {
+            #[serde(default)]
+            secrets: IndexMap<String, SpanNode>,
+        }
+
+        match toml_edit::de::from_str::<Probe>(content) {
+            Ok(probe) => Self {
+                secrets: probe.secrets,
+                profiles: probe
+                    .profiles
+                    .into_iter()
+                    .map(|(name, profile)| (name, profile.secrets))
+                    .collect(),
+            },
+            Err(e) => {
+                tracing::debug!("Could not collect spans for secret groups: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Span of `field` for a grouped secret: the secret's own setting, or else the
+    /// nearest group that set it.
+    pub fn field_span(&self, secret: &GroupedSecret, field: &str) -> Option<Range<usize>> {
+        let root = match &secret.profile {
+            Some(profile) => self.profiles.get(profile)?,
+            None => &self.secrets,
+        };
+        let mut nodes = Vec::with_capacity(secret.path.len());
+        let mut children = root;
+        for segment in &secret.path {
+            let node = children.get(segment)?;
+            nodes.push(node);
+            children = &node.children;
+        }
+        nodes
+            .iter()
+            .rev()
+            .find_map(|node| node.children.get(field).map(|field| field.span.clone()))
+    }
+}
+
+/// A value in the original file and, for tables, its entries.
+#[derive(Debug, Default)]
+struct SpanNode {
+    span: Range<usize>,
+    children: IndexMap<String, SpanNode>,
+}
+
+impl<'de> Deserialize<'de> for SpanNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct Entries(IndexMap<String, SpanNode>);
+
+        impl<'de> Deserialize<'de> for Entries {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                struct EntriesVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for EntriesVisitor {
+                    type Value = Entries;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("any TOML value")
+                    }
+
+                    fn visit_map<A: serde::de::MapAccess<'de>>(
+                        self,
+                        mut map: A,
+                    ) -> std::result::Result<Entries, A::Error> {
+                        let mut entries = IndexMap::new();
+                        while let Some(key) = map.next_key::<String>()? {
+                            entries.insert(key, map.next_value::<SpanNode>()?);
+                        }
+                        Ok(Entries(entries))
+                    }
+
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> std::result::Result<Entries, A::Error> {
+                        while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                        Ok(Entries(IndexMap::new()))
+                    }
+
+                    fn visit_bool<E>(self, _: bool) -> std::result::Result<Entries, E> {
+                        Ok(Entries(IndexMap::new()))
+                    }
+
+                    fn visit_i64<E>(self, _: i64) -> std::result::Result<Entries, E> {
+                        Ok(Entries(IndexMap::new()))
+                    }
+
+                    fn visit_u64<E>(self, _: u64) -> std::result::Result<Entries, E> {
+                        Ok(Entries(IndexMap::new()))
+                    }
+
+                    fn visit_f64<E>(self, _: f64) -> std::result::Result<Entries, E> {
+                        Ok(Entries(IndexMap::new()))
+                    }
+
+                    fn visit_str<E>(self, _: &str) -> std::result::Result<Entries, E> {
+                        Ok(Entries(IndexMap::new()))
+                    }
+                }
+
+                deserializer.deserialize_any(EntriesVisitor)
+            }
+        }
+
+        let spanned = serde_spanned::Spanned::<Entries>::deserialize(deserializer)?;
+        let span = spanned.span();
+        Ok(SpanNode {
+            span,
+            children: spanned.into_inner().0,
+        })
+    }
+}
+
+/// Where a secret lives in a `secrets` table: the groups to descend into, then its key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub groups: Vec<String>,
+    pub key: String,
+}
+
+impl Location {
+    fn flat(name: &str) -> Self {
+        Self {
+            groups: Vec::new(),
+            key: name.to_string(),
+        }
+    }
+
+    pub fn is_grouped(&self) -> bool {
+        !self.groups.is_empty()
+    }
+}
+
+/// Find an existing secret by its flattened name.
+pub fn locate(
+    secrets: &dyn TableLike,
+    name: &str,
+    options: &SecretGroupsConfig,
+) -> Option<Location> {
+    if let Some(item) = secrets.get(name)
+        && !item.as_table_like().is_some_and(is_group)
+    {
+        return Some(Location::flat(name));
+    }
+
+    fn search(
+        group: &dyn TableLike,
+        groups: &mut Vec<String>,
+        name: &str,
+        options: &SecretGroupsConfig,
+    ) -> Option<Location> {
+        for (key, item) in group.iter() {
+            let Some(member) = item
+                .as_table_like()
+                .filter(|_| !SECRET_FIELDS.contains(&key))
+            else {
+                continue;
+            };
+            groups.push(key.to_string());
+            let found = if is_group(member) {
+                search(member, groups, name, options)
+            } else if options.key_name(groups) == name {
+                Some(Location {
+                    groups: groups[..groups.len() - 1].to_vec(),
+                    key: key.to_string(),
+                })
+            } else {
+                None
+            };
+            groups.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    let mut groups = Vec::new();
+    for (key, item) in secrets.iter() {
+        if let Some(group) = item.as_table_like().filter(|t| is_group(*t)) {
+            groups.push(key.to_string());
+            if let Some(location) = search(group, &mut groups, name, options) {
+                return Some(location);
+            }
+            groups.pop();
+        }
+    }
+    None
+}
+
+/// Where a new secret should be written: inside the deepest existing group whose
+/// name prefixes `name` (e.g. `STRIPE_PUBLISHABLE_KEY` goes into `[secrets.stripe]`),
+/// or at the top level.
+pub fn placement(secrets: &dyn TableLike, name: &str, options: &SecretGroupsConfig) -> Location {
+    fn deepest(
+        group: &dyn TableLike,
+        groups: &mut Vec<String>,
+        name: &str,
+        options: &SecretGroupsConfig,
+        best: &mut Option<Location>,
+    ) {
+        let prefix = format!("{}{}", options.key_name(groups), options.separator());
+        let Some(key) = name
+            .strip_prefix(prefix.as_str())
+            .and_then(|rest| options.member_key(rest))
+        else {
+            return;
+        };
+        // The key must not be mistaken for a group setting or replace a nested group
+        let free = !SECRET_FIELDS.contains(&key.as_str())
+            && group
+                .get(&key)
+                .is_none_or(|item| !item.as_table_like().is_some_and(is_group));
+        if free && best.as_ref().is_none_or(|b| b.groups.len() < groups.len()) {
+            *best = Some(Location {
+                groups: groups.clone(),
+                key,
+            });
+        }
+        for (key, item) in group.iter() {
+            if let Some(member) = item
+                .as_table_like()
+                .filter(|t| !SECRET_FIELDS.contains(&key) && is_group(*t))
+            {
+                groups.push(key.to_string());
+                deepest(member, groups, name, options, best);
+                groups.pop();
+            }
+        }
+    }
+
+    let mut best = None;
+    for (key, item) in secrets.iter() {
+        if let Some(group) = item.as_table_like().filter(|t| is_group(*t)) {
+            let mut groups = vec![key.to_string()];
+            deepest(group, &mut groups, name, options, &mut best);
+        }
+    }
+    best.unwrap_or_else(|| Location::flat(name))
+}
+
+/// The table holding a located secret.
+pub fn parent_mut<'a>(
+    secrets: &'a mut dyn TableLike,
+    location: &Location,
+) -> Option<&'a mut dyn TableLike> {
+    let mut table = secrets;
+    for group in &location.groups {
+        table = table.get_mut(group)?.as_table_like_mut()?;
+    }
+    Some(table)
+}
+
+/// Remove groups along `groups` that no longer hold any secrets, so a group's
+/// leftover defaults aren't read back as a secret of its own.
+pub fn prune_empty_groups(secrets: &mut dyn TableLike, groups: &[String]) {
+    let Some((first, rest)) = groups.split_first() else {
+        return;
+    };
+    let Some(group) = secrets.get_mut(first).and_then(Item::as_table_like_mut) else {
+        return;
+    };
+    prune_empty_groups(group, rest);
+    if !is_group(group) {
+        secrets.remove(first);
+    }
+}
+
+/// Settings a secret at `location` inherits from its groups.
+pub fn inherited_defaults(secrets: &dyn TableLike, location: &Location) -> InlineTable {
+    let mut defaults = InlineTable::new();
+    let mut table = secrets;
+    for group in &location.groups {
+        let Some(next) = table.get(group).and_then(Item::as_table_like) else {
+            break;
+        };
+        table = next;
+        for (key, item) in table.iter() {
+            if let Some(value) = item.as_value().filter(|_| INHERITED_FIELDS.contains(&key)) {
+                if key == "provider" {
+                    defaults.remove("providers");
+                } else if key == "providers" {
+                    defaults.remove("provider");
+                }
+                defaults.insert(key, value.clone().decorated("", ""));
+            }
+        }
+    }
+    defaults
+}
+
+/// Drop settings from a written secret that it would inherit from its group anyway,
+/// and spell out booleans that differ from the group's default.
+pub fn strip_inherited(item: &mut Item, env: bool, as_file: bool, defaults: &InlineTable) {
+    let Some(secret) = item.as_table_like_mut() else {
+        return;
+    };
+    for (key, default) in defaults.iter() {
+        let default_repr = default.to_string();
+        match secret.get(key).and_then(Item::as_value) {
+            Some(value) if value.to_string().trim() == default_repr.trim() => {
+                secret.remove(key);
+            }
+            Some(_) => {}
+            None if key == "env" && env && default.as_bool() == Some(false) => {
+                secret.insert(key, Item::Value(Value::from(true)));
+            }
+            None if key == "as_file" && !as_file && default.as_bool() == Some(true) => {
+                secret.insert(key, Item::Value(Value::from(false)));
+            }
+            None => {}
+        }
+    }
+    secret.fmt();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flatten_str(content: &str) -> Flattened {
+        flatten(content).unwrap().expect("config has groups")
+    }
+
+    #[test]
+    fn test_ungrouped_config_is_untouched() {
+        let content = r#"
+[secrets]
+API_KEY = { provider = "age", value = "x" }
+
+[secrets.DB_URL]
+default = "postgres://localhost"
+sync = { provider = "age", value = "y" }
+"#;
+        assert!(flatten(content).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_group_flattens_to_prefixed_names() {
+        let flattened = flatten_str(
+            r#"
+[secrets]
+PLAIN = { default = "p" }
+
+[secrets.stripe]
+provider = "age"
+api_key = { value = "a" }
+webhook_secret = { value = "w", provider = "plain" }
+"#,
+        );
+        let doc: DocumentMut = flattened.content.parse().unwrap();
+        let secrets = doc["secrets"].as_table().unwrap();
+        let names: Vec<_> = secrets.iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            names,
+            vec!["PLAIN", "STRIPE_API_KEY", "STRIPE_WEBHOOK_SECRET"]
+        );
+        assert_eq!(secrets["STRIPE_API_KEY"]["provider"].as_str(), Some("age"));
+        assert_eq!(
+            secrets["STRIPE_WEBHOOK_SECRET"]["provider"].as_str(),
+            Some("plain")
+        );
+        assert_eq!(
+            flattened.secrets[0],
+            GroupedSecret {
+                profile: None,
+                name: "STRIPE_API_KEY".to_string(),
+                path: vec!["stripe".to_string(), "api_key".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_nested_groups_and_options() {
+        let flattened = flatten_str(
+            r#"
+secret_groups = { separator = "__", case = "preserve" }
+
+[profiles.prod.secrets.stripe]
+env = false
+
+[profiles.prod.secrets.stripe.live]
+key = { value = "k" }
+"#,
+        );
+        assert_eq!(flattened.secrets[0].name, "stripe__live__key");
+        assert_eq!(flattened.secrets[0].profile.as_deref(), Some("prod"));
+        let doc: DocumentMut = flattened.content.parse().unwrap();
+        let secret = &doc["profiles"]["prod"]["secrets"]["stripe__live__key"];
+        assert_eq!(secret["env"].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn test_own_source_overrides_group_provider() {
+        let flattened = flatten_str(
+            r#"
+[secrets.db]
+provider = "age"
+url = { template = "postgres://${DB_USER}@host/db" }
+user = { providers = ["vault", "age"], value = "user" }
+"#,
+        );
+        let doc: DocumentMut = flattened.content.parse().unwrap();
+        assert!(doc["secrets"]["DB_URL"].get("provider").is_none());
+        assert!(doc["secrets"]["DB_USER"].get("provider").is_none());
+    }
+
+    #[test]
+    fn test_group_rejects_secret_only_fields() {
+        let err =
+            flatten("[secrets.stripe]\nvalue = \"x\"\napi_key = { value = \"a\" }\n").unwrap_err();
+        assert!(err.to_string().contains("can't set 'value'"));
+    }
+
+    #[test]
+    fn test_duplicate_flattened_name_is_an_error() {
+        let err = flatten(
+            r#"
+[secrets]
+STRIPE_API_KEY = { value = "flat" }
+
+[secrets.stripe]
+api_key = { value = "grouped" }
+"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("defined more than once"));
+    }
+
+    #[test]
+    fn test_spans_point_at_original_file() {
+        let content = r#"[secrets.stripe]
+provider = "age"
+api_key = { value = "a" }
+"#;
+        let flattened = flatten_str(content);
+        let spans = SourceSpans::parse(content);
+        let secret = &flattened.secrets[0];
+
+        // The inherited provider points at the group's setting
+        let provider = spans.field_span(secret, "provider").unwrap();
+        assert_eq!(content[provider].trim_matches('"'), "age");
+        let value = spans.field_span(secret, "value").unwrap();
+        assert_eq!(content[value].trim_matches('"'), "a");
+    }
+
+    #[test]
+    fn test_locate_and_placement() {
+        let doc: DocumentMut = r#"
+[secrets]
+FLAT = { value = "f" }
+
+[secrets.stripe]
+api_key = { value = "a" }
+
+[secrets.stripe.live]
+key = { value = "k" }
+"#
+        .parse()
+        .unwrap();
+        let secrets = doc["secrets"].as_table().unwrap();
+        let options = SecretGroupsConfig::default();
+
+        assert_eq!(
+            locate(secrets, "FLAT", &options),
+            Some(Location::flat("FLAT"))
+        );
+        assert_eq!(
+            locate(secrets, "STRIPE_LIVE_KEY", &options),
+            Some(Location {
+                groups: vec!["stripe".to_string(), "live".to_string()],
+                key: "key".to_string(),
+            })
+        );
+        assert_eq!(locate(secrets, "STRIPE_OTHER", &options), None);
+
+        assert_eq!(
+            placement(secrets, "STRIPE_LIVE_WEBHOOK", &options),
+            Location {
+                groups: vec!["stripe".to_string(), "live".to_string()],
+                key: "webhook".to_string(),
+            }
+        );
+        assert_eq!(
+            placement(secrets, "STRIPE_PUBLISHABLE_KEY", &options),
+            Location {
+                groups: vec!["stripe".to_string()],
+                key: "publishable_key".to_string(),
+            }
+        );
+        // Names that can't round-trip through the group stay flat
+        assert_eq!(
+            placement(secrets, "STRIPE_mixed", &options),
+            Location::flat("STRIPE_mixed")
+        );
+        assert_eq!(
+            placement(secrets, "OTHER", &options),
+            Location::flat("OTHER")
+        );
+    }
+
+    #[test]
+    fn test_prune_empty_groups() {
+        let mut doc: DocumentMut = r#"
+[secrets.stripe]
+provider = "age"
+api_key = { value = "a" }
+
+[secrets.stripe.live]
+env = false
+"#
+        .parse()
+        .unwrap();
+        let secrets = doc["secrets"].as_table_mut().unwrap();
+
+        // `live` only has a default left, `stripe` still has a secret
+        prune_empty_groups(secrets, &["stripe".to_string(), "live".to_string()]);
+        assert!(secrets["stripe"].get("live").is_none());
+        assert!(secrets.contains_key("stripe"));
+
+        secrets["stripe"].as_table_mut().unwrap().remove("api_key");
+        prune_empty_groups(secrets, &["stripe".to_string()]);
+        assert!(!secrets.contains_key("stripe"));
+    }
+
+    #[test]
+    fn test_strip_inherited() {
+        let mut defaults = InlineTable::new();
+        defaults.insert("provider", Value::from("age"));
+        defaults.insert("env", Value::from(false));
+
+        let mut item = Item::Value(r#"{ provider = "age", value = "x" }"#.parse().unwrap());
+        strip_inherited(&mut item, true, false, &defaults);
+        let secret = item.as_inline_table().unwrap();
+        assert!(secret.get("provider").is_none());
+        assert_eq!(secret.get("env").and_then(Value::as_bool), Some(true));
+        assert_eq!(secret.get("value").and_then(Value::as_str), Some("x"));
+    }
+}
@@ -247,6 +247,11 @@ mod tests {
             if_missing: None,
             if_missing_default: None,
             http_timeout: "30s".to_string(),
+            http_connect_timeout: "10s".to_string(),
+            batch_concurrency: None,
+            redact_min_length: "3".to_string(),
+            get_tty_warning: true,
+            export_git_guard: true,
         };
 
         let mut env = SourceMap::new();
@@ -280,6 +285,11 @@ mod tests {
             if_missing: None,
             if_missing_default: None,
             http_timeout: "30s".to_string(),
+            http_connect_timeout: "10s".to_string(),
+            batch_concurrency: None,
+            redact_min_length: "3".to_string(),
+            get_tty_warning: true,
+            export_git_guard: true,
         };
 
         let mut env = SourceMap::new();
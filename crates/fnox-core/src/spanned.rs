@@ -34,7 +34,6 @@ pub struct SpannedValue<T> {
 
 impl<T> SpannedValue<T> {
     /// Create a new spanned value with a known span.
-    #[cfg(test)]
     pub fn new(value: T, span: Range<usize>) -> Self {
         Self {
             value,
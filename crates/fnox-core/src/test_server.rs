@@ -0,0 +1,149 @@
+//! Minimal HTTP/1.1 server for tests: the stand-in services of the HTTP-based
+//! provider tests, and the connection handling of the cassette server.
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request the server received
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of header `name`, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body parsed as JSON, or null when it isn't JSON (such as for a GET)
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+pub type Requests = Arc<Mutex<Vec<Request>>>;
+
+/// A running [`serve`] server
+pub struct TestServer {
+    pub url: String,
+    pub requests: Requests,
+    /// Connections accepted so far
+    pub connections: Arc<AtomicUsize>,
+}
+
+/// Answer each request with `handler(request, n)`, where `n` counts the
+/// requests before it, and record every request and connection.
+pub async fn serve<F>(handler: F) -> TestServer
+where
+    F: Fn(&Request, usize) -> (u16, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Requests::default();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let handler = Arc::new(handler);
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let (seen, opened) = (requests.clone(), connections.clone());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            opened.fetch_add(1, Ordering::SeqCst);
+            let (seen, handler, count) = (seen.clone(), handler.clone(), count.clone());
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                while let Some(request) = read_request(&mut socket, &mut buf).await {
+                    let (status, body) = handler(&request, count.fetch_add(1, Ordering::SeqCst));
+                    let head_only = request.method == "HEAD";
+                    seen.lock().unwrap().push(request);
+                    let body = if head_only { "" } else { body.as_str() };
+                    if write_response(&mut socket, status, body).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    TestServer {
+        url: format!("http://{}", addr),
+        requests,
+        connections,
+    }
+}
+
+/// A local URL with nothing listening on it
+pub async fn unreachable_url() -> String {
+    // Bind then drop to get a free port
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
+
+/// Read the next request on a connection, or `None` once it closes
+pub async fn read_request(socket: &mut TcpStream, buf: &mut Vec<u8>) -> Option<Request> {
+    let mut chunk = [0u8; 4096];
+    loop {
+        // Wait for the headers and the full body
+        if let Some(head_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+            let mut lines = head.lines();
+            let mut request_line = lines.next()?.split_whitespace();
+            let method = request_line.next()?.to_string();
+            let path = request_line.next()?.to_string();
+            let headers: Vec<(String, String)> = lines
+                .filter_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    Some((name.trim().to_string(), value.trim().to_string()))
+                })
+                .collect();
+            let length = headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.parse::<usize>().ok())
+                .unwrap_or(0);
+
+            if buf.len() >= head_end + 4 + length {
+                let body = buf[head_end + 4..head_end + 4 + length].to_vec();
+                buf.drain(..head_end + 4 + length);
+                return Some(Request {
+                    method,
+                    path,
+                    headers,
+                    body,
+                });
+            }
+        }
+
+        match socket.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Send a JSON response with `status` and `body`
+pub async fn write_response(socket: &mut TcpStream, status: u16, body: &str) -> io::Result<()> {
+    let reason = reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Unknown");
+    let head = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    socket.write_all(head.as_bytes()).await?;
+    socket.write_all(body.as_bytes()).await
+}
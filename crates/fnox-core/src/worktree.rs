@@ -0,0 +1,103 @@
+//! Git worktree detection, for `[settings] follow_worktrees`.
+//!
+//! A linked worktree (`git worktree add`) has a `.git` file instead of a
+//! directory. The file points at the worktree's git dir, whose `commondir`
+//! file points back at the main repository's `.git`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A linked git worktree and the main worktree it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedWorktree {
+    /// Top-level directory of the linked worktree
+    pub root: PathBuf,
+    /// Top-level directory of the main worktree
+    pub main_root: PathBuf,
+}
+
+/// The linked worktree containing `dir`, or `None` if `dir` is in a main
+/// worktree, a bare repository's worktree, a submodule, or no repository
+pub fn linked_worktree(dir: &Path) -> Option<LinkedWorktree> {
+    let root = dir.ancestors().find(|d| d.join(".git").exists())?;
+    let git_dir = git_dir_from_file(&root.join(".git"))?;
+
+    // Submodules also use a `.git` file, but their git dir has no `commondir`
+    let common_dir = fs::read_to_string(git_dir.join("commondir")).ok()?;
+    let common_dir = fs::canonicalize(git_dir.join(common_dir.trim())).ok()?;
+
+    // A bare repository has no main worktree
+    if common_dir.file_name()? != ".git" {
+        return None;
+    }
+    Some(LinkedWorktree {
+        root: root.to_path_buf(),
+        main_root: common_dir.parent()?.to_path_buf(),
+    })
+}
+
+/// The git dir a `.git` file points at (`gitdir: <path>`)
+fn git_dir_from_file(dot_git: &Path) -> Option<PathBuf> {
+    if !dot_git.is_file() {
+        return None;
+    }
+    let content = fs::read_to_string(dot_git).ok()?;
+    let git_dir = content.trim().strip_prefix("gitdir:")?.trim();
+    // Relative paths are relative to the worktree root
+    Some(dot_git.parent()?.join(git_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lay out a main worktree at `main` with a linked worktree at `linked`,
+    /// the way `git worktree add` does
+    fn fake_worktrees(main: &Path, linked: &Path) {
+        let git_dir = main.join(".git/worktrees/linked");
+        fs::create_dir_all(&git_dir).unwrap();
+        fs::write(git_dir.join("commondir"), "../..\n").unwrap();
+        fs::create_dir_all(linked.join("src")).unwrap();
+        fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", git_dir.display()),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn finds_the_main_worktree_from_a_linked_one() {
+        let temp = tempfile::tempdir().unwrap();
+        let temp = fs::canonicalize(temp.path()).unwrap();
+        let main = temp.join("main");
+        let linked = temp.join("linked");
+        fake_worktrees(&main, &linked);
+
+        assert_eq!(
+            linked_worktree(&linked.join("src")),
+            Some(LinkedWorktree {
+                root: linked.clone(),
+                main_root: main.clone(),
+            })
+        );
+        assert_eq!(linked_worktree(&main), None);
+    }
+
+    #[test]
+    fn ignores_submodules() {
+        let temp = tempfile::tempdir().unwrap();
+        let git_dir = temp.path().join("repo/.git/modules/sub");
+        fs::create_dir_all(&git_dir).unwrap();
+        let sub = temp.path().join("repo/sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join(".git"), "gitdir: ../.git/modules/sub\n").unwrap();
+
+        assert_eq!(linked_worktree(&sub), None);
+    }
+
+    #[test]
+    fn outside_a_repository() {
+        let temp = tempfile::tempdir().unwrap();
+        assert_eq!(linked_worktree(temp.path()), None);
+    }
+}
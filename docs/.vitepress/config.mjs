@@ -115,6 +115,8 @@ export default defineConfig({
               { text: "AWS KMS", link: "/providers/aws-kms" },
               { text: "Azure Key Vault Keys", link: "/providers/azure-kms" },
               { text: "Google Cloud KMS", link: "/providers/gcp-kms" },
+              { text: "Local (OS keychain key)", link: "/providers/local" },
+              { text: "systemd-creds", link: "/providers/systemd-creds" },
             ],
           },
           {
@@ -125,8 +127,10 @@ export default defineConfig({
               { text: "AWS Secrets Manager", link: "/providers/aws-sm" },
               { text: "Azure Key Vault Secrets", link: "/providers/azure-sm" },
               { text: "Doppler", link: "/providers/doppler" },
+              { text: "etcd", link: "/providers/etcd" },
               { text: "FOKS", link: "/providers/foks" },
               { text: "GCP Secret Manager", link: "/providers/gcp-sm" },
+              { text: "Kubernetes", link: "/providers/kubernetes" },
               {
                 text: "Bitwarden Secrets Manager",
                 link: "/providers/bitwarden-sm",
@@ -140,7 +144,10 @@ export default defineConfig({
             items: [
               { text: "1Password", link: "/providers/1password" },
               { text: "Bitwarden", link: "/providers/bitwarden" },
+              { text: "Exec", link: "/providers/exec" },
+              { text: "HTTP", link: "/providers/http" },
               { text: "Infisical", link: "/providers/infisical" },
+              { text: "Passwordstate", link: "/providers/passwordstate" },
             ],
           },
           {
@@ -149,6 +156,7 @@ export default defineConfig({
             items: [
               { text: "OS Keychain", link: "/providers/keychain" },
               { text: "KeePass", link: "/providers/keepass" },
+              { text: "Env file", link: "/providers/envfile" },
               { text: "password-store", link: "/providers/password-store" },
               { text: "Plain Text", link: "/providers/plain" },
             ],
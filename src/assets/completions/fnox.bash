@@ -0,0 +1,6 @@
+# fnox completions for bash, generated by `fnox completion bash`
+_fnox() {
+    local IFS=$'\n'
+    COMPREPLY=($(fnox __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _fnox fnox
@@ -0,0 +1,2 @@
+# fnox completions for fish, generated by `fnox completion fish`
+complete -c fnox -f -a '(fnox __complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'
@@ -1,6 +1,12 @@
-use crate::shell::{self, ActivateOptions};
-use anyhow::Result;
+use crate::shell::{self, ActivateOptions, Shell};
+use anyhow::{Context, Result};
 use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Marks the lines `--install` added, so re-running replaces them and
+/// `--uninstall` removes exactly them
+const BEGIN_MARKER: &str = "# >>> fnox activate >>>";
+const END_MARKER: &str = "# <<< fnox activate <<<";
 
 #[derive(Debug, Parser)]
 #[command(about = "Output shell activation code to enable automatic secret loading")]
@@ -9,9 +15,21 @@ pub struct ActivateCommand {
     #[arg(value_name = "SHELL")]
     pub shell: Option<String>,
 
+    /// Add the activation line to the shell's startup file
+    #[arg(long, conflicts_with_all = ["uninstall", "print_rc_line", "no_hook_env"])]
+    pub install: bool,
+
     /// Don't automatically invoke hook-env (for testing)
     #[arg(long)]
     pub no_hook_env: bool,
+
+    /// Print the line to add to the shell's startup file
+    #[arg(long, conflicts_with = "no_hook_env")]
+    pub print_rc_line: bool,
+
+    /// Remove the activation line added by --install
+    #[arg(long, conflicts_with_all = ["print_rc_line", "no_hook_env"])]
+    pub uninstall: bool,
 }
 
 impl ActivateCommand {
@@ -27,6 +45,14 @@ impl ActivateCommand {
 
         let shell = shell::get_shell(Some(&shell_name))?;
 
+        if self.print_rc_line {
+            println!("{}", shell.rc_line());
+            return Ok(());
+        }
+        if self.install || self.uninstall {
+            return self.update_rc_file(shell.as_ref());
+        }
+
         // Get the current executable path
         let exe = std::env::current_exe()
             .or_else(|_| which::which("fnox"))
@@ -42,4 +68,192 @@ impl ActivateCommand {
 
         Ok(())
     }
+
+    /// Add or remove the marked activation block in the shell's startup
+    /// file, backing the file up first
+    fn update_rc_file(&self, shell: &dyn Shell) -> Result<()> {
+        let line = shell.rc_line();
+        let path = shell.rc_file().ok_or_else(|| {
+            anyhow::anyhow!(
+                "fnox can't tell where {shell}'s startup file is; add this to it by hand:\n\n{line}"
+            )
+        })?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+
+        let updated = if self.install {
+            add_rc_block(&content, &line)
+        } else {
+            remove_rc_block(&content)
+        };
+        let Some(updated) = updated else {
+            if self.install {
+                println!("fnox is already activated in {}", path.display());
+            } else if content.contains(&line) {
+                println!(
+                    "{} activates fnox without fnox's markers; remove this line by hand:\n\n{line}",
+                    path.display()
+                );
+            } else {
+                println!("fnox isn't activated in {}", path.display());
+            }
+            return Ok(());
+        };
+
+        let backup = if path.exists() {
+            let action = if self.install { "install" } else { "uninstall" };
+            let backup = backup_path(&path, action);
+            std::fs::copy(&path, &backup)
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        // fish's conf.d file is fnox's own, so it goes away with the block
+        if self.uninstall && updated.trim().is_empty() && is_own_file(&path) {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&path, updated)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let check = console::style("✓").green();
+        if self.install {
+            println!("{check} Added fnox activation to {}", path.display());
+        } else {
+            println!("{check} Removed fnox activation from {}", path.display());
+        }
+        if let Some(backup) = backup {
+            println!("  Backup: {}", backup.display());
+        }
+        if self.install {
+            println!("  Open a new {shell} session to start using it");
+        }
+        Ok(())
+    }
+}
+
+/// `content` with the marked block holding `line` appended, replacing a
+/// block from an earlier install, or None when it already activates fnox
+/// with `line`. A commented-out `line` doesn't count.
+fn add_rc_block(content: &str, line: &str) -> Option<String> {
+    let block = format!("{BEGIN_MARKER}\n{line}\n{END_MARKER}\n");
+    let existing = remove_rc_block(content);
+    let has_line = content.lines().any(|l| l.trim() == line);
+    if content.contains(&block) || (existing.is_none() && has_line) {
+        return None;
+    }
+
+    let mut updated = existing.unwrap_or_else(|| content.to_string());
+    if !updated.is_empty() {
+        if !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push('\n');
+    }
+    updated.push_str(&block);
+    Some(updated)
+}
+
+/// `content` without the marked block and the blank line before it, or None
+/// when there's no block
+fn remove_rc_block(content: &str) -> Option<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let begin = lines.iter().position(|l| l.trim_end() == BEGIN_MARKER)?;
+    let end = begin
+        + lines[begin..]
+            .iter()
+            .position(|l| l.trim_end() == END_MARKER)?;
+    let start = if begin > 0 && lines[begin - 1].trim().is_empty() {
+        begin - 1
+    } else {
+        begin
+    };
+    Some([&lines[..start], &lines[end + 1..]].concat().concat())
+}
+
+/// Where the copy of `path` is kept before `action` changes it, e.g.
+/// `.bashrc.fnox-install-backup`. Each action has its own, so uninstalling
+/// doesn't replace the copy from before the install.
+fn backup_path(path: &Path, action: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".fnox-{action}-backup"));
+    path.with_file_name(name)
+}
+
+fn is_own_file(path: &Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem == "fnox")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = r#"eval "$(fnox activate bash)""#;
+
+    #[test]
+    fn add_appends_a_marked_block_once() {
+        let installed = add_rc_block("export PATH=~/bin:$PATH\n", LINE).unwrap();
+        assert_eq!(
+            installed,
+            format!("export PATH=~/bin:$PATH\n\n{BEGIN_MARKER}\n{LINE}\n{END_MARKER}\n")
+        );
+        assert_eq!(add_rc_block(&installed, LINE), None);
+
+        assert_eq!(
+            add_rc_block("", LINE).unwrap(),
+            format!("{BEGIN_MARKER}\n{LINE}\n{END_MARKER}\n")
+        );
+        // A line added by hand counts as installed, unless it's commented out
+        assert_eq!(add_rc_block(&format!("{LINE}\n"), LINE), None);
+        assert_eq!(
+            add_rc_block(&format!("# {LINE}\n"), LINE).unwrap(),
+            format!("# {LINE}\n\n{BEGIN_MARKER}\n{LINE}\n{END_MARKER}\n")
+        );
+    }
+
+    #[test]
+    fn add_replaces_an_outdated_block() {
+        let old = format!("a\n\n{BEGIN_MARKER}\neval \"$(fnox activate)\"\n{END_MARKER}\nb\n");
+        assert_eq!(
+            add_rc_block(&old, LINE).unwrap(),
+            format!("a\nb\n\n{BEGIN_MARKER}\n{LINE}\n{END_MARKER}\n")
+        );
+    }
+
+    #[test]
+    fn remove_restores_the_original_content() {
+        let original = "export PATH=~/bin:$PATH";
+        let installed = add_rc_block(original, LINE).unwrap();
+        assert_eq!(
+            remove_rc_block(&installed).unwrap(),
+            format!("{original}\n")
+        );
+        assert_eq!(remove_rc_block(original), None);
+        // An unterminated block is left alone
+        assert_eq!(remove_rc_block(&format!("{BEGIN_MARKER}\n{LINE}\n")), None);
+    }
+
+    #[test]
+    fn backups_sit_next_to_the_file() {
+        assert_eq!(
+            backup_path(Path::new("/home/me/.bashrc"), "install"),
+            Path::new("/home/me/.bashrc.fnox-install-backup")
+        );
+        assert_eq!(
+            backup_path(Path::new("/home/me/.bashrc"), "uninstall"),
+            Path::new("/home/me/.bashrc.fnox-uninstall-backup")
+        );
+    }
 }
@@ -0,0 +1,48 @@
+use crate::commands::Cli;
+use crate::daemon;
+use crate::error::Result;
+use crate::table::Table;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Args)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    command: CacheSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheSubcommand {
+    /// Show the cached values being served stale because their provider failed
+    Status,
+}
+
+impl CacheCommand {
+    pub async fn run(&self, cli: &Cli) -> Result<()> {
+        match self.command {
+            CacheSubcommand::Status => {
+                let Some(stale) = daemon::stale_values(cli).await? else {
+                    println!("fnox daemon not running, so no values are cached");
+                    return Ok(());
+                };
+                if stale.is_empty() {
+                    println!("No stale values are being served");
+                    return Ok(());
+                }
+
+                let mut table =
+                    Table::new(["Key", "Profile", "Project", "Age", "Error"]).max_width(4, 60);
+                for value in stale {
+                    table.row([
+                        value.key,
+                        value.profile,
+                        value.project.display().to_string(),
+                        daemon::format_age(value.age_secs),
+                        value.error,
+                    ]);
+                }
+                table.print();
+                Ok(())
+            }
+        }
+    }
+}
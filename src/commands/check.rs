@@ -1,7 +1,14 @@
-use crate::config::Config;
-use crate::error::Result;
+use crate::config::{Config, ProviderConfig, SecretConfig};
+use crate::error::{FnoxError, Result};
+use crate::naming::NamingPolicy;
+use crate::providers::age::{self, RecipientCoverage};
+use crate::sanitize::escape_controls;
 use crate::secret_resolver;
+use crate::table::Table;
+use crate::watch::{ConfigWatcher, watched_files};
 use clap::Args;
+use indexmap::IndexSet;
+use std::path::{Path, PathBuf};
 
 use crate::commands::Cli;
 
@@ -11,25 +18,207 @@ pub struct CheckCommand {
     /// Check all secrets including those with if_missing=warn or if_missing=ignore
     #[arg(short = 'a', long)]
     all: bool,
+
+    /// Re-check whenever a loaded config file or import changes, until Ctrl+C
+    #[arg(short = 'w', long)]
+    watch: bool,
+
+    /// Only validate the config, without resolving secrets from providers
+    #[arg(long)]
+    config_only: bool,
+
+    /// Fail unless this secret is defined and resolves to a non-empty value (repeatable)
+    #[arg(long, value_name = "KEY")]
+    require: Vec<String>,
+
+    /// Require every secret listed in this file, one key per line
+    #[arg(long, value_name = "FILE")]
+    require_file: Option<PathBuf>,
+
+    /// Also fail when a secret's value is too large to pass through an environment variable
+    #[arg(long)]
+    strict: bool,
+}
+
+/// Largest value the OS accepts in a single environment variable: Linux
+/// limits each `NAME=value` string to 128 KiB, Windows to 32767 characters
+#[cfg(not(windows))]
+const MAX_ENV_VALUE: usize = 128 * 1024;
+#[cfg(windows)]
+const MAX_ENV_VALUE: usize = 32767;
+
+/// What a check found
+#[derive(Debug, Default)]
+struct Report {
+    issues: Vec<String>,
+    warnings: Vec<String>,
+    secrets: usize,
+    providers: usize,
 }
 
 impl CheckCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
+        if self.watch {
+            return self.watch(cli, config).await;
+        }
+
         config.validate()?;
         let profile = Config::get_profile(cli.profile.as_deref());
 
         // Load config
         println!("Checking configuration for profile: {}", profile);
 
-        let mut issues = Vec::new();
-        let mut warnings = Vec::new();
+        let Report {
+            issues,
+            warnings,
+            secrets,
+            providers,
+        } = self.check(cli, &config, &profile).await?;
+
+        // Report results
+        if !issues.is_empty() {
+            eprintln!("Found {} error(s):", issues.len());
+            for issue in &issues {
+                eprintln!("  {}", escape_controls(issue));
+            }
+        }
+
+        if !warnings.is_empty() {
+            eprintln!("Found {} warning(s):", warnings.len());
+            for warning in &warnings {
+                eprintln!("  {}", escape_controls(warning));
+            }
+        }
+
+        let mut summary = Table::headerless();
+        for (label, count) in [
+            ("Secrets", secrets),
+            ("Providers", providers),
+            ("Errors", issues.len()),
+            ("Warnings", warnings.len()),
+        ] {
+            summary.row([label.to_string(), count.to_string()]);
+        }
+        summary.print();
+
+        if issues.is_empty() && warnings.is_empty() {
+            println!("✓ Configuration is healthy");
+        } else if issues.is_empty() {
+            println!("✓ Configuration is OK (with warnings)");
+        }
+
+        if !issues.is_empty() {
+            std::process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    /// Re-run the check on every change to the loaded config files, printing
+    /// one line per run and details only when something is wrong. Runs in
+    /// this process share the HTTP connection pool, and the daemon's provider
+    /// cache when it's running.
+    async fn watch(&self, cli: &Cli, config: Config) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        let (mut watcher, mut changes) = ConfigWatcher::new(watched_files(&cli.config, &config))?;
+        eprintln!(
+            "Watching configuration for profile {}, press Ctrl+C to stop",
+            console::style(&profile).magenta()
+        );
+
+        let mut loaded: Result<Config> = Ok(config);
+        loop {
+            let report = match &loaded {
+                Ok(config) => match config.validate() {
+                    Ok(()) => self.check(cli, config, &profile).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(FnoxError::Config(e.to_string())),
+            };
+            Self::print_summary(report);
+
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+                changed = changes.recv() => {
+                    if changed.is_none() {
+                        return Ok(());
+                    }
+                }
+            }
+
+            loaded = Config::load_smart(&cli.config);
+            if let Ok(config) = &loaded {
+                // Imports may have been added or removed
+                watcher.set_files(watched_files(&cli.config, config))?;
+            }
+        }
+    }
+
+    /// Print a timestamped pass/fail line for a `--watch` run
+    fn print_summary(report: Result<Report>) {
+        let time = console::style(chrono::Local::now().format("%H:%M:%S")).dim();
+        let report = match report {
+            Ok(report) => report,
+            Err(e) => {
+                println!("{} {} {}", time, console::style("✗").red(), e);
+                return;
+            }
+        };
+
+        let counts = format!(
+            "{} secret(s), {} provider(s)",
+            report.secrets, report.providers
+        );
+        if !report.issues.is_empty() {
+            println!(
+                "{} {} {} error(s), {} warning(s) ({})",
+                time,
+                console::style("✗").red(),
+                report.issues.len(),
+                report.warnings.len(),
+                counts
+            );
+        } else if !report.warnings.is_empty() {
+            println!(
+                "{} {} OK with {} warning(s) ({})",
+                time,
+                console::style("✓").green(),
+                report.warnings.len(),
+                counts
+            );
+        } else {
+            println!("{} {} OK ({})", time, console::style("✓").green(), counts);
+        }
+        for issue in &report.issues {
+            println!(
+                "  {} {}",
+                console::style("error:").red(),
+                escape_controls(issue)
+            );
+        }
+        for warning in &report.warnings {
+            println!(
+                "  {} {}",
+                console::style("warning:").yellow(),
+                escape_controls(warning)
+            );
+        }
+    }
+
+    /// Check the secrets and providers of `profile`, resolving secrets from
+    /// their providers unless `--config-only` is set
+    async fn check(&self, cli: &Cli, config: &Config, profile: &str) -> Result<Report> {
+        let mut report = Report::default();
+        let issues = &mut report.issues;
+        let warnings = &mut report.warnings;
+        let required = self.required_keys()?;
 
         // Check secrets
-        if let Ok(secrets) = config.get_secrets(&profile) {
+        if let Ok(secrets) = config.get_secrets(profile) {
             if secrets.is_empty() {
                 warnings.push("No secrets defined in profile".to_string());
             } else {
-                println!("Found {} secret(s) in profile", secrets.len());
+                report.secrets = secrets.len();
 
                 for (name, secret_config) in secrets {
                     // Check if secret has a value source
@@ -52,7 +241,7 @@ impl CheckCommand {
 
                     // Check provider configuration
                     if let Some(provider) = secret_config.provider() {
-                        let providers = config.get_providers(&profile);
+                        let providers = config.get_providers(profile);
                         if !providers.contains_key(provider) {
                             warnings.push(format!(
                                 "Secret '{}' references unknown provider '{}'",
@@ -62,9 +251,16 @@ impl CheckCommand {
                             // Determine if we should check this secret
                             let if_missing = secret_resolver::resolve_if_missing_behavior(
                                 &secret_config,
-                                &config,
+                                config,
+                                profile,
                             );
 
+                            // --config-only never contacts providers, and
+                            // required secrets are resolved by check_required
+                            if self.config_only || required.contains(&name) {
+                                continue;
+                            }
+
                             // Skip checking if not --all and if_missing is not Error
                             if !self.all
                                 && matches!(
@@ -79,16 +275,16 @@ impl CheckCommand {
                             // Try to actually resolve the secret from the provider
                             match crate::daemon::resolve_one(
                                 cli,
-                                &config,
-                                &profile,
+                                config,
+                                profile,
                                 &name,
                                 &secret_config,
                                 crate::daemon::Purpose::Check,
                             )
                             .await
                             {
-                                Ok(Some(_)) => {
-                                    // Secret resolved successfully
+                                Ok(Some(value)) => {
+                                    issues.extend(self.oversized(&name, &secret_config, &value));
                                 }
                                 Ok(None) => {
                                     // No value found, but that might be OK depending on if_missing
@@ -139,39 +335,207 @@ impl CheckCommand {
             issues.push(format!("Profile '{}' not found", profile));
         }
 
+        self.check_required(cli, config, profile, &required, issues)
+            .await;
+        Self::check_naming(config, profile, issues)?;
+        Self::check_age_recipients(config, profile, warnings);
+
         // Check providers
-        let providers = config.get_providers(&profile);
+        let providers = config.get_providers(profile);
         if providers.is_empty() {
             warnings.push("No providers configured".to_string());
         } else {
-            println!("Found {} provider(s) in profile", providers.len());
+            report.providers = providers.len();
         }
 
-        // Report results
-        if !issues.is_empty() {
-            eprintln!("Found {} error(s):", issues.len());
-            for issue in &issues {
-                eprintln!("  {}", issue);
-            }
+        Ok(report)
+    }
+
+    /// Keys from `--require` and `--require-file`, in the order given. Blank
+    /// lines and `#` comments in the file are skipped.
+    fn required_keys(&self) -> Result<IndexSet<String>> {
+        let mut keys: IndexSet<String> = self.require.iter().cloned().collect();
+        if let Some(path) = &self.require_file {
+            let content = std::fs::read_to_string(path).map_err(|source| {
+                FnoxError::RequireFileReadFailed {
+                    path: path.clone(),
+                    source,
+                }
+            })?;
+            keys.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
         }
+        Ok(keys)
+    }
 
-        if !warnings.is_empty() {
-            eprintln!("Found {} warning(s):", warnings.len());
-            for warning in &warnings {
-                eprintln!("  {}", warning);
+    /// Report each required secret that the profile doesn't define, or that
+    /// is defined but resolves to nothing or to an empty string. With
+    /// `--config-only` a defined secret only needs a value source.
+    async fn check_required(
+        &self,
+        cli: &Cli,
+        config: &Config,
+        profile: &str,
+        required: &IndexSet<String>,
+        issues: &mut Vec<String>,
+    ) {
+        if required.is_empty() {
+            return;
+        }
+        let secrets = config.get_secrets(profile).unwrap_or_default();
+        for key in required {
+            let Some(secret_config) = secrets.get(key) else {
+                issues.push(format!(
+                    "Required secret '{}' is not defined in profile '{}'",
+                    key, profile
+                ));
+                continue;
+            };
+            if self.config_only {
+                if !secret_config.has_value() {
+                    issues.push(format!(
+                        "Required secret '{}' is defined but has no value source",
+                        key
+                    ));
+                }
+                continue;
+            }
+            match crate::daemon::resolve_one(
+                cli,
+                config,
+                profile,
+                key,
+                secret_config,
+                crate::daemon::Purpose::Check,
+            )
+            .await
+            {
+                Ok(Some(value)) if !value.is_empty() => {
+                    issues.extend(self.oversized(key, secret_config, &value));
+                }
+                Ok(Some(_)) => issues.push(format!(
+                    "Required secret '{}' is defined but resolves to an empty value",
+                    key
+                )),
+                Ok(None) => issues.push(format!(
+                    "Required secret '{}' is defined but resolves to no value",
+                    key
+                )),
+                Err(err) => issues.push(format!(
+                    "Required secret '{}' failed to resolve: {}",
+                    key, err
+                )),
             }
         }
+    }
 
-        if issues.is_empty() && warnings.is_empty() {
-            println!("✓ Configuration is healthy");
-        } else if issues.is_empty() {
-            println!("✓ Configuration is OK (with warnings)");
+    /// With `--strict`, an issue for a value that `exec` couldn't pass as an
+    /// environment variable. `as_file` secrets are passed as a path instead.
+    fn oversized(&self, key: &str, secret_config: &SecretConfig, value: &str) -> Option<String> {
+        let size = key.len() + 1 + value.len();
+        if !self.strict || secret_config.as_file || size <= MAX_ENV_VALUE {
+            return None;
         }
+        Some(format!(
+            "Secret '{}' is too large for an environment variable ({} bytes with its name, limit {}); set as_file = true to pass it as a file",
+            key, size, MAX_ENV_VALUE
+        ))
+    }
 
-        if !issues.is_empty() {
-            std::process::exit(1);
+    /// Flag secret keys that break the `[settings.naming]` policy
+    fn check_naming(config: &Config, profile: &str, issues: &mut Vec<String>) -> Result<()> {
+        let Some(policy) = NamingPolicy::for_profile(config, profile)? else {
+            return Ok(());
+        };
+        let Ok(secrets) = config.get_secrets(profile) else {
+            return Ok(());
+        };
+        for key in secrets.keys() {
+            if let Some(violation) = policy.violation(key) {
+                issues.push(format!(
+                    "Secret '{}' breaks the naming policy: {} (e.g. '{}')",
+                    key,
+                    violation,
+                    policy.fix(key)
+                ));
+            }
         }
-
         Ok(())
     }
+
+    /// Warn about age ciphertexts that no longer match the provider's recipients,
+    /// e.g. after a recipient was removed. Only SSH recipients can be matched
+    /// exactly; X25519 stanzas are anonymous, so for those we can only compare
+    /// how many recipients a ciphertext was encrypted to.
+    fn check_age_recipients(config: &Config, profile: &str, warnings: &mut Vec<String>) {
+        let Ok(secrets) = config.get_secrets(profile) else {
+            return;
+        };
+        let providers = config.get_providers(profile);
+        let default_provider = config.get_default_provider(profile).ok().flatten();
+
+        for (name, secret_config) in &secrets {
+            let Some(provider_name) = secret_config
+                .provider()
+                .map(str::to_string)
+                .or_else(|| default_provider.clone())
+            else {
+                continue;
+            };
+            // Passphrase providers have no recipients to check against
+            let Some(ProviderConfig::AgeEncryption {
+                recipients,
+                passphrase: None | Some(false),
+                ..
+            }) = providers.get(&provider_name)
+            else {
+                continue;
+            };
+            let Some(value) = secret_config.value() else {
+                continue;
+            };
+            let config_dir = config
+                .provider_source(profile, &provider_name)
+                .and_then(Path::parent);
+            let recipients = match age::expand_recipients(recipients, config_dir) {
+                Ok(recipients) => recipients,
+                Err(err) => {
+                    warnings.push(format!("Provider '{}': {}", provider_name, err));
+                    continue;
+                }
+            };
+            let stanzas = match age::ciphertext_stanzas(value) {
+                Ok(stanzas) => stanzas,
+                Err(err) => {
+                    warnings.push(format!("Secret '{}': {}", name, err));
+                    continue;
+                }
+            };
+            let remediation = format!("run 'fnox reencrypt -p {}'", provider_name);
+            match age::recipient_coverage(&stanzas, &recipients) {
+                RecipientCoverage::Current => {}
+                RecipientCoverage::Stale => warnings.push(format!(
+                    "Secret '{}' is not encrypted to any recipient of provider '{}'; {} with a key that can still decrypt it",
+                    name, provider_name, remediation
+                )),
+                RecipientCoverage::Unknown => {
+                    if stanzas.len() != recipients.len() {
+                        warnings.push(format!(
+                            "Secret '{}' is encrypted to {} recipient(s) but provider '{}' has {} (recipient match unknown); {} if the recipients changed",
+                            name,
+                            stanzas.len(),
+                            provider_name,
+                            recipients.len(),
+                            remediation
+                        ));
+                    }
+                }
+            }
+        }
+    }
 }
@@ -1,74 +1,78 @@
+use crate::env;
 use crate::error::{FnoxError, Result};
 use crate::secret_resolver::{handle_provider_error, resolve_if_missing_behavior};
 use crate::{commands::Cli, config::Config};
-use clap::Args;
+use clap::{Args, ValueEnum};
 
-type MaskFn = Box<dyn Fn(&str, &str)>;
+/// CI platforms `ci-redact` knows how to mask secrets for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CiPlatform {
+    /// GitHub Actions `::add-mask::` workflow commands
+    Github,
+    /// GitLab CI/CD: guidance only. GitLab can't mask values at runtime, so
+    /// this lists the secrets to add as masked CI/CD variables.
+    Gitlab,
+}
+
+impl CiPlatform {
+    /// The platform the job runs on, from the variables each one sets
+    fn detect(env: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let set = |name: &str| env(name).is_some_and(|v| v == "true");
+        if set("GITHUB_ACTIONS") {
+            Some(Self::Github)
+        } else if set("GITLAB_CI") {
+            Some(Self::Gitlab)
+        } else {
+            None
+        }
+    }
+
+    /// The line that masks `value` in this platform's job log (for GitLab,
+    /// advice on masking it), or why it can't be masked
+    fn mask_line(self, key: &str, value: &str) -> std::result::Result<String, String> {
+        match self {
+            // GitHub Actions doesn't properly handle multiline secrets
+            Self::Github if value.contains('\n') => Err(format!(
+                "Secret '{}' contains newlines and cannot be fully redacted in CI logs. \
+                Consider using a secret manager or storing multiline secrets outside fnox config.",
+                key
+            )),
+            Self::Github => Ok(format!("::add-mask::{}", value)),
+            // GitLab has no runtime masking command; it masks the values of
+            // variables marked "Masked", which must be a single line of at
+            // least 8 characters without spaces
+            Self::Gitlab if value.len() < 8 || value.chars().any(char::is_whitespace) => {
+                Err(format!(
+                    "Secret '{}' can't be masked by GitLab: masked variables must be at least 8 characters with no spaces or newlines",
+                    key
+                ))
+            }
+            Self::Gitlab => Ok(format!(
+                "{}: add as a masked CI/CD variable (Settings > CI/CD > Variables) to redact it from job logs",
+                key
+            )),
+        }
+    }
+}
 
 #[derive(Debug, Args)]
-pub struct CiRedactCommand {}
+pub struct CiRedactCommand {
+    /// CI platform to mask secrets for; GitLab only gets masking guidance (detected from GITHUB_ACTIONS or GITLAB_CI by default)
+    #[arg(long, value_enum)]
+    platform: Option<CiPlatform>,
+}
 
 impl CiRedactCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         let profile = Config::get_profile(cli.profile.as_deref());
         tracing::debug!("Redacting secrets from profile '{}'", profile);
 
-        // Check if we're in CI and get the vendor
-        let ci_info = ci_info::get();
-        if !ci_info.ci {
-            return Err(FnoxError::Config(
-                "Not running in a CI environment. The ci-redact command is only for CI/CD pipelines.".to_string()
-            ));
-        }
-
-        // Determine the masking format based on CI vendor
-        let mask_fn: MaskFn = match ci_info.vendor {
-            Some(ci_info::types::Vendor::GitHubActions) => {
-                Box::new(|key: &str, value: &str| {
-                    // GitHub Actions doesn't properly handle multiline secrets
-                    // Warn the user instead of attempting to mask
-                    if value.contains('\n') {
-                        tracing::warn!(
-                            "Secret '{}' contains newlines and cannot be fully redacted in CI logs. \
-                            Consider using a secret manager or storing multiline secrets outside fnox config.",
-                            key
-                        );
-                    } else {
-                        println!("::add-mask::{}", value);
-                    }
-                })
-            }
-            Some(ci_info::types::Vendor::GitLabCI) => {
-                // GitLab CI doesn't have a built-in mask command
-                // Instead, you need to configure masked variables in the UI
-                return Err(FnoxError::Config(
-                    "GitLab CI does not support runtime secret masking. Configure masked variables in GitLab CI/CD settings.".to_string()
-                ));
-            }
-            Some(ci_info::types::Vendor::CircleCI) => {
-                // CircleCI doesn't have a built-in mask command
-                return Err(FnoxError::Config(
-                    "CircleCI does not support runtime secret masking. Use CircleCI context secrets or project environment variables.".to_string()
-                ));
-            }
-            Some(ci_info::types::Vendor::Unknown) => {
-                return Err(FnoxError::Config(
-                    "Running in CI but vendor is unknown. Cannot determine masking format."
-                        .to_string(),
-                ));
-            }
-            Some(vendor) => {
-                return Err(FnoxError::Config(format!(
-                    "CI vendor '{:?}' does not have known secret masking support. Please configure secrets through your CI provider's settings.",
-                    vendor
-                )));
-            }
-            None => {
-                return Err(FnoxError::Config(
-                    "Running in CI but vendor is None. Cannot determine masking format."
-                        .to_string(),
-                ));
-            }
+        let platform = match self
+            .platform
+            .or_else(|| CiPlatform::detect(|name| env::var(name).ok()))
+        {
+            Some(platform) => platform,
+            None => return Err(Self::unsupported_ci()),
         };
 
         // Get the profile secrets
@@ -86,16 +90,17 @@ impl CiRedactCommand {
             )
             .await
             {
-                Ok(Some(value)) => {
+                Ok(Some(value)) => match platform.mask_line(key, &value) {
                     // Output CI-specific mask command
-                    mask_fn(key, &value);
-                }
+                    Ok(line) => println!("{}", line),
+                    Err(reason) => tracing::warn!("{}", reason),
+                },
                 Ok(None) => {
                     // Secret not found, ignore based on if_missing setting
                 }
                 Err(e) => {
                     // Provider error - respect if_missing to decide whether to fail or continue
-                    let if_missing = resolve_if_missing_behavior(secret_config, &config);
+                    let if_missing = resolve_if_missing_behavior(secret_config, &config, &profile);
 
                     if let Some(error) = handle_provider_error(key, e, if_missing, false) {
                         return Err(error);
@@ -106,4 +111,80 @@ impl CiRedactCommand {
 
         Ok(())
     }
+
+    /// Why masking isn't possible here, when no platform was given or detected
+    fn unsupported_ci() -> FnoxError {
+        let ci_info = ci_info::get();
+        if !ci_info.ci {
+            return FnoxError::Config(
+                "Not running in a CI environment. The ci-redact command is only for CI/CD pipelines.".to_string()
+            );
+        }
+        match ci_info.vendor {
+            Some(ci_info::types::Vendor::CircleCI) => FnoxError::Config(
+                "CircleCI does not support runtime secret masking. Use CircleCI context secrets or project environment variables.".to_string()
+            ),
+            Some(ci_info::types::Vendor::Unknown) | None => FnoxError::Config(
+                "Running in CI but vendor is unknown. Cannot determine masking format. Pass --platform to choose one."
+                    .to_string(),
+            ),
+            Some(vendor) => FnoxError::Config(format!(
+                "CI vendor '{:?}' does not have known secret masking support. Please configure secrets through your CI provider's settings.",
+                vendor
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<CiPlatform> {
+        CiPlatform::detect(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn detects_platform_from_env() {
+        assert_eq!(
+            detect(&[("GITHUB_ACTIONS", "true")]),
+            Some(CiPlatform::Github)
+        );
+        assert_eq!(detect(&[("GITLAB_CI", "true")]), Some(CiPlatform::Gitlab));
+        assert_eq!(
+            detect(&[("GITLAB_CI", "true"), ("GITHUB_ACTIONS", "true")]),
+            Some(CiPlatform::Github)
+        );
+        assert_eq!(detect(&[("GITLAB_CI", "false")]), None);
+        assert_eq!(detect(&[("CI", "true")]), None);
+    }
+
+    #[test]
+    fn each_platform_emits_its_masking_directive() {
+        assert_eq!(
+            CiPlatform::Github
+                .mask_line("TOKEN", "s3cr3t-value")
+                .unwrap(),
+            "::add-mask::s3cr3t-value"
+        );
+        let line = CiPlatform::Gitlab
+            .mask_line("TOKEN", "s3cr3t-value")
+            .unwrap();
+        assert!(
+            line.starts_with("TOKEN: add as a masked CI/CD variable"),
+            "{line}"
+        );
+        assert!(!line.contains("s3cr3t-value"), "{line}");
+    }
+
+    #[test]
+    fn reports_values_a_platform_cannot_mask() {
+        assert!(CiPlatform::Github.mask_line("K", "a\nb").is_err());
+        assert!(CiPlatform::Gitlab.mask_line("K", "short").is_err());
+        assert!(CiPlatform::Gitlab.mask_line("K", "has a space").is_err());
+    }
 }
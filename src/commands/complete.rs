@@ -0,0 +1,312 @@
+//! Hidden `__complete` command that the shell completion scripts call back
+//! into. Given the words typed after `fnox`, it prints the candidates for the
+//! last one: subcommands, flags, or values such as secret keys, provider
+//! names and profiles from the config. With `--arg`, it prints the values for
+//! one argument, for the usage spec's `complete` directives.
+
+use crate::commands::Cli;
+use crate::config::Config;
+use crate::error::Result;
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub struct CompleteCommand {
+    /// Print the values for the argument with this name instead, such as `key`
+    #[arg(long, value_name = "NAME", conflicts_with = "words")]
+    pub arg: Option<String>,
+
+    /// Words after `fnox`, ending with the (possibly empty) word being completed
+    #[arg(allow_hyphen_values = true, trailing_var_arg = true)]
+    pub words: Vec<String>,
+}
+
+/// What the word being completed is
+#[derive(Debug, PartialEq, Eq)]
+enum Slot {
+    Subcommand,
+    Flag,
+    /// A value for the argument with this id
+    Value(String),
+    None,
+}
+
+/// The command line up to the word being completed
+#[derive(Debug)]
+struct Context {
+    /// Names of the subcommands typed so far
+    path: Vec<String>,
+    slot: Slot,
+    profile: Option<String>,
+    config: Option<PathBuf>,
+}
+
+impl CompleteCommand {
+    pub async fn run(&self, cli: &Cli) -> Result<()> {
+        if let Some(arg) = &self.arg {
+            for candidate in config_values(&cli.config, cli.profile.as_deref(), arg, "") {
+                println!("{}", candidate);
+            }
+            return Ok(());
+        }
+
+        let mut root = Cli::command();
+        root.build();
+
+        let (typed, partial) = match self.words.split_last() {
+            Some((partial, typed)) => (typed, partial.as_str()),
+            None => (&[][..], ""),
+        };
+        let context = parse(&root, typed, partial);
+
+        let candidates = match &context.slot {
+            Slot::Subcommand => subcommands(command_at(&root, &context.path)),
+            Slot::Flag => flags(command_at(&root, &context.path)),
+            Slot::Value(id) => {
+                let command = command_at(&root, &context.path);
+                match command
+                    .get_arguments()
+                    .find(|arg| arg.get_id().as_str() == id)
+                {
+                    Some(arg) => values(cli, &context, command, arg),
+                    None => Vec::new(),
+                }
+            }
+            Slot::None => Vec::new(),
+        };
+
+        for candidate in candidates {
+            if candidate.starts_with(partial) {
+                println!("{}", candidate);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Walk the typed words down the command tree to find what `partial`
+/// completes to, remembering any `--profile` or `--config` along the way
+fn parse(root: &Command, typed: &[String], partial: &str) -> Context {
+    let mut context = Context {
+        path: Vec::new(),
+        slot: Slot::None,
+        profile: None,
+        config: None,
+    };
+    let mut command = root;
+    let mut positionals = 0;
+    let mut pending: Option<&Arg> = None;
+
+    for word in typed {
+        if let Some(arg) = pending.take() {
+            context.remember(arg, word);
+            continue;
+        }
+        if word == "--" {
+            // Everything after is passed through, such as `fnox exec -- cmd`
+            return context;
+        }
+        if let Some(flag) = word.strip_prefix('-').filter(|flag| !flag.is_empty()) {
+            if let Some((long, value)) = flag.strip_prefix('-').and_then(|f| f.split_once('=')) {
+                if let Some(arg) = command.get_arguments().find(|a| a.get_long() == Some(long)) {
+                    context.remember(arg, value);
+                }
+                continue;
+            }
+            let arg = match flag.strip_prefix('-') {
+                Some(long) => command.get_arguments().find(|a| a.get_long() == Some(long)),
+                None if flag.chars().count() == 1 => command
+                    .get_arguments()
+                    .find(|a| a.get_short() == flag.chars().next()),
+                None => None,
+            };
+            pending = arg.filter(|arg| arg.get_action().takes_values());
+            continue;
+        }
+        if positionals == 0
+            && let Some(sub) = command
+                .get_subcommands()
+                .find(|sub| sub.get_name() == word || sub.get_all_aliases().any(|a| a == word))
+        {
+            context.path.push(sub.get_name().to_string());
+            command = sub;
+            continue;
+        }
+        positionals += 1;
+    }
+
+    context.slot = if let Some(arg) = pending {
+        Slot::Value(arg.get_id().to_string())
+    } else if partial.starts_with('-') {
+        Slot::Flag
+    } else if positionals == 0 && command.has_subcommands() {
+        Slot::Subcommand
+    } else {
+        let mut args = command.get_positionals();
+        let last = command.get_positionals().last();
+        match args
+            .nth(positionals)
+            .or_else(|| last.filter(|arg| matches!(arg.get_action(), ArgAction::Append)))
+        {
+            Some(arg) => Slot::Value(arg.get_id().to_string()),
+            None => Slot::None,
+        }
+    };
+    context
+}
+
+impl Context {
+    /// Keep the value of `--profile` or `--config` for loading the config
+    fn remember(&mut self, arg: &Arg, value: &str) {
+        match arg.get_id().as_str() {
+            "profile" => self.profile = Some(value.to_string()),
+            "config" => self.config = Some(PathBuf::from(value)),
+            _ => {}
+        }
+    }
+}
+
+fn command_at<'a>(root: &'a Command, path: &[String]) -> &'a Command {
+    path.iter().fold(root, |command, name| {
+        command.find_subcommand(name).unwrap_or(command)
+    })
+}
+
+fn subcommands(command: &Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+        .map(|sub| sub.get_name().to_string())
+        .collect()
+}
+
+fn flags(command: &Command) -> Vec<String> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .filter_map(|arg| arg.get_long())
+        .map(|long| format!("--{}", long))
+        .collect()
+}
+
+/// Values for an argument: its choices if it has any, otherwise secret keys,
+/// provider names or profiles from the config, picked by the argument's name
+fn values(cli: &Cli, context: &Context, command: &Command, arg: &Arg) -> Vec<String> {
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !choices.is_empty() {
+        return choices;
+    }
+
+    config_values(
+        context.config.as_ref().unwrap_or(&cli.config),
+        context.profile.as_deref().or(cli.profile.as_deref()),
+        arg.get_id().as_str(),
+        command.get_name(),
+    )
+}
+
+/// Secret keys, provider names or profiles from the config for the argument
+/// `id` of `command`
+fn config_values(
+    config_path: &Path,
+    profile: Option<&str>,
+    id: &str,
+    command: &str,
+) -> Vec<String> {
+    let Ok(config) = Config::load_smart(config_path) else {
+        return Vec::new();
+    };
+    let profile = Config::get_profile(profile);
+
+    match id {
+        "key" | "keys" | "old" => config
+            .get_secrets(&profile)
+            .map(|secrets| secrets.into_keys().collect())
+            .unwrap_or_default(),
+        // `provider add` names a new provider
+        "provider" | "share_session" if command != "add" => {
+            let mut names: Vec<_> = config.get_providers(&profile).into_keys().collect();
+            names.sort();
+            names
+        }
+        "profile" | "from" | "to" => {
+            let mut names = vec!["default".to_string()];
+            names.extend(config.profiles.keys().cloned());
+            names.sort();
+            names.dedup();
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(words: &[&str]) -> Context {
+        let mut root = Cli::command();
+        root.build();
+        let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        let (partial, typed) = words.split_last().unwrap();
+        parse(&root, typed, partial)
+    }
+
+    #[test]
+    fn completes_subcommands_at_each_level() {
+        let ctx = context(&[""]);
+        assert_eq!(ctx.slot, Slot::Subcommand);
+        assert!(ctx.path.is_empty());
+
+        let ctx = context(&["provider", "re"]);
+        assert_eq!(ctx.slot, Slot::Subcommand);
+        assert_eq!(ctx.path, vec!["provider"]);
+    }
+
+    #[test]
+    fn completes_positional_and_flag_values() {
+        assert_eq!(context(&["get", ""]).slot, Slot::Value("key".to_string()));
+        assert_eq!(
+            context(&["provider", "remove", ""]).slot,
+            Slot::Value("provider".to_string())
+        );
+        assert_eq!(
+            context(&["set", "--provider", ""]).slot,
+            Slot::Value("provider".to_string())
+        );
+        assert_eq!(
+            context(&["get", "KEY", "--profile", ""]).slot,
+            Slot::Value("profile".to_string())
+        );
+        assert_eq!(context(&["get", "KEY", ""]).slot, Slot::None);
+    }
+
+    #[test]
+    fn completes_flags_for_a_leading_dash() {
+        let ctx = context(&["get", "--"]);
+        assert_eq!(ctx.slot, Slot::Flag);
+        let mut root = Cli::command();
+        root.build();
+        let flags = flags(command_at(&root, &ctx.path));
+        assert!(flags.contains(&"--base64-decode".to_string()), "{flags:?}");
+        assert!(flags.contains(&"--profile".to_string()), "{flags:?}");
+    }
+
+    #[test]
+    fn remembers_profile_and_config_flags() {
+        let ctx = context(&["--profile=prod", "-c", "other.toml", "get", ""]);
+        assert_eq!(ctx.profile.as_deref(), Some("prod"));
+        assert_eq!(ctx.config, Some(PathBuf::from("other.toml")));
+        assert_eq!(ctx.slot, Slot::Value("key".to_string()));
+    }
+
+    #[test]
+    fn stops_after_a_double_dash() {
+        assert_eq!(context(&["exec", "--", ""]).slot, Slot::None);
+    }
+}
@@ -1,7 +1,14 @@
 use crate::commands::Cli;
 use crate::error::Result;
+use std::io::ErrorKind;
 use std::process::Command;
 
+/// Scripts used when the `usage` CLI isn't installed. They only complete
+/// through `fnox __complete`.
+const BASH: &str = include_str!("../assets/completions/fnox.bash");
+const ZSH: &str = include_str!("../assets/completions/fnox.zsh");
+const FISH: &str = include_str!("../assets/completions/fnox.fish");
+
 #[derive(clap::Args)]
 #[command(about = "Generate shell completions")]
 #[command(aliases = ["complete", "completions"])]
@@ -24,7 +31,12 @@ impl CompletionCommand {
                 "--cache-key",
                 env!("CARGO_PKG_VERSION"),
             ])
-            .output()?;
+            .output();
+        let output = match output {
+            Ok(output) => output,
+            Err(e) if e.kind() == ErrorKind::NotFound => return self.print_builtin(),
+            Err(e) => return Err(e.into()),
+        };
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -39,4 +51,21 @@ impl CompletionCommand {
 
         Ok(())
     }
+
+    fn print_builtin(&self) -> Result<()> {
+        let script = match self.shell.as_str() {
+            "bash" => BASH,
+            "zsh" => ZSH,
+            "fish" => FISH,
+            other => {
+                return Err(crate::error::FnoxError::Config(format!(
+                    "Completions for '{}' need the usage CLI (https://usage.jdx.dev); \
+                     without it only bash, zsh and fish are supported",
+                    other
+                )));
+            }
+        };
+        print!("{}", script);
+        Ok(())
+    }
 }
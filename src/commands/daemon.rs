@@ -53,10 +53,11 @@ impl DaemonCommand {
             }
             DaemonSubcommand::Status => {
                 match daemon::status(cli).await? {
-                    Some((pid, cached_entries)) => {
+                    Some(status) => {
                         println!("fnox daemon running");
-                        println!("pid: {}", pid);
-                        println!("cached_entries: {}", cached_entries);
+                        println!("pid: {}", status.pid);
+                        println!("cached_entries: {}", status.cached_entries);
+                        println!("watched_projects: {}", status.watched_projects);
                     }
                     None => println!("fnox daemon not running"),
                 }
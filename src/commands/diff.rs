@@ -0,0 +1,294 @@
+use crate::commands::Cli;
+use crate::config::{Config, SecretConfig};
+use crate::error::{FnoxError, Result};
+use crate::suggest::{find_similar, format_suggestions};
+use clap::Args;
+use indexmap::IndexMap;
+
+#[derive(Debug, Args)]
+pub struct DiffCommand {
+    /// Profile to compare from
+    pub from: String,
+
+    /// Profile to compare to
+    pub to: String,
+
+    /// Resolve secrets in both profiles and report whether their values differ (values are never printed)
+    #[arg(long)]
+    pub values: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffStatus {
+    /// Only defined in the `to` profile
+    Added,
+    /// Only defined in the `from` profile
+    Removed,
+    Changed,
+    Same,
+}
+
+#[derive(Debug)]
+struct DiffEntry {
+    key: String,
+    status: DiffStatus,
+    details: Vec<String>,
+}
+
+impl DiffCommand {
+    pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
+        let active_profile = Config::get_profile(cli.profile.as_deref());
+        for profile in [&self.from, &self.to] {
+            check_profile_exists(&config, &active_profile, profile)?;
+        }
+        tracing::debug!("Diffing profiles '{}' and '{}'", self.from, self.to);
+
+        let from_secrets = config.get_secrets(&self.from)?;
+        let to_secrets = config.get_secrets(&self.to)?;
+
+        let mut entries = diff_metadata(&from_secrets, &to_secrets);
+
+        if self.values {
+            let shared = |secrets: &IndexMap<String, SecretConfig>| {
+                secrets
+                    .iter()
+                    .filter(|(k, _)| from_secrets.contains_key(*k) && to_secrets.contains_key(*k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect::<IndexMap<_, _>>()
+            };
+            let from_values = crate::daemon::resolve_batch(
+                cli,
+                &config,
+                &self.from,
+                &shared(&from_secrets),
+                crate::daemon::Purpose::Diff,
+                true,
+            )
+            .await?;
+            let to_values = crate::daemon::resolve_batch(
+                cli,
+                &config,
+                &self.to,
+                &shared(&to_secrets),
+                crate::daemon::Purpose::Diff,
+                true,
+            )
+            .await?;
+            apply_value_diff(&mut entries, &from_values, &to_values);
+        }
+
+        if entries.is_empty() {
+            println!(
+                "No secrets defined in profiles '{}' or '{}'",
+                self.from, self.to
+            );
+            return Ok(());
+        }
+
+        let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+        for entry in &entries {
+            let (marker, summary) = match entry.status {
+                DiffStatus::Added => (console::style("+").green(), format!("only in {}", self.to)),
+                DiffStatus::Removed => {
+                    (console::style("-").red(), format!("only in {}", self.from))
+                }
+                DiffStatus::Changed => (console::style("~").yellow(), entry.details.join(", ")),
+                DiffStatus::Same => (console::style("=").dim(), "same".to_string()),
+            };
+            println!("{} {:width$}  {}", marker, entry.key, summary);
+        }
+
+        Ok(())
+    }
+}
+
+/// Profiles only exist if they are `default`, declared under `[profiles]`, or
+/// the active profile (which may come from a `fnox.<profile>.toml` file).
+fn check_profile_exists(config: &Config, active_profile: &str, profile: &str) -> Result<()> {
+    if profile == "default" || profile == active_profile || config.profiles.contains_key(profile) {
+        return Ok(());
+    }
+
+    let mut available = vec!["default"];
+    available.extend(config.profiles.keys().map(String::as_str));
+    let similar = find_similar(profile, available.iter().copied());
+
+    Err(FnoxError::ProfileNotFound {
+        profile: profile.to_string(),
+        available: available.join(", "),
+        suggestion: format_suggestions(&similar),
+    })
+}
+
+fn describe_provider(secret: &SecretConfig) -> &str {
+    secret.provider().unwrap_or("(default)")
+}
+
+/// Compare the two secret sets by presence, provider and description.
+///
+/// Keys keep the `from` profile's order, followed by keys only in `to`.
+fn diff_metadata(
+    from: &IndexMap<String, SecretConfig>,
+    to: &IndexMap<String, SecretConfig>,
+) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+
+    for (key, from_secret) in from {
+        let Some(to_secret) = to.get(key) else {
+            entries.push(DiffEntry {
+                key: key.clone(),
+                status: DiffStatus::Removed,
+                details: vec![],
+            });
+            continue;
+        };
+
+        let mut details = vec![];
+        if from_secret.provider() != to_secret.provider() {
+            details.push(format!(
+                "provider: {} -> {}",
+                describe_provider(from_secret),
+                describe_provider(to_secret)
+            ));
+        }
+        if from_secret.description != to_secret.description {
+            details.push("description changed".to_string());
+        }
+
+        entries.push(DiffEntry {
+            key: key.clone(),
+            status: if details.is_empty() {
+                DiffStatus::Same
+            } else {
+                DiffStatus::Changed
+            },
+            details,
+        });
+    }
+
+    for key in to.keys().filter(|k| !from.contains_key(*k)) {
+        entries.push(DiffEntry {
+            key: key.clone(),
+            status: DiffStatus::Added,
+            details: vec![],
+        });
+    }
+
+    entries
+}
+
+/// Mark shared keys whose resolved values differ as changed.
+fn apply_value_diff(
+    entries: &mut [DiffEntry],
+    from_values: &IndexMap<String, Option<String>>,
+    to_values: &IndexMap<String, Option<String>>,
+) {
+    for entry in entries
+        .iter_mut()
+        .filter(|e| matches!(e.status, DiffStatus::Same | DiffStatus::Changed))
+    {
+        let from_value = from_values.get(&entry.key).cloned().flatten();
+        let to_value = to_values.get(&entry.key).cloned().flatten();
+        if from_value != to_value {
+            entry.status = DiffStatus::Changed;
+            entry.details.push("value changed".to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(provider: &str, value: &str) -> SecretConfig {
+        let mut secret = SecretConfig::new();
+        secret.set_provider(Some(provider.to_string()));
+        secret.set_value(Some(value.to_string()));
+        secret
+    }
+
+    fn statuses(entries: &[DiffEntry]) -> Vec<(&str, DiffStatus)> {
+        entries.iter().map(|e| (e.key.as_str(), e.status)).collect()
+    }
+
+    #[test]
+    fn diff_metadata_classifies_added_removed_changed_and_same() {
+        let from = IndexMap::from([
+            ("ONLY_FROM".to_string(), secret("age", "a")),
+            ("MOVED".to_string(), secret("age", "b")),
+            ("SAME".to_string(), secret("age", "c")),
+        ]);
+        let to = IndexMap::from([
+            ("SAME".to_string(), secret("age", "different-ciphertext")),
+            ("MOVED".to_string(), secret("vault", "b")),
+            ("ONLY_TO".to_string(), secret("age", "d")),
+        ]);
+
+        let entries = diff_metadata(&from, &to);
+        assert_eq!(
+            statuses(&entries),
+            vec![
+                ("ONLY_FROM", DiffStatus::Removed),
+                ("MOVED", DiffStatus::Changed),
+                ("SAME", DiffStatus::Same),
+                ("ONLY_TO", DiffStatus::Added),
+            ]
+        );
+        assert_eq!(entries[1].details, vec!["provider: age -> vault"]);
+    }
+
+    #[test]
+    fn diff_metadata_detects_description_change() {
+        let mut described = secret("age", "a");
+        described.description = Some("API token".to_string());
+        let from = IndexMap::from([("KEY".to_string(), secret("age", "a"))]);
+        let to = IndexMap::from([("KEY".to_string(), described)]);
+
+        let entries = diff_metadata(&from, &to);
+        assert_eq!(entries[0].status, DiffStatus::Changed);
+        assert_eq!(entries[0].details, vec!["description changed"]);
+    }
+
+    #[test]
+    fn apply_value_diff_marks_changed_values_only() {
+        let from = IndexMap::from([
+            ("A".to_string(), secret("age", "x")),
+            ("B".to_string(), secret("age", "y")),
+            ("C".to_string(), secret("age", "z")),
+        ]);
+        let to = from.clone();
+        let mut entries = diff_metadata(&from, &to);
+
+        let from_values = IndexMap::from([
+            ("A".to_string(), Some("one".to_string())),
+            ("B".to_string(), Some("two".to_string())),
+            ("C".to_string(), None),
+        ]);
+        let to_values = IndexMap::from([
+            ("A".to_string(), Some("one".to_string())),
+            ("B".to_string(), Some("changed".to_string())),
+            ("C".to_string(), Some("now-set".to_string())),
+        ]);
+        apply_value_diff(&mut entries, &from_values, &to_values);
+
+        assert_eq!(
+            statuses(&entries),
+            vec![
+                ("A", DiffStatus::Same),
+                ("B", DiffStatus::Changed),
+                ("C", DiffStatus::Changed),
+            ]
+        );
+        assert_eq!(entries[1].details, vec!["value changed"]);
+    }
+
+    #[test]
+    fn apply_value_diff_ignores_keys_missing_from_one_side() {
+        let from = IndexMap::from([("A".to_string(), secret("age", "x"))]);
+        let to = IndexMap::new();
+        let mut entries = diff_metadata(&from, &to);
+
+        apply_value_diff(&mut entries, &IndexMap::new(), &IndexMap::new());
+        assert_eq!(statuses(&entries), vec![("A", DiffStatus::Removed)]);
+    }
+}
@@ -1,17 +1,31 @@
 use crate::commands::Cli;
-use crate::config::Config;
+use crate::config::{Config, ProviderConfig};
 use crate::env;
-use crate::error::Result;
+use crate::error::{FnoxError, Result};
 use crate::providers::get_provider_resolved;
 use clap::Args;
+use std::time::Duration;
 
 #[derive(Debug, Args)]
 #[command(visible_aliases = ["dr"])]
-pub struct DoctorCommand {}
+pub struct DoctorCommand {
+    /// Only check this provider's connection
+    #[arg(long, value_name = "NAME")]
+    provider: Option<String>,
+
+    /// Exit with an error if a provider check fails
+    #[arg(long)]
+    strict: bool,
+
+    /// How long to wait for each provider check, such as "10s" or "1m"
+    #[arg(long, value_name = "DURATION", default_value = "30s")]
+    timeout: String,
+}
 
 impl DoctorCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         let profile = Config::get_profile(cli.profile.as_deref());
+        let timeout = crate::daemon::parse_duration(&self.timeout)?;
 
         println!("🏥 Fnox Doctor Report");
         println!("====================");
@@ -24,6 +38,7 @@ impl DoctorCommand {
 
         config.validate()?;
         println!("  Status: ✓ Loaded successfully");
+        print_worktree(&config);
         println!();
 
         // Secrets info
@@ -71,6 +86,16 @@ impl DoctorCommand {
         // Providers info
         println!("🔧 Providers:");
         let providers = config.get_providers(&profile);
+        if let Some(name) = &self.provider
+            && !providers.contains_key(name)
+        {
+            return Err(FnoxError::ProviderNotConfigured {
+                provider: name.clone(),
+                profile: profile.to_string(),
+                config_path: None,
+                suggestion: None,
+            });
+        }
         println!("  Count: {}", providers.len());
 
         if !providers.is_empty() {
@@ -90,20 +115,29 @@ impl DoctorCommand {
         }
 
         // Test providers
+        let mut failed = 0;
         if !providers.is_empty() {
             println!();
             println!("🔍 Provider Health:");
-            for (name, provider_config) in &providers {
-                match get_provider_resolved(&config, &profile, name, provider_config).await {
-                    Ok(provider) => {
-                        print!("  {}: Testing...", name);
-                        match provider.test_connection().await {
-                            Ok(_) => println!(" ✓"),
-                            Err(e) => println!(" ✗ {}", e),
-                        }
-                    }
+            // Checked concurrently, each with its own timeout, so one hanging
+            // provider can't hold up the others
+            let checks = providers
+                .iter()
+                .filter(|(name, _)| self.provider.as_ref().is_none_or(|p| p == *name))
+                .map(|(name, provider_config)| {
+                    check_provider(&config, &profile, name, provider_config, timeout)
+                });
+            let results = futures::future::join_all(checks).await;
+            for (name, result) in providers
+                .keys()
+                .filter(|name| self.provider.as_ref().is_none_or(|p| p == *name))
+                .zip(results)
+            {
+                match result {
+                    Ok(()) => println!("  {}: ✓", name),
                     Err(e) => {
-                        println!("  {}: ✗ Failed to initialize: {}", name, e);
+                        failed += 1;
+                        println!("  {}: ✗ {}", name, e);
                     }
                 }
             }
@@ -126,6 +160,57 @@ impl DoctorCommand {
         }
         println!("  - Run 'fnox check' to validate your configuration");
 
+        if self.strict && failed > 0 {
+            return Err(FnoxError::Config(format!(
+                "{} provider check(s) failed",
+                failed
+            )));
+        }
+
         Ok(())
     }
 }
+
+/// Explain what was detected when running in a linked git worktree
+fn print_worktree(config: &Config) {
+    let Some(worktree) = env::current_dir()
+        .ok()
+        .and_then(|dir| crate::worktree::linked_worktree(&dir))
+    else {
+        return;
+    };
+    println!("  Git worktree: {}", worktree.root.display());
+    println!("  Main worktree: {}", worktree.main_root.display());
+    let following = config
+        .settings
+        .as_ref()
+        .and_then(|s| s.follow_worktrees)
+        .unwrap_or(false);
+    if following {
+        println!("  Following worktrees: yes, the main worktree's config is a fallback layer");
+    } else {
+        println!(
+            "  Following worktrees: no, set [settings] follow_worktrees = true to load the main worktree's config"
+        );
+    }
+}
+
+/// Initialize a provider and test its connection, giving up after `timeout`
+async fn check_provider(
+    config: &Config,
+    profile: &str,
+    name: &str,
+    provider_config: &ProviderConfig,
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    let check = async {
+        let provider = get_provider_resolved(config, profile, name, provider_config)
+            .await
+            .map_err(|e| format!("Failed to initialize: {}", e))?;
+        provider.test_connection().await.map_err(|e| e.to_string())
+    };
+    match tokio::time::timeout(timeout, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {:?}", timeout)),
+    }
+}
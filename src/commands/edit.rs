@@ -2,12 +2,14 @@ use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::Write as _;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
 use clap::Args;
 use indexmap::IndexMap;
 use tempfile::NamedTempFile;
-use toml_edit::{DocumentMut, Table, Value};
+use toml_edit::{DocumentMut, InlineTable, Item, TableLike, Value};
 
 use crate::commands::Cli;
 use crate::config::{Config, SecretConfig};
@@ -26,7 +28,11 @@ const TEMP_FILE_HEADER: &str = "\
 ";
 
 #[derive(Debug, Args)]
-pub struct EditCommand;
+pub struct EditCommand {
+    /// How long to wait for a provider to store each changed secret, such as "30s" or "2m"
+    #[arg(long, value_name = "DURATION", default_value = "30s")]
+    pub timeout: String,
+}
 
 /// Represents a secret with its metadata for tracking during editing
 #[derive(Debug, Clone)]
@@ -45,9 +51,46 @@ struct SecretEntry {
     provider_name: Option<String>,
 }
 
+/// What happens to one secret of the edited file
+#[derive(Debug)]
+enum Change {
+    /// Write this value: the stored value, a new ciphertext, or plaintext
+    /// for a secret without a provider
+    Keep(String),
+    /// Encrypt the edited plaintext with this provider
+    Encrypt { provider: String, plaintext: String },
+    /// The edit can't be saved
+    Fail {
+        provider: Option<String>,
+        plaintext: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug)]
+struct PlannedChange {
+    profile: String,
+    key: String,
+    /// The value stored before the edit, kept if the edit fails
+    original: Option<String>,
+    is_new: bool,
+    change: Change,
+}
+
+/// A secret whose edit couldn't be saved, with the plaintext that was entered
+#[derive(Debug)]
+struct FailedSecret {
+    profile: String,
+    key: String,
+    provider: Option<String>,
+    plaintext: String,
+    reason: String,
+}
+
 impl EditCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         let profile = Config::get_profile(cli.profile.as_deref());
+        let timeout = crate::daemon::parse_duration(&self.timeout)?;
         tracing::debug!("Starting enhanced edit with profile: {}", profile);
 
         // Step 1: Load raw TOML with toml_edit to preserve formatting
@@ -168,7 +211,8 @@ impl EditCommand {
         let modified_config: Config = toml_edit::de::from_str(&modified_toml)
             .map_err(|e| FnoxError::Config(format!("Invalid configuration after edit: {}", e)))?;
 
-        self.reencrypt_secrets(&modified_config, &mut modified_doc, &all_secrets)
+        let failures = self
+            .reencrypt_secrets(&modified_config, &mut modified_doc, &all_secrets, timeout)
             .await?;
 
         // Step 8: Save the modified config (preserves all user edits)
@@ -181,9 +225,42 @@ impl EditCommand {
 
         let check = console::style("✓").green();
         let styled_config = console::style(cli.config.display()).cyan();
-        println!("{check} Configuration file {styled_config} updated with re-encrypted secrets");
+        if failures.is_empty() {
+            println!(
+                "{check} Configuration file {styled_config} updated with re-encrypted secrets"
+            );
+            return Ok(());
+        }
 
-        Ok(())
+        // Step 9: Keep the edits that couldn't be saved
+        println!("{check} Configuration file {styled_config} updated with the other changes");
+        let recovery_dir = crate::env::FNOX_STATE_DIR.join("edit-recovery");
+        let recovery = match write_recovery_file(&recovery_dir, &cli.config, &failures) {
+            Ok(path) => path,
+            Err(e) => {
+                // The edited file still has them; keep it instead of deleting it
+                tracing::warn!("Failed to write a recovery file: {}", e);
+                temp_file.keep().map(|(_, path)| path).map_err(|e| {
+                    FnoxError::Config(format!("Failed to keep the edited file: {}", e))
+                })?
+            }
+        };
+        let cross = console::style("✗").red();
+        for failure in &failures {
+            eprintln!(
+                "{cross} {} ({}): {}",
+                failure.key, failure.profile, failure.reason
+            );
+        }
+
+        Err(FnoxError::EditSecretsNotSaved {
+            keys: failures
+                .iter()
+                .map(|failure| failure.key.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            recovery: recovery.display().to_string(),
+        })
     }
 
     /// Collect secrets from a specific secrets table (top-level or profile)
@@ -259,39 +336,7 @@ impl EditCommand {
                 .map_err(|e| FnoxError::Config(format!("Failed to set file permissions: {}", e)))?;
         }
 
-        // Clone the document and replace encrypted values with plaintext
-        let mut decrypted_doc = doc.clone();
-
-        // Create a map of secrets by (profile, key) for quick lookup to avoid collisions
-        let secrets_map: HashMap<_, _> = all_secrets
-            .iter()
-            .map(|s| ((s.profile.clone(), s.key.clone()), s))
-            .collect();
-
-        // Replace values in [secrets] section
-        if let Some(secrets_table) = decrypted_doc
-            .get_mut("secrets")
-            .and_then(|item| item.as_table_mut())
-        {
-            self.replace_secrets_in_table(secrets_table, "default", &secrets_map)?;
-        }
-
-        // Replace values in [profiles.*] sections
-        if let Some(profiles_table) = decrypted_doc
-            .get_mut("profiles")
-            .and_then(|item| item.as_table_mut())
-        {
-            for (profile_name, profile_item) in profiles_table.iter_mut() {
-                let profile_name_str = profile_name.to_string();
-                if let Some(profile_table) = profile_item.as_table_mut()
-                    && let Some(secrets_table) = profile_table
-                        .get_mut("secrets")
-                        .and_then(|item| item.as_table_mut())
-                {
-                    self.replace_secrets_in_table(secrets_table, &profile_name_str, &secrets_map)?;
-                }
-            }
-        }
+        let decrypted_doc = self.decrypted_document(doc, all_secrets)?;
 
         // Add header comment
         let header = format!("{}{}", TEMP_FILE_HEADER, decrypted_doc);
@@ -307,234 +352,355 @@ impl EditCommand {
         Ok(temp_file)
     }
 
+    /// A copy of `doc` with secret values replaced by their plaintext
+    fn decrypted_document(
+        &self,
+        doc: &DocumentMut,
+        all_secrets: &[SecretEntry],
+    ) -> Result<DocumentMut> {
+        let mut decrypted_doc = doc.clone();
+
+        // Create a map of secrets by (profile, key) for quick lookup to avoid collisions
+        let secrets_map: HashMap<_, _> = all_secrets
+            .iter()
+            .map(|s| ((s.profile.clone(), s.key.clone()), s))
+            .collect();
+
+        for (profile, secrets_table) in secrets_tables_mut(&mut decrypted_doc) {
+            self.replace_secrets_in_table(secrets_table, &profile, &secrets_map)?;
+        }
+
+        Ok(decrypted_doc)
+    }
+
     /// Replace encrypted secret values with plaintext in a TOML table
     fn replace_secrets_in_table(
         &self,
-        secrets_table: &mut Table,
+        secrets_table: &mut dyn TableLike,
         profile: &str,
         secrets_map: &HashMap<(String, String), &SecretEntry>,
     ) -> Result<()> {
         for (key, value) in secrets_table.iter_mut() {
-            let key_string = key.to_string();
-            let lookup_key = (profile.to_string(), key_string.clone());
-            if let Some(secret_entry) = secrets_map.get(&lookup_key) {
-                // Try inline table first (KEY = { provider = "...", value = "..." })
-                if let Some(inline_table) = value.as_inline_table_mut() {
-                    if let Some(plaintext) = &secret_entry.plaintext_value {
-                        inline_table.insert("value", Value::from(plaintext.as_str()));
-                    }
-                } else if let Some(table) = value.as_table_mut() {
-                    // Handle regular table format ([secrets.KEY])
-                    if let Some(plaintext) = &secret_entry.plaintext_value {
-                        table.insert("value", toml_edit::value(plaintext.as_str()));
-                    }
-                }
-
-                // Note: We can't easily add inline comments to tables in toml_edit
-                // The read-only status will be enforced when processing changes
+            let lookup_key = (profile.to_string(), key.get().to_string());
+            if let Some(secret_entry) = secrets_map.get(&lookup_key)
+                && let Some(plaintext) = &secret_entry.plaintext_value
+            {
+                Self::set_secret_value(value, plaintext);
             }
+
+            // Note: We can't easily add inline comments to tables in toml_edit
+            // The read-only status will be enforced when processing changes
         }
         Ok(())
     }
 
     /// Re-encrypt secrets in the modified document
     /// This preserves all user edits (comments, formatting, non-secret config)
+    ///
+    /// A secret whose edit can't be saved keeps its stored value in the
+    /// document, or is left out if it's new, and is returned with the
+    /// plaintext that was entered so the edit isn't lost.
     async fn reencrypt_secrets(
         &self,
         config: &Config,
         modified_doc: &mut DocumentMut,
         all_secrets: &[SecretEntry],
-    ) -> Result<()> {
+        timeout: Duration,
+    ) -> Result<Vec<FailedSecret>> {
         // Create a map of secrets by (profile, key) to avoid collisions
         let secrets_map: HashMap<_, _> = all_secrets
             .iter()
             .map(|s| ((s.profile.clone(), s.key.clone()), s))
             .collect();
 
-        // Process [secrets] section
-        if let Some(secrets_table) = modified_doc
-            .get_mut("secrets")
-            .and_then(|item| item.as_table_mut())
-        {
-            self.reencrypt_secrets_table(config, secrets_table, "default", &secrets_map)
-                .await?;
-        }
+        let mut changes = Self::plan_changes(config, modified_doc, &secrets_map)?;
+        Self::encrypt_changes(config, &mut changes, timeout).await;
+        Self::apply_changes(modified_doc, &changes);
 
-        // Process [profiles.*] sections
-        if let Some(profiles_table) = modified_doc
-            .get_mut("profiles")
-            .and_then(|item| item.as_table_mut())
-        {
-            // Collect profile names first to avoid borrow issues
-            let profile_names: Vec<_> = profiles_table.iter().map(|(k, _)| k.to_string()).collect();
-
-            for profile_name in profile_names {
-                if let Some(profile_item) = profiles_table.get_mut(&profile_name)
-                    && let Some(profile_table) = profile_item.as_table_mut()
-                    && let Some(secrets_table) = profile_table
-                        .get_mut("secrets")
-                        .and_then(|item| item.as_table_mut())
-                {
-                    self.reencrypt_secrets_table(
+        Ok(changes
+            .into_iter()
+            .filter_map(|change| match change.change {
+                Change::Fail {
+                    provider,
+                    plaintext,
+                    reason,
+                } => Some(FailedSecret {
+                    profile: change.profile,
+                    key: change.key,
+                    provider,
+                    plaintext,
+                    reason,
+                }),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Decide what happens to each secret in the modified document
+    fn plan_changes(
+        config: &Config,
+        modified_doc: &mut DocumentMut,
+        secrets_map: &HashMap<(String, String), &SecretEntry>,
+    ) -> Result<Vec<PlannedChange>> {
+        let mut changes = Vec::new();
+        for (profile, secrets_table) in secrets_tables_mut(modified_doc) {
+            for (key, item) in secrets_table.iter() {
+                // Extract plaintext value and provider from the value. Inline tables,
+                // [secrets.KEY] tables and dotted keys (KEY.value = "...") all read
+                // the same way.
+                let Some(secret) = item.as_table_like() else {
+                    continue;
+                };
+                let Some(plaintext) = secret.get("value").and_then(Item::as_str) else {
+                    continue;
+                };
+                let explicit_provider = secret
+                    .get("provider")
+                    .and_then(Item::as_str)
+                    .map(String::from);
+
+                // Check if this is an existing secret or a new one
+                let existing = secrets_map.get(&(profile.clone(), key.to_string()));
+                let change = match existing {
+                    Some(secret_entry) => Self::plan_existing(
                         config,
-                        secrets_table,
-                        &profile_name,
-                        &secrets_map,
-                    )
-                    .await?;
+                        &profile,
+                        key,
+                        secret_entry,
+                        plaintext,
+                        explicit_provider,
+                    )?,
+                    None => Self::plan_new(config, &profile, key, plaintext, explicit_provider)?,
+                };
+                if let Some(change) = change {
+                    changes.push(PlannedChange {
+                        profile: profile.clone(),
+                        key: key.to_string(),
+                        original: existing
+                            .and_then(|entry| entry.original_config.value())
+                            .map(String::from),
+                        is_new: existing.is_none(),
+                        change,
+                    });
                 }
             }
         }
-
-        Ok(())
+        Ok(changes)
     }
 
-    /// Re-encrypt secrets in a specific secrets table
-    async fn reencrypt_secrets_table(
-        &self,
+    fn plan_existing(
         config: &Config,
-        secrets_table: &mut Table,
-        secret_profile: &str,
-        secrets_map: &HashMap<(String, String), &SecretEntry>,
-    ) -> Result<()> {
-        // Collect keys first to avoid borrow issues when mutating
-        let keys: Vec<_> = secrets_table.iter().map(|(k, _)| k.to_string()).collect();
-
-        for key_str in keys {
-            let lookup_key = (secret_profile.to_string(), key_str.clone());
-
-            // Get the current value from the table
-            let Some(value) = secrets_table.get_mut(&key_str) else {
-                continue;
-            };
+        profile: &str,
+        key: &str,
+        secret_entry: &SecretEntry,
+        plaintext: &str,
+        explicit_provider: Option<String>,
+    ) -> Result<Option<Change>> {
+        let original = || {
+            secret_entry
+                .original_config
+                .value()
+                .map(|value| Change::Keep(value.to_string()))
+        };
 
-            // Extract plaintext value and provider from the value
-            let (plaintext, explicit_provider) = if let Some(inline_table) = value.as_inline_table()
-            {
-                let plaintext = inline_table.get("value").and_then(|v| v.as_str());
-                let provider = inline_table.get("provider").and_then(|v| v.as_str());
-                (plaintext, provider.map(String::from))
-            } else if let Some(table) = value.as_table() {
-                let plaintext = table.get("value").and_then(|v| v.as_str());
-                let provider = table.get("provider").and_then(|v| v.as_str());
-                (plaintext, provider.map(String::from))
-            } else {
-                continue;
-            };
+        if secret_entry.is_read_only {
+            // Read-only and unchanged - restore original encrypted value
+            if Some(plaintext) == secret_entry.plaintext_value.as_deref() {
+                return Ok(original());
+            }
+            let provider = secret_entry.provider_name.clone();
+            return Ok(Some(Change::Fail {
+                reason: format!(
+                    "secrets from provider '{}' are read-only",
+                    provider.as_deref().unwrap_or("unknown")
+                ),
+                provider,
+                plaintext: plaintext.to_string(),
+            }));
+        }
 
-            let Some(plaintext) = plaintext else {
-                continue;
-            };
+        // Check if the value or provider changed
+        // Compare explicit provider fields (not resolved provider names)
+        // to avoid false positives when secrets use default provider
+        let value_changed = Some(plaintext) != secret_entry.plaintext_value.as_deref();
+        let provider_changed =
+            explicit_provider.as_deref() != secret_entry.original_config.provider();
+        if !value_changed && !provider_changed {
+            // Nothing changed - restore original encrypted value to avoid version control churn
+            return Ok(original());
+        }
 
-            // Check if this is an existing secret or a new one
-            if let Some(secret_entry) = secrets_map.get(&lookup_key) {
-                // Existing secret - check if read-only
-                if secret_entry.is_read_only {
-                    // Verify it wasn't changed
-                    if Some(plaintext) != secret_entry.plaintext_value.as_deref() {
-                        return Err(FnoxError::Config(format!(
-                            "Cannot modify read-only secret '{}' from provider '{}'",
-                            key_str,
-                            secret_entry
-                                .provider_name
-                                .as_ref()
-                                .unwrap_or(&"unknown".to_string())
-                        )));
-                    }
-                    // Read-only and unchanged - restore original encrypted value
-                    if let Some(original_value) = secret_entry.original_config.value() {
-                        Self::set_secret_value(value, original_value);
-                    }
-                    continue;
+        // Value or provider changed - re-encrypt
+        // If explicit provider is set, use it; otherwise use default provider for this secret's profile
+        tracing::debug!("Secret '{}' changed, re-encrypting", key);
+        let provider = match explicit_provider {
+            Some(provider) => Some(provider),
+            None => config.get_default_provider(profile)?,
+        };
+        Ok(Some(match provider {
+            Some(provider) if config.get_providers(profile).contains_key(&provider) => {
+                Change::Encrypt {
+                    provider,
+                    plaintext: plaintext.to_string(),
                 }
+            }
+            _ => Change::Keep(plaintext.to_string()),
+        }))
+    }
 
-                // Check if the value or provider changed
-                // Compare explicit provider fields (not resolved provider names)
-                // to avoid false positives when secrets use default provider
-                let value_changed = Some(plaintext) != secret_entry.plaintext_value.as_deref();
-                let provider_changed =
-                    explicit_provider.as_deref() != secret_entry.original_config.provider();
-
-                if !value_changed && !provider_changed {
-                    // Nothing changed - restore original encrypted value to avoid version control churn
-                    if let Some(original_value) = secret_entry.original_config.value() {
-                        Self::set_secret_value(value, original_value);
-                    }
-                    continue;
-                }
+    fn plan_new(
+        config: &Config,
+        profile: &str,
+        key: &str,
+        plaintext: &str,
+        explicit_provider: Option<String>,
+    ) -> Result<Option<Change>> {
+        // New secret added by user
+        tracing::debug!("New secret '{}' detected, encrypting", key);
 
-                // Value or provider changed - re-encrypt
-                // If explicit provider is set, use it; otherwise use default provider for this secret's profile
-                tracing::debug!("Secret '{}' changed, re-encrypting", key_str);
-                let provider_to_use = if let Some(ref prov) = explicit_provider {
-                    Some(prov.clone())
-                } else {
-                    config.get_default_provider(secret_profile)?
-                };
-                let encrypted_value = if let Some(provider_name) = provider_to_use {
-                    let providers = config.get_providers(secret_profile);
-                    if let Some(provider_config) = providers.get(&provider_name) {
-                        let provider = get_provider_resolved(
-                            config,
-                            secret_profile,
-                            &provider_name,
-                            provider_config,
-                        )
-                        .await?;
-                        provider.put_secret(&key_str, plaintext).await?
-                    } else {
-                        plaintext.to_string()
-                    }
-                } else {
-                    plaintext.to_string()
-                };
+        // Determine provider to use (from this secret's profile)
+        let provider = if let Some(provider) = explicit_provider {
+            provider
+        } else if let Some(default_provider) = config.get_default_provider(profile)? {
+            default_provider
+        } else {
+            // No provider - keep as plaintext
+            tracing::warn!(
+                "No provider specified for new secret '{}', storing as plaintext",
+                key
+            );
+            return Ok(None);
+        };
 
-                Self::set_secret_value(value, &encrypted_value);
-            } else {
-                // New secret added by user
-                tracing::debug!("New secret '{}' detected, encrypting", key_str);
-
-                // Determine provider to use (from this secret's profile)
-                let provider_name = if let Some(prov) = explicit_provider {
-                    prov
-                } else if let Some(default_prov) = config.get_default_provider(secret_profile)? {
-                    default_prov
-                } else {
-                    // No provider - keep as plaintext
-                    tracing::warn!(
-                        "No provider specified for new secret '{}', storing as plaintext",
-                        key_str
-                    );
-                    continue;
-                };
+        if !config.get_providers(profile).contains_key(&provider) {
+            return Ok(Some(Change::Fail {
+                reason: format!("provider '{}' is not configured", provider),
+                provider: Some(provider),
+                plaintext: plaintext.to_string(),
+            }));
+        }
+        Ok(Some(Change::Encrypt {
+            provider,
+            plaintext: plaintext.to_string(),
+        }))
+    }
 
-                // Encrypt with the provider from this secret's profile
-                let providers = config.get_providers(secret_profile);
-                let Some(provider_config) = providers.get(&provider_name) else {
-                    return Err(FnoxError::Config(format!(
-                        "Provider '{}' not found for new secret '{}'",
-                        provider_name, key_str
-                    )));
-                };
+    /// Encrypt the changed secrets, concurrently across providers. Each
+    /// provider stores its secrets one at a time, since providers backed by a
+    /// single file (such as KeePass) would lose concurrent writes.
+    async fn encrypt_changes(config: &Config, changes: &mut [PlannedChange], timeout: Duration) {
+        let mut by_provider: IndexMap<(String, String), Vec<&mut PlannedChange>> = IndexMap::new();
+        for change in changes.iter_mut() {
+            if let Change::Encrypt { provider, .. } = &change.change {
+                by_provider
+                    .entry((change.profile.clone(), provider.clone()))
+                    .or_default()
+                    .push(change);
+            }
+        }
 
-                let provider =
-                    get_provider_resolved(config, secret_profile, &provider_name, provider_config)
-                        .await?;
-                let encrypted_value = provider.put_secret(&key_str, plaintext).await?;
+        let work = by_provider
+            .into_iter()
+            .map(|((profile, provider), changes)| async move {
+                for change in changes {
+                    let Change::Encrypt { plaintext, .. } = &change.change else {
+                        continue;
+                    };
+                    let plaintext = plaintext.clone();
+                    let result = Self::encrypt_one(
+                        config,
+                        &profile,
+                        &provider,
+                        &change.key,
+                        &plaintext,
+                        timeout,
+                    )
+                    .await;
+                    change.change = match result {
+                        Ok(encrypted) => Change::Keep(encrypted),
+                        Err(reason) => Change::Fail {
+                            provider: Some(provider.clone()),
+                            plaintext,
+                            reason,
+                        },
+                    };
+                }
+            });
+        futures::future::join_all(work).await;
+    }
 
-                Self::set_secret_value(value, &encrypted_value);
-            }
+    /// Store one secret with its provider, giving up after `timeout`
+    async fn encrypt_one(
+        config: &Config,
+        profile: &str,
+        provider_name: &str,
+        key: &str,
+        plaintext: &str,
+        timeout: Duration,
+    ) -> std::result::Result<String, String> {
+        let put = async {
+            let providers = config.get_providers(profile);
+            let provider_config = providers.get(provider_name).ok_or_else(|| {
+                FnoxError::Config(format!("Provider '{}' is not configured", provider_name))
+            })?;
+            let provider =
+                get_provider_resolved(config, profile, provider_name, provider_config).await?;
+            crate::providers::check_value_size(provider.as_ref(), key, plaintext)?;
+            provider.put_secret(key, plaintext).await
+        };
+        match tokio::time::timeout(timeout, put).await {
+            Ok(result) => result.map_err(|e| format!("provider '{}': {}", provider_name, e)),
+            Err(_) => Err(format!(
+                "provider '{}' timed out after {:?}",
+                provider_name, timeout
+            )),
         }
+    }
 
-        Ok(())
+    /// Write the outcome of each change into the modified document
+    fn apply_changes(modified_doc: &mut DocumentMut, changes: &[PlannedChange]) {
+        for (profile, secrets_table) in secrets_tables_mut(modified_doc) {
+            for change in changes.iter().filter(|change| change.profile == profile) {
+                match (&change.change, &change.original) {
+                    (Change::Keep(value), _) | (Change::Fail { .. }, Some(value)) => {
+                        if let Some(item) = secrets_table.get_mut(&change.key) {
+                            Self::set_secret_value(item, value);
+                        }
+                    }
+                    // Never write the plaintext of an edit that couldn't be encrypted
+                    (Change::Fail { .. }, None) if change.is_new => {
+                        secrets_table.remove(&change.key);
+                    }
+                    (Change::Fail { .. }, None) => {
+                        if let Some(secret) = secrets_table
+                            .get_mut(&change.key)
+                            .and_then(Item::as_table_like_mut)
+                        {
+                            secret.remove("value");
+                        }
+                    }
+                    (Change::Encrypt { .. }, _) => {}
+                }
+            }
+        }
     }
 
-    /// Helper to set the value field in a secret (handles both inline table and table formats)
-    fn set_secret_value(item: &mut toml_edit::Item, value: &str) {
-        if let Some(inline_table) = item.as_inline_table_mut() {
-            inline_table.insert("value", Value::from(value));
-        } else if let Some(table) = item.as_table_mut() {
-            table.insert("value", toml_edit::value(value));
+    /// Helper to set the value field in a secret, in whichever syntax it's
+    /// written with. An existing value keeps its surrounding whitespace and
+    /// comments.
+    fn set_secret_value(item: &mut Item, value: &str) {
+        let Some(secret) = item.as_table_like_mut() else {
+            return;
+        };
+        match secret.get_mut("value").and_then(Item::as_value_mut) {
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = Value::from(value);
+                *existing.decor_mut() = decor;
+            }
+            None => {
+                secret.insert("value", toml_edit::value(value));
+            }
         }
     }
 
@@ -548,3 +714,380 @@ impl EditCommand {
             .to_string()
     }
 }
+
+/// Save the plaintext of edits that couldn't be saved to `config_path` in a
+/// new file in `dir`, readable only by the user, as secrets in config syntax
+/// with instructions for retrying them.
+fn write_recovery_file(
+    dir: &Path,
+    config_path: &Path,
+    failures: &[FailedSecret],
+) -> Result<PathBuf> {
+    let mut doc = DocumentMut::new();
+    for failure in failures {
+        let mut secret = InlineTable::new();
+        if let Some(provider) = &failure.provider {
+            secret.insert("provider", provider.as_str().into());
+        }
+        secret.insert("value", failure.plaintext.as_str().into());
+        secret.fmt();
+        let secrets = if failure.profile == "default" {
+            &mut doc["secrets"]
+        } else {
+            &mut doc["profiles"][failure.profile.as_str()]["secrets"]
+        };
+        secrets[failure.key.as_str()] = toml_edit::value(secret);
+    }
+    if let Some(profiles) = doc.get_mut("profiles").and_then(Item::as_table_mut) {
+        profiles.set_implicit(true);
+        for (_, profile) in profiles.iter_mut() {
+            if let Some(profile) = profile.as_table_mut() {
+                profile.set_implicit(true);
+            }
+        }
+    }
+
+    let mut header = format!(
+        "# fnox edit couldn't save these secrets to {}:\n",
+        config_path.display()
+    );
+    for failure in failures {
+        header.push_str(&format!(
+            "#   {} ({}): {}\n",
+            failure.key,
+            failure.profile,
+            failure.reason.replace('\n', " ")
+        ));
+    }
+    header.push_str(
+        "#\n\
+         # The values below are the plaintext you entered. Save each one again with\n\
+         # `fnox set KEY` (and `--profile NAME` for a profile's secret) or `fnox edit`,\n\
+         # then delete this file.\n\n",
+    );
+
+    fs::create_dir_all(dir).map_err(|source| FnoxError::OutputWriteFailed {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    // Never replace an earlier recovery file, such as one from a failed edit
+    // in the same second
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut attempt = 0u32;
+    loop {
+        let name = match attempt {
+            0 => format!("fnox-edit-recovery-{stamp}.toml"),
+            n => format!("fnox-edit-recovery-{stamp}-{n}.toml"),
+        };
+        let path = dir.join(name);
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                attempt += 1;
+                continue;
+            }
+            Err(source) => return Err(FnoxError::OutputWriteFailed { path, source }),
+        };
+        file.write_all(format!("{header}{doc}").as_bytes())
+            .map_err(|source| FnoxError::OutputWriteFailed {
+                path: path.clone(),
+                source,
+            })?;
+        return Ok(path);
+    }
+}
+
+/// The top-level secrets table and each profile's, by profile name ("default"
+/// for top-level secrets). Each may be a `[secrets]` table, an inline table or
+/// a dotted key like `secrets.KEY.value`.
+fn secrets_tables_mut(doc: &mut DocumentMut) -> Vec<(String, &mut dyn TableLike)> {
+    let mut tables = Vec::new();
+    for (key, item) in doc.iter_mut() {
+        match key.get() {
+            "secrets" => {
+                if let Some(secrets_table) = item.as_table_like_mut() {
+                    tables.push(("default".to_string(), secrets_table));
+                }
+            }
+            "profiles" => {
+                let Some(profiles_table) = item.as_table_like_mut() else {
+                    continue;
+                };
+                for (profile_name, profile_item) in profiles_table.iter_mut() {
+                    if let Some(secrets_table) = profile_item
+                        .as_table_like_mut()
+                        .and_then(|profile| profile.get_mut("secrets"))
+                        .and_then(Item::as_table_like_mut)
+                    {
+                        tables.push((profile_name.get().to_string(), secrets_table));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    fn edit_command() -> EditCommand {
+        EditCommand {
+            timeout: "30s".to_string(),
+        }
+    }
+
+    /// Secrets in each syntax a config file can use. String shorthand
+    /// (`KEY = "value"`) isn't valid secret config, so it's not covered.
+    fn config_toml() -> String {
+        format!(
+            r#"default_provider = "age"
+
+[providers.age]
+type = "age"
+recipients = ["{RECIPIENT}"]
+
+[secrets]
+INLINE = {{ provider = "age", value = "enc-inline" }} # inline
+DOTTED.value = "enc-dotted" # dotted
+DOTTED.description = "written as dotted keys"
+
+[secrets.TABLE]
+provider = "age"
+value = "enc-table" # table
+
+[profiles.prod]
+secrets = {{ PROD_INLINE = {{ value = "enc-prod" }} }}
+"#
+        )
+    }
+
+    fn entry(profile: &str, key: &str, config: &Config, plaintext: &str) -> SecretEntry {
+        let original_config = match profile {
+            "default" => config.secrets[key].clone(),
+            _ => config.profiles[profile].secrets[key].clone(),
+        };
+        SecretEntry {
+            profile: profile.to_string(),
+            key: key.to_string(),
+            original_config,
+            plaintext_value: Some(plaintext.to_string()),
+            is_read_only: false,
+            provider_name: Some("age".to_string()),
+        }
+    }
+
+    fn entries(config: &Config) -> Vec<SecretEntry> {
+        vec![
+            entry("default", "INLINE", config, "inline-secret"),
+            entry("default", "DOTTED", config, "dotted-secret"),
+            entry("default", "TABLE", config, "table-secret"),
+            entry("prod", "PROD_INLINE", config, "prod-secret"),
+        ]
+    }
+
+    fn parse(content: &str) -> (DocumentMut, Config) {
+        let doc = content.parse::<DocumentMut>().unwrap();
+        let config = toml_edit::de::from_str(content).unwrap();
+        (doc, config)
+    }
+
+    #[test]
+    fn decrypts_every_secret_syntax_in_place() {
+        let (doc, config) = parse(&config_toml());
+
+        let decrypted = edit_command()
+            .decrypted_document(&doc, &entries(&config))
+            .unwrap()
+            .to_string();
+
+        let expected = config_toml()
+            .replace("enc-inline", "inline-secret")
+            .replace("enc-dotted", "dotted-secret")
+            .replace("enc-table", "table-secret")
+            .replace("enc-prod", "prod-secret");
+        assert_eq!(decrypted, expected);
+    }
+
+    #[tokio::test]
+    async fn unchanged_secrets_round_trip_byte_for_byte() {
+        let (doc, config) = parse(&config_toml());
+        let all_secrets = entries(&config);
+        let mut edited = edit_command()
+            .decrypted_document(&doc, &all_secrets)
+            .unwrap();
+
+        let failures = edit_command()
+            .reencrypt_secrets(&config, &mut edited, &all_secrets, TIMEOUT)
+            .await
+            .unwrap();
+        assert!(failures.is_empty(), "{failures:?}");
+
+        assert_eq!(edited.to_string(), config_toml());
+    }
+
+    #[tokio::test]
+    async fn changed_secrets_are_reencrypted_in_their_own_syntax() {
+        let (doc, config) = parse(&config_toml());
+        let all_secrets = entries(&config);
+        let decrypted = edit_command()
+            .decrypted_document(&doc, &all_secrets)
+            .unwrap()
+            .to_string()
+            .replace("dotted-secret", "new-dotted")
+            .replace("table-secret", "new-table");
+        let (mut edited, modified_config) = parse(&decrypted);
+
+        let failures = edit_command()
+            .reencrypt_secrets(&modified_config, &mut edited, &all_secrets, TIMEOUT)
+            .await
+            .unwrap();
+        assert!(failures.is_empty(), "{failures:?}");
+
+        let output = edited.to_string();
+        assert!(!output.contains("new-dotted"), "{output}");
+        assert!(!output.contains("new-table"), "{output}");
+        assert!(
+            output.contains(r#"INLINE = { provider = "age", value = "enc-inline" } # inline"#),
+            "{output}"
+        );
+        assert!(
+            output.contains(r#"PROD_INLINE = { value = "enc-prod" }"#),
+            "{output}"
+        );
+
+        let reloaded: Config = toml_edit::de::from_str(&output).unwrap();
+        for key in ["DOTTED", "TABLE"] {
+            let ciphertext = reloaded.secrets[key].value().unwrap();
+            let stanzas = crate::providers::age::ciphertext_stanzas(ciphertext).unwrap();
+            assert_eq!(stanzas.len(), 1, "{key}");
+        }
+
+        // Each secret keeps the syntax and comments it was written with
+        let lines: Vec<_> = output.lines().collect();
+        let dotted = lines
+            .iter()
+            .find(|l| l.starts_with("DOTTED.value = "))
+            .unwrap();
+        assert!(dotted.ends_with(" # dotted"), "{dotted}");
+        assert!(lines.contains(&r#"DOTTED.description = "written as dotted keys""#));
+        assert!(lines.contains(&"[secrets.TABLE]"));
+        let table = lines.iter().find(|l| l.starts_with("value = ")).unwrap();
+        assert!(table.ends_with(" # table"), "{table}");
+    }
+
+    #[tokio::test]
+    async fn failed_secrets_keep_their_stored_value_and_are_recovered() {
+        let content = format!(
+            r#"[providers.age]
+type = "age"
+recipients = ["{RECIPIENT}"]
+
+[providers.broken]
+type = "age"
+recipients = ["not-an-age-recipient"]
+
+[secrets]
+GOOD = {{ provider = "age", value = "enc-good" }}
+BAD = {{ provider = "broken", value = "enc-bad" }}
+
+[profiles.prod.secrets]
+PROD_BAD = {{ provider = "broken", value = "enc-prod" }}
+"#
+        );
+        let (doc, config) = parse(&content);
+        let all_secrets = vec![
+            entry("default", "GOOD", &config, "good-secret"),
+            entry("default", "BAD", &config, "bad-secret"),
+            entry("prod", "PROD_BAD", &config, "prod-secret"),
+        ];
+        let decrypted = edit_command()
+            .decrypted_document(&doc, &all_secrets)
+            .unwrap()
+            .to_string()
+            .replace("good-secret", "new-good")
+            .replace("bad-secret", "new-bad")
+            .replace("prod-secret", "new-prod")
+            .replace(
+                "[profiles.prod.secrets]\n",
+                "NEW = { provider = \"broken\", value = \"new-secret\" }\n\n[profiles.prod.secrets]\n",
+            );
+        let (mut edited, modified_config) = parse(&decrypted);
+
+        let failures = edit_command()
+            .reencrypt_secrets(&modified_config, &mut edited, &all_secrets, TIMEOUT)
+            .await
+            .unwrap();
+
+        // The secret whose provider works is saved, the others keep their
+        // stored value or are left out, and no plaintext reaches the config
+        let output = edited.to_string();
+        for plaintext in ["new-good", "new-bad", "new-prod", "new-secret"] {
+            assert!(!output.contains(plaintext), "{output}");
+        }
+        let reloaded: Config = toml_edit::de::from_str(&output).unwrap();
+        let good = reloaded.secrets["GOOD"].value().unwrap();
+        assert_eq!(
+            crate::providers::age::ciphertext_stanzas(good)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(reloaded.secrets["BAD"].value(), Some("enc-bad"));
+        assert!(!reloaded.secrets.contains_key("NEW"));
+        assert_eq!(
+            reloaded.profiles["prod"].secrets["PROD_BAD"].value(),
+            Some("enc-prod")
+        );
+
+        let failed: Vec<_> = failures
+            .iter()
+            .map(|f| (f.profile.as_str(), f.key.as_str(), f.plaintext.as_str()))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                ("default", "BAD", "new-bad"),
+                ("default", "NEW", "new-secret"),
+                ("prod", "PROD_BAD", "new-prod"),
+            ]
+        );
+        assert!(failures.iter().all(|f| f.reason.contains("'broken'")));
+
+        // Every failed edit is in the recovery file, under its profile
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_recovery_file(dir.path(), Path::new("fnox.toml"), &failures).unwrap();
+        let recovered = fs::read_to_string(&path).unwrap();
+        assert!(recovered.starts_with("# fnox edit couldn't save these secrets to fnox.toml:"));
+        let recovered: Config = toml_edit::de::from_str(&recovered).unwrap();
+        assert_eq!(recovered.secrets["BAD"].value(), Some("new-bad"));
+        assert_eq!(recovered.secrets["NEW"].value(), Some("new-secret"));
+        assert_eq!(
+            recovered.profiles["prod"].secrets["PROD_BAD"].value(),
+            Some("new-prod")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // A second failure in the same second gets its own file
+        let again = write_recovery_file(dir.path(), Path::new("fnox.toml"), &failures).unwrap();
+        assert_ne!(again, path);
+        assert!(fs::read_to_string(&path).unwrap().contains("new-bad"));
+    }
+}
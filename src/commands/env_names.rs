@@ -0,0 +1,130 @@
+use crate::error::{FnoxError, Result};
+use clap::Args;
+use std::collections::HashMap;
+
+/// Options for renaming secrets on their way into the environment, shared by
+/// `export` and `exec`. `export` declares `--map` and `--prefix` itself so they
+/// sort among its other flags, and builds this from them.
+#[derive(Debug, Clone, Default, Args)]
+pub struct EnvNameArgs {
+    /// Export a secret under a different name (FROM=TO, repeatable; TO is not prefixed)
+    #[arg(long = "map", value_name = "FROM=TO", value_parser = parse_mapping)]
+    pub map: Vec<(String, String)>,
+
+    /// Prefix to add to every exported variable name
+    #[arg(long)]
+    pub prefix: Option<String>,
+}
+
+impl EnvNameArgs {
+    /// Work out the variable name for each secret key.
+    ///
+    /// `--map` entries take precedence and are used as-is; every other key gets
+    /// `--prefix`. Two secrets ending up with the same name is an error, and a
+    /// `--map` for a key that isn't being exported only warns so one set of flags
+    /// can be reused across profiles.
+    pub fn env_names<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashMap<String, String>> {
+        let keys: Vec<&str> = keys.into_iter().collect();
+
+        for (from, _) in &self.map {
+            if !keys.contains(&from.as_str()) {
+                tracing::warn!("--map {from}: no secret named '{from}' is being exported");
+            }
+        }
+
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+        let mut names = HashMap::with_capacity(keys.len());
+        let mut owners: HashMap<String, &str> = HashMap::with_capacity(keys.len());
+        for key in keys {
+            let name = match self.map.iter().rev().find(|(from, _)| from == key) {
+                Some((_, to)) => to.clone(),
+                None => format!("{prefix}{key}"),
+            };
+            if let Some(first) = owners.insert(name.clone(), key) {
+                return Err(FnoxError::EnvNameCollision {
+                    name,
+                    first: first.to_string(),
+                    second: key.to_string(),
+                });
+            }
+            names.insert(key.to_string(), name);
+        }
+        Ok(names)
+    }
+}
+
+pub(crate) fn parse_mapping(value: &str) -> std::result::Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((from.to_string(), to.to_string()))
+        }
+        _ => Err(format!("expected FROM=TO, got '{value}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(prefix: Option<&str>, map: &[(&str, &str)]) -> EnvNameArgs {
+        EnvNameArgs {
+            prefix: prefix.map(str::to_string),
+            map: map
+                .iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn prefix_applies_to_unmapped_keys() {
+        let names = args(Some("APP_"), &[("SECRET", "RENAMED")])
+            .env_names(["DATABASE_URL", "SECRET"])
+            .unwrap();
+        assert_eq!(names["DATABASE_URL"], "APP_DATABASE_URL");
+        assert_eq!(names["SECRET"], "RENAMED");
+    }
+
+    #[test]
+    fn no_options_keeps_names() {
+        let names = EnvNameArgs::default().env_names(["A", "B"]).unwrap();
+        assert_eq!(names["A"], "A");
+        assert_eq!(names["B"], "B");
+    }
+
+    #[test]
+    fn collisions_are_errors() {
+        let err = args(None, &[("A", "B")]).env_names(["A", "B"]).unwrap_err();
+        assert!(matches!(
+            err,
+            FnoxError::EnvNameCollision { ref name, .. } if name == "B"
+        ));
+
+        assert!(
+            args(Some("APP_"), &[("A", "APP_B")])
+                .env_names(["A", "B"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn unknown_map_key_is_not_an_error() {
+        let names = args(None, &[("MISSING", "X")]).env_names(["A"]).unwrap();
+        assert_eq!(names.len(), 1);
+        assert_eq!(names["A"], "A");
+    }
+
+    #[test]
+    fn parse_mapping_requires_both_sides() {
+        assert_eq!(
+            parse_mapping("DATABASE_URL=APP_DB").unwrap(),
+            ("DATABASE_URL".to_string(), "APP_DB".to_string())
+        );
+        assert!(parse_mapping("DATABASE_URL").is_err());
+        assert!(parse_mapping("=APP_DB").is_err());
+        assert!(parse_mapping("DATABASE_URL=").is_err());
+    }
+}
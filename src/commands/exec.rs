@@ -1,10 +1,18 @@
+use crate::commands::env_names::EnvNameArgs;
 use crate::error::{FnoxError, Result};
 use crate::lease::{self, LeaseLedger};
+use crate::redact::{self, Redactor};
+use crate::settings::Settings;
 use crate::temp_file_secrets::create_ephemeral_secret_file;
 use crate::{commands::Cli, config::Config};
 use clap::{Args, ValueHint};
 use std::collections::HashSet;
-use std::process::Command;
+use std::io;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 
 #[derive(Debug, Args)]
@@ -13,6 +21,45 @@ pub struct ExecCommand {
     /// Command to run
     #[arg(trailing_var_arg = true, allow_hyphen_values = true, value_hint = ValueHint::CommandWithArguments)]
     pub command: Vec<String>,
+
+    /// Run the command once for every profile
+    #[arg(long)]
+    pub each_profile: bool,
+
+    #[command(flatten)]
+    pub env_names: EnvNameArgs,
+
+    /// Profiles to run with --each-profile (comma-separated, default: all)
+    #[arg(long, value_delimiter = ',', requires = "each_profile")]
+    pub profiles: Vec<String>,
+
+    /// Mask secret values in the command's stdout and stderr
+    #[arg(long)]
+    pub redact: bool,
+
+    /// Re-run the command up to N more times while it fails, resolving secrets again each time
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retry_child: u32,
+
+    /// How long to wait between --retry-child attempts, such as "5s" or "1m"
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "1s",
+        requires = "retry_child"
+    )]
+    pub retry_delay: String,
+
+    /// Pass a provider's session to the command, so tools it runs (op, vault,
+    /// infisical) don't have to log in again. Repeatable. The command and
+    /// anything it starts can use the session, so only share it with commands
+    /// you trust.
+    #[arg(long, value_name = "PROVIDER")]
+    pub share_session: Vec<String>,
+
+    /// Stop after the first profile whose command fails
+    #[arg(long, requires = "each_profile")]
+    pub stop_on_error: bool,
 }
 
 impl ExecCommand {
@@ -21,11 +68,201 @@ impl ExecCommand {
             return Err(FnoxError::CommandNotSpecified);
         }
 
+        if self.each_profile {
+            return self.run_each_profile(cli, &config).await;
+        }
+
         let profile = Config::get_profile(cli.profile.as_deref());
+        let status = self.run_with_retries(cli, &config, &profile).await?;
+
+        if !status.success() {
+            // Exit silently — the child already printed its own errors.
+            std::process::exit(exit_code(status));
+        }
+
+        Ok(())
+    }
+
+    /// Run the command once per profile, reporting each exit status and
+    /// exiting non-zero if any run failed.
+    async fn run_each_profile(&self, cli: &Cli, config: &Config) -> Result<()> {
+        let profiles = if self.profiles.is_empty() {
+            let mut names = vec!["default".to_string()];
+            names.extend(config.profiles.keys().cloned());
+            names.sort();
+            names.dedup();
+            names
+        } else {
+            self.profiles.clone()
+        };
+
+        // A profile whose secrets can't be resolved counts as failed, like
+        // one whose command fails, rather than stopping the rest
+        let mut failed: Vec<String> = Vec::new();
+        for profile in &profiles {
+            eprintln!("==> {profile}");
+            match self.run_with_retries(cli, config, profile).await {
+                Ok(status) if status.success() => {
+                    eprintln!("==> {profile}: ok");
+                    continue;
+                }
+                Ok(status) => {
+                    eprintln!("==> {profile}: failed (exit code {})", exit_code(status));
+                }
+                Err(e) => eprintln!("==> {profile}: failed: {e}"),
+            }
+            failed.push(profile.clone());
+            if self.stop_on_error {
+                break;
+            }
+        }
+
+        if !failed.is_empty() {
+            eprintln!(
+                "{} of {} profile(s) failed: {}",
+                failed.len(),
+                profiles.len(),
+                failed.join(", ")
+            );
+            std::process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    /// Run the command for `profile`, and with --retry-child re-run it while
+    /// it fails. Each retry resolves secrets again, bypassing the daemon's
+    /// cache, since they may have rotated. SIGINT or SIGTERM stops retrying;
+    /// the exit status is the last attempt's.
+    async fn run_with_retries(
+        &self,
+        cli: &Cli,
+        config: &Config,
+        profile: &str,
+    ) -> Result<ExitStatus> {
+        if self.retry_child == 0 {
+            return self.run_for_profile(cli, config, profile, false).await;
+        }
+        let delay = crate::daemon::parse_duration(&self.retry_delay)?;
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        let signal_ids = [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM]
+            .map(|signal| signal_hook::flag::register(signal, interrupted.clone()).ok());
+
+        let result = self
+            .retry_loop(cli, config, profile, delay, &interrupted)
+            .await;
+
+        #[cfg(unix)]
+        for id in signal_ids.into_iter().flatten() {
+            signal_hook::low_level::unregister(id);
+        }
+        result
+    }
+
+    async fn retry_loop(
+        &self,
+        cli: &Cli,
+        config: &Config,
+        profile: &str,
+        delay: Duration,
+        interrupted: &AtomicBool,
+    ) -> Result<ExitStatus> {
+        let attempts = self.retry_child + 1;
+        let mut attempt = 1;
+        loop {
+            let status = self
+                .run_for_profile(cli, config, profile, attempt > 1)
+                .await?;
+            if status.success() {
+                if attempt > 1 {
+                    eprintln!("==> attempt {attempt} of {attempts} succeeded");
+                }
+                return Ok(status);
+            }
+            if attempt == attempts {
+                eprintln!(
+                    "==> all {attempts} attempts failed (exit code {})",
+                    exit_code(status)
+                );
+                return Ok(status);
+            }
+            if !interrupted.load(Ordering::SeqCst) {
+                eprintln!(
+                    "==> attempt {attempt} of {attempts} failed (exit code {}), retrying in {}",
+                    exit_code(status),
+                    self.retry_delay
+                );
+                sleep_unless_interrupted(delay, interrupted).await;
+            }
+            if interrupted.load(Ordering::SeqCst) {
+                eprintln!("==> interrupted after attempt {attempt} of {attempts}");
+                return Ok(status);
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Session variables from the `--share-session` providers
+    async fn shared_sessions(
+        &self,
+        config: &Config,
+        profile: &str,
+    ) -> Result<Vec<(String, String)>> {
+        if self.share_session.is_empty() {
+            return Ok(Vec::new());
+        }
+        let providers = config.get_providers(profile);
+        let mut vars = Vec::new();
+        for name in &self.share_session {
+            let provider_config =
+                providers
+                    .get(name)
+                    .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                        provider: name.clone(),
+                        profile: profile.to_string(),
+                        config_path: None,
+                        suggestion: None,
+                    })?;
+            let provider =
+                crate::providers::get_provider_resolved(config, profile, name, provider_config)
+                    .await?;
+            let session = provider.session_env().await?;
+            if session.is_empty() {
+                return Err(FnoxError::Config(format!(
+                    "Provider '{}' ({}) has no session to share; --share-session supports \
+                     1password, vault and infisical providers that have authenticated",
+                    name,
+                    provider_config.provider_type()
+                )));
+            }
+            // Only the names: the values are credentials
+            tracing::debug!(
+                "Sharing the session of provider '{}' as {}",
+                name,
+                session
+                    .iter()
+                    .map(|(var, _)| var.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            vars.extend(session);
+        }
+        Ok(vars)
+    }
+
+    async fn run_for_profile(
+        &self,
+        cli: &Cli,
+        config: &Config,
+        profile: &str,
+        fresh: bool,
+    ) -> Result<ExitStatus> {
         tracing::debug!("Running command with secrets from profile '{}'", profile);
 
         // Get the profile secrets
-        let profile_secrets = config.get_secrets(&profile)?;
+        let profile_secrets = config.get_secrets(profile)?;
 
         let cmd_name = &self.command[0];
 
@@ -40,11 +277,20 @@ impl ExecCommand {
             cmd.args(&self.command[1..]);
         }
 
-        // Resolve secrets using batch resolution first
-        let resolved_secrets = crate::daemon::resolve_batch(
-            cli,
-            &config,
-            &profile,
+        // Overrides replace secrets for this run only; the command sees the
+        // secret's own name, never FNOX_OVERRIDE_*
+        for name in crate::secret_resolver::override_vars() {
+            cmd.env_remove(name);
+        }
+
+        // Resolve secrets using batch resolution first. A retry resolves
+        // them directly rather than reuse the daemon's cached values.
+        let mut ctx = crate::daemon::ResolveContext::from_cli(cli);
+        ctx.no_daemon |= fresh;
+        let (resolved_secrets, profile_secrets) = crate::daemon::resolve_batch_with_configs(
+            &ctx,
+            config,
+            profile,
             &profile_secrets,
             crate::daemon::Purpose::Exec,
             true,
@@ -58,11 +304,14 @@ impl ExecCommand {
         // don't overwrite short-lived lease credentials with long-lived master ones
         let mut lease_keys: HashSet<String> = HashSet::new();
 
+        // Values to mask in the command's output with --redact
+        let mut redact_values: Vec<String> = Vec::new();
+
         // Resolve leases if configured.
         // Temporarily set resolved secrets as process env vars so lease backend
         // SDKs (AWS, GCP, Azure) can find master credentials during lease creation.
         // The TempEnvGuard ensures cleanup on all exit paths (including errors).
-        let leases = config.get_leases(&profile);
+        let leases = config.get_leases(profile);
         let mut _temp_env_guard = lease::TempEnvGuard::default();
         if !leases.is_empty() {
             _temp_files.extend(lease::set_secrets_as_env(
@@ -70,7 +319,7 @@ impl ExecCommand {
                 &profile_secrets,
                 &mut _temp_env_guard,
             )?);
-            let project_dir = lease::project_dir_from_config(&config, &cli.config);
+            let project_dir = lease::project_dir_from_config(config, &cli.config);
             // Each resolve_lease call manages its own short-lived ledger locks.
             // Leases are processed sequentially; no shared lock is needed.
             for (name, lease_config) in &leases {
@@ -103,8 +352,8 @@ impl ExecCommand {
                 let creds = lease::resolve_lease(
                     name,
                     lease_config,
-                    &config,
-                    &profile,
+                    config,
+                    profile,
                     &project_dir,
                     prereq_missing.as_deref(),
                     "exec",
@@ -113,19 +362,37 @@ impl ExecCommand {
                 .await?;
                 for (cred_key, cred_value) in creds {
                     lease_keys.insert(cred_key.clone());
+                    if self.redact {
+                        redact_values.push(cred_value.clone());
+                    }
                     cmd.env(cred_key, cred_value);
                 }
             }
         }
 
+        // Secrets with env=false are never set, so they can't collide
+        let env_names = self.env_names.env_names(
+            resolved_secrets
+                .iter()
+                .filter(|(key, value)| {
+                    value.is_some() && profile_secrets.get(*key).is_none_or(|s| s.env)
+                })
+                .map(|(key, _)| key.as_str()),
+        )?;
+
+        if self.redact {
+            redact_values.extend(resolved_secrets.values().flatten().cloned());
+        }
+
         // Add resolved secrets as environment variables
         for (key, value) in resolved_secrets {
+            let name = env_names.get(&key).cloned().unwrap_or_else(|| key.clone());
             // Skip secrets whose keys were already set by lease backends.
             // This MUST come before env=false: if a master credential has
             // env=false and the lease backend produced a short-lived credential
             // under the same key (e.g., AWS_ACCESS_KEY_ID), calling env_remove
             // here would strip the lease credential that cmd.env() already set.
-            if lease_keys.contains(&key) {
+            if lease_keys.contains(&name) {
                 tracing::debug!("Skipping secret '{}': already set by lease backend", key);
                 continue;
             }
@@ -152,71 +419,127 @@ impl ExecCommand {
                         );
 
                         // Set env var to the file path
-                        cmd.env(key, file_path);
+                        cmd.env(name, file_path);
 
                         // Keep the temp file alive
                         _temp_files.push(temp_file);
                     } else {
                         // Set env var to the secret value directly
-                        cmd.env(key, value);
+                        cmd.env(name, value);
                     }
                 } else {
-                    cmd.env(key, value);
+                    cmd.env(name, value);
                 }
             }
         }
 
+        // Explicitly shared provider sessions win over secrets of the same name
+        for (name, value) in self.shared_sessions(config, profile).await? {
+            if self.redact {
+                redact_values.push(value.clone());
+            }
+            cmd.env(name, value);
+        }
+
         // Drop the temp env guard BEFORE spawning the child process.
         // This removes temporary secrets (including env=false master credentials)
         // from the parent process environment so the child doesn't inherit them.
         drop(_temp_env_guard);
 
+        let redactor = if self.redact {
+            let min_length = redact::parse_min_length(&Settings::try_get()?.redact_min_length)?;
+            Redactor::new(redact_values.iter().map(String::as_str), min_length)?
+        } else {
+            None
+        };
+        if redactor.is_some() {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+
         let mut child = cmd.spawn().map_err(|e| FnoxError::CommandExecutionFailed {
             command: self.command.join(" "),
             source: e,
         })?;
 
+        // Copy the child's output through the redactor on background threads
+        // so neither pipe fills up and blocks the child
+        let mut pumps: Vec<JoinHandle<io::Result<()>>> = Vec::new();
+        if let Some(redactor) = redactor.map(Arc::new) {
+            if let Some(stdout) = child.stdout.take() {
+                let redactor = redactor.clone();
+                pumps.push(std::thread::spawn(move || {
+                    redactor.stream(stdout, io::stdout())
+                }));
+            }
+            if let Some(stderr) = child.stderr.take() {
+                pumps.push(std::thread::spawn(move || {
+                    redactor.stream(stderr, io::stderr())
+                }));
+            }
+        }
+
         // Forward SIGINT/SIGTERM to the child so Ctrl-C and `kill` reach it.
         #[cfg(unix)]
-        {
+        let signal_ids = {
             let child_pid = nix::unistd::Pid::from_raw(child.id() as i32);
             unsafe {
                 // Ignore signals in the parent — the child handles them.
                 // When the child exits we propagate its exit code below.
-                signal_hook::low_level::register(signal_hook::consts::SIGINT, move || {
-                    nix::sys::signal::kill(child_pid, nix::sys::signal::SIGINT).ok();
-                })
-                .ok();
-                signal_hook::low_level::register(signal_hook::consts::SIGTERM, move || {
-                    nix::sys::signal::kill(child_pid, nix::sys::signal::SIGTERM).ok();
-                })
-                .ok();
+                [
+                    signal_hook::low_level::register(signal_hook::consts::SIGINT, move || {
+                        nix::sys::signal::kill(child_pid, nix::sys::signal::SIGINT).ok();
+                    })
+                    .ok(),
+                    signal_hook::low_level::register(signal_hook::consts::SIGTERM, move || {
+                        nix::sys::signal::kill(child_pid, nix::sys::signal::SIGTERM).ok();
+                    })
+                    .ok(),
+                ]
+            }
+        };
+
+        let status = child.wait();
+
+        for pump in pumps {
+            if let Ok(Err(e)) = pump.join() {
+                tracing::debug!("Failed to copy redacted output: {}", e);
             }
         }
 
-        let status = child
-            .wait()
-            .map_err(|e| FnoxError::CommandExecutionFailed {
-                command: self.command.join(" "),
-                source: e,
-            })?;
+        // Stop forwarding signals to this child; with --each-profile the next
+        // child gets its own handlers.
+        #[cfg(unix)]
+        for id in signal_ids.into_iter().flatten() {
+            signal_hook::low_level::unregister(id);
+        }
 
         // Temp files are cleaned up when _temp_files drops here
         drop(_temp_files);
 
-        if !status.success() {
-            // Exit silently — the child already printed its own errors.
-            #[cfg(unix)]
-            {
-                use std::os::unix::process::ExitStatusExt;
-                // If killed by signal, exit with 128+signal (standard convention)
-                if let Some(sig) = status.signal() {
-                    std::process::exit(128 + sig);
-                }
-            }
-            std::process::exit(status.code().unwrap_or(1));
-        }
+        status.map_err(|e| FnoxError::CommandExecutionFailed {
+            command: self.command.join(" "),
+            source: e,
+        })
+    }
+}
 
-        Ok(())
+/// Wait `delay`, returning early once `interrupted` is set
+async fn sleep_unless_interrupted(delay: Duration, interrupted: &AtomicBool) {
+    let deadline = tokio::time::Instant::now() + delay;
+    while !interrupted.load(Ordering::SeqCst) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100).min(delay)).await;
+    }
+}
+
+/// Map a child's exit status to the code fnox should exit with.
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        // If killed by signal, exit with 128+signal (standard convention)
+        if let Some(sig) = status.signal() {
+            return 128 + sig;
+        }
     }
+    status.code().unwrap_or(1)
 }
@@ -1,13 +1,18 @@
 use crate::commands::Cli;
+use crate::commands::env_names::{self, EnvNameArgs};
 use crate::config::Config;
+use crate::dotenv;
 use crate::error::{FnoxError, Result};
-use crate::shell;
+use crate::settings::Settings;
 use crate::temp_file_secrets::create_persistent_secret_file;
+use crate::{git, shell};
 use clap::{Args, ValueEnum};
 use console;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use strum::{Display, EnumString, VariantNames};
 
 /// Supported export formats
@@ -16,6 +21,10 @@ use strum::{Display, EnumString, VariantNames};
 pub enum ExportFormat {
     /// Environment variable format (KEY=value)
     Env,
+    /// docker-compose env_file format (KEY=value with `$` escaped as `$$`)
+    Compose,
+    /// `docker run --env-file` format (bare KEY=value, no quoting)
+    Docker,
     /// POSIX shell format (export KEY=value)
     Shell,
     /// JSON format
@@ -24,6 +33,22 @@ pub enum ExportFormat {
     Yaml,
     /// TOML format
     Toml,
+    /// Kubernetes Secret manifest (needs --name)
+    K8s,
+    /// Masked preview for code review: provider, length and a salted hash per key
+    Preview,
+}
+
+/// Order of exported secrets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportSort {
+    /// Sorted by exported name
+    #[default]
+    Key,
+    /// The order secrets appear in the merged config
+    Config,
+    /// Grouped by provider, then sorted by exported name
+    Provider,
 }
 
 /// Export secrets in various formats
@@ -41,6 +66,38 @@ pub struct ExportCommand {
     /// Output file (default: stdout)
     #[arg(short = 'o', long)]
     output: Option<PathBuf>,
+
+    /// Compare against the preview in --output instead of writing it, failing on drift
+    #[arg(long, requires = "output")]
+    check: bool,
+
+    /// Write --output even where git would commit it
+    #[arg(long, requires = "output")]
+    force: bool,
+
+    /// Export a secret under a different name (FROM=TO, repeatable; TO is not prefixed)
+    #[arg(long, value_name = "FROM=TO", value_parser = env_names::parse_mapping)]
+    map: Vec<(String, String)>,
+
+    /// metadata.name of the Secret, for --format k8s
+    #[arg(long)]
+    name: Option<String>,
+
+    /// metadata.namespace of the Secret, for --format k8s
+    #[arg(long)]
+    namespace: Option<String>,
+
+    /// Prefix to add to every exported variable name
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Order of exported secrets
+    #[arg(long, default_value = "key", value_enum)]
+    sort: ExportSort,
+
+    /// Write plain values under stringData instead of base64 under data, for --format k8s
+    #[arg(long)]
+    string_data: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -49,6 +106,28 @@ struct ExportData {
     metadata: Option<ExportMetadata>,
 }
 
+/// A Kubernetes `Secret` manifest
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sSecret<'a> {
+    api_version: &'static str,
+    kind: &'static str,
+    metadata: K8sMetadata<'a>,
+    #[serde(rename = "type")]
+    secret_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<IndexMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    string_data: Option<IndexMap<String, String>>,
+}
+
+#[derive(Serialize)]
+struct K8sMetadata<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<&'a str>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct ExportMetadata {
     profile: String,
@@ -62,10 +141,27 @@ impl ExportCommand {
         tracing::debug!("Exporting secrets from profile '{}'", profile);
 
         let profile_secrets = config.get_secrets(&profile)?;
+        let preview = matches!(self.format, ExportFormat::Preview);
+        if self.check && !preview {
+            return Err(FnoxError::Config(
+                "--check only works with --format preview".to_string(),
+            ));
+        }
+        let k8s = matches!(self.format, ExportFormat::K8s);
+        if !k8s && (self.name.is_some() || self.namespace.is_some() || self.string_data) {
+            return Err(FnoxError::Config(
+                "--name, --namespace and --string-data only work with --format k8s".to_string(),
+            ));
+        }
+        if k8s && self.name.is_none() {
+            return Err(FnoxError::Config(
+                "--format k8s requires --name for the Secret".to_string(),
+            ));
+        }
 
         // Resolve secrets using batch resolution for better performance
-        let resolved_secrets = crate::daemon::resolve_batch(
-            cli,
+        let (resolved_secrets, profile_secrets) = crate::daemon::resolve_batch_with_configs(
+            &crate::daemon::ResolveContext::from_cli(cli),
             &config,
             &profile,
             &profile_secrets,
@@ -81,7 +177,9 @@ impl ExportCommand {
             if let Some(value) = value_opt {
                 // Check if this secret should be file-based
                 if let Some(secret_config) = profile_secrets.get(&key) {
-                    if secret_config.as_file {
+                    // Previews hash the value itself, not a temp file's path, and
+                    // a manifest carries the value into the cluster
+                    if secret_config.as_file && !preview && !k8s {
                         // Create a persistent temp file for this secret
                         match create_persistent_secret_file("fnox-export-", &key, &value) {
                             Ok(file_path) => {
@@ -107,6 +205,31 @@ impl ExportCommand {
             }
         }
 
+        let default_provider = config.get_default_provider(&profile).ok().flatten();
+        let mut providers: HashMap<String, String> = HashMap::new();
+
+        // Rename before formatting so every format sees the same keys
+        let env_name_args = EnvNameArgs {
+            map: self.map.clone(),
+            prefix: self.prefix.clone(),
+        };
+        let mut env_names = env_name_args.env_names(secrets.keys().map(String::as_str))?;
+        let mut secrets: IndexMap<String, String> = secrets
+            .into_iter()
+            .map(|(key, value)| {
+                let provider = profile_secrets
+                    .get(&key)
+                    .and_then(|secret| secret.provider().map(str::to_string))
+                    .or_else(|| default_provider.clone());
+                let name = env_names.remove(&key).unwrap_or(key);
+                if let Some(provider) = provider {
+                    providers.insert(name.clone(), provider);
+                }
+                (name, value)
+            })
+            .collect();
+        sort_secrets(&mut secrets, self.sort, &providers);
+
         let metadata = Some(ExportMetadata {
             profile: profile.clone(),
             exported_at: chrono::Utc::now().to_rfc3339(),
@@ -115,14 +238,49 @@ impl ExportCommand {
 
         let export_data = ExportData { secrets, metadata };
 
+        if preview {
+            // Keep the committed preview's salt, so unchanged values hash the same
+            let committed = self
+                .output
+                .as_ref()
+                .and_then(|path| std::fs::read_to_string(path).ok());
+            let salt = committed.as_deref().and_then(preview_salt);
+            if self.check {
+                let path = self.output.as_ref().expect("--check requires --output");
+                let (Some(committed), Some(salt)) = (&committed, salt) else {
+                    return Err(FnoxError::Config(format!(
+                        "No export preview found at {}",
+                        path.display()
+                    )));
+                };
+                let live = render_preview(&export_data, &providers, salt);
+                return check_preview(committed, &live, path);
+            }
+            let salt = salt.map(str::to_string).unwrap_or_else(|| {
+                let salt: [u8; 16] = rand::random();
+                data_encoding::HEXLOWER.encode(&salt)
+            });
+            let output = render_preview(&export_data, &providers, &salt);
+            return self.write_output(&export_data, &output);
+        }
+
         let output = match self.format {
             ExportFormat::Env => self.export_as_env(&export_data),
+            ExportFormat::Compose => self.export_as_compose(&export_data),
+            ExportFormat::Docker => self.export_as_docker(&export_data),
             ExportFormat::Shell => self.export_as_shell(&export_data),
             ExportFormat::Json => self.export_as_json(&export_data),
             ExportFormat::Yaml => self.export_as_yaml(&export_data),
             ExportFormat::Toml => self.export_as_toml(&export_data),
+            ExportFormat::K8s => self.export_as_k8s(&export_data),
+            ExportFormat::Preview => unreachable!("previews are rendered above"),
         }?;
 
+        self.write_output(&export_data, &output)
+    }
+
+    /// Write `output` to --output, or stdout
+    fn write_output(&self, export_data: &ExportData, output: &str) -> Result<()> {
         match &self.output {
             Some(path) => {
                 if self.dry_run {
@@ -137,8 +295,18 @@ impl ExportCommand {
                         println!("  {}", console::style(key).dim());
                     }
                 } else {
+                    // A preview holds no values and is meant to be committed
+                    if !self.force
+                        && !matches!(self.format, ExportFormat::Preview)
+                        && Settings::try_get()?.export_git_guard
+                        && git::is_committable(path)
+                    {
+                        return Err(FnoxError::ExportWouldBeCommitted {
+                            path: path.to_path_buf(),
+                        });
+                    }
                     let path = path.to_path_buf();
-                    std::fs::write(&path, &output)
+                    std::fs::write(&path, output)
                         .map_err(|e| FnoxError::ExportWriteFailed { path, source: e })?;
                     println!(
                         "Secrets exported to: {}",
@@ -162,7 +330,31 @@ impl ExportCommand {
         append_metadata_header(&mut output, data.metadata.as_ref());
 
         for (key, value) in &data.secrets {
-            output.push_str(&format!("{}={}\n", key, dotenv_quote(value)));
+            output.push_str(&format!("{}={}\n", key, dotenv::quote(value)));
+        }
+
+        Ok(output)
+    }
+
+    fn export_as_compose(&self, data: &ExportData) -> Result<String> {
+        let mut output = String::new();
+
+        append_metadata_header(&mut output, data.metadata.as_ref());
+
+        for (key, value) in &data.secrets {
+            output.push_str(&format!("{}={}\n", key, compose_quote(value)));
+        }
+
+        Ok(output)
+    }
+
+    fn export_as_docker(&self, data: &ExportData) -> Result<String> {
+        let mut output = String::new();
+
+        append_metadata_header(&mut output, data.metadata.as_ref());
+
+        for (key, value) in &data.secrets {
+            output.push_str(&docker_env_line(key, value)?);
         }
 
         Ok(output)
@@ -191,6 +383,82 @@ impl ExportCommand {
     fn export_as_toml(&self, data: &ExportData) -> Result<String> {
         toml_edit::ser::to_string_pretty(data).map_err(|source| FnoxError::Toml { source })
     }
+
+    fn export_as_k8s(&self, data: &ExportData) -> Result<String> {
+        let name = self.name.as_deref().expect("--format k8s requires --name");
+        validate_k8s_name("--name", name, 253)?;
+        if let Some(namespace) = &self.namespace {
+            validate_k8s_name("--namespace", namespace, 63)?;
+            if namespace.contains('.') {
+                return Err(FnoxError::Config(format!(
+                    "Invalid --namespace '{}': namespaces can't contain '.'",
+                    namespace
+                )));
+            }
+        }
+
+        let invalid: Vec<&str> = data
+            .secrets
+            .keys()
+            .filter(|key| !is_k8s_secret_key(key))
+            .map(String::as_str)
+            .collect();
+        if !invalid.is_empty() {
+            return Err(FnoxError::Config(format!(
+                "Kubernetes Secret keys may only contain letters, digits, '-', '_' and '.'. Invalid keys: {}",
+                invalid.join(", ")
+            )));
+        }
+
+        use base64::Engine;
+        let (data_entries, string_data) = if self.string_data {
+            (None, Some(data.secrets.clone()))
+        } else {
+            let encoded = data
+                .secrets
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.clone(),
+                        base64::engine::general_purpose::STANDARD.encode(value),
+                    )
+                })
+                .collect();
+            (Some(encoded), None)
+        };
+        let manifest = K8sSecret {
+            api_version: "v1",
+            kind: "Secret",
+            metadata: K8sMetadata {
+                name,
+                namespace: self.namespace.as_deref(),
+            },
+            secret_type: "Opaque",
+            data: data_entries,
+            string_data,
+        };
+
+        let mut output = String::new();
+        append_metadata_header(&mut output, data.metadata.as_ref());
+        output.push_str(&serde_yaml::to_string(&manifest)?);
+        Ok(output)
+    }
+}
+
+/// Reorder secrets so the output doesn't depend on how config files were merged.
+/// Secrets without a provider come first when grouping by provider.
+fn sort_secrets(
+    secrets: &mut IndexMap<String, String>,
+    sort: ExportSort,
+    providers: &HashMap<String, String>,
+) {
+    match sort {
+        ExportSort::Config => {}
+        ExportSort::Key => secrets.sort_keys(),
+        ExportSort::Provider => {
+            secrets.sort_by(|a, _, b, _| (providers.get(a), a).cmp(&(providers.get(b), b)))
+        }
+    }
 }
 
 fn append_metadata_header(output: &mut String, metadata: Option<&ExportMetadata>) {
@@ -202,50 +470,510 @@ fn append_metadata_header(output: &mut String, metadata: Option<&ExportMetadata>
     }
 }
 
-fn dotenv_quote(value: &str) -> String {
-    if !value.is_empty()
-        && value
+/// Quote a value for docker-compose, which interpolates `${VAR}` in env files
+/// unless the `$` is doubled.
+fn compose_quote(value: &str) -> String {
+    dotenv::quote(&value.replace('$', "$$"))
+}
+
+/// A `docker run --env-file` line. Docker takes everything after the first
+/// `=` literally, so values aren't quoted, but a line can't continue onto the
+/// next one: values with line breaks are rejected rather than split.
+fn docker_env_line(key: &str, value: &str) -> Result<String> {
+    let mut chars = key.chars();
+    let valid_key = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(FnoxError::Config(format!(
+            "Cannot export '{}' for docker --env-file: names must be letters, digits and underscores, not starting with a digit",
+            key
+        )));
+    }
+    if value.contains(['\n', '\r']) {
+        return Err(FnoxError::Config(format!(
+            "Cannot export '{}' for docker --env-file: its value spans multiple lines, which env files can't hold. Use --format compose or --format env instead",
+            key
+        )));
+    }
+    Ok(format!("{}={}\n", key, value))
+}
+
+/// Whether `key` can be a key of a Kubernetes Secret's data
+fn is_k8s_secret_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key != "."
+        && key != ".."
+        && key
             .chars()
-            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':'))
-    {
-        return value.to_string();
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Check a Secret name or namespace against Kubernetes' DNS naming rules:
+/// lowercase letters, digits, '-' and '.', starting and ending alphanumeric
+fn validate_k8s_name(flag: &str, name: &str, max_len: usize) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= max_len
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.'))
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric());
+    if valid {
+        Ok(())
+    } else {
+        Err(FnoxError::Config(format!(
+            "Invalid {} '{}': use at most {} lowercase letters, digits, '-' and '.', starting and ending with a letter or digit",
+            flag, name, max_len
+        )))
     }
+}
+
+/// Render a preview of `data` for code review: each key with its provider,
+/// value length and a prefix of the value's sha256, salted with `salt` so the
+/// hashes can't be looked up. There's no timestamp, so it only changes when
+/// the secrets do.
+fn render_preview(data: &ExportData, providers: &HashMap<String, String>, salt: &str) -> String {
+    let mut output = String::new();
+    if let Some(metadata) = &data.metadata {
+        output.push_str(&format!(
+            "# fnox export preview for profile: {}\n",
+            metadata.profile
+        ));
+    }
+    output.push_str("# Values are shown as their length and a salted sha256 prefix\n");
+    output.push_str(&format!("{}{}\n\n", PREVIEW_SALT_PREFIX, salt));
+
+    for (key, value) in &data.secrets {
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(value.as_bytes());
+        let hash = data_encoding::HEXLOWER.encode(&hasher.finalize());
+        output.push_str(&format!(
+            "{} provider={} length={} sha256={}\n",
+            key,
+            providers.get(key).map(String::as_str).unwrap_or("-"),
+            value.chars().count(),
+            &hash[..PREVIEW_HASH_LEN]
+        ));
+    }
+    output
+}
+
+const PREVIEW_SALT_PREFIX: &str = "# salt: ";
+
+/// Hex digits of the hash shown per value
+const PREVIEW_HASH_LEN: usize = 12;
 
-    // Dotenv parsers treat `$` and backticks literally; use `--format shell`
-    // for sourceable shell output.
-    let mut quoted = String::with_capacity(value.len() + 2);
-    quoted.push('"');
-    for c in value.chars() {
-        match c {
-            '\\' => quoted.push_str("\\\\"),
-            '"' => quoted.push_str("\\\""),
-            '\n' => quoted.push_str("\\n"),
-            '\r' => quoted.push_str("\\r"),
-            '\t' => quoted.push_str("\\t"),
-            _ => quoted.push(c),
+/// The salt recorded in a preview's header
+fn preview_salt(preview: &str) -> Option<&str> {
+    preview
+        .lines()
+        .find_map(|line| line.strip_prefix(PREVIEW_SALT_PREFIX))
+        .map(str::trim)
+        .filter(|salt| !salt.is_empty())
+}
+
+/// Compare the committed preview at `path` against the live one, listing
+/// added, removed and changed keys on drift
+fn check_preview(committed: &str, live: &str, path: &Path) -> Result<()> {
+    let entries = |preview: &str| -> IndexMap<String, String> {
+        preview
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let key = line.split_whitespace().next().unwrap_or_default();
+                (key.to_string(), line.trim_end().to_string())
+            })
+            .collect()
+    };
+    let committed_entries = entries(committed);
+    let live_entries = entries(live);
+
+    let mut drift = Vec::new();
+    for (key, line) in &live_entries {
+        match committed_entries.get(key) {
+            None => drift.push(format!("+ {}", key)),
+            Some(committed_line) if committed_line != line => drift.push(format!("~ {}", key)),
+            Some(_) => {}
+        }
+    }
+    for key in committed_entries.keys() {
+        if !live_entries.contains_key(key) {
+            drift.push(format!("- {}", key));
         }
     }
-    quoted.push('"');
-    quoted
+
+    let header = |preview: &str| -> Vec<String> {
+        preview
+            .lines()
+            .filter(|line| line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    };
+    if drift.is_empty() && header(committed) == header(live) {
+        println!(
+            "{} Export preview {} is up to date",
+            console::style("✓").green(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    for line in &drift {
+        eprintln!("  {}", line);
+    }
+    Err(FnoxError::Config(format!(
+        "Export preview {} is out of date ({} secret{} changed). Run `fnox export --format preview --output {}` to update it",
+        path.display(),
+        drift.len(),
+        if drift.len() == 1 { "" } else { "s" },
+        path.display()
+    )))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::dotenv_quote;
+    use super::*;
+
+    fn command(format: ExportFormat) -> ExportCommand {
+        ExportCommand {
+            format,
+            dry_run: false,
+            output: None,
+            force: false,
+            sort: ExportSort::Key,
+            check: false,
+            name: None,
+            namespace: None,
+            string_data: false,
+            map: Vec::new(),
+            prefix: None,
+        }
+    }
+
+    fn export_data() -> ExportData {
+        let mut secrets = IndexMap::new();
+        secrets.insert("ZED_TOKEN".to_string(), "plain".to_string());
+        secrets.insert(
+            "CERT".to_string(),
+            "-----BEGIN CERT-----\nsay \"hi\"\n-----END CERT-----".to_string(),
+        );
+        secrets.insert("ALPHA".to_string(), "it's: {not} yaml".to_string());
+        ExportData {
+            secrets,
+            metadata: Some(ExportMetadata {
+                profile: "default".to_string(),
+                exported_at: "2026-01-01T00:00:00+00:00".to_string(),
+                total_secrets: 3,
+            }),
+        }
+    }
+
+    #[test]
+    fn json_export_round_trips_in_order() {
+        let data = export_data();
+        let output = command(ExportFormat::Json).export_as_json(&data).unwrap();
+
+        let parsed: ExportData = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed.secrets, data.secrets);
+        let keys: Vec<&str> = parsed.secrets.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["ZED_TOKEN", "CERT", "ALPHA"]);
+        assert!(
+            output.contains(r#""CERT": "-----BEGIN CERT-----\nsay \"hi\"\n-----END CERT-----""#)
+        );
+    }
+
+    #[test]
+    fn yaml_export_round_trips_multiline_values() {
+        let data = export_data();
+        let output = command(ExportFormat::Yaml).export_as_yaml(&data).unwrap();
+
+        let parsed: ExportData = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed.secrets, data.secrets);
+        let keys: Vec<&str> = parsed.secrets.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["ZED_TOKEN", "CERT", "ALPHA"]);
+        // Multi-line values are written as block scalars, not escaped strings
+        assert!(output.contains("CERT: |-\n"), "{output}");
+    }
+
+    /// Secrets inserted out of order, then sorted by key the way `run` does
+    fn key_sorted_data() -> ExportData {
+        let mut secrets = IndexMap::new();
+        secrets.insert("ZED".to_string(), "z".to_string());
+        secrets.insert("ALPHA".to_string(), "a b".to_string());
+        secrets.insert("MID".to_string(), "m".to_string());
+        sort_secrets(&mut secrets, ExportSort::Key, &HashMap::new());
+        ExportData {
+            secrets,
+            metadata: Some(ExportMetadata {
+                profile: "default".to_string(),
+                exported_at: "2026-01-01T00:00:00+00:00".to_string(),
+                total_secrets: 3,
+            }),
+        }
+    }
+
+    const HEADER: &str = "# Exported from profile: default\n\
+                          # Exported at: 2026-01-01T00:00:00+00:00\n\
+                          # Total secrets: 3\n\n";
+
+    #[test]
+    fn sort_orders_by_key_config_or_provider() {
+        let mut secrets = IndexMap::new();
+        for key in ["ZED", "ALPHA", "MID", "BETA"] {
+            secrets.insert(key.to_string(), String::new());
+        }
+        let providers: HashMap<String, String> =
+            [("ZED", "age"), ("ALPHA", "vault"), ("MID", "age")]
+                .into_iter()
+                .map(|(key, provider)| (key.to_string(), provider.to_string()))
+                .collect();
+        let sorted = |sort| {
+            let mut secrets = secrets.clone();
+            sort_secrets(&mut secrets, sort, &providers);
+            secrets.into_keys().collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(ExportSort::Key), ["ALPHA", "BETA", "MID", "ZED"]);
+        assert_eq!(sorted(ExportSort::Config), ["ZED", "ALPHA", "MID", "BETA"]);
+        // Secrets without a provider first, then grouped by provider
+        assert_eq!(
+            sorted(ExportSort::Provider),
+            ["BETA", "MID", "ZED", "ALPHA"]
+        );
+    }
+
+    #[test]
+    fn env_export_is_key_sorted() {
+        let output = command(ExportFormat::Env)
+            .export_as_env(&key_sorted_data())
+            .unwrap();
+        assert_eq!(output, format!("{HEADER}ALPHA=\"a b\"\nMID=m\nZED=z\n"));
+    }
+
+    #[test]
+    fn compose_export_is_key_sorted() {
+        let output = command(ExportFormat::Compose)
+            .export_as_compose(&key_sorted_data())
+            .unwrap();
+        assert_eq!(output, format!("{HEADER}ALPHA=\"a b\"\nMID=m\nZED=z\n"));
+    }
+
+    #[test]
+    fn shell_export_is_key_sorted() {
+        let output = command(ExportFormat::Shell)
+            .export_as_shell(&key_sorted_data())
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("{HEADER}export ALPHA='a b'\nexport MID=m\nexport ZED=z\n")
+        );
+    }
+
+    #[test]
+    fn json_export_is_key_sorted() {
+        let output = command(ExportFormat::Json)
+            .export_as_json(&key_sorted_data())
+            .unwrap();
+        assert!(
+            output.starts_with(
+                "{\n  \"secrets\": {\n    \"ALPHA\": \"a b\",\n    \"MID\": \"m\",\n    \"ZED\": \"z\"\n  },"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn yaml_export_is_key_sorted() {
+        let output = command(ExportFormat::Yaml)
+            .export_as_yaml(&key_sorted_data())
+            .unwrap();
+        assert!(
+            output.starts_with("secrets:\n  ALPHA: a b\n  MID: m\n  ZED: z\nmetadata:\n"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn toml_export_is_key_sorted() {
+        let output = command(ExportFormat::Toml)
+            .export_as_toml(&key_sorted_data())
+            .unwrap();
+        assert!(
+            output.contains("[secrets]\nALPHA = \"a b\"\nMID = \"m\"\nZED = \"z\"\n"),
+            "{output}"
+        );
+    }
+
+    fn preview_providers() -> HashMap<String, String> {
+        [("ALPHA", "age"), ("ZED", "vault")]
+            .into_iter()
+            .map(|(key, provider)| (key.to_string(), provider.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn preview_shows_provider_length_and_salted_hash() {
+        let providers = preview_providers();
+        let output = render_preview(&key_sorted_data(), &providers, "pepper");
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "# fnox export preview for profile: default");
+        assert_eq!(lines[2], "# salt: pepper");
+        assert!(lines[4].starts_with("ALPHA provider=age length=3 sha256="));
+        assert!(lines[5].starts_with("MID provider=- length=1 sha256="));
+        assert!(lines[6].starts_with("ZED provider=vault length=1 sha256="));
+        // Neither values nor the export time are shown
+        assert!(!output.contains("a b"), "{output}");
+        assert!(!output.contains("2026"), "{output}");
+
+        // The same salt gives the same preview, another salt other hashes
+        assert_eq!(
+            render_preview(&key_sorted_data(), &providers, "pepper"),
+            output
+        );
+        let resalted = render_preview(&key_sorted_data(), &providers, "salt");
+        assert_ne!(resalted.lines().nth(4), lines.get(4).copied());
+        assert_eq!(preview_salt(&output), Some("pepper"));
+    }
+
+    #[test]
+    fn preview_check_reports_drift() {
+        let providers = preview_providers();
+        let committed = render_preview(&key_sorted_data(), &providers, "pepper");
+        let path = Path::new("fnox.preview");
+        assert!(check_preview(&committed, &committed, path).is_ok());
+
+        let mut data = key_sorted_data();
+        data.secrets
+            .insert("MID".to_string(), "changed".to_string());
+        data.secrets.shift_remove("ZED");
+        data.secrets.insert("NEW".to_string(), "n".to_string());
+        let live = render_preview(&data, &providers, "pepper");
+
+        let err = check_preview(&committed, &live, path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Export preview fnox.preview is out of date (3 secrets changed)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn compose_quote_escapes_dollar_signs() {
+        assert_eq!(compose_quote("kek"), "kek");
+        assert_eq!(compose_quote("pa$$word"), "\"pa$$$$word\"");
+        assert_eq!(compose_quote("${HOME}/bin"), "\"$${HOME}/bin\"");
+        assert_eq!(compose_quote("$VAR and $OTHER"), "\"$$VAR and $$OTHER\"");
+    }
 
     #[test]
-    fn dotenv_quote_leaves_simple_values_unquoted() {
-        assert_eq!(dotenv_quote("kek"), "kek");
+    fn docker_export_is_unquoted() {
+        let output = command(ExportFormat::Docker)
+            .export_as_docker(&key_sorted_data())
+            .unwrap();
+        assert_eq!(output, format!("{HEADER}ALPHA=a b\nMID=m\nZED=z\n"));
+
+        assert_eq!(
+            docker_env_line("URL", "postgres://u:p@h/db?sslmode=require&x='y'").unwrap(),
+            "URL=postgres://u:p@h/db?sslmode=require&x='y'\n"
+        );
         assert_eq!(
-            dotenv_quote("/tmp/fnox-export-FILE_SECRET-abc"),
-            "/tmp/fnox-export-FILE_SECRET-abc"
+            docker_env_line("_A1", "$HOME \"q\"").unwrap(),
+            "_A1=$HOME \"q\"\n"
+        );
+    }
+
+    #[test]
+    fn docker_export_rejects_multiline_values_and_invalid_names() {
+        for value in ["line1\nline2", "crlf\r\n"] {
+            let err = docker_env_line("CERT", value).unwrap_err();
+            assert!(err.to_string().contains("spans multiple lines"), "{err}");
+        }
+        for key in ["1PASSWORD", "MY-KEY", "MY KEY", ""] {
+            let err = docker_env_line(key, "v").unwrap_err();
+            assert!(err.to_string().contains("names must be letters"), "{err}");
+        }
+    }
+
+    fn k8s_command(string_data: bool) -> ExportCommand {
+        ExportCommand {
+            name: Some("my-secret".to_string()),
+            namespace: Some("default".to_string()),
+            string_data,
+            ..command(ExportFormat::K8s)
+        }
+    }
+
+    #[test]
+    fn k8s_export_is_a_secret_manifest_with_base64_data() {
+        let mut data = key_sorted_data();
+        data.secrets
+            .insert("CERT".to_string(), "-----BEGIN-----\nline\n".to_string());
+        data.secrets
+            .insert("UNICODE".to_string(), "pässwörd 🔑".to_string());
+        let output = k8s_command(false).export_as_k8s(&data).unwrap();
+
+        let manifest: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(manifest["apiVersion"], "v1");
+        assert_eq!(manifest["kind"], "Secret");
+        assert_eq!(manifest["type"], "Opaque");
+        assert_eq!(manifest["metadata"]["name"], "my-secret");
+        assert_eq!(manifest["metadata"]["namespace"], "default");
+        assert!(manifest.get("stringData").is_none());
+
+        use base64::Engine;
+        let encoded = manifest["data"].as_mapping().unwrap();
+        assert_eq!(encoded.len(), data.secrets.len());
+        for (key, value) in &data.secrets {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded[key.as_str()].as_str().unwrap())
+                .unwrap();
+            assert_eq!(String::from_utf8(decoded).unwrap(), *value);
+        }
+    }
+
+    #[test]
+    fn k8s_export_writes_plain_values_under_string_data() {
+        let mut command = k8s_command(true);
+        command.namespace = None;
+        let output = command.export_as_k8s(&key_sorted_data()).unwrap();
+
+        let manifest: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(manifest["stringData"]["ALPHA"], "a b");
+        assert!(manifest.get("data").is_none());
+        assert!(manifest["metadata"].get("namespace").is_none());
+    }
+
+    #[test]
+    fn k8s_export_reports_invalid_keys_and_names() {
+        let mut data = key_sorted_data();
+        for key in ["has space", "colon:key", "ok.key-1_x"] {
+            data.secrets.insert(key.to_string(), "v".to_string());
+        }
+        let err = k8s_command(false).export_as_k8s(&data).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("Invalid keys: has space, colon:key"),
+            "{err}"
+        );
+
+        let mut command = k8s_command(false);
+        command.name = Some("My_Secret".to_string());
+        let err = command.export_as_k8s(&key_sorted_data()).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid --name 'My_Secret'"),
+            "{err}"
         );
     }
 
     #[test]
-    fn dotenv_quote_escapes_special_values() {
-        assert_eq!(dotenv_quote("value with spaces"), "\"value with spaces\"");
-        assert_eq!(dotenv_quote("it's \"fine\""), "\"it's \\\"fine\\\"\"");
-        assert_eq!(dotenv_quote("a\nb\t$c`d"), "\"a\\nb\\t$c`d\"");
+    fn compose_quote_matches_dotenv_without_dollar_signs() {
+        for value in ["simple", "with spaces", "it's \"fine\"", "a\nb"] {
+            assert_eq!(compose_quote(value), dotenv::quote(value));
+        }
     }
 }
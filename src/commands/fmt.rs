@@ -0,0 +1,586 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use toml_edit::{
+    Array, ArrayOfTables, Decor, DocumentMut, InlineTable, Item, Key, RawString, Table, Value,
+};
+
+use crate::commands::Cli;
+use crate::config::{self, Config};
+use crate::error::{FnoxError, Result};
+use crate::secret_groups;
+
+/// Secrets whose inline form is longer than this are written as tables
+const INLINE_WIDTH: usize = 100;
+
+/// Rewrite config files in a canonical style
+///
+/// Providers and secrets are sorted by name, a secret is written as an inline
+/// table when it fits on one line and as a table otherwise, and strings use the
+/// simplest quoting that holds them. Values are never changed, and comments
+/// move with the item they're attached to.
+#[derive(Debug, Args)]
+pub struct FmtCommand {
+    /// Also format the files the config imports, and the files they import
+    #[arg(long)]
+    pub all: bool,
+
+    /// Don't write anything; exit with an error if a file isn't formatted
+    #[arg(long)]
+    pub check: bool,
+
+    /// Keep providers and secrets in the order they're written
+    #[arg(long)]
+    pub no_sort: bool,
+}
+
+impl FmtCommand {
+    pub async fn run(&self, cli: &Cli) -> Result<()> {
+        let mut queue = VecDeque::from([config_path(cli)?]);
+        let mut seen = HashSet::new();
+        let mut unformatted = 0;
+
+        while let Some(path) = queue.pop_front() {
+            if !seen.insert(fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                continue;
+            }
+            // Loading first reports syntax errors with their location
+            let loaded = Config::load(&path)?;
+            if self.all {
+                let dir = path.parent().unwrap_or(Path::new("."));
+                queue.extend(loaded.import.iter().map(|import| dir.join(import)));
+            }
+
+            let content =
+                fs::read_to_string(&path).map_err(|source| FnoxError::ConfigReadFailed {
+                    path: path.clone(),
+                    source,
+                })?;
+            let formatted = format_config(&content, !self.no_sort).map_err(|e| {
+                FnoxError::Config(format!("Failed to parse {}: {}", path.display(), e))
+            })?;
+            if formatted == content {
+                tracing::debug!("{} is already formatted", path.display());
+                continue;
+            }
+
+            if self.check {
+                eprintln!("{} is not formatted", path.display());
+                unformatted += 1;
+                continue;
+            }
+            fs::write(&path, formatted).map_err(|source| FnoxError::ConfigWriteFailed {
+                path: path.clone(),
+                source,
+            })?;
+            let check = console::style("✓").green();
+            println!("{check} Formatted {}", path.display());
+        }
+
+        if unformatted > 0 {
+            return Err(FnoxError::FormatCheckFailed { files: unformatted });
+        }
+        Ok(())
+    }
+}
+
+/// The file to format: the one given with `--config`, or the local config file
+fn config_path(cli: &Cli) -> Result<PathBuf> {
+    let path = if cli.config == Path::new(config::DEFAULT_CONFIG_FILENAME) {
+        let current_dir = std::env::current_dir()
+            .map_err(|e| FnoxError::Config(format!("Failed to get current directory: {}", e)))?;
+        let profile = Config::get_profile(cli.profile.as_deref());
+        config::find_local_config(&current_dir, Some(&profile))
+    } else {
+        cli.config.clone()
+    };
+    if !path.exists() {
+        return Err(FnoxError::ConfigFileNotFound { path });
+    }
+    Ok(path)
+}
+
+/// Where a table sits in a config file, which decides how its entries are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Root,
+    Profiles,
+    Profile,
+    /// `[providers]`, whose entries are always tables
+    Providers,
+    /// `[secrets]` or a secret group, whose entries are inline tables when short
+    Secrets,
+    Other,
+}
+
+impl Section {
+    fn child(self, name: &str) -> Section {
+        match (self, name) {
+            (Section::Root | Section::Profile, "providers") => Section::Providers,
+            (Section::Root | Section::Profile, "secrets") => Section::Secrets,
+            (Section::Root, "profiles") => Section::Profiles,
+            (Section::Profiles, _) => Section::Profile,
+            _ => Section::Other,
+        }
+    }
+}
+
+/// Format a config file's contents. Formatting a formatted file changes nothing.
+pub fn format_config(
+    content: &str,
+    sort: bool,
+) -> std::result::Result<String, toml_edit::TomlError> {
+    let mut doc = content.parse::<DocumentMut>()?;
+    let root = format_table(doc.as_table(), Section::Root, sort);
+    *doc.as_table_mut() = root;
+    let trailing = comment_block(raw(Some(doc.trailing())), true);
+    doc.set_trailing(trailing);
+
+    let formatted = doc.to_string();
+    let formatted = formatted.trim();
+    if formatted.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("{formatted}\n"))
+}
+
+/// Rebuild `table` with its entries formatted and, in `[providers]` and
+/// `[secrets]`, sorted. Tables are built fresh rather than edited, since a
+/// parsed table keeps its position in the file and would be written there.
+fn format_table(table: &Table, section: Section, sort: bool) -> Table {
+    let mut formatted = Table::new();
+    formatted.set_implicit(table.is_implicit());
+    formatted.set_dotted(table.is_dotted());
+    *formatted.decor_mut() = if section == Section::Root || table.is_dotted() {
+        table.decor().clone()
+    } else {
+        header_decor(table.decor())
+    };
+
+    let mut entries: Vec<(&str, &Item)> = table.iter().collect();
+    if sort && matches!(section, Section::Providers | Section::Secrets) {
+        // A group's settings stay first, in their order; providers and secrets
+        // follow by name
+        entries.sort_by(|(a, a_item), (b, b_item)| {
+            match (a_item.is_table_like(), b_item.is_table_like()) {
+                (true, true) => a.cmp(b),
+                (a_table, b_table) => a_table.cmp(&b_table),
+            }
+        });
+    }
+
+    let mut first_value = true;
+    for (name, item) in entries {
+        let key = table.key(name).expect("key exists");
+        let (key, item) = match section {
+            Section::Providers => format_provider(key, item, sort),
+            Section::Secrets => format_secret(key, item, sort),
+            _ => format_item(key, item, section.child(name), sort),
+        };
+        if item.is_none() {
+            continue;
+        }
+        let key = if item.is_value() && !table.is_dotted() {
+            // `KEY= { ... }`, the way `fnox set` and `fnox import` write secrets
+            let inline_secret = section == Section::Secrets && item.is_inline_table();
+            let key = value_key(&key, first_value, if inline_secret { "" } else { " " });
+            first_value = false;
+            key
+        } else {
+            key
+        };
+        formatted.insert_formatted(&key, item);
+    }
+
+    if formatted.iter().any(|(_, item)| item.is_value()) {
+        formatted.set_implicit(false);
+    }
+    formatted
+}
+
+fn format_item(key: &Key, item: &Item, section: Section, sort: bool) -> (Key, Item) {
+    match item {
+        Item::Value(value) => (key.clone(), Item::Value(format_entry_value(value))),
+        Item::Table(table) if table.is_dotted() => {
+            (key.clone(), Item::Table(format_table(table, section, sort)))
+        }
+        Item::Table(table) => (
+            Key::new(key.get()),
+            Item::Table(format_table(table, section, sort)),
+        ),
+        Item::ArrayOfTables(tables) => {
+            let mut formatted = ArrayOfTables::new();
+            for table in tables.iter() {
+                formatted.push(format_table(table, section, sort));
+            }
+            (Key::new(key.get()), Item::ArrayOfTables(formatted))
+        }
+        Item::None => (key.clone(), Item::None),
+    }
+}
+
+/// Providers are always written as `[providers.<name>]` tables
+fn format_provider(key: &Key, item: &Item, sort: bool) -> (Key, Item) {
+    match item {
+        Item::Value(Value::InlineTable(inline)) => {
+            let table = table_from_inline(inline, inline_decor(key, item));
+            (
+                Key::new(key.get()),
+                Item::Table(format_table(&table, Section::Other, sort)),
+            )
+        }
+        _ => format_item(key, item, Section::Other, sort),
+    }
+}
+
+/// A secret is an inline table when it fits on one line, and a table when it
+/// doesn't. Groups are tables whose secrets are formatted the same way.
+fn format_secret(key: &Key, item: &Item, sort: bool) -> (Key, Item) {
+    let Some(fields) = item.as_table_like() else {
+        return format_item(key, item, Section::Other, sort);
+    };
+    if secret_groups::is_group(fields) {
+        return format_item(key, item, Section::Secrets, sort);
+    }
+    let Some((inline, decor)) = as_inline(key, item) else {
+        return format_item(key, item, Section::Other, sort);
+    };
+
+    let mut value = format_value(&Value::InlineTable(inline.clone()));
+    value.decor_mut().clear();
+    let line = format!("{}= {}", Key::new(key.get()), value);
+    if line.contains('\n') || line.len() > INLINE_WIDTH {
+        let table = table_from_inline(&inline, decor);
+        return (
+            Key::new(key.get()),
+            Item::Table(format_table(&table, Section::Other, sort)),
+        );
+    }
+
+    let mut inline_key = Key::new(key.get());
+    inline_key
+        .leaf_decor_mut()
+        .set_prefix(raw(decor.prefix()).to_string());
+    *value.decor_mut() = Decor::new(" ", trailing_comment(decor.suffix()));
+    (inline_key, Item::Value(value))
+}
+
+/// A secret's fields as an inline table, with the comments around it. `None`
+/// if it holds a table or comments of its own, which an inline table can't.
+fn as_inline(key: &Key, item: &Item) -> Option<(InlineTable, Decor)> {
+    match item {
+        Item::Value(Value::InlineTable(inline)) => Some((inline.clone(), inline_decor(key, item))),
+        Item::Table(table) if !table.is_dotted() => {
+            let mut inline = InlineTable::new();
+            for (name, field) in table.iter() {
+                let value = field.as_value()?;
+                let field_key = table.key(name).expect("key exists");
+                if has_comment(field_key.leaf_decor()) || has_comment(value.decor()) {
+                    return None;
+                }
+                inline.insert(name, value.clone());
+            }
+            Some((inline, table.decor().clone()))
+        }
+        _ => None,
+    }
+}
+
+/// The comments before a `key = { ... }` line and at its end
+fn inline_decor(key: &Key, item: &Item) -> Decor {
+    let suffix = item
+        .as_value()
+        .map(|value| raw(value.decor().suffix()))
+        .unwrap_or_default();
+    Decor::new(raw(key.leaf_decor().prefix()), suffix)
+}
+
+/// A table holding `inline`'s fields, with `decor` around its header
+fn table_from_inline(inline: &InlineTable, decor: Decor) -> Table {
+    let mut table = Table::new();
+    for (name, value) in inline.iter() {
+        table.insert(name, Item::Value(value.clone()));
+    }
+    *table.decor_mut() = decor;
+    table
+}
+
+/// A value on its own `key = value` line, keeping its trailing comment
+fn format_entry_value(value: &Value) -> Value {
+    let mut formatted = format_value(value);
+    *formatted.decor_mut() = Decor::new(" ", trailing_comment(value.decor().suffix()));
+    formatted
+}
+
+/// `value` with strings in their default quoting and arrays and inline tables
+/// evenly spaced. Its own decor is kept for the caller to adjust.
+fn format_value(value: &Value) -> Value {
+    let mut formatted = match value {
+        Value::String(string) => Value::from(string.value().as_str()),
+        Value::Array(array) => Value::Array(format_array(array)),
+        Value::InlineTable(table) => {
+            let mut inline = InlineTable::new();
+            for (name, value) in table.iter() {
+                inline.insert(name, format_value(value));
+            }
+            inline.fmt();
+            Value::InlineTable(inline)
+        }
+        other => other.clone(),
+    };
+    *formatted.decor_mut() = value.decor().clone();
+    formatted
+}
+
+/// Arrays stay on one line or one element per line, as written. Arrays with
+/// comments keep their layout so the comments stay where they are.
+fn format_array(array: &Array) -> Array {
+    let mut formatted = Array::new();
+    for value in array.iter() {
+        formatted.push_formatted(format_value(value));
+    }
+    let commented = array.iter().any(|value| has_comment(value.decor()))
+        || raw(Some(array.trailing())).contains('#');
+    if commented {
+        formatted.set_trailing(raw(Some(array.trailing())));
+        formatted.set_trailing_comma(array.trailing_comma());
+        return formatted;
+    }
+
+    let multiline = array
+        .iter()
+        .any(|value| raw(value.decor().prefix()).contains('\n'))
+        || raw(Some(array.trailing())).contains('\n');
+    if multiline && !formatted.is_empty() {
+        for value in formatted.iter_mut() {
+            *value.decor_mut() = Decor::new("\n    ", "");
+        }
+        formatted.set_trailing("\n");
+        formatted.set_trailing_comma(true);
+    } else {
+        formatted.fmt();
+        formatted.set_trailing("");
+        formatted.set_trailing_comma(false);
+    }
+    formatted
+}
+
+/// A `key = value` key, with its comments and at most one blank line before it
+/// and `suffix` between it and the `=`
+fn value_key(key: &Key, first: bool, suffix: &str) -> Key {
+    let prefix = raw(key.leaf_decor().prefix());
+    let mut formatted = Key::new(key.get());
+    formatted
+        .leaf_decor_mut()
+        .set_prefix(comment_block(prefix, !first && has_blank_line(prefix)));
+    formatted.leaf_decor_mut().set_suffix(suffix);
+    formatted
+}
+
+/// A table header has a blank line and its comments before it
+fn header_decor(decor: &Decor) -> Decor {
+    Decor::new(
+        comment_block(raw(decor.prefix()), true),
+        trailing_comment(decor.suffix()),
+    )
+}
+
+/// The comment lines in `prefix`, unindented, with blank lines dropped
+fn comment_block(prefix: &str, blank_line: bool) -> String {
+    let mut block = String::new();
+    if blank_line {
+        block.push('\n');
+    }
+    for comment in prefix
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        block.push_str(comment);
+        block.push('\n');
+    }
+    block
+}
+
+/// A comment at the end of a line, two spaces after the value
+fn trailing_comment(suffix: Option<&RawString>) -> String {
+    match raw(suffix).trim() {
+        "" => String::new(),
+        comment => format!("  {comment}"),
+    }
+}
+
+/// Whether a blank line separates an item from whatever is above it
+fn has_blank_line(prefix: &str) -> bool {
+    prefix.split('#').next().unwrap_or_default().contains('\n')
+}
+
+fn has_comment(decor: &Decor) -> bool {
+    raw(decor.prefix()).contains('#') || raw(decor.suffix()).contains('#')
+}
+
+fn raw(raw: Option<&RawString>) -> &str {
+    raw.and_then(RawString::as_str).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSY: &str = r#"
+root=true
+
+  # Secrets for the app
+[secrets]
+# The database
+   DATABASE_URL={provider='age',value="YWdlLWVuY3J5cHRpb24="}
+API_KEY = {   provider = "age", value = 'abc' }   # rotated monthly
+
+
+"LOG_LEVEL" = { default = "info" }
+
+[providers.vault]
+type='vault'
+address = "https://vault.example.com"
+
+# Encryption for the repo
+[providers.age]
+type = "age"
+recipients = [ "age1abc",
+  "age1def" ]
+
+[secrets.SIGNING_KEY]
+description = "Key used to sign release artifacts for every platform we publish to"
+provider = "age"
+value = "c2lnbmluZw=="
+
+[profiles.prod.secrets]
+ZED = { provider = "vault", value = "zed" }
+ALPHA = { provider = "vault", value = "alpha" }
+"#;
+
+    const GROUPED: &str = r#"
+[secrets.stripe]
+provider = "age"
+webhook_secret = { value = "whsec" }
+api_key = { value = 'sk' }
+
+[secrets.AWS_REGION]
+default = "us-east-1"
+"#;
+
+    const COMMENTED: &str = r#"
+[secrets]
+B = { default = "b" }
+
+# Keep this one
+[secrets.A]
+default = "a"  # the first letter
+provider = "age" # where it lives
+"#;
+
+    fn values(content: &str) -> serde_json::Value {
+        toml_edit::de::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn formats_messy_config() {
+        assert_eq!(
+            format_config(MESSY, true).unwrap(),
+            r#"root = true
+
+# Secrets for the app
+[secrets]
+API_KEY= { provider = "age", value = "abc" }  # rotated monthly
+# The database
+DATABASE_URL= { provider = "age", value = "YWdlLWVuY3J5cHRpb24=" }
+
+LOG_LEVEL= { default = "info" }
+
+[secrets.SIGNING_KEY]
+description = "Key used to sign release artifacts for every platform we publish to"
+provider = "age"
+value = "c2lnbmluZw=="
+
+# Encryption for the repo
+[providers.age]
+type = "age"
+recipients = [
+    "age1abc",
+    "age1def",
+]
+
+[providers.vault]
+type = "vault"
+address = "https://vault.example.com"
+
+[profiles.prod.secrets]
+ALPHA= { provider = "vault", value = "alpha" }
+ZED= { provider = "vault", value = "zed" }
+"#
+        );
+    }
+
+    #[test]
+    fn no_sort_keeps_order() {
+        let formatted = format_config(MESSY, false).unwrap();
+        let zed = formatted.find("ZED=").unwrap();
+        let alpha = formatted.find("ALPHA=").unwrap();
+        assert!(zed < alpha);
+        assert!(formatted.find("[providers.vault]") < formatted.find("[providers.age]"));
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for fixture in [MESSY, GROUPED, COMMENTED] {
+            for sort in [true, false] {
+                let once = format_config(fixture, sort).unwrap();
+                let twice = format_config(&once, sort).unwrap();
+                assert_eq!(once, twice, "fixture:\n{fixture}");
+            }
+        }
+    }
+
+    #[test]
+    fn formatting_keeps_values() {
+        for fixture in [MESSY, GROUPED, COMMENTED] {
+            for sort in [true, false] {
+                let formatted = format_config(fixture, sort).unwrap();
+                assert_eq!(values(fixture), values(&formatted));
+            }
+        }
+    }
+
+    #[test]
+    fn groups_keep_settings_first_and_sort_their_secrets() {
+        assert_eq!(
+            format_config(GROUPED, true).unwrap(),
+            r#"[secrets]
+AWS_REGION= { default = "us-east-1" }
+
+[secrets.stripe]
+provider = "age"
+api_key= { value = "sk" }
+webhook_secret= { value = "whsec" }
+"#
+        );
+    }
+
+    #[test]
+    fn secrets_with_comments_on_fields_stay_tables() {
+        let formatted = format_config(COMMENTED, true).unwrap();
+        assert_eq!(
+            formatted,
+            r#"[secrets]
+B= { default = "b" }
+
+# Keep this one
+[secrets.A]
+default = "a"  # the first letter
+provider = "age"  # where it lives
+"#
+        );
+    }
+}
@@ -1,61 +1,109 @@
 use crate::config::SecretConfig;
+use crate::env;
 use crate::error::{FnoxError, Result};
 use crate::lease::{self, LeaseLedger};
+use crate::secret_resolver;
+use crate::settings::Settings;
 use crate::suggest::{find_similar, format_suggestions};
 use crate::temp_file_secrets::create_persistent_secret_file;
 use crate::{commands::Cli, config::Config};
 use clap::Args;
 use indexmap::IndexMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Args)]
 pub struct GetCommand {
     /// Secret key to retrieve
-    pub key: String,
+    #[arg(
+        required_unless_present_any = ["reference", "all"],
+        conflicts_with_all = ["reference", "all"]
+    )]
+    pub key: Option<String>,
+
+    /// Write the value to this file instead of printing it
+    #[arg(short = 'o', long, value_hint = clap::ValueHint::FilePath, conflicts_with = "all")]
+    pub output: Option<PathBuf>,
+
+    /// Provider to resolve --ref with (default: the profile's default provider)
+    #[arg(short = 'p', long, requires = "reference")]
+    pub provider: Option<String>,
+
+    /// Output every secret in the profile as a JSON object, null where missing
+    #[arg(long, conflicts_with = "base64_decode")]
+    pub all: bool,
 
     /// Base64 decode the secret
     #[arg(long)]
     pub base64_decode: bool,
+
+    /// Copy the value to the clipboard instead of printing it
+    #[arg(long, conflicts_with_all = ["all", "output"])]
+    pub copy: bool,
+
+    /// With --all, output a JSON array of the secret keys without resolving values
+    #[arg(long, requires = "all")]
+    pub keys_only: bool,
+
+    /// End the value with a newline even when stdout isn't a terminal
+    #[arg(long, overrides_with = "no_newline")]
+    pub newline: bool,
+
+    /// Output the exact value without a trailing newline, even to a terminal
+    #[arg(long, overrides_with = "newline")]
+    pub no_newline: bool,
+
+    /// Resolve a raw provider reference instead of a configured secret
+    #[arg(long = "ref", value_name = "REFERENCE", conflicts_with = "all")]
+    pub reference: Option<String>,
 }
 
 impl GetCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         let profile = Config::get_profile(cli.profile.as_deref());
-        tracing::debug!("Getting secret '{}' from profile '{}'", self.key, profile);
 
         // Validate the configuration first
         config.validate()?;
 
+        if let Some(ref reference) = self.reference {
+            return self.run_reference(&config, &profile, reference).await;
+        }
+        if self.all {
+            return self.run_all(cli, &config, &profile).await;
+        }
+
+        let key = self.key();
+        tracing::debug!("Getting secret '{}' from profile '{}'", key, profile);
+
         // Check if the requested key is produced by a lease backend
         if let Some((value, profile_secrets)) =
             self.resolve_from_lease(cli, &config, &profile).await?
         {
             let value = self.maybe_base64_decode(value)?;
             // Respect as_file from the profile secret config when present
-            if let Some(sc) = profile_secrets.get(&self.key)
+            if let Some(sc) = profile_secrets.get(key)
                 && sc.as_file
             {
-                let file_path = create_persistent_secret_file("fnox-", &self.key, &value)?;
-                println!("{}", file_path);
-                return Ok(());
+                let file_path = create_persistent_secret_file("fnox-", key, &value)?;
+                return self.print_value(&file_path);
             }
-            println!("{}", value);
-            return Ok(());
+            return self.print_value(&value);
         }
 
         // Get the profile secrets
         let profile_secrets = config.get_secrets(&profile)?;
 
         // Get the secret config
-        let secret_config = profile_secrets.get(&self.key).ok_or_else(|| {
+        let secret_config = profile_secrets.get(key).ok_or_else(|| {
             // Find similar secret names for suggestion
             let available_keys: Vec<_> = profile_secrets.keys().map(|s| s.as_str()).collect();
-            let similar = find_similar(&self.key, available_keys);
+            let similar = find_similar(key, available_keys);
             let suggestion = format_suggestions(&similar);
 
             FnoxError::SecretNotFound {
-                key: self.key.clone(),
+                key: key.to_string(),
                 profile: profile.clone(),
-                config_path: config.secret_sources.get(&self.key).cloned(),
+                config_path: config.secret_sources.get(key).cloned(),
                 suggestion,
             }
         })?;
@@ -65,7 +113,7 @@ impl GetCommand {
             cli,
             &config,
             &profile,
-            &self.key,
+            key,
             secret_config,
             crate::daemon::Purpose::Get,
         )
@@ -76,12 +124,11 @@ impl GetCommand {
 
                 // Check if this secret should be written to a file
                 if secret_config.as_file {
-                    let file_path = create_persistent_secret_file("fnox-", &self.key, &value)?;
-                    println!("{}", file_path);
+                    let file_path = create_persistent_secret_file("fnox-", key, &value)?;
+                    self.print_value(&file_path)
                 } else {
-                    println!("{}", value);
+                    self.print_value(&value)
                 }
-                Ok(())
             }
             Ok(None) => {
                 // Secret not found but if_missing allows it
@@ -91,6 +138,117 @@ impl GetCommand {
         }
     }
 
+    /// The positional KEY; clap guarantees it is present unless `--ref` is used.
+    fn key(&self) -> &str {
+        self.key.as_deref().unwrap_or_default()
+    }
+
+    /// Output every secret of `profile` as JSON, or just their keys with
+    /// `--keys-only`. Secrets are resolved in one batch, and `as_file` secrets
+    /// are written to files whose paths are output, as for a single key.
+    async fn run_all(&self, cli: &Cli, config: &Config, profile: &str) -> Result<()> {
+        let profile_secrets = config.get_secrets(profile)?;
+
+        if self.keys_only {
+            let keys: Vec<&String> = profile_secrets.keys().collect();
+            println!("{}", serde_json::to_string_pretty(&keys)?);
+            return Ok(());
+        }
+
+        let mut resolved = crate::daemon::resolve_batch(
+            cli,
+            config,
+            profile,
+            &profile_secrets,
+            crate::daemon::Purpose::Get,
+            true,
+        )
+        .await?;
+
+        // Every configured key is in the output, null when unresolved. Path
+        // secrets are replaced by the secrets they loaded.
+        let mut output: IndexMap<String, Option<String>> = IndexMap::new();
+        for (key, secret_config) in &profile_secrets {
+            if secret_resolver::is_path_secret(config, profile, secret_config) {
+                continue;
+            }
+            let value = match resolved.swap_remove(key).flatten() {
+                Some(value) if secret_config.as_file => {
+                    Some(create_persistent_secret_file("fnox-", key, &value)?)
+                }
+                value => value,
+            };
+            output.insert(key.clone(), value);
+        }
+        output.extend(resolved);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        Ok(())
+    }
+
+    /// Resolve a raw provider reference without a `[secrets]` entry.
+    async fn run_reference(&self, config: &Config, profile: &str, reference: &str) -> Result<()> {
+        tracing::debug!(
+            "Resolving reference '{}' in profile '{}' via provider {:?}",
+            reference,
+            profile,
+            self.provider
+        );
+
+        // Without --provider the resolver falls back to the default provider
+        let mut secret_config = SecretConfig::new();
+        secret_config.set_provider(self.provider.clone());
+        secret_config.set_value(Some(reference.to_string()));
+
+        if let Some(value) =
+            crate::secret_resolver::resolve_secret(config, profile, reference, &secret_config)
+                .await?
+        {
+            self.print_value(&self.maybe_base64_decode(value)?)?;
+        }
+        Ok(())
+    }
+
+    /// Write `value` to stdout exactly as it is. A newline is added only for
+    /// a terminal, so `$(fnox get KEY)` and pipes see the value's own bytes,
+    /// unless `--newline` or `--no-newline` says otherwise. With `--copy` or
+    /// `--output` the value goes to the clipboard or a file instead.
+    fn print_value(&self, value: &str) -> Result<()> {
+        if self.copy {
+            arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.set_text(value.to_string()))
+                .map_err(|e| FnoxError::Config(format!("Failed to copy to the clipboard: {e}")))?;
+            eprintln!("Copied {} to the clipboard", self.key());
+            return Ok(());
+        }
+        if let Some(path) = &self.output {
+            let mut content = value.to_string();
+            if self.newline {
+                content.push('\n');
+            }
+            return write_private_file(path, &content);
+        }
+
+        let terminal = atty::is(atty::Stream::Stdout);
+        if terminal && Settings::try_get()?.get_tty_warning {
+            warn_terminal_output_once(&env::FNOX_STATE_DIR.join("get-tty-warning-shown"));
+        }
+        let newline = if self.newline {
+            true
+        } else if self.no_newline {
+            false
+        } else {
+            terminal
+        };
+
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(value.as_bytes())?;
+        if newline {
+            stdout.write_all(b"\n")?;
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+
     fn maybe_base64_decode(&self, value: String) -> Result<String> {
         if self.base64_decode {
             let decoded_bytes = data_encoding::BASE64
@@ -125,7 +283,7 @@ impl GetCommand {
         // produce the same key.
         let matching_lease = leases
             .iter()
-            .rfind(|(_, lease_config)| lease_config.produces_env_var(&self.key));
+            .rfind(|(_, lease_config)| lease_config.produces_env_var(self.key()));
 
         let Some((name, lease_config)) = matching_lease else {
             return Ok(None);
@@ -242,12 +400,68 @@ impl GetCommand {
         creds: IndexMap<String, String>,
         all_secrets: IndexMap<String, SecretConfig>,
     ) -> Result<Option<(String, IndexMap<String, SecretConfig>)>> {
-        match creds.get(&self.key) {
+        match creds.get(self.key()) {
             Some(value) => Ok(Some((value.clone(), all_secrets))),
             None => Err(FnoxError::LeaseContractViolation {
                 lease: name.to_string(),
-                key: self.key.clone(),
+                key: self.key().to_string(),
             }),
         }
     }
 }
+
+/// Warn that a value printed to a terminal stays in its scrollback, unless
+/// `marker` shows the warning was given before. Returns whether it warned.
+fn warn_terminal_output_once(marker: &Path) -> bool {
+    if marker.exists() {
+        return false;
+    }
+    eprintln!(
+        "{} secret values printed to a terminal stay in its scrollback; use --copy or --output to keep them off screen (shown once, FNOX_GET_TTY_WARNING=false turns it off)",
+        console::style("warning:").yellow().bold()
+    );
+    let remembered = marker
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(marker, ""));
+    if let Err(e) = remembered {
+        tracing::debug!(
+            "Failed to remember the terminal warning at {}: {e}",
+            marker.display()
+        );
+    }
+    true
+}
+
+/// Write `content` to `path`, readable only by the current user on unix
+pub(crate) fn write_private_file(path: &Path, content: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|source| FnoxError::OutputWriteFailed {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_warning_is_shown_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("state/get-tty-warning-shown");
+
+        assert!(warn_terminal_output_once(&marker));
+        assert!(marker.exists());
+        assert!(!warn_terminal_output_once(&marker));
+    }
+}
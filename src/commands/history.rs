@@ -0,0 +1,111 @@
+use crate::commands::Cli;
+use crate::config::Config;
+use crate::error::{FnoxError, Result};
+use crate::suggest::{find_similar, format_suggestions};
+use crate::table::Table;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct HistoryCommand {
+    /// Secret key to show the history of
+    pub key: String,
+
+    /// Also show the value of each version
+    #[arg(long)]
+    pub show_values: bool,
+}
+
+impl HistoryCommand {
+    pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        tracing::debug!(
+            "Showing history of secret '{}' in profile '{}'",
+            self.key,
+            profile
+        );
+
+        config.validate()?;
+
+        let profile_secrets = config.get_secrets(&profile)?;
+        let secret_config = profile_secrets.get(&self.key).ok_or_else(|| {
+            let similar = find_similar(&self.key, profile_secrets.keys().map(String::as_str));
+            FnoxError::SecretNotFound {
+                key: self.key.clone(),
+                profile: profile.clone(),
+                config_path: config.secret_sources.get(&self.key).cloned(),
+                suggestion: format_suggestions(&similar),
+            }
+        })?;
+
+        let provider_name = match secret_config.provider() {
+            Some(name) => name.to_string(),
+            None => config.get_default_provider(&profile)?.ok_or_else(|| {
+                FnoxError::Config(format!(
+                    "Secret '{}' has no provider, so it has no stored history",
+                    self.key
+                ))
+            })?,
+        };
+        let value = secret_config.value().ok_or_else(|| {
+            FnoxError::Config(format!(
+                "Secret '{}' has no value, so it has no stored history",
+                self.key
+            ))
+        })?;
+
+        let providers = config.get_providers(&profile);
+        let provider_config =
+            providers
+                .get(&provider_name)
+                .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                    provider: provider_name.clone(),
+                    profile: profile.clone(),
+                    config_path: config.provider_sources.get(&provider_name).cloned(),
+                    suggestion: None,
+                })?;
+        let provider = crate::providers::get_provider_resolved(
+            &config,
+            &profile,
+            &provider_name,
+            provider_config,
+        )
+        .await?;
+
+        let versions = provider
+            .get_history(value, self.show_values)
+            .await
+            .map_err(|e| match e {
+                FnoxError::Provider(details) => FnoxError::Provider(format!(
+                    "Can't show the history of '{}' from provider '{}': {}",
+                    self.key, provider_name, details
+                )),
+                e => e,
+            })?;
+
+        let mut headers = vec!["Version", "Modified", "Labels"];
+        if self.show_values {
+            headers.push("Value");
+        }
+        let mut table = Table::new(headers);
+        for version in versions {
+            let mut row = vec![
+                version.id,
+                version
+                    .created
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                version.labels.join(", "),
+            ];
+            if self.show_values {
+                row.push(version.value.unwrap_or_default());
+            }
+            table.row(row);
+        }
+        if table.is_empty() {
+            println!("No versions of '{}' are stored", self.key);
+        } else {
+            table.print();
+        }
+        Ok(())
+    }
+}
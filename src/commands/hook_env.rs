@@ -45,6 +45,10 @@ pub struct HookEnvCommand {
 
 impl HookEnvCommand {
     pub async fn run(&self, cli: &Cli) -> Result<()> {
+        // This runs from the shell prompt, so a provider prompting for input
+        // (e.g. an age passphrase) would hang it: fail instead
+        crate::env::disable_prompts();
+
         // Get settings for output mode
         let settings =
             Settings::try_get().map_err(|e| anyhow::anyhow!("Failed to get settings: {}", e))?;
@@ -222,8 +226,8 @@ async fn load_secrets_from_config(cli: &Cli) -> Result<LoadedSecrets> {
         .map_err(|e| anyhow::anyhow!("Failed to get secrets: {}", e))?;
 
     // Use batch resolution for better performance
-    let resolved = match crate::daemon::resolve_batch(
-        cli,
+    let (resolved, profile_secrets) = match crate::daemon::resolve_batch_with_configs(
+        &crate::daemon::ResolveContext::from_cli(cli),
         &config,
         profile_name,
         &profile_secrets,
@@ -1,14 +1,18 @@
-use crate::commands::Cli;
+use crate::commands::{Cli, provider};
 use crate::config::Config;
+use crate::dotenv;
 use crate::error::{FnoxError, Result};
+use crate::naming::NamingPolicy;
+use crate::sanitize::escape_controls;
 use clap::{Args, ValueEnum};
 use console;
 use indexmap::IndexMap;
 use miette::{NamedSource, SourceSpan};
 use regex::Regex;
+use std::collections::HashSet;
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::{collections::HashMap, path::PathBuf};
 use strum::{Display, EnumString, VariantNames};
 
 /// Supported import formats
@@ -49,14 +53,49 @@ pub struct ImportCommand {
     #[arg(short = 'n', long)]
     dry_run: bool,
 
-    /// Provider to use for encrypting/storing imported secrets (required)
-    #[arg(short = 'p', long)]
-    provider: String,
+    /// Provider to use for encrypting/storing imported secrets (required
+    /// unless --from-provider is given)
+    #[arg(short = 'p', long, required_unless_present = "from_provider")]
+    provider: Option<String>,
+
+    /// With --from-env, only import the variables named in this file, one
+    /// per line
+    #[arg(long, value_name = "FILE", requires = "from_env")]
+    allowlist: Option<PathBuf>,
+
+    /// With --from-env, only import variables whose names start with this
+    #[arg(long, value_name = "PREFIX", requires = "from_env")]
+    env_prefix: Option<String>,
 
     /// Only import matching secrets (regex pattern)
     #[arg(long)]
     filter: Option<String>,
 
+    /// Rename keys that break the naming policy to upper snake case with the
+    /// required prefix
+    #[arg(long)]
+    fix_names: bool,
+
+    /// Import a .env file, giving each secret the comment lines directly
+    /// above its key as a description
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "from_env"])]
+    from_dotenv: Option<PathBuf>,
+
+    /// Import variables from the current environment instead of a file,
+    /// skipping fnox's own FNOX_* variables and shell/session variables such
+    /// as PATH and HOME unless --allowlist names them
+    #[arg(long, conflicts_with = "input")]
+    from_env: bool,
+
+    /// Add every secret a provider lists as a reference to it, named after
+    /// the reference in upper snake case. Nothing is copied or encrypted.
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["input", "from_env", "from_dotenv", "provider"]
+    )]
+    from_provider: Option<String>,
+
     /// Prefix to add to imported secret names
     #[arg(long)]
     prefix: Option<String>,
@@ -71,13 +110,61 @@ impl ImportCommand {
             profile
         );
 
-        let input = self.read_input()?;
-        let mut secrets = self.parse_input(&input)?;
+        // With --from-provider the secrets stay where they are, so the provider
+        // listing them is the one the imported secrets refer to
+        let provider_name = self
+            .from_provider
+            .as_deref()
+            .or(self.provider.as_deref())
+            .unwrap_or_default();
+        let source = match self.from_provider {
+            Some(ref name) => {
+                Some(provider::resolve_provider(&merged_config, &profile, name).await?)
+            }
+            None => None,
+        };
+
+        let mut secrets: IndexMap<String, ImportedValue> = if let Some(ref source) = source {
+            let references = source.list_secrets(None).await?;
+            secrets_for_references(references)?
+                .into_iter()
+                .map(|(key, reference)| (key, reference.into()))
+                .collect()
+        } else if let Some(ref path) = self.from_dotenv {
+            read_dotenv(path)?
+                .into_iter()
+                .map(|entry| {
+                    let value = ImportedValue {
+                        value: entry.value,
+                        description: entry.description,
+                    };
+                    (entry.key, value)
+                })
+                .collect()
+        } else if self.from_env {
+            let allowlist = self.read_allowlist()?;
+            env_secrets(
+                std::env::vars_os().filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                }),
+                self.env_prefix.as_deref(),
+                allowlist.as_ref(),
+            )
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect()
+        } else {
+            let input = self.read_input()?;
+            self.parse_input(&input)?
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect()
+        };
 
         // When importing from stdin, --force or --dry-run is required because stdin is consumed
         // by read_input() and won't be available for the confirmation prompt
         // (dry-run doesn't need confirmation since it doesn't modify anything)
-        if self.input.is_none() && !self.force && !self.dry_run {
+        if self.reads_stdin() && !self.force && !self.dry_run {
             return Err(FnoxError::ImportStdinRequiresForce);
         }
 
@@ -92,7 +179,7 @@ impl ImportCommand {
 
         // Apply prefix if specified
         if let Some(ref prefix) = self.prefix {
-            let mut prefixed_secrets = HashMap::new();
+            let mut prefixed_secrets = IndexMap::new();
             for (key, value) in secrets {
                 let prefixed_key = format!("{}{}", prefix, key);
                 prefixed_secrets.insert(prefixed_key, value);
@@ -100,58 +187,87 @@ impl ImportCommand {
             secrets = prefixed_secrets;
         }
 
+        if let Some(policy) = NamingPolicy::for_profile(&merged_config, &profile)? {
+            secrets = self.apply_naming_policy(&policy, secrets)?;
+        }
+
         if secrets.is_empty() {
             println!("No secrets to import");
             return Ok(());
         }
 
-        // Verify provider exists (use merged config to find providers from any source)
-        let providers = merged_config.get_providers(&profile);
-        let provider_config =
-            providers
-                .get(&self.provider)
-                .ok_or_else(|| FnoxError::ProviderNotConfigured {
-                    provider: self.provider.clone(),
-                    profile: profile.to_string(),
-                    config_path: None,
-                    suggestion: None,
-                })?;
-
         // Get provider and validate capabilities (needed for both dry-run and actual import)
-        let provider = crate::providers::get_provider_resolved(
-            &merged_config,
-            &profile,
-            &self.provider,
-            provider_config,
-        )
-        .await?;
-        let capabilities = provider.capabilities();
-        let is_encryption_provider =
-            capabilities.contains(&crate::providers::ProviderCapability::Encryption);
-        let is_remote_storage_provider =
-            capabilities.contains(&crate::providers::ProviderCapability::RemoteStorage);
-
-        // Validate that provider supports import (encryption capability required)
-        if !is_encryption_provider {
-            if is_remote_storage_provider {
-                return Err(FnoxError::ImportProviderUnsupported {
-                    provider: self.provider.clone(),
-                    help: "Remote storage providers are not yet supported for import. Use an encryption provider like 'age' instead.".to_string(),
-                });
+        let provider = match source {
+            Some(source) => source,
+            None => {
+                let provider =
+                    provider::resolve_provider(&merged_config, &profile, provider_name).await?;
+                let capabilities = provider.capabilities();
+                let is_encryption_provider =
+                    capabilities.contains(&crate::providers::ProviderCapability::Encryption);
+                let is_remote_storage_provider =
+                    capabilities.contains(&crate::providers::ProviderCapability::RemoteStorage);
+
+                // Validate that provider supports import (encryption capability required)
+                if !is_encryption_provider {
+                    if is_remote_storage_provider {
+                        return Err(FnoxError::ImportProviderUnsupported {
+                            provider: provider_name.to_string(),
+                            help: "Remote storage providers are not yet supported for import. Use an encryption provider like 'age' instead, or --from-provider to reference the secrets it already holds.".to_string(),
+                        });
+                    } else {
+                        return Err(FnoxError::ImportProviderUnsupported {
+                            provider: provider_name.to_string(),
+                            help: "Provider does not support encryption or remote storage"
+                                .to_string(),
+                        });
+                    }
+                }
+                provider
+            }
+        };
+
+        // Determine the target config file path
+        let target_path = if self.global {
+            Config::global_config_path()
+        } else {
+            cli.config.clone()
+        };
+
+        // Load existing target config to preserve metadata on re-import
+        let mut existing_config = if target_path.exists() {
+            Some(Config::load(&target_path)?)
+        } else {
+            None
+        };
+        let overwrites: HashSet<String> = existing_config
+            .as_ref()
+            .map(|c| {
+                secrets
+                    .keys()
+                    .filter(|key| c.get_secret(&profile, key).is_some())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let label = |key: &str| {
+            if overwrites.contains(key) {
+                format!(
+                    "{} {}",
+                    escape_controls(key),
+                    console::style("(overwrites existing)").yellow()
+                )
             } else {
-                return Err(FnoxError::ImportProviderUnsupported {
-                    provider: self.provider.clone(),
-                    help: "Provider does not support encryption or remote storage".to_string(),
-                });
+                escape_controls(key).into_owned()
             }
-        }
+        };
 
         // In dry-run mode, show what would be imported and exit
         // (provider and capability validation above ensures dry-run fails on invalid provider)
         if self.dry_run {
             let dry_run_label = console::style("[dry-run]").yellow().bold();
             let styled_profile = console::style(&profile).magenta();
-            let styled_provider = console::style(&self.provider).green();
+            let styled_provider = console::style(provider_name).green();
             let global_suffix = if self.global { " (global)" } else { "" };
 
             println!(
@@ -159,7 +275,15 @@ impl ImportCommand {
                 secrets.len()
             );
             for key in secrets.keys() {
-                println!("  {}", console::style(key).cyan());
+                if overwrites.contains(key) {
+                    println!(
+                        "  {} {}",
+                        console::style(escape_controls(key)).cyan(),
+                        console::style("(overwrites existing)").yellow()
+                    );
+                } else {
+                    println!("  {}", console::style(escape_controls(key)).cyan());
+                }
             }
             return Ok(());
         }
@@ -172,11 +296,17 @@ impl ImportCommand {
                 profile
             );
             for key in secrets.keys().take(10) {
-                println!("  {}", key);
+                println!("  {}", label(key));
             }
             if secrets.len() > 10 {
                 println!("  ... and {} more", secrets.len() - 10);
             }
+            if !overwrites.is_empty() {
+                println!(
+                    "\n{} of these already exist and will be overwritten",
+                    overwrites.len()
+                );
+            }
 
             println!("\nContinue? [y/N]");
             let mut response = String::new();
@@ -190,33 +320,21 @@ impl ImportCommand {
             }
         }
 
-        // Determine the target config file path
-        let target_path = if self.global {
-            let global_path = Config::global_config_path();
-            // Create parent directory if it doesn't exist
-            if let Some(parent) = global_path.parent() {
-                std::fs::create_dir_all(parent).map_err(|e| FnoxError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
-            }
-            global_path
-        } else {
-            cli.config.clone()
-        };
-
-        // Load existing target config to preserve metadata on re-import
-        let mut existing_config = if target_path.exists() {
-            Some(Config::load(&target_path)?)
-        } else {
-            None
-        };
+        // Create the global config's directory if it doesn't exist
+        if self.global
+            && let Some(parent) = target_path.parent()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| FnoxError::CreateDirFailed {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+        }
 
         // Build the secrets to import (encrypt each value)
         let mut import_secrets = IndexMap::new();
         let total_secrets = secrets.len();
 
-        for (key, value) in secrets {
+        for (key, ImportedValue { value, description }) in secrets {
             // Start from existing config if key already exists, to preserve metadata
             // (description, if_missing, default, as_file, etc.)
             let mut secret_config = existing_config
@@ -225,7 +343,19 @@ impl ImportCommand {
                 .unwrap_or_default();
 
             // Set the provider
-            secret_config.set_provider(Some(self.provider.clone()));
+            secret_config.set_provider(Some(provider_name.to_string()));
+            if description.is_some() {
+                secret_config.description = description;
+            }
+
+            // A reference to where the secret already is
+            if self.from_provider.is_some() {
+                secret_config.set_value(Some(value));
+                import_secrets.insert(key, secret_config);
+                continue;
+            }
+
+            crate::providers::check_value_size(provider.as_ref(), &key, &value)?;
 
             // Encrypt the value (provider already validated as encryption provider)
             match provider.encrypt(&value).await {
@@ -235,7 +365,7 @@ impl ImportCommand {
                 Err(e) => {
                     return Err(FnoxError::ImportEncryptionFailed {
                         key: key.clone(),
-                        provider: self.provider.clone(),
+                        provider: provider_name.to_string(),
                         details: e.to_string(),
                     });
                 }
@@ -250,12 +380,108 @@ impl ImportCommand {
         let global_suffix = if self.global { " (global)" } else { "" };
         println!(
             "✓ Imported {} secrets into profile '{}' using provider '{}'{}",
-            total_secrets, profile, self.provider, global_suffix
+            total_secrets, profile, provider_name, global_suffix
         );
 
         Ok(())
     }
 
+    /// Check imported keys against the naming policy. Offending keys are
+    /// renamed with `--fix-names`, or after confirming when the prompt is
+    /// available; otherwise the import fails listing them.
+    fn apply_naming_policy<V>(
+        &self,
+        policy: &NamingPolicy,
+        secrets: IndexMap<String, V>,
+    ) -> Result<IndexMap<String, V>> {
+        let mut violations = policy.violations(secrets.keys().map(String::as_str));
+        if violations.is_empty() {
+            return Ok(secrets);
+        }
+        violations.sort();
+
+        // Stdin is consumed by the input, so only file and environment
+        // imports can prompt
+        let can_prompt = !self.reads_stdin() && !self.force && !self.dry_run;
+        if !self.fix_names {
+            if !can_prompt {
+                return Err(policy.error(&violations, ", or pass --fix-names to rename them"));
+            }
+            println!(
+                "\n{} keys break the naming policy (keys must {}):",
+                violations.len(),
+                policy.expected()
+            );
+            for key in &violations {
+                println!(
+                    "  {} → {}",
+                    escape_controls(key),
+                    escape_controls(&policy.fix(key))
+                );
+            }
+            println!("\nRename them? [y/N]");
+            let mut response = String::new();
+            io::stdin()
+                .read_line(&mut response)
+                .map_err(|e| FnoxError::StdinReadFailed { source: e })?;
+            if !response.trim().to_lowercase().starts_with('y') {
+                return Err(policy.error(&violations, ", or pass --fix-names to rename them"));
+            }
+        }
+
+        let mut renamed = IndexMap::new();
+        for (key, value) in secrets {
+            let key = if policy.violation(&key).is_some() {
+                let fixed = policy.fix(&key);
+                tracing::debug!("Renaming imported key '{}' to '{}'", key, fixed);
+                fixed
+            } else {
+                key
+            };
+            if renamed.insert(key.clone(), value).is_some() {
+                return Err(FnoxError::Config(format!(
+                    "More than one imported key would be named '{}' after renaming",
+                    key
+                )));
+            }
+        }
+
+        // A custom pattern may still reject the renamed keys
+        let mut remaining = policy.violations(renamed.keys().map(String::as_str));
+        if !remaining.is_empty() {
+            remaining.sort();
+            return Err(policy.error(&remaining, ""));
+        }
+        Ok(renamed)
+    }
+
+    /// Whether the secrets come from stdin, which leaves it unusable for prompts
+    fn reads_stdin(&self) -> bool {
+        self.input.is_none()
+            && !self.from_env
+            && self.from_dotenv.is_none()
+            && self.from_provider.is_none()
+    }
+
+    /// Names listed in the --allowlist file, skipping blank lines and `#` comments
+    fn read_allowlist(&self) -> Result<Option<HashSet<String>>> {
+        let Some(ref path) = self.allowlist else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| FnoxError::ImportReadFailed {
+            path: path.clone(),
+            source: e,
+        })?;
+        Ok(Some(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+        ))
+    }
+
     fn read_input(&self) -> Result<String> {
         if let Some(ref input_path) = self.input {
             // Read from specified file
@@ -275,7 +501,7 @@ impl ImportCommand {
         }
     }
 
-    fn parse_input(&self, input: &str) -> Result<HashMap<String, String>> {
+    fn parse_input(&self, input: &str) -> Result<IndexMap<String, String>> {
         let source_name = self
             .input
             .as_ref()
@@ -283,59 +509,22 @@ impl ImportCommand {
             .unwrap_or_else(|| "<stdin>".to_string());
 
         match self.format {
-            ImportFormat::Env => self.parse_env(input),
+            ImportFormat::Env => self.parse_env(input, &source_name),
             ImportFormat::Json => self.parse_json(input, &source_name),
             ImportFormat::Yaml => self.parse_yaml(input, &source_name),
             ImportFormat::Toml => self.parse_toml(input, &source_name),
         }
     }
 
-    fn parse_env(&self, input: &str) -> Result<HashMap<String, String>> {
-        let mut secrets = HashMap::new();
-
-        for line in input.lines() {
-            let line = line.trim();
-
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            // Parse export statements and simple KEY=VALUE
-            if let Some(export_key_value) = line.strip_prefix("export ") {
-                self.parse_key_value(export_key_value, &mut secrets)?;
-            } else {
-                self.parse_key_value(line, &mut secrets)?;
-            }
-        }
-
-        Ok(secrets)
-    }
-
-    fn parse_key_value(&self, line: &str, secrets: &mut HashMap<String, String>) -> Result<()> {
-        if let Some((key, value)) = line.split_once('=') {
-            let key = key.trim();
-            let value = value.trim();
-
-            let value = if let Some(inner) =
-                value.strip_prefix('"').and_then(|v| v.strip_suffix('"'))
-            {
-                unescape_double_quoted_env_value(inner)
-            } else if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\''))
-            {
-                inner.to_string()
-            } else {
-                value.to_string()
-            };
-
-            if !key.is_empty() {
-                secrets.insert(key.to_string(), value);
-            }
-        }
-        Ok(())
+    fn parse_env(&self, input: &str, source_name: &str) -> Result<IndexMap<String, String>> {
+        let entries = dotenv::parse(input).map_err(|e| dotenv_error(e, source_name, input))?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect())
     }
 
-    fn parse_json(&self, input: &str, source_name: &str) -> Result<HashMap<String, String>> {
+    fn parse_json(&self, input: &str, source_name: &str) -> Result<IndexMap<String, String>> {
         let data: serde_json::Value = serde_json::from_str(input).map_err(|e| {
             // serde_json provides line and column
             let offset = self.offset_from_line_col(input, e.line(), e.column());
@@ -349,7 +538,7 @@ impl ImportCommand {
         self.extract_string_values(&data)
     }
 
-    fn parse_yaml(&self, input: &str, source_name: &str) -> Result<HashMap<String, String>> {
+    fn parse_yaml(&self, input: &str, source_name: &str) -> Result<IndexMap<String, String>> {
         let data: serde_yaml::Value = serde_yaml::from_str(input).map_err(|e| {
             // serde_yaml provides location via e.location()
             // Note: serde_yaml uses 0-indexed line/column, so we add 1 for our 1-indexed function
@@ -368,7 +557,7 @@ impl ImportCommand {
         self.extract_string_values(&data)
     }
 
-    fn parse_toml(&self, input: &str, source_name: &str) -> Result<HashMap<String, String>> {
+    fn parse_toml(&self, input: &str, source_name: &str) -> Result<IndexMap<String, String>> {
         let data: serde_json::Value = toml_edit::de::from_str(input).map_err(|e| {
             // toml_edit provides span via e.span()
             if let Some(span) = e.span() {
@@ -438,13 +627,13 @@ impl ImportCommand {
             .unwrap_or(input.len())
     }
 
-    fn extract_string_values<V>(&self, data: &V) -> Result<HashMap<String, String>>
+    fn extract_string_values<V>(&self, data: &V) -> Result<IndexMap<String, String>>
     where
         V: serde::Serialize,
     {
         let json_value = serde_json::to_value(data)?;
 
-        let mut secrets = HashMap::new();
+        let mut secrets = IndexMap::new();
 
         if let serde_json::Value::Object(map) = json_value {
             for (key, value) in map {
@@ -468,50 +657,217 @@ impl ImportCommand {
     }
 }
 
-fn unescape_double_quoted_env_value(value: &str) -> String {
-    let mut unescaped = String::with_capacity(value.len());
-    let mut chars = value.chars();
-
-    while let Some(c) = chars.next() {
-        if c != '\\' {
-            unescaped.push(c);
-            continue;
+/// Key each reference under its name in upper snake case, e.g.
+/// `myapp/db-password` as `MYAPP_DB_PASSWORD`, failing when two references
+/// would get the same key
+fn secrets_for_references(references: Vec<String>) -> Result<IndexMap<String, String>> {
+    let mut secrets: IndexMap<String, String> = IndexMap::new();
+    for reference in references {
+        let key = key_for_reference(&reference);
+        if let Some(other) = secrets.get(&key) {
+            return Err(FnoxError::Config(format!(
+                "'{}' and '{}' would both be imported as {}; add one of them to the config by hand",
+                other, reference, key
+            )));
         }
+        secrets.insert(key, reference);
+    }
+    Ok(secrets)
+}
 
-        match chars.next() {
-            Some('\\') => unescaped.push('\\'),
-            Some('"') => unescaped.push('"'),
-            Some('n') => unescaped.push('\n'),
-            Some('r') => unescaped.push('\r'),
-            Some('t') => unescaped.push('\t'),
-            Some(other) => {
-                unescaped.push('\\');
-                unescaped.push(other);
+fn key_for_reference(reference: &str) -> String {
+    let key: String = reference
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
             }
-            None => unescaped.push('\\'),
+        })
+        .collect();
+    key.trim_matches('_').to_string()
+}
+
+/// A value to import, and the description to give its secret
+struct ImportedValue {
+    value: String,
+    description: Option<String>,
+}
+
+impl From<String> for ImportedValue {
+    fn from(value: String) -> Self {
+        Self {
+            value,
+            description: None,
         }
     }
+}
 
-    unescaped
+fn read_dotenv(path: &Path) -> Result<Vec<dotenv::Entry>> {
+    let input = std::fs::read_to_string(path).map_err(|e| FnoxError::ImportReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    dotenv::parse(&input).map_err(|e| dotenv_error(e, &path.display().to_string(), &input))
+}
+
+fn dotenv_error(e: dotenv::UnterminatedQuote, source_name: &str, input: &str) -> FnoxError {
+    FnoxError::ImportParseErrorWithSource {
+        format: "dotenv".to_string(),
+        details: e.to_string(),
+        src: Arc::new(NamedSource::new(source_name, Arc::new(input.to_string()))),
+        span: SourceSpan::new(e.offset.into(), 1usize),
+    }
+}
+
+/// Variables the shell or login session sets, which are never secrets and
+/// only get imported with --from-env when an allowlist names them
+const SESSION_VARS: &[&str] = &[
+    "_",
+    "COLORTERM",
+    "COLUMNS",
+    "DISPLAY",
+    "EDITOR",
+    "HISTFILE",
+    "HOME",
+    "HOSTNAME",
+    "LANG",
+    "LINES",
+    "LOGNAME",
+    "MAIL",
+    "OLDPWD",
+    "PAGER",
+    "PATH",
+    "PS1",
+    "PS2",
+    "PWD",
+    "SHELL",
+    "SHLVL",
+    "SSH_AGENT_PID",
+    "SSH_AUTH_SOCK",
+    "SSH_CLIENT",
+    "SSH_CONNECTION",
+    "SSH_TTY",
+    "TERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "TMPDIR",
+    "TZ",
+    "USER",
+    "VISUAL",
+    "WAYLAND_DISPLAY",
+];
+
+fn is_session_var(key: &str) -> bool {
+    SESSION_VARS.contains(&key)
+        || key.starts_with("LC_")
+        || key.starts_with("XDG_")
+        || key.starts_with("DBUS_")
+}
+
+/// The variables in `vars` to import with --from-env: those starting with
+/// `env_prefix` and named in `allowlist`, when given. fnox's own `FNOX_*`
+/// settings are never imported, and shell/session variables only when the
+/// allowlist names them.
+fn env_secrets(
+    vars: impl IntoIterator<Item = (String, String)>,
+    env_prefix: Option<&str>,
+    allowlist: Option<&HashSet<String>>,
+) -> IndexMap<String, String> {
+    vars.into_iter()
+        .filter(|(key, _)| !key.starts_with("FNOX_"))
+        .filter(|(key, _)| {
+            !is_session_var(key) || allowlist.is_some_and(|names| names.contains(key))
+        })
+        .filter(|(key, _)| env_prefix.is_none_or(|prefix| key.starts_with(prefix)))
+        .filter(|(key, _)| allowlist.is_none_or(|names| names.contains(key)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::unescape_double_quoted_env_value;
+    use super::{env_secrets, secrets_for_references};
+    use indexmap::IndexMap;
+    use std::collections::HashSet;
+
+    fn env() -> Vec<(String, String)> {
+        [
+            ("APP_DATABASE_URL", "postgres://localhost/app"),
+            ("APP_API_KEY", "key-123"),
+            ("FNOX_PROFILE", "production"),
+            ("FNOX_AGE_KEY", "AGE-SECRET-KEY-1..."),
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/me"),
+            ("SSH_AUTH_SOCK", "/tmp/ssh-agent.sock"),
+            ("XDG_RUNTIME_DIR", "/run/user/1000"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn keys(secrets: &IndexMap<String, String>) -> Vec<&str> {
+        let mut keys: Vec<&str> = secrets.keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn env_import_skips_fnox_settings_and_session_vars() {
+        let secrets = env_secrets(env(), None, None);
+        assert_eq!(keys(&secrets), ["APP_API_KEY", "APP_DATABASE_URL"]);
+        assert_eq!(secrets["APP_DATABASE_URL"], "postgres://localhost/app");
+    }
+
+    #[test]
+    fn env_import_filters_by_prefix_and_allowlist() {
+        let secrets = env_secrets(env(), Some("APP_"), None);
+        assert_eq!(keys(&secrets), ["APP_API_KEY", "APP_DATABASE_URL"]);
+
+        // FNOX_ variables stay out even when the prefix matches them
+        assert!(env_secrets(env(), Some("FNOX_"), None).is_empty());
+        // and so do session variables unless the allowlist names them
+        assert!(env_secrets(env(), Some("XDG_"), None).is_empty());
+
+        let allowlist: HashSet<String> = ["APP_API_KEY".to_string(), "PATH".to_string()].into();
+        let secrets = env_secrets(env(), None, Some(&allowlist));
+        assert_eq!(keys(&secrets), ["APP_API_KEY", "PATH"]);
+
+        let secrets = env_secrets(env(), Some("APP_"), Some(&allowlist));
+        assert_eq!(keys(&secrets), ["APP_API_KEY"]);
+    }
 
     #[test]
-    fn unescape_double_quoted_env_value_handles_export_escapes() {
+    fn references_are_keyed_in_upper_snake_case() {
+        let secrets = secrets_for_references(vec![
+            "myapp/db-password".to_string(),
+            "api.key".to_string(),
+            "/prod/Token".to_string(),
+        ])
+        .unwrap();
+        let mut pairs: Vec<(&str, &str)> = secrets
+            .iter()
+            .map(|(key, reference)| (key.as_str(), reference.as_str()))
+            .collect();
+        pairs.sort();
         assert_eq!(
-            unescape_double_quoted_env_value(r#"line1\nline2\t\"quoted\"\\path"#),
-            "line1\nline2\t\"quoted\"\\path"
+            pairs,
+            [
+                ("API_KEY", "api.key"),
+                ("MYAPP_DB_PASSWORD", "myapp/db-password"),
+                ("PROD_TOKEN", "/prod/Token"),
+            ]
         );
     }
 
     #[test]
-    fn unescape_double_quoted_env_value_preserves_unknown_escapes() {
-        assert_eq!(
-            unescape_double_quoted_env_value(r#"secret\$value\`tick"#),
-            r#"secret\$value\`tick"#
+    fn references_with_the_same_key_are_rejected() {
+        let err =
+            secrets_for_references(vec!["db-url".to_string(), "db.url".to_string()]).unwrap_err();
+        assert!(
+            err.to_string().contains("would both be imported as DB_URL"),
+            "{err}"
         );
     }
 }
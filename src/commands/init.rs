@@ -1,11 +1,52 @@
 use crate::commands::Cli;
-use crate::config::{Config, ProviderConfig, SecretConfig};
+use crate::config::{Config, IfMissing, ProviderConfig, SecretConfig};
 use crate::error::{FnoxError, Result};
 use crate::providers::{WizardCategory, WizardInfo, get_provider_from_resolved};
 use clap::Args;
 use demand::{Confirm, DemandOption, Input, Select};
+use indexmap::IndexMap;
+use serde::Deserialize;
 use std::collections::HashMap;
 
+/// Project templates shipped with fnox: name, description and secret stubs
+const TEMPLATES: &[(&str, &str, &str)] = &[
+    (
+        "node",
+        "Node.js service",
+        include_str!("../assets/templates/node.toml"),
+    ),
+    (
+        "python",
+        "Python service (Django, Flask, FastAPI)",
+        include_str!("../assets/templates/python.toml"),
+    ),
+    (
+        "rails",
+        "Rails application",
+        include_str!("../assets/templates/rails.toml"),
+    ),
+];
+
+/// A template only declares secret stubs, never values or providers
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Template {
+    #[serde(default)]
+    secrets: IndexMap<String, TemplateSecret>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateSecret {
+    description: Option<String>,
+    #[serde(default = "default_required")]
+    required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
 #[derive(Debug, Args)]
 #[command(visible_alias = "i")]
 pub struct InitCommand {
@@ -20,6 +61,14 @@ pub struct InitCommand {
     /// Skip the interactive wizard and create a minimal config
     #[arg(long)]
     skip_wizard: bool,
+
+    /// Pre-populate secret stubs from a built-in project template
+    #[arg(long, value_parser = ["node", "python", "rails"], conflicts_with = "template_file")]
+    template: Option<String>,
+
+    /// Pre-populate secret stubs from a template file or https:// URL
+    #[arg(long, value_name = "PATH|URL")]
+    template_file: Option<String>,
 }
 
 impl InitCommand {
@@ -56,14 +105,29 @@ impl InitCommand {
             })?;
         }
 
-        let config = if self.skip_wizard || !atty::is(atty::Stream::Stdin) {
-            // Non-interactive mode
-            Config::new()
-        } else {
+        // Load the template up front so a bad one fails before the wizard
+        let template = match (&self.template, &self.template_file) {
+            (Some(name), _) => Some(builtin_template(name)?),
+            (None, Some(source)) => Some(load_template_file(source).await?),
+            (None, None) => None,
+        };
+
+        let interactive = !self.skip_wizard && atty::is(atty::Stream::Stdin);
+        let mut config = if interactive {
             // Interactive wizard mode
             self.run_wizard().await?
+        } else {
+            // Non-interactive mode
+            Config::new()
         };
 
+        let template = match template {
+            Some(template) => Some(template),
+            None if interactive => self.select_template()?,
+            None => None,
+        };
+        let stubs = template.map_or(0, |template| apply_template(&mut config, template));
+
         config.save(&config_path)?;
 
         println!(
@@ -78,6 +142,12 @@ impl InitCommand {
             "  • Add secrets: fnox set MY_SECRET <value>{}",
             if self.global { " --global" } else { "" }
         );
+        if stubs > 0 {
+            println!(
+                "  • Fill in the {} template secrets: fnox check lists what's missing",
+                stubs
+            );
+        }
         println!("  • List secrets: fnox list");
         println!("  • Use in commands: fnox exec -- <command>");
 
@@ -220,6 +290,27 @@ impl InitCommand {
     fn collect_fields(&self, info: &WizardInfo) -> Result<HashMap<String, String>> {
         let mut fields = HashMap::new();
 
+        // age can share one passphrase instead of a keypair
+        if info.provider_type == "age" {
+            let mode = Select::new("How should secrets be encrypted?")
+                .option(
+                    DemandOption::new("keypair")
+                        .label("Keypair")
+                        .description("Encrypt to an age public key"),
+                )
+                .option(
+                    DemandOption::new("passphrase")
+                        .label("Passphrase")
+                        .description("Share one passphrase, from FNOX_AGE_PASSPHRASE or a prompt"),
+                )
+                .run()
+                .map_err(|e| FnoxError::Config(format!("Wizard cancelled: {}", e)))?;
+            if mode == "passphrase" {
+                fields.insert("mode".to_string(), mode.to_string());
+                return Ok(fields);
+            }
+        }
+
         for field in info.fields {
             let result = Input::new(field.label).placeholder(field.placeholder).run();
 
@@ -239,6 +330,25 @@ impl InitCommand {
         Ok(fields)
     }
 
+    /// Offer the built-in templates at the end of the wizard
+    fn select_template(&self) -> Result<Option<Template>> {
+        let mut select = Select::new("Start from a project template?")
+            .description("Adds secret stubs you then fill in with `fnox set`")
+            .filterable(false)
+            .option(DemandOption::new("none").label("No template"));
+        for (name, description, _) in TEMPLATES {
+            select = select.option(DemandOption::new(*name).label(description));
+        }
+
+        let selected = select
+            .run()
+            .map_err(|e| FnoxError::Config(format!("Wizard cancelled: {}", e)))?;
+        if selected == "none" {
+            return Ok(None);
+        }
+        builtin_template(selected).map(Some)
+    }
+
     /// Get the provider name from the user
     fn get_provider_name(&self, default: &str) -> Result<String> {
         Input::new("Provider name:")
@@ -288,3 +398,128 @@ impl InitCommand {
         }
     }
 }
+
+fn builtin_template(name: &str) -> Result<Template> {
+    let (_, _, content) = TEMPLATES
+        .iter()
+        .find(|(template, _, _)| *template == name)
+        .ok_or_else(|| FnoxError::Config(format!("Unknown template '{}'", name)))?;
+    parse_template(name, content)
+}
+
+/// Read a custom template from disk, or fetch it over HTTPS
+async fn load_template_file(source: &str) -> Result<Template> {
+    let content = if source.starts_with("https://") {
+        fetch_template(source).await?
+    } else if source.contains("://") {
+        return Err(FnoxError::Config(format!(
+            "Template URL '{}' must use https://",
+            source
+        )));
+    } else {
+        std::fs::read_to_string(source).map_err(|e| {
+            FnoxError::Config(format!("Failed to read template '{}': {}", source, e))
+        })?
+    };
+    parse_template(source, &content)
+}
+
+async fn fetch_template(url: &str) -> Result<String> {
+    let failed =
+        |e: String| FnoxError::Config(format!("Failed to fetch template '{}': {}", url, e));
+    let response = crate::http::http_client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(failed(format!("HTTP {}", response.status())));
+    }
+    response.text().await.map_err(|e| failed(e.to_string()))
+}
+
+fn parse_template(source: &str, content: &str) -> Result<Template> {
+    toml_edit::de::from_str(content).map_err(|e| {
+        FnoxError::Config(format!(
+            "Invalid template '{}' (templates only declare secrets with a description and `required`): {}",
+            source, e
+        ))
+    })
+}
+
+/// Add the template's secrets as stubs without a value, so `fnox check`
+/// reports them until they're set. Existing secrets are kept as they are.
+/// Returns how many stubs were added.
+fn apply_template(config: &mut Config, template: Template) -> usize {
+    let mut added = 0;
+    for (name, stub) in template.secrets {
+        if config.secrets.contains_key(&name) {
+            continue;
+        }
+        let mut secret = SecretConfig::new();
+        secret.description = stub.description;
+        secret.if_missing = Some(if stub.required {
+            IfMissing::Error
+        } else {
+            IfMissing::Warn
+        });
+        config.secrets.insert(name, secret);
+        added += 1;
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_templates_parse_as_stubs() {
+        for (name, _, _) in TEMPLATES {
+            let template = builtin_template(name).unwrap();
+            assert!(!template.secrets.is_empty(), "{name} has no secrets");
+        }
+    }
+
+    #[test]
+    fn templates_cannot_set_values() {
+        let err = parse_template("custom", "[secrets]\nA = { default = \"x\" }\n").unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid template 'custom'"),
+            "{err}"
+        );
+
+        let err = parse_template("custom", "[providers.age]\ntype = \"age\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid template 'custom'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn apply_template_adds_stubs_and_keeps_existing_secrets() {
+        let mut config = Config::new();
+        let mut existing = SecretConfig::new();
+        existing.default = Some("kept".to_string());
+        config.secrets.insert("DATABASE_URL".to_string(), existing);
+
+        let template = parse_template(
+            "custom",
+            "[secrets]\nDATABASE_URL = { description = \"db\" }\nSENTRY_DSN = { required = false }\nAPI_KEY = {}\n",
+        )
+        .unwrap();
+        assert_eq!(apply_template(&mut config, template), 2);
+
+        assert_eq!(
+            config.secrets["DATABASE_URL"].default.as_deref(),
+            Some("kept")
+        );
+        assert_eq!(
+            config.secrets["SENTRY_DSN"].if_missing,
+            Some(IfMissing::Warn)
+        );
+        let api_key = &config.secrets["API_KEY"];
+        assert_eq!(api_key.if_missing, Some(IfMissing::Error));
+        assert!(!api_key.has_value());
+    }
+}
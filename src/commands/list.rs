@@ -1,13 +1,9 @@
 use crate::commands::Cli;
 use crate::config::Config;
 use crate::error::Result;
+use crate::table::Table;
 use clap::Args;
 use indexmap::IndexMap;
-use tabled::settings::{
-    Color, Format, Modify, Style, Width,
-    object::{Columns, Rows},
-};
-use tabled::{Table, Tabled};
 
 #[derive(Debug, Args)]
 #[command(visible_aliases = ["ls", "secrets"])]
@@ -27,62 +23,21 @@ pub struct ListCommand {
     /// Output secret keys for shell completion (one per line)
     #[arg(long, hide = true)]
     pub complete: bool,
-}
 
-#[derive(Debug, Tabled)]
-struct SecretRow {
-    #[tabled(rename = "Key")]
-    key: String,
-    #[tabled(rename = "Type")]
-    source_type: String,
-    #[tabled(rename = "Provider Key")]
-    provider_key: String,
-    #[tabled(rename = "Description")]
-    description: String,
+    /// Show secrets from nested groups under a row for their group
+    #[arg(long)]
+    pub grouped: bool,
 }
 
-#[derive(Debug, Tabled)]
-struct SecretRowWithSources {
-    #[tabled(rename = "Key")]
-    key: String,
-    #[tabled(rename = "Type")]
-    source_type: String,
-    #[tabled(rename = "Source File")]
-    source_file: String,
-    #[tabled(rename = "Provider Key")]
-    provider_key: String,
-    #[tabled(rename = "Description")]
-    description: String,
-}
+/// Provider keys and the last column are cut to this width unless `--full`
+const COLUMN_WIDTH: usize = 40;
 
-#[derive(Debug, Tabled)]
-struct SecretRowWithValues {
-    #[tabled(rename = "Key")]
-    key: String,
-    #[tabled(rename = "Type")]
-    source_type: String,
-    #[tabled(rename = "Provider Key")]
-    provider_key: String,
-    #[tabled(rename = "Description")]
-    description: String,
-    #[tabled(rename = "Value")]
-    value: String,
-}
-
-#[derive(Debug, Tabled)]
-struct SecretRowWithValuesAndSources {
-    #[tabled(rename = "Key")]
-    key: String,
-    #[tabled(rename = "Type")]
-    source_type: String,
-    #[tabled(rename = "Source File")]
-    source_file: String,
-    #[tabled(rename = "Provider Key")]
-    provider_key: String,
-    #[tabled(rename = "Description")]
-    description: String,
-    #[tabled(rename = "Value")]
-    value: String,
+/// A row of the Key column
+enum KeyCell<'a> {
+    /// Heading for a secret group (`--grouped`)
+    Group(String),
+    /// A secret and the text to show for it
+    Secret(&'a String, String),
 }
 
 impl ListCommand {
@@ -128,19 +83,7 @@ impl ListCommand {
             None
         };
 
-        if self.values && self.sources {
-            self.display_with_values_and_sources(
-                &keys,
-                &profile_secrets,
-                resolved_values.as_ref().unwrap(),
-            )?;
-        } else if self.values {
-            self.display_with_values(&keys, &profile_secrets, resolved_values.as_ref().unwrap())?;
-        } else if self.sources {
-            self.display_with_sources(&keys, &profile_secrets)?;
-        } else {
-            self.display_basic(&keys, &profile_secrets)?;
-        }
+        self.display(&keys, &profile_secrets, resolved_values.as_ref());
 
         Ok(())
     }
@@ -151,12 +94,9 @@ impl ListCommand {
     ) -> (String, String) {
         let (base_type, provider_key) = if let Some(provider) = secret_config.provider() {
             let pk = secret_config.value().unwrap_or("");
-            let pk_display = if !self.full && pk.len() > 40 {
-                format!("{}...", &pk[..37])
-            } else {
-                pk.to_string()
-            };
-            (format!("provider ({})", provider), pk_display)
+            (format!("provider ({})", provider), pk.to_string())
+        } else if let Some(ref template) = secret_config.template {
+            ("template".to_string(), template.clone())
         } else if secret_config.value().is_some() {
             ("stored value".to_string(), String::new())
         } else if secret_config.default.is_some() {
@@ -174,163 +114,108 @@ impl ListCommand {
         (source_type, provider_key)
     }
 
-    fn display_basic(
-        &self,
-        keys: &[&String],
-        profile_secrets: &indexmap::IndexMap<String, crate::config::SecretConfig>,
-    ) -> Result<()> {
-        let mut rows = Vec::new();
-        for key in keys {
-            let secret_config = &profile_secrets[*key];
-            let (source_type, provider_key_str) =
-                self.get_source_type_and_provider_key(secret_config);
-            let description_str = secret_config
-                .description
-                .as_deref()
-                .unwrap_or("")
-                .to_string();
-
-            rows.push(SecretRow {
-                key: (*key).clone(),
-                source_type,
-                provider_key: provider_key_str,
-                description: description_str,
-            });
-        }
-
-        self.display_table(rows)
-    }
-
-    fn display_with_sources(
+    /// The Key column: with `--grouped`, secrets defined in a group are indented
+    /// under a heading row for each group they're nested in.
+    fn key_cells<'a>(
         &self,
-        keys: &[&String],
+        keys: &[&'a String],
         profile_secrets: &indexmap::IndexMap<String, crate::config::SecretConfig>,
-    ) -> Result<()> {
-        let mut rows = Vec::new();
-        for key in keys {
-            let secret_config = &profile_secrets[*key];
-            let (source_type, provider_key_str) =
-                self.get_source_type_and_provider_key(secret_config);
-            let description_str = secret_config
-                .description
-                .as_deref()
-                .unwrap_or("")
-                .to_string();
-            let source_file = secret_config
-                .source_path
-                .as_ref()
-                .map(|p| p.display().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-
-            rows.push(SecretRowWithSources {
-                key: (*key).clone(),
-                source_type,
-                source_file,
-                provider_key: provider_key_str,
-                description: description_str,
-            });
+    ) -> Vec<KeyCell<'a>> {
+        let mut cells = Vec::new();
+        let mut open: &[String] = &[];
+        for &key in keys {
+            if !self.grouped {
+                cells.push(KeyCell::Secret(key, key.clone()));
+                continue;
+            }
+            let groups = profile_secrets[key]
+                .source_group
+                .split_last()
+                .map_or(&[][..], |(_, groups)| groups);
+            let shared = open
+                .iter()
+                .zip(groups)
+                .take_while(|(open, group)| open == group)
+                .count();
+            for depth in shared..groups.len() {
+                cells.push(KeyCell::Group(format!(
+                    "{}[{}]",
+                    "  ".repeat(depth),
+                    groups[..=depth].join(".")
+                )));
+            }
+            cells.push(KeyCell::Secret(
+                key,
+                format!("{}{}", "  ".repeat(groups.len()), key),
+            ));
+            open = groups;
         }
-
-        self.display_table(rows)
+        cells
     }
 
-    fn display_with_values(
+    /// Print the secrets table, with Source File and Value columns for
+    /// `--sources` and `--values`
+    fn display(
         &self,
         keys: &[&String],
         profile_secrets: &indexmap::IndexMap<String, crate::config::SecretConfig>,
-        resolved_values: &IndexMap<String, Option<String>>,
-    ) -> Result<()> {
-        let mut rows = Vec::new();
-        for key in keys {
-            let secret_config = &profile_secrets[*key];
-            let (source_type, provider_key_str) =
-                self.get_source_type_and_provider_key(secret_config);
-            let description_str = secret_config
-                .description
-                .as_deref()
-                .unwrap_or("")
-                .to_string();
-
-            // Use the resolved value if available, otherwise show placeholder
-            let value_str = resolved_values
-                .get(*key)
-                .and_then(|v| v.as_ref())
-                .cloned()
-                .unwrap_or_else(|| "<not available>".to_string());
-
-            rows.push(SecretRowWithValues {
-                key: (*key).clone(),
-                source_type,
-                provider_key: provider_key_str,
-                description: description_str,
-                value: value_str,
-            });
+        resolved_values: Option<&IndexMap<String, Option<String>>>,
+    ) {
+        let mut headers = vec!["Key", "Type"];
+        if self.sources {
+            headers.push("Source File");
         }
-
-        self.display_table(rows)
-    }
-
-    fn display_with_values_and_sources(
-        &self,
-        keys: &[&String],
-        profile_secrets: &indexmap::IndexMap<String, crate::config::SecretConfig>,
-        resolved_values: &IndexMap<String, Option<String>>,
-    ) -> Result<()> {
-        let mut rows = Vec::new();
-        for key in keys {
-            let secret_config = &profile_secrets[*key];
-            let (source_type, provider_key_str) =
-                self.get_source_type_and_provider_key(secret_config);
-            let description_str = secret_config
-                .description
-                .as_deref()
-                .unwrap_or("")
-                .to_string();
-            let source_file = secret_config
-                .source_path
-                .as_ref()
-                .map(|p| p.display().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-
-            // Use the resolved value if available, otherwise show placeholder
-            let value_str = resolved_values
-                .get(*key)
-                .and_then(|v| v.as_ref())
-                .cloned()
-                .unwrap_or_else(|| "<not available>".to_string());
-
-            rows.push(SecretRowWithValuesAndSources {
-                key: (*key).clone(),
-                source_type,
-                source_file,
-                provider_key: provider_key_str,
-                description: description_str,
-                value: value_str,
-            });
+        headers.extend(["Provider Key", "Description"]);
+        if resolved_values.is_some() {
+            headers.push("Value");
         }
-
-        self.display_table(rows)
-    }
-
-    fn display_table<T: tabled::Tabled>(&self, rows: Vec<T>) -> Result<()> {
-        let mut table = Table::new(rows);
-        table.with(Style::empty());
-
-        // Apply colors only if enabled
-        if console::colors_enabled() {
-            table.with(
-                Modify::new(Rows::first())
-                    .with(Color::FG_BRIGHT_BLUE)
-                    .with(Format::content(|s| format!("\x1b[1m{}\x1b[0m", s))),
-            );
+        let provider_key_column = headers.len() - if resolved_values.is_some() { 3 } else { 2 };
+        let last_column = headers.len() - 1;
+
+        let mut table = Table::new(headers);
+        for cell in self.key_cells(keys, profile_secrets) {
+            let (key, label) = match cell {
+                KeyCell::Group(label) => {
+                    table.row([label]);
+                    continue;
+                }
+                KeyCell::Secret(key, label) => (key, label),
+            };
+            let secret_config = &profile_secrets[key];
+            let (source_type, provider_key) = self.get_source_type_and_provider_key(secret_config);
+
+            let mut row = vec![label, source_type];
+            if self.sources {
+                row.push(
+                    secret_config
+                        .source_path
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                );
+            }
+            row.push(provider_key);
+            row.push(secret_config.description.clone().unwrap_or_default());
+            if let Some(resolved_values) = resolved_values {
+                // Use the resolved value if available, otherwise show placeholder
+                row.push(
+                    resolved_values
+                        .get(key)
+                        .and_then(|v| v.as_ref())
+                        .cloned()
+                        .unwrap_or_else(|| "<not available>".to_string()),
+                );
+            }
+            table.row(row);
         }
 
-        if !self.full {
-            // Apply width constraints for description and provider key columns
-            table.with(Modify::new(Columns::last()).with(Width::wrap(40)));
+        if self.full {
+            table.print_full();
+        } else {
+            table
+                .max_width(provider_key_column, COLUMN_WIDTH)
+                .max_width(last_column, COLUMN_WIDTH)
+                .print();
         }
-
-        println!("{}", table);
-        Ok(())
     }
 }
@@ -6,17 +6,23 @@ use clap::{Parser, Subcommand};
 use crate::config::Config;
 
 pub mod activate;
+pub mod cache;
 pub mod check;
 pub mod ci_redact;
+pub mod complete;
 pub mod completion;
 pub mod config_files;
 pub mod daemon;
 pub mod deactivate;
+pub mod diff;
 pub mod doctor;
 pub mod edit;
+mod env_names;
 pub mod exec;
 pub mod export;
+pub mod fmt;
 pub mod get;
+pub mod history;
 pub mod hook_env;
 pub mod import;
 pub mod init;
@@ -27,6 +33,7 @@ pub mod profiles;
 pub mod provider;
 pub mod reencrypt;
 pub mod remove;
+pub mod rename;
 pub mod scan;
 pub mod schema;
 pub mod set;
@@ -84,13 +91,20 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Print candidates for the word being completed, for the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete(complete::CompleteCommand),
+
     /// Output shell activation code to enable automatic secret loading
     Activate(activate::ActivateCommand),
 
+    /// Inspect the daemon's cache of resolved values
+    Cache(cache::CacheCommand),
+
     /// Check if all required secrets are defined and configured
     Check(check::CheckCommand),
 
-    /// Redact secrets in CI/CD output (GitHub Actions mask)
+    /// Redact secrets in CI/CD output (GitHub Actions; GitLab CI gets masking guidance)
     #[command(hide = true)]
     CiRedact(ci_redact::CiRedactCommand),
 
@@ -106,6 +120,9 @@ pub enum Commands {
     /// Disable fnox shell integration in the current shell session
     Deactivate(deactivate::DeactivateCommand),
 
+    /// Compare secrets between two profiles
+    Diff(diff::DiffCommand),
+
     /// Show diagnostic information about the current fnox state
     Doctor(doctor::DoctorCommand),
 
@@ -118,9 +135,15 @@ pub enum Commands {
     /// Export secrets in various formats
     Export(export::ExportCommand),
 
+    /// Rewrite config files in a canonical style
+    Fmt(fmt::FmtCommand),
+
     /// Get a secret value
     Get(get::GetCommand),
 
+    /// Show the stored versions of a secret, for providers that keep them
+    History(history::HistoryCommand),
+
     /// Internal command used by shell hooks to load secrets
     #[command(hide = true)]
     HookEnv(hook_env::HookEnvCommand),
@@ -152,6 +175,9 @@ pub enum Commands {
     /// Remove a secret
     Remove(remove::RemoveCommand),
 
+    /// Rename a secret
+    Rename(rename::RenameCommand),
+
     /// Scan repository for potential secrets
     Scan(scan::ScanCommand),
 
@@ -184,8 +210,11 @@ impl Commands {
             // Commands that don't need config
             Commands::Version(cmd) => cmd.run(cli).await,
             Commands::Init(cmd) => cmd.run(cli).await,
+            Commands::Complete(cmd) => cmd.run(cli).await,
             Commands::Completion(cmd) => cmd.run(cli).await,
             Commands::ConfigFiles(cmd) => cmd.run(cli).await,
+            Commands::Fmt(cmd) => cmd.run(cli).await,
+            Commands::Cache(cmd) => cmd.run(cli).await,
             Commands::Daemon(cmd) => cmd.run(cli).await,
             Commands::Schema(cmd) => cmd.run(cli).await,
             Commands::Sponsors(cmd) => cmd.run(cli).await,
@@ -206,10 +235,12 @@ impl Commands {
             // Commands that need config
             Commands::Check(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::CiRedact(cmd) => cmd.run(cli, self.load_config(cli)?).await,
+            Commands::Diff(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Doctor(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Edit(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Export(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Get(cmd) => cmd.run(cli, self.load_config(cli)?).await,
+            Commands::History(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Import(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Lease(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::List(cmd) => cmd.run(cli, self.load_config(cli)?).await,
@@ -218,6 +249,7 @@ impl Commands {
             Commands::Provider(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Reencrypt(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Remove(cmd) => cmd.run(cli).await,
+            Commands::Rename(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Exec(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Set(cmd) => cmd.run(cli, self.load_config(cli)?).await,
             Commands::Sync(cmd) => cmd.run(cli, self.load_config(cli)?).await,
@@ -1,6 +1,7 @@
 use crate::commands::Cli;
 use crate::config::Config;
 use crate::error::Result;
+use crate::table::Table;
 use clap::Args;
 
 #[derive(Debug, Args)]
@@ -25,11 +26,12 @@ impl ProfilesCommand {
             }
         } else {
             // Normal output
-            println!("Available profiles:");
+            let mut table = Table::new(["Profile", "Secrets"]);
             for name in profile_names {
                 let secret_count = config.get_secrets(&name).map(|s| s.len()).unwrap_or(0);
-                println!("  {} ({} secrets)", name, secret_count);
+                table.row([name, secret_count.to_string()]);
             }
+            table.print();
         }
         Ok(())
     }
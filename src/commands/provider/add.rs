@@ -1,8 +1,12 @@
 use crate::commands::Cli;
-use crate::config::Config;
+use crate::commands::set::{StoredValue, ValueStore};
+use crate::config::{Config, ProviderConfig, SecretConfig};
 use crate::error::{FnoxError, Result};
 use crate::providers::{OptionProviderSecretRef, OptionStringOrSecretRef, StringOrSecretRef};
 use clap::Args;
+use demand::Confirm;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
 
 use super::ProviderType;
 
@@ -20,13 +24,17 @@ pub struct AddCommand {
     #[arg(short = 'g', long)]
     pub global: bool,
 
+    /// Make the new provider the default and move plaintext secrets to it without prompting
+    #[arg(long)]
+    pub migrate: bool,
+
     /// Default Proton Pass vault name (only valid with provider type proton-pass)
     #[arg(long)]
     pub vault: Option<String>,
 }
 
 impl AddCommand {
-    pub async fn run(&self, cli: &Cli) -> Result<()> {
+    pub async fn run(&self, cli: &Cli, merged_config: Config) -> Result<()> {
         tracing::debug!(
             "Adding provider '{}' of type '{}'",
             self.provider,
@@ -80,6 +88,9 @@ impl AddCommand {
                 vault: OptionStringOrSecretRef::literal("default"),
                 account: OptionStringOrSecretRef::none(),
                 token: OptionStringOrSecretRef::none(),
+                backend: None,
+                connect_host: OptionStringOrSecretRef::none(),
+                connect_token: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -88,6 +99,8 @@ impl AddCommand {
                 profile: OptionStringOrSecretRef::none(),
                 prefix: OptionStringOrSecretRef::none(),
                 endpoint: OptionStringOrSecretRef::none(),
+                role_arn: OptionStringOrSecretRef::none(),
+                external_id: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -97,6 +110,8 @@ impl AddCommand {
                 token: OptionStringOrSecretRef::none(),
                 namespace: OptionStringOrSecretRef::none(),
                 credential_command: OptionStringOrSecretRef::none(),
+                auth: Default::default(),
+                kv_version: None,
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -110,6 +125,10 @@ impl AddCommand {
                 region: StringOrSecretRef::from("us-east-1"),
                 key_id: StringOrSecretRef::from("alias/my-key"),
                 endpoint: OptionStringOrSecretRef::none(),
+                profile: OptionStringOrSecretRef::none(),
+                role_arn: OptionStringOrSecretRef::none(),
+                external_id: OptionStringOrSecretRef::none(),
+                encryption_context: IndexMap::new(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -118,12 +137,16 @@ impl AddCommand {
                 profile: OptionStringOrSecretRef::none(),
                 prefix: OptionStringOrSecretRef::literal("/myapp/prod/"),
                 endpoint: OptionStringOrSecretRef::none(),
+                role_arn: OptionStringOrSecretRef::none(),
+                external_id: OptionStringOrSecretRef::none(),
+                tier: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             },
             ProviderType::AzureKms => crate::config::ProviderConfig::AzureKms {
                 vault_url: StringOrSecretRef::from("https://my-vault.vault.azure.net/"),
                 key_name: StringOrSecretRef::from("my-key"),
+                auth: IndexMap::new(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -131,6 +154,7 @@ impl AddCommand {
                 crate::config::ProviderConfig::AzureSecretsManager {
                     vault_url: StringOrSecretRef::from("https://my-vault.vault.azure.net/"),
                     prefix: OptionStringOrSecretRef::none(),
+                    auth: IndexMap::new(),
                     auth_command: None,
                     daemon_cache: None,
                 }
@@ -140,6 +164,7 @@ impl AddCommand {
                 location: StringOrSecretRef::from("global"),
                 keyring: StringOrSecretRef::from("my-keyring"),
                 key: StringOrSecretRef::from("my-key"),
+                aad: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -163,6 +188,8 @@ impl AddCommand {
                 recipients: vec!["age1...".to_string()],
                 key_file: OptionStringOrSecretRef::none(),
                 identity: OptionProviderSecretRef::none(),
+                key_files: Vec::new(),
+                passphrase: None,
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -206,6 +233,26 @@ impl AddCommand {
                 auth_command: None,
                 daemon_cache: None,
             },
+            ProviderType::Envfile => crate::config::ProviderConfig::Envfile {
+                path: StringOrSecretRef::from(".env"),
+                auth_command: None,
+                daemon_cache: None,
+            },
+            ProviderType::Etcd => crate::config::ProviderConfig::Etcd {
+                endpoints: vec!["http://localhost:2379".to_string()],
+                prefix: OptionStringOrSecretRef::literal("/fnox/"),
+                ca_cert: OptionStringOrSecretRef::none(),
+                client_cert: OptionStringOrSecretRef::none(),
+                client_key: OptionStringOrSecretRef::none(),
+                auth_command: None,
+                daemon_cache: None,
+            },
+            ProviderType::Exec => crate::config::ProviderConfig::Exec {
+                command: StringOrSecretRef::from("my-tool get {key}"),
+                put_command: OptionStringOrSecretRef::none(),
+                auth_command: None,
+                daemon_cache: None,
+            },
             ProviderType::Foks => crate::config::ProviderConfig::Foks {
                 prefix: OptionStringOrSecretRef::literal("/fnox/"),
                 team: OptionStringOrSecretRef::none(),
@@ -215,6 +262,16 @@ impl AddCommand {
                 auth_command: None,
                 daemon_cache: None,
             },
+            ProviderType::Http => crate::config::ProviderConfig::Http {
+                url_template: StringOrSecretRef::from(
+                    "https://secrets.example.com/v1/secrets/{key}",
+                ),
+                headers: IndexMap::new(),
+                json_path: OptionStringOrSecretRef::literal("data.value"),
+                health_path: OptionStringOrSecretRef::none(),
+                auth_command: None,
+                daemon_cache: None,
+            },
             ProviderType::Infisical => crate::config::ProviderConfig::Infisical {
                 project_id: OptionStringOrSecretRef::literal("your-project-id"),
                 environment: OptionStringOrSecretRef::literal("dev"),
@@ -232,6 +289,19 @@ impl AddCommand {
             ProviderType::Keychain => crate::config::ProviderConfig::Keychain {
                 service: StringOrSecretRef::from("fnox"),
                 prefix: OptionStringOrSecretRef::none(),
+                require_biometrics: None,
+                auth_command: None,
+                daemon_cache: None,
+            },
+            ProviderType::Kubernetes => crate::config::ProviderConfig::Kubernetes {
+                namespace: StringOrSecretRef::from("default"),
+                context: OptionStringOrSecretRef::none(),
+                kubeconfig: OptionStringOrSecretRef::none(),
+                auth_command: None,
+                daemon_cache: None,
+            },
+            ProviderType::Local => crate::config::ProviderConfig::Local {
+                service: OptionStringOrSecretRef::none(),
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -239,6 +309,7 @@ impl AddCommand {
                 prefix: OptionStringOrSecretRef::literal("fnox/"),
                 store_dir: OptionStringOrSecretRef::none(),
                 gpg_opts: OptionStringOrSecretRef::none(),
+                whole_entry: None,
                 auth_command: None,
                 daemon_cache: None,
             },
@@ -264,6 +335,11 @@ impl AddCommand {
                 auth_command: None,
                 daemon_cache: None,
             },
+            ProviderType::SystemdCreds => crate::config::ProviderConfig::SystemdCreds {
+                with_key: OptionStringOrSecretRef::none(),
+                auth_command: None,
+                daemon_cache: None,
+            },
         };
 
         config
@@ -278,6 +354,170 @@ impl AddCommand {
             target_path.display()
         );
 
+        let interactive = !cli.non_interactive && atty::is(atty::Stream::Stdin);
+        if self.migrate || interactive {
+            self.offer_default(&mut config, &target_path)?;
+            self.offer_migration(cli, &merged_config).await?;
+        }
+
         Ok(())
     }
+
+    /// Ask before acting, unless `--migrate` already answered yes
+    fn confirm(&self, prompt: &str) -> Result<bool> {
+        if self.migrate {
+            return Ok(true);
+        }
+        Confirm::new(prompt)
+            .affirmative("Yes")
+            .negative("No")
+            .run()
+            .map_err(|e| FnoxError::Config(format!("Prompt cancelled: {}", e)))
+    }
+
+    /// Offer to make the new provider the default so `fnox set` uses it
+    fn offer_default(&self, config: &mut Config, target_path: &Path) -> Result<()> {
+        if config.default_provider() == Some(self.provider.as_str()) {
+            return Ok(());
+        }
+        if !self.confirm(&format!("Set '{}' as the default provider?", self.provider))? {
+            return Ok(());
+        }
+
+        config.set_default_provider(Some(self.provider.clone()));
+        config.save(target_path)?;
+        println!("✓ Set '{}' as the default provider", self.provider);
+        Ok(())
+    }
+
+    /// Offer to move the secrets stored in plaintext to the new provider.
+    /// `merged_config` is the config as it was before the provider was added.
+    async fn offer_migration(&self, cli: &Cli, merged_config: &Config) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        let secrets = plaintext_secrets(merged_config, &profile)?;
+        if secrets.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "\n{} secrets in profile '{}' are stored in plaintext:",
+            secrets.len(),
+            profile
+        );
+        for key in secrets.keys() {
+            println!("  {}", console::style(key).cyan());
+        }
+        if !self.confirm(&format!("Move them to '{}'?", self.provider))? {
+            return Ok(());
+        }
+
+        // Reload so the new provider (and default) are part of the config
+        let config = Config::load_smart(&cli.config)?;
+        let provider_config = config
+            .get_providers(&profile)
+            .get(&self.provider)
+            .cloned()
+            .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                provider: self.provider.clone(),
+                profile: profile.clone(),
+                config_path: None,
+                suggestion: None,
+            })?;
+        let provider = crate::providers::get_provider_resolved(
+            &config,
+            &profile,
+            &self.provider,
+            &provider_config,
+        )
+        .await?;
+        let store = ValueStore {
+            cli,
+            config: &config,
+            profile: &profile,
+            dry_run: false,
+            no_guess: false,
+        };
+
+        // Store everything first so a failure leaves the config files untouched
+        let mut by_source: IndexMap<(PathBuf, String), IndexMap<String, SecretConfig>> =
+            IndexMap::new();
+        for (key, (secret_config, plaintext)) in &secrets {
+            let value = match store
+                .store(&self.provider, provider.as_ref(), key, key, plaintext)
+                .await?
+            {
+                StoredValue::Encrypted(value) | StoredValue::Remote(value) => value,
+                StoredValue::Unchanged => {
+                    return Err(FnoxError::Config(format!(
+                        "Provider '{}' can't store secrets; nothing was migrated",
+                        self.provider
+                    )));
+                }
+            };
+
+            let mut updated = secret_config.clone();
+            // A value that only lived in `default` moves to the provider
+            if secret_config.value().is_none() {
+                updated.default = None;
+            }
+            updated.set_provider(Some(self.provider.clone()));
+            updated.set_value(Some(value));
+
+            let source_path = secret_config.source_path.clone().ok_or_else(|| {
+                FnoxError::Config(format!(
+                    "Secret '{}' has no known source file; cannot write back migrated value",
+                    key
+                ))
+            })?;
+            let save_profile = if secret_config.source_is_profile {
+                profile.clone()
+            } else {
+                "default".to_string()
+            };
+            by_source
+                .entry((source_path, save_profile))
+                .or_default()
+                .insert(key.clone(), updated);
+        }
+
+        for ((source_path, save_profile), secrets) in &by_source {
+            Config::save_secrets_to_source(secrets, save_profile, source_path)?;
+        }
+        println!("✓ Moved {} secrets to '{}'", secrets.len(), self.provider);
+        Ok(())
+    }
+}
+
+/// Secrets whose value sits unencrypted in a config file, with that value:
+/// those stored through a `plain` provider and those with only a literal `default`.
+fn plaintext_secrets(
+    config: &Config,
+    profile: &str,
+) -> Result<IndexMap<String, (SecretConfig, String)>> {
+    let providers = config.get_providers(profile);
+    let default_provider = config.get_default_provider(profile).ok().flatten();
+
+    let mut secrets = IndexMap::new();
+    for (key, secret_config) in config.get_secrets(profile)? {
+        if secret_config.template.is_some()
+            || secret_config.providers().is_some()
+            || secret_config.sync.is_some()
+        {
+            continue;
+        }
+        let provider = secret_config
+            .provider()
+            .map(str::to_string)
+            .or_else(|| default_provider.clone());
+        let plaintext = match (secret_config.value(), &secret_config.default) {
+            (Some(value), _) => match provider.and_then(|provider| providers.get(&provider)) {
+                Some(ProviderConfig::Plain { .. }) => value.to_string(),
+                _ => continue,
+            },
+            (None, Some(default)) if !default.contains("${") => default.clone(),
+            _ => continue,
+        };
+        secrets.insert(key, (secret_config, plaintext));
+    }
+    Ok(secrets)
 }
@@ -1,7 +1,13 @@
 use crate::commands::Cli;
-use crate::config::Config;
-use crate::error::Result;
-use clap::Args;
+use crate::config::{Config, ProviderConfig, SecretConfig};
+use crate::error::{FnoxError, Result};
+use crate::providers::ALL_WIZARD_INFO;
+use crate::sanitize::escape_controls;
+use crate::table::Table;
+use clap::{Args, ValueEnum};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::path::PathBuf;
 
 #[derive(Debug, Args)]
 #[command(visible_aliases = ["ls"])]
@@ -9,23 +15,412 @@ pub struct ListCommand {
     /// Output provider names for shell completion (one per line)
     #[arg(long, hide = true)]
     pub complete: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    pub format: ListFormat,
+
+    /// List the secrets this provider holds instead, as the references a
+    /// secret's value would use
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["provider_type", "unused"])]
+    pub keys: Option<String>,
+
+    /// Only show providers of this type, e.g. aws-sm
+    #[arg(long = "type", value_name = "TYPE")]
+    pub provider_type: Option<String>,
+
+    /// Only show providers that no secret or other provider references
+    #[arg(long)]
+    pub unused: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ListFormat {
+    Table,
+    Json,
+}
+
+/// Where a provider is defined and what uses it
+#[derive(Debug, Serialize)]
+struct ProviderUsage {
+    name: String,
+    #[serde(rename = "type")]
+    provider_type: String,
+    /// "global", or the profile whose `[profiles.<name>.providers]` defines it
+    defined_in: String,
+    /// Number of secrets resolved through it, per profile
+    secrets: IndexMap<String, usize>,
+    /// Providers whose config references it, e.g. an age `identity`
+    providers: Vec<String>,
+    /// Profiles it's the default provider of
+    default_for: Vec<String>,
+    source: Option<PathBuf>,
+}
+
+impl ProviderUsage {
+    fn is_unused(&self) -> bool {
+        self.secrets.is_empty() && self.providers.is_empty()
+    }
 }
 
 impl ListCommand {
-    pub async fn run(&self, _cli: &Cli, config: Config) -> Result<()> {
+    pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         tracing::debug!("Listing providers");
 
-        if config.providers.is_empty() {
+        if self.complete {
+            // Always just output provider names, one per line
+            let mut names: Vec<_> = config.providers.keys().collect();
+            names.sort();
+            for name in names {
+                println!("{}", name);
+            }
             return Ok(());
         }
 
-        // Always just output provider names, one per line
-        let mut names: Vec<_> = config.providers.keys().collect();
-        names.sort();
-        for name in names {
-            println!("{}", name);
+        if let Some(name) = &self.keys {
+            return self.list_keys(cli, &config, name).await;
+        }
+
+        if let Some(provider_type) = &self.provider_type
+            && !ALL_WIZARD_INFO
+                .iter()
+                .any(|info| info.provider_type == provider_type)
+        {
+            let mut types: Vec<_> = ALL_WIZARD_INFO
+                .iter()
+                .map(|info| info.provider_type)
+                .collect();
+            types.sort();
+            return Err(FnoxError::Config(format!(
+                "Unknown provider type '{}'. Valid types: {}",
+                provider_type,
+                types.join(", ")
+            )));
+        }
+
+        let usages: Vec<ProviderUsage> = provider_usage(&config)?
+            .into_iter()
+            .filter(|usage| {
+                self.provider_type
+                    .as_ref()
+                    .is_none_or(|t| &usage.provider_type == t)
+            })
+            .filter(|usage| !self.unused || usage.is_unused())
+            .collect();
+
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&usages)?),
+            ListFormat::Table if usages.is_empty() => println!("No providers found"),
+            ListFormat::Table => display_table(&usages),
         }
 
         Ok(())
     }
+
+    /// Print the references `list_secrets` returns for the provider `name`
+    async fn list_keys(&self, cli: &Cli, config: &Config, name: &str) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        let provider = super::resolve_provider(config, &profile, name).await?;
+        let references = provider.list_secrets(None).await?;
+
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&references)?),
+            ListFormat::Table if references.is_empty() => {
+                println!("Provider '{}' has no secrets", name)
+            }
+            ListFormat::Table => {
+                for reference in &references {
+                    println!("{}", escape_controls(reference));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn display_table(usages: &[ProviderUsage]) {
+    let or_dash = |s: String| if s.is_empty() { "-".to_string() } else { s };
+    let mut table = Table::new([
+        "Name",
+        "Type",
+        "Defined In",
+        "Used By",
+        "Default For",
+        "Source File",
+    ]);
+    for usage in usages {
+        let used_by = usage
+            .secrets
+            .iter()
+            .map(|(profile, count)| {
+                format!(
+                    "{}: {} secret{}",
+                    profile,
+                    count,
+                    if *count == 1 { "" } else { "s" }
+                )
+            })
+            .chain(
+                usage
+                    .providers
+                    .iter()
+                    .map(|provider| format!("provider {}", provider)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        table.row([
+            usage.name.clone(),
+            usage.provider_type.clone(),
+            usage.defined_in.clone(),
+            or_dash(used_by),
+            or_dash(usage.default_for.join(", ")),
+            usage
+                .source
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    table.print();
+}
+
+/// Every provider definition, global and per profile, with the secrets and
+/// providers that reference it in each profile where it's in effect. A
+/// profile's own provider shadows a global one of the same name there.
+fn provider_usage(config: &Config) -> Result<Vec<ProviderUsage>> {
+    let mut usages: Vec<ProviderUsage> = config
+        .providers
+        .iter()
+        .map(|(name, provider)| {
+            usage_for(name, provider, "global", config.provider_sources.get(name))
+        })
+        .collect();
+    for (profile_name, profile) in &config.profiles {
+        usages.extend(profile.providers.iter().map(|(name, provider)| {
+            usage_for(
+                name,
+                provider,
+                profile_name,
+                profile.provider_sources.get(name),
+            )
+        }));
+    }
+
+    let mut profiles = vec!["default".to_string()];
+    profiles.extend(config.profiles.keys().filter(|p| *p != "default").cloned());
+    for profile in &profiles {
+        let providers = config.get_providers(profile);
+        let is_in_effect = |usage: &ProviderUsage| {
+            if profile != "default" && config.profiles[profile].providers.contains_key(&usage.name)
+            {
+                usage.defined_in == *profile
+            } else {
+                usage.defined_in == "global"
+            }
+        };
+        let mut in_effect: Vec<&mut ProviderUsage> = usages
+            .iter_mut()
+            .filter(|usage| providers.contains_key(&usage.name) && is_in_effect(usage))
+            .collect();
+
+        let default_provider = config.get_default_provider(profile).ok().flatten();
+        let secrets = config.get_secrets(profile)?;
+        let mut counts: IndexMap<&str, usize> = IndexMap::new();
+        for secret in secrets.values() {
+            for name in secret_providers(secret, default_provider.as_deref()) {
+                *counts.entry(name).or_default() += 1;
+            }
+        }
+
+        for usage in in_effect.iter_mut() {
+            if let Some(count) = counts.get(usage.name.as_str()) {
+                usage.secrets.insert(profile.clone(), *count);
+            }
+            if default_provider.as_deref() == Some(usage.name.as_str()) {
+                usage.default_for.push(profile.clone());
+            }
+        }
+        for (name, provider) in &providers {
+            for referenced in referenced_providers(provider) {
+                if let Some(usage) = in_effect.iter_mut().find(|u| u.name == referenced)
+                    && !usage.providers.contains(name)
+                {
+                    usage.providers.push(name.clone());
+                }
+            }
+        }
+    }
+
+    Ok(usages)
+}
+
+fn usage_for(
+    name: &str,
+    provider: &ProviderConfig,
+    defined_in: &str,
+    source: Option<&PathBuf>,
+) -> ProviderUsage {
+    ProviderUsage {
+        name: name.to_string(),
+        provider_type: provider.provider_type().to_string(),
+        defined_in: defined_in.to_string(),
+        secrets: IndexMap::new(),
+        providers: Vec::new(),
+        default_for: Vec::new(),
+        source: source.cloned(),
+    }
+}
+
+/// Providers a secret is resolved or synced through: its own, its fallback
+/// chain, or the default provider when it has a value but no provider
+fn secret_providers<'a>(secret: &'a SecretConfig, default: Option<&'a str>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = if let Some(provider) = secret.provider() {
+        vec![provider]
+    } else if let Some(chain) = secret.providers() {
+        chain.iter().map(String::as_str).collect()
+    } else if secret.value().is_some() {
+        default.into_iter().collect()
+    } else {
+        Vec::new()
+    };
+    if let Some(sync) = &secret.sync
+        && !names.contains(&sync.provider.as_str())
+    {
+        names.push(&sync.provider);
+    }
+    names
+}
+
+/// Providers that `provider`'s config refers to, through provider references
+/// like an age `identity = { provider = "keychain", value = "age-key" }`
+fn referenced_providers(provider: &ProviderConfig) -> Vec<String> {
+    fn walk(value: &serde_json::Value, found: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(name)) = map.get("provider") {
+                    found.push(name.clone());
+                }
+                map.values().for_each(|v| walk(v, found));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| walk(v, found)),
+            _ => {}
+        }
+    }
+
+    let mut found = Vec::new();
+    if let Ok(value) = serde_json::to_value(provider) {
+        walk(&value, &mut found);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usages(content: &str) -> Vec<ProviderUsage> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fnox.toml");
+        std::fs::write(&path, content).unwrap();
+        provider_usage(&Config::load(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn counts_secrets_per_profile_and_defaults() {
+        let usages = usages(
+            r#"
+default_provider = "age"
+
+[providers.age]
+type = "age"
+recipients = ["age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p"]
+
+[providers.keychain]
+type = "keychain"
+service = "fnox"
+
+[providers.aws]
+type = "aws-sm"
+region = "us-east-1"
+
+[secrets]
+A = { value = "encrypted" }
+B = { provider = "keychain", value = "b" }
+C = { providers = ["keychain", "age"] }
+PLAIN = { default = "visible" }
+
+[profiles.prod]
+default_provider = "aws"
+
+[profiles.prod.secrets]
+D = { value = "prod/d" }
+
+[profiles.prod.providers.keychain]
+type = "keychain"
+service = "fnox-prod"
+"#,
+        );
+        let find = |name: &str, defined_in: &str| {
+            usages
+                .iter()
+                .find(|u| u.name == name && u.defined_in == defined_in)
+                .unwrap()
+        };
+
+        let age = find("age", "global");
+        assert_eq!(age.provider_type, "age");
+        assert_eq!(
+            age.secrets,
+            IndexMap::from([("default".to_string(), 2), ("prod".to_string(), 1)])
+        );
+        assert_eq!(age.default_for, ["default"]);
+
+        // prod's own keychain shadows the global one there
+        let keychain = find("keychain", "global");
+        assert_eq!(
+            keychain.secrets,
+            IndexMap::from([("default".to_string(), 2)])
+        );
+        let prod_keychain = find("keychain", "prod");
+        assert_eq!(
+            prod_keychain.secrets,
+            IndexMap::from([("prod".to_string(), 2)])
+        );
+
+        let aws = find("aws", "global");
+        assert_eq!(aws.provider_type, "aws-sm");
+        // A inherited by prod uses prod's default provider
+        assert_eq!(aws.secrets, IndexMap::from([("prod".to_string(), 2)]));
+        assert_eq!(aws.default_for, ["prod"]);
+        assert!(!aws.is_unused());
+        assert!(aws.source.as_ref().unwrap().ends_with("fnox.toml"));
+    }
+
+    #[test]
+    fn providers_referenced_by_other_providers_are_used() {
+        let usages = usages(
+            r#"
+[providers.keychain]
+type = "keychain"
+service = "fnox"
+
+[providers.age]
+type = "age"
+recipients = ["age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p"]
+identity = { provider = "keychain", value = "age-key" }
+
+[providers.spare]
+type = "plain"
+
+[secrets]
+A = { provider = "age", value = "encrypted" }
+"#,
+        );
+        let find = |name: &str| usages.iter().find(|u| u.name == name).unwrap();
+
+        assert_eq!(find("keychain").providers, ["age"]);
+        assert!(!find("keychain").is_unused());
+        assert!(!find("age").is_unused());
+        assert!(find("spare").is_unused());
+    }
 }
@@ -1,16 +1,18 @@
 use crate::commands::Cli;
 use crate::config::Config;
-use crate::error::Result;
+use crate::error::{FnoxError, Result};
 use clap::{Args, Subcommand, ValueEnum};
 use strum::{Display, EnumString, VariantNames};
 
 mod add;
 mod list;
+mod rekey;
 mod remove;
 mod test;
 
 pub use add::AddCommand;
 pub use list::ListCommand;
+pub use rekey::RekeyCommand;
 pub use remove::RemoveCommand;
 pub use test::TestCommand;
 
@@ -61,9 +63,21 @@ pub enum ProviderType {
     /// Doppler secrets manager
     #[value(name = "doppler")]
     Doppler,
+    /// Variables in a .env file
+    #[value(name = "envfile")]
+    Envfile,
+    /// etcd v3 key-value store
+    #[value(name = "etcd")]
+    Etcd,
+    /// Run a custom command to fetch secrets
+    #[value(name = "exec")]
+    Exec,
     /// FOKS (Federated Open Key Service)
     #[value(name = "foks")]
     Foks,
+    /// Generic HTTPS/REST API
+    #[value(name = "http")]
+    Http,
     /// Bitwarden Secrets Manager
     #[value(name = "bitwarden-sm")]
     #[strum(serialize = "bitwarden-sm")]
@@ -78,6 +92,12 @@ pub enum ProviderType {
     /// OS Keychain
     #[value(name = "keychain")]
     Keychain,
+    /// Kubernetes Secrets
+    #[value(name = "kubernetes")]
+    Kubernetes,
+    /// Local encryption with a key held in the OS keychain
+    #[value(name = "local")]
+    Local,
     /// password-store (pass)
     #[value(name = "password-store")]
     #[strum(serialize = "password-store")]
@@ -92,6 +112,10 @@ pub enum ProviderType {
     #[value(name = "proton-pass")]
     #[strum(serialize = "proton-pass")]
     ProtonPass,
+    /// systemd-creds encryption with the host key or TPM2 (Linux)
+    #[value(name = "systemd-creds")]
+    #[strum(serialize = "systemd-creds")]
+    SystemdCreds,
     /// HashiCorp Vault
     #[value(name = "vault")]
     Vault,
@@ -114,6 +138,9 @@ pub enum ProviderAction {
     /// List available providers
     List(ListCommand),
 
+    /// Generate a new key for a local provider and re-encrypt its values
+    Rekey(RekeyCommand),
+
     /// Remove a provider
     Remove(RemoveCommand),
 
@@ -124,15 +151,45 @@ pub enum ProviderAction {
 impl ProviderCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
         match &self.action {
-            None => ListCommand { complete: false }.run(cli, config).await,
+            None => {
+                ListCommand {
+                    provider_type: None,
+                    unused: false,
+                    format: list::ListFormat::Table,
+                    keys: None,
+                    complete: false,
+                }
+                .run(cli, config)
+                .await
+            }
             Some(ProviderAction::List(cmd)) => cmd.run(cli, config).await,
-            Some(ProviderAction::Add(cmd)) => cmd.run(cli).await,
+            Some(ProviderAction::Add(cmd)) => cmd.run(cli, config).await,
+            Some(ProviderAction::Rekey(cmd)) => cmd.run(cli, config).await,
             Some(ProviderAction::Remove(cmd)) => cmd.run(cli).await,
             Some(ProviderAction::Test(cmd)) => cmd.run(cli, config).await,
         }
     }
 }
 
+/// The configured provider `name`, resolved (use merged config to find
+/// providers from any source)
+pub(crate) async fn resolve_provider(
+    config: &Config,
+    profile: &str,
+    name: &str,
+) -> Result<Box<dyn crate::providers::Provider>> {
+    let providers = config.get_providers(profile);
+    let provider_config = providers
+        .get(name)
+        .ok_or_else(|| FnoxError::ProviderNotConfigured {
+            provider: name.to_string(),
+            profile: profile.to_string(),
+            config_path: None,
+            suggestion: None,
+        })?;
+    crate::providers::get_provider_resolved(config, profile, name, provider_config).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::ProviderType;
@@ -0,0 +1,145 @@
+use crate::commands::Cli;
+use crate::config::{Config, SecretConfig};
+use crate::error::{FnoxError, Result};
+use crate::providers::local::{self, LocalProvider};
+use crate::providers::{Provider, ResolvedProviderConfig, resolve_provider_config};
+use clap::Args;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Generate a new key for a local provider and re-encrypt its values
+///
+/// Every secret encrypted with the provider, in any profile, is decrypted with
+/// the current key and encrypted again with a newly generated one. The old key
+/// is added to the previous keys kept in the keychain, so values in other
+/// configs sharing the service, or that couldn't be written back, can still
+/// be read.
+#[derive(Debug, Args)]
+pub struct RekeyCommand {
+    /// Name of the local provider to rekey
+    pub provider: String,
+}
+
+impl RekeyCommand {
+    pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        tracing::debug!("Rekeying provider '{}'", self.provider);
+
+        let providers = config.get_providers(&profile);
+        let provider_config =
+            providers
+                .get(&self.provider)
+                .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                    provider: self.provider.clone(),
+                    profile: profile.clone(),
+                    config_path: config.provider_sources.get(&self.provider).cloned(),
+                    suggestion: None,
+                })?;
+        let resolved =
+            resolve_provider_config(&config, &profile, &self.provider, provider_config).await?;
+        let ResolvedProviderConfig::Local { service } = resolved else {
+            return Err(FnoxError::Config(format!(
+                "Provider '{}' is a {} provider; only local providers can be rekeyed",
+                self.provider,
+                provider_config.provider_type()
+            )));
+        };
+        let provider = LocalProvider::new(service)?;
+
+        // Decrypt everything before touching the key, so a value that can't be
+        // read stops the rekey with nothing changed
+        let secrets = self.encrypted_secrets(&config)?;
+        if secrets.is_empty() {
+            println!("No secrets are encrypted with provider '{}'", self.provider);
+            return Ok(());
+        }
+        let mut plaintexts = Vec::with_capacity(secrets.len());
+        for secret in &secrets {
+            let value = secret.config.value().unwrap_or_default();
+            let plaintext = provider.get_secret(value).await.map_err(|e| {
+                FnoxError::ReencryptDecryptFailed {
+                    key: secret.key.clone(),
+                    details: e.to_string(),
+                }
+            })?;
+            plaintexts.push(plaintext);
+        }
+
+        let new_key = provider.rotate_key().await?;
+
+        let mut by_source: IndexMap<(PathBuf, String), IndexMap<String, SecretConfig>> =
+            IndexMap::new();
+        for (secret, plaintext) in secrets.into_iter().zip(&plaintexts) {
+            let mut updated = secret.config;
+            updated.set_value(Some(local::encrypt_with(&new_key, plaintext)?));
+            updated.sync = None; // Clear stale sync cache
+            by_source
+                .entry((secret.source_path, secret.section))
+                .or_default()
+                .insert(secret.key, updated);
+        }
+        for ((source_path, section), secrets) in &by_source {
+            Config::save_secrets_to_source(secrets, section, source_path)?;
+        }
+
+        let check = console::style("✓").green();
+        let styled_provider = console::style(&self.provider).cyan();
+        println!(
+            "{check} Rekeyed provider {styled_provider} (service '{}') and re-encrypted {} secrets",
+            provider.service(),
+            plaintexts.len()
+        );
+        Ok(())
+    }
+
+    /// Secrets stored with this provider in every profile. A secret shared by
+    /// several profiles is only listed once.
+    fn encrypted_secrets(&self, config: &Config) -> Result<Vec<EncryptedSecret>> {
+        let mut profiles = vec!["default".to_string()];
+        profiles.extend(config.profiles.keys().cloned());
+
+        let mut secrets = Vec::new();
+        let mut seen = HashSet::new();
+        for profile in profiles {
+            let default_provider = config.get_default_provider(&profile)?;
+            for (key, secret_config) in config.get_secrets(&profile)? {
+                let provider_name = secret_config.provider().or(default_provider.as_deref());
+                if provider_name != Some(self.provider.as_str()) || secret_config.value().is_none()
+                {
+                    continue;
+                }
+                let source_path = secret_config.source_path.clone().ok_or_else(|| {
+                    FnoxError::Config(format!(
+                        "Secret '{}' has no known source file; cannot write back re-encrypted value",
+                        key
+                    ))
+                })?;
+                // Secrets from the top-level [secrets] are saved back there
+                let section = if secret_config.source_is_profile {
+                    profile.clone()
+                } else {
+                    "default".to_string()
+                };
+                if seen.insert((source_path.clone(), section.clone(), key.clone())) {
+                    secrets.push(EncryptedSecret {
+                        key,
+                        config: secret_config,
+                        source_path,
+                        section,
+                    });
+                }
+            }
+        }
+        Ok(secrets)
+    }
+}
+
+/// A secret to re-encrypt, and where to write it back
+struct EncryptedSecret {
+    key: String,
+    config: SecretConfig,
+    source_path: PathBuf,
+    /// Profile whose section of the file the secret is saved in
+    section: String,
+}
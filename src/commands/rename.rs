@@ -0,0 +1,181 @@
+use crate::commands::Cli;
+use crate::config::Config;
+use crate::error::{FnoxError, Result};
+use crate::suggest::{find_similar, format_suggestions};
+use clap::Args;
+
+#[derive(Debug, Args)]
+#[command(visible_aliases = ["mv"])]
+pub struct RenameCommand {
+    /// Current secret key
+    pub old: String,
+
+    /// New secret key
+    pub new: String,
+
+    /// Rename in the global config file (~/.config/fnox/config.toml)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+
+    /// Show what would be renamed without making changes
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Also move the stored value in the provider (for providers where the key is the remote reference, e.g. keychain, keepass, vault)
+    #[arg(long)]
+    pub move_remote: bool,
+}
+
+impl RenameCommand {
+    pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
+        let profile = Config::get_profile(cli.profile.as_deref());
+        tracing::debug!(
+            "Renaming secret '{}' to '{}' in profile '{}'",
+            self.old,
+            self.new,
+            profile
+        );
+
+        // Determine the target config file
+        let target_path = if self.global {
+            Config::global_config_path()
+        } else {
+            let current_dir = std::env::current_dir().map_err(|e| {
+                FnoxError::Config(format!("Failed to get current directory: {}", e))
+            })?;
+            current_dir.join(&cli.config)
+        };
+
+        // Load the target config file directly (not the merged config)
+        if !target_path.exists() {
+            return Err(FnoxError::ConfigFileNotFound {
+                path: target_path.clone(),
+            });
+        }
+        let file_config = Config::load(&target_path)?;
+        let profile_secrets = file_config.get_secrets(&profile)?;
+
+        let Some(secret_config) = profile_secrets.get(&self.old) else {
+            let similar = find_similar(&self.old, profile_secrets.keys().map(String::as_str));
+            return Err(FnoxError::SecretNotFound {
+                key: self.old.clone(),
+                profile: profile.to_string(),
+                config_path: Some(target_path),
+                suggestion: format_suggestions(&similar),
+            });
+        };
+        if profile_secrets.contains_key(&self.new) {
+            return Err(FnoxError::SecretAlreadyExists {
+                key: self.new.clone(),
+                profile: profile.to_string(),
+                config_path: Some(target_path),
+            });
+        }
+
+        let styled_old = console::style(&self.old).cyan();
+        let styled_new = console::style(&self.new).cyan();
+        let styled_profile = console::style(&profile).magenta();
+        let location = if profile == "default" {
+            String::new()
+        } else {
+            format!(" in profile {styled_profile}")
+        };
+        let global_suffix = if self.global { " (global)" } else { "" };
+
+        if self.dry_run {
+            let dry_run_label = console::style("[dry-run]").yellow().bold();
+            let styled_path = console::style(target_path.display()).dim();
+            let remote_suffix = if self.move_remote {
+                " and move its stored value"
+            } else {
+                ""
+            };
+            println!(
+                "{dry_run_label} Would rename secret {styled_old} to {styled_new}{location}{global_suffix}{remote_suffix} in {styled_path}"
+            );
+            return Ok(());
+        }
+
+        // Move the remote value first: if that fails, the config is left untouched
+        let moved_value = if self.move_remote {
+            let mut secret_config = secret_config.clone();
+            let new_value = self
+                .move_remote_value(&config, &profile, &secret_config)
+                .await?;
+            secret_config.set_value(Some(new_value));
+            Some(secret_config)
+        } else {
+            None
+        };
+
+        // Rename directly in the TOML document, preserving position and comments
+        let renamed =
+            Config::rename_secret_in_source(&self.old, &self.new, &profile, &target_path)?;
+        if !renamed {
+            return Err(FnoxError::SecretNotFound {
+                key: self.old.clone(),
+                profile: profile.to_string(),
+                config_path: Some(target_path),
+                suggestion: None,
+            });
+        }
+        if let Some(ref secret_config) = moved_value {
+            file_config.save_secret_to_source(&self.new, secret_config, &profile, &target_path)?;
+        }
+
+        let check = console::style("✓").green();
+        println!("{check} Renamed secret {styled_old} to {styled_new}{location}{global_suffix}");
+
+        Ok(())
+    }
+
+    /// Move the secret's value within its provider, returning the new provider reference.
+    async fn move_remote_value(
+        &self,
+        config: &Config,
+        profile: &str,
+        secret_config: &crate::config::SecretConfig,
+    ) -> Result<String> {
+        let provider_name = match secret_config.provider() {
+            Some(name) => name.to_string(),
+            None => config.get_default_provider(profile)?.ok_or_else(|| {
+                FnoxError::Config(format!(
+                    "Secret '{}' has no provider, so there is no stored value to move",
+                    self.old
+                ))
+            })?,
+        };
+        let value = secret_config.value().ok_or_else(|| {
+            FnoxError::Config(format!(
+                "Secret '{}' has no value, so there is no stored value to move",
+                self.old
+            ))
+        })?;
+
+        let providers = config.get_providers(profile);
+        let provider_config =
+            providers
+                .get(&provider_name)
+                .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                    provider: provider_name.clone(),
+                    profile: profile.to_string(),
+                    config_path: config.provider_sources.get(&provider_name).cloned(),
+                    suggestion: None,
+                })?;
+        let provider = crate::providers::get_provider_resolved(
+            config,
+            profile,
+            &provider_name,
+            provider_config,
+        )
+        .await?;
+
+        tracing::debug!(
+            "Moving '{}' to '{}' in provider '{}'",
+            value,
+            self.new,
+            provider_name
+        );
+        provider.move_secret(value, &self.new).await
+    }
+}
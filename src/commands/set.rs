@@ -1,6 +1,9 @@
 use crate::commands::Cli;
-use crate::config::{self, Config, IfMissing};
+use crate::config::{self, Config, IfMissing, ProviderConfig, ReferenceGuard};
 use crate::error::{FnoxError, Result};
+use crate::naming::NamingPolicy;
+use crate::providers::{Provider, ProviderCapability, reference};
+use crate::sanitize::escape_controls;
 use clap::Args;
 use std::io::{self, Read};
 
@@ -50,6 +53,15 @@ pub struct SetCommand {
     /// What to do if the secret is missing (error, warn, ignore)
     #[arg(long)]
     pub if_missing: Option<IfMissing>,
+
+    /// Don't warn when a value to encrypt looks like a provider reference
+    #[arg(long)]
+    pub no_guess: bool,
+
+    /// Store VALUE as a reference to a secret already in the provider, e.g.
+    /// `op://vault/item/field`, instead of storing it with the provider
+    #[arg(long)]
+    pub raw_ref: bool,
 }
 
 impl SetCommand {
@@ -57,6 +69,16 @@ impl SetCommand {
         let profile = Config::get_profile(cli.profile.as_deref());
         tracing::debug!("Setting secret '{}' in profile '{}'", self.key, profile);
 
+        // Enforce the naming policy before prompting for or storing anything
+        if let Some(policy) = NamingPolicy::for_profile(&config, &profile)?
+            && policy.violation(&self.key).is_some()
+        {
+            return Err(policy.error(
+                std::slice::from_ref(&self.key),
+                &format!(", e.g. '{}'", policy.fix(&self.key)),
+            ));
+        }
+
         // Check if we're only setting metadata (no actual secret value)
         let has_metadata =
             self.description.is_some() || self.if_missing.is_some() || self.default.is_some();
@@ -109,8 +131,23 @@ impl SetCommand {
             secret_value
         };
 
+        if self.raw_ref {
+            self.check_raw_ref(
+                &config,
+                &profile,
+                provider_name_to_use.as_deref(),
+                secret_value.as_deref(),
+            )?;
+        }
+
+        // What a dry run would have done remotely, as (provider, stored name)
+        let mut skipped_remote_write = None;
+
         // Handle provider-specific behavior (before we get mutable borrow)
-        let (encrypted_value, remote_key_name) = if let Some(ref value) = secret_value {
+        let (encrypted_value, remote_key_name) = if self.raw_ref {
+            // The value names a secret in the provider; store it as-is
+            (None, None)
+        } else if let Some(ref value) = secret_value {
             if let Some(ref provider_name) = provider_name_to_use {
                 // Get the provider config
                 let providers = config.get_providers(&profile);
@@ -133,48 +170,29 @@ impl SetCommand {
                         )));
                     }
 
-                    let is_encryption_provider =
-                        capabilities.contains(&crate::providers::ProviderCapability::Encryption);
-                    let is_remote_storage_provider =
-                        capabilities.contains(&crate::providers::ProviderCapability::RemoteStorage);
-
-                    if is_encryption_provider {
-                        tracing::debug!(
-                            "Encrypting secret value with provider '{}'",
-                            provider_name
-                        );
-
-                        if self.dry_run {
-                            // In dry-run mode, skip actual encryption
-                            (Some("<encrypted>".to_string()), None)
-                        } else {
-                            // Encrypt with the provider — fail on error rather than
-                            // silently storing plaintext
-                            let encrypted = provider.encrypt(value).await?;
-                            (Some(encrypted), None)
-                        }
-                    } else if is_remote_storage_provider {
-                        tracing::debug!(
-                            "Storing secret '{}' in remote provider '{}'",
-                            self.key,
-                            provider_name
-                        );
-
-                        if self.dry_run {
-                            // In dry-run mode, skip actual remote storage
-                            let key_name = self.key_name.as_deref().unwrap_or(&self.key);
-                            (None, Some(key_name.to_string()))
-                        } else {
-                            // Use the already-resolved provider to store the secret
-                            let key_name = self.key_name.as_deref().unwrap_or(&self.key);
-                            let stored_key = provider.put_secret(key_name, value).await?;
-
+                    let store = ValueStore {
+                        cli,
+                        config: &config,
+                        profile: &profile,
+                        dry_run: self.dry_run,
+                        no_guess: self.no_guess,
+                    };
+                    let key_name = self.key_name.as_deref().unwrap_or(&self.key);
+                    match store
+                        .store(provider_name, provider.as_ref(), &self.key, key_name, value)
+                        .await?
+                    {
+                        StoredValue::Encrypted(encrypted) => (Some(encrypted), None),
+                        StoredValue::Remote(stored_key) => {
+                            if self.dry_run {
+                                skipped_remote_write =
+                                    Some((provider_name.clone(), stored_key.clone()));
+                            }
                             // Store just the key name (without prefix) in config
                             (None, Some(stored_key))
                         }
-                    } else {
                         // Not an encryption or remote storage provider
-                        (None, None)
+                        StoredValue::Unchanged => (None, None),
                     }
                 } else {
                     return Err(FnoxError::Config(format!(
@@ -298,13 +316,22 @@ impl SetCommand {
                 } else {
                     value.to_string()
                 };
-                println!("  value: {}", console::style(display_value).dim());
+                println!(
+                    "  value: {}",
+                    console::style(escape_controls(&display_value)).dim()
+                );
             }
             if let Some(ref desc) = secret_config.description {
-                println!("  description: {}", console::style(desc).dim());
+                println!(
+                    "  description: {}",
+                    console::style(escape_controls(desc)).dim()
+                );
             }
             if let Some(ref default) = secret_config.default {
-                println!("  default: {}", console::style(default).dim());
+                println!(
+                    "  default: {}",
+                    console::style(escape_controls(default)).dim()
+                );
             }
             if let Some(if_missing) = secret_config.if_missing {
                 println!(
@@ -312,6 +339,17 @@ impl SetCommand {
                     console::style(format!("{:?}", if_missing).to_lowercase()).dim()
                 );
             }
+            if let Some((provider, name)) = skipped_remote_write {
+                println!(
+                    "{dry_run_label} Would store the value in provider {} as {}",
+                    console::style(provider).green(),
+                    console::style(name).cyan()
+                );
+            }
+            println!("{dry_run_label} Would write:");
+            for line in Config::secret_snippet(&self.key, &secret_config, &profile).lines() {
+                println!("  {}", line);
+            }
         } else {
             config.save_secret_to_source(&self.key, &secret_config, &profile, &target_path)?;
 
@@ -330,4 +368,157 @@ impl SetCommand {
 
         Ok(())
     }
+
+    /// Check that `--raw-ref` has a provider to refer into, and that the
+    /// value is in that provider's reference syntax
+    fn check_raw_ref(
+        &self,
+        config: &Config,
+        profile: &str,
+        provider: Option<&str>,
+        value: Option<&str>,
+    ) -> Result<()> {
+        let Some(provider_name) = provider else {
+            return Err(FnoxError::Config(
+                "--raw-ref needs a provider; choose one with --provider".to_string(),
+            ));
+        };
+        let providers = config.get_providers(profile);
+        let Some(provider_config) = providers.get(provider_name) else {
+            return Err(FnoxError::Config(format!(
+                "Provider '{}' not found in configuration",
+                provider_name
+            )));
+        };
+        if let (Some(value), Some(grammar)) =
+            (value, reference::grammar(provider_config.provider_type()))
+        {
+            grammar.parse(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// What storing a value with its provider leaves for the config to record
+pub(crate) enum StoredValue {
+    /// Ciphertext from an encryption provider
+    Encrypted(String),
+    /// The name a remote storage provider holds the value under
+    Remote(String),
+    /// The provider neither encrypts nor stores values
+    Unchanged,
+}
+
+/// Stores secret values with their provider the way `fnox set` does, for
+/// the commands that write secrets on its behalf
+pub(crate) struct ValueStore<'a> {
+    pub cli: &'a Cli,
+    pub config: &'a Config,
+    pub profile: &'a str,
+    /// Skip remote writes. Encrypting writes nothing, so dry runs still do it
+    /// and show the real ciphertext.
+    pub dry_run: bool,
+    /// Don't warn when a value to encrypt looks like a provider reference
+    pub no_guess: bool,
+}
+
+impl ValueStore<'_> {
+    /// Store `value` for `key` with `provider`, named `provider_name` in the
+    /// config: check its size, then encrypt it, or write it to remote storage
+    /// under `key_name`
+    pub async fn store(
+        &self,
+        provider_name: &str,
+        provider: &dyn Provider,
+        key: &str,
+        key_name: &str,
+        value: &str,
+    ) -> Result<StoredValue> {
+        let capabilities = provider.capabilities();
+        crate::providers::check_value_size(provider, key, value)?;
+
+        if capabilities.contains(&ProviderCapability::Encryption) {
+            if !self.no_guess {
+                self.guard_reference(key, value)?;
+            }
+            tracing::debug!("Encrypting secret value with provider '{}'", provider_name);
+            // Fail on error rather than silently storing plaintext
+            Ok(StoredValue::Encrypted(provider.encrypt(value).await?))
+        } else if capabilities.contains(&ProviderCapability::RemoteStorage) {
+            tracing::debug!(
+                "Storing secret '{}' in remote provider '{}'",
+                key,
+                provider_name
+            );
+            if self.dry_run {
+                return Ok(StoredValue::Remote(key_name.to_string()));
+            }
+            Ok(StoredValue::Remote(
+                provider.put_secret(key_name, value).await?,
+            ))
+        } else {
+            Ok(StoredValue::Unchanged)
+        }
+    }
+
+    /// Catch a provider reference pasted as the value to encrypt, which
+    /// would store the reference text instead of linking to the secret.
+    /// Asks before continuing when interactive, or just warns, per
+    /// `[settings] reference_guard`.
+    fn guard_reference(&self, key: &str, value: &str) -> Result<()> {
+        let providers = self.config.get_providers(self.profile);
+        let vault_paths: Vec<&str> = providers
+            .values()
+            .filter_map(|provider| match provider {
+                ProviderConfig::HashiCorpVault { path, .. } => {
+                    path.as_ref().and_then(|p| p.as_literal())
+                }
+                _ => None,
+            })
+            .collect();
+        let Some(lookalike) = reference::lookalike(value, &vault_paths) else {
+            return Ok(());
+        };
+
+        let suggestion = match providers
+            .iter()
+            .find(|(_, provider)| provider.provider_type() == lookalike.provider_type)
+        {
+            Some((name, _)) => format!("did you mean `--provider {} --raw-ref`?", name),
+            None => format!(
+                "to link to it, add a {} provider and use `--provider <name> --raw-ref`",
+                lookalike.provider_type
+            ),
+        };
+        eprintln!(
+            "{} this looks like a {} reference — {} (pass --no-guess to silence this)",
+            console::style("warning:").yellow(),
+            lookalike.provider,
+            suggestion
+        );
+
+        let guard = self
+            .config
+            .settings
+            .as_ref()
+            .and_then(|s| s.reference_guard)
+            .unwrap_or_default();
+        let interactive = !self.cli.non_interactive
+            && atty::is(atty::Stream::Stdin)
+            && atty::is(atty::Stream::Stderr);
+        if guard == ReferenceGuard::Prompt && interactive {
+            let proceed = demand::Confirm::new("Encrypt it as the secret value anyway?")
+                .affirmative("Yes")
+                .negative("No")
+                .run()
+                .map_err(|e| FnoxError::Config(format!("Prompt cancelled: {}", e)))?;
+            if !proceed {
+                return Err(FnoxError::Config(format!(
+                    "Not setting '{}'; nothing was stored",
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
 }
@@ -22,7 +22,15 @@ impl Drop for TerminalGuard {
 }
 
 #[derive(Debug, Args)]
-pub struct TuiCommand;
+pub struct TuiCommand {
+    /// Browse secrets without editing, setting or deleting them
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Reload and re-resolve secrets when a loaded config file or import changes
+    #[arg(long)]
+    pub watch: bool,
+}
 
 impl TuiCommand {
     pub async fn run(&self, cli: &Cli, config: Config) -> Result<()> {
@@ -45,12 +53,16 @@ impl TuiCommand {
         // Create app state
         let daemon_context = crate::daemon::ResolveContext::from_cli(cli);
         let mut app = App::new(config, profile, daemon_context)?;
+        app.set_read_only(self.read_only);
 
         // Create event handler
         let mut events = EventHandler::new(Duration::from_millis(250));
 
         // Store event tx for refresh operations
         app.set_event_tx(events.message_tx());
+        if self.watch {
+            app.watch_config()?;
+        }
 
         // Spawn initial secret resolution
         app.spawn_resolve_secrets(events.message_tx());
@@ -67,9 +79,7 @@ impl TuiCommand {
                 match event {
                     Event::Key(key) => app.handle_key(key),
                     Event::Mouse(mouse) => app.handle_mouse(mouse),
-                    Event::Tick => {
-                        // Periodic tick - could be used for animations/updates
-                    }
+                    Event::Tick => app.tick(),
                     Event::Message(msg) => app.handle_message(msg),
                 }
             }
@@ -1,7 +1,10 @@
 use crate::commands::Cli;
 use crate::config::{Config, SecretConfig};
 use crate::error::{FnoxError, Result};
-use crate::secret_resolver::resolve_secrets_batch;
+use crate::secret_resolver::{
+    expand_path_secrets, expand_path_secrets_with_configs, resolve_secrets_batch,
+    resolve_secrets_partial,
+};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -9,7 +12,7 @@ use std::collections::{HashMap, HashSet};
 use std::os::fd::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 #[cfg(unix)]
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 #[cfg(unix)]
@@ -20,6 +23,12 @@ use tokio::task::JoinSet;
 
 const SOCKET_NAME: &str = "fnoxd.sock";
 const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+/// How often the daemon checks watched projects for secrets to refresh
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Projects not requested for this long stop being refreshed
+const PREFETCH_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// Delay before retrying a refresh that failed
+const PREFETCH_RETRY: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone)]
 pub struct ResolveContext {
@@ -74,6 +83,7 @@ pub enum Purpose {
     Tui,
     Mcp,
     CiRedact,
+    Diff,
 }
 
 impl Purpose {
@@ -88,6 +98,7 @@ impl Purpose {
             Self::Tui => "tui",
             Self::Mcp => "mcp",
             Self::CiRedact => "ci-redact",
+            Self::Diff => "diff",
         }
     }
 }
@@ -98,6 +109,7 @@ enum Request {
     ResolveBatch(ResolveBatchRequest),
     ResolveOne(ResolveOneRequest),
     Status,
+    CacheStatus,
     Clear,
     Shutdown,
 }
@@ -136,10 +148,18 @@ struct ResolveOneRequest {
 enum Response {
     Resolved {
         values: IndexMap<String, Option<String>>,
+        /// Values served from an expired cache entry because resolving failed
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        stale: Vec<StaleValue>,
+    },
+    CacheStatus {
+        stale: Vec<StaleValue>,
     },
     Status {
         pid: u32,
         cached_entries: usize,
+        #[serde(default)]
+        watched_projects: usize,
     },
     Ok,
     Error {
@@ -182,9 +202,62 @@ impl DaemonCallError {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CacheKey(String);
 
+struct CacheEntry {
+    value: Option<String>,
+    /// When the value was resolved from its provider
+    fetched_at: Instant,
+    /// When the value goes stale, if `daemon.cache_ttl` is set
+    expires_at: Option<Instant>,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// A cached value served past its expiry because its provider failed, under
+/// `settings.stale_if_error`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleValue {
+    pub key: String,
+    pub profile: String,
+    /// Directory of the request it was served to
+    pub project: PathBuf,
+    /// Seconds since the value was resolved from its provider
+    pub age_secs: u64,
+    /// Why resolving it failed
+    pub error: String,
+}
+
+/// A value the daemon is currently serving stale
+struct StaleServe {
+    value: StaleValue,
+    fetched_at: Instant,
+    /// `stale_if_error` when it was served; past it the value isn't served
+    max_age: Duration,
+}
+
+/// A batch request the daemon re-runs before its cached values expire
+struct WatchedProject {
+    request: ResolveBatchRequest,
+    last_used: Instant,
+    refresh_at: Instant,
+}
+
 #[derive(Default)]
 struct DaemonState {
-    cache: HashMap<CacheKey, Option<String>>,
+    cache: HashMap<CacheKey, CacheEntry>,
+    watched: HashMap<String, WatchedProject>,
+    stale: HashMap<CacheKey, StaleServe>,
+}
+
+/// What `fnox daemon status` reports about a running daemon
+#[derive(Debug, Clone, Copy)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub cached_entries: usize,
+    pub watched_projects: usize,
 }
 
 pub async fn resolve_batch(
@@ -213,18 +286,42 @@ pub async fn resolve_batch_with_context(
     secrets: &IndexMap<String, SecretConfig>,
     purpose: Purpose,
     include_env_false: bool,
+) -> Result<IndexMap<String, Option<String>>> {
+    let resolved =
+        resolve_batch_values(ctx, config, profile, secrets, purpose, include_env_false).await?;
+    // Path secrets are cached as one value and expanded here
+    expand_path_secrets(config, profile, secrets, resolved)
+}
+
+/// Like [`resolve_batch_with_context`], also returning the config of every
+/// resolved key. A secret loaded by a path secret gets the path secret's
+/// config, so commands apply its `env` and `as_file` settings.
+pub async fn resolve_batch_with_configs(
+    ctx: &ResolveContext,
+    config: &Config,
+    profile: &str,
+    secrets: &IndexMap<String, SecretConfig>,
+    purpose: Purpose,
+    include_env_false: bool,
+) -> Result<(
+    IndexMap<String, Option<String>>,
+    IndexMap<String, SecretConfig>,
+)> {
+    let resolved =
+        resolve_batch_values(ctx, config, profile, secrets, purpose, include_env_false).await?;
+    expand_path_secrets_with_configs(config, profile, secrets, resolved)
+}
+
+async fn resolve_batch_values(
+    ctx: &ResolveContext,
+    config: &Config,
+    profile: &str,
+    secrets: &IndexMap<String, SecretConfig>,
+    purpose: Purpose,
+    include_env_false: bool,
 ) -> Result<IndexMap<String, Option<String>>> {
     if !should_use_daemon(ctx, config) {
-        let secrets = if include_env_false {
-            secrets.clone()
-        } else {
-            secrets
-                .iter()
-                .filter(|(_, secret)| secret.env)
-                .map(|(key, secret)| (key.clone(), secret.clone()))
-                .collect()
-        };
-        return resolve_secrets_batch(config, profile, &secrets).await;
+        return resolve_batch_directly(config, profile, secrets, include_env_false).await;
     }
 
     let keys = secrets.keys().cloned().collect();
@@ -243,8 +340,18 @@ pub async fn resolve_batch_with_context(
         env: std::env::vars().collect(),
     });
 
-    match call_or_start(ctx, config, request).await? {
-        Response::Resolved { values } => Ok(values),
+    let response = match call_or_start(ctx, config, request).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!("fnox daemon unavailable ({e}), resolving directly");
+            return resolve_batch_directly(config, profile, secrets, include_env_false).await;
+        }
+    };
+    match response {
+        Response::Resolved { values, stale } => {
+            warn_stale(&stale);
+            Ok(values)
+        }
         Response::Error { message } => Err(FnoxError::Config(message)),
         _ => Err(FnoxError::Config(
             "Invalid daemon response for ResolveBatch".to_string(),
@@ -252,6 +359,56 @@ pub async fn resolve_batch_with_context(
     }
 }
 
+async fn resolve_batch_directly(
+    config: &Config,
+    profile: &str,
+    secrets: &IndexMap<String, SecretConfig>,
+    include_env_false: bool,
+) -> Result<IndexMap<String, Option<String>>> {
+    let secrets = if include_env_false {
+        secrets.clone()
+    } else {
+        secrets
+            .iter()
+            .filter(|(_, secret)| secret.env)
+            .map(|(key, secret)| (key.clone(), secret.clone()))
+            .collect()
+    };
+    resolve_secrets_batch(config, profile, &secrets).await
+}
+
+/// Resolve a batch, falling back to one secret at a time if the batch fails so
+/// each secret gets its own result.
+pub async fn resolve_partial_with_context(
+    ctx: &ResolveContext,
+    config: &Config,
+    profile: &str,
+    secrets: &IndexMap<String, SecretConfig>,
+    purpose: Purpose,
+) -> IndexMap<String, Result<Option<String>>> {
+    if !should_use_daemon(ctx, config) {
+        return resolve_secrets_partial(config, profile, secrets).await;
+    }
+
+    match resolve_batch_with_context(ctx, config, profile, secrets, purpose, true).await {
+        Ok(resolved) => resolved
+            .into_iter()
+            .map(|(key, value)| (key, Ok(value)))
+            .collect(),
+        Err(e) => {
+            tracing::debug!("Daemon batch resolution failed ({e}), resolving individually");
+            let mut results = IndexMap::new();
+            for (key, secret_config) in secrets {
+                let result =
+                    resolve_one_with_context(ctx, config, profile, key, secret_config, purpose)
+                        .await;
+                results.insert(key.clone(), result);
+            }
+            results
+        }
+    }
+}
+
 pub async fn resolve_one(
     cli: &Cli,
     config: &Config,
@@ -297,8 +454,19 @@ pub async fn resolve_one_with_context(
         env: std::env::vars().collect(),
     });
 
-    match call_or_start(ctx, config, request).await? {
-        Response::Resolved { mut values } => Ok(values.swap_remove(key).flatten()),
+    let response = match call_or_start(ctx, config, request).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!("fnox daemon unavailable ({e}), resolving directly");
+            return crate::secret_resolver::resolve_secret(config, profile, key, secret_config)
+                .await;
+        }
+    };
+    match response {
+        Response::Resolved { mut values, stale } => {
+            warn_stale(&stale);
+            Ok(values.swap_remove(key).flatten())
+        }
         Response::Error { message } => Err(FnoxError::Config(message)),
         _ => Err(FnoxError::Config(
             "Invalid daemon response for ResolveOne".to_string(),
@@ -306,16 +474,21 @@ pub async fn resolve_one_with_context(
     }
 }
 
-pub async fn status(cli: &Cli) -> Result<Option<(u32, usize)>> {
+pub async fn status(cli: &Cli) -> Result<Option<DaemonStatus>> {
     status_for_context(&ResolveContext::from_cli(cli)).await
 }
 
-async fn status_for_context(ctx: &ResolveContext) -> Result<Option<(u32, usize)>> {
+async fn status_for_context(ctx: &ResolveContext) -> Result<Option<DaemonStatus>> {
     match call(socket_path_for_context(ctx)?, Request::Status).await {
         Ok(Response::Status {
             pid,
             cached_entries,
-        }) => Ok(Some((pid, cached_entries))),
+            watched_projects,
+        }) => Ok(Some(DaemonStatus {
+            pid,
+            cached_entries,
+            watched_projects,
+        })),
         Ok(Response::Error { message }) => Err(FnoxError::Config(message)),
         Ok(_) => Err(FnoxError::Config(
             "Invalid daemon response for Status".to_string(),
@@ -325,6 +498,41 @@ async fn status_for_context(ctx: &ResolveContext) -> Result<Option<(u32, usize)>
     }
 }
 
+/// Values the running daemon is serving stale, or `None` if it isn't running
+pub async fn stale_values(cli: &Cli) -> Result<Option<Vec<StaleValue>>> {
+    match call(socket_path(cli)?, Request::CacheStatus).await {
+        Ok(Response::CacheStatus { stale }) => Ok(Some(stale)),
+        Ok(Response::Error { message }) => Err(FnoxError::Config(message)),
+        Ok(_) => Err(FnoxError::Config(
+            "Invalid daemon response for CacheStatus".to_string(),
+        )),
+        Err(e) if e.is_socket_missing() => Ok(None),
+        Err(e) => Err(e.into_fnox_error()),
+    }
+}
+
+/// Warn loudly about each value served stale, since its provider is failing
+fn warn_stale(stale: &[StaleValue]) {
+    for value in stale {
+        tracing::warn!(
+            "Using a stale value for '{}' resolved {} ago, because its provider failed: {}",
+            value.key,
+            format_age(value.age_secs),
+            value.error
+        );
+    }
+}
+
+/// `secs` in its largest whole unit, such as "3h"
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        s if s >= 60 * 60 * 24 => format!("{}d", s / (60 * 60 * 24)),
+        s if s >= 60 * 60 => format!("{}h", s / (60 * 60)),
+        s if s >= 60 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
 pub async fn clear(cli: &Cli) -> Result<()> {
     match call(socket_path(cli)?, Request::Clear).await {
         Ok(Response::Ok) => Ok(()),
@@ -483,6 +691,11 @@ pub async fn serve(cli: &Cli, idle_timeout: Duration) -> Result<()> {
         let request_lock = std::sync::Arc::new(Mutex::new(()));
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
         let mut tasks = JoinSet::new();
+        let mut refresh_check = tokio::time::interval(REFRESH_CHECK_INTERVAL);
+        refresh_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut refreshing: Option<tokio::task::JoinHandle<()>> = None;
+        // Background refreshes don't count as activity; only clients do
+        let mut idle_deadline = tokio::time::Instant::now() + idle_timeout;
         loop {
             let accepted = tokio::select! {
                 _ = shutdown_rx.recv() => break,
@@ -492,15 +705,25 @@ pub async fn serve(cli: &Cli, idle_timeout: Duration) -> Result<()> {
                     }
                     continue;
                 }
-                accepted = tokio::time::timeout(idle_timeout, listener.accept()) => accepted,
+                _ = refresh_check.tick() => {
+                    if refreshing.as_ref().is_none_or(|task| task.is_finished()) {
+                        refreshing = Some(tokio::spawn(refresh_watched(
+                            state.clone(),
+                            request_lock.clone(),
+                        )));
+                    }
+                    continue;
+                }
+                _ = tokio::time::sleep_until(idle_deadline) => break,
+                accepted = listener.accept() => accepted,
             };
+            idle_deadline = tokio::time::Instant::now() + idle_timeout;
             let (stream, _) = match accepted {
-                Ok(Ok(pair)) => pair,
-                Ok(Err(e)) => {
+                Ok(pair) => pair,
+                Err(e) => {
                     tracing::warn!("daemon accept failed: {e}");
                     continue;
                 }
-                Err(_) => break,
             };
             if let Err(e) = verify_peer(&stream) {
                 tracing::warn!("rejected daemon client: {e}");
@@ -517,6 +740,10 @@ pub async fn serve(cli: &Cli, idle_timeout: Duration) -> Result<()> {
             });
         }
 
+        if let Some(task) = refreshing {
+            task.abort();
+        }
+
         if !tasks.is_empty() {
             let drained = tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, async {
                 while let Some(result) = tasks.join_next().await {
@@ -553,6 +780,12 @@ fn should_use_daemon(ctx: &ResolveContext, config: &Config) -> bool {
     if ctx.no_daemon {
         return false;
     }
+    // Overrides come from this process's environment, which the daemon's
+    // cached values know nothing about
+    if !crate::secret_resolver::override_vars().is_empty() {
+        tracing::debug!("FNOX_OVERRIDE_* is set, resolving without the daemon");
+        return false;
+    }
     match std::env::var("FNOX_DAEMON").ok().as_deref() {
         Some("0" | "false" | "off" | "no") => return false,
         Some("1" | "true" | "on" | "yes") => return true,
@@ -699,11 +932,32 @@ async fn process_request(
             Ok(Response::Status {
                 pid: std::process::id(),
                 cached_entries: state.cache.len(),
+                watched_projects: state.watched.len(),
             })
         }
+        Request::CacheStatus => {
+            let mut state = state.lock().await;
+            let now = Instant::now();
+            state
+                .stale
+                .retain(|_, serve| now.duration_since(serve.fetched_at) <= serve.max_age);
+            let mut stale: Vec<StaleValue> = state
+                .stale
+                .values()
+                .map(|serve| StaleValue {
+                    age_secs: now.duration_since(serve.fetched_at).as_secs(),
+                    ..serve.value.clone()
+                })
+                .collect();
+            stale.sort_by(|a, b| (&a.project, &a.key).cmp(&(&b.project, &b.key)));
+            Ok(Response::CacheStatus { stale })
+        }
         Request::Clear => {
             let _guard = request_lock.lock().await;
-            state.lock().await.cache.clear();
+            let mut state = state.lock().await;
+            state.cache.clear();
+            state.watched.clear();
+            state.stale.clear();
             Ok(Response::Ok)
         }
         Request::Shutdown => {
@@ -711,25 +965,8 @@ async fn process_request(
             Ok(Response::Ok)
         }
         Request::ResolveBatch(req) => {
-            let _guard = request_lock.lock().await;
-            let _env = EnvOverlay::apply(&req.env)?;
-            let _cwd = CwdGuard::change_to(&req.cwd)?;
-            apply_request_settings(
-                req.age_key_file.clone(),
-                Some(req.profile.clone()),
-                req.if_missing.clone(),
-                req.no_defaults,
-                req.non_interactive,
-            );
-            let config = Config::load_smart(&req.config)?;
-            let all_secrets = config.get_secrets(&req.profile)?;
-            let requested = req.keys.iter().cloned().collect::<HashSet<_>>();
-            let secrets: IndexMap<String, SecretConfig> = all_secrets
-                .into_iter()
-                .filter(|(key, sc)| requested.contains(key) && (req.include_env_false || sc.env))
-                .collect();
-            let values = resolve_with_cache(&config, &req.profile, secrets, &req, state).await?;
-            Ok(Response::Resolved { values })
+            let (values, stale) = resolve_batch_request(req, state, request_lock, false).await?;
+            Ok(Response::Resolved { values, stale })
         }
         Request::ResolveOne(req) => {
             let _guard = request_lock.lock().await;
@@ -743,9 +980,11 @@ async fn process_request(
                 req.non_interactive,
             );
             let config = Config::load_smart(&req.config)?;
+            let ttl = cache_ttl(&config)?;
             let Some(secret_config) = config.get_secret(&req.profile, &req.key).cloned() else {
                 return Ok(Response::Resolved {
                     values: [(req.key, None)].into_iter().collect(),
+                    stale: Vec::new(),
                 });
             };
             let batch_req = ResolveBatchRequest {
@@ -761,15 +1000,158 @@ async fn process_request(
                 include_env_false: true,
                 env: req.env,
             };
-            let values = resolve_with_cache(
+            let (values, stale) = resolve_with_cache(
                 &config,
                 &batch_req.profile,
                 [(req.key, secret_config)].into_iter().collect(),
                 &batch_req,
                 state,
+                ttl,
+                false,
             )
             .await?;
-            Ok(Response::Resolved { values })
+            Ok(Response::Resolved { values, stale })
+        }
+    }
+}
+
+/// Resolve a batch request, caching the values. With `cache_ttl` and
+/// `prefetch` set, the request is also remembered so the daemon can refresh it
+/// before the values expire; `refresh` marks one of those background runs.
+async fn resolve_batch_request(
+    req: ResolveBatchRequest,
+    state: std::sync::Arc<Mutex<DaemonState>>,
+    request_lock: std::sync::Arc<Mutex<()>>,
+    refresh: bool,
+) -> Result<(IndexMap<String, Option<String>>, Vec<StaleValue>)> {
+    let _guard = request_lock.lock().await;
+    let _env = EnvOverlay::apply(&req.env)?;
+    let _cwd = CwdGuard::change_to(&req.cwd)?;
+    // Nobody is around to answer a prompt during a background refresh
+    apply_request_settings(
+        req.age_key_file.clone(),
+        Some(req.profile.clone()),
+        req.if_missing.clone(),
+        req.no_defaults,
+        req.non_interactive || refresh,
+    );
+    let config = Config::load_smart(&req.config)?;
+    let ttl = cache_ttl(&config)?;
+    let all_secrets = config.get_secrets(&req.profile)?;
+    let requested = req.keys.iter().cloned().collect::<HashSet<_>>();
+    let secrets: IndexMap<String, SecretConfig> = all_secrets
+        .into_iter()
+        .filter(|(key, sc)| requested.contains(key) && (req.include_env_false || sc.env))
+        .collect();
+    let resolved = resolve_with_cache(
+        &config,
+        &req.profile,
+        secrets,
+        &req,
+        state.clone(),
+        ttl,
+        refresh,
+    )
+    .await?;
+
+    let prefetch = config.daemon.as_ref().is_some_and(|d| d.prefetch());
+    if let Some(ttl) = ttl
+        && prefetch
+        && req.purpose != Purpose::Check.as_str()
+    {
+        watch(&mut *state.lock().await, req, ttl, refresh);
+    }
+    Ok(resolved)
+}
+
+fn cache_ttl(config: &Config) -> Result<Option<Duration>> {
+    config
+        .daemon
+        .as_ref()
+        .and_then(|d| d.cache_ttl.as_deref())
+        .map(parse_duration)
+        .transpose()
+}
+
+/// How old a cached value may be and still stand in for one whose provider
+/// fails, from `settings.stale_if_error`. Unset serves no stale values.
+fn stale_if_error(config: &Config) -> Result<Option<Duration>> {
+    config
+        .settings
+        .as_ref()
+        .and_then(|s| s.stale_if_error.as_deref())
+        .map(parse_duration)
+        .transpose()
+}
+
+/// Schedule the next refresh of `req`. Client requests also mark the project
+/// as in use; background refreshes don't, so unused projects age out.
+fn watch(state: &mut DaemonState, req: ResolveBatchRequest, ttl: Duration, refresh: bool) {
+    let now = Instant::now();
+    let refresh_at = now + ttl - refresh_lead(ttl);
+    match state.watched.entry(watch_key(&req)) {
+        std::collections::hash_map::Entry::Occupied(mut entry) => {
+            let project = entry.get_mut();
+            project.refresh_at = refresh_at;
+            if !refresh {
+                project.last_used = now;
+                project.request = req;
+            }
+        }
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            if !refresh {
+                entry.insert(WatchedProject {
+                    request: req,
+                    last_used: now,
+                    refresh_at,
+                });
+            }
+        }
+    }
+}
+
+/// Identify a project by what it asks for, not by the client's environment
+fn watch_key(req: &ResolveBatchRequest) -> String {
+    let mut req = req.clone();
+    req.env.clear();
+    serde_json::to_string(&req).unwrap_or_default()
+}
+
+/// How long before expiry to refresh a value cached for `ttl`
+fn refresh_lead(ttl: Duration) -> Duration {
+    (ttl / 10).max(Duration::from_secs(1)).min(ttl)
+}
+
+/// Re-resolve watched projects whose values are about to expire, and forget
+/// projects that haven't been used within [`PREFETCH_WINDOW`]
+async fn refresh_watched(
+    state: std::sync::Arc<Mutex<DaemonState>>,
+    request_lock: std::sync::Arc<Mutex<()>>,
+) {
+    let due: Vec<ResolveBatchRequest> = {
+        let mut state = state.lock().await;
+        let now = Instant::now();
+        state
+            .watched
+            .retain(|_, project| now.duration_since(project.last_used) < PREFETCH_WINDOW);
+        state
+            .watched
+            .values_mut()
+            .filter(|project| project.refresh_at <= now)
+            .map(|project| {
+                // Held off until the refresh reschedules it, or retried later
+                // if it fails
+                project.refresh_at = now + PREFETCH_RETRY;
+                project.request.clone()
+            })
+            .collect()
+    };
+
+    for req in due {
+        let cwd = req.cwd.clone();
+        if let Err(e) = resolve_batch_request(req, state.clone(), request_lock.clone(), true).await
+        {
+            tracing::warn!("daemon prefetch for {} failed: {e}", cwd.display());
         }
     }
 }
@@ -796,40 +1178,95 @@ async fn resolve_with_cache(
     secrets: IndexMap<String, SecretConfig>,
     req: &ResolveBatchRequest,
     state: std::sync::Arc<Mutex<DaemonState>>,
-) -> Result<IndexMap<String, Option<String>>> {
+    ttl: Option<Duration>,
+    refresh: bool,
+) -> Result<(IndexMap<String, Option<String>>, Vec<StaleValue>)> {
     let fingerprint = config_fingerprint(config, &req.env)?;
     let providers = config.get_providers(profile);
+    let default_provider = config.get_default_provider(profile).ok().flatten();
     let mut results = IndexMap::new();
     let mut misses = IndexMap::new();
     let mut miss_keys = HashMap::new();
 
     {
         let state = state.lock().await;
+        let now = Instant::now();
         for (key, secret) in &secrets {
+            let provider = secret
+                .provider()
+                .or(default_provider.as_deref())
+                .and_then(|p| providers.get(p));
             let cacheable = req.purpose != Purpose::Check.as_str()
                 && secret.daemon_cache.unwrap_or(true)
-                && secret
-                    .provider()
-                    .and_then(|p| providers.get(p))
-                    .is_none_or(|p| p.daemon_cache_enabled());
+                && provider.is_none_or(|p| p.daemon_cache_enabled())
+                // A cached TOTP code would be served after it expired
+                && !provider
+                    .zip(secret.value())
+                    .is_some_and(|(p, value)| p.is_time_based_reference(value));
             if cacheable {
                 let cache_key = cache_key(&fingerprint, profile, key, secret, req);
-                if let Some(value) = state.cache.get(&cache_key) {
-                    results.insert(key.clone(), value.clone());
+                // A refresh re-resolves everything it caches
+                if !refresh
+                    && let Some(entry) = state.cache.get(&cache_key)
+                    && entry.is_fresh(now)
+                {
+                    results.insert(key.clone(), entry.value.clone());
                     continue;
                 }
                 miss_keys.insert(key.clone(), cache_key);
+            } else if refresh {
+                continue;
             }
             misses.insert(key.clone(), secret.clone());
         }
     }
 
+    let mut stale = Vec::new();
     if !misses.is_empty() {
-        let resolved = resolve_secrets_batch(config, profile, &misses).await?;
+        let resolved = match stale_if_error(config)? {
+            Some(max_age) if !refresh => {
+                // Per key, so only secrets whose own resolution failed fall
+                // back on their cached values
+                let mut resolved = IndexMap::new();
+                for (key, result) in resolve_secrets_partial(config, profile, &misses).await {
+                    let e = match result {
+                        Ok(value) => {
+                            resolved.insert(key, value);
+                            continue;
+                        }
+                        Err(e) => e,
+                    };
+                    let Some(cache_key) = miss_keys.remove(&key) else {
+                        return Err(e);
+                    };
+                    let Some((value, serve)) =
+                        serve_stale(&state, &key, &cache_key, max_age, &e, profile, req).await
+                    else {
+                        return Err(e);
+                    };
+                    stale.push(serve.value.clone());
+                    results.insert(key, value);
+                    state.lock().await.stale.insert(cache_key, serve);
+                }
+                resolved
+            }
+            // A background refresh keeps the old entries and retries later
+            _ => resolve_secrets_batch(config, profile, &misses).await?,
+        };
+        let now = Instant::now();
+        let expires_at = ttl.map(|ttl| now + ttl);
         let mut state = state.lock().await;
         for (key, value) in resolved {
             if let Some(cache_key) = miss_keys.remove(&key) {
-                state.cache.insert(cache_key, value.clone());
+                state.stale.remove(&cache_key);
+                state.cache.insert(
+                    cache_key,
+                    CacheEntry {
+                        value: value.clone(),
+                        fetched_at: now,
+                        expires_at,
+                    },
+                );
             }
             results.insert(key, value);
         }
@@ -841,7 +1278,37 @@ async fn resolve_with_cache(
             ordered.insert(key.clone(), value);
         }
     }
-    Ok(ordered)
+    Ok((ordered, stale))
+}
+
+/// The cached value, expired or not, of the secret `key` that failed to
+/// resolve with `error`, if it's no older than `max_age`
+async fn serve_stale(
+    state: &Mutex<DaemonState>,
+    key: &str,
+    cache_key: &CacheKey,
+    max_age: Duration,
+    error: &FnoxError,
+    profile: &str,
+    req: &ResolveBatchRequest,
+) -> Option<(Option<String>, StaleServe)> {
+    let state = state.lock().await;
+    let entry = state.cache.get(cache_key)?;
+    let age = Instant::now().duration_since(entry.fetched_at);
+    (age <= max_age).then(|| {
+        let serve = StaleServe {
+            value: StaleValue {
+                key: key.to_string(),
+                profile: profile.to_string(),
+                project: req.cwd.clone(),
+                age_secs: age.as_secs(),
+                error: error.to_string(),
+            },
+            fetched_at: entry.fetched_at,
+            max_age,
+        };
+        (entry.value.clone(), serve)
+    })
 }
 
 fn cache_key(
@@ -910,6 +1377,8 @@ fn provider_env_key(key: &str) -> bool {
             | "AWS_REGION"
             | "AWS_DEFAULT_REGION"
             | "OP_SERVICE_ACCOUNT_TOKEN"
+            | "OP_CONNECT_HOST"
+            | "OP_CONNECT_TOKEN"
             | "BW_SESSION"
             | "BWS_ACCESS_TOKEN"
             | "VAULT_TOKEN"
@@ -1224,7 +1693,7 @@ pub fn parse_duration(value: &str) -> Result<Duration> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_duration;
+    use super::{format_age, parse_duration};
 
     #[test]
     fn parse_duration_accepts_combined_values() {
@@ -1237,4 +1706,12 @@ mod tests {
         assert!(parse_duration("0s").is_err());
         assert!(parse_duration("18446744073709551615d").is_err());
     }
+
+    #[test]
+    fn format_age_uses_the_largest_unit() {
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(90), "1m");
+        assert_eq!(format_age(2 * 60 * 60 + 5), "2h");
+        assert_eq!(format_age(3 * 24 * 60 * 60), "3d");
+    }
 }
@@ -0,0 +1,57 @@
+//! Questions about the git working tree, shared by the commands that care
+//! whether a file would be committed (`scan --staged`, `export --output`).
+//!
+//! Everything shells out to `git`, so `.gitignore`, `.git/info/exclude` and
+//! the global excludes file are honored exactly as git honors them.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in `dir` and return its stdout, or a message saying why it failed
+pub fn run(dir: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("failed to run git: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Whether `path` is inside a git work tree and would be committed by
+/// `git add`: it's tracked, or it's untracked and no ignore rule covers it.
+/// Outside a repository, or without git, nothing is committable.
+pub fn is_committable(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    // `git check-ignore` exits 0 for an ignored path, 1 for a path that isn't
+    // (tracked paths never count as ignored) and 128 outside a repository
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["check-ignore", "-q", "--"])
+        .arg(name)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    match status {
+        Ok(status) => status.code() == Some(1),
+        Err(err) => {
+            tracing::debug!("Can't ask git whether {} is ignored: {err}", path.display());
+            false
+        }
+    }
+}
@@ -6,17 +6,23 @@
 // consumers and for our own modules.
 
 pub use fnox_core::{
-    auth_prompt, config, env, error, http, lease, lease_backends, library, providers,
-    secret_resolver, settings, source_registry, spanned, suggest, temp_file_secrets,
+    auth_prompt, config, dotenv, env, error, http, lease, lease_backends, library, naming,
+    providers, secret_groups, secret_resolver, settings, source_registry, spanned, suggest,
+    temp_file_secrets, worktree,
 };
 
 // CLI-only modules — depend on fnox-core for everything else.
 pub mod commands;
 pub mod daemon;
+pub mod git;
 pub mod hook_env;
 pub mod mcp_server;
+pub mod redact;
+pub mod sanitize;
 pub mod shell;
+pub mod table;
 pub mod tui;
+pub mod watch;
 
 // Re-export commonly used items
 pub use error::{FnoxError, Result};
@@ -0,0 +1,215 @@
+//! Streaming redaction of secret values from command output
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use std::io::{self, Read, Write};
+
+use crate::error::{FnoxError, Result};
+
+/// Text written in place of a secret value
+pub const MASK: &[u8] = b"***";
+
+/// Default for the `redact_min_length` setting
+pub const DEFAULT_MIN_LENGTH: usize = 3;
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Replaces secret values in a byte stream with `***`.
+///
+/// Output is written as soon as it's known not to be part of a secret, so at
+/// most one secret's length minus one byte is held back between reads. A value
+/// split across two reads is still masked.
+pub struct Redactor {
+    matcher: AhoCorasick,
+    /// Length of the longest secret in bytes
+    max_len: usize,
+}
+
+impl Redactor {
+    /// Build a redactor for `values`, ignoring any shorter than `min_length`
+    /// characters. Returns `None` when there is nothing to redact.
+    pub fn new<'a>(
+        values: impl IntoIterator<Item = &'a str>,
+        min_length: usize,
+    ) -> Result<Option<Self>> {
+        let mut values: Vec<&str> = values
+            .into_iter()
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && v.chars().count() >= min_length)
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+
+        let Some(max_len) = values.iter().map(|v| v.len()).max() else {
+            return Ok(None);
+        };
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&values)
+            .map_err(|e| FnoxError::Config(format!("Failed to build redaction filter: {e}")))?;
+        Ok(Some(Self { matcher, max_len }))
+    }
+
+    /// Mask every secret in a complete buffer
+    pub fn redact(&self, text: &[u8]) -> Vec<u8> {
+        self.matcher
+            .replace_all_bytes(text, &vec![MASK; self.matcher.patterns_len()])
+    }
+
+    /// Copy `reader` to `writer`, masking secrets and flushing after every read
+    /// so interactive output isn't delayed.
+    pub fn stream(&self, mut reader: impl Read, mut writer: impl Write) -> io::Result<()> {
+        let mut pending: Vec<u8> = Vec::new();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            pending.extend_from_slice(&chunk[..n]);
+
+            let (output, consumed) = self.redact_settled(&pending);
+            writer.write_all(&output)?;
+            writer.flush()?;
+            pending.drain(..consumed);
+        }
+
+        writer.write_all(&self.redact(&pending))?;
+        writer.flush()
+    }
+
+    /// Redact the part of `buf` that more input can't change. Returns the
+    /// redacted output and how many bytes of `buf` it covers.
+    ///
+    /// A match starting before `settled` is final: every secret that could
+    /// start there fits within `buf`, so leftmost-longest matching can't pick a
+    /// longer one later. Anything from `settled` on may be the start of a secret
+    /// that continues in the next read.
+    fn redact_settled(&self, buf: &[u8]) -> (Vec<u8>, usize) {
+        let settled = buf.len().saturating_sub(self.max_len - 1);
+        let mut output = Vec::with_capacity(settled);
+        let mut pos = 0;
+        for m in self.matcher.find_iter(buf) {
+            if m.start() >= settled {
+                break;
+            }
+            output.extend_from_slice(&buf[pos..m.start()]);
+            output.extend_from_slice(MASK);
+            pos = m.end();
+        }
+        if pos < settled {
+            output.extend_from_slice(&buf[pos..settled]);
+            pos = settled;
+        }
+        (output, pos)
+    }
+}
+
+/// Parse the `redact_min_length` setting
+pub fn parse_min_length(value: &str) -> Result<usize> {
+    value.trim().parse().map_err(|_| {
+        FnoxError::Config(format!(
+            "Invalid FNOX_REDACT_MIN_LENGTH '{value}': expected a non-negative integer"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader that returns at most `size` bytes per read, to split secrets
+    /// across reads
+    struct Chunked<'a> {
+        data: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.size.min(self.data.len()).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn stream(redactor: &Redactor, input: &str, size: usize) -> String {
+        let mut output = Vec::new();
+        redactor
+            .stream(
+                Chunked {
+                    data: input.as_bytes(),
+                    size,
+                },
+                &mut output,
+            )
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn masks_exact_matches() {
+        let redactor = Redactor::new(["hunter2", "s3cr3t-token"], 3)
+            .unwrap()
+            .unwrap();
+        let input = "password=hunter2\ntoken: s3cr3t-token, again hunter2\nhunter!\n";
+        assert_eq!(
+            stream(&redactor, input, CHUNK_SIZE),
+            "password=***\ntoken: ***, again ***\nhunter!\n"
+        );
+    }
+
+    #[test]
+    fn masks_secrets_split_across_reads() {
+        let redactor = Redactor::new(["hunter2", "s3cr3t-token"], 3)
+            .unwrap()
+            .unwrap();
+        let input = "a hunter2 b s3cr3t-token c hunter2";
+        for size in 1..=input.len() {
+            assert_eq!(
+                stream(&redactor, input, size),
+                "a *** b *** c ***",
+                "read size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_the_longest_secret_across_reads() {
+        let redactor = Redactor::new(["abc", "abcdef"], 3).unwrap().unwrap();
+        for size in 1..=8 {
+            assert_eq!(stream(&redactor, "xabcdefx abcx", size), "x***x ***x");
+        }
+    }
+
+    #[test]
+    fn skips_short_and_blank_values() {
+        let redactor = Redactor::new(["1", "  ", "ab", "long-enough\n"], 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stream(&redactor, "1 ab long-enough\n", CHUNK_SIZE),
+            "1 ab ***\n"
+        );
+        assert!(Redactor::new(["1", ""], 3).unwrap().is_none());
+        assert!(Redactor::new(["1"], 1).unwrap().is_some());
+    }
+
+    #[test]
+    fn passes_through_non_utf8_output() {
+        let redactor = Redactor::new(["secret"], 3).unwrap().unwrap();
+        let mut output = Vec::new();
+        redactor
+            .stream(&b"\xff secret \xfe"[..], &mut output)
+            .unwrap();
+        assert_eq!(output, b"\xff *** \xfe");
+    }
+
+    #[test]
+    fn parses_min_length() {
+        assert_eq!(parse_min_length("5").unwrap(), 5);
+        assert!(parse_min_length("five").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Making config-controlled text safe to show in a terminal.
+//!
+//! Keys, descriptions and values come from config files anyone can edit, and
+//! a control character in one can misalign a table, move the cursor or, as
+//! part of an escape sequence, retitle or reprogram the terminal. Tables and
+//! the TUI show such characters escaped instead.
+
+use std::borrow::Cow;
+
+/// `text` with control characters and bidirectional overrides written as
+/// escapes (`\n`, `\t`, `\u{1b}`), or `text` itself when it has none
+pub fn escape_controls(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(needs_escape) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        if needs_escape(c) {
+            escaped.extend(c.escape_default());
+        } else {
+            escaped.push(c);
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// C0 and C1 controls, DEL, and the characters that reorder how the text
+/// around them is displayed
+fn needs_escape(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_printable_text_alone() {
+        for text in ["API_KEY", "数据库_URL", "Cafe\u{301}", "pa$$ \"word\" \\"] {
+            assert!(matches!(escape_controls(text), Cow::Borrowed(_)), "{text}");
+        }
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(
+            escape_controls("\x1b]0;pwned\x07title"),
+            r"\u{1b}]0;pwned\u{7}title"
+        );
+        assert_eq!(escape_controls("a\tb\r\nc"), r"a\tb\r\nc");
+        assert_eq!(escape_controls("\u{9b}31m\u{7f}"), r"\u{9b}31m\u{7f}");
+        assert_eq!(
+            escape_controls("invoice\u{202e}fdp.exe"),
+            r"invoice\u{202e}fdp.exe"
+        );
+    }
+}
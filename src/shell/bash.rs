@@ -1,5 +1,6 @@
 use super::{ActivateOptions, Shell};
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Bash;
 
@@ -62,6 +63,14 @@ fi
         out
     }
 
+    fn rc_line(&self) -> String {
+        r#"eval "$(fnox activate bash)""#.to_string()
+    }
+
+    fn rc_file(&self) -> Option<PathBuf> {
+        Some(crate::env::HOME_DIR.join(".bashrc"))
+    }
+
     fn deactivate(&self) -> String {
         let mut out = String::new();
 
@@ -1,5 +1,6 @@
 use super::{ActivateOptions, Shell};
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Fish;
 
@@ -15,11 +16,15 @@ impl Shell for Fish {
         out.push_str(&format!(
             r#"
 function fnox
-    set command $argv[1]
+    if test (count $argv) -eq 0
+        command {exe}
+        return
+    end
+    set -l command $argv[1]
 
     switch "$command"
         case deactivate shell
-            eval (command {exe} "$command" $argv[2..-1])
+            eval (command {exe} "$command" $argv[2..-1] | string collect)
         case '*'
             command {exe} "$command" $argv[2..-1]
     end
@@ -28,7 +33,8 @@ end
         ));
 
         if !opts.no_hook_env {
-            // Define the hook function that runs on every prompt
+            // Run on every prompt through the fish_prompt event rather than by
+            // wrapping fish_prompt, which prompt frameworks redefine
             out.push_str(&format!(
                 r#"
 function __fnox_env_eval --on-event fish_prompt
@@ -57,15 +63,28 @@ end
         out
     }
 
+    fn rc_line(&self) -> String {
+        "fnox activate fish | source".to_string()
+    }
+
+    /// A file of its own in conf.d, which fish sources before config.fish
+    fn rc_file(&self) -> Option<PathBuf> {
+        let config = crate::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| crate::env::HOME_DIR.join(".config"));
+        Some(config.join("fish").join("conf.d").join("fnox.fish"))
+    }
+
     fn deactivate(&self) -> String {
         let mut out = String::new();
 
-        // Remove hook functions
+        // Remove hook functions, which removes their event handlers too
         out.push_str("functions -e __fnox_env_eval __fnox_cd_hook 2>/dev/null\n");
 
         // Unset fnox-related variables (one at a time for compatibility)
-        out.push_str("set -e FNOX_SHELL 2>/dev/null\n");
-        out.push_str("set -e __FNOX_SESSION 2>/dev/null\n");
+        out.push_str("set -e -g FNOX_SHELL 2>/dev/null\n");
+        out.push_str("set -e -g __FNOX_SESSION 2>/dev/null\n");
 
         // Erase the fnox function last (we're currently inside it)
         out.push_str("functions -e fnox 2>/dev/null\n");
@@ -79,11 +98,14 @@ end
             .replace('\\', "\\\\")
             .replace('"', "\\\"")
             .replace('$', "\\$");
+        // Always global: a universal variable would be shared with every fish
+        // session and outlive this one
         format!("set -gx {} \"{}\"\n", key, value)
     }
 
     fn unset_env(&self, key: &str) -> String {
-        format!("set -e {}\n", key)
+        // Without -g, a universal variable of the same name could be erased
+        format!("set -e -g {}\n", key)
     }
 }
 
@@ -39,6 +39,13 @@ pub trait Shell: fmt::Display + Send + Sync {
     /// Generate activation script for this shell
     fn activate(&self, opts: ActivateOptions) -> String;
 
+    /// The line(s) that enable fnox when added to the shell's startup file
+    fn rc_line(&self) -> String;
+
+    /// The startup file `fnox activate --install` adds `rc_line` to, or None
+    /// when fnox can't tell where it is
+    fn rc_file(&self) -> Option<std::path::PathBuf>;
+
     /// Generate deactivation script for this shell
     fn deactivate(&self) -> String;
 
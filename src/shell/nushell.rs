@@ -1,5 +1,6 @@
 use super::{ActivateOptions, Shell};
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Nushell;
 
@@ -88,6 +89,20 @@ $env.config = ($env.config | upsert hooks.pre_prompt ($env.config.hooks.pre_prom
         out
     }
 
+    fn rc_line(&self) -> String {
+        [
+            r#"mkdir ($nu.data-dir | path join "vendor/autoload")"#,
+            r#"fnox activate nu | save -f ($nu.data-dir | path join "vendor/autoload/fnox.nu")"#,
+        ]
+        .join("\n")
+    }
+
+    /// Nushell's config location depends on its version and platform; only
+    /// `$nu.config-path` inside Nushell knows it
+    fn rc_file(&self) -> Option<PathBuf> {
+        None
+    }
+
     fn deactivate(&self) -> String {
         // Nushell has no eval — deactivation is handled by deactivate_output()
         // producing JSON, and the wrapper function cleaning up hooks/env inline.
@@ -1,6 +1,7 @@
 use super::{ActivateOptions, Shell};
 use std::borrow::Cow;
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Pwsh;
 
@@ -80,6 +81,23 @@ _fnox_hook"#,
         out
     }
 
+    fn rc_line(&self) -> String {
+        "(&fnox activate pwsh) | Out-String | Invoke-Expression".to_string()
+    }
+
+    /// The current user's profile for all hosts' default host ($PROFILE)
+    fn rc_file(&self) -> Option<PathBuf> {
+        #[cfg(windows)]
+        let dir = crate::env::HOME_DIR.join("Documents").join("PowerShell");
+        #[cfg(not(windows))]
+        let dir = crate::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| crate::env::HOME_DIR.join(".config"))
+            .join("powershell");
+        Some(dir.join("Microsoft.PowerShell_profile.ps1"))
+    }
+
     fn deactivate(&self) -> String {
         r#"
 if ($Global:__fnox_pwsh_previous_prompt_function) {
@@ -1,5 +1,6 @@
 use super::{ActivateOptions, Shell};
 use std::fmt;
+use std::path::PathBuf;
 
 pub struct Zsh;
 
@@ -71,6 +72,18 @@ fi
         out
     }
 
+    fn rc_line(&self) -> String {
+        r#"eval "$(fnox activate zsh)""#.to_string()
+    }
+
+    fn rc_file(&self) -> Option<PathBuf> {
+        let dir = crate::env::var_os("ZDOTDIR")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| crate::env::HOME_DIR.clone());
+        Some(dir.join(".zshrc"))
+    }
+
     fn deactivate(&self) -> String {
         let mut out = String::new();
 
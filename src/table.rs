@@ -0,0 +1,248 @@
+//! Aligned table output shared by the commands that print tables (`list`,
+//! `provider list`, `profiles`, `check`).
+//!
+//! Cells have their control characters escaped, so a key or description
+//! can't break a row or send escape sequences to the terminal. Widths are
+//! measured in terminal columns, so wide and combining unicode characters
+//! line up, and cells over a column's limit are cut with an ellipsis. When
+//! stdout is a terminal the widest columns shrink until a row fits its
+//! width.
+
+use crate::sanitize::escape_controls;
+use console::{measure_text_width, truncate_str};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Columns are indented by one space and separated by two
+const INDENT: usize = 1;
+const GAP: usize = 2;
+/// Columns never shrink below this to fit the terminal
+const MIN_COLUMN_WIDTH: usize = 4;
+const ELLIPSIS: &str = "…";
+
+#[derive(Debug, Default)]
+pub struct Table {
+    headers: Option<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    max_widths: HashMap<usize, usize>,
+}
+
+impl Table {
+    /// A table with a header row
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            headers: Some(headers.into_iter().map(cell).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// A table without a header row
+    pub fn headerless() -> Self {
+        Self::default()
+    }
+
+    pub fn row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rows.push(cells.into_iter().map(cell).collect());
+    }
+
+    /// Cut cells in `column` (0-based) to at most `width` terminal columns
+    pub fn max_width(mut self, column: usize, width: usize) -> Self {
+        self.max_widths.insert(column, width);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Print to stdout, fitted to the terminal when stdout is one
+    pub fn print(&self) {
+        println!(
+            "{}",
+            self.render(terminal_width(), console::colors_enabled())
+        );
+    }
+
+    /// Print to stdout without fitting it to the terminal
+    pub fn print_full(&self) {
+        println!("{}", self.render(None, console::colors_enabled()));
+    }
+
+    /// Render the table, fitted to `width` columns if given, with a bold
+    /// header when `color` is set. Lines have no trailing whitespace.
+    pub fn render(&self, width: Option<usize>, color: bool) -> String {
+        let lines: Vec<&Vec<String>> = self.headers.iter().chain(&self.rows).collect();
+        let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+
+        let mut widths = vec![0; columns];
+        for line in &lines {
+            for (column, cell) in line.iter().enumerate() {
+                widths[column] = widths[column].max(measure_text_width(cell));
+            }
+        }
+        for (column, width) in widths.iter_mut().enumerate() {
+            if let Some(max) = self.max_widths.get(&column) {
+                *width = (*width).min(*max);
+            }
+        }
+        if let Some(available) = width {
+            shrink_to_fit(&mut widths, available);
+        }
+
+        let mut out = Vec::with_capacity(lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            let is_header = index == 0 && self.headers.is_some();
+            let mut text = " ".repeat(INDENT);
+            for (column, width) in widths.iter().enumerate() {
+                if column > 0 {
+                    text.push_str(&" ".repeat(GAP));
+                }
+                let cell = line.get(column).map(String::as_str).unwrap_or("");
+                let cell = truncate_str(cell, *width, ELLIPSIS);
+                let padding = width.saturating_sub(measure_text_width(&cell));
+                if is_header && color && !cell.is_empty() {
+                    text.push_str(&format!("\x1b[1;94m{}\x1b[0m", cell));
+                } else {
+                    text.push_str(&cell);
+                }
+                text.push_str(&" ".repeat(padding));
+            }
+            out.push(text.trim_end().to_string());
+        }
+        out.join("\n")
+    }
+}
+
+fn cell(text: impl Into<String>) -> String {
+    let text = text.into();
+    if let Cow::Owned(escaped) = escape_controls(&text) {
+        return escaped;
+    }
+    text
+}
+
+/// Narrow the widest column one step at a time until the row fits in
+/// `available` columns, or every column is at its minimum
+fn shrink_to_fit(widths: &mut [usize], available: usize) {
+    let total = |widths: &[usize]| {
+        INDENT + widths.iter().sum::<usize>() + GAP * widths.len().saturating_sub(1)
+    };
+    while total(widths) > available {
+        let Some(widest) = widths
+            .iter_mut()
+            .filter(|width| **width > MIN_COLUMN_WIDTH)
+            .max_by_key(|width| **width)
+        else {
+            break;
+        };
+        *widest -= 1;
+    }
+}
+
+/// The width of the terminal on stdout, if stdout is a terminal
+pub fn terminal_width() -> Option<usize> {
+    let term = console::Term::stdout();
+    if !term.is_term() {
+        return None;
+    }
+    term.size_checked().map(|(_, columns)| columns as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Table {
+        let mut table = Table::new(["Key", "Type", "Description"]);
+        table.row(["API_KEY", "age", "Key for the payments API"]);
+        table.row(["数据库_URL", "default", "Primary database"]);
+        table.row(["TOKEN", "1password", ""]);
+        table
+    }
+
+    #[test]
+    fn renders_at_full_width() {
+        let expected = [
+            " Key         Type       Description",
+            " API_KEY     age        Key for the payments API",
+            " 数据库_URL  default    Primary database",
+            " TOKEN       1password",
+        ]
+        .join("\n");
+        assert_eq!(fixture().render(Some(80), false), expected);
+        assert_eq!(fixture().render(None, false), expected);
+    }
+
+    #[test]
+    fn renders_fitted_to_a_narrow_terminal() {
+        let expected = [
+            " Key         Type       Description",
+            " API_KEY     age        Key for the pay…",
+            " 数据库_URL  default    Primary database",
+            " TOKEN       1password",
+        ]
+        .join("\n");
+        assert_eq!(fixture().render(Some(40), false), expected);
+    }
+
+    #[test]
+    fn colors_only_the_header_text() {
+        let header = " \x1b[1;94mKey\x1b[0m         \x1b[1;94mType\x1b[0m       \x1b[1;94mDescription\x1b[0m";
+        for width in [80, 40] {
+            let plain = fixture().render(Some(width), false);
+            let colored = fixture().render(Some(width), true);
+            let (first, rest) = colored.split_once('\n').unwrap();
+            assert_eq!(first, header);
+            assert_eq!(rest, plain.split_once('\n').unwrap().1);
+        }
+    }
+
+    #[test]
+    fn truncates_to_column_limits() {
+        let mut table = Table::headerless().max_width(0, 6);
+        table.row(["ABCDEFGHIJ", "x"]);
+        table.row(["数据库数据库", "y"]);
+        assert_eq!(table.render(None, true), " ABCDE…  x\n 数据…   y");
+    }
+
+    #[test]
+    fn escapes_control_characters_and_keeps_columns_aligned() {
+        let mut table = Table::new(["Key", "Description"]);
+        table.row(["TITLE", "\x1b]0;pwned\x07"]);
+        table.row(["数据库", "line one\nline two"]);
+        table.row(["CAFE\u{301}", "\x1b[2J\ttab"]);
+        table.row(["PLAIN", "plain"]);
+
+        let rendered = table.render(None, false);
+        assert!(!rendered.contains('\x1b'), "{rendered:?}");
+        let expected = [
+            " Key     Description",
+            r" TITLE   \u{1b}]0;pwned\u{7}",
+            r" 数据库  line one\nline two",
+            " CAFE\u{301}    \\u{1b}[2J\\ttab",
+            " PLAIN   plain",
+        ];
+        assert_eq!(rendered, expected.join("\n"));
+
+        // Descriptions start in the same terminal column on every row
+        let descriptions = [
+            "Description",
+            r"\u{1b}]0;",
+            "line one",
+            r"\u{1b}[2J",
+            "plain",
+        ];
+        for (line, description) in rendered.lines().zip(descriptions) {
+            let start = line.find(description).unwrap();
+            assert_eq!(measure_text_width(&line[..start]), 9, "{line}");
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use indexmap::IndexMap;
 use ratatui::layout::Rect;
 use tokio::sync::mpsc;
@@ -10,7 +10,13 @@ use tokio::sync::mpsc;
 use crate::config::{Config, SecretConfig};
 use crate::daemon::{Purpose, ResolveContext};
 use crate::error::Result;
+use crate::naming::NamingPolicy;
 use crate::tui::event::Event;
+use crate::tui::keymap::{self, Action, Context};
+use crate::tui::store::{
+    add_reference, current_provider, delete_secret, list_provider_secrets, save_secret,
+};
+use crate::watch::{ConfigWatcher, watched_files};
 
 /// Focus area in the TUI
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +35,7 @@ pub enum Popup {
     ConfirmDelete(String), // Secret key to delete
     EditSecret(EditState), // Edit secret value
     SetSecret(SetState),   // Set new secret value
+    Browse(BrowseState),   // Browse a provider's stored secrets
 }
 
 /// State for editing a secret
@@ -46,12 +53,177 @@ pub struct SetState {
     pub value: String,
     pub field: SetField, // Which field is being edited
     pub cursor: usize,
+    /// Why the key breaks the naming policy, updated as it's typed
+    pub key_error: Option<String>,
+    /// The profile's providers to choose from
+    pub providers: Vec<String>,
+    /// Provider to store the secret with; `None` stores a plain value
+    pub provider: Option<String>,
+}
+
+impl SetState {
+    /// An empty form, with the profile's default provider selected
+    pub fn new(config: &Config, profile: &str) -> Self {
+        Self {
+            key: String::new(),
+            value: String::new(),
+            field: SetField::Key,
+            cursor: 0,
+            key_error: None,
+            providers: config.get_providers(profile).keys().cloned().collect(),
+            provider: config.get_default_provider(profile).ok().flatten(),
+        }
+    }
+
+    /// The keymap context for the field being edited
+    pub fn context(&self) -> Context {
+        match self.field {
+            SetField::Provider => Context::SetProvider,
+            _ => Context::SetSecret,
+        }
+    }
+
+    /// Move to the next field (Tab), or the previous one (Shift+Tab)
+    fn switch_field(&mut self, forward: bool) {
+        self.field = match (self.field, forward) {
+            (SetField::Key, true) | (SetField::Provider, false) => SetField::Value,
+            (SetField::Value, true) | (SetField::Key, false) => SetField::Provider,
+            (SetField::Provider, true) | (SetField::Value, false) => SetField::Key,
+        };
+        self.cursor = match self.field {
+            SetField::Key => self.key.chars().count(),
+            SetField::Value => self.value.chars().count(),
+            SetField::Provider => 0,
+        };
+    }
+
+    /// Select the next or previous provider, wrapping around
+    fn cycle_provider(&mut self, forward: bool) {
+        let count = self.providers.len();
+        if count == 0 {
+            return;
+        }
+        let next = match self
+            .provider
+            .as_ref()
+            .and_then(|current| self.providers.iter().position(|p| p == current))
+        {
+            Some(index) if forward => (index + 1) % count,
+            Some(index) => (index + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        };
+        self.provider = Some(self.providers[next].clone());
+    }
+}
+
+/// State for browsing the secrets stored in a provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseState {
+    pub provider: String,
+    pub status: BrowseStatus,
+    /// References listed by the provider
+    pub entries: Vec<String>,
+    /// Search filter string
+    pub filter: String,
+    /// Whether we're in search mode
+    pub searching: bool,
+    /// Selected index into the filtered entries
+    pub selected: usize,
+    /// The config key being typed for the picked entry
+    pub adding: Option<AddState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowseStatus {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// State for naming a browsed entry before adding it to the config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddState {
+    pub reference: String,
+    pub key: String,
+    pub cursor: usize,
+    /// Why the key can't be used, shown under it
+    pub error: Option<String>,
+}
+
+impl BrowseState {
+    fn new(provider: String) -> Self {
+        Self {
+            provider,
+            status: BrowseStatus::Loading,
+            entries: Vec::new(),
+            filter: String::new(),
+            searching: false,
+            selected: 0,
+            adding: None,
+        }
+    }
+
+    /// Entries, filtered by search
+    pub fn filtered_entries(&self) -> Vec<&String> {
+        let filter = self.filter.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| entry.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    /// Move the selection by `delta` entries, staying within the list
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.filtered_entries().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Start naming the selected entry, suggesting a key from its last segment
+    fn pick_selected(&mut self) {
+        let Some(reference) = self
+            .filtered_entries()
+            .get(self.selected)
+            .map(|e| e.to_string())
+        else {
+            return;
+        };
+        let key = suggested_key(&reference);
+        self.adding = Some(AddState {
+            cursor: key.chars().count(),
+            reference,
+            key,
+            error: None,
+        });
+    }
+}
+
+/// An environment variable name for a provider reference: its last path
+/// segment in upper snake case, e.g. `prod/db-password` becomes `DB_PASSWORD`
+fn suggested_key(reference: &str) -> String {
+    let name = reference
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(reference);
+    let key: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    key.trim_matches('_').to_string()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SetField {
     Key,
     Value,
+    Provider,
 }
 
 /// Messages that can be sent to the app
@@ -61,9 +233,27 @@ pub enum Message {
     SecretsResolved {
         resolution_id: u64,
         resolved: IndexMap<String, Option<String>>,
+        /// Error messages for secrets that failed to resolve
+        failed: IndexMap<String, String>,
+    },
+    /// A contributing config file changed on disk (`fnox tui --watch`)
+    ConfigChanged,
+    /// A secret was removed from its config file (or failed to be)
+    SecretDeleted {
+        key: String,
+        result: std::result::Result<(), String>,
+    },
+    /// A secret was written to its provider and config file (or failed to be)
+    SecretSaved {
+        key: String,
+        value: String,
+        result: std::result::Result<Box<SecretConfig>, String>,
+    },
+    /// A provider listed its stored secrets for the browse popup (or failed to)
+    ProviderListed {
+        browse_id: u64,
+        result: std::result::Result<Vec<String>, String>,
     },
-    /// Error occurred (includes resolution_id to handle race conditions)
-    Error { resolution_id: u64, message: String },
 }
 
 /// Main application state
@@ -110,6 +300,9 @@ pub struct App {
     /// Set of secrets currently being loaded
     pub loading_secrets: HashSet<String>,
 
+    /// Secrets that failed to resolve, with the error message
+    pub failed_secrets: IndexMap<String, String>,
+
     /// Whether initial load is in progress
     pub initial_loading: bool,
 
@@ -131,9 +324,18 @@ pub struct App {
     /// Whether to show secret values in the list (instead of ******)
     pub show_values: bool,
 
+    /// Whether to show grouped secrets by their group path (e.g. stripe.api_key)
+    pub show_groups: bool,
+
+    /// Whether edit/set/delete are disabled (`fnox tui --read-only`)
+    pub read_only: bool,
+
     /// Channel sender for async operations
     pub event_tx: Option<mpsc::UnboundedSender<Event>>,
 
+    /// Watcher on the config's source files (`fnox tui --watch`)
+    pub config_watcher: Option<ConfigWatcher>,
+
     /// Layout areas for mouse click detection
     pub providers_area: Rect,
     pub secrets_area: Rect,
@@ -141,6 +343,25 @@ pub struct App {
     /// Scroll offsets for mouse click handling (updated during render)
     pub providers_scroll_offset: usize,
     pub secrets_scroll_offset: usize,
+
+    /// Help popup scroll position (clamped during render)
+    pub help_scroll: usize,
+
+    /// Visible line count of the help popup (updated during render, used for paging)
+    pub help_viewport_height: usize,
+
+    /// Current browse ID (incremented each time the browse popup opens, so a
+    /// listing for an earlier one is ignored)
+    pub current_browse_id: u64,
+
+    /// Task listing a provider's secrets for the browse popup
+    pub browse_task: Option<tokio::task::AbortHandle>,
+
+    /// Entries per page of the browse popup (updated during render, used for paging)
+    pub browse_page_size: usize,
+
+    /// Animation frame for loading spinners, advanced on every tick
+    pub spinner_frame: usize,
 }
 
 impl App {
@@ -170,6 +391,7 @@ impl App {
             secret_index: 0,
             resolved_values: IndexMap::new(),
             loading_secrets: HashSet::new(),
+            failed_secrets: IndexMap::new(),
             initial_loading: true,
             current_resolution_id: 0,
             error_message: None,
@@ -177,11 +399,20 @@ impl App {
             search_filter: String::new(),
             searching: false,
             show_values: false,
+            show_groups: false,
+            read_only: false,
             event_tx: None,
+            config_watcher: None,
             providers_area: Rect::default(),
             secrets_area: Rect::default(),
             providers_scroll_offset: 0,
             secrets_scroll_offset: 0,
+            help_scroll: 0,
+            help_viewport_height: 0,
+            current_browse_id: 0,
+            browse_task: None,
+            browse_page_size: 0,
+            spinner_frame: 0,
         })
     }
 
@@ -190,6 +421,35 @@ impl App {
         self.event_tx = Some(tx);
     }
 
+    /// Reload the config and re-resolve secrets whenever a config file changes
+    pub fn watch_config(&mut self) -> Result<()> {
+        let Some(tx) = &self.event_tx else {
+            return Ok(());
+        };
+        let (watcher, mut changes) =
+            ConfigWatcher::new(watched_files(&self.daemon_context.config, &self.config))?;
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            while changes.recv().await.is_some() {
+                if tx.send(Event::Message(Message::ConfigChanged)).is_err() {
+                    break;
+                }
+            }
+        });
+        self.config_watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Advance animations
+    pub fn tick(&mut self) {
+        self.spinner_frame = self.spinner_frame.wrapping_add(1);
+    }
+
+    /// Disable actions that change secrets
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
     /// Get byte index from character index (UTF-8 safe)
     fn char_to_byte_index(s: &str, char_idx: usize) -> usize {
         s.char_indices()
@@ -239,6 +499,7 @@ impl App {
 
         // Clear stale resolved values to prevent showing wrong data
         self.resolved_values.clear();
+        self.failed_secrets.clear();
         self.initial_loading = true;
         self.loading_secrets = self.secrets.keys().cloned().collect();
 
@@ -248,30 +509,113 @@ impl App {
         let secrets = self.secrets.clone();
 
         tokio::spawn(async move {
-            match crate::daemon::resolve_batch_with_context(
+            let results = crate::daemon::resolve_partial_with_context(
                 &daemon_context,
                 &config,
                 &profile,
                 &secrets,
                 Purpose::Tui,
-                true,
             )
-            .await
-            {
-                Ok(resolved) => {
-                    let _ = tx.send(Event::Message(Message::SecretsResolved {
-                        resolution_id,
-                        resolved,
-                    }));
-                }
-                Err(e) => {
-                    let _ = tx.send(Event::Message(Message::Error {
-                        resolution_id,
-                        message: e.to_string(),
-                    }));
+            .await;
+
+            // Keep whatever resolved and report the rest per key
+            let mut resolved = IndexMap::new();
+            let mut failed = IndexMap::new();
+            for (key, result) in results {
+                match result {
+                    Ok(value) => {
+                        resolved.insert(key, value);
+                    }
+                    Err(e) => {
+                        failed.insert(key, e.to_string());
+                    }
                 }
             }
+            let _ = tx.send(Event::Message(Message::SecretsResolved {
+                resolution_id,
+                resolved,
+                failed,
+            }));
+        });
+    }
+
+    /// Spawn async task to remove a secret from its config file, and from its
+    /// provider's remote storage too if `delete_remote` is set
+    fn spawn_delete_secret(&mut self, key: String, delete_remote: bool) {
+        let Some(tx) = self.event_tx.clone() else {
+            return;
+        };
+        let config = self.config.clone();
+        let profile = self.profile.clone();
+
+        tokio::spawn(async move {
+            let result = delete_secret(&config, &profile, &key, delete_remote)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(Event::Message(Message::SecretDeleted { key, result }));
+        });
+    }
+
+    /// Spawn async task to store a secret's value through its provider and
+    /// write it to the config file
+    fn spawn_save_secret(&mut self, key: String, value: String, provider: Option<String>) {
+        let Some(tx) = self.event_tx.clone() else {
+            return;
+        };
+        let config = self.config.clone();
+        let profile = self.profile.clone();
+        let config_path = self.daemon_context.config.clone();
+        self.status_message = Some(format!("Saving {}...", key));
+
+        tokio::spawn(async move {
+            let result = save_secret(
+                &config,
+                &profile,
+                &config_path,
+                &key,
+                &value,
+                provider.as_deref(),
+            )
+            .await
+            .map(Box::new)
+            .map_err(|e| e.to_string());
+            let _ = tx.send(Event::Message(Message::SecretSaved { key, value, result }));
+        });
+    }
+
+    /// Open the browse popup for the selected provider and spawn a task to
+    /// list its secrets
+    fn open_browse(&mut self) {
+        let Some(provider) = self.providers.get(self.provider_index).cloned() else {
+            return;
+        };
+        self.current_browse_id = self.current_browse_id.wrapping_add(1);
+        self.popup = Popup::Browse(BrowseState::new(provider.clone()));
+
+        let Some(tx) = self.event_tx.clone() else {
+            return;
+        };
+        let browse_id = self.current_browse_id;
+        let config = self.config.clone();
+        let profile = self.profile.clone();
+        let task = tokio::spawn(async move {
+            let result = list_provider_secrets(&config, &profile, &provider)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(Event::Message(Message::ProviderListed {
+                browse_id,
+                result,
+            }));
         });
+        self.browse_task = Some(task.abort_handle());
+    }
+
+    /// Close the browse popup, cancelling a listing still in progress
+    fn close_browse(&mut self) {
+        if let Some(task) = self.browse_task.take() {
+            task.abort();
+        }
+        self.popup = Popup::None;
     }
 
     /// Handle an incoming message
@@ -280,28 +624,141 @@ impl App {
             Message::SecretsResolved {
                 resolution_id,
                 resolved,
+                failed,
             } => {
                 // Ignore results from stale resolution tasks (e.g., after profile switch)
                 if resolution_id != self.current_resolution_id {
                     return;
                 }
+                if !failed.is_empty() {
+                    self.error_message = Some(format!(
+                        "Failed to resolve {} of {} secrets",
+                        failed.len(),
+                        resolved.len() + failed.len()
+                    ));
+                }
                 self.resolved_values = resolved;
+                self.failed_secrets = failed;
                 self.loading_secrets.clear();
                 self.initial_loading = false;
             }
-            Message::Error {
-                resolution_id,
-                message,
-            } => {
-                // Ignore errors from stale resolution tasks
-                if resolution_id != self.current_resolution_id {
+            Message::ConfigChanged => self.reload_config(),
+            Message::SecretDeleted { key, result } => match result {
+                Ok(()) => self.remove_deleted_secret(&key),
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to delete {}: {}", key, e));
+                }
+            },
+            Message::SecretSaved { key, value, result } => match result {
+                Ok(secret) => self.store_saved_secret(key, value, *secret),
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to save {}: {}", key, e));
+                }
+            },
+            Message::ProviderListed { browse_id, result } => {
+                // Ignore listings for a browse popup that was closed or reopened
+                if browse_id != self.current_browse_id {
                     return;
                 }
-                self.error_message = Some(message);
-                self.loading_secrets.clear();
-                self.initial_loading = false;
+                self.browse_task = None;
+                if let Popup::Browse(state) = &mut self.popup {
+                    match result {
+                        Ok(entries) => {
+                            state.entries = entries;
+                            state.status = BrowseStatus::Loaded;
+                        }
+                        Err(e) => state.status = BrowseStatus::Failed(e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record a saved secret in the in-memory state
+    fn store_saved_secret(&mut self, key: String, value: String, secret: SecretConfig) {
+        let secrets = if secret.source_is_profile {
+            self.config.get_secrets_mut(&self.profile)
+        } else {
+            self.config.get_default_secrets_mut()
+        };
+        secrets.insert(key.clone(), secret.clone());
+        self.secrets.insert(key.clone(), secret);
+        self.failed_secrets.shift_remove(&key);
+        self.resolved_values.insert(key.clone(), Some(value));
+        self.status_message = Some(format!("Saved {}", key));
+    }
+
+    /// Record a secret added from the browse popup, and resolve it
+    fn store_added_secret(&mut self, key: String, secret: SecretConfig) {
+        let secrets = if secret.source_is_profile {
+            self.config.get_secrets_mut(&self.profile)
+        } else {
+            self.config.get_default_secrets_mut()
+        };
+        secrets.insert(key.clone(), secret.clone());
+        self.secrets.insert(key.clone(), secret);
+        self.refresh();
+        self.status_message = Some(format!("Added {}", key));
+    }
+
+    /// Drop a deleted secret from the in-memory state
+    fn remove_deleted_secret(&mut self, key: &str) {
+        if let Some(secret) = self.secrets.shift_remove(key) {
+            let secrets = if secret.source_is_profile {
+                self.config.get_secrets_mut(&self.profile)
+            } else {
+                self.config.get_default_secrets_mut()
+            };
+            secrets.shift_remove(key);
+        }
+        self.resolved_values.shift_remove(key);
+        self.failed_secrets.shift_remove(key);
+        self.loading_secrets.remove(key);
+        self.secret_index = self
+            .secret_index
+            .min(self.filtered_secrets().len().saturating_sub(1));
+        self.status_message = Some(format!("Deleted {}", key));
+    }
+
+    /// Reload the config from disk, keeping the current profile and selection
+    fn reload_config(&mut self) {
+        let config = match Config::load_smart(&self.daemon_context.config) {
+            Ok(config) => config,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to reload config: {}", e));
+                return;
+            }
+        };
+        let secrets = match config.get_secrets(&self.profile) {
+            Ok(secrets) => secrets,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to reload config: {}", e));
+                return;
             }
+        };
+
+        // Imports may have been added or removed
+        if let Some(watcher) = &mut self.config_watcher
+            && let Err(e) = watcher.set_files(watched_files(&self.daemon_context.config, &config))
+        {
+            self.error_message = Some(e.to_string());
         }
+
+        self.providers = config
+            .get_providers(&self.profile)
+            .keys()
+            .cloned()
+            .collect();
+        self.provider_index = self
+            .provider_index
+            .min(self.providers.len().saturating_sub(1));
+        self.secrets = secrets;
+        self.secret_index = self
+            .secret_index
+            .min(self.filtered_secrets().len().saturating_sub(1));
+        self.config = config;
+        self.status_message = Some("Config reloaded".to_string());
+        self.refresh();
     }
 
     /// Handle a key event
@@ -318,8 +775,7 @@ impl App {
         // Handle popup modes first
         match &self.popup {
             Popup::Help => {
-                // Any key closes help
-                self.popup = Popup::None;
+                self.handle_help_key(key);
                 return;
             }
             Popup::ProfilePicker => {
@@ -328,8 +784,8 @@ impl App {
             }
             Popup::SecretDetail(secret_key) => {
                 // Handle copy, otherwise close
-                match key.code {
-                    KeyCode::Char('c') => {
+                match keymap::action_for(Context::Detail, &key) {
+                    Some(Action::Copy) => {
                         // Copy the secret value
                         if let Some(Some(value)) = self.resolved_values.get(secret_key) {
                             match arboard::Clipboard::new() {
@@ -368,64 +824,75 @@ impl App {
                 self.handle_set_secret_key(key);
                 return;
             }
+            Popup::Browse(_) => {
+                self.handle_browse_key(key);
+                return;
+            }
             Popup::None => {}
         }
 
         // Handle search mode
         if self.searching {
-            match key.code {
-                KeyCode::Esc => {
+            match keymap::action_for(Context::Search, &key) {
+                Some(Action::ClearSearch) => {
                     self.searching = false;
                     self.search_filter.clear();
                     self.secret_index = 0;
                 }
-                KeyCode::Enter => {
+                Some(Action::ApplySearch) => {
                     self.searching = false;
                 }
-                KeyCode::Backspace => {
+                Some(Action::DeleteBack) => {
                     self.search_filter.pop();
                     self.secret_index = 0;
                 }
-                KeyCode::Char(c) => {
-                    self.search_filter.push(c);
-                    self.secret_index = 0;
+                // Other characters are typed into the filter
+                Some(_) => {}
+                None => {
+                    if let KeyCode::Char(c) = key.code {
+                        self.search_filter.push(c);
+                        self.secret_index = 0;
+                    }
                 }
-                _ => {}
             }
             return;
         }
 
         // Normal mode
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.running = false;
-            }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        let Some(action) = keymap::action_for(Context::Main, &key) else {
+            return;
+        };
+        if self.read_only && action.is_mutating() {
+            self.status_message = Some("Read-only mode".to_string());
+            return;
+        }
+        match action {
+            Action::Quit => {
                 self.running = false;
             }
-            KeyCode::Tab => {
+            Action::ToggleFocus => {
                 self.focus = match self.focus {
                     Focus::Providers => Focus::Secrets,
                     Focus::Secrets => Focus::Providers,
                 };
             }
-            KeyCode::Char('/') => {
+            Action::StartSearch => {
                 self.searching = true;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Action::MoveDown => {
                 self.move_selection(1);
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Action::MoveUp => {
                 self.move_selection(-1);
             }
-            KeyCode::Char('g') => {
+            Action::GoTop => {
                 // Go to top
                 match self.focus {
                     Focus::Providers => self.provider_index = 0,
                     Focus::Secrets => self.secret_index = 0,
                 }
             }
-            KeyCode::Char('G') => {
+            Action::GoBottom => {
                 // Go to bottom
                 match self.focus {
                     Focus::Providers => {
@@ -441,10 +908,11 @@ impl App {
                     }
                 }
             }
-            KeyCode::Char('?') => {
+            Action::ShowHelp => {
+                self.help_scroll = 0;
                 self.popup = Popup::Help;
             }
-            KeyCode::Char('P') => {
+            Action::SwitchProfile => {
                 // Open profile picker
                 self.profile_picker_index = self
                     .available_profiles
@@ -453,15 +921,15 @@ impl App {
                     .unwrap_or(0);
                 self.popup = Popup::ProfilePicker;
             }
-            KeyCode::Char('r') => {
+            Action::Refresh => {
                 // Refresh - reload secrets
                 self.refresh();
             }
-            KeyCode::Char('c') => {
+            Action::Copy => {
                 // Copy secret value to clipboard
                 self.copy_selected_secret();
             }
-            KeyCode::Enter => {
+            Action::ViewDetail => {
                 // Show secret detail view
                 if self.focus == Focus::Secrets
                     && let Some(key) = self.selected_secret()
@@ -469,7 +937,7 @@ impl App {
                     self.popup = Popup::SecretDetail(key.clone());
                 }
             }
-            KeyCode::Char('d') => {
+            Action::Delete => {
                 // Delete secret (with confirmation)
                 if self.focus == Focus::Secrets
                     && let Some(key) = self.selected_secret()
@@ -477,40 +945,67 @@ impl App {
                     self.popup = Popup::ConfirmDelete(key.clone());
                 }
             }
-            KeyCode::Char('e')
+            Action::Edit
                 // Edit selected secret value
                 if self.focus == Focus::Secrets => {
                     self.open_edit_secret();
                 }
-            KeyCode::Char('s') => {
+            Action::Set => {
                 // Set/create a new secret
-                self.popup = Popup::SetSecret(SetState {
-                    key: String::new(),
-                    value: String::new(),
-                    field: SetField::Key,
-                    cursor: 0,
-                });
-            }
-            KeyCode::Char('V') => {
+                self.popup = Popup::SetSecret(SetState::new(&self.config, &self.profile));
+            }
+            Action::ToggleValues => {
                 // Toggle showing secret values
                 self.show_values = !self.show_values;
             }
+            Action::ToggleGroups => {
+                self.show_groups = !self.show_groups;
+            }
+            Action::Browse
+                // Browse the selected provider's secrets
+                if self.focus == Focus::Providers => {
+                    self.open_browse();
+                }
             _ => {}
         }
     }
 
+    /// Handle keys in the help popup: scroll keys move, anything else closes it
+    fn handle_help_key(&mut self, key: KeyEvent) {
+        let page = self.help_viewport_height.max(1);
+        match keymap::action_for(Context::Help, &key) {
+            Some(Action::ScrollDown) => self.help_scroll = self.help_scroll.saturating_add(1),
+            Some(Action::ScrollUp) => self.help_scroll = self.help_scroll.saturating_sub(1),
+            Some(Action::PageDown) => self.help_scroll = self.help_scroll.saturating_add(page),
+            Some(Action::PageUp) => self.help_scroll = self.help_scroll.saturating_sub(page),
+            Some(Action::ScrollTop) => self.help_scroll = 0,
+            // Clamped to the last page during render
+            Some(Action::ScrollBottom) => self.help_scroll = usize::MAX,
+            _ => self.popup = Popup::None,
+        }
+    }
+
     /// Handle a mouse event
     pub fn handle_mouse(&mut self, mouse: MouseEvent) {
         // Ignore mouse events when popup is open (except for dismissing)
         if self.popup != Popup::None {
-            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
-                // Click dismisses most popups
-                match &self.popup {
-                    Popup::Help | Popup::SecretDetail(_) => {
-                        self.popup = Popup::None;
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    // Click dismisses most popups
+                    match &self.popup {
+                        Popup::Help | Popup::SecretDetail(_) => {
+                            self.popup = Popup::None;
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
+                MouseEventKind::ScrollDown if self.popup == Popup::Help => {
+                    self.help_scroll = self.help_scroll.saturating_add(1);
+                }
+                MouseEventKind::ScrollUp if self.popup == Popup::Help => {
+                    self.help_scroll = self.help_scroll.saturating_sub(1);
+                }
+                _ => {}
             }
             return;
         }
@@ -592,16 +1087,18 @@ impl App {
     }
 
     /// Handle keys in confirm delete popup
-    fn handle_confirm_delete_key(&mut self, key: KeyEvent, _secret_key: String) {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
+    fn handle_confirm_delete_key(&mut self, key: KeyEvent, secret_key: String) {
+        match keymap::action_for(Context::ConfirmDelete, &key) {
+            Some(Action::Cancel) => {
                 self.popup = Popup::None;
             }
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                // Note: Actual deletion would require modifying the config file
-                // For now, we just show a message that this feature isn't implemented
-                self.error_message = Some("Delete not yet implemented in TUI".to_string());
+            Some(Action::ConfirmDelete) => {
                 self.popup = Popup::None;
+                self.spawn_delete_secret(secret_key, false);
+            }
+            Some(Action::DeleteRemote) => {
+                self.popup = Popup::None;
+                self.spawn_delete_secret(secret_key, true);
             }
             _ => {}
         }
@@ -634,152 +1131,261 @@ impl App {
             return;
         };
 
-        match key.code {
-            KeyCode::Esc => {
+        match keymap::action_for(Context::Form, &key) {
+            Some(Action::Cancel) => {
                 self.popup = Popup::None;
             }
-            KeyCode::Enter => {
-                // Save the edited value
+            Some(Action::Save) => {
+                // Save the edited value, with the provider it's stored with
                 let key = state.key.clone();
                 let value = state.value.clone();
                 self.popup = Popup::None;
 
-                // Note: Actually saving would require modifying the config file
-                // For now, just update the in-memory resolved value
-                self.resolved_values.insert(key.clone(), Some(value));
-                self.status_message = Some(format!("Updated {} (in memory only)", key));
-            }
-            KeyCode::Backspace if state.cursor > 0 => {
-                Self::remove_char_at(&mut state.value, state.cursor - 1);
-                state.cursor -= 1;
-            }
-            KeyCode::Delete if state.cursor < state.value.chars().count() => {
-                Self::remove_char_at(&mut state.value, state.cursor);
-            }
-            KeyCode::Left => {
-                state.cursor = state.cursor.saturating_sub(1);
+                match current_provider(&self.config, &self.profile, &key) {
+                    Ok(provider) => self.spawn_save_secret(key, value, provider),
+                    Err(e) => self.error_message = Some(e.to_string()),
+                }
             }
-            KeyCode::Right => {
-                state.cursor = (state.cursor + 1).min(state.value.chars().count());
+            action => {
+                Self::edit_text(&key, action, &mut state.value, &mut state.cursor);
             }
-            KeyCode::Home => {
-                state.cursor = 0;
+        }
+    }
+
+    /// Apply a text field action to `text`, or type the key's character if it
+    /// has none. Returns whether the key was handled.
+    fn edit_text(
+        key: &KeyEvent,
+        action: Option<Action>,
+        text: &mut String,
+        cursor: &mut usize,
+    ) -> bool {
+        let len = text.chars().count();
+        match action {
+            Some(Action::DeleteBack) if *cursor > 0 => {
+                Self::remove_char_at(text, *cursor - 1);
+                *cursor -= 1;
             }
-            KeyCode::End => {
-                state.cursor = state.value.chars().count();
+            Some(Action::DeleteForward) if *cursor < len => {
+                Self::remove_char_at(text, *cursor);
             }
-            KeyCode::Char(c) => {
-                Self::insert_char_at(&mut state.value, state.cursor, c);
-                state.cursor += 1;
+            Some(Action::DeleteBack | Action::DeleteForward) => {}
+            Some(Action::CursorLeft) => *cursor = cursor.saturating_sub(1),
+            Some(Action::CursorRight) => *cursor = (*cursor + 1).min(len),
+            Some(Action::CursorHome) => *cursor = 0,
+            Some(Action::CursorEnd) => *cursor = len,
+            None => {
+                let KeyCode::Char(c) = key.code else {
+                    return false;
+                };
+                Self::insert_char_at(text, *cursor, c);
+                *cursor += 1;
             }
-            _ => {}
+            Some(_) => return false,
         }
+        true
     }
 
     /// Handle keys in set secret popup
     fn handle_set_secret_key(&mut self, key: KeyEvent) {
+        // An invalid pattern is reported by `fnox check`; don't block the popup on it
+        let policy = NamingPolicy::for_profile(&self.config, &self.profile)
+            .ok()
+            .flatten();
         let Popup::SetSecret(ref mut state) = self.popup else {
             return;
         };
 
-        // Clear error on any keypress except Esc (which closes the popup)
-        if key.code != KeyCode::Esc {
+        let action = keymap::action_for(state.context(), &key);
+
+        // Clear error on any keypress except Cancel (which closes the popup)
+        if action != Some(Action::Cancel) {
             self.error_message = None;
         }
 
-        match key.code {
-            KeyCode::Esc => {
+        match action {
+            Some(Action::Cancel) => {
                 self.popup = Popup::None;
             }
-            KeyCode::Tab => {
-                // Switch between key and value fields
-                state.field = match state.field {
-                    SetField::Key => {
-                        state.cursor = state.value.chars().count();
-                        SetField::Value
-                    }
-                    SetField::Value => {
-                        state.cursor = state.key.chars().count();
-                        SetField::Key
-                    }
-                };
-            }
-            KeyCode::Enter => {
+            Some(Action::NextField) => state.switch_field(true),
+            Some(Action::PrevField) => state.switch_field(false),
+            Some(Action::Save) => {
                 if state.key.is_empty() {
                     self.error_message = Some("Secret key cannot be empty".to_string());
                     return;
                 }
+                if let Some(error) = &state.key_error {
+                    self.error_message = Some(format!("Secret key {}", error));
+                    return;
+                }
 
                 // Save the new secret
                 let key = state.key.clone();
                 let value = state.value.clone();
+                let provider = state.provider.clone();
                 self.popup = Popup::None;
 
-                // Note: Actually saving would require modifying the config file
-                // For now, just update the in-memory state
-                let mut secret_config = SecretConfig::new();
-                secret_config.set_value(Some(value.clone()));
-                self.secrets.insert(key.clone(), secret_config);
-                self.resolved_values.insert(key.clone(), Some(value));
-                self.status_message = Some(format!("Set {} (in memory only)", key));
+                self.spawn_save_secret(key, value, provider);
             }
-            KeyCode::Backspace if state.cursor > 0 => {
+            Some(Action::PrevProvider) => state.cycle_provider(false),
+            Some(Action::NextProvider) => state.cycle_provider(true),
+            // The provider field takes no text
+            _ if state.field == SetField::Provider => {}
+            action => {
                 let field = match state.field {
                     SetField::Key => &mut state.key,
-                    SetField::Value => &mut state.value,
+                    _ => &mut state.value,
                 };
-                Self::remove_char_at(field, state.cursor - 1);
-                state.cursor -= 1;
+                Self::edit_text(&key, action, field, &mut state.cursor);
             }
-            KeyCode::Delete => {
-                let field = match state.field {
-                    SetField::Key => &mut state.key,
-                    SetField::Value => &mut state.value,
-                };
-                if state.cursor < field.chars().count() {
-                    Self::remove_char_at(field, state.cursor);
+        }
+
+        if let Popup::SetSecret(ref mut state) = self.popup {
+            state.key_error = policy
+                .as_ref()
+                .filter(|_| !state.key.is_empty())
+                .and_then(|policy| policy.violation(&state.key));
+        }
+    }
+
+    /// Handle keys in the browse popup
+    fn handle_browse_key(&mut self, key: KeyEvent) {
+        let page = self.browse_page_size.max(1) as isize;
+        let Popup::Browse(ref mut state) = self.popup else {
+            return;
+        };
+
+        if state.adding.is_some() {
+            self.handle_browse_add_key(key);
+            return;
+        }
+
+        if state.searching {
+            match keymap::action_for(Context::Search, &key) {
+                Some(Action::ClearSearch) => {
+                    state.searching = false;
+                    state.filter.clear();
+                    state.selected = 0;
+                }
+                Some(Action::ApplySearch) => state.searching = false,
+                Some(Action::DeleteBack) => {
+                    state.filter.pop();
+                    state.selected = 0;
+                }
+                // Other characters are typed into the filter
+                Some(_) => {}
+                None => {
+                    if let KeyCode::Char(c) = key.code {
+                        state.filter.push(c);
+                        state.selected = 0;
+                    }
                 }
             }
-            KeyCode::Left => {
-                state.cursor = state.cursor.saturating_sub(1);
-            }
-            KeyCode::Right => {
-                let max = match state.field {
-                    SetField::Key => state.key.chars().count(),
-                    SetField::Value => state.value.chars().count(),
-                };
-                state.cursor = (state.cursor + 1).min(max);
+            return;
+        }
+
+        let Some(action) = keymap::action_for(Context::Browse, &key) else {
+            return;
+        };
+        match action {
+            // Also cancels a listing still in progress
+            Action::CloseBrowse => self.close_browse(),
+            Action::MoveDown => state.move_selection(1),
+            Action::MoveUp => state.move_selection(-1),
+            Action::PageDown => state.move_selection(page),
+            Action::PageUp => state.move_selection(-page),
+            Action::GoTop => state.selected = 0,
+            Action::GoBottom => state.move_selection(isize::MAX),
+            Action::StartSearch if state.status == BrowseStatus::Loaded => {
+                state.searching = true;
             }
-            KeyCode::Char(c) => {
-                let field = match state.field {
-                    SetField::Key => &mut state.key,
-                    SetField::Value => &mut state.value,
-                };
-                Self::insert_char_at(field, state.cursor, c);
-                state.cursor += 1;
+            Action::AddBrowsed if state.status == BrowseStatus::Loaded => {
+                if self.read_only {
+                    self.status_message = Some("Read-only mode".to_string());
+                } else {
+                    state.pick_selected();
+                }
             }
             _ => {}
         }
     }
 
+    /// Handle keys while naming a browsed entry
+    fn handle_browse_add_key(&mut self, key: KeyEvent) {
+        // An invalid pattern is reported by `fnox check`; don't block the popup on it
+        let policy = NamingPolicy::for_profile(&self.config, &self.profile)
+            .ok()
+            .flatten();
+        let Popup::Browse(ref mut state) = self.popup else {
+            return;
+        };
+        let Some(adding) = state.adding.as_mut() else {
+            return;
+        };
+
+        match keymap::action_for(Context::Form, &key) {
+            Some(Action::Cancel) => {
+                state.adding = None;
+                return;
+            }
+            Some(Action::Save) => {
+                if adding.key.is_empty() {
+                    adding.error = Some("Secret key cannot be empty".to_string());
+                    return;
+                }
+                if self.secrets.contains_key(&adding.key) {
+                    adding.error = Some(format!("'{}' already exists in this profile", adding.key));
+                    return;
+                }
+                if let Some(error) = policy.as_ref().and_then(|p| p.violation(&adding.key)) {
+                    adding.error = Some(format!("Secret key {}", error));
+                    return;
+                }
+
+                match add_reference(
+                    &self.config,
+                    &self.profile,
+                    &self.daemon_context.config,
+                    &adding.key,
+                    &state.provider,
+                    &adding.reference,
+                ) {
+                    Ok(secret) => {
+                        let key = adding.key.clone();
+                        self.close_browse();
+                        self.store_added_secret(key, secret);
+                    }
+                    Err(e) => adding.error = Some(e.to_string()),
+                }
+                return;
+            }
+            action => {
+                if !Self::edit_text(&key, action, &mut adding.key, &mut adding.cursor) {
+                    return;
+                }
+            }
+        }
+        adding.error = None;
+    }
+
     /// Handle keys in profile picker popup
     fn handle_profile_picker_key(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => {
+        match keymap::action_for(Context::ProfilePicker, &key) {
+            Some(Action::Cancel) => {
                 self.popup = Popup::None;
             }
-            KeyCode::Enter => {
+            Some(Action::Select) => {
                 // Select the profile
                 if let Some(profile) = self.available_profiles.get(self.profile_picker_index) {
                     self.switch_profile(profile.clone());
                 }
                 self.popup = Popup::None;
             }
-            KeyCode::Down | KeyCode::Char('j') if !self.available_profiles.is_empty() => {
+            Some(Action::MoveDown) if !self.available_profiles.is_empty() => {
                 self.profile_picker_index =
                     (self.profile_picker_index + 1) % self.available_profiles.len();
             }
-            KeyCode::Up | KeyCode::Char('k') if !self.available_profiles.is_empty() => {
+            Some(Action::MoveUp) if !self.available_profiles.is_empty() => {
                 self.profile_picker_index = self
                     .profile_picker_index
                     .checked_sub(1)
@@ -846,3 +1452,606 @@ impl App {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::FnoxError;
+    use crossterm::event::KeyModifiers;
+
+    fn app() -> App {
+        let mut config = Config::new();
+        config
+            .secrets
+            .insert("API_KEY".to_string(), SecretConfig::new());
+        let daemon_context = ResolveContext {
+            config: "fnox.toml".into(),
+            profile: None,
+            age_key_file: None,
+            if_missing: None,
+            no_defaults: false,
+            non_interactive: true,
+            no_daemon: true,
+        };
+        App::new(config, "default".to_string(), daemon_context).unwrap()
+    }
+
+    fn press(app: &mut App, c: char) {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_partial_resolution_keeps_resolved_values_and_marks_failures() {
+        let mut app = app();
+        app.current_resolution_id = 1;
+
+        let mut resolved = IndexMap::new();
+        resolved.insert("DATABASE_URL".to_string(), Some("postgres://".to_string()));
+        let mut failed = IndexMap::new();
+        failed.insert("API_KEY".to_string(), "provider unreachable".to_string());
+        app.handle_message(Message::SecretsResolved {
+            resolution_id: 1,
+            resolved,
+            failed,
+        });
+
+        assert!(!app.initial_loading);
+        assert_eq!(
+            app.resolved_values.get("DATABASE_URL"),
+            Some(&Some("postgres://".to_string()))
+        );
+        assert_eq!(
+            app.failed_secrets.get("API_KEY").map(String::as_str),
+            Some("provider unreachable")
+        );
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Failed to resolve 1 of 2 secrets")
+        );
+    }
+
+    #[test]
+    fn test_read_only_ignores_mutating_keys() {
+        let mut app = app();
+        app.set_read_only(true);
+
+        for c in ['e', 's', 'd'] {
+            press(&mut app, c);
+            assert_eq!(app.popup, Popup::None, "'{c}' opened a popup");
+            assert_eq!(app.status_message.as_deref(), Some("Read-only mode"));
+        }
+        assert!(app.secrets.contains_key("API_KEY"));
+
+        // Browsing still works
+        press(&mut app, 'V');
+        assert!(app.show_values);
+        press(&mut app, '?');
+        assert_eq!(app.popup, Popup::Help);
+    }
+
+    #[test]
+    fn test_mutating_keys_work_when_writable() {
+        let mut app = app();
+        press(&mut app, 'd');
+        assert_eq!(app.popup, Popup::ConfirmDelete("API_KEY".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_secret_from_its_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fnox.toml");
+        std::fs::write(
+            &path,
+            "# project secrets\n\
+             [secrets]\n\
+             API_KEY = { default = \"a\" } # keep me\n\
+             DB_URL = { default = \"b\" }\n\
+             \n\
+             [profiles.prod.secrets]\n\
+             PROD_URL = { default = \"prod\" }\n",
+        )
+        .unwrap();
+        let mut app = app();
+        app.config = Config::load(&path).unwrap();
+        app.profile = "prod".to_string();
+        app.secrets = app.config.get_secrets("prod").unwrap();
+        app.secret_index = 2;
+
+        delete_secret(&app.config, "prod", "PROD_URL", false)
+            .await
+            .unwrap();
+        app.handle_message(Message::SecretDeleted {
+            key: "PROD_URL".to_string(),
+            result: Ok(()),
+        });
+
+        // The profile section is kept, and the rest of the file untouched
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# project secrets\n\
+             [secrets]\n\
+             API_KEY = { default = \"a\" } # keep me\n\
+             DB_URL = { default = \"b\" }\n\
+             \n\
+             [profiles.prod.secrets]\n"
+        );
+        assert!(!app.secrets.contains_key("PROD_URL"));
+        assert!(!app.config.profiles["prod"].secrets.contains_key("PROD_URL"));
+        assert_eq!(app.secret_index, 1);
+        assert_eq!(app.status_message.as_deref(), Some("Deleted PROD_URL"));
+
+        let err = delete_secret(&app.config, "prod", "MISSING", false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FnoxError::SecretNotFound { .. }));
+    }
+
+    #[test]
+    fn test_set_popup_validates_key_against_naming_policy() {
+        let mut app = app();
+        app.config.settings = Some(crate::config::SettingsConfig {
+            naming: Some(crate::config::NamingConfig {
+                require_prefix: Some("APP_".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        press(&mut app, 's');
+
+        let key_error = |app: &App| match &app.popup {
+            Popup::SetSecret(state) => state.key_error.clone(),
+            other => panic!("set popup closed: {other:?}"),
+        };
+        for c in "api".chars() {
+            press(&mut app, c);
+        }
+        assert_eq!(key_error(&app).as_deref(), Some("must start with 'APP_'"));
+
+        // Enter is refused while the key is invalid
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(matches!(app.popup, Popup::SetSecret(_)));
+        assert!(!app.secrets.contains_key("api"));
+
+        for _ in 0..3 {
+            app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        }
+        for c in "APP_TOKEN".chars() {
+            press(&mut app, c);
+        }
+        assert_eq!(key_error(&app), None);
+    }
+
+    fn set_state(app: &App) -> &SetState {
+        match &app.popup {
+            Popup::SetSecret(state) => state,
+            other => panic!("set popup closed: {other:?}"),
+        }
+    }
+
+    /// An app for `profile` of a config with providers `age` and `backup`,
+    /// where the `staging` profile defaults to `backup`
+    fn app_with_providers(profile: &str) -> App {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fnox.toml");
+        std::fs::write(
+            &path,
+            "[providers.age]\n\
+             type = \"age\"\n\
+             recipients = [\"age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p\"]\n\
+             \n\
+             [providers.backup]\n\
+             type = \"age\"\n\
+             recipients = [\"age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p\"]\n\
+             \n\
+             [profiles.staging]\n\
+             default_provider = \"backup\"\n",
+        )
+        .unwrap();
+        let config = Config::load(&path).unwrap();
+        let mut app = app();
+        app.config = config;
+        app.profile = profile.to_string();
+        app
+    }
+
+    #[test]
+    fn test_set_popup_switches_between_key_value_and_provider() {
+        let mut app = app_with_providers("default");
+        press(&mut app, 's');
+        press(&mut app, 'K');
+        assert_eq!(set_state(&app).field, SetField::Key);
+
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(set_state(&app).field, SetField::Value);
+        press(&mut app, 'v');
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(set_state(&app).field, SetField::Provider);
+
+        // Typing on the provider field changes nothing
+        press(&mut app, 'x');
+        assert_eq!(set_state(&app).key, "K");
+        assert_eq!(set_state(&app).value, "v");
+
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(set_state(&app).field, SetField::Key);
+        assert_eq!(set_state(&app).cursor, 1);
+
+        app.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(set_state(&app).field, SetField::Provider);
+        app.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(set_state(&app).field, SetField::Value);
+    }
+
+    #[test]
+    fn test_set_popup_cycles_through_providers() {
+        let mut app = app_with_providers("default");
+        press(&mut app, 's');
+        let provider = |app: &App| set_state(app).provider.clone();
+        // Neither provider is the default
+        assert_eq!(provider(&app), None);
+
+        app.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert_eq!(provider(&app).as_deref(), Some("age"));
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert_eq!(provider(&app).as_deref(), Some("backup"));
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert_eq!(provider(&app).as_deref(), Some("age"));
+        app.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        assert_eq!(provider(&app).as_deref(), Some("backup"));
+    }
+
+    #[test]
+    fn test_set_popup_defaults_to_the_profiles_default_provider() {
+        let mut app = app_with_providers("staging");
+        press(&mut app, 's');
+        let state = set_state(&app);
+        assert_eq!(state.providers, ["age", "backup"]);
+        assert_eq!(state.provider.as_deref(), Some("backup"));
+    }
+
+    fn browse_state(app: &App) -> &BrowseState {
+        match &app.popup {
+            Popup::Browse(state) => state,
+            other => panic!("browse popup closed: {other:?}"),
+        }
+    }
+
+    fn list(app: &mut App, result: std::result::Result<Vec<String>, String>) {
+        app.handle_message(Message::ProviderListed {
+            browse_id: app.current_browse_id,
+            result,
+        });
+    }
+
+    #[test]
+    fn test_browse_opens_for_the_focused_provider() {
+        let mut app = app_with_providers("default");
+        app.providers = vec!["age".to_string(), "backup".to_string()];
+
+        // Only from the providers panel
+        press(&mut app, 'b');
+        assert_eq!(app.popup, Popup::None);
+
+        app.focus = Focus::Providers;
+        app.provider_index = 1;
+        press(&mut app, 'b');
+        assert_eq!(browse_state(&app).provider, "backup");
+        assert_eq!(browse_state(&app).status, BrowseStatus::Loading);
+
+        // A listing for an earlier popup is ignored
+        app.handle_message(Message::ProviderListed {
+            browse_id: app.current_browse_id - 1,
+            result: Ok(vec!["stale".to_string()]),
+        });
+        assert_eq!(browse_state(&app).status, BrowseStatus::Loading);
+
+        list(
+            &mut app,
+            Err("This provider can't list the secrets it stores".to_string()),
+        );
+        assert_eq!(
+            browse_state(&app).status,
+            BrowseStatus::Failed("This provider can't list the secrets it stores".to_string())
+        );
+
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(app.popup, Popup::None);
+    }
+
+    #[test]
+    fn test_browse_searches_and_pages_through_entries() {
+        let mut app = app_with_providers("default");
+        app.providers = vec!["age".to_string()];
+        app.focus = Focus::Providers;
+        app.browse_page_size = 2;
+        press(&mut app, 'b');
+        list(
+            &mut app,
+            Ok(vec![
+                "prod/api-key".to_string(),
+                "prod/db".to_string(),
+                "staging/api-key".to_string(),
+                "staging/db".to_string(),
+            ]),
+        );
+
+        app.handle_key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(browse_state(&app).selected, 2);
+        press(&mut app, 'G');
+        assert_eq!(browse_state(&app).selected, 3);
+
+        press(&mut app, '/');
+        for c in "api".chars() {
+            press(&mut app, c);
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let state = browse_state(&app);
+        assert!(!state.searching);
+        assert_eq!(state.selected, 0);
+        assert_eq!(
+            state.filtered_entries(),
+            vec!["prod/api-key", "staging/api-key"]
+        );
+
+        // `q` closes the popup outside of search
+        press(&mut app, 'q');
+        assert_eq!(app.popup, Popup::None);
+    }
+
+    #[test]
+    fn test_browse_adds_a_reference_to_the_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fnox.toml");
+        std::fs::write(
+            &path,
+            "[providers.vault]\n\
+             type = \"plain\"\n\
+             \n\
+             [secrets]\n\
+             API_KEY = { default = \"a\" }\n",
+        )
+        .unwrap();
+        let mut app = app();
+        app.config = Config::load(&path).unwrap();
+        app.secrets = app.config.get_secrets("default").unwrap();
+        app.providers = vec!["vault".to_string()];
+        app.daemon_context.config = path.clone();
+        app.focus = Focus::Providers;
+        press(&mut app, 'b');
+        list(
+            &mut app,
+            Ok(vec![
+                "prod/api-key".to_string(),
+                "prod/db-password".to_string(),
+            ]),
+        );
+
+        // The suggested key is taken, so it has to be changed
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            browse_state(&app).adding.as_ref().map(|a| a.key.as_str()),
+            Some("API_KEY")
+        );
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            browse_state(&app)
+                .adding
+                .as_ref()
+                .and_then(|a| a.error.as_deref()),
+            Some("'API_KEY' already exists in this profile")
+        );
+
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(browse_state(&app).adding, None);
+        press(&mut app, 'j');
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert_eq!(app.popup, Popup::None);
+        assert_eq!(app.status_message.as_deref(), Some("Added DB_PASSWORD"));
+        let added = &app.secrets["DB_PASSWORD"];
+        assert_eq!(added.provider(), Some("vault"));
+        assert_eq!(added.value(), Some("prod/db-password"));
+        let reloaded = Config::load(&path).unwrap();
+        assert_eq!(
+            reloaded.secrets["DB_PASSWORD"].value(),
+            Some("prod/db-password")
+        );
+        assert_eq!(reloaded.secrets["DB_PASSWORD"].provider(), Some("vault"));
+    }
+
+    #[test]
+    fn test_browse_in_read_only_mode_does_not_add() {
+        let mut app = app_with_providers("default");
+        app.providers = vec!["age".to_string()];
+        app.set_read_only(true);
+        app.focus = Focus::Providers;
+        press(&mut app, 'b');
+        list(&mut app, Ok(vec!["token".to_string()]));
+
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(browse_state(&app).adding, None);
+        assert_eq!(app.status_message.as_deref(), Some("Read-only mode"));
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// The app in each keymap context `handle_key` dispatches on, with text
+    /// fields holding "ab" and the cursor between the two characters. Secrets
+    /// added from the browse popup are written to `dir`.
+    fn apps_in_every_context(dir: &std::path::Path) -> Vec<(Context, App)> {
+        let mut apps = vec![(Context::Main, app())];
+
+        let mut searching = app();
+        press(&mut searching, '/');
+        apps.push((Context::Search, searching));
+
+        let mut help = app();
+        press(&mut help, '?');
+        apps.push((Context::Help, help));
+
+        let mut detail = app();
+        app_key(&mut detail, KeyCode::Enter);
+        apps.push((Context::Detail, detail));
+
+        let mut delete = app();
+        press(&mut delete, 'd');
+        apps.push((Context::ConfirmDelete, delete));
+
+        let mut edit = app();
+        edit.popup = Popup::EditSecret(EditState {
+            key: "API_KEY".to_string(),
+            value: "ab".to_string(),
+            cursor: 1,
+        });
+        apps.push((Context::Form, edit));
+
+        let set = || {
+            let mut app = app_with_providers("default");
+            press(&mut app, 's');
+            for c in ['A', 'B'] {
+                press(&mut app, c);
+            }
+            app_key(&mut app, KeyCode::Left);
+            app
+        };
+        apps.push((Context::SetSecret, set()));
+        let mut provider = set();
+        app_key(&mut provider, KeyCode::BackTab);
+        apps.push((Context::SetProvider, provider));
+
+        let browse = || {
+            let mut app = app_with_providers("default");
+            app.providers = vec!["age".to_string()];
+            app.daemon_context.config = dir.join("fnox.toml");
+            app.focus = Focus::Providers;
+            press(&mut app, 'b');
+            list(
+                &mut app,
+                Ok(vec!["prod/a".to_string(), "prod/b".to_string()]),
+            );
+            app_key(&mut app, KeyCode::Down);
+            app
+        };
+        apps.push((Context::Browse, browse()));
+        let mut browse_search = browse();
+        for c in ['/', 'b'] {
+            press(&mut browse_search, c);
+        }
+        apps.push((Context::Search, browse_search));
+        let mut browse_add = browse();
+        app_key(&mut browse_add, KeyCode::Enter);
+        apps.push((Context::Form, browse_add));
+
+        let mut picker = app();
+        picker.available_profiles = vec!["default".to_string(), "staging".to_string()];
+        press(&mut picker, 'P');
+        apps.push((Context::ProfilePicker, picker));
+
+        apps
+    }
+
+    /// The keymap context `handle_key` uses for the app's current state
+    fn context_of(app: &App) -> Context {
+        match &app.popup {
+            Popup::None if app.searching => Context::Search,
+            Popup::None => Context::Main,
+            Popup::Help => Context::Help,
+            Popup::ProfilePicker => Context::ProfilePicker,
+            Popup::SecretDetail(_) => Context::Detail,
+            Popup::ConfirmDelete(_) => Context::ConfirmDelete,
+            Popup::EditSecret(_) => Context::Form,
+            Popup::SetSecret(state) => state.context(),
+            Popup::Browse(state) if state.adding.is_some() => Context::Form,
+            Popup::Browse(state) if state.searching => Context::Search,
+            Popup::Browse(_) => Context::Browse,
+        }
+    }
+
+    fn app_key(app: &mut App, code: KeyCode) {
+        app.handle_key(key(code));
+    }
+
+    /// What a key press can change
+    fn snapshot(app: &App) -> String {
+        format!(
+            "{:?}",
+            (
+                (&app.popup, app.running, app.focus, app.profile_picker_index),
+                app.searching,
+                &app.search_filter,
+                app.secret_index,
+                app.show_values,
+                app.show_groups,
+                app.help_scroll,
+                &app.status_message,
+                &app.error_message,
+                &app.profile,
+            )
+        )
+    }
+
+    #[test]
+    fn test_every_handled_key_maps_to_a_registered_action() {
+        let mut codes = vec![
+            KeyCode::Backspace,
+            KeyCode::Enter,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::Tab,
+            KeyCode::BackTab,
+            KeyCode::Delete,
+            KeyCode::Insert,
+            KeyCode::F(1),
+            KeyCode::Esc,
+        ];
+        codes.extend((' '..='~').map(KeyCode::Char));
+
+        let dir = tempfile::tempdir().unwrap();
+        let before: Vec<_> = apps_in_every_context(dir.path())
+            .iter()
+            .map(|(context, app)| {
+                assert_eq!(context_of(app), *context);
+                (*context, snapshot(app))
+            })
+            .collect();
+        for code in &codes {
+            for modifiers in [KeyModifiers::NONE, KeyModifiers::CONTROL] {
+                let event = KeyEvent::new(*code, modifiers);
+                let pressed = apps_in_every_context(dir.path()).into_iter();
+                for ((context, before), (_, mut pressed)) in before.iter().zip(pressed) {
+                    let context = *context;
+                    pressed.handle_key(event);
+                    if snapshot(&pressed) == *before
+                        || keymap::action_for(context, &event).is_some()
+                    {
+                        continue;
+                    }
+                    // Typed text, and closing popups that close on any key
+                    let typed = context.takes_text() && matches!(code, KeyCode::Char(_));
+                    let closed = context.closes_on_other_keys() && pressed.popup == Popup::None;
+                    assert!(
+                        typed || closed,
+                        "{modifiers:?} {code:?} is handled in {context:?} but has no keybinding"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_suggested_key_uses_the_last_segment() {
+        assert_eq!(suggested_key("prod/db-password"), "DB_PASSWORD");
+        assert_eq!(suggested_key("api.key"), "API_KEY");
+        assert_eq!(suggested_key("projects/p/secrets/token/"), "TOKEN");
+    }
+}
@@ -0,0 +1,1034 @@
+//! Keybinding registry
+//!
+//! Single source of truth for TUI keybindings. Input handling in
+//! [`App::handle_key`](crate::tui::App::handle_key) dispatches through
+//! [`action_for`], and the help popup and footer are rendered from
+//! [`BINDINGS`], so the help text can't drift from what the keys actually do.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a keypress can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    // Main view
+    MoveDown,
+    MoveUp,
+    GoTop,
+    GoBottom,
+    ToggleFocus,
+    StartSearch,
+    ViewDetail,
+    Copy,
+    ToggleValues,
+    ToggleGroups,
+    Edit,
+    Set,
+    Delete,
+    Browse,
+    SwitchProfile,
+    Refresh,
+    ShowHelp,
+    Quit,
+    // Search mode
+    ClearSearch,
+    ApplySearch,
+    // Help popup
+    ScrollDown,
+    ScrollUp,
+    PageDown,
+    PageUp,
+    ScrollTop,
+    ScrollBottom,
+    // Browse popup
+    AddBrowsed,
+    CloseBrowse,
+    // Delete confirmation
+    ConfirmDelete,
+    DeleteRemote,
+    // Forms and pickers
+    Save,
+    Cancel,
+    Select,
+    NextField,
+    PrevField,
+    NextProvider,
+    PrevProvider,
+    // Text fields
+    DeleteBack,
+    DeleteForward,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+}
+
+impl Action {
+    /// Whether the action changes secrets (disabled in read-only mode)
+    pub fn is_mutating(self) -> bool {
+        matches!(
+            self,
+            Action::Edit
+                | Action::Set
+                | Action::Delete
+                | Action::AddBrowsed
+                | Action::ConfirmDelete
+                | Action::DeleteRemote
+                | Action::Save
+        )
+    }
+
+    /// Every action, in declaration order
+    pub const ALL: &'static [Action] = &[
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::GoTop,
+        Action::GoBottom,
+        Action::ToggleFocus,
+        Action::StartSearch,
+        Action::ViewDetail,
+        Action::Copy,
+        Action::ToggleValues,
+        Action::ToggleGroups,
+        Action::Edit,
+        Action::Set,
+        Action::Delete,
+        Action::Browse,
+        Action::SwitchProfile,
+        Action::Refresh,
+        Action::ShowHelp,
+        Action::Quit,
+        Action::ClearSearch,
+        Action::ApplySearch,
+        Action::ScrollDown,
+        Action::ScrollUp,
+        Action::PageDown,
+        Action::PageUp,
+        Action::ScrollTop,
+        Action::ScrollBottom,
+        Action::AddBrowsed,
+        Action::CloseBrowse,
+        Action::ConfirmDelete,
+        Action::DeleteRemote,
+        Action::Save,
+        Action::Cancel,
+        Action::Select,
+        Action::NextField,
+        Action::PrevField,
+        Action::NextProvider,
+        Action::PrevProvider,
+        Action::DeleteBack,
+        Action::DeleteForward,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorHome,
+        Action::CursorEnd,
+    ];
+}
+
+/// Where a binding is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// The main dashboard (no popup, not searching)
+    Main,
+    /// While typing a search filter
+    Search,
+    /// While the help popup is open
+    Help,
+    /// While the browse popup is open (and not typing its filter, which
+    /// uses the [`Context::Search`] bindings)
+    Browse,
+    /// While a secret's details are shown
+    Detail,
+    /// While asking to confirm a delete
+    ConfirmDelete,
+    /// While typing into the edit popup or naming a browsed entry
+    Form,
+    /// On the key or value field of the set popup
+    SetSecret,
+    /// On the provider field of the set popup
+    SetProvider,
+    /// Cursor movement and deletion in the text fields of forms
+    Input,
+    /// While the profile picker is open
+    ProfilePicker,
+}
+
+impl Context {
+    /// The contexts searched for a key, in order: this one, then those whose
+    /// bindings it shares
+    pub fn layers(self) -> &'static [Context] {
+        match self {
+            Context::Form => &[Context::Form, Context::Input],
+            Context::SetSecret => &[Context::SetSecret, Context::Form, Context::Input],
+            Context::SetProvider => &[Context::SetProvider, Context::SetSecret, Context::Form],
+            Context::Main => &[Context::Main],
+            Context::Search => &[Context::Search],
+            Context::Help => &[Context::Help],
+            Context::Browse => &[Context::Browse],
+            Context::Detail => &[Context::Detail],
+            Context::ConfirmDelete => &[Context::ConfirmDelete],
+            Context::Input => &[Context::Input],
+            Context::ProfilePicker => &[Context::ProfilePicker],
+        }
+    }
+
+    /// Whether unbound characters are typed into a text field
+    pub fn takes_text(self) -> bool {
+        matches!(
+            self,
+            Context::Search | Context::Form | Context::SetSecret | Context::Input
+        )
+    }
+
+    /// Whether keys without a binding close the popup
+    pub fn closes_on_other_keys(self) -> bool {
+        matches!(self, Context::Help | Context::Detail)
+    }
+}
+
+/// Heading a binding is listed under in the help popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Navigation,
+    Search,
+    Secrets,
+    General,
+    Help,
+    Browse,
+    Details,
+    Delete,
+    Forms,
+    Profiles,
+}
+
+impl Section {
+    /// Sections in the order they appear in the help popup
+    pub const ALL: &'static [Section] = &[
+        Section::Navigation,
+        Section::Search,
+        Section::Secrets,
+        Section::General,
+        Section::Help,
+        Section::Browse,
+        Section::Details,
+        Section::Delete,
+        Section::Forms,
+        Section::Profiles,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Navigation => "Navigation",
+            Section::Search => "Search & Filter",
+            Section::Secrets => "Secret Actions",
+            Section::General => "General",
+            Section::Help => "Help",
+            Section::Browse => "Browse Provider",
+            Section::Details => "Secret Details",
+            Section::Delete => "Delete Confirmation",
+            Section::Forms => "Edit & Set Secret",
+            Section::Profiles => "Profile Picker",
+        }
+    }
+}
+
+/// A key (or set of equivalent keys) bound to an action
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBinding {
+    pub action: Action,
+    pub context: Context,
+    pub section: Section,
+    /// Key codes that trigger the action
+    pub keys: &'static [KeyCode],
+    /// Modifiers that must be held, and no others. SHIFT is ignored, since
+    /// it's how keys like `G` and `?` are typed.
+    pub modifiers: KeyModifiers,
+    /// How the keys are shown in the help popup
+    pub label: &'static str,
+    pub description: &'static str,
+    /// Short `(key, text)` shown in the footer, if any
+    pub footer: Option<(&'static str, &'static str)>,
+}
+
+impl KeyBinding {
+    fn matches(&self, key: &KeyEvent) -> bool {
+        self.keys.contains(&key.code)
+            && key.modifiers.difference(KeyModifiers::SHIFT)
+                == self.modifiers.difference(KeyModifiers::SHIFT)
+    }
+}
+
+const fn bind(
+    action: Action,
+    context: Context,
+    section: Section,
+    keys: &'static [KeyCode],
+    label: &'static str,
+    description: &'static str,
+) -> KeyBinding {
+    KeyBinding {
+        action,
+        context,
+        section,
+        keys,
+        modifiers: KeyModifiers::NONE,
+        label,
+        description,
+        footer: None,
+    }
+}
+
+const fn with_footer(mut binding: KeyBinding, key: &'static str, text: &'static str) -> KeyBinding {
+    binding.footer = Some((key, text));
+    binding
+}
+
+const fn with_modifiers(mut binding: KeyBinding, modifiers: KeyModifiers) -> KeyBinding {
+    binding.modifiers = modifiers;
+    binding
+}
+
+/// All keybindings. Within a context the first match wins, so bindings with
+/// modifiers must come before unmodified bindings on the same key.
+pub const BINDINGS: &[KeyBinding] = &[
+    // Navigation
+    with_footer(
+        bind(
+            Action::MoveDown,
+            Context::Main,
+            Section::Navigation,
+            &[KeyCode::Char('j'), KeyCode::Down],
+            "j/↓",
+            "Move down",
+        ),
+        "j/k",
+        "Nav",
+    ),
+    bind(
+        Action::MoveUp,
+        Context::Main,
+        Section::Navigation,
+        &[KeyCode::Char('k'), KeyCode::Up],
+        "k/↑",
+        "Move up",
+    ),
+    bind(
+        Action::GoTop,
+        Context::Main,
+        Section::Navigation,
+        &[KeyCode::Char('g')],
+        "g",
+        "Go to top",
+    ),
+    bind(
+        Action::GoBottom,
+        Context::Main,
+        Section::Navigation,
+        &[KeyCode::Char('G')],
+        "G",
+        "Go to bottom",
+    ),
+    bind(
+        Action::ToggleFocus,
+        Context::Main,
+        Section::Navigation,
+        &[KeyCode::Tab],
+        "Tab",
+        "Switch panel",
+    ),
+    // Search & Filter
+    with_footer(
+        bind(
+            Action::StartSearch,
+            Context::Main,
+            Section::Search,
+            &[KeyCode::Char('/')],
+            "/",
+            "Start search",
+        ),
+        "/",
+        "Search",
+    ),
+    bind(
+        Action::ClearSearch,
+        Context::Search,
+        Section::Search,
+        &[KeyCode::Esc],
+        "Esc",
+        "Clear search",
+    ),
+    bind(
+        Action::ApplySearch,
+        Context::Search,
+        Section::Search,
+        &[KeyCode::Enter],
+        "Enter",
+        "Keep filter and leave search",
+    ),
+    bind(
+        Action::DeleteBack,
+        Context::Search,
+        Section::Search,
+        &[KeyCode::Backspace],
+        "Backspace",
+        "Delete the last character of the filter",
+    ),
+    // Secret Actions
+    bind(
+        Action::ViewDetail,
+        Context::Main,
+        Section::Secrets,
+        &[KeyCode::Enter],
+        "Enter",
+        "View secret details",
+    ),
+    // Ctrl-C quits; must precede the plain `c` copy binding
+    with_modifiers(
+        bind(
+            Action::Quit,
+            Context::Main,
+            Section::General,
+            &[KeyCode::Char('c')],
+            "Ctrl-c",
+            "Quit",
+        ),
+        KeyModifiers::CONTROL,
+    ),
+    with_footer(
+        bind(
+            Action::Copy,
+            Context::Main,
+            Section::Secrets,
+            &[KeyCode::Char('c')],
+            "c",
+            "Copy value to clipboard",
+        ),
+        "c",
+        "Copy",
+    ),
+    with_footer(
+        bind(
+            Action::ToggleValues,
+            Context::Main,
+            Section::Secrets,
+            &[KeyCode::Char('V')],
+            "V",
+            "Toggle show/hide values",
+        ),
+        "V",
+        "Show",
+    ),
+    bind(
+        Action::ToggleGroups,
+        Context::Main,
+        Section::Secrets,
+        &[KeyCode::Char('T')],
+        "T",
+        "Toggle grouped names for nested secrets",
+    ),
+    with_footer(
+        bind(
+            Action::Edit,
+            Context::Main,
+            Section::Secrets,
+            &[KeyCode::Char('e')],
+            "e",
+            "Edit secret value",
+        ),
+        "e",
+        "Edit",
+    ),
+    with_footer(
+        bind(
+            Action::Set,
+            Context::Main,
+            Section::Secrets,
+            &[KeyCode::Char('s')],
+            "s",
+            "Set new secret",
+        ),
+        "s",
+        "Set",
+    ),
+    bind(
+        Action::Delete,
+        Context::Main,
+        Section::Secrets,
+        &[KeyCode::Char('d')],
+        "d",
+        "Delete secret",
+    ),
+    // General
+    bind(
+        Action::Browse,
+        Context::Main,
+        Section::General,
+        &[KeyCode::Char('b')],
+        "b",
+        "Browse the selected provider's secrets",
+    ),
+    bind(
+        Action::SwitchProfile,
+        Context::Main,
+        Section::General,
+        &[KeyCode::Char('P')],
+        "P",
+        "Switch profile",
+    ),
+    bind(
+        Action::Refresh,
+        Context::Main,
+        Section::General,
+        &[KeyCode::Char('r')],
+        "r",
+        "Refresh secrets",
+    ),
+    with_footer(
+        bind(
+            Action::ShowHelp,
+            Context::Main,
+            Section::General,
+            &[KeyCode::Char('?')],
+            "?",
+            "Show this help",
+        ),
+        "?",
+        "Help",
+    ),
+    with_footer(
+        bind(
+            Action::Quit,
+            Context::Main,
+            Section::General,
+            &[KeyCode::Char('q'), KeyCode::Esc],
+            "q/Esc",
+            "Quit",
+        ),
+        "q",
+        "Quit",
+    ),
+    // Help popup
+    bind(
+        Action::ScrollDown,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::Char('j'), KeyCode::Down],
+        "j/↓",
+        "Scroll down",
+    ),
+    bind(
+        Action::ScrollUp,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::Char('k'), KeyCode::Up],
+        "k/↑",
+        "Scroll up",
+    ),
+    bind(
+        Action::PageDown,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::PageDown, KeyCode::Char(' ')],
+        "PgDn",
+        "Scroll down a page",
+    ),
+    bind(
+        Action::PageUp,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::PageUp],
+        "PgUp",
+        "Scroll up a page",
+    ),
+    bind(
+        Action::ScrollTop,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::Char('g'), KeyCode::Home],
+        "g",
+        "Scroll to top",
+    ),
+    bind(
+        Action::ScrollBottom,
+        Context::Help,
+        Section::Help,
+        &[KeyCode::Char('G'), KeyCode::End],
+        "G",
+        "Scroll to bottom",
+    ),
+    // Browse popup
+    with_footer(
+        bind(
+            Action::MoveDown,
+            Context::Browse,
+            Section::Browse,
+            &[KeyCode::Char('j'), KeyCode::Down],
+            "j/↓",
+            "Move down",
+        ),
+        "j/k",
+        "Move",
+    ),
+    bind(
+        Action::MoveUp,
+        Context::Browse,
+        Section::Browse,
+        &[KeyCode::Char('k'), KeyCode::Up],
+        "k/↑",
+        "Move up",
+    ),
+    with_footer(
+        bind(
+            Action::PageDown,
+            Context::Browse,
+            Section::Browse,
+            &[KeyCode::PageDown, KeyCode::Char(' ')],
+            "PgDn",
+            "Move down a page",
+        ),
+        "PgUp/PgDn",
+        "Page",
+    ),
+    bind(
+        Action::PageUp,
+        Context::Browse,
+        Section::Browse,
+        &[KeyCode::PageUp],
+        "PgUp",
+        "Move up a page",
+    ),
+    bind(
+        Action::GoTop,
+        Context::Browse,
+        Section::Browse,
+        &[KeyCode::Char('g'), KeyCode::Home],
+        "g",
+        "Go to top",
+    ),
+    bind(
+        Action::GoBottom,
+        Context::Browse,
+        Section::Browse,
+        &[KeyCode::Char('G'), KeyCode::End],
+        "G",
+        "Go to bottom",
+    ),
+    with_footer(
+        bind(
+            Action::StartSearch,
+            Context::Browse,
+            Section::Browse,
+            &[KeyCode::Char('/')],
+            "/",
+            "Filter the listing",
+        ),
+        "/",
+        "Search",
+    ),
+    with_footer(
+        bind(
+            Action::AddBrowsed,
+            Context::Browse,
+            Section::Browse,
+            &[KeyCode::Enter],
+            "Enter",
+            "Add the selected entry as a secret",
+        ),
+        "Enter",
+        "Add to config",
+    ),
+    with_footer(
+        bind(
+            Action::CloseBrowse,
+            Context::Browse,
+            Section::Browse,
+            &[KeyCode::Esc, KeyCode::Char('q')],
+            "Esc/q",
+            "Close (cancels a listing in progress)",
+        ),
+        "Esc",
+        "Close",
+    ),
+    // Secret details
+    with_footer(
+        bind(
+            Action::Copy,
+            Context::Detail,
+            Section::Details,
+            &[KeyCode::Char('c')],
+            "c",
+            "Copy value to clipboard and close",
+        ),
+        "c",
+        "Copy",
+    ),
+    // Delete confirmation
+    with_footer(
+        bind(
+            Action::ConfirmDelete,
+            Context::ConfirmDelete,
+            Section::Delete,
+            &[KeyCode::Char('y'), KeyCode::Char('Y')],
+            "y",
+            "Remove the secret from its config file",
+        ),
+        "y",
+        "Delete",
+    ),
+    with_footer(
+        bind(
+            Action::DeleteRemote,
+            Context::ConfirmDelete,
+            Section::Delete,
+            &[KeyCode::Char('r'), KeyCode::Char('R')],
+            "r",
+            "Also delete it from the provider",
+        ),
+        "r",
+        "Delete from provider too",
+    ),
+    with_footer(
+        bind(
+            Action::Cancel,
+            Context::ConfirmDelete,
+            Section::Delete,
+            &[KeyCode::Char('n'), KeyCode::Char('N'), KeyCode::Esc],
+            "n/Esc",
+            "Keep the secret",
+        ),
+        "n/Esc",
+        "Cancel",
+    ),
+    // Edit & set popups
+    with_footer(
+        bind(
+            Action::PrevProvider,
+            Context::SetProvider,
+            Section::Forms,
+            &[KeyCode::Left, KeyCode::Up],
+            "←/↑",
+            "Previous provider (on the provider field)",
+        ),
+        "←/→",
+        "Provider",
+    ),
+    bind(
+        Action::NextProvider,
+        Context::SetProvider,
+        Section::Forms,
+        &[KeyCode::Right, KeyCode::Down],
+        "→/↓",
+        "Next provider (on the provider field)",
+    ),
+    with_footer(
+        bind(
+            Action::NextField,
+            Context::SetSecret,
+            Section::Forms,
+            &[KeyCode::Tab],
+            "Tab",
+            "Next field",
+        ),
+        "Tab",
+        "Switch field",
+    ),
+    bind(
+        Action::PrevField,
+        Context::SetSecret,
+        Section::Forms,
+        &[KeyCode::BackTab],
+        "Shift-Tab",
+        "Previous field",
+    ),
+    with_footer(
+        bind(
+            Action::Save,
+            Context::Form,
+            Section::Forms,
+            &[KeyCode::Enter],
+            "Enter",
+            "Save the secret",
+        ),
+        "Enter",
+        "Save",
+    ),
+    with_footer(
+        bind(
+            Action::Cancel,
+            Context::Form,
+            Section::Forms,
+            &[KeyCode::Esc],
+            "Esc",
+            "Cancel",
+        ),
+        "Esc",
+        "Cancel",
+    ),
+    bind(
+        Action::DeleteBack,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::Backspace],
+        "Backspace",
+        "Delete the character before the cursor",
+    ),
+    bind(
+        Action::DeleteForward,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::Delete],
+        "Del",
+        "Delete the character under the cursor",
+    ),
+    bind(
+        Action::CursorLeft,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::Left],
+        "←",
+        "Move the cursor left",
+    ),
+    bind(
+        Action::CursorRight,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::Right],
+        "→",
+        "Move the cursor right",
+    ),
+    bind(
+        Action::CursorHome,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::Home],
+        "Home",
+        "Move the cursor to the start",
+    ),
+    bind(
+        Action::CursorEnd,
+        Context::Input,
+        Section::Forms,
+        &[KeyCode::End],
+        "End",
+        "Move the cursor to the end",
+    ),
+    // Profile picker
+    bind(
+        Action::MoveDown,
+        Context::ProfilePicker,
+        Section::Profiles,
+        &[KeyCode::Char('j'), KeyCode::Down],
+        "j/↓",
+        "Move down",
+    ),
+    bind(
+        Action::MoveUp,
+        Context::ProfilePicker,
+        Section::Profiles,
+        &[KeyCode::Char('k'), KeyCode::Up],
+        "k/↑",
+        "Move up",
+    ),
+    with_footer(
+        bind(
+            Action::Select,
+            Context::ProfilePicker,
+            Section::Profiles,
+            &[KeyCode::Enter],
+            "Enter",
+            "Switch to the selected profile",
+        ),
+        "Enter",
+        "Switch",
+    ),
+    with_footer(
+        bind(
+            Action::Cancel,
+            Context::ProfilePicker,
+            Section::Profiles,
+            &[KeyCode::Esc, KeyCode::Char('q')],
+            "Esc/q",
+            "Close",
+        ),
+        "Esc",
+        "Close",
+    ),
+];
+
+/// Look up the action bound to a key in the given context, or in the
+/// contexts it shares bindings with
+pub fn action_for(context: Context, key: &KeyEvent) -> Option<Action> {
+    context.layers().iter().find_map(|layer| {
+        BINDINGS
+            .iter()
+            .filter(|binding| binding.context == *layer)
+            .find(|binding| binding.matches(key))
+            .map(|binding| binding.action)
+    })
+}
+
+/// Bindings shown in the footer, in display order
+pub fn footer_bindings() -> impl Iterator<Item = &'static KeyBinding> {
+    let footer_order = [
+        Action::Quit,
+        Action::MoveDown,
+        Action::ToggleValues,
+        Action::Copy,
+        Action::Edit,
+        Action::Set,
+        Action::StartSearch,
+        Action::ShowHelp,
+    ];
+    footer_order.into_iter().filter_map(|action| {
+        BINDINGS.iter().find(|binding| {
+            binding.context == Context::Main && binding.action == action && binding.footer.is_some()
+        })
+    })
+}
+
+/// Bindings with a footer hint in a popup's context and the contexts it
+/// shares bindings with, in declaration order
+pub fn popup_footer_bindings(context: Context) -> impl Iterator<Item = &'static KeyBinding> {
+    context.layers().iter().flat_map(|layer| {
+        BINDINGS
+            .iter()
+            .filter(move |binding| binding.context == *layer && binding.footer.is_some())
+    })
+}
+
+/// Bindings listed under a help section
+pub fn section_bindings(section: Section) -> impl Iterator<Item = &'static KeyBinding> {
+    BINDINGS
+        .iter()
+        .filter(move |binding| binding.section == section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_every_action_is_bound_with_description() {
+        for action in Action::ALL {
+            let bindings: Vec<_> = BINDINGS.iter().filter(|b| b.action == *action).collect();
+            assert!(!bindings.is_empty(), "{action:?} has no keybinding");
+            for binding in bindings {
+                assert!(
+                    !binding.description.is_empty(),
+                    "{action:?} has an empty description"
+                );
+                assert!(!binding.label.is_empty(), "{action:?} has an empty label");
+            }
+        }
+    }
+
+    #[test]
+    fn test_forms_share_text_field_bindings() {
+        assert_eq!(
+            action_for(Context::Form, &key(KeyCode::Home)),
+            Some(Action::CursorHome)
+        );
+        assert_eq!(
+            action_for(Context::SetSecret, &key(KeyCode::Left)),
+            Some(Action::CursorLeft)
+        );
+        assert_eq!(
+            action_for(Context::SetSecret, &key(KeyCode::Esc)),
+            Some(Action::Cancel)
+        );
+        // The provider field cycles with the arrows and takes no text
+        assert_eq!(
+            action_for(Context::SetProvider, &key(KeyCode::Left)),
+            Some(Action::PrevProvider)
+        );
+        assert_eq!(
+            action_for(Context::SetProvider, &key(KeyCode::Tab)),
+            Some(Action::NextField)
+        );
+        assert_eq!(
+            action_for(Context::SetProvider, &key(KeyCode::Backspace)),
+            None
+        );
+        let hints: Vec<_> = popup_footer_bindings(Context::SetProvider)
+            .filter_map(|binding| binding.footer)
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(hints, ["←/→", "Tab", "Enter", "Esc"]);
+    }
+
+    #[test]
+    fn test_every_section_is_listed() {
+        for binding in BINDINGS {
+            assert!(Section::ALL.contains(&binding.section));
+        }
+    }
+
+    #[test]
+    fn test_ctrl_c_quits_but_c_copies() {
+        assert_eq!(
+            action_for(
+                Context::Main,
+                &KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            action_for(Context::Main, &key(KeyCode::Char('c'))),
+            Some(Action::Copy)
+        );
+    }
+
+    #[test]
+    fn test_other_modifiers_do_not_trigger_plain_bindings() {
+        for modifiers in [KeyModifiers::CONTROL, KeyModifiers::ALT] {
+            assert_eq!(
+                action_for(Context::Main, &KeyEvent::new(KeyCode::Char('j'), modifiers)),
+                None
+            );
+        }
+        assert_eq!(
+            action_for(
+                Context::Main,
+                &KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)
+            ),
+            Some(Action::ShowHelp)
+        );
+    }
+
+    #[test]
+    fn test_context_scopes_bindings() {
+        assert_eq!(
+            action_for(Context::Main, &key(KeyCode::Esc)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            action_for(Context::Search, &key(KeyCode::Esc)),
+            Some(Action::ClearSearch)
+        );
+        assert_eq!(action_for(Context::Search, &key(KeyCode::Char('q'))), None);
+        assert_eq!(
+            action_for(Context::Help, &key(KeyCode::PageDown)),
+            Some(Action::PageDown)
+        );
+        assert_eq!(
+            action_for(Context::Browse, &key(KeyCode::Enter)),
+            Some(Action::AddBrowsed)
+        );
+        assert_eq!(
+            action_for(Context::Browse, &key(KeyCode::Char('q'))),
+            Some(Action::CloseBrowse)
+        );
+        assert_eq!(action_for(Context::Browse, &key(KeyCode::Char('e'))), None);
+    }
+
+    #[test]
+    fn test_footer_bindings_have_labels() {
+        let footer: Vec<_> = footer_bindings().collect();
+        assert_eq!(footer.len(), 8);
+        assert!(footer.iter().all(|binding| binding.footer.is_some()));
+    }
+}
@@ -4,6 +4,8 @@
 
 mod app;
 mod event;
+pub mod keymap;
+mod store;
 pub mod terminal;
 pub mod ui;
 
@@ -0,0 +1,325 @@
+//! Writes from the TUI to config files and providers
+//!
+//! These mirror `fnox set`, `fnox edit` and `fnox remove`, and run as
+//! background tasks that report back through the event channel.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, Config, SecretConfig};
+use crate::error::{FnoxError, Result};
+use crate::providers::{Provider, ProviderCapability, get_provider_resolved};
+
+/// The provider `key` is stored with: its own, or the profile's default
+pub fn current_provider(config: &Config, profile: &str, key: &str) -> Result<Option<String>> {
+    match config
+        .get_secret(profile, key)
+        .and_then(SecretConfig::provider)
+    {
+        Some(provider) => Ok(Some(provider.to_string())),
+        None => config.get_default_provider(profile),
+    }
+}
+
+/// The provider `provider_name`, failing for read-only providers, as in
+/// `fnox edit`. `action` names the change in that error.
+async fn writable_provider(
+    config: &Config,
+    profile: &str,
+    key: &str,
+    provider_name: Option<&str>,
+    action: &str,
+) -> Result<Option<(String, Box<dyn Provider>)>> {
+    let Some(provider_name) = provider_name else {
+        return Ok(None);
+    };
+    let Some(provider_config) = config.get_providers(profile).get(provider_name).cloned() else {
+        return Ok(None);
+    };
+
+    let provider = get_provider_resolved(config, profile, provider_name, &provider_config).await?;
+    let capabilities = provider.capabilities();
+    if capabilities.contains(&ProviderCapability::RemoteRead)
+        && !capabilities.contains(&ProviderCapability::Encryption)
+        && !capabilities.contains(&ProviderCapability::RemoteStorage)
+    {
+        return Err(FnoxError::Config(format!(
+            "Cannot {} read-only secret '{}' from provider '{}'",
+            action, key, provider_name
+        )));
+    }
+    Ok(Some((provider_name.to_string(), provider)))
+}
+
+/// Store `value` for `key` with `provider` and write the secret to the
+/// config file that defines it, or to the local config file for a new
+/// secret. The value goes through the provider (encrypted, or stored
+/// remotely) like `fnox set`; without one it's written as a plain value.
+/// Returns the secret as written.
+pub async fn save_secret(
+    config: &Config,
+    profile: &str,
+    config_path: &Path,
+    key: &str,
+    value: &str,
+    provider: Option<&str>,
+) -> Result<SecretConfig> {
+    let existing = config.get_secret(profile, key);
+    let mut secret = existing.cloned().unwrap_or_default();
+    let current = match existing {
+        Some(_) => current_provider(config, profile, key)?,
+        None => None,
+    };
+
+    match writable_provider(config, profile, key, provider, "modify").await? {
+        Some((provider_name, provider)) => {
+            let unchanged = current.as_deref() == Some(provider_name.as_str());
+            // Remote secrets are updated where they already live
+            let target = match existing.and_then(SecretConfig::value) {
+                Some(reference)
+                    if unchanged
+                        && !provider
+                            .capabilities()
+                            .contains(&ProviderCapability::Encryption) =>
+                {
+                    reference.to_string()
+                }
+                _ => key.to_string(),
+            };
+            crate::providers::check_value_size(provider.as_ref(), key, value)?;
+            let stored = provider.put_secret(&target, value).await?;
+            if !unchanged {
+                secret.set_provider(Some(provider_name));
+            }
+            secret.set_value(Some(stored));
+        }
+        None => {
+            secret.set_provider(None);
+            secret.set_value(Some(value.to_string()));
+            if existing.is_none() {
+                secret.default = Some(value.to_string());
+            }
+        }
+    }
+
+    let (target, section) = match existing.and_then(|s| s.source_path.clone()) {
+        Some(source) if existing.is_some_and(|s| s.source_is_profile) => (source, profile),
+        Some(source) => (source, "default"),
+        None => (local_config_path(config_path, profile)?, profile),
+    };
+    config.save_secret_to_source(key, &secret, section, &target)?;
+
+    secret.source_path = Some(target);
+    secret.source_is_profile = section != "default";
+    Ok(secret)
+}
+
+/// Remove `key` from the config file that defines it, preserving the file's
+/// formatting, and from its provider's remote storage too if `delete_remote`
+/// is set. Secrets of read-only providers can't be deleted.
+pub async fn delete_secret(
+    config: &Config,
+    profile: &str,
+    key: &str,
+    delete_remote: bool,
+) -> Result<()> {
+    let not_found = || FnoxError::SecretNotFound {
+        key: key.to_string(),
+        profile: profile.to_string(),
+        config_path: None,
+        suggestion: None,
+    };
+    let secret = config.get_secret(profile, key).ok_or_else(not_found)?;
+    let source = secret.source_path.clone().ok_or_else(not_found)?;
+
+    // Without providers there's nothing to delete remotely, and no default
+    // provider to look up
+    let provider_name = if config.get_providers(profile).is_empty() {
+        None
+    } else {
+        current_provider(config, profile, key)?
+    };
+    if let Some((_, provider)) =
+        writable_provider(config, profile, key, provider_name.as_deref(), "delete").await?
+    {
+        // Delete the remote value first, so a failure leaves the config intact
+        if delete_remote
+            && provider
+                .capabilities()
+                .contains(&ProviderCapability::RemoteStorage)
+            && let Some(value) = secret.value()
+        {
+            provider.delete_secret(value).await?;
+        }
+    }
+
+    let section = if secret.source_is_profile {
+        profile
+    } else {
+        "default"
+    };
+    if !Config::remove_secret_from_source(key, section, &source)? {
+        return Err(not_found());
+    }
+    Ok(())
+}
+
+/// References to the secrets stored with `provider_name`, for browsing
+pub async fn list_provider_secrets(
+    config: &Config,
+    profile: &str,
+    provider_name: &str,
+) -> Result<Vec<String>> {
+    let providers = config.get_providers(profile);
+    let provider_config =
+        providers
+            .get(provider_name)
+            .ok_or_else(|| FnoxError::ProviderNotConfigured {
+                provider: provider_name.to_string(),
+                profile: profile.to_string(),
+                config_path: config.provider_sources.get(provider_name).cloned(),
+                suggestion: None,
+            })?;
+    let provider = get_provider_resolved(config, profile, provider_name, provider_config).await?;
+    provider.list_secrets(None).await
+}
+
+/// Add `key` to the local config file as a reference to `reference` in
+/// `provider_name`, leaving the provider untouched. Returns the secret as
+/// written.
+pub fn add_reference(
+    config: &Config,
+    profile: &str,
+    config_path: &Path,
+    key: &str,
+    provider_name: &str,
+    reference: &str,
+) -> Result<SecretConfig> {
+    let mut secret = SecretConfig::new();
+    secret.set_provider(Some(provider_name.to_string()));
+    secret.set_value(Some(reference.to_string()));
+
+    let target = local_config_path(config_path, profile)?;
+    config.save_secret_to_source(key, &secret, profile, &target)?;
+
+    secret.source_path = Some(target);
+    secret.source_is_profile = profile != "default";
+    Ok(secret)
+}
+
+/// The config file new secrets are written to, as in `fnox set`
+fn local_config_path(config_path: &Path, profile: &str) -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()
+        .map_err(|e| FnoxError::Config(format!("Failed to get current directory: {}", e)))?;
+    // Only use auto-detection when --config is the default ("fnox.toml")
+    if config_path == Path::new(config::DEFAULT_CONFIG_FILENAME) {
+        Ok(config::find_local_config(&current_dir, Some(profile)))
+    } else {
+        Ok(current_dir.join(config_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+
+    /// A config file in a temp dir, loaded
+    fn config(content: &str) -> (tempfile::TempDir, PathBuf, Config) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fnox.toml");
+        std::fs::write(&path, content).unwrap();
+        let config = Config::load(&path).unwrap();
+        (dir, path, config)
+    }
+
+    #[tokio::test]
+    async fn save_encrypts_with_the_secrets_provider_and_writes_the_ciphertext() {
+        let (_dir, path, config) = config(&format!(
+            "[providers.age]\n\
+             type = \"age\"\n\
+             recipients = [\"{RECIPIENT}\"]\n\
+             \n\
+             [secrets]\n\
+             # the API key\n\
+             API_KEY = {{ provider = \"age\", value = \"old\" }}\n"
+        ));
+
+        let secret = save_secret(&config, "default", &path, "API_KEY", "s3cret", Some("age"))
+            .await
+            .unwrap();
+
+        let ciphertext = secret.value().unwrap();
+        assert_ne!(ciphertext, "s3cret");
+        let stanzas = crate::providers::age::ciphertext_stanzas(ciphertext).unwrap();
+        assert_eq!(stanzas.len(), 1);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("# the API key"), "{written}");
+        let reloaded = Config::load(&path).unwrap();
+        assert_eq!(reloaded.secrets["API_KEY"].value(), Some(ciphertext));
+        assert_eq!(reloaded.secrets["API_KEY"].provider(), Some("age"));
+    }
+
+    #[tokio::test]
+    async fn save_rejects_secrets_of_read_only_providers() {
+        let (_dir, path, config) = config(
+            "[providers.op]\n\
+             type = \"1password\"\n\
+             \n\
+             [secrets]\n\
+             DB_PASSWORD = { provider = \"op\", value = \"op://Private/db/password\" }\n",
+        );
+        let before = std::fs::read_to_string(&path).unwrap();
+
+        let err = save_secret(&config, "default", &path, "DB_PASSWORD", "new", Some("op"))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Cannot modify read-only secret 'DB_PASSWORD' from provider 'op'"),
+            "{err}"
+        );
+        let err = delete_secret(&config, "default", "DB_PASSWORD", false)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Cannot delete read-only secret"),
+            "{err}"
+        );
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[tokio::test]
+    async fn save_stores_new_secrets_with_the_chosen_provider() {
+        let (_dir, path, config) = config(&format!(
+            "[providers.age]\n\
+             type = \"age\"\n\
+             recipients = [\"{RECIPIENT}\"]\n\
+             \n\
+             [providers.backup]\n\
+             type = \"age\"\n\
+             recipients = [\"{RECIPIENT}\"]\n"
+        ));
+
+        let secret = save_secret(&config, "default", &path, "TOKEN", "t0ken", Some("backup"))
+            .await
+            .unwrap();
+        assert_eq!(secret.provider(), Some("backup"));
+        assert_ne!(secret.value(), Some("t0ken"));
+
+        let secret = save_secret(&config, "default", &path, "PLAIN", "visible", None)
+            .await
+            .unwrap();
+        assert_eq!(secret.provider(), None);
+        assert_eq!(secret.default.as_deref(), Some("visible"));
+
+        let reloaded = Config::load(&path).unwrap();
+        assert_eq!(reloaded.secrets["TOKEN"].provider(), Some("backup"));
+        assert_eq!(
+            reloaded.secrets["PLAIN"].default.as_deref(),
+            Some("visible")
+        );
+    }
+}
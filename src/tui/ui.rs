@@ -1,14 +1,20 @@
 //! UI rendering
 
+use console::{Alignment, pad_str, truncate_str};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Wrap,
+    },
 };
 
-use crate::tui::app::{App, EditState, Focus, Popup, SetField, SetState};
+use crate::sanitize::escape_controls;
+use crate::tui::app::{App, BrowseStatus, EditState, Focus, Popup, SetField, SetState};
+use crate::tui::keymap::{self, Action};
 
 /// Color palette that respects --no-color flag
 struct Colors;
@@ -86,12 +92,13 @@ pub fn render(app: &mut App, frame: &mut Frame) {
 
     // Render popups
     match &app.popup {
-        Popup::Help => render_help_popup(frame),
+        Popup::Help => render_help_popup(app, frame),
         Popup::ProfilePicker => render_profile_picker(app, frame),
         Popup::SecretDetail(key) => render_secret_detail(app, frame, key),
         Popup::ConfirmDelete(key) => render_confirm_delete(frame, key),
         Popup::EditSecret(state) => render_edit_secret(frame, state),
         Popup::SetSecret(state) => render_set_secret(frame, state),
+        Popup::Browse(_) => render_browse(app, frame),
         Popup::None => {}
     }
 
@@ -102,7 +109,12 @@ pub fn render(app: &mut App, frame: &mut Frame) {
 }
 
 fn render_header(app: &App, frame: &mut Frame, area: Rect) {
-    let title = format!(" fnox - Secrets Dashboard │ Profile: {} ", app.profile);
+    let mode = if app.read_only { " │ read-only" } else { "" };
+    let title = format!(
+        " fnox - Secrets Dashboard │ Profile: {}{} ",
+        escape_controls(&app.profile),
+        mode
+    );
 
     let header = Paragraph::new(title)
         .style(
@@ -135,7 +147,7 @@ fn render_providers(app: &mut App, frame: &mut Frame, area: Rect) {
     let items: Vec<ListItem> = app
         .providers
         .iter()
-        .map(|name| ListItem::new(name.as_str()))
+        .map(|name| ListItem::new(escape_controls(name).into_owned()))
         .collect();
 
     let border_style = if is_focused {
@@ -184,14 +196,13 @@ fn render_secrets(app: &mut App, frame: &mut Frame, area: Rect) {
             // Get value status
             let value_status = if app.loading_secrets.contains(*key) || app.initial_loading {
                 Span::styled("loading...", Style::default().fg(Colors::yellow()))
+            } else if app.failed_secrets.contains_key(*key) {
+                Span::styled("✗ error", Style::default().fg(Colors::red()))
             } else if let Some(Some(value)) = app.resolved_values.get(*key) {
                 if app.show_values {
-                    // Truncate long values for display (UTF-8 safe)
-                    let display_val: String = if value.chars().count() > 40 {
-                        format!("{}...", value.chars().take(37).collect::<String>())
-                    } else {
-                        value.clone()
-                    };
+                    // Truncate long values to 40 terminal columns
+                    let value = escape_controls(value);
+                    let display_val = truncate_str(&value, 40, "...").into_owned();
                     Span::styled(display_val, Style::default().fg(Colors::green()))
                 } else {
                     Span::styled("******", Style::default().fg(Colors::green()))
@@ -200,12 +211,20 @@ fn render_secrets(app: &mut App, frame: &mut Frame, area: Rect) {
                 Span::styled("<not set>", Style::default().fg(Colors::red()))
             };
 
+            // Secrets from nested groups can be shown by their path in the config
+            let name = if app.show_groups && !secret_config.source_group.is_empty() {
+                secret_config.source_group.join(".")
+            } else {
+                key.to_string()
+            };
+
+            // Pad by terminal columns so wide characters keep the columns aligned
+            let pad = |text: &str, width| {
+                pad_str(&escape_controls(text), width, Alignment::Left, None).into_owned()
+            };
             let line = Line::from(vec![
-                Span::raw(format!("{:<30}", key)),
-                Span::styled(
-                    format!("{:<15}", provider),
-                    Style::default().fg(Colors::cyan()),
-                ),
+                Span::raw(pad(&name, 30)),
+                Span::styled(pad(provider, 15), Style::default().fg(Colors::cyan())),
                 value_status,
             ]);
 
@@ -219,10 +238,11 @@ fn render_secrets(app: &mut App, frame: &mut Frame, area: Rect) {
         Style::default().fg(Colors::dark_gray())
     };
 
+    let search_filter = escape_controls(&app.search_filter);
     let title = if app.searching {
-        format!(" Secrets (/{}) ", app.search_filter)
+        format!(" Secrets (/{}) ", search_filter)
     } else if !app.search_filter.is_empty() {
-        format!(" Secrets [filtered: {}] ", app.search_filter)
+        format!(" Secrets [filtered: {}] ", search_filter)
     } else {
         " Secrets ".to_string()
     };
@@ -290,27 +310,27 @@ fn render_status(app: &App, frame: &mut Frame, area: Rect) {
 }
 
 fn render_keybindings(app: &App, frame: &mut Frame, area: Rect) {
-    let show_hide = if app.show_values { "Hide" } else { "Show" };
-    let bindings = Line::from(vec![
-        Span::styled(" q", Style::default().fg(Colors::yellow())),
-        Span::raw(" Quit  "),
-        Span::styled("j/k", Style::default().fg(Colors::yellow())),
-        Span::raw(" Nav  "),
-        Span::styled("V", Style::default().fg(Colors::yellow())),
-        Span::raw(format!(" {}  ", show_hide)),
-        Span::styled("c", Style::default().fg(Colors::yellow())),
-        Span::raw(" Copy  "),
-        Span::styled("e", Style::default().fg(Colors::yellow())),
-        Span::raw(" Edit  "),
-        Span::styled("s", Style::default().fg(Colors::yellow())),
-        Span::raw(" Set  "),
-        Span::styled("/", Style::default().fg(Colors::yellow())),
-        Span::raw(" Search  "),
-        Span::styled("?", Style::default().fg(Colors::yellow())),
-        Span::raw(" Help"),
-    ]);
+    let mut spans = vec![Span::raw(" ")];
+    let bindings = keymap::footer_bindings()
+        .filter(|binding| !(app.read_only && binding.action.is_mutating()));
+    for (i, binding) in bindings.enumerate() {
+        let Some((key, text)) = binding.footer else {
+            continue;
+        };
+        let text = if binding.action == Action::ToggleValues && app.show_values {
+            "Hide"
+        } else {
+            text
+        };
+        if i > 0 {
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::styled(key, Style::default().fg(Colors::yellow())));
+        spans.push(Span::raw(format!(" {}", text)));
+    }
 
-    let keybindings = Paragraph::new(bindings).style(Style::default().fg(Colors::dark_gray()));
+    let keybindings =
+        Paragraph::new(Line::from(spans)).style(Style::default().fg(Colors::dark_gray()));
 
     frame.render_widget(keybindings, area);
 }
@@ -331,121 +351,87 @@ fn render_error_popup(frame: &mut Frame, error: &str) {
     frame.render_widget(error_block, area);
 }
 
-fn render_help_popup(frame: &mut Frame) {
-    let area = centered_rect(50, 80, frame.area());
-
-    let help_text = vec![
-        Line::from(vec![Span::styled(
-            "Navigation",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Colors::cyan()),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  j/↓  ", Style::default().fg(Colors::yellow())),
-            Span::raw("Move down"),
-        ]),
-        Line::from(vec![
-            Span::styled("  k/↑  ", Style::default().fg(Colors::yellow())),
-            Span::raw("Move up"),
-        ]),
-        Line::from(vec![
-            Span::styled("  g    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Go to top"),
-        ]),
-        Line::from(vec![
-            Span::styled("  G    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Go to bottom"),
-        ]),
-        Line::from(vec![
-            Span::styled("  Tab  ", Style::default().fg(Colors::yellow())),
-            Span::raw("Switch panel"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Search & Filter",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Colors::cyan()),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  /    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Start search"),
-        ]),
-        Line::from(vec![
-            Span::styled("  Esc  ", Style::default().fg(Colors::yellow())),
-            Span::raw("Clear search"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Secret Actions",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Colors::cyan()),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  Enter", Style::default().fg(Colors::yellow())),
-            Span::raw(" View secret details"),
-        ]),
-        Line::from(vec![
-            Span::styled("  c    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Copy value to clipboard"),
-        ]),
-        Line::from(vec![
-            Span::styled("  V    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Toggle show/hide values"),
-        ]),
-        Line::from(vec![
-            Span::styled("  e    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Edit secret value"),
-        ]),
-        Line::from(vec![
-            Span::styled("  s    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Set new secret"),
-        ]),
-        Line::from(vec![
-            Span::styled("  d    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Delete secret"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "General",
+/// Build the help popup contents from the keybinding registry
+fn help_lines(read_only: bool) -> Vec<Line<'static>> {
+    let label_width = keymap::BINDINGS
+        .iter()
+        .map(|binding| binding.label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for section in keymap::Section::ALL {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(vec![Span::styled(
+            section.title(),
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Colors::cyan()),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  P    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Switch profile"),
-        ]),
-        Line::from(vec![
-            Span::styled("  r    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Refresh secrets"),
-        ]),
-        Line::from(vec![
-            Span::styled("  q    ", Style::default().fg(Colors::yellow())),
-            Span::raw("Quit"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "       Press any key to close",
-            Style::default().fg(Colors::dark_gray()),
-        )]),
-    ];
+        )]));
+        lines.push(Line::from(""));
+        for binding in keymap::section_bindings(*section) {
+            if read_only && binding.action.is_mutating() {
+                continue;
+            }
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<label_width$}  ", binding.label),
+                    Style::default().fg(Colors::yellow()),
+                ),
+                Span::raw(binding.description),
+            ]));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![Span::styled(
+        "  Press any other key to close",
+        Style::default().fg(Colors::dark_gray()),
+    )]));
+    lines
+}
 
-    let help_block = Paragraph::new(help_text).block(
-        Block::default()
-            .title(" Help ")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Colors::cyan())),
-    );
+fn render_help_popup(app: &mut App, frame: &mut Frame) {
+    let area = centered_rect(50, 80, frame.area());
+    let lines = help_lines(app.read_only);
+
+    // Clamp scrolling to the content and remember the page size for PgUp/PgDn
+    let viewport = area.height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(viewport);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+    app.help_viewport_height = viewport;
+
+    let title = if max_scroll > 0 {
+        " Help (j/k to scroll) "
+    } else {
+        " Help "
+    };
+    let help_block = Paragraph::new(lines)
+        .scroll((app.help_scroll as u16, 0))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Colors::cyan())),
+        );
 
     frame.render_widget(Clear, area);
     frame.render_widget(help_block, area);
+
+    if max_scroll > 0 {
+        let mut scrollbar_state = ScrollbarState::new(max_scroll)
+            .position(app.help_scroll)
+            .viewport_content_length(viewport);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+    }
 }
 
 fn render_profile_picker(app: &App, frame: &mut Frame) {
@@ -463,7 +449,7 @@ fn render_profile_picker(app: &App, frame: &mut Frame) {
             } else {
                 Style::default()
             };
-            ListItem::new(format!("  {}", name)).style(style)
+            ListItem::new(format!("  {}", escape_controls(name))).style(style)
         })
         .collect();
 
@@ -471,6 +457,10 @@ fn render_profile_picker(app: &App, frame: &mut Frame) {
         .block(
             Block::default()
                 .title(" Select Profile ")
+                .title_bottom(Line::from(popup_hints(
+                    keymap::Context::ProfilePicker,
+                    false,
+                )))
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Colors::cyan())),
         )
@@ -497,7 +487,10 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
     let mut lines = vec![
         Line::from(vec![
             Span::styled("Key: ", Style::default().fg(Colors::cyan())),
-            Span::styled(secret_key, Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                escape_controls(secret_key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
         ]),
         Line::from(""),
     ];
@@ -507,7 +500,7 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
         if let Some(provider) = config.provider() {
             lines.push(Line::from(vec![
                 Span::styled("Provider: ", Style::default().fg(Colors::cyan())),
-                Span::raw(provider),
+                Span::raw(escape_controls(provider)),
             ]));
         }
 
@@ -515,7 +508,7 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
         if let Some(value) = config.value() {
             lines.push(Line::from(vec![
                 Span::styled("Provider Key: ", Style::default().fg(Colors::cyan())),
-                Span::raw(value),
+                Span::raw(escape_controls(value)),
             ]));
         }
 
@@ -523,7 +516,7 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
         if let Some(ref desc) = config.description {
             lines.push(Line::from(vec![
                 Span::styled("Description: ", Style::default().fg(Colors::cyan())),
-                Span::raw(desc.as_str()),
+                Span::raw(escape_controls(desc)),
             ]));
         }
 
@@ -531,7 +524,7 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
         if let Some(ref default) = config.default {
             lines.push(Line::from(vec![
                 Span::styled("Default: ", Style::default().fg(Colors::cyan())),
-                Span::raw(default.as_str()),
+                Span::raw(escape_controls(default)),
             ]));
         }
 
@@ -547,7 +540,7 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
         if let Some(ref path) = config.source_path {
             lines.push(Line::from(vec![
                 Span::styled("Source: ", Style::default().fg(Colors::cyan())),
-                Span::raw(path.display().to_string()),
+                Span::raw(escape_controls(&path.display().to_string()).into_owned()),
             ]));
         }
     }
@@ -564,11 +557,6 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
                     Style::default().fg(Colors::green()),
                 ),
             ]));
-            lines.push(Line::from(vec![
-                Span::styled("       Press ", Style::default().fg(Colors::dark_gray())),
-                Span::styled("c", Style::default().fg(Colors::yellow())),
-                Span::styled(" to copy value", Style::default().fg(Colors::dark_gray())),
-            ]));
         }
         Some(None) => {
             lines.push(Line::from(vec![
@@ -576,19 +564,31 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
                 Span::styled("<not set>", Style::default().fg(Colors::red())),
             ]));
         }
-        None => {
-            lines.push(Line::from(vec![
-                Span::styled("Value: ", Style::default().fg(Colors::cyan())),
-                Span::styled("<loading...>", Style::default().fg(Colors::yellow())),
-            ]));
-        }
+        None => match app.failed_secrets.get(secret_key) {
+            Some(error) => {
+                lines.push(Line::from(vec![
+                    Span::styled("Value: ", Style::default().fg(Colors::cyan())),
+                    Span::styled("✗ error", Style::default().fg(Colors::red())),
+                ]));
+                lines.push(Line::from(Span::styled(
+                    format!("       {}", error),
+                    Style::default().fg(Colors::red()),
+                )));
+            }
+            None => {
+                lines.push(Line::from(vec![
+                    Span::styled("Value: ", Style::default().fg(Colors::cyan())),
+                    Span::styled("<loading...>", Style::default().fg(Colors::yellow())),
+                ]));
+            }
+        },
     }
 
     lines.push(Line::from(""));
-    lines.push(Line::from(vec![Span::styled(
-        "       Press any key to close",
-        Style::default().fg(Colors::dark_gray()),
-    )]));
+    let mut hints = vec![Span::raw("       ")];
+    hints.extend(popup_hints(keymap::Context::Detail, false));
+    hints.push(Span::raw("  any other key Close"));
+    lines.push(Line::from(hints).style(Style::default().fg(Colors::dark_gray())));
 
     let detail_block = Paragraph::new(lines).block(
         Block::default()
@@ -602,14 +602,14 @@ fn render_secret_detail(app: &App, frame: &mut Frame, secret_key: &str) {
 }
 
 fn render_confirm_delete(frame: &mut Frame, secret_key: &str) {
-    let area = centered_rect(50, 25, frame.area());
+    let area = centered_rect(60, 30, frame.area());
 
     let lines = vec![
         Line::from(""),
         Line::from(vec![
             Span::raw("  Delete secret "),
             Span::styled(
-                secret_key,
+                escape_controls(secret_key),
                 Style::default()
                     .add_modifier(Modifier::BOLD)
                     .fg(Colors::yellow()),
@@ -619,30 +619,12 @@ fn render_confirm_delete(frame: &mut Frame, secret_key: &str) {
         Line::from(""),
         Line::from("  This will remove the secret from your config file."),
         Line::from(""),
-        Line::from(vec![
-            Span::raw("  Press "),
-            Span::styled(
-                "y",
-                Style::default()
-                    .fg(Colors::green())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" to confirm, "),
-            Span::styled(
-                "n",
-                Style::default()
-                    .fg(Colors::red())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" or "),
-            Span::styled(
-                "Esc",
-                Style::default()
-                    .fg(Colors::red())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" to cancel"),
-        ]),
+        Line::from(
+            [Span::raw("  ")]
+                .into_iter()
+                .chain(popup_hints(keymap::Context::ConfirmDelete, false))
+                .collect::<Vec<_>>(),
+        ),
     ];
 
     let confirm_block = Paragraph::new(lines).block(
@@ -679,7 +661,10 @@ fn render_edit_secret(frame: &mut Frame, state: &EditState) {
         Line::from(""),
         Line::from(vec![
             Span::styled("  Key: ", Style::default().fg(Colors::cyan())),
-            Span::styled(&state.key, Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                escape_controls(&state.key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
         ]),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -693,12 +678,12 @@ fn render_edit_secret(frame: &mut Frame, state: &EditState) {
             input_line.spans[2].clone(),
         ]),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("  Enter", Style::default().fg(Colors::yellow())),
-            Span::raw(" Save  "),
-            Span::styled("Esc", Style::default().fg(Colors::yellow())),
-            Span::raw(" Cancel"),
-        ]),
+        Line::from(
+            [Span::raw("  ")]
+                .into_iter()
+                .chain(popup_hints(keymap::Context::Form, false))
+                .collect::<Vec<_>>(),
+        ),
     ];
 
     let edit_block = Paragraph::new(lines).block(
@@ -713,10 +698,11 @@ fn render_edit_secret(frame: &mut Frame, state: &EditState) {
 }
 
 fn render_set_secret(frame: &mut Frame, state: &SetState) {
-    let area = centered_rect(60, 35, frame.area());
+    let area = centered_rect(60, 45, frame.area());
 
     let key_active = state.field == SetField::Key;
     let value_active = state.field == SetField::Value;
+    let provider_active = state.field == SetField::Provider;
 
     let key_style = if key_active {
         Style::default()
@@ -732,6 +718,13 @@ fn render_set_secret(frame: &mut Frame, state: &SetState) {
     } else {
         Style::default().fg(Colors::dark_gray())
     };
+    let provider_style = if provider_active {
+        Style::default()
+            .fg(Colors::cyan())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Colors::dark_gray())
+    };
 
     // Render key input (UTF-8 safe using char indices)
     let key_line = if key_active {
@@ -773,22 +766,44 @@ fn render_set_secret(frame: &mut Frame, state: &SetState) {
         Line::from(vec![Span::raw(format!("  {}", state.value))])
     };
 
+    let provider_name = state.provider.as_deref().unwrap_or("(none: plain value)");
+    let provider_line = if provider_active {
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("◀ ", Style::default().fg(Colors::yellow())),
+            Span::raw(provider_name.to_string()),
+            Span::styled(" ▶", Style::default().fg(Colors::yellow())),
+        ])
+    } else {
+        Line::from(vec![Span::raw(format!("  {}", provider_name))])
+    };
+
+    // Live naming policy feedback under the key
+    let key_hint = match &state.key_error {
+        Some(error) => Line::from(vec![Span::styled(
+            format!("  ✗ Key {}", error),
+            Style::default().fg(Colors::red()),
+        )]),
+        None => Line::from(""),
+    };
+
     let lines = vec![
         Line::from(""),
         Line::from(vec![Span::styled("  Key: ", key_style)]),
         key_line,
-        Line::from(""),
+        key_hint,
         Line::from(vec![Span::styled("  Value: ", value_style)]),
         value_line,
         Line::from(""),
-        Line::from(vec![
-            Span::styled("  Tab", Style::default().fg(Colors::yellow())),
-            Span::raw(" Switch field  "),
-            Span::styled("Enter", Style::default().fg(Colors::yellow())),
-            Span::raw(" Save  "),
-            Span::styled("Esc", Style::default().fg(Colors::yellow())),
-            Span::raw(" Cancel"),
-        ]),
+        Line::from(vec![Span::styled("  Provider: ", provider_style)]),
+        provider_line,
+        Line::from(""),
+        Line::from(
+            [Span::raw("  ")]
+                .into_iter()
+                .chain(popup_hints(state.context(), false))
+                .collect::<Vec<_>>(),
+        ),
     ];
 
     let set_block = Paragraph::new(lines).block(
@@ -802,6 +817,183 @@ fn render_set_secret(frame: &mut Frame, state: &SetState) {
     frame.render_widget(set_block, area);
 }
 
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+fn render_browse(app: &mut App, frame: &mut Frame) {
+    let area = centered_rect(70, 70, frame.area());
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Entries
+            Constraint::Length(4), // Search, add form and hints
+        ])
+        .split(inner);
+
+    // Remember the page size for PgUp/PgDn
+    let page_size = (chunks[0].height as usize).max(1);
+    app.browse_page_size = page_size;
+    let Popup::Browse(state) = &app.popup else {
+        return;
+    };
+
+    let filtered = state.filtered_entries();
+    let page_count = filtered.len().div_ceil(page_size).max(1);
+    let page = state.selected / page_size;
+    let title = if state.status == BrowseStatus::Loaded && page_count > 1 {
+        format!(
+            " Browse {} │ page {}/{} ",
+            state.provider,
+            page + 1,
+            page_count
+        )
+    } else {
+        format!(" Browse {} ", state.provider)
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Colors::cyan())),
+        area,
+    );
+
+    match &state.status {
+        BrowseStatus::Loading => {
+            let spinner = SPINNER[app.spinner_frame % SPINNER.len()];
+            let loading = Paragraph::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", spinner),
+                    Style::default().fg(Colors::yellow()),
+                ),
+                Span::raw(format!("Listing secrets in {}...", state.provider)),
+            ]));
+            frame.render_widget(loading, chunks[0]);
+        }
+        BrowseStatus::Failed(error) => {
+            let failed = Paragraph::new(format!("✗ {}", error))
+                .style(Style::default().fg(Colors::red()))
+                .wrap(Wrap { trim: false });
+            frame.render_widget(failed, chunks[0]);
+        }
+        BrowseStatus::Loaded if filtered.is_empty() => {
+            let message = if state.entries.is_empty() {
+                "No secrets found"
+            } else {
+                "No matching secrets"
+            };
+            let empty = Paragraph::new(message).style(Style::default().fg(Colors::dark_gray()));
+            frame.render_widget(empty, chunks[0]);
+        }
+        BrowseStatus::Loaded => {
+            let start = page * page_size;
+            let items: Vec<ListItem> = filtered
+                .iter()
+                .skip(start)
+                .take(page_size)
+                .map(|entry| ListItem::new(escape_controls(entry)))
+                .collect();
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .bg(Colors::dark_gray()),
+                )
+                .highlight_symbol("> ");
+            let mut list_state = ListState::default();
+            list_state.select(Some(state.selected - start));
+            frame.render_stateful_widget(list, chunks[0], &mut list_state);
+        }
+    }
+
+    let lines = match &state.adding {
+        Some(adding) => {
+            let char_count = adding.key.chars().count();
+            let cursor_pos = adding.cursor.min(char_count);
+            let before: String = adding.key.chars().take(cursor_pos).collect();
+            let cursor_char = adding.key.chars().nth(cursor_pos).unwrap_or(' ');
+            let after_cursor: String = adding.key.chars().skip(cursor_pos + 1).collect();
+            let status = match &adding.error {
+                Some(error) => Line::from(Span::styled(
+                    format!("✗ {}", error),
+                    Style::default().fg(Colors::red()),
+                )),
+                None => Line::from(Span::styled(
+                    format!("References {}", adding.reference),
+                    Style::default().fg(Colors::dark_gray()),
+                )),
+            };
+            vec![
+                Line::from(vec![
+                    Span::styled("Add to config as: ", Style::default().fg(Colors::cyan())),
+                    Span::raw(before),
+                    Span::styled(
+                        cursor_char.to_string(),
+                        Style::default().bg(Colors::white()).fg(Color::Black),
+                    ),
+                    Span::raw(after_cursor),
+                ]),
+                status,
+                Line::from(""),
+                Line::from(popup_hints(keymap::Context::Form, app.read_only)),
+            ]
+        }
+        None => {
+            let search = if state.searching {
+                Line::from(vec![
+                    Span::styled("/", Style::default().fg(Colors::yellow())),
+                    Span::raw(state.filter.as_str()),
+                ])
+            } else if !state.filter.is_empty() {
+                Line::from(Span::styled(
+                    format!("[filtered: {}]", state.filter),
+                    Style::default().fg(Colors::dark_gray()),
+                ))
+            } else {
+                Line::from("")
+            };
+            let count = if state.status == BrowseStatus::Loaded {
+                format!("{} of {} secrets", filtered.len(), state.entries.len())
+            } else {
+                String::new()
+            };
+            vec![
+                search,
+                Line::from(Span::styled(
+                    count,
+                    Style::default().fg(Colors::dark_gray()),
+                )),
+                Line::from(""),
+                Line::from(popup_hints(keymap::Context::Browse, app.read_only)),
+            ]
+        }
+    };
+    frame.render_widget(Paragraph::new(lines), chunks[1]);
+}
+
+/// Key hints for a popup, from the footer labels of its keybindings
+fn popup_hints(context: keymap::Context, read_only: bool) -> Vec<Span<'static>> {
+    let mut hints = vec![];
+    let bindings = keymap::popup_footer_bindings(context)
+        .filter(|binding| !(read_only && binding.action.is_mutating()));
+    for binding in bindings {
+        let Some((key, text)) = binding.footer else {
+            continue;
+        };
+        if !hints.is_empty() {
+            hints.push(Span::raw("  "));
+        }
+        hints.push(Span::styled(key, Style::default().fg(Colors::yellow())));
+        hints.push(Span::raw(format!(" {}", text)));
+    }
+    hints
+}
+
 /// Helper to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -822,3 +1014,89 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, SecretConfig};
+    use crate::daemon::ResolveContext;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    /// Sets the terminal title when printed raw
+    const TITLE_ESCAPE: &str = "\x1b]0;pwned\x07";
+
+    fn app() -> App {
+        let mut config = Config::new();
+        for key in ["API_KEY", "数据库_URL", "CAFE\u{301}", TITLE_ESCAPE] {
+            let mut secret = SecretConfig::new();
+            secret.set_provider(Some("age".to_string()));
+            secret.description = Some(format!("{TITLE_ESCAPE}\x1b[2J"));
+            config.secrets.insert(key.to_string(), secret);
+        }
+        let daemon_context = ResolveContext {
+            config: "fnox.toml".into(),
+            profile: None,
+            age_key_file: None,
+            if_missing: None,
+            no_defaults: false,
+            non_interactive: true,
+            no_daemon: true,
+        };
+        App::new(config, "default".to_string(), daemon_context).unwrap()
+    }
+
+    /// The rendered screen, one symbol per terminal cell
+    fn screen(app: &mut App) -> Vec<Vec<String>> {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| render(app, frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn assert_no_control_characters(screen: &[Vec<String>]) {
+        for row in screen {
+            let text = row.concat();
+            assert!(!text.chars().any(char::is_control), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_secret_list_escapes_keys_and_keeps_columns_aligned() {
+        let screen = screen(&mut app());
+        assert_no_control_characters(&screen);
+        assert!(
+            screen
+                .iter()
+                .any(|row| row.concat().contains(r"\u{1b}]0;pwned\u{7}"))
+        );
+
+        // The provider column starts in the same cell on every row
+        let provider_columns: Vec<usize> = screen
+            .iter()
+            .filter_map(|row| row.windows(3).position(|cells| cells == ["a", "g", "e"]))
+            .collect();
+        assert_eq!(provider_columns.len(), 4, "{provider_columns:?}");
+        assert!(
+            provider_columns.iter().all(|&x| x == provider_columns[0]),
+            "{provider_columns:?}"
+        );
+    }
+
+    #[test]
+    fn test_secret_detail_escapes_the_description() {
+        let mut app = app();
+        app.popup = Popup::SecretDetail(TITLE_ESCAPE.to_string());
+        let screen = screen(&mut app);
+        assert_no_control_characters(&screen);
+        assert!(screen.iter().any(|row| {
+            row.concat()
+                .contains(r"Description: \u{1b}]0;pwned\u{7}\u{1b}[2J")
+        }));
+    }
+}
@@ -0,0 +1,114 @@
+//! Config file watching, shared by `fnox tui --watch` and `fnox check --watch`
+//!
+//! Watches the directories of every config file that contributed to the
+//! loaded config and signals once writes settle. Directories are watched
+//! rather than the files themselves, since editors often save by renaming a
+//! new file over the old one.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::error::{FnoxError, Result};
+
+/// How long writes must stop before a change is signalled
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub struct ConfigWatcher {
+    watcher: RecommendedWatcher,
+    /// Config files whose changes are signalled
+    files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Directories currently watched
+    dirs: HashSet<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Watch `files`. The receiver gets one message per burst of changes.
+    pub fn new(files: Vec<PathBuf>) -> Result<(Self, mpsc::UnboundedReceiver<()>)> {
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel::<()>();
+        let (settled_tx, settled_rx) = mpsc::unbounded_channel();
+        let watched = Arc::new(Mutex::new(HashSet::new()));
+
+        let filter = Arc::clone(&watched);
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let files = filter.lock().expect("watched files lock poisoned");
+            if event.paths.iter().any(|path| files.contains(path)) {
+                let _ = changed_tx.send(());
+            }
+        })
+        .map_err(watch_error)?;
+
+        // Wait for a change, then for DEBOUNCE without another
+        tokio::spawn(async move {
+            while changed_rx.recv().await.is_some() {
+                while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, changed_rx.recv()).await {}
+                if settled_tx.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut config_watcher = Self {
+            watcher,
+            files: watched,
+            dirs: HashSet::new(),
+        };
+        config_watcher.set_files(files)?;
+        Ok((config_watcher, settled_rx))
+    }
+
+    /// Replace the watched files, e.g. after a reload added an import
+    pub fn set_files(&mut self, files: Vec<PathBuf>) -> Result<()> {
+        // Events carry resolved paths (e.g. /private/var on macOS)
+        let files: Vec<PathBuf> = files
+            .into_iter()
+            .map(|file| std::fs::canonicalize(&file).unwrap_or(file))
+            .collect();
+        let dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+
+        for dir in self.dirs.difference(&dirs) {
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.dirs) {
+            self.watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(watch_error)?;
+        }
+
+        self.dirs = dirs;
+        *self.files.lock().expect("watched files lock poisoned") = files.into_iter().collect();
+        Ok(())
+    }
+}
+
+/// Files to watch for a config loaded from `config_path` (`--config`): every
+/// file that contributed to it, plus the config file itself, which may not
+/// define anything yet
+pub fn watched_files(config_path: &Path, config: &Config) -> Vec<PathBuf> {
+    let mut files = config.source_paths();
+    if let Ok(path) = std::path::absolute(config_path)
+        && path.is_file()
+        && !files.contains(&path)
+    {
+        files.push(path);
+    }
+    files
+}
+
+fn watch_error(e: notify::Error) -> FnoxError {
+    FnoxError::Config(format!("Failed to watch config files: {}", e))
+}
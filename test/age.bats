@@ -235,3 +235,38 @@ EOF
 	assert_output --partial "Circular dependency detected in provider configuration"
 	assert_output --partial "age-a -> age-b -> age-a"
 }
+
+@test "fnox check warns about secrets encrypted to removed ssh recipients" {
+	if ! command -v ssh-keygen >/dev/null 2>&1; then
+		skip "ssh-keygen not installed"
+	fi
+
+	ssh-keygen -q -t ed25519 -N "" -f old_key
+	ssh-keygen -q -t ed25519 -N "" -f new_key
+
+	cat >fnox.toml <<EOF
+root = true
+
+[providers.age]
+type = "age"
+recipients = ["$(cat old_key.pub)"]
+
+[secrets]
+EOF
+
+	run "$FNOX_BIN" set MY_SECRET "secret-value"
+	assert_success
+
+	export FNOX_AGE_KEY="$(cat old_key)"
+	run "$FNOX_BIN" check
+	assert_success
+	refute_output --partial "not encrypted to any recipient"
+
+	# Swap the recipient without re-encrypting
+	sed -i.bak "s|$(cut -d' ' -f2 old_key.pub)|$(cut -d' ' -f2 new_key.pub)|" fnox.toml
+
+	run "$FNOX_BIN" check
+	assert_success
+	assert_output --partial "Secret 'MY_SECRET' is not encrypted to any recipient of provider 'age'"
+	assert_output --partial "fnox reencrypt -p age"
+}
@@ -0,0 +1,61 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	cat >fnox.toml <<'EOF'
+root = true
+
+[providers.age]
+type = "age"
+passphrase = true
+EOF
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "age passphrase provider round-trips with FNOX_AGE_PASSPHRASE" {
+	export FNOX_AGE_PASSPHRASE="correct horse battery staple"
+
+	assert_fnox_success set MY_SECRET "s3cret" --provider age
+	run grep 's3cret' fnox.toml
+	assert_failure
+
+	assert_fnox_success get MY_SECRET
+	assert_output "s3cret"
+}
+
+@test "age passphrase provider fails with the wrong passphrase" {
+	FNOX_AGE_PASSPHRASE="correct horse battery staple" run "$FNOX_BIN" set MY_SECRET "s3cret" --provider age
+	assert_success
+
+	FNOX_AGE_PASSPHRASE="battery staple" run "$FNOX_BIN" get MY_SECRET
+	assert_failure
+	assert_output --partial "wrong passphrase"
+	refute_output --partial "s3cret"
+}
+
+@test "age passphrase provider fails clearly without a passphrase or terminal" {
+	unset FNOX_AGE_PASSPHRASE
+
+	run "$FNOX_BIN" set MY_SECRET "s3cret" --provider age
+	assert_failure
+	assert_output --partial "set FNOX_AGE_PASSPHRASE"
+
+	run grep 'MY_SECRET' fnox.toml
+	assert_failure
+}
+
+@test "age passphrase provider can't also have recipients" {
+	cat >>fnox.toml <<'EOF'
+recipients = ["age1cdk0klj88zzhg0ncfhe4ul9ja5k58w2st3fpkhmy0f46vlsuh5wq0s0gr9"]
+EOF
+	export FNOX_AGE_PASSPHRASE="correct horse battery staple"
+
+	run "$FNOX_BIN" set MY_SECRET "s3cret" --provider age
+	assert_failure
+	assert_output --partial "both \`passphrase = true\` and \`recipients\`"
+}
@@ -311,3 +311,44 @@ EOF
 	assert_success
 	assert_output "$SHARED_SIMPLE_VALUE"
 }
+
+# Helper to create config with an encryption context
+create_kms_config_with_context() {
+	local context="$1"
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+root = true
+
+[providers.kms]
+type = "aws-kms"
+key_id = "alias/fnox-testing"
+region = "us-east-1"
+endpoint = "$LOCALSTACK_ENDPOINT"
+encryption_context = $context
+
+[secrets]
+EOF
+}
+
+@test "fnox set and get round-trip with an encryption context" {
+	create_kms_config_with_context '{ app = "fnox", env = "prod" }'
+
+	run "$FNOX_BIN" set KMS_CONTEXT_TEST "context-value" --provider kms
+	assert_success
+
+	run "$FNOX_BIN" get KMS_CONTEXT_TEST
+	assert_success
+	assert_output "context-value"
+}
+
+@test "fnox get fails when the encryption context doesn't match" {
+	create_kms_config_with_context '{ app = "fnox", env = "prod" }'
+
+	run "$FNOX_BIN" set KMS_CONTEXT_TEST "context-value" --provider kms
+	assert_success
+
+	sed -i.bak 's/env = "prod"/env = "dev"/' "${FNOX_CONFIG_FILE:-fnox.toml}"
+
+	run "$FNOX_BIN" get KMS_CONTEXT_TEST
+	assert_failure
+	assert_output --partial "encryption context in config (app=fnox, env=dev) doesn't match"
+}
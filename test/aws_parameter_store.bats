@@ -339,3 +339,40 @@ EOF
 	assert_output --partial "DESCRIBED_PARAM"
 	assert_output --partial "A parameter with a description"
 }
+
+@test "prefixed parameter round-trips through set, get, list and exec" {
+	create_ps_config "us-east-1" "/fnox-test/"
+
+	local key="round-trip-$$-$(date +%s)"
+	export TEST_PARAM_NAME="/fnox-test/$key"
+
+	# The config stores the key as given; the prefix is added on every request
+	run "$FNOX_BIN" set ROUND_TRIP "round-trip-value" --provider ps --key-name "$key"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial "value = \"$key\""
+	refute_output --partial "value = \"/fnox-test/$key\""
+
+	# The remote parameter carries the prefix exactly once
+	run aws --endpoint-url "$LOCALSTACK_ENDPOINT" ssm get-parameter \
+		--name "/fnox-test/$key" \
+		--with-decryption \
+		--query Parameter.Value \
+		--output text \
+		--region "$PS_REGION"
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" get ROUND_TRIP
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" list --values
+	assert_success
+	assert_output --partial "round-trip-value"
+
+	run bash -c "'$FNOX_BIN' exec -- bash -c 'echo \$ROUND_TRIP' 2>/dev/null"
+	assert_success
+	assert_output "round-trip-value"
+}
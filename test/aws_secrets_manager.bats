@@ -260,3 +260,43 @@ EOF
 	assert_output --partial "DESCRIBED_SECRET"
 	assert_output --partial "A secret with a description"
 }
+
+@test "prefixed secret round-trips through set, get, list and exec" {
+	create_sm_config "us-east-1" "fnox-test/"
+
+	local key="round-trip-$$-$(date +%s)"
+
+	# The config stores the key as given; the prefix is added on every request
+	run "$FNOX_BIN" set ROUND_TRIP "round-trip-value" --provider sm --key-name "$key"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial "value = \"$key\""
+	refute_output --partial "value = \"fnox-test/$key\""
+
+	# The remote secret carries the prefix exactly once
+	run aws --endpoint-url "$LOCALSTACK_ENDPOINT" secretsmanager get-secret-value \
+		--secret-id "fnox-test/$key" \
+		--query SecretString \
+		--output text \
+		--region "$SM_REGION"
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" get ROUND_TRIP
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" list --values
+	assert_success
+	assert_output --partial "round-trip-value"
+
+	run bash -c "'$FNOX_BIN' exec -- bash -c 'echo \$ROUND_TRIP' 2>/dev/null"
+	assert_success
+	assert_output "round-trip-value"
+
+	aws --endpoint-url "$LOCALSTACK_ENDPOINT" secretsmanager delete-secret \
+		--secret-id "fnox-test/$key" \
+		--force-delete-without-recovery \
+		--region "$SM_REGION" >/dev/null 2>&1 || true
+}
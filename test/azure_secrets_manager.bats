@@ -351,3 +351,32 @@ EOF
 
 	# Cleanup will be handled by teardown function
 }
+
+@test "prefixed secret round-trips through set, get, list and exec" {
+	create_azure_sm_config "https://fnox-testing-kv.vault.azure.net/" "fnox-test-"
+
+	local timestamp
+	timestamp="$(date +%s)-$$-${BATS_TEST_NUMBER:-0}"
+	local key="ROUND-TRIP-${timestamp}"
+	export TEST_SECRET_NAME="fnox-test-${key}"
+
+	# The config stores the key as given; the prefix is added on every request
+	run "$FNOX_BIN" set ROUND_TRIP "round-trip-value" --provider azure-sm --key-name "${key}"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial "value = \"${key}\""
+	refute_output --partial "value = \"fnox-test-${key}\""
+
+	run "$FNOX_BIN" get ROUND_TRIP
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" list --values
+	assert_success
+	assert_output --partial "round-trip-value"
+
+	run bash -c "'$FNOX_BIN' exec -- bash -c 'echo \$ROUND_TRIP' 2>/dev/null"
+	assert_success
+	assert_output "round-trip-value"
+}
@@ -78,3 +78,137 @@ EOF
 	assert_fnox_success check
 	assert_output --partial "No secrets"
 }
+
+@test "fnox check --config-only doesn't resolve secrets from providers" {
+	create_test_config
+
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+
+[secrets.undecryptable]
+provider = "test-provider"
+value = "not-age-ciphertext"
+if_missing = "error"
+EOF
+
+	assert_fnox_failure check
+	assert_output --partial "undecryptable"
+
+	assert_fnox_success check --config-only
+}
+
+@test "fnox check --require fails for each key the profile doesn't define" {
+	create_test_config
+
+	assert_fnox_success check --require test_secret
+
+	assert_fnox_failure check --require test_secret --require MISSING_ONE --require MISSING_TWO
+	assert_output --partial "Required secret 'MISSING_ONE' is not defined in profile 'default'"
+	assert_output --partial "Required secret 'MISSING_TWO' is not defined in profile 'default'"
+	refute_output --partial "Required secret 'test_secret'"
+}
+
+@test "fnox check --require tells an empty value apart from a missing key" {
+	create_test_config
+
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+
+[secrets.EMPTY_SECRET]
+default = ""
+EOF
+
+	assert_fnox_failure check --require EMPTY_SECRET
+	assert_output --partial "Required secret 'EMPTY_SECRET' is defined but resolves to an empty value"
+	refute_output --partial "is not defined"
+}
+
+@test "fnox check --require-file reads one key per line" {
+	create_test_config
+
+	cat >required.txt <<EOF
+# deploy needs these
+test_secret
+
+DEPLOY_TOKEN
+EOF
+
+	assert_fnox_failure check --require-file required.txt
+	assert_output --partial "Required secret 'DEPLOY_TOKEN' is not defined in profile 'default'"
+	refute_output --partial "Required secret 'test_secret'"
+	refute_output --partial "deploy needs these"
+
+	assert_fnox_failure check --require-file does-not-exist.txt
+	assert_output --partial "Failed to read required keys from does-not-exist.txt"
+}
+
+@test "fnox check --strict fails on values too large for an environment variable" {
+	create_test_config
+
+	big=$(head -c 140000 /dev/zero | tr '\0' x)
+	printf '\n[secrets.BIG]\ndefault = "%s"\n' "$big" >>"${FNOX_CONFIG_FILE:-fnox.toml}"
+
+	assert_fnox_success check --require BIG
+	assert_fnox_failure check --require BIG --strict
+	assert_output --partial "Secret 'BIG' is too large for an environment variable"
+
+	# Files aren't passed through the environment
+	create_test_config
+	printf '\n[secrets.BIG]\nas_file = true\ndefault = "%s"\n' "$big" >>"${FNOX_CONFIG_FILE:-fnox.toml}"
+	assert_fnox_success check --require BIG --strict
+}
+
+@test "fnox check escapes control characters in keys" {
+	create_test_config
+
+	assert_fnox_failure check --require $'EVIL\e]0;pwned\aKEY'
+	assert_output --partial 'EVIL\u{1b}]0;pwned\u{7}KEY'
+}
+
+# Wait up to 10s for $1 to appear in the --watch log, at least $2 times
+wait_for_watch_log() {
+	for _ in $(seq 1 100); do
+		[ "$(grep -cF "$1" "$TEST_TEMP_DIR/watch.log")" -ge "${2:-1}" ] && return 0
+		sleep 0.1
+	done
+	cat "$TEST_TEMP_DIR/watch.log"
+	return 1
+}
+
+@test "fnox check --watch re-checks when the config changes" {
+	create_test_config
+	"$FNOX_BIN" check --watch --config-only >"$TEST_TEMP_DIR/watch.log" 2>&1 &
+	local pid=$!
+
+	wait_for_watch_log "OK (1 secret(s), 1 provider(s))"
+
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+
+[secrets.required_secret]
+if_missing = "error"
+EOF
+	wait_for_watch_log "1 error(s)"
+	wait_for_watch_log "required_secret"
+
+	# Still running after a failed check
+	kill -0 "$pid"
+	kill -INT "$pid"
+	wait "$pid" || true
+}
+
+@test "fnox check --watch reloads a config that failed to load" {
+	create_test_config
+	local config="${FNOX_CONFIG_FILE:-fnox.toml}"
+	cp "$config" "$TEST_TEMP_DIR/valid.toml"
+	"$FNOX_BIN" check --watch --config-only >"$TEST_TEMP_DIR/watch.log" 2>&1 &
+	local pid=$!
+
+	wait_for_watch_log "OK (1 secret(s), 1 provider(s))"
+
+	echo "[secrets" >>"$config"
+	wait_for_watch_log "✗"
+
+	cp "$TEST_TEMP_DIR/valid.toml" "$config"
+	wait_for_watch_log "OK (1 secret(s), 1 provider(s))" 2
+
+	kill -INT "$pid"
+	wait "$pid" || true
+}
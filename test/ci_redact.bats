@@ -61,14 +61,32 @@ teardown() {
 	[ "$mask_count" -eq 3 ]
 }
 
-@test "ci-redact fails on GitLab CI" {
+@test "ci-redact lists masked variables to add on GitLab CI" {
 	export CI=true
 	export GITLAB_CI=true
 	unset GITHUB_ACTIONS
 
 	run "$FNOX_BIN" ci-redact
-	assert_failure
-	assert_output --partial "GitLab CI does not support runtime secret masking"
+	assert_success
+	assert_output --partial "SECRET_ONE: add as a masked CI/CD variable"
+	assert_output --partial "PASSWORD: add as a masked CI/CD variable"
+	refute_output --partial "secret-value-1"
+	refute_output --partial "::add-mask::"
+}
+
+@test "ci-redact --platform overrides detection" {
+	export CI=true
+	export GITHUB_ACTIONS=true
+
+	run "$FNOX_BIN" ci-redact --platform gitlab
+	assert_success
+	assert_output --partial "SECRET_ONE: add as a masked CI/CD variable"
+	refute_output --partial "::add-mask::"
+
+	unset CI GITHUB_ACTIONS
+	run "$FNOX_BIN" ci-redact --platform github
+	assert_success
+	assert_output --partial "::add-mask::secret-value-1"
 }
 
 @test "ci-redact fails on CircleCI" {
@@ -0,0 +1,94 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+API_KEY = { default = "a" }
+DATABASE_URL = { default = "d" }
+
+[profiles.prod.providers]
+vault = { type = "plain" }
+
+[profiles.prod.secrets]
+PROD_ONLY = { default = "p" }
+EOF_CONFIG
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "__complete get lists secret keys for the active profile" {
+	run "$FNOX_BIN" __complete get ""
+	assert_success
+	assert_output "API_KEY
+DATABASE_URL"
+}
+
+@test "__complete get filters keys by the typed prefix" {
+	run "$FNOX_BIN" __complete get DA
+	assert_success
+	assert_output "DATABASE_URL"
+}
+
+@test "__complete get uses the profile from the command line" {
+	run "$FNOX_BIN" __complete -- --profile prod get ""
+	assert_success
+	assert_output --partial "PROD_ONLY"
+
+	FNOX_PROFILE=prod run "$FNOX_BIN" __complete remove P
+	assert_success
+	assert_output "PROD_ONLY"
+}
+
+@test "__complete lists provider names and profiles" {
+	run "$FNOX_BIN" __complete provider test ""
+	assert_success
+	assert_output "plain"
+
+	run "$FNOX_BIN" __complete get --profile ""
+	assert_success
+	assert_output "default
+prod"
+}
+
+@test "__complete lists subcommands and flags" {
+	run "$FNOX_BIN" __complete ge
+	assert_success
+	assert_output "get"
+
+	run "$FNOX_BIN" __complete get --base
+	assert_success
+	assert_output "--base64-decode"
+}
+
+@test "fnox completion falls back to built-in scripts without usage" {
+	if PATH="/usr/bin:/bin" command -v usage >/dev/null 2>&1; then
+		skip "usage is installed in /usr/bin or /bin"
+	fi
+
+	PATH="/usr/bin:/bin" run "$FNOX_BIN" completion bash
+	assert_success
+	assert_output --partial "fnox __complete"
+}
+
+@test "__complete --arg prints the values for one argument" {
+	run "$FNOX_BIN" __complete --arg key
+	assert_success
+	assert_output "API_KEY
+DATABASE_URL"
+}
+
+@test "__complete --arg respects --profile" {
+	run "$FNOX_BIN" --profile prod __complete --arg provider
+	assert_success
+	assert_line "vault"
+}
@@ -143,3 +143,187 @@ EOF
 	assert_failure
 	assert_output --partial "Secret 'BASE_ONLY' not found"
 }
+
+counting_config() {
+	cat >counter.sh <<'EOF'
+#!/usr/bin/env bash
+count=$(($(cat "$(dirname "$0")/count" 2>/dev/null || echo 0) + 1))
+echo "$count" >"$(dirname "$0")/count"
+echo "value-$count"
+EOF
+	chmod +x counter.sh
+	cat >fnox.toml <<EOF
+root = true
+
+[daemon]
+enabled = true
+$1
+
+[providers.counter]
+type = "exec"
+command = "$PWD/counter.sh {key}"
+
+[secrets]
+COUNTED = { provider = "counter", value = "counted" }
+EOF
+}
+
+@test "daemon re-resolves values older than cache_ttl" {
+	counting_config 'cache_ttl = "1s"'
+
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output "value-1"
+
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output "value-1"
+
+	sleep 2
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output "value-2"
+}
+
+@test "daemon prefetch refreshes watched projects before the ttl expires" {
+	counting_config 'cache_ttl = "2s"
+prefetch = true'
+
+	run "$FNOX_BIN" exec -- printenv COUNTED
+	assert_success
+	assert_output "value-1"
+
+	run "$FNOX_BIN" daemon status
+	assert_success
+	assert_output --partial "watched_projects: 1"
+
+	# Refreshed in the background without another client request
+	sleep 4
+	run cat count
+	assert_success
+	refute_output "1"
+
+	run "$FNOX_BIN" exec -- printenv COUNTED
+	assert_success
+	refute_output "value-1"
+}
+
+@test "daemon without prefetch does not watch projects" {
+	counting_config 'cache_ttl = "2s"'
+
+	run "$FNOX_BIN" exec -- printenv COUNTED
+	assert_success
+
+	run "$FNOX_BIN" daemon status
+	assert_success
+	assert_output --partial "watched_projects: 0"
+
+	sleep 3
+	run cat count
+	assert_output "1"
+}
+
+stale_config() {
+	counting_config 'cache_ttl = "1s"'
+	cat >>fnox.toml <<'EOF'
+
+[settings]
+stale_if_error = "1h"
+EOF
+}
+
+break_counter() {
+	cat >counter.sh <<'EOF'
+#!/usr/bin/env bash
+echo "provider unavailable" >&2
+exit 1
+EOF
+}
+
+@test "daemon serves a stale value when its provider fails within stale_if_error" {
+	stale_config
+
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output "value-1"
+
+	sleep 2
+	break_counter
+
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output --partial "value-1"
+	assert_output --partial "stale value for 'COUNTED'"
+
+	run "$FNOX_BIN" cache status
+	assert_success
+	assert_output --partial "COUNTED"
+
+	run "$FNOX_BIN" check
+	assert_failure
+}
+
+@test "daemon serves stale values only for secrets whose provider failed" {
+	stale_config
+	cat >other.sh <<'EOF'
+#!/usr/bin/env bash
+count=$(($(cat "$(dirname "$0")/other-count" 2>/dev/null || echo 0) + 1))
+echo "$count" >"$(dirname "$0")/other-count"
+echo "other-$count"
+EOF
+	chmod +x other.sh
+	cat >>fnox.toml <<EOF
+
+[providers.other]
+type = "exec"
+command = "$PWD/other.sh {key}"
+
+[secrets.OTHER]
+provider = "other"
+value = "other"
+EOF
+
+	run "$FNOX_BIN" export
+	assert_success
+	assert_output --partial "value-1"
+	assert_output --partial "other-1"
+
+	sleep 2
+	break_counter
+
+	run "$FNOX_BIN" export
+	assert_success
+	assert_output --partial "value-1"
+	assert_output --partial "other-2"
+	assert_output --partial "stale value for 'COUNTED'"
+	refute_output --partial "stale value for 'OTHER'"
+}
+
+@test "daemon does not serve stale values without stale_if_error" {
+	counting_config 'cache_ttl = "1s"'
+
+	run "$FNOX_BIN" get COUNTED
+	assert_success
+	assert_output "value-1"
+
+	sleep 2
+	break_counter
+
+	run "$FNOX_BIN" get COUNTED
+	assert_failure
+	refute_output --partial "value-1"
+
+	run "$FNOX_BIN" cache status
+	assert_success
+	assert_output --partial "No stale values are being served"
+}
+
+@test "falls back to direct resolution when the daemon can't start" {
+	daemon_config
+	export XDG_RUNTIME_DIR="$TEST_TEMP_DIR/not-a-dir"
+	touch "$XDG_RUNTIME_DIR"
+
+	run "$FNOX_BIN" get FOO
+	assert_success
+	assert_output "bar"
+}
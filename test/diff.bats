@@ -0,0 +1,60 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[profiles.staging.secrets]
+ONLY_STAGING = { provider = "plain", value = "a" }
+SHARED = { provider = "plain", value = "same" }
+ROTATED = { provider = "plain", value = "old" }
+
+[profiles.prod.secrets]
+SHARED = { provider = "plain", value = "same" }
+ROTATED = { provider = "plain", value = "new" }
+ONLY_PROD = { provider = "plain", value = "b" }
+EOF_CONFIG
+}
+
+@test "fnox diff classifies added and removed keys" {
+	write_config
+
+	run "$FNOX_BIN" diff staging prod
+	assert_success
+	assert_line --regexp '^- ONLY_STAGING +only in staging$'
+	assert_line --regexp '^\+ ONLY_PROD +only in prod$'
+	assert_line --regexp '^= SHARED +same$'
+	assert_line --regexp '^= ROTATED +same$'
+}
+
+@test "fnox diff --values reports changed values without printing them" {
+	write_config
+
+	run "$FNOX_BIN" diff staging prod --values
+	assert_success
+	assert_line --regexp '^~ ROTATED +value changed$'
+	assert_line --regexp '^= SHARED +same$'
+	refute_output --partial "old"
+	refute_output --partial "new"
+}
+
+@test "fnox diff fails for an unknown profile" {
+	write_config
+
+	run "$FNOX_BIN" diff staging prdo
+	assert_failure
+	assert_output --partial "Profile 'prdo' not found"
+	assert_output --partial "prod"
+}
@@ -95,3 +95,49 @@ EOF
 	assert_fnox_success doctor
 	assert_output --partial "Count: 16" # 1 from create_test_config + 15 new ones
 }
+
+failing_provider_config() {
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[providers.broken]
+type = "exec"
+command = "fnox-test-missing-command {key}"
+
+[secrets]
+FOO = { provider = "plain", value = "bar" }
+EOF
+}
+
+@test "fnox doctor reports failed provider checks" {
+	failing_provider_config
+	assert_fnox_success doctor
+	assert_output --partial "plain: ✓"
+	assert_output --partial "broken: ✗"
+	assert_output --partial "fnox-test-missing-command"
+}
+
+@test "fnox doctor --strict fails when a provider check fails" {
+	failing_provider_config
+	run "$FNOX_BIN" doctor --strict
+	assert_failure
+	assert_output --partial "broken: ✗"
+	assert_output --partial "1 provider check(s) failed"
+}
+
+@test "fnox doctor --provider checks only that provider" {
+	failing_provider_config
+	assert_fnox_success doctor --strict --provider plain
+	assert_output --partial "plain: ✓"
+	refute_output --partial "broken: ✗"
+}
+
+@test "fnox doctor --provider rejects unknown providers" {
+	failing_provider_config
+	run "$FNOX_BIN" doctor --provider nope
+	assert_failure
+	assert_output --partial "Provider 'nope' not configured"
+}
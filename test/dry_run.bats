@@ -92,6 +92,39 @@ EOF
 	assert_output --partial "A test secret"
 }
 
+@test "fnox set --dry-run encrypts and shows the TOML it would write" {
+	setup_age_provider
+	cp fnox.toml fnox.toml.orig
+
+	assert_fnox_success set MY_SECRET "test-value" --provider age --dry-run
+	assert_output --partial "Would write:"
+	assert_output --partial "[secrets]"
+	assert_output --partial 'MY_SECRET= { provider = "age", value = "'
+	refute_output --partial "<encrypted>"
+	refute_output --partial "test-value"
+
+	diff fnox.toml fnox.toml.orig
+}
+
+@test "fnox set --dry-run with a remote provider doesn't store the value" {
+	# Nothing listens on this endpoint, so any remote call would fail
+	cat >fnox.toml <<EOF
+root = true
+
+[providers.sm]
+type = "aws-sm"
+region = "us-east-1"
+endpoint = "http://127.0.0.1:9"
+EOF
+	cp fnox.toml fnox.toml.orig
+
+	assert_fnox_success set MY_SECRET "test-value" --provider sm --dry-run
+	assert_output --partial "Would store the value in provider sm as MY_SECRET"
+	assert_output --partial 'MY_SECRET= { provider = "sm", value = "MY_SECRET" }'
+
+	diff fnox.toml fnox.toml.orig
+}
+
 # ============================================================================
 # REMOVE COMMAND DRY-RUN TESTS
 # ============================================================================
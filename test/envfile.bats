@@ -0,0 +1,86 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.dotenv]
+type = "envfile"
+path = ".env"
+
+[secrets]
+DATABASE_URL = { provider = "dotenv", value = "DATABASE_URL" }
+TLS_CERT = { provider = "dotenv", value = "TLS_CERT" }
+EOF_CONFIG
+
+	cat >.env <<'EOF_ENV'
+# Local development
+export DATABASE_URL="postgres://localhost/db" # primary
+TLS_CERT="-----BEGIN CERT-----
+abc
+-----END CERT-----"
+EOF_ENV
+}
+
+@test "envfile provider reads quoted, exported and multi-line values" {
+	write_config
+
+	run "$FNOX_BIN" get DATABASE_URL
+	assert_success
+	assert_output "postgres://localhost/db"
+
+	run "$FNOX_BIN" get TLS_CERT
+	assert_success
+	assert_output "-----BEGIN CERT-----
+abc
+-----END CERT-----"
+}
+
+@test "envfile provider reads a relative path from the config file's directory" {
+	write_config
+	mkdir -p sub
+	cd sub
+
+	run "$FNOX_BIN" get DATABASE_URL
+	assert_success
+	assert_output "postgres://localhost/db"
+}
+
+@test "envfile provider reports missing variables" {
+	write_config
+	cat >>fnox.toml <<'EOF_CONFIG'
+MISSING = { provider = "dotenv", value = "NOT_IN_FILE" }
+EOF_CONFIG
+
+	run "$FNOX_BIN" get MISSING
+	assert_failure
+	assert_output --partial "not found"
+}
+
+@test "fnox set updates the env file in place" {
+	write_config
+
+	run "$FNOX_BIN" set DATABASE_URL "postgres://prod/db" --provider dotenv
+	assert_success
+	run "$FNOX_BIN" set API_KEY "with spaces" --provider dotenv
+	assert_success
+
+	run cat .env
+	assert_output --partial "# Local development
+export DATABASE_URL=postgres://prod/db
+TLS_CERT="
+	assert_output --partial 'API_KEY="with spaces"'
+
+	run "$FNOX_BIN" get API_KEY
+	assert_success
+	assert_output "with spaces"
+}
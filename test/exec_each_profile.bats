@@ -0,0 +1,84 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets.PROFILE_NAME]
+provider = "plain"
+value = "default"
+
+[profiles.staging.secrets.PROFILE_NAME]
+provider = "plain"
+value = "staging"
+
+[profiles.prod.secrets.PROFILE_NAME]
+provider = "plain"
+value = "prod"
+TOML
+}
+
+@test "fnox exec --each-profile runs the command for every profile" {
+	write_config
+
+	run "$FNOX_BIN" exec --each-profile -- sh -c 'echo "profile=$PROFILE_NAME"'
+	assert_success
+	assert_output --partial "profile=default"
+	assert_output --partial "profile=staging"
+	assert_output --partial "profile=prod"
+}
+
+@test "fnox exec --each-profile --profiles limits the set" {
+	write_config
+
+	run "$FNOX_BIN" exec --each-profile --profiles staging,prod -- sh -c 'echo "profile=$PROFILE_NAME"'
+	assert_success
+	assert_output --partial "profile=staging"
+	assert_output --partial "profile=prod"
+	refute_output --partial "profile=default"
+}
+
+@test "fnox exec --each-profile fails if any profile fails" {
+	write_config
+
+	run "$FNOX_BIN" exec --each-profile --profiles prod,staging -- sh -c 'echo "profile=$PROFILE_NAME"; [ "$PROFILE_NAME" != prod ]'
+	assert_failure
+	assert_output --partial "profile=prod"
+	assert_output --partial "profile=staging"
+	assert_output --partial "1 of 2 profile(s) failed: prod"
+}
+
+@test "fnox exec --each-profile --stop-on-error stops at the first failure" {
+	write_config
+
+	run "$FNOX_BIN" exec --each-profile --profiles prod,staging --stop-on-error -- sh -c 'echo "profile=$PROFILE_NAME"; [ "$PROFILE_NAME" != prod ]'
+	assert_failure
+	assert_output --partial "profile=prod"
+	refute_output --partial "profile=staging"
+}
+
+@test "fnox exec --each-profile keeps going when a profile's secrets fail" {
+	write_config
+	cat >>fnox.toml <<'TOML'
+
+[profiles.broken.secrets.PROFILE_NAME]
+provider = "missing"
+value = "broken"
+if_missing = "error"
+TOML
+
+	run "$FNOX_BIN" exec --each-profile --profiles broken,staging -- sh -c 'echo "profile=$PROFILE_NAME"'
+	assert_failure
+	assert_output --partial "==> broken: failed"
+	assert_output --partial "profile=staging"
+	assert_output --partial "1 of 2 profile(s) failed: broken"
+}
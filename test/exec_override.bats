@@ -0,0 +1,48 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+DATABASE_URL = { provider = "plain", value = "postgres://prod-db/app" }
+API_KEY = { provider = "plain", value = "from-provider" }
+TOML
+}
+
+@test "FNOX_OVERRIDE_<KEY> wins over the provider value in fnox exec" {
+	write_config
+
+	run env FNOX_OVERRIDE_DATABASE_URL=postgres://localhost/app "$FNOX_BIN" exec -- sh -c 'echo "$DATABASE_URL $API_KEY"'
+	assert_success
+	assert_output "postgres://localhost/app from-provider"
+}
+
+@test "FNOX_OVERRIDE_<KEY> is not passed to the command" {
+	write_config
+
+	run env FNOX_OVERRIDE_DATABASE_URL=postgres://localhost/app "$FNOX_BIN" exec -- sh -c 'env | grep "^FNOX_OVERRIDE_" || echo none'
+	assert_success
+	assert_output "none"
+}
+
+@test "FNOX_OVERRIDE_<KEY> also applies to fnox get" {
+	write_config
+
+	run env FNOX_OVERRIDE_API_KEY=debug-key "$FNOX_BIN" get API_KEY
+	assert_success
+	assert_output "debug-key"
+}
@@ -0,0 +1,58 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+API_TOKEN = { provider = "plain", value = "tok-12345" }
+SHORT = { provider = "plain", value = "on" }
+TOML
+}
+
+@test "fnox exec --redact masks secrets in stdout and stderr" {
+	write_config
+
+	run "$FNOX_BIN" exec --redact -- sh -c 'echo "token=$API_TOKEN"; echo "err $API_TOKEN" >&2'
+	assert_success
+	assert_output --partial "token=***"
+	assert_output --partial "err ***"
+	refute_output --partial "tok-12345"
+}
+
+@test "fnox exec --redact leaves values below the minimum length alone" {
+	write_config
+
+	run "$FNOX_BIN" exec --redact -- sh -c 'echo "flag=$SHORT"'
+	assert_success
+	assert_output "flag=on"
+
+	FNOX_REDACT_MIN_LENGTH=1 run "$FNOX_BIN" exec --redact -- sh -c 'echo "flag=$SHORT"'
+	assert_success
+	assert_output "flag=***"
+}
+
+@test "fnox exec --redact keeps the command's exit code" {
+	write_config
+
+	run "$FNOX_BIN" exec --redact -- sh -c 'echo "$API_TOKEN"; exit 3'
+	assert_failure 3
+	assert_output "***"
+}
+
+@test "fnox exec without --redact passes output through" {
+	write_config
+
+	run "$FNOX_BIN" exec -- sh -c 'echo "$API_TOKEN"'
+	assert_success
+	assert_output "tok-12345"
+}
@@ -0,0 +1,65 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets.TOKEN]
+provider = "plain"
+value = "old"
+TOML
+}
+
+@test "fnox exec --retry-child re-runs a failing command with fresh secrets" {
+	write_config
+	# Fails with the old token after rotating it, as a flaky test might
+	cat >flaky.sh <<'SH'
+#!/bin/sh
+echo "token=$TOKEN"
+if [ "$TOKEN" = "old" ]; then
+	sed -i.bak 's/value = "old"/value = "new"/' fnox.toml
+	exit 3
+fi
+SH
+	chmod +x flaky.sh
+
+	run "$FNOX_BIN" exec --retry-child 2 --retry-delay 0s -- ./flaky.sh
+	assert_success
+	assert_output --partial "token=old"
+	assert_output --partial "attempt 1 of 3 failed (exit code 3), retrying in 0s"
+	assert_output --partial "token=new"
+	assert_output --partial "attempt 2 of 3 succeeded"
+}
+
+@test "fnox exec --retry-child exits with the last attempt's code" {
+	write_config
+
+	run "$FNOX_BIN" exec --retry-child 2 --retry-delay 0s -- sh -c 'echo attempt; exit 7'
+	assert_failure 7
+	assert_equal "$(echo "$output" | grep -c '^attempt$')" 3
+	assert_output --partial "all 3 attempts failed (exit code 7)"
+}
+
+@test "fnox exec --retry-child doesn't retry a successful command" {
+	write_config
+
+	run "$FNOX_BIN" exec --retry-child 2 -- sh -c 'echo ran'
+	assert_success
+	assert_output "ran"
+}
+
+@test "fnox exec --retry-delay requires --retry-child" {
+	write_config
+
+	run "$FNOX_BIN" exec --retry-delay 5s -- true
+	assert_failure
+}
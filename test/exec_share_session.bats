@@ -0,0 +1,56 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	unset VAULT_ADDR VAULT_TOKEN VAULT_NAMESPACE FNOX_VAULT_ADDR FNOX_VAULT_TOKEN
+
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.vault]
+type = "vault"
+address = "http://127.0.0.1:8200"
+token = "hvs.test-session-token"
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+APP_SETTING = { default = "on" }
+EOF_CONFIG
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "fnox exec doesn't pass provider sessions by default" {
+	run "$FNOX_BIN" exec -- sh -c 'echo "token=${VAULT_TOKEN:-unset}"'
+	assert_success
+	assert_output "token=unset"
+}
+
+@test "fnox exec --share-session passes the provider's session to the command" {
+	run "$FNOX_BIN" exec --share-session vault -- sh -c 'echo "$VAULT_ADDR $VAULT_TOKEN"'
+	assert_success
+	assert_output "http://127.0.0.1:8200 hvs.test-session-token"
+}
+
+@test "fnox exec --share-session keeps session values out of debug logs" {
+	run "$FNOX_BIN" --verbose exec --share-session vault -- true
+	assert_success
+	assert_output --partial "VAULT_TOKEN"
+	refute_output --partial "hvs.test-session-token"
+}
+
+@test "fnox exec --share-session fails for providers without a session" {
+	run "$FNOX_BIN" exec --share-session plain -- true
+	assert_failure
+	assert_output --partial "Provider 'plain' (plain) has no session to share"
+
+	run "$FNOX_BIN" exec --share-session missing -- true
+	assert_failure
+	assert_output --partial "Provider 'missing' not configured"
+}
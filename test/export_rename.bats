@@ -0,0 +1,73 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+DATABASE_URL = { provider = "plain", value = "postgres://localhost" }
+STRIPE_KEY = { provider = "plain", value = "sk_test" }
+TOML
+}
+
+@test "fnox export --prefix prefixes every key in dotenv format" {
+	write_config
+
+	run "$FNOX_BIN" export --prefix APP_
+	assert_success
+	assert_output --partial "APP_DATABASE_URL=postgres://localhost"
+	assert_output --partial "APP_STRIPE_KEY=sk_test"
+	refute_output --regexp "^DATABASE_URL="
+}
+
+@test "fnox export --map renames keys in shell format" {
+	write_config
+
+	run "$FNOX_BIN" export --format shell --prefix APP_ --map STRIPE_KEY=STRIPE_SECRET_KEY
+	assert_success
+	assert_output --partial "export APP_DATABASE_URL=postgres://localhost"
+	assert_output --partial "export STRIPE_SECRET_KEY=sk_test"
+	refute_output --partial "APP_STRIPE"
+}
+
+@test "fnox export --map to an existing name is a collision" {
+	write_config
+
+	run "$FNOX_BIN" export --map STRIPE_KEY=DATABASE_URL
+	assert_failure
+	assert_output --partial "would both be exported as 'DATABASE_URL'"
+}
+
+@test "fnox export --map warns about unknown secrets" {
+	write_config
+
+	run "$FNOX_BIN" export --map MISSING=OTHER
+	assert_success
+	assert_output --partial "no secret named 'MISSING'"
+	assert_output --partial "DATABASE_URL=postgres://localhost"
+}
+
+@test "fnox export rejects a malformed --map" {
+	write_config
+
+	run "$FNOX_BIN" export --map STRIPE_KEY
+	assert_failure
+	assert_output --partial "expected FROM=TO"
+}
+
+@test "fnox exec applies --prefix and --map" {
+	write_config
+
+	run "$FNOX_BIN" exec --prefix APP_ --map STRIPE_KEY=STRIPE_SECRET_KEY -- sh -c 'echo "$APP_DATABASE_URL $STRIPE_SECRET_KEY ${DATABASE_URL:-unset}"'
+	assert_success
+	assert_output "postgres://localhost sk_test unset"
+}
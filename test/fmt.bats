@@ -0,0 +1,104 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_messy_config() {
+	cat >fnox.toml <<'TOML'
+root=true
+import = ["shared.toml"]
+
+[providers.plain]
+type='plain'
+
+[secrets]
+# Read by the app
+ZED={provider="plain",value='z'}
+ALPHA = { provider = "plain", value = "a" }
+TOML
+	cat >shared.toml <<'TOML'
+[secrets]
+SHARED_B = { default = "b" }
+SHARED_A = { default = 'a' }
+TOML
+}
+
+@test "fnox fmt rewrites the config in canonical style" {
+	write_messy_config
+
+	run "$FNOX_BIN" fmt
+	assert_success
+	assert_output --partial "Formatted"
+
+	run cat fnox.toml
+	assert_output 'root = true
+import = ["shared.toml"]
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+ALPHA= { provider = "plain", value = "a" }
+# Read by the app
+ZED= { provider = "plain", value = "z" }'
+
+	# Imports are left alone without --all
+	run cat shared.toml
+	assert_line --index 1 'SHARED_B = { default = "b" }'
+}
+
+@test "fnox fmt is idempotent and doesn't change values" {
+	write_messy_config
+
+	run "$FNOX_BIN" get ZED
+	assert_output "z"
+
+	assert_fnox_success fmt
+	cp fnox.toml once.toml
+	assert_fnox_success fmt
+	run diff once.toml fnox.toml
+	assert_success
+
+	run "$FNOX_BIN" get ZED
+	assert_output "z"
+}
+
+@test "fnox fmt --check fails on an unformatted config without writing it" {
+	write_messy_config
+	cp fnox.toml before.toml
+
+	run "$FNOX_BIN" fmt --check
+	assert_failure
+	assert_output --partial "fnox.toml is not formatted"
+	run diff before.toml fnox.toml
+	assert_success
+
+	assert_fnox_success fmt
+	assert_fnox_success fmt --check
+}
+
+@test "fnox fmt --no-sort keeps secrets in their order" {
+	write_messy_config
+
+	assert_fnox_success fmt --no-sort
+	run grep -n "= {" fnox.toml
+	assert_line --index 0 --partial "ZED"
+	assert_line --index 1 --partial "ALPHA"
+}
+
+@test "fnox fmt --all also formats imports" {
+	write_messy_config
+
+	assert_fnox_success fmt --all
+	run cat shared.toml
+	assert_output '[secrets]
+SHARED_A= { default = "a" }
+SHARED_B= { default = "b" }'
+}
@@ -241,3 +241,30 @@ EOF
 	assert_success
 	assert_output "updated-value"
 }
+
+@test "fnox set and get round-trip with aad" {
+	create_gcp_kms_config
+	sed -i.bak 's/^key = .*/&\naad = "fnox\/prod"/' "${FNOX_CONFIG_FILE:-fnox.toml}"
+
+	run "$FNOX_BIN" set GCP_KMS_AAD_TEST "aad-value" --provider gcp_kms
+	assert_success
+
+	run "$FNOX_BIN" get GCP_KMS_AAD_TEST
+	assert_success
+	assert_output "aad-value"
+}
+
+@test "fnox get fails when aad doesn't match" {
+	create_gcp_kms_config
+	sed -i.bak 's/^key = .*/&\naad = "fnox\/prod"/' "${FNOX_CONFIG_FILE:-fnox.toml}"
+
+	run "$FNOX_BIN" set GCP_KMS_AAD_TEST "aad-value" --provider gcp_kms
+	assert_success
+
+	sed -i.bak 's/aad = "fnox\/prod"/aad = "fnox\/dev"/' "${FNOX_CONFIG_FILE:-fnox.toml}"
+
+	run "$FNOX_BIN" get GCP_KMS_AAD_TEST
+	assert_failure
+	assert_output --partial "configured aad ('fnox/dev')"
+	refute_output --partial "aad-value"
+}
@@ -294,3 +294,37 @@ EOF
 	# fnox set creates a secret with the provider prefix + secret key
 	gcloud secrets delete "$TEST_SECRET_NAME" --project="$GCP_PROJECT" --quiet >/dev/null 2>&1 || true
 }
+
+@test "prefixed secret round-trips through set, get, list and exec" {
+	create_gcp_sm_config "chim-361015" "fnox-test-"
+
+	local key
+	key="round-trip-$(date +%s)-$$-${BATS_TEST_NUMBER:-0}"
+
+	# The config stores the key as given; the prefix is added on every request
+	run "$FNOX_BIN" set ROUND_TRIP "round-trip-value" --provider gcp_sm --key-name "${key}"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial "value = \"${key}\""
+	refute_output --partial "value = \"fnox-test-${key}\""
+
+	# The remote secret carries the prefix exactly once
+	run gcloud secrets versions access latest --secret="fnox-test-${key}" --project="$GCP_PROJECT"
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" get ROUND_TRIP
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" list --values
+	assert_success
+	assert_output --partial "round-trip-value"
+
+	run bash -c "'$FNOX_BIN' exec -- bash -c 'echo \$ROUND_TRIP' 2>/dev/null"
+	assert_success
+	assert_output "round-trip-value"
+
+	gcloud secrets delete "fnox-test-${key}" --project="$GCP_PROJECT" --quiet >/dev/null 2>&1 || true
+}
@@ -0,0 +1,79 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.age]
+type = "age"
+recipients = ["age1cdk0klj88zzhg0ncfhe4ul9ja5k58w2st3fpkhmy0f46vlsuh5wq0s0gr9"]
+
+[secrets]
+API_URL = { default = "https://api.example.com" }
+MISSING = { provider = "age", value = "not-a-ciphertext", if_missing = "ignore" }
+TOKEN = { default = "t0ken" }
+EOF_CONFIG
+}
+
+@test "fnox get --all outputs every secret as a JSON object" {
+	write_config
+
+	run "$FNOX_BIN" get --all
+	assert_success
+	assert_output '{
+  "API_URL": "https://api.example.com",
+  "MISSING": null,
+  "TOKEN": "t0ken"
+}'
+}
+
+@test "fnox get --all honors --if-missing" {
+	write_config
+	# A secret-level if_missing would take precedence over the flag
+	sed -i.bak 's/, if_missing = "ignore"//' fnox.toml
+
+	run "$FNOX_BIN" --if-missing error get --all
+	assert_failure
+
+	run "$FNOX_BIN" --if-missing ignore get --all
+	assert_success
+	assert_output --partial '"MISSING": null'
+}
+
+@test "fnox get --all --keys-only lists keys without resolving them" {
+	write_config
+	# Resolving MISSING with if_missing = error would fail
+	sed -i.bak 's/if_missing = "ignore"/if_missing = "error"/' fnox.toml
+
+	run "$FNOX_BIN" get --all --keys-only
+	assert_success
+	assert_output '[
+  "API_URL",
+  "MISSING",
+  "TOKEN"
+]'
+}
+
+@test "fnox get --all can't be combined with a key or --ref" {
+	write_config
+
+	run "$FNOX_BIN" get TOKEN --all
+	assert_failure
+	assert_output --partial "cannot be used with"
+
+	run "$FNOX_BIN" get --ref raw --all
+	assert_failure
+	assert_output --partial "cannot be used with"
+
+	run "$FNOX_BIN" get --keys-only
+	assert_failure
+}
@@ -0,0 +1,73 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[secrets]
+PLAIN = { default = "value" }
+TRAILING_NEWLINE = { default = "value\n" }
+TRAILING_SPACE = { default = "value  " }
+EOF_CONFIG
+}
+
+teardown() {
+	_common_teardown
+}
+
+# SHA-256 of stdin
+hash_of() {
+	if command -v sha256sum >/dev/null 2>&1; then
+		sha256sum | cut -d' ' -f1
+	else
+		shasum -a 256 | cut -d' ' -f1
+	fi
+}
+
+@test "fnox get piped outputs the exact value without a newline" {
+	actual=$("$FNOX_BIN" get PLAIN | hash_of)
+	expected=$(printf 'value' | hash_of)
+	assert_equal "$actual" "$expected"
+}
+
+@test "fnox get keeps a value's own trailing newline" {
+	actual=$("$FNOX_BIN" get TRAILING_NEWLINE | hash_of)
+	expected=$(printf 'value\n' | hash_of)
+	assert_equal "$actual" "$expected"
+}
+
+@test "fnox get keeps trailing whitespace in command substitution" {
+	value="$("$FNOX_BIN" get TRAILING_SPACE)"
+	assert_equal "$value" "value  "
+}
+
+@test "fnox get --newline adds a newline when piped" {
+	actual=$("$FNOX_BIN" get PLAIN --newline | hash_of)
+	expected=$(printf 'value\n' | hash_of)
+	assert_equal "$actual" "$expected"
+}
+
+@test "fnox get uses the last of --newline and --no-newline" {
+	actual=$("$FNOX_BIN" get PLAIN --newline --no-newline | hash_of)
+	expected=$(printf 'value' | hash_of)
+	assert_equal "$actual" "$expected"
+
+	actual=$("$FNOX_BIN" get PLAIN --no-newline --newline | hash_of)
+	expected=$(printf 'value\n' | hash_of)
+	assert_equal "$actual" "$expected"
+}
+
+@test "fnox get --ref outputs the exact value" {
+	cat >>fnox.toml <<'EOF_CONFIG'
+
+[providers.plain]
+type = "plain"
+EOF_CONFIG
+
+	actual=$("$FNOX_BIN" get --ref "raw-value" --provider plain | hash_of)
+	expected=$(printf 'raw-value' | hash_of)
+	assert_equal "$actual" "$expected"
+}
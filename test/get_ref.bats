@@ -0,0 +1,68 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+default_provider = "plain"
+
+[providers.plain]
+type = "plain"
+
+[providers.other]
+type = "plain"
+
+[secrets]
+EOF_CONFIG
+}
+
+@test "fnox get --ref resolves a reference with the named provider" {
+	write_config
+
+	run "$FNOX_BIN" get --ref "raw-value" --provider other
+	assert_success
+	assert_output "raw-value"
+}
+
+@test "fnox get --ref falls back to the default provider" {
+	write_config
+
+	run "$FNOX_BIN" get --ref "raw-value"
+	assert_success
+	assert_output "raw-value"
+}
+
+@test "fnox get --ref does not require a secrets entry" {
+	write_config
+
+	run "$FNOX_BIN" get RAW_VALUE
+	assert_failure
+
+	run "$FNOX_BIN" get --ref "cmF3LXZhbHVl" --base64-decode
+	assert_success
+	assert_output "raw-value"
+}
+
+@test "fnox get rejects KEY together with --ref" {
+	write_config
+
+	run "$FNOX_BIN" get MY_SECRET --ref "raw-value"
+	assert_failure
+	assert_output --partial "cannot be used with"
+}
+
+@test "fnox get --provider requires --ref" {
+	write_config
+
+	run "$FNOX_BIN" get MY_SECRET --provider plain
+	assert_failure
+	assert_output --partial "--ref"
+}
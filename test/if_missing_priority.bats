@@ -118,7 +118,7 @@ TOML
 	refute_output --partial "Warning:"
 }
 
-@test "fnox exec CLI flag --if-missing overrides secret-level config" {
+@test "fnox exec secret-level config overrides CLI flag --if-missing" {
 	cat >fnox.toml <<'TOML'
 root = true
 
@@ -135,9 +135,10 @@ TOML
 	# Set invalid age key to trigger error
 	export FNOX_AGE_KEY="/tmp/nonexistent-age-key.txt"
 
-	# Should fail because --if-missing error overrides secret-level if_missing=ignore
-	run "$FNOX_BIN" exec --if-missing error -- echo "should not run"
-	assert_failure
+	# Should succeed because secret-level if_missing=ignore overrides --if-missing error
+	run "$FNOX_BIN" exec --if-missing error -- echo "command succeeded"
+	assert_success
+	assert_output --partial "command succeeded"
 }
 
 @test "fnox exec secret-level if_missing overrides top-level config" {
@@ -164,7 +165,7 @@ TOML
 	assert_output --partial "command succeeded"
 }
 
-@test "fnox exec FNOX_IF_MISSING env var overrides secret-level config" {
+@test "fnox exec secret-level config overrides FNOX_IF_MISSING env var" {
 	cat >fnox.toml <<'TOML'
 root = true
 
@@ -182,9 +183,10 @@ TOML
 	export FNOX_AGE_KEY="/tmp/nonexistent-age-key.txt"
 	export FNOX_IF_MISSING="error"
 
-	# Should fail because FNOX_IF_MISSING=error overrides secret-level if_missing=ignore
-	run "$FNOX_BIN" exec -- echo "should not run"
-	assert_failure
+	# Should succeed because secret-level if_missing=ignore overrides FNOX_IF_MISSING=error
+	run "$FNOX_BIN" exec -- echo "command succeeded"
+	assert_success
+	assert_output --partial "command succeeded"
 }
 
 @test "fnox exec FNOX_IF_MISSING env var overrides top-level config" {
@@ -327,3 +329,76 @@ TOML
 	assert_success
 	assert_output --partial "command succeeded"
 }
+
+# Writes a config with top-level if_missing=ignore and a prod profile with
+# if_missing=error. Extra lines are appended to MY_SECRET.
+profile_if_missing_config() {
+	cat >fnox.toml <<TOML
+root = true
+if_missing = "ignore"
+
+[providers.age]
+type = "age"
+recipients = ["age1cdk0klj88zzhg0ncfhe4ul9ja5k58w2st3fpkhmy0f46vlsuh5wq0s0gr9"]
+
+[profiles.prod]
+if_missing = "error"
+
+[secrets.MY_SECRET]
+provider = "age"
+value = "YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSBaaTFhczNBYnN3S1c0NjZwZnlDN2NUMTVaSTFXd2k1OWhnWUJvckVxYmh3CjNRSmhxSWJiYXU3eHoyNlcyOVVLRWNnUlFJeFBjL2N0YlA5K2hUaU04VDQKLS0tIGN6UVYzMHZJUUhKNmlkQjFOaXRXYUpjbzBOaHRMZkFFVVRPa3FaQUs2dHcKf3AcueEBLdl8lzRwKXik+OvDVg48g44QoPZu0j0NLV4lPLDqoq0="
+${1:-}
+TOML
+
+	# Set invalid age key to trigger error
+	export FNOX_AGE_KEY="/tmp/nonexistent-age-key.txt"
+}
+
+@test "fnox exec profile-level if_missing overrides top-level config" {
+	profile_if_missing_config
+
+	run "$FNOX_BIN" exec --profile prod -- echo "should not run"
+	assert_failure
+
+	# Other profiles keep the top-level if_missing=ignore
+	run "$FNOX_BIN" exec -- echo "command succeeded"
+	assert_success
+	assert_output --partial "command succeeded"
+}
+
+@test "fnox exec secret-level if_missing overrides profile-level config" {
+	profile_if_missing_config 'if_missing = "ignore"'
+
+	run "$FNOX_BIN" exec --profile prod -- echo "command succeeded"
+	assert_success
+	assert_output --partial "command succeeded"
+}
+
+@test "fnox exec profile-level config overrides CLI flag --if-missing" {
+	profile_if_missing_config
+
+	run "$FNOX_BIN" exec --profile prod --if-missing ignore -- echo "should not run"
+	assert_failure
+}
+
+@test "fnox exec CLI flag --if-missing applies when the secret and profile don't set it" {
+	profile_if_missing_config
+	sed -i.bak '/^if_missing = "error"$/d' fnox.toml
+
+	# Overrides the top-level if_missing=ignore
+	run "$FNOX_BIN" exec --profile prod --if-missing error -- echo "should not run"
+	assert_failure
+
+	run "$FNOX_BIN" exec --profile prod --if-missing ignore -- echo "command succeeded"
+	assert_success
+	assert_output --partial "command succeeded"
+}
+
+@test "fnox rejects an invalid profile-level if_missing" {
+	profile_if_missing_config
+	sed -i.bak 's/if_missing = "error"/if_missing = "loud"/' fnox.toml
+
+	run "$FNOX_BIN" exec --profile prod -- echo "should not run"
+	assert_failure
+	assert_output --partial "unknown variant \`loud\`"
+}
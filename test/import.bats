@@ -305,3 +305,123 @@ EOF
 	assert_fnox_failure import -i .env --provider nonexistent --force
 	assert_output --partial "Provider 'nonexistent' not configured"
 }
+
+@test "fnox import --from-env imports variables with the prefix" {
+	setup_age_provider
+
+	export APP_DATABASE_URL="postgresql://localhost:5432/mydb"
+	export APP_API_KEY="secret-key-123"
+	export OTHER_SECRET="not-imported"
+	export FNOX_IMPORT_TEST="not-imported"
+
+	assert_fnox_success import --from-env --env-prefix APP_ --provider age --force
+	assert_config_not_contains "secret-key-123"
+
+	unset APP_DATABASE_URL APP_API_KEY
+	assert_fnox_success get APP_DATABASE_URL --age-key-file key.txt
+	assert_output "postgresql://localhost:5432/mydb"
+
+	assert_fnox_success get APP_API_KEY --age-key-file key.txt
+	assert_output "secret-key-123"
+
+	assert_config_not_contains "OTHER_SECRET"
+	assert_config_not_contains "FNOX_IMPORT_TEST"
+}
+
+@test "fnox import --from-env with --allowlist imports only the listed variables" {
+	setup_age_provider
+
+	export API_KEY="secret-key-123"
+	export UNLISTED_SECRET="not-imported"
+	cat >allowlist.txt <<EOF
+# Secrets to move into fnox
+API_KEY
+EOF
+
+	assert_fnox_success import --from-env --allowlist allowlist.txt --provider age --dry-run
+	assert_output --partial "API_KEY"
+	refute_output --partial "UNLISTED_SECRET"
+}
+
+@test "fnox import --from-env warns before overwriting existing secrets" {
+	setup_age_provider
+
+	export API_KEY="first"
+	echo API_KEY >allowlist.txt
+	assert_fnox_success import --from-env --allowlist allowlist.txt --provider age --force
+
+	export API_KEY="second"
+	run bash -c "echo 'n' | $FNOX_BIN import --from-env --env-prefix API_KEY --provider age"
+	assert_output --partial "API_KEY (overwrites existing)"
+	assert_output --partial "Import cancelled"
+
+	assert_fnox_success get API_KEY --age-key-file key.txt
+	assert_output "first"
+}
+
+@test "fnox import --from-dotenv keeps comments as descriptions" {
+	setup_age_provider
+
+	cat >.env <<'EOF'
+# Primary database
+DATABASE_URL="postgresql://localhost:5432/mydb"
+export API_KEY='secret-key-123' # rotated monthly
+TLS_KEY="line one
+line two"
+EOF
+
+	assert_fnox_success import --from-dotenv .env --provider age --force
+	assert_config_not_contains "secret-key-123"
+	assert_config_contains 'description = "Primary database"'
+	assert_config_not_contains "rotated monthly"
+
+	assert_fnox_success get API_KEY --age-key-file key.txt
+	assert_output "secret-key-123"
+
+	assert_fnox_success get TLS_KEY --age-key-file key.txt
+	assert_output "line one
+line two"
+}
+
+@test "fnox import --from-dotenv points at unparseable lines" {
+	setup_age_provider
+
+	cat >.env <<'EOF'
+GOOD=1
+BAD="never closed
+EOF
+
+	assert_fnox_failure import --from-dotenv .env --provider age --dry-run
+	assert_output --partial "'BAD' has no closing \" quote"
+	assert_config_not_contains "GOOD"
+}
+
+@test "fnox import -i parses .env files like --from-dotenv" {
+	setup_age_provider
+
+	cat >.env <<'EOF'
+GOOD=pa#ss # comment
+BAD="never closed
+EOF
+
+	assert_fnox_failure import -i .env --provider age --dry-run
+	assert_output --partial "'BAD' has no closing \" quote"
+	assert_config_not_contains "GOOD"
+}
+
+@test "fnox import keeps .env keys in file order and escapes as written" {
+	setup_age_provider
+
+	cat >.env <<'EOF'
+B=second
+A="x\$y"
+EOF
+
+	assert_fnox_success import -i .env --provider age --force
+	run bash -c "grep -o '^[AB] *=' fnox.toml | cut -c1"
+	assert_output "B
+A"
+
+	assert_fnox_success get A --age-key-file key.txt
+	assert_output 'x\$y'
+}
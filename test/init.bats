@@ -50,3 +50,45 @@ teardown() {
 	# Clean up
 	rm -rf "$isolated_dir"
 }
+
+@test "fnox init --template adds secret stubs that fnox check reports" {
+	assert_fnox_success init --template node
+	assert_output --partial "template secrets"
+	run grep 'DATABASE_URL' fnox.toml
+	assert_success
+	assert_output --partial 'if_missing = "error"'
+	refute_output --partial "default"
+
+	assert_fnox_failure check
+	assert_output --partial "DATABASE_URL"
+}
+
+@test "fnox init --template-file uses a custom template" {
+	cat >template.toml <<'EOF_TEMPLATE'
+[secrets]
+STRIPE_KEY = { description = "Stripe API key" }
+FEATURE_FLAGS_TOKEN = { required = false }
+EOF_TEMPLATE
+
+	assert_fnox_success init --template-file template.toml
+	run grep 'STRIPE_KEY' fnox.toml
+	assert_output --partial 'description = "Stripe API key"'
+	run grep 'FEATURE_FLAGS_TOKEN' fnox.toml
+	assert_output --partial 'if_missing = "warn"'
+}
+
+@test "fnox init --template-file rejects templates with values" {
+	cat >template.toml <<'EOF_TEMPLATE'
+[secrets]
+STRIPE_KEY = { default = "sk_live_123" }
+EOF_TEMPLATE
+
+	assert_fnox_failure init --template-file template.toml
+	assert_output --partial "Invalid template"
+	assert_file_not_exists "fnox.toml"
+}
+
+@test "fnox init --template-file rejects plain http URLs" {
+	assert_fnox_failure init --template-file http://example.com/template.toml
+	assert_output --partial "must use https://"
+}
@@ -435,3 +435,81 @@ EOF
 	assert_success
 	assert_output "nested-value"
 }
+
+@test "fnox history lists previous KeePass values" {
+	create_keepass_config
+
+	run "$FNOX_BIN" set ROTATED "first-value" --provider keepass
+	assert_success
+	run "$FNOX_BIN" set ROTATED "second-value" --provider keepass
+	assert_success
+	run "$FNOX_BIN" set ROTATED "third-value" --provider keepass
+	assert_success
+	track_entry_name "ROTATED"
+
+	run "$FNOX_BIN" history ROTATED
+	assert_success
+	assert_output --partial "current"
+	refute_output --partial "first-value"
+	refute_output --partial "third-value"
+
+	run "$FNOX_BIN" history ROTATED --show-values
+	assert_success
+	assert_output --partial "first-value"
+	assert_output --partial "second-value"
+	assert_output --partial "third-value"
+}
+
+@test "fnox history fails for providers without history" {
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+[providers.plain]
+type = "plain"
+
+[secrets]
+PLAIN_SECRET = { provider = "plain", value = "plain-value" }
+EOF
+
+	run "$FNOX_BIN" history PLAIN_SECRET
+	assert_failure
+	assert_output --partial "Can't show the history of 'PLAIN_SECRET' from provider 'plain'"
+}
+
+@test "fnox import --from-provider references every KeePass entry" {
+	create_keepass_config
+
+	run "$FNOX_BIN" set DB_PASSWORD "hunter2" --provider keepass --key-name db-password
+	assert_success
+	run "$FNOX_BIN" set API_TOKEN "tok-123" --provider keepass --key-name api-token
+	assert_success
+
+	# Start over with only the provider configured
+	create_keepass_config
+
+	run "$FNOX_BIN" provider list --keys keepass
+	assert_success
+	assert_output "api-token
+db-password"
+
+	run "$FNOX_BIN" import --from-provider keepass --force
+	assert_success
+	assert_output --partial "Imported 2 secrets"
+	assert_config_contains 'value = "db-password"'
+
+	run "$FNOX_BIN" get DB_PASSWORD
+	assert_success
+	assert_output "hunter2"
+	run "$FNOX_BIN" get API_TOKEN
+	assert_success
+	assert_output "tok-123"
+}
+
+@test "fnox import --from-provider fails for providers that can't list" {
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+[providers.plain]
+type = "plain"
+EOF
+
+	run "$FNOX_BIN" import --from-provider plain --force
+	assert_failure
+	assert_output --partial "can't list the secrets it stores"
+}
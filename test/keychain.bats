@@ -403,3 +403,121 @@ EOF
 	assert_failure
 	assert_output --partial "MISSING_SECRET"
 }
+
+@test "prefixed keychain secret round-trips through set, get, list and exec" {
+	create_keychain_config "$KEYCHAIN_SERVICE" "myapp/"
+	track_secret "myapp/ROUND_TRIP"
+
+	# The config stores the key as given; the prefix is added on every request
+	run "$FNOX_BIN" set ROUND_TRIP "round-trip-value" --provider keychain --key-name "ROUND_TRIP"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial 'value = "ROUND_TRIP"'
+	refute_output --partial 'value = "myapp/ROUND_TRIP"'
+
+	run "$FNOX_BIN" get ROUND_TRIP
+	assert_success
+	assert_output "round-trip-value"
+
+	run "$FNOX_BIN" list --values
+	assert_success
+	assert_output --partial "round-trip-value"
+
+	run bash -c "'$FNOX_BIN' exec -- bash -c 'echo \$ROUND_TRIP' 2>/dev/null"
+	assert_success
+	assert_output "round-trip-value"
+}
+
+@test "keychain reference that starts with the prefix is still prefixed" {
+	create_keychain_config "$KEYCHAIN_SERVICE" "myapp/"
+	track_secret "myapp/myapp/FULL_NAME"
+
+	run "$FNOX_BIN" set FULL_NAME "full-name-value" --provider keychain --key-name "myapp/FULL_NAME"
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial 'value = "myapp/FULL_NAME"'
+
+	run "$FNOX_BIN" get FULL_NAME
+	assert_success
+	assert_output "full-name-value"
+
+	# The remote name carries the prefix twice
+	create_keychain_config "$KEYCHAIN_SERVICE"
+	echo 'FULL_NAME = { provider = "keychain", value = "myapp/myapp/FULL_NAME" }' >>"${FNOX_CONFIG_FILE}"
+	run "$FNOX_BIN" get FULL_NAME
+	assert_success
+	assert_output "full-name-value"
+}
+
+# Helper to create a local provider config keeping its key under the test service
+create_local_config() {
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+[providers.local]
+type = "local"
+service = "$KEYCHAIN_SERVICE"
+
+[secrets]
+EOF
+	track_secret "data-key"
+	track_secret "data-key.previous"
+}
+
+@test "local provider encrypts with a key generated in the keychain" {
+	create_local_config
+
+	run "$FNOX_BIN" set LOCAL_SECRET "local-secret-value" --provider local
+	assert_success
+
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial 'value = "local:v1:'
+	refute_output --partial "local-secret-value"
+
+	run "$FNOX_BIN" get LOCAL_SECRET
+	assert_success
+	assert_output "local-secret-value"
+}
+
+@test "fnox provider rekey re-encrypts local provider values" {
+	create_local_config
+
+	run "$FNOX_BIN" set FIRST "first-value" --provider local
+	assert_success
+	run "$FNOX_BIN" set SECOND "second-value" --provider local
+	assert_success
+	local before
+	before="$(cat "${FNOX_CONFIG_FILE}")"
+
+	run "$FNOX_BIN" provider rekey local
+	assert_success
+	assert_output --partial "re-encrypted 2 secrets"
+	[ "$(cat "${FNOX_CONFIG_FILE}")" != "$before" ]
+
+	run "$FNOX_BIN" get FIRST
+	assert_success
+	assert_output "first-value"
+	run "$FNOX_BIN" get SECOND
+	assert_success
+	assert_output "second-value"
+}
+
+@test "local provider reports a lost key as unrecoverable" {
+	create_local_config
+
+	run "$FNOX_BIN" set LOST "lost-value" --provider local
+	assert_success
+
+	if [[ $PLATFORM == "Darwin" ]]; then
+		security delete-generic-password -s "$KEYCHAIN_SERVICE" -a "data-key" >/dev/null 2>&1
+	elif command -v secret-tool >/dev/null 2>&1; then
+		secret-tool clear service "$KEYCHAIN_SERVICE" account "data-key"
+	else
+		skip "secret-tool not available to delete the key"
+	fi
+
+	run "$FNOX_BIN" get LOST
+	assert_failure
+	assert_output --partial "key is missing from the OS keychain"
+	assert_output --partial "unrecoverable"
+}
@@ -56,6 +56,27 @@ EOF
 	assert_output --partial "My test secret"
 }
 
+@test "fnox list escapes control characters in keys and descriptions" {
+	create_test_config
+
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF'
+
+[secrets."TITLE\u001b]0;pwned\u0007"]
+default = "x"
+description = "Retitles\u001b]0;pwned\u0007\nit"
+
+[secrets."数据库_URL"]
+default = "y"
+description = "Wide key"
+EOF
+
+	assert_fnox_success list
+	refute_output --partial $'\e'
+	refute_output --partial $'\a'
+	assert_output --partial 'TITLE\u{1b}]0;pwned\u{7}'
+	assert_output --partial 'Retitles\u{1b}]0;pwned\u{7}\nit'
+}
+
 @test "fnox list shows provider information" {
 	create_test_config
 
@@ -189,7 +210,7 @@ EOF
 	# Without --full, should be truncated
 	run "$FNOX_BIN" list
 	assert_success
-	assert_output --partial "this-is-a-very-long-provider-key-that..."
+	assert_output --partial "this-is-a-very-long-provider-key-that-e…"
 
 	# With --full, should show complete key
 	run "$FNOX_BIN" list --full
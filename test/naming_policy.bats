@@ -0,0 +1,79 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF2'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[settings.naming]
+require_prefix = "APP_"
+
+[settings.naming.profile_prefixes]
+prod = "PROD_"
+
+[secrets]
+EOF2
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "fnox set rejects a key that breaks the naming policy" {
+	assert_fnox_failure set databaseUrl "postgres://localhost" --provider plain
+	assert_output --partial "databaseUrl"
+	assert_output --partial "APP_DATABASE_URL"
+
+	run cat "${FNOX_CONFIG_FILE:-fnox.toml}"
+	refute_output --partial "databaseUrl"
+}
+
+@test "fnox set accepts a key that follows the naming policy" {
+	assert_fnox_success set APP_DATABASE_URL "postgres://localhost" --provider plain
+}
+
+@test "fnox set uses the profile's prefix" {
+	assert_fnox_failure set APP_TOKEN "secret" --provider plain --profile prod
+	assert_output --partial "PROD_"
+	assert_fnox_success set PROD_TOKEN "secret" --provider plain --profile prod
+}
+
+@test "fnox check flags existing keys that break the naming policy" {
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF2'
+APP_TOKEN = { default = "ok" }
+api_key = { default = "bad" }
+EOF2
+
+	assert_fnox_failure check
+	assert_output --partial "api_key"
+	refute_output --partial "Secret 'APP_TOKEN'"
+}
+
+@test "fnox import refuses keys that break the naming policy" {
+	cat >.env <<'EOF2'
+databaseUrl=postgres://localhost
+EOF2
+
+	assert_fnox_failure import -i .env --provider plain --force
+	assert_output --partial "databaseUrl"
+	assert_output --partial "--fix-names"
+}
+
+@test "fnox import --fix-names renames keys to follow the naming policy" {
+	cat >.env <<'EOF2'
+databaseUrl=postgres://localhost
+api-key=secret
+EOF2
+
+	assert_fnox_success import -i .env --provider plain --force --fix-names
+
+	run cat "${FNOX_CONFIG_FILE:-fnox.toml}"
+	assert_output --partial "APP_DATABASE_URL"
+	assert_output --partial "APP_API_KEY"
+	refute_output --partial "databaseUrl"
+}
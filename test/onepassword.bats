@@ -279,7 +279,7 @@ EOF
 
 	run "$FNOX_BIN" get INVALID_FORMAT
 	assert_failure
-	assert_output --partial "Invalid secret reference format"
+	assert_output --partial "invalid reference 'invalid/format/with/too/many/slashes'"
 }
 
 @test "1Password provider works with service account token from environment" {
@@ -307,3 +307,30 @@ EOF
 	# Cleanup
 	delete_test_op_item "fnox" "$item_name"
 }
+
+@test "fnox set creates and updates a 1Password item" {
+	create_onepassword_config "fnox"
+	local item_name="FNOX_SET_TEST_$(date +%s)_$$"
+
+	run "$FNOX_BIN" set "$item_name" "first-value-$$" --provider onepass
+	assert_success
+
+	# The config stores the canonical reference
+	run cat "${FNOX_CONFIG_FILE}"
+	assert_output --partial "op://fnox/$item_name/password"
+
+	run op read "op://fnox/$item_name/password"
+	assert_success
+	assert_output "first-value-$$"
+
+	# Setting it again edits the existing item
+	run "$FNOX_BIN" set "$item_name" "second-value-$$" --provider onepass
+	assert_success
+
+	run "$FNOX_BIN" get "$item_name"
+	assert_success
+	assert_output "second-value-$$"
+
+	# Cleanup
+	delete_test_op_item "fnox" "$item_name"
+}
@@ -0,0 +1,88 @@
+#!/usr/bin/env bats
+#
+# 1Password batch resolution, run against a fake `op` that records its
+# arguments. See onepassword.bats for tests against a real 1Password account.
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	export OP_LOG="$TEST_TEMP_DIR/op.log"
+	mkdir -p "$TEST_TEMP_DIR/bin"
+	cat >"$TEST_TEMP_DIR/bin/op" <<'EOF'
+#!/usr/bin/env bash
+echo "$1" >>"$OP_LOG"
+if [ -n "$OP_FAIL_AUTH" ]; then
+	echo "[ERROR] 2024/01/01 00:00:00 You are not currently signed in. Please run \`op signin --help\` for instructions" >&2
+	exit 1
+fi
+# Every item resolves to "<item>-value", except items named "missing"
+missing() {
+	echo "[ERROR] 2024/01/01 00:00:00 \"missing\" isn't an item in the \"Private\" vault." >&2
+	exit 1
+}
+case "$1" in
+inject)
+	input=$(cat)
+	case "$input" in *op://Private/missing/*) missing ;; esac
+	echo "$input" | sed -E 's#op://Private/([^/]+)/[^/]+#\1-value#'
+	;;
+read)
+	case "$2" in op://Private/missing/*) missing ;; esac
+	echo "$2" | sed -E 's#op://Private/([^/]+)/[^/]+#\1-value#'
+	;;
+esac
+EOF
+	chmod +x "$TEST_TEMP_DIR/bin/op"
+	export PATH="$TEST_TEMP_DIR/bin:$PATH"
+
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF'
+root = true
+
+[providers.onepass]
+type = "1password"
+vault = "Private"
+
+[secrets]
+DB_PASSWORD = { provider = "onepass", value = "db" }
+API_TOKEN = { provider = "onepass", value = "api/credential" }
+STRIPE_KEY = { provider = "onepass", value = "op://Private/stripe/password" }
+EOF
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "fnox exec resolves 1Password secrets with one op inject" {
+	run "$FNOX_BIN" exec -- env
+	assert_success
+	assert_line "DB_PASSWORD=db-value"
+	assert_line "API_TOKEN=api-value"
+	assert_line "STRIPE_KEY=stripe-value"
+
+	run cat "$OP_LOG"
+	assert_output "inject"
+}
+
+@test "fnox exec reports a bad 1Password reference for its key only" {
+	cat >>"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF'
+GONE = { provider = "onepass", value = "missing", if_missing = "warn" }
+EOF
+
+	run "$FNOX_BIN" exec -- env
+	assert_success
+	assert_line "DB_PASSWORD=db-value"
+	assert_line "STRIPE_KEY=stripe-value"
+	assert_output --partial "GONE"
+	refute_line --partial "GONE="
+}
+
+@test "fnox exec doesn't retry each 1Password reference when op is not signed in" {
+	export OP_FAIL_AUTH=1
+	run "$FNOX_BIN" exec -- env
+	refute_line --partial "DB_PASSWORD="
+
+	run cat "$OP_LOG"
+	assert_output "inject"
+}
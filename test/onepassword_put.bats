@@ -0,0 +1,98 @@
+#!/usr/bin/env bats
+#
+# 1Password write support, run against a fake `op` that records its arguments.
+# See onepassword.bats for tests against a real 1Password account.
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	export OP_LOG="$TEST_TEMP_DIR/op.log"
+	export OP_TEMPLATE_OUT="$TEST_TEMP_DIR/template.json"
+	export OP_ITEM_JSON="$TEST_TEMP_DIR/item.json"
+	mkdir -p "$TEST_TEMP_DIR/bin"
+	cat >"$TEST_TEMP_DIR/bin/op" <<'EOF'
+#!/usr/bin/env bash
+echo "$*" >>"$OP_LOG"
+if [ -n "$OP_FAIL_AUTH" ]; then
+	echo "[ERROR] 2024/01/01 00:00:00 You are not currently signed in. Please run \`op signin --help\` for instructions" >&2
+	exit 1
+fi
+case "$1 $2" in
+"item get")
+	if [ -f "$OP_ITEM_JSON" ]; then
+		cat "$OP_ITEM_JSON"
+	else
+		echo "[ERROR] 2024/01/01 00:00:00 \"$3\" isn't an item in the \"Private\" vault. Specify the item with its UUID, name, or domain." >&2
+		exit 1
+	fi
+	;;
+"item create" | "item edit")
+	while [ $# -gt 0 ]; do
+		if [ "$1" = "--template" ]; then
+			cp "$2" "$OP_TEMPLATE_OUT"
+		fi
+		shift
+	done
+	echo "{}"
+	;;
+esac
+EOF
+	chmod +x "$TEST_TEMP_DIR/bin/op"
+	export PATH="$TEST_TEMP_DIR/bin:$PATH"
+
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<'EOF'
+root = true
+
+[providers.onepass]
+type = "1password"
+vault = "Private"
+EOF
+}
+
+teardown() {
+	_common_teardown
+}
+
+@test "fnox set creates a missing 1Password item" {
+	assert_fnox_success set STRIPE_KEY "sk_test_123" --provider onepass
+
+	run cat "$OP_LOG"
+	assert_line --index 0 "item get STRIPE_KEY --vault Private --format json"
+	assert_line --index 1 --regexp "^item create --vault Private --template .+"
+	refute_output --partial "sk_test_123"
+
+	run cat "$OP_TEMPLATE_OUT"
+	assert_output --partial '"title":"STRIPE_KEY"'
+	assert_output --partial '"value":"sk_test_123"'
+
+	run cat "${FNOX_CONFIG_FILE:-fnox.toml}"
+	assert_output --partial 'op://Private/STRIPE_KEY/password'
+}
+
+@test "fnox set updates a field of an existing 1Password item" {
+	cat >"$OP_ITEM_JSON" <<'EOF'
+{"id":"abc123","title":"Database","fields":[{"id":"username","label":"username","value":"old"}]}
+EOF
+
+	assert_fnox_success set DB_USER "admin" --provider onepass --key-name "Database/username"
+
+	run cat "$OP_LOG"
+	assert_line --index 1 --regexp "^item edit abc123 --vault Private --template .+"
+	refute_output --partial "admin"
+
+	run cat "$OP_TEMPLATE_OUT"
+	assert_output --partial '"value":"admin"'
+	refute_output --partial '"value":"old"'
+
+	run cat "${FNOX_CONFIG_FILE:-fnox.toml}"
+	assert_output --partial 'op://Private/Database/username'
+}
+
+@test "fnox set reports when op is not signed in" {
+	export OP_FAIL_AUTH=1
+	run "$FNOX_BIN" set STRIPE_KEY "sk_test_123" --provider onepass
+	assert_failure
+	assert_output --partial "authentication failed"
+	assert_output --partial "op signin"
+}
@@ -0,0 +1,100 @@
+#!/usr/bin/env bats
+
+load 'test_helper/common_setup'
+
+setup() {
+	_common_setup --git
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'TOML'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+DATABASE_URL = { provider = "plain", value = "postgres://localhost" }
+TOML
+}
+
+@test "fnox export --output refuses a path git would commit" {
+	write_config
+
+	assert_fnox_failure export --output .env
+	assert_output --partial "which git would commit"
+	assert_file_not_exists .env
+}
+
+@test "fnox export --output writes a gitignored path" {
+	write_config
+	echo '.env' >.gitignore
+
+	assert_fnox_success export --output .env
+	assert_file_contains .env "DATABASE_URL=postgres://localhost"
+}
+
+@test "fnox export --output refuses a tracked file even when gitignored" {
+	write_config
+	echo 'old' >secrets.env
+	git add secrets.env
+	echo 'secrets.env' >.gitignore
+
+	assert_fnox_failure export --output secrets.env
+	assert_output --partial "which git would commit"
+}
+
+@test "fnox export --output --force writes a path git would commit" {
+	write_config
+
+	assert_fnox_success export --output .env --force
+	assert_file_contains .env "DATABASE_URL=postgres://localhost"
+}
+
+@test "fnox export --output skips the check when FNOX_EXPORT_GIT_GUARD is false" {
+	write_config
+
+	run env FNOX_EXPORT_GIT_GUARD=false "$FNOX_BIN" export --output .env
+	assert_success
+	assert_file_exists .env
+}
+
+@test "fnox export --output allows committing a preview" {
+	write_config
+
+	assert_fnox_success export --format preview --output fnox.preview
+	assert_file_exists fnox.preview
+}
+
+@test "fnox export --output writes anywhere outside a git repository" {
+	write_config
+	local outside
+	outside="$(mktemp -d)"
+
+	assert_fnox_success export --output "$outside/app.env"
+	assert_file_contains "$outside/app.env" "DATABASE_URL=postgres://localhost"
+	rm -rf "$outside"
+}
+
+@test "fnox get --output writes the value to a private file" {
+	write_config
+
+	assert_fnox_success get DATABASE_URL --output db.txt
+	refute_output --partial "postgres://localhost"
+	run cat db.txt
+	assert_output "postgres://localhost"
+	run bash -c "stat -c '%a' db.txt 2>/dev/null || stat -f '%OLp' db.txt"
+	assert_output "600"
+}
+
+@test "fnox get does not warn when stdout is not a terminal" {
+	write_config
+
+	assert_fnox_success get DATABASE_URL
+	assert_output "postgres://localhost"
+	refute_output --partial "scrollback"
+}
@@ -83,8 +83,10 @@ teardown() {
 }
 
 # Helper function to create a password-store provider config
+# Pass "whole_entry" as the second argument to return whole entries
 create_pass_config() {
 	local prefix="${1:-}"
+	local whole_entry="${2:-}"
 	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
 [providers.pass]
 type = "password-store"
@@ -96,6 +98,12 @@ prefix = "$prefix"
 EOF
 	fi
 
+	if [ "$whole_entry" = "whole_entry" ]; then
+		cat >>"${FNOX_CONFIG_FILE}" <<EOF
+whole_entry = true
+EOF
+	fi
+
 	cat >>"${FNOX_CONFIG_FILE}" <<EOF
 
 [secrets]
@@ -199,7 +207,7 @@ EOF
 }
 
 @test "fnox set with multiline value" {
-	create_pass_config
+	create_pass_config "" whole_entry
 
 	local multiline_value="line1
 line2
@@ -221,6 +229,14 @@ line3"
 	assert_output "$multiline_value"
 }
 
+@test "fnox set refuses a multiline value without whole_entry" {
+	create_pass_config
+
+	run bash -c "printf 'line1\nline2\n' | '$FNOX_BIN' set MULTILINE --provider pass"
+	assert_failure
+	assert_output --partial "whole_entry = true"
+}
+
 @test "fnox set updates existing secret" {
 	create_pass_config
 
@@ -429,7 +445,7 @@ EOF
 }
 
 @test "fnox get with line selects a single line of a multiline entry" {
-	create_pass_config
+	create_pass_config "" whole_entry
 
 	# Store a multi-line entry directly via `pass insert -m`, mimicking
 	# the `pass` convention of password-on-line-1, metadata below.
@@ -477,3 +493,69 @@ EOF
 	assert_failure
 	assert_output --partial "out of range"
 }
+
+@test "bare references return the first line of a multiline entry" {
+	create_pass_config
+
+	run bash -c "printf 'hunter2\nusername: alice\n' | pass insert -m -f web-login"
+	assert_success
+	track_secret_path "web-login"
+
+	cat >>"${FNOX_CONFIG_FILE}" <<EOF
+WEB_PASSWORD = { provider = "pass", value = "web-login" }
+EOF
+
+	run "$FNOX_BIN" get WEB_PASSWORD
+	assert_success
+	assert_output "hunter2"
+}
+
+@test "references select fields and lines of an entry" {
+	create_pass_config
+
+	run bash -c "printf 'hunter2\nUsername: alice\nurl: https://example.com\n' | pass insert -m -f web-login"
+	assert_success
+	track_secret_path "web-login"
+
+	cat >>"${FNOX_CONFIG_FILE}" <<EOF
+WEB_USER = { provider = "pass", value = "web-login#username" }
+WEB_URL  = { provider = "pass", value = "web-login#line:3" }
+WEB_MAIL = { provider = "pass", value = "web-login#email" }
+EOF
+
+	run "$FNOX_BIN" get WEB_USER
+	assert_success
+	assert_output "alice"
+
+	run "$FNOX_BIN" get WEB_URL
+	assert_success
+	assert_output "url: https://example.com"
+
+	run "$FNOX_BIN" get WEB_MAIL
+	assert_failure
+	assert_output --partial "no 'email: ...' line"
+}
+
+@test "fnox set rewrites only the first line of a structured entry" {
+	create_pass_config
+
+	run bash -c "printf 'hunter2\nusername: alice\n' | pass insert -m -f web-login"
+	assert_success
+	track_secret_path "web-login"
+
+	run "$FNOX_BIN" set WEB_PASSWORD "correct-horse" --provider pass --key-name web-login
+	assert_success
+
+	run pass show web-login
+	assert_success
+	assert_output "correct-horse
+username: alice"
+
+	run "$FNOX_BIN" set WEB_USER "bob" --provider pass --key-name "web-login#username"
+	assert_success
+
+	run pass show web-login
+	assert_success
+	assert_output "correct-horse
+username: bob"
+}
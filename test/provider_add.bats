@@ -84,3 +84,71 @@ vault|vault|vault"
 	assert_failure
 	assert_output --partial "--vault is only supported for provider type"
 }
+
+@test "fnox provider add --migrate sets the default and moves plaintext secrets" {
+	cat >fnox.toml <<'EOF'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+API_KEY = { provider = "plain", value = "plaintext-api-key" }
+LOG_LEVEL = { default = "debug" }
+EOF
+
+	run "$FNOX_BIN" provider add dotenv envfile --migrate
+	assert_success
+	assert_output --partial "Set 'dotenv' as the default provider"
+	assert_output --partial "API_KEY"
+	assert_output --partial "LOG_LEVEL"
+	assert_output --partial "Moved 2 secrets to 'dotenv'"
+
+	assert_config_contains 'default_provider = "dotenv"'
+	assert_config_not_contains "plaintext-api-key"
+	assert_config_not_contains 'default = "debug"'
+	run grep -x "API_KEY=plaintext-api-key" .env
+	assert_success
+
+	run "$FNOX_BIN" get API_KEY
+	assert_success
+	assert_output "plaintext-api-key"
+}
+
+@test "fnox provider add --migrate leaves secrets in place when the provider can't store them" {
+	cat >fnox.toml <<'EOF'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+API_KEY = { provider = "plain", value = "plaintext-api-key" }
+EOF
+
+	# The template's placeholder recipient can't encrypt anything
+	run "$FNOX_BIN" provider add age age --migrate
+	assert_failure
+	refute_output --partial "Moved"
+
+	assert_config_contains 'provider = "plain", value = "plaintext-api-key"'
+}
+
+@test "fnox provider add without --migrate leaves secrets untouched when not interactive" {
+	cat >fnox.toml <<'EOF'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+API_KEY = { provider = "plain", value = "plaintext-api-key" }
+EOF
+
+	run "$FNOX_BIN" provider add age age </dev/null
+	assert_success
+	refute_output --partial "stored in plaintext"
+
+	assert_config_contains "plaintext-api-key"
+	assert_config_not_contains "default_provider"
+}
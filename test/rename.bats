@@ -0,0 +1,74 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+# first
+FIRST = { provider = "plain", value = "one" }
+# the one to rename
+OLD_NAME = { provider = "plain", value = "two" }
+LAST = { provider = "plain", value = "three" }
+EOF_CONFIG
+}
+
+@test "fnox rename keeps the secret's position, value and comments" {
+	write_config
+
+	run "$FNOX_BIN" rename OLD_NAME NEW_NAME
+	assert_success
+	assert_output --partial "Renamed secret OLD_NAME to NEW_NAME"
+
+	run cat fnox.toml
+	assert_line --index 6 "# the one to rename"
+	assert_line --index 7 'NEW_NAME = { provider = "plain", value = "two" }'
+	refute_output --partial "OLD_NAME"
+
+	run "$FNOX_BIN" get NEW_NAME
+	assert_success
+	assert_output "two"
+}
+
+@test "fnox rename refuses to overwrite an existing secret" {
+	write_config
+
+	run "$FNOX_BIN" rename OLD_NAME LAST
+	assert_failure
+	assert_output --partial "Secret 'LAST' already exists"
+
+	run grep -c "OLD_NAME" fnox.toml
+	assert_output "1"
+}
+
+@test "fnox rename fails for an unknown secret" {
+	write_config
+
+	run "$FNOX_BIN" rename MISSING NEW_NAME
+	assert_failure
+	assert_output --partial "Secret 'MISSING' not found"
+}
+
+@test "fnox rename --dry-run leaves the config unchanged" {
+	write_config
+	cp fnox.toml before.toml
+
+	run "$FNOX_BIN" rename --dry-run OLD_NAME NEW_NAME
+	assert_success
+	assert_output --partial "[dry-run] Would rename secret OLD_NAME to NEW_NAME"
+
+	run diff before.toml fnox.toml
+	assert_success
+}
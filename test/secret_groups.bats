@@ -0,0 +1,130 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[providers.plain]
+type = "plain"
+
+[secrets]
+DATABASE_URL = { provider = "plain", value = "postgres://localhost" }
+
+# Stripe credentials
+[secrets.stripe]
+provider = "plain"
+api_key = { value = "sk_test" }
+webhook_secret = { value = "whsec", env = false }
+
+[secrets.stripe.live]
+key = { value = "sk_live" }
+EOF_CONFIG
+}
+
+@test "grouped secrets are flattened to prefixed names" {
+	write_config
+
+	run "$FNOX_BIN" get STRIPE_API_KEY
+	assert_success
+	assert_output "sk_test"
+
+	run "$FNOX_BIN" get STRIPE_LIVE_KEY
+	assert_success
+	assert_output "sk_live"
+
+	run "$FNOX_BIN" exec -- sh -c 'echo "$STRIPE_API_KEY ${STRIPE_WEBHOOK_SECRET:-unset}"'
+	assert_success
+	assert_output "sk_test unset"
+}
+
+@test "secret_groups configures the separator and case" {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+secret_groups = { separator = "__", case = "preserve" }
+
+[providers.plain]
+type = "plain"
+
+[secrets.stripe]
+provider = "plain"
+api_key = { value = "sk_test" }
+EOF_CONFIG
+
+	run "$FNOX_BIN" get stripe__api_key
+	assert_success
+	assert_output "sk_test"
+}
+
+@test "fnox set writes new and existing secrets into their group" {
+	write_config
+
+	run "$FNOX_BIN" set STRIPE_PUBLISHABLE_KEY pk_test --provider plain
+	assert_success
+	run "$FNOX_BIN" set STRIPE_API_KEY sk_rotated --provider plain
+	assert_success
+
+	run cat fnox.toml
+	assert_output --partial 'api_key= { value = "sk_rotated" }'
+	assert_output --partial 'publishable_key= { value = "pk_test" }'
+	refute_output --partial "STRIPE_"
+
+	run "$FNOX_BIN" get STRIPE_PUBLISHABLE_KEY
+	assert_success
+	assert_output "pk_test"
+}
+
+@test "fnox remove and rename edit grouped secrets in place" {
+	write_config
+
+	run "$FNOX_BIN" rename STRIPE_API_KEY STRIPE_SECRET_KEY
+	assert_success
+	run "$FNOX_BIN" remove STRIPE_LIVE_KEY
+	assert_success
+
+	run cat fnox.toml
+	assert_output --partial 'secret_key = { value = "sk_test" }'
+	refute_output --partial "[secrets.stripe.live]"
+
+	run "$FNOX_BIN" get STRIPE_SECRET_KEY
+	assert_success
+	assert_output "sk_test"
+
+	# Renaming out of the group has to be done by hand
+	run "$FNOX_BIN" rename STRIPE_SECRET_KEY OTHER
+	assert_failure
+	assert_output --partial "not in secret group 'stripe'"
+}
+
+@test "fnox list --grouped shows the group hierarchy" {
+	write_config
+
+	run "$FNOX_BIN" list --grouped
+	assert_success
+	assert_output --partial "[stripe]"
+	assert_output --partial "  STRIPE_API_KEY"
+	assert_output --partial "  [stripe.live]"
+	assert_output --partial "    STRIPE_LIVE_KEY"
+}
+
+@test "group settings that only make sense on a secret are rejected" {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+
+[secrets.stripe]
+value = "oops"
+api_key = { default = "x" }
+EOF_CONFIG
+
+	run "$FNOX_BIN" get STRIPE_API_KEY
+	assert_failure
+	assert_output --partial "Secret group 'stripe' can't set 'value'"
+}
@@ -0,0 +1,73 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+write_config() {
+	cat >fnox.toml <<'EOF_CONFIG'
+root = true
+default_provider = "age"
+
+[providers.age]
+type = "age"
+recipients = ["age1cdk0klj88zzhg0ncfhe4ul9ja5k58w2st3fpkhmy0f46vlsuh5wq0s0gr9"]
+
+[providers.op]
+type = "1password"
+vault = "Private"
+EOF_CONFIG
+}
+
+@test "fnox set warns when a value to encrypt looks like a provider reference" {
+	write_config
+
+	run "$FNOX_BIN" set DB_PASSWORD "op://Private/db/password"
+	assert_success
+	assert_output --partial 'this looks like a 1Password reference — did you mean `--provider op --raw-ref`?'
+	assert_output --partial "Set secret DB_PASSWORD"
+
+	run grep 'op://' fnox.toml
+	assert_failure
+}
+
+@test "fnox set --no-guess doesn't warn" {
+	write_config
+
+	run "$FNOX_BIN" set DB_PASSWORD "op://Private/db/password" --no-guess
+	assert_success
+	refute_output --partial "looks like"
+}
+
+@test "fnox set doesn't warn about ordinary URLs" {
+	write_config
+
+	run "$FNOX_BIN" set WEBHOOK_URL "https://example.com/hooks/abc?token=xyz"
+	assert_success
+	refute_output --partial "looks like"
+}
+
+@test "fnox set --raw-ref stores the reference as-is" {
+	write_config
+
+	run "$FNOX_BIN" set DB_PASSWORD "op://Private/db/password" --provider op --raw-ref
+	assert_success
+	refute_output --partial "looks like"
+
+	run grep 'DB_PASSWORD' fnox.toml
+	assert_output --partial 'provider = "op"'
+	assert_output --partial 'value = "op://Private/db/password"'
+}
+
+@test "fnox set --raw-ref rejects a malformed reference" {
+	write_config
+
+	run "$FNOX_BIN" set DB_PASSWORD "op://Private" --provider op --raw-ref
+	assert_failure
+	assert_output --partial "invalid reference 'op://Private'"
+}
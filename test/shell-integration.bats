@@ -40,8 +40,9 @@ teardown() {
 	assert_success
 	assert_output --partial "set -gx FNOX_SHELL fish"
 	assert_output --partial "function fnox"
-	assert_output --partial "function __fnox_env_eval"
+	assert_output --partial "function __fnox_env_eval --on-event fish_prompt"
 	assert_output --partial "function __fnox_cd_hook --on-variable PWD"
+	refute_output --partial "function fish_prompt"
 }
 
 @test "fnox activate pwsh generates valid powershell code" {
@@ -197,6 +198,93 @@ teardown() {
 	assert_output --partial 'set -gx __FNOX_SESSION'
 }
 
+# Run a fish script with universal variables kept in the test directory
+_fish() {
+	if ! command -v fish >/dev/null 2>&1; then
+		skip "fish not installed"
+	fi
+	XDG_CONFIG_HOME="$TEST_TEMP_DIR/.config" XDG_DATA_HOME="$TEST_TEMP_DIR/.local/share" \
+		run fish --no-config -c "$1"
+}
+
+_fish_config() {
+	mkdir -p "$TEST_TEMP_DIR/with-config" "$TEST_TEMP_DIR/without-config"
+	cat >"$TEST_TEMP_DIR/with-config/fnox.toml" <<-EOF
+		[providers.plain]
+		type = "plain"
+
+		[secrets.FISH_SECRET]
+		provider = "plain"
+		value = "fish-value"
+	EOF
+}
+
+@test "fish activation loads and unloads secrets across prompts" {
+	_fish_config
+
+	_fish "
+		cd '$TEST_TEMP_DIR/with-config'
+		'$FNOX_BIN' activate fish | source
+		emit fish_prompt
+		echo \"loaded: \$FISH_SECRET\"
+		set -qU FISH_SECRET; and echo 'universal: FISH_SECRET'
+		cd '$TEST_TEMP_DIR/without-config'
+		emit fish_prompt
+		set -q FISH_SECRET; or echo 'unloaded: FISH_SECRET'
+	"
+
+	assert_success
+	assert_output --partial "loaded: fish-value"
+	refute_output --partial "universal:"
+	assert_output --partial "unloaded: FISH_SECRET"
+}
+
+@test "fish activation leaves universal variables alone" {
+	_fish_config
+
+	_fish "
+		set -Ux FISH_SECRET universal-value
+		cd '$TEST_TEMP_DIR/with-config'
+		'$FNOX_BIN' activate fish | source
+		emit fish_prompt
+		echo \"loaded: \$FISH_SECRET\"
+		cd '$TEST_TEMP_DIR/without-config'
+		emit fish_prompt
+		echo \"after: \$FISH_SECRET\"
+		set -qU FISH_SECRET; and echo 'universal kept'
+		set -eU FISH_SECRET
+	"
+
+	assert_success
+	assert_output --partial "loaded: fish-value"
+	assert_output --partial "after: universal-value"
+	assert_output --partial "universal kept"
+}
+
+@test "fish deactivate removes the prompt hook" {
+	_fish_config
+
+	_fish "
+		cd '$TEST_TEMP_DIR/with-config'
+		'$FNOX_BIN' activate fish | source
+		emit fish_prompt
+		fnox deactivate
+		functions -q __fnox_env_eval; or echo 'hook removed'
+		functions --handlers-type event | string match -q '*__fnox*'; or echo 'no handlers'
+		set -q FNOX_SHELL; or echo 'FNOX_SHELL unset'
+		set -q FISH_SECRET; or echo 'FISH_SECRET unset'
+		emit fish_prompt
+		set -q FISH_SECRET; or echo 'still unset after prompt'
+	"
+
+	assert_success
+	assert_output --partial "hook removed"
+	assert_output --partial "no handlers"
+	assert_output --partial "FNOX_SHELL unset"
+	assert_output --partial "FISH_SECRET unset"
+	assert_output --partial "still unset after prompt"
+}
+
 @test "fnox hook-env generates powershell-compatible output" {
 	cd "$TEST_TEMP_DIR"
 	cat >fnox.toml <<-EOF
@@ -759,3 +847,87 @@ teardown() {
 	assert_output --partial 'export LOCAL_ONLY_SECRET=local-only-value'
 	assert_output --partial '__FNOX_SESSION='
 }
+
+# ============================================================================
+# fnox activate --install tests
+# ============================================================================
+
+@test "fnox activate --print-rc-line prints the line for each shell" {
+	run "$FNOX_BIN" activate bash --print-rc-line
+	assert_success
+	assert_output 'eval "$(fnox activate bash)"'
+
+	run "$FNOX_BIN" activate fish --print-rc-line
+	assert_success
+	assert_output 'fnox activate fish | source'
+}
+
+@test "fnox activate --install adds the bash line once and --uninstall removes it" {
+	echo 'export PATH="$HOME/bin:$PATH"' >"$HOME/.bashrc"
+
+	run "$FNOX_BIN" activate bash --install
+	assert_success
+	assert_output --partial "Added fnox activation to $HOME/.bashrc"
+	assert_equal "$(grep -c 'eval "$(fnox activate bash)"' "$HOME/.bashrc")" 1
+	assert_equal "$(cat "$HOME/.bashrc.fnox-install-backup")" 'export PATH="$HOME/bin:$PATH"'
+
+	# Idempotent
+	run "$FNOX_BIN" activate bash --install
+	assert_success
+	assert_output --partial "already activated"
+	assert_equal "$(grep -c 'fnox activate' "$HOME/.bashrc")" 3
+
+	run "$FNOX_BIN" activate bash --uninstall
+	assert_success
+	assert_output --partial "Removed fnox activation from $HOME/.bashrc"
+	assert_equal "$(cat "$HOME/.bashrc")" 'export PATH="$HOME/bin:$PATH"'
+	# The install backup still holds the file from before the install
+	assert_equal "$(cat "$HOME/.bashrc.fnox-install-backup")" 'export PATH="$HOME/bin:$PATH"'
+	assert_equal "$(grep -c 'fnox activate' "$HOME/.bashrc.fnox-uninstall-backup")" 3
+
+	run "$FNOX_BIN" activate bash --uninstall
+	assert_success
+	assert_output --partial "isn't activated"
+}
+
+@test "fnox activate --install uses ZDOTDIR for zsh" {
+	export ZDOTDIR="$HOME/zdot"
+
+	run "$FNOX_BIN" activate zsh --install
+	assert_success
+	assert [ -f "$ZDOTDIR/.zshrc" ]
+	assert [ ! -f "$HOME/.zshrc" ]
+	run "$FNOX_BIN" activate zsh --install
+	assert_success
+	assert_equal "$(grep -c 'eval "$(fnox activate zsh)"' "$ZDOTDIR/.zshrc")" 1
+
+	unset ZDOTDIR
+	run "$FNOX_BIN" activate zsh --install
+	assert_success
+	assert_equal "$(grep -c 'eval "$(fnox activate zsh)"' "$HOME/.zshrc")" 1
+}
+
+@test "fnox activate --install writes fish's conf.d file" {
+	unset FNOX_SHELL
+	export SHELL=/usr/bin/fish
+
+	run "$FNOX_BIN" activate --install
+	assert_success
+	local file="$HOME/.config/fish/conf.d/fnox.fish"
+	assert_equal "$(grep -c 'fnox activate fish | source' "$file")" 1
+
+	run "$FNOX_BIN" activate --install
+	assert_success
+	assert_equal "$(grep -c 'fnox activate fish | source' "$file")" 1
+
+	run "$FNOX_BIN" activate --uninstall
+	assert_success
+	assert [ ! -f "$file" ]
+}
+
+@test "fnox activate --install prints the lines for nushell instead" {
+	run "$FNOX_BIN" activate nu --install
+	assert_failure
+	assert_output --partial "add this to it by hand"
+	assert_output --partial 'fnox activate nu | save -f'
+}
@@ -0,0 +1,43 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+}
+
+teardown() {
+	_common_teardown
+}
+
+# Credentials systemd loaded for the service are read without systemd-creds,
+# so these run anywhere
+write_config() {
+	cat >fnox.toml <<'EOF'
+root = true
+
+[providers.creds]
+type = "systemd-creds"
+
+[secrets]
+DATABASE_URL = { provider = "creds", value = "not-a-real-credential" }
+EOF
+	mkdir -p credentials
+	printf 'postgres://localhost/app' >credentials/DATABASE_URL
+	export CREDENTIALS_DIRECTORY="$TEST_TEMP_DIR/credentials"
+}
+
+@test "fnox get reads a credential loaded into \$CREDENTIALS_DIRECTORY" {
+	write_config
+
+	run "$FNOX_BIN" get DATABASE_URL
+	assert_success
+	assert_output "postgres://localhost/app"
+}
+
+@test "fnox exec reads a credential loaded into \$CREDENTIALS_DIRECTORY" {
+	write_config
+
+	run "$FNOX_BIN" exec -- sh -c 'printf "%s" "$DATABASE_URL"'
+	assert_success
+	assert_output "postgres://localhost/app"
+}
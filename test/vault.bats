@@ -181,7 +181,7 @@ EOF
 
 	run "$FNOX_BIN" get INVALID_FORMAT
 	assert_failure
-	assert_output --partial "Invalid secret reference format"
+	assert_output --partial "invalid reference 'invalid/format/with/too/many/slashes'"
 }
 
 @test "fnox list shows Vault secrets" {
@@ -289,6 +289,71 @@ EOF
 	vault kv delete "secret/test-secret" >/dev/null 2>&1 || true
 }
 
+@test "Vault provider reads its token from a keychain secret" {
+	if [[ "$(uname)" == "Darwin" ]]; then
+		command -v security >/dev/null 2>&1 || skip "macOS keychain not available"
+	elif ! dbus-send --print-reply --dest=org.freedesktop.secrets /org/freedesktop/secrets org.freedesktop.DBus.Peer.Ping >/dev/null 2>&1; then
+		skip "Secret Service not available"
+	fi
+
+	local service="fnox-test-vault-$$"
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+[providers.keychain]
+type = "keychain"
+service = "$service"
+
+[providers.vault]
+type = "vault"
+address = "http://localhost:8200"
+token = { secret = "VAULT_TOKEN_KC" }
+
+[secrets.TEST_TOKEN_FROM_KEYCHAIN]
+provider = "vault"
+value = "keychain-token-test"
+EOF
+
+	vault kv put "secret/keychain-token-test" value="token-from-keychain" >/dev/null 2>&1
+	assert_fnox_success set VAULT_TOKEN_KC "$VAULT_TOKEN" --provider keychain
+
+	# Only the keychain has the token now
+	VAULT_TOKEN_BACKUP="$VAULT_TOKEN"
+	unset VAULT_TOKEN FNOX_VAULT_TOKEN
+
+	run "$FNOX_BIN" get TEST_TOKEN_FROM_KEYCHAIN
+	assert_success
+	assert_output "token-from-keychain"
+
+	export VAULT_TOKEN="$VAULT_TOKEN_BACKUP"
+	vault kv delete "secret/keychain-token-test" >/dev/null 2>&1 || true
+	if [[ "$(uname)" == "Darwin" ]]; then
+		security delete-generic-password -s "$service" -a "VAULT_TOKEN_KC" >/dev/null 2>&1 || true
+	elif command -v secret-tool >/dev/null 2>&1; then
+		secret-tool clear service "$service" account "VAULT_TOKEN_KC" >/dev/null 2>&1 || true
+	fi
+}
+
+@test "Vault provider reports a cycle when its token is stored in itself" {
+	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
+[providers.vault]
+type = "vault"
+address = "http://localhost:8200"
+token = { secret = "VAULT_TOKEN_SELF" }
+
+[secrets.VAULT_TOKEN_SELF]
+provider = "vault"
+value = "vault-token"
+
+[secrets.MY_SECRET]
+provider = "vault"
+value = "anything"
+EOF
+
+	run "$FNOX_BIN" get MY_SECRET
+	assert_failure
+	assert_output --partial "Circular dependency detected in provider configuration for 'vault'"
+	assert_output --partial "vault.token -> secret 'VAULT_TOKEN_SELF' -> vault"
+}
+
 @test "Vault provider uses credential_command when token is not in environment" {
 	cat >"${FNOX_CONFIG_FILE:-fnox.toml}" <<EOF
 [providers.vault]
@@ -0,0 +1,86 @@
+#!/usr/bin/env bats
+
+setup() {
+	load 'test_helper/common_setup'
+	_common_setup
+
+	export GIT_CONFIG_NOSYSTEM=1
+	export GIT_CONFIG_GLOBAL="$TEST_TEMP_DIR/.gitconfig"
+	git config --global init.defaultBranch main
+	git config --global user.email "test@example.com"
+	git config --global user.name "Test User"
+
+	# Main worktree with an untracked fnox.toml, so the linked worktree
+	# doesn't get a copy of it
+	mkdir main
+	git -C main init -q .
+	echo "fnox.toml" >main/.gitignore
+	git -C main add .gitignore
+	git -C main commit -q -m "initial"
+	cat >main/fnox.toml <<EOF
+[secrets]
+MAIN_SECRET = { default = "from-main" }
+SHARED_SECRET = { default = "main-value" }
+EOF
+	git -C main worktree add -q ../feature -b feature
+	mkdir -p feature/src
+}
+
+teardown() {
+	_common_teardown
+}
+
+# Stop the search above the worktrees, optionally following them
+write_root_config() {
+	cat >"$TEST_TEMP_DIR/fnox.toml" <<EOF
+root = true
+
+[settings]
+follow_worktrees = $1
+EOF
+}
+
+@test "linked worktrees don't load the main worktree's config by default" {
+	write_root_config false
+	cd feature/src
+
+	run "$FNOX_BIN" get MAIN_SECRET
+	assert_failure
+}
+
+@test "follow_worktrees loads the main worktree's config from a linked worktree" {
+	write_root_config true
+	cd feature/src
+
+	run "$FNOX_BIN" get MAIN_SECRET
+	assert_success
+	assert_output "from-main"
+}
+
+@test "the linked worktree's config overrides the main worktree's" {
+	write_root_config true
+	cat >feature/fnox.toml <<EOF
+[secrets]
+SHARED_SECRET = { default = "feature-value" }
+EOF
+	cd feature/src
+
+	run "$FNOX_BIN" get SHARED_SECRET
+	assert_success
+	assert_output "feature-value"
+
+	run "$FNOX_BIN" get MAIN_SECRET
+	assert_success
+	assert_output "from-main"
+}
+
+@test "fnox doctor explains the detected worktree" {
+	write_root_config false
+	cd feature/src
+
+	run "$FNOX_BIN" doctor
+	assert_success
+	assert_output --partial "Git worktree: "
+	assert_output --partial "Main worktree: "
+	assert_output --partial "follow_worktrees = true"
+}